@@ -0,0 +1,181 @@
+#![feature(allocator_api)]
+
+use alloc_compose::{
+    region::{Region, Up},
+    stats,
+    workload,
+    AllocateAll,
+    Allocator,
+    CallbackRef,
+    Chunk,
+    Fallback,
+    Proxy,
+    Segregate,
+};
+use core::{alloc::Layout, mem::MaybeUninit};
+use std::alloc::System;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const BLOCKS: usize = 64;
+
+fn stack(alloc: &impl Allocator, layout: Layout) {
+    assert_eq!(
+        workload::stack::<_, BLOCKS>(alloc, black_box(layout)),
+        BLOCKS
+    );
+}
+
+fn fallback(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fallback");
+    let layout = Layout::new::<[u8; 64]>();
+    let mut data = [MaybeUninit::uninit(); BLOCKS * 64];
+
+    group.bench_function("System", |b| b.iter(|| stack(&System, layout)));
+    for &hits in &[0, BLOCKS / 4, BLOCKS / 2, BLOCKS * 3 / 4, BLOCKS] {
+        let alloc = Fallback {
+            primary: Region::new(&mut data[..hits * 64]),
+            secondary: System,
+        };
+        group.bench_with_input(
+            BenchmarkId::new(
+                "Fallback<Region, System>",
+                format!("{}% hits", hits * 100 / BLOCKS),
+            ),
+            &alloc,
+            |b, alloc| {
+                b.iter(|| {
+                    stack(alloc, layout);
+                    alloc.primary.deallocate_all();
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn fallback_grow(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fallback_grow");
+    let layout = Layout::new::<[u8; 8]>();
+    let mut data = [MaybeUninit::uninit(); 4096];
+
+    group.bench_function("System", |b| {
+        b.iter(|| workload::grow(&System, black_box(layout), 8))
+    });
+    // Grows in place, as the memory block is the last one of the region
+    let alloc = Fallback {
+        primary: Region::<Up>::with_direction(&mut data),
+        secondary: System,
+    };
+    group.bench_function("Fallback<Region<Up>, System>", |b| {
+        b.iter(|| {
+            workload::grow(&alloc, black_box(layout), 8);
+            alloc.primary.deallocate_all();
+        })
+    });
+    // Copies the memory block to `System` on the first grow
+    let alloc = Fallback {
+        primary: Region::new(&mut data),
+        secondary: System,
+    };
+    group.bench_function("Fallback<Region, System>", |b| {
+        b.iter(|| {
+            workload::grow(&alloc, black_box(layout), 8);
+            alloc.primary.deallocate_all();
+        })
+    });
+
+    group.finish();
+}
+
+fn proxy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("proxy");
+    let layout = Layout::new::<[u8; 64]>();
+
+    fn run(c: impl CallbackRef) -> impl FnMut(&mut criterion::Bencher) {
+        let alloc = Proxy {
+            alloc: System,
+            callbacks: c,
+        };
+        move |b| b.iter(|| stack(&alloc, Layout::new::<[u8; 64]>()))
+    }
+
+    group.bench_function("System", |b| b.iter(|| stack(&System, layout)));
+    group.bench_function("()", run(()));
+    group.bench_function("Counter", run(stats::Counter::default()));
+    group.bench_function("AtomicCounter", run(stats::AtomicCounter::default()));
+    group.bench_function("FilteredCounter", run(stats::FilteredCounter::default()));
+    group.bench_function(
+        "FilteredAtomicCounter",
+        run(stats::FilteredAtomicCounter::default()),
+    );
+
+    group.finish();
+}
+
+fn chunk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk");
+
+    for &size in &[8, 100, 1000] {
+        let layout = Layout::from_size_align(size, 8).unwrap();
+        group.bench_with_input(BenchmarkId::new("System", size), &layout, |b, &layout| {
+            b.iter(|| stack(&System, layout))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("Chunk<System, 64>", size),
+            &layout,
+            |b, &layout| b.iter(|| stack(&Chunk::<_, 64>(System), layout)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("Chunk<System, 4096>", size),
+            &layout,
+            |b, &layout| b.iter(|| stack(&Chunk::<_, 4096>(System), layout)),
+        );
+        group.bench_with_input(BenchmarkId::new("grow", size), &layout, |b, &layout| {
+            b.iter(|| workload::grow(&Chunk::<_, 64>(System), black_box(layout), 8))
+        });
+    }
+
+    group.finish();
+}
+
+fn segregate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("segregate");
+    let layouts = [
+        Layout::new::<[u8; 16]>(),
+        Layout::new::<[u8; 200]>(),
+        Layout::new::<[u8; 32]>(),
+        Layout::new::<[u8; 2000]>(),
+    ];
+    let mut data = [MaybeUninit::uninit(); BLOCKS * 64];
+
+    group.bench_function("System", |b| {
+        b.iter(|| workload::mixed::<_, BLOCKS>(&System, black_box(&layouts)))
+    });
+    let alloc: Segregate<_, _, 64> = Segregate {
+        small: Region::new(&mut data),
+        large: System,
+    };
+    group.bench_function("Segregate<Region, System, 64>", |b| {
+        b.iter(|| {
+            workload::mixed::<_, BLOCKS>(&alloc, black_box(&layouts));
+            alloc.small.deallocate_all();
+        })
+    });
+    let alloc: Segregate<_, _, 64> = Segregate {
+        small: Segregate::<_, _, 16> {
+            small: Chunk::<_, 16>(System),
+            large: Chunk::<_, 64>(System),
+        },
+        large: System,
+    };
+    group.bench_function("Segregate<Segregate<Chunk, Chunk, 16>, System, 64>", |b| {
+        b.iter(|| workload::mixed::<_, BLOCKS>(&alloc, black_box(&layouts)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, fallback, fallback_grow, proxy, chunk, segregate);
+criterion_main!(benches);
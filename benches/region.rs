@@ -1,22 +1,23 @@
 #![feature(allocator_api)]
 
-use alloc_compose::{region::*, AllocateAll};
-use core::{
-    alloc::{AllocRef, Layout},
-    mem::MaybeUninit,
-};
+use alloc_compose::{region::*, AllocateAll, Allocator};
+use core::{alloc::Layout, mem::MaybeUninit};
 
 use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
 
+// `&Region` is benchmarked on purpose
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn regions(c: &mut Criterion) {
     let mut group = c.benchmark_group("region");
     let mut data = [MaybeUninit::uninit(); 1024 * 1024];
 
     #[inline]
-    fn run(region: impl AllocRef + AllocateAll, b: &mut Bencher) {
+    fn run(region: impl Allocator + AllocateAll, b: &mut Bencher) {
         b.iter(|| {
             for _ in 0..16 {
-                region.alloc(black_box(Layout::new::<[u8; 16]>())).unwrap();
+                region
+                    .allocate(black_box(Layout::new::<[u8; 16]>()))
+                    .unwrap();
             }
             region.deallocate_all();
         })
@@ -32,9 +33,54 @@ fn regions(c: &mut Criterion) {
     group.finish();
 }
 
+fn fast_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("region-fast-path");
+    let mut data = [MaybeUninit::uninit(); 1024 * 1024];
+    let region = Region::new(&mut data);
+
+    group.bench_function("alloc", |b| {
+        b.iter(|| {
+            for _ in 0..16 {
+                region
+                    .allocate(black_box(Layout::new::<[u64; 2]>()))
+                    .unwrap();
+            }
+            region.deallocate_all();
+        })
+    });
+    group.bench_function("alloc_aligned", |b| {
+        b.iter(|| {
+            for _ in 0..16 {
+                region.alloc_aligned::<8>(black_box(16)).unwrap();
+            }
+            region.deallocate_all();
+        })
+    });
+    group.bench_function("alloc (align 1)", |b| {
+        b.iter(|| {
+            for _ in 0..16 {
+                region
+                    .allocate(black_box(Layout::new::<[u8; 16]>()))
+                    .unwrap();
+            }
+            region.deallocate_all();
+        })
+    });
+    group.bench_function("alloc_unaligned", |b| {
+        b.iter(|| {
+            for _ in 0..16 {
+                region.alloc_unaligned(black_box(16)).unwrap();
+            }
+            region.deallocate_all();
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(1000).measurement_time(std::time::Duration::from_secs(3));
-    targets = regions
+    targets = regions, fast_path
 }
 criterion_main!(benches);
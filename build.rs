@@ -0,0 +1,47 @@
+//! Detects nightlies from before `AllocRef` was renamed to `Allocator`.
+//!
+//! On those nightlies `cfg(alloc_ref)` is set, and the crate provides its own `Allocator` trait
+//! on top of `AllocRef`. This script has to compile on those nightlies as well.
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+
+    if !compiles("Allocator") && compiles("AllocRef") {
+        println!("cargo:rustc-cfg=alloc_ref");
+    }
+}
+
+/// Returns, whether `core::alloc::<name>` can be imported.
+fn compiles(name: &str) -> bool {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is not set"));
+    let crate_name = ["probe_", &name.to_lowercase()].concat();
+    let probe = out_dir.join([&crate_name, ".rs"].concat());
+    let source = [
+        "#![no_std]\n#![feature(allocator_api)]\n#![allow(unused_imports)]\nuse core::alloc::",
+        name,
+        ";\n",
+    ]
+    .concat();
+    if fs::write(&probe, source).is_err() {
+        return false;
+    }
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc)
+        .arg("--edition=2018")
+        .arg("--crate-type=lib")
+        .arg("--emit=metadata")
+        .arg("--crate-name")
+        .arg(crate_name)
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg(&probe)
+        .output();
+    match output {
+        Ok(output) => output.status.success(),
+        Err(_) => false,
+    }
+}
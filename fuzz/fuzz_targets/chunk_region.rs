@@ -0,0 +1,10 @@
+#![no_main]
+
+use alloc_compose::{fuzzing::OpSequence, region::Region, Chunk};
+use core::mem::MaybeUninit;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|ops: OpSequence| {
+    let mut data = [MaybeUninit::new(0); 4096];
+    ops.run(&Chunk::<_, 64>(Region::new(&mut data)));
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use alloc_compose::{fuzzing::OpSequence, region::Region, Fallback};
+use core::mem::MaybeUninit;
+use libfuzzer_sys::fuzz_target;
+use std::alloc::System;
+
+fuzz_target!(|ops: OpSequence| {
+    let mut data = [MaybeUninit::new(0); 4096];
+    ops.run(&Fallback {
+        primary: Region::new(&mut data),
+        secondary: System,
+    });
+});
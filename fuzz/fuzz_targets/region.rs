@@ -0,0 +1,10 @@
+#![no_main]
+
+use alloc_compose::{fuzzing::OpSequence, region::Region};
+use core::mem::MaybeUninit;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|ops: OpSequence| {
+    let mut data = [MaybeUninit::new(0); 4096];
+    ops.run(&Region::new(&mut data));
+});
@@ -1,7 +1,21 @@
-use crate::{helper::AllocInit, AllocAll, ReallocInPlace};
+use crate::{
+    describe::Bytes,
+    helper::AllocInit,
+    AllocateAll,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+};
 use core::{
-    alloc::{AllocErr, AllocRef, Layout},
+    alloc::{AllocError, Layout},
+    cmp,
     fmt,
+    hash::{Hash, Hasher},
+    hint,
     marker::PhantomData,
     mem::{self, MaybeUninit},
     ptr::{self, NonNull},
@@ -60,7 +74,7 @@ use core::{
 /// # use alloc_compose::{Affix, Chunk};
 /// # use std::alloc::{Layout, System};
 ///
-/// use core::alloc::AllocRef;
+/// use core::alloc::Allocator;
 /// # type Prefix = [u32; 3];
 /// # type Suffix = [u64; 2];
 /// # type Alloc = Affix<Chunk<System, 128>, Prefix, Suffix>;
@@ -75,7 +89,7 @@ use core::{
 /// // │               ├┄┄┄┄┄┄ layout.size() ┄┄┄┄┄┄┘   │
 /// // │               ├┄┄┄┄┄┄┄┄ memory.len() ┄┄┄┄┄┄┄┄┄┤
 /// // └→ prefix()     └→ memory                       └→ suffix()
-/// let memory = my_alloc.alloc(layout)?;
+/// let memory = my_alloc.allocate(layout)?;
 ///
 /// assert_eq!(memory.len(), 32);
 /// unsafe {
@@ -88,7 +102,7 @@ use core::{
 ///         memory.as_mut_ptr().add(32)
 ///     );
 /// }
-/// # Ok::<(), core::alloc::AllocErr>(())
+/// # Ok::<(), core::alloc::AllocError>(())
 /// ```
 ///
 /// The memory between `Prefix` and the requested memory is unused. If there is a padding between
@@ -101,7 +115,7 @@ use core::{
 /// ```
 /// # #![feature(allocator_api, slice_ptr_get, slice_ptr_len)]
 /// # use alloc_compose::{Affix, Chunk};
-/// # use std::alloc::{Layout, System, AllocRef};
+/// # use std::alloc::{Layout, System, Allocator};
 /// use core::ptr::NonNull;
 /// # type Prefix = [u32; 3];
 ///
@@ -118,7 +132,7 @@ use core::{
 /// // │               ├┄┄┄┄┄┄ layout.size() ┄┄┄┄┄┄┘                            │
 /// // │               ├┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄ memory.len() ┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┘
 /// // └→ prefix()     └→ memory
-/// let memory = my_alloc.alloc(layout)?;
+/// let memory = my_alloc.allocate(layout)?;
 ///
 /// assert_eq!(memory.len(), 112);
 /// unsafe {
@@ -128,7 +142,7 @@ use core::{
 ///     );
 ///     assert_eq!(Alloc::suffix(memory.as_non_null_ptr(), layout), NonNull::dangling());
 /// }
-/// # Ok::<(), core::alloc::AllocErr>(())
+/// # Ok::<(), core::alloc::AllocError>(())
 /// ```
 ///
 /// This results in only `4` bytes unused memory.
@@ -138,7 +152,7 @@ use core::{
 /// ```
 /// # #![feature(allocator_api, slice_ptr_get, slice_ptr_len)]
 /// # use alloc_compose::{Affix, Chunk};
-/// # use std::alloc::{Layout, System, AllocRef};
+/// # use std::alloc::{Layout, System, Allocator};
 /// # use core::ptr::NonNull;
 /// # type Suffix = [u64; 2];
 /// type Alloc = Affix<Chunk<System, 128>, (), Suffix>;
@@ -153,7 +167,7 @@ use core::{
 /// // ├┄┄┄┄┄┄ layout.size() ┄┄┄┄┄┄┘   │
 /// // ├┄┄┄┄┄┄┄┄ memory.len() ┄┄┄┄┄┄┄┄┄┤
 /// // └→ memory                       └→ suffix()
-/// let memory = my_alloc.alloc(layout)?;
+/// let memory = my_alloc.allocate(layout)?;
 ///
 /// assert_eq!(memory.len(), 32);
 /// unsafe {
@@ -163,7 +177,7 @@ use core::{
 ///         memory.as_mut_ptr().add(32)
 ///     );
 /// }
-/// # Ok::<(), core::alloc::AllocErr>(())
+/// # Ok::<(), core::alloc::AllocError>(())
 /// ```
 ///
 /// This results in 80 bytes unused memory. As can be seen, if possible a prefix should be
@@ -174,7 +188,7 @@ use core::{
 /// ```
 /// # #![feature(allocator_api, slice_ptr_get, slice_ptr_len)]
 /// # use alloc_compose::{Affix, Chunk};
-/// # use std::alloc::{Layout, System, AllocRef};
+/// # use std::alloc::{Layout, System, Allocator};
 /// # use core::ptr::NonNull;
 /// # type Suffix = [u64; 2];
 /// type Alloc = Affix<Chunk<System, 128>, (), ()>;
@@ -189,14 +203,14 @@ use core::{
 /// // ├┄┄┄┄┄┄ layout.size() ┄┄┄┄┄┄┘                                            │
 /// // ├┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄ memory.len() ┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┘
 /// // └→ memory
-/// let memory = my_alloc.alloc(layout)?;
+/// let memory = my_alloc.allocate(layout)?;
 ///
 /// assert_eq!(memory.len(), 128);
 /// unsafe {
 ///     assert_eq!(Alloc::prefix(memory.as_non_null_ptr(), layout), NonNull::dangling());
 ///     assert_eq!(Alloc::suffix(memory.as_non_null_ptr(), layout), NonNull::dangling());
 /// }
-/// # Ok::<(), core::alloc::AllocErr>(())
+/// # Ok::<(), core::alloc::AllocError>(())
 /// ```
 pub struct Affix<Alloc, Prefix = (), Suffix = ()> {
     /// The parent allocator to be used as backend
@@ -235,6 +249,24 @@ impl<Alloc: PartialEq, Prefix, Suffix> PartialEq for Affix<Alloc, Prefix, Suffix
 
 impl<Alloc: Eq, Prefix, Suffix> Eq for Affix<Alloc, Prefix, Suffix> {}
 
+impl<Alloc: PartialOrd, Prefix, Suffix> PartialOrd for Affix<Alloc, Prefix, Suffix> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.parent.partial_cmp(&other.parent)
+    }
+}
+
+impl<Alloc: Ord, Prefix, Suffix> Ord for Affix<Alloc, Prefix, Suffix> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.parent.cmp(&other.parent)
+    }
+}
+
+impl<Alloc: Hash, Prefix, Suffix> Hash for Affix<Alloc, Prefix, Suffix> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parent.hash(state)
+    }
+}
+
 unsafe impl<Alloc: Send, Prefix, Suffix> Send for Affix<Alloc, Prefix, Suffix> {}
 unsafe impl<Alloc: Sync, Prefix, Suffix> Sync for Affix<Alloc, Prefix, Suffix> {}
 impl<Alloc: Unpin, Prefix, Suffix> Unpin for Affix<Alloc, Prefix, Suffix> {}
@@ -248,12 +280,58 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
         }
     }
 
-    fn allocation_layout(layout: Layout) -> Option<(Layout, usize, usize)> {
-        let (layout, prefix_offset) = Layout::new::<Prefix>().extend(layout).ok()?;
-        let (layout, suffix_offset) = layout.extend(Layout::new::<Suffix>()).ok()?;
+    const fn allocation_layout(layout: Layout) -> Option<(Layout, usize, usize)> {
+        let (layout, prefix_offset) = match Layout::new::<Prefix>().extend(layout) {
+            Ok(extended) => extended,
+            Err(_) => return None,
+        };
+        let (layout, suffix_offset) = match layout.extend(Layout::new::<Suffix>()) {
+            Ok(extended) => extended,
+            Err(_) => return None,
+        };
         Some((layout, prefix_offset, suffix_offset))
     }
 
+    /// Returns the layout requested from the parent for a memory block of `layout`.
+    ///
+    /// Returns `None`, if the layout extended by `Prefix` and `Suffix` overflows.
+    pub const fn parent_layout(layout: Layout) -> Option<Layout> {
+        match Self::allocation_layout(layout) {
+            Some((layout, ..)) => Some(layout),
+            None => None,
+        }
+    }
+
+    /// Returns the number of bytes requested from the parent on top of `layout.size()`.
+    ///
+    /// This covers the prefix, the suffix, and the padding in front of the memory block and the
+    /// suffix. Returns `None`, if the layout extended by `Prefix` and `Suffix` overflows.
+    pub const fn overhead(layout: Layout) -> Option<usize> {
+        match Self::parent_layout(layout) {
+            Some(parent) => Some(parent.size() - layout.size()),
+            None => None,
+        }
+    }
+
+    /// Like [`allocation_layout`], but for a `layout`, which was already used for allocating.
+    ///
+    /// # Safety
+    ///
+    /// `layout` must fit a memory block allocated via this allocator.
+    ///
+    /// [`allocation_layout`]: Self::allocation_layout
+    unsafe fn allocation_layout_unchecked(layout: Layout) -> (Layout, usize, usize) {
+        match Self::allocation_layout(layout) {
+            Some(layout) => layout,
+            None => {
+                if cfg!(debug_assertions) {
+                    unreachable!("`layout` does not fit a memory block of this allocator")
+                }
+                hint::unreachable_unchecked()
+            }
+        }
+    }
+
     /// Returns a pointer to the prefix.
     ///
     /// # Safety
@@ -261,13 +339,13 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
     /// * `ptr` must denote a block of memory *[currently allocated]* via this allocator, and
     /// * `layout` must *[fit]* that block of memory.
     ///
-    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.AllocRef.html#currently-allocated-memory
-    /// [fit]: https://doc.rust-lang.org/nightly/core/alloc/trait.AllocRef.html#memory-fitting
+    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [fit]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#memory-fitting
     pub unsafe fn prefix(ptr: NonNull<u8>, layout: Layout) -> NonNull<Prefix> {
         if mem::size_of::<Prefix>() == 0 {
             NonNull::dangling()
         } else {
-            let (_, prefix, _) = Self::allocation_layout(layout).unwrap();
+            let (_, prefix, _) = Self::allocation_layout_unchecked(layout);
             NonNull::new_unchecked(ptr.as_ptr().sub(prefix)).cast()
         }
     }
@@ -279,13 +357,13 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
     /// * `ptr` must denote a block of memory *[currently allocated]* via this allocator, and
     /// * `layout` must *[fit]* that block of memory.
     ///
-    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.AllocRef.html#currently-allocated-memory
-    /// [fit]: https://doc.rust-lang.org/nightly/core/alloc/trait.AllocRef.html#memory-fitting
+    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [fit]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#memory-fitting
     pub unsafe fn suffix(ptr: NonNull<u8>, layout: Layout) -> NonNull<Suffix> {
         if mem::size_of::<Suffix>() == 0 {
             NonNull::dangling()
         } else {
-            let (_, prefix, suffix) = Self::allocation_layout(layout).unwrap();
+            let (_, prefix, suffix) = Self::allocation_layout_unchecked(layout);
             NonNull::new_unchecked(ptr.as_ptr().add(suffix - prefix)).cast()
         }
     }
@@ -304,10 +382,10 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
     #[inline]
     fn alloc_impl(
         layout: Layout,
-        alloc: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocErr>,
-    ) -> Result<NonNull<[u8]>, AllocErr> {
+        alloc: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
         let (layout, offset_prefix, offset_suffix) =
-            Self::allocation_layout(layout).ok_or(AllocErr)?;
+            Self::allocation_layout(layout).ok_or(AllocError)?;
 
         Ok(Self::create_ptr(
             alloc(layout)?,
@@ -320,12 +398,21 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
     unsafe fn grow_impl(
         old_ptr: NonNull<u8>,
         old_layout: Layout,
-        new_size: usize,
+        new_layout: Layout,
         init: AllocInit,
-        grow: impl FnOnce(NonNull<u8>, Layout, usize) -> Result<NonNull<[u8]>, AllocErr>,
-    ) -> Result<NonNull<[u8]>, AllocErr> {
-        let (old_alloc_layout, old_offset_prefix, old_offset_suffix) =
-            Self::allocation_layout(old_layout).ok_or(AllocErr)?;
+        grow: impl FnOnce(NonNull<u8>, Layout, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (old_alloc_layout, old_offset_prefix, _) =
+            Self::allocation_layout(old_layout).ok_or(AllocError)?;
+        let (new_alloc_layout, new_offset_prefix, new_offset_suffix) =
+            Self::allocation_layout(new_layout).ok_or(AllocError)?;
+
+        // The parent only preserves the bytes relative to the base pointer, so the requested
+        // memory must not move inside of the block.
+        if old_offset_prefix != new_offset_prefix {
+            return Err(AllocError);
+        }
+
         let old_base_ptr = NonNull::new_unchecked(old_ptr.as_ptr().sub(old_offset_prefix));
 
         let suffix = Self::suffix(old_ptr, old_layout)
@@ -333,21 +420,16 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
             .as_ptr()
             .read();
 
-        let new_layout =
-            Layout::from_size_align(new_size, old_layout.align()).map_err(|_| AllocErr)?;
-        let (new_alloc_layout, new_offset_prefix, new_offset_suffix) =
-            Self::allocation_layout(new_layout).ok_or(AllocErr)?;
-
-        let new_base_ptr = grow(old_base_ptr, old_alloc_layout, new_alloc_layout.size())?;
+        let new_base_ptr = grow(old_base_ptr, old_alloc_layout, new_alloc_layout)?;
 
+        // The underlying allocator only zeroes behind the old allocation, which ends with the
+        // padding and the suffix behind the requested memory.
         if init == AllocInit::Zeroed {
+            let old_end = old_offset_prefix + old_layout.size();
             ptr::write_bytes(
-                new_base_ptr
-                    .as_non_null_ptr()
-                    .as_ptr()
-                    .add(old_offset_suffix),
+                new_base_ptr.as_non_null_ptr().as_ptr().add(old_end),
                 0,
-                mem::size_of::<Suffix>(),
+                old_alloc_layout.size() - old_end,
             );
         }
 
@@ -365,11 +447,19 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
     unsafe fn shrink_impl(
         old_ptr: NonNull<u8>,
         old_layout: Layout,
-        new_size: usize,
-        shrink: impl FnOnce(NonNull<u8>, Layout, usize) -> Result<NonNull<[u8]>, AllocErr>,
-    ) -> Result<NonNull<[u8]>, AllocErr> {
+        new_layout: Layout,
+        shrink: impl FnOnce(NonNull<u8>, Layout, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
         let (old_alloc_layout, old_offset_prefix, _) =
-            Self::allocation_layout(old_layout).ok_or(AllocErr)?;
+            Self::allocation_layout(old_layout).ok_or(AllocError)?;
+        let (new_alloc_layout, new_offset_prefix, new_offset_suffix) =
+            Self::allocation_layout(new_layout).ok_or(AllocError)?;
+
+        // See `grow_impl`
+        if old_offset_prefix != new_offset_prefix {
+            return Err(AllocError);
+        }
+
         let old_base_ptr = NonNull::new_unchecked(old_ptr.as_ptr().sub(old_offset_prefix));
 
         let suffix = Self::suffix(old_ptr, old_layout)
@@ -377,12 +467,7 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
             .as_ptr()
             .read();
 
-        let new_layout =
-            Layout::from_size_align(new_size, old_layout.align()).map_err(|_| AllocErr)?;
-        let (new_alloc_layout, new_offset_prefix, new_offset_suffix) =
-            Self::allocation_layout(new_layout).ok_or(AllocErr)?;
-
-        let new_base_ptr = shrink(old_base_ptr, old_alloc_layout, new_alloc_layout.size())?;
+        let new_base_ptr = shrink(old_base_ptr, old_alloc_layout, new_alloc_layout)?;
 
         let new_ptr = Self::create_ptr(new_base_ptr, new_offset_prefix, new_offset_suffix);
 
@@ -395,41 +480,168 @@ impl<Alloc, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
     }
 }
 
-unsafe impl<Alloc, Prefix, Suffix> AllocRef for Affix<Alloc, Prefix, Suffix>
+impl<Alloc: AllocateAll, Prefix, Suffix> Affix<Alloc, Prefix, Suffix> {
+    /// Drains the parent allocator like [`allocate_all`], but returns the memory block without
+    /// reserving a prefix or a suffix.
+    ///
+    /// [`prefix()`] and [`suffix()`] must not be called on the returned memory block.
+    ///
+    /// [`allocate_all`]: AllocateAll::allocate_all
+    /// [`prefix()`]: Self::prefix
+    /// [`suffix()`]: Self::suffix
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the parent allocator fails to allocate all of its memory.
+    pub fn allocate_all_raw(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.parent.allocate_all()
+    }
+
+    /// Reserves the prefix at the start and the suffix at the end of the drained `memory`.
+    fn affix_all(memory: NonNull<[u8]>) -> Result<NonNull<[u8]>, AllocError> {
+        let align = cmp::max(mem::align_of::<Prefix>(), mem::align_of::<Suffix>());
+        let padding = memory.as_mut_ptr().align_offset(align);
+        let offset_prefix = mem::size_of::<Prefix>();
+        let offset_suffix = memory
+            .len()
+            .checked_sub(padding)
+            .and_then(|len| len.checked_sub(mem::size_of::<Suffix>()))
+            .ok_or(AllocError)?
+            & !(mem::align_of::<Suffix>() - 1);
+        if offset_suffix < offset_prefix {
+            return Err(AllocError);
+        }
+
+        let base_ptr = unsafe { NonNull::new_unchecked(memory.as_mut_ptr().add(padding)) };
+        let len = if mem::size_of::<Suffix>() == 0 {
+            memory.len() - padding - offset_prefix
+        } else {
+            offset_suffix - offset_prefix
+        };
+        Ok(NonNull::slice_from_raw_parts(
+            unsafe { NonNull::new_unchecked(base_ptr.as_ptr().add(offset_prefix)) },
+            len,
+        ))
+    }
+}
+
+unsafe impl<Alloc, Prefix, Suffix> Allocator for Affix<Alloc, Prefix, Suffix>
 where
-    Alloc: AllocRef,
+    Alloc: Allocator,
 {
-    impl_alloc_ref!(parent);
+    impl_allocator!(parent);
 
-    unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
-        let (layout, prefix_offset, _) = Self::allocation_layout(layout).unwrap();
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+
+        let (layout, prefix_offset, _) = Self::allocation_layout_unchecked(layout);
         let base_ptr = ptr.as_ptr().sub(prefix_offset);
         self.parent
-            .dealloc(NonNull::new_unchecked(base_ptr), layout)
+            .deallocate(NonNull::new_unchecked(base_ptr), layout)
     }
 }
 
-unsafe impl<Alloc, Prefix, Suffix> AllocAll for Affix<Alloc, Prefix, Suffix>
+unsafe impl<Alloc, Prefix, Suffix> ReallocateInPlace for Affix<Alloc, Prefix, Suffix>
 where
-    Alloc: AllocAll,
+    Alloc: ReallocateInPlace,
 {
-    impl_alloc_all!(parent);
+    impl_realloc_in_place!(parent);
 }
 
-unsafe impl<Alloc, Prefix, Suffix> ReallocInPlace for Affix<Alloc, Prefix, Suffix>
+/// `allocate_all` reserves a prefix and a suffix around the drained memory, so [`prefix()`] and
+/// [`suffix()`] may be called on the returned block with a layout of its length and an alignment
+/// of `1`. If the drained memory is too small to hold them, `Err` is returned and the memory stays
+/// allocated until `deallocate_all` is called. Use [`allocate_all_raw`] to get the drained memory
+/// without affixes.
+///
+/// The capacities are forwarded unchanged, as the size of the affixes depends on the number of
+/// allocations.
+///
+/// [`prefix()`]: Affix::prefix
+/// [`suffix()`]: Affix::suffix
+/// [`allocate_all_raw`]: Affix::allocate_all_raw
+unsafe impl<Alloc, Prefix, Suffix> AllocateAll for Affix<Alloc, Prefix, Suffix>
 where
-    Alloc: ReallocInPlace,
+    Alloc: AllocateAll,
 {
-    impl_realloc_in_place!(parent);
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        Self::affix_all(self.parent.allocate_all()?)
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        Self::affix_all(self.parent.allocate_all_zeroed()?)
+    }
+
+    fn deallocate_all(&self) {
+        self.parent.deallocate_all()
+    }
+
+    fn capacity(&self) -> usize {
+        self.parent.capacity()
+    }
+
+    fn capacity_left(&self) -> usize {
+        self.parent.capacity_left()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.parent.is_full()
+    }
+
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.parent.reserve(additional)
+    }
+}
+
+impl<Alloc, Prefix, Suffix> Owns for Affix<Alloc, Prefix, Suffix>
+where
+    Alloc: Owns,
+{
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.parent.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.parent.owns_ptr(ptr)
+    }
+}
+
+impl<Alloc: Describe, Prefix, Suffix> Describe for Affix<Alloc, Prefix, Suffix> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Affix(prefix: {}, suffix: {})",
+            Bytes(mem::size_of::<Prefix>()),
+            Bytes(mem::size_of::<Suffix>())
+        )
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.parent)
+    }
+}
+
+impl<Alloc: Purge, Prefix, Suffix> Purge for Affix<Alloc, Prefix, Suffix> {
+    fn purge(&self, level: PurgeLevel) {
+        self.parent.purge(level)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #![allow(clippy::wildcard_imports)]
     use super::*;
-    use crate::helper::tracker;
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+    };
+    use alloc::alloc::Global;
     use core::fmt;
-    use std::alloc::System;
 
     #[allow(clippy::too_many_lines)]
     fn test_alloc<Prefix, Suffix>(
@@ -443,19 +655,19 @@ mod tests {
         Suffix: fmt::Debug + Copy + PartialEq,
     {
         unsafe {
-            let mut alloc = tracker(Affix::<_, Prefix, Suffix>::new(tracker(System)));
+            let alloc = tracker(Affix::<_, Prefix, Suffix>::new(tracker(Global)));
             let memory = alloc
-                .alloc_zeroed(layout)
+                .allocate_zeroed(layout)
                 .unwrap_or_else(|_| panic!("Could not allocate {} bytes", layout.size()));
 
             if mem::size_of::<Prefix>() == 0 {
                 assert_eq!(
-                    Affix::<System, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout),
+                    Affix::<Global, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout),
                     NonNull::dangling()
                 );
             } else {
                 assert_eq!(
-                    Affix::<System, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout)
+                    Affix::<Global, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout)
                         .cast()
                         .as_ptr(),
                     memory.as_mut_ptr().sub(offset_prefix)
@@ -463,37 +675,39 @@ mod tests {
             }
             if mem::size_of::<Suffix>() == 0 {
                 assert_eq!(
-                    Affix::<System, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout),
+                    Affix::<Global, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout),
                     NonNull::dangling()
                 );
             } else {
                 assert_eq!(
-                    Affix::<System, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout)
+                    Affix::<Global, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout)
                         .cast()
                         .as_ptr(),
                     memory.as_mut_ptr().add(offset_suffix)
                 );
             }
 
-            Affix::<System, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout)
+            Affix::<Global, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout)
                 .as_ptr()
                 .write(prefix);
-            Affix::<System, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout)
+            Affix::<Global, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout)
                 .as_ptr()
                 .write(suffix);
 
             assert_eq!(
-                Affix::<System, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout).as_ref(),
+                Affix::<Global, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout).as_ref(),
                 &prefix
             );
             assert_eq!(
-                Affix::<System, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout).as_ref(),
+                Affix::<Global, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout).as_ref(),
                 &suffix
             );
 
             let old_size = memory.len();
+            let new_layout =
+                Layout::from_size_align(memory.len() * 2, layout.align()).expect("Invalid layout");
             let memory = alloc
-                .grow_zeroed(memory.as_non_null_ptr(), layout, memory.len() * 2)
+                .grow_zeroed(memory.as_non_null_ptr(), layout, new_layout)
                 .expect("Could not grow allocation");
             let layout =
                 Layout::from_size_align(memory.len(), layout.align()).expect("Invalid layout");
@@ -503,30 +717,32 @@ mod tests {
             }
 
             assert_eq!(
-                Affix::<System, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout).as_ref(),
+                Affix::<Global, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout).as_ref(),
                 &prefix
             );
             assert_eq!(
-                Affix::<System, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout).as_ref(),
+                Affix::<Global, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout).as_ref(),
                 &suffix
             );
 
+            let new_layout =
+                Layout::from_size_align(layout.size() / 2, layout.align()).expect("Invalid layout");
             let memory = alloc
-                .shrink(memory.as_non_null_ptr(), layout, layout.size())
+                .shrink(memory.as_non_null_ptr(), layout, new_layout)
                 .expect("Could not shrink allocation");
             let layout =
                 Layout::from_size_align(memory.len(), layout.align()).expect("Invalid layout");
 
             assert_eq!(
-                Affix::<System, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout).as_ref(),
+                Affix::<Global, Prefix, Suffix>::prefix(memory.as_non_null_ptr(), layout).as_ref(),
                 &prefix
             );
             assert_eq!(
-                Affix::<System, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout).as_ref(),
+                Affix::<Global, Prefix, Suffix>::suffix(memory.as_non_null_ptr(), layout).as_ref(),
                 &suffix
             );
 
-            alloc.dealloc(memory.as_non_null_ptr(), layout);
+            alloc.deallocate(memory.as_non_null_ptr(), layout);
         }
     }
 
@@ -585,4 +801,46 @@ mod tests {
     fn test_alloc_u16_u32_a64() {
         test_alloc::<u16, AlignTo64>(0xDEDE, Layout::new::<u32>(), AlignTo64, 4, 0)
     }
+
+    #[test]
+    fn allocate_all() {
+        let mut data = [MaybeUninit::uninit(); 67];
+        let region = Region::<Up>::with_direction(&mut data);
+        let start = region
+            .allocate_all()
+            .expect("Could not allocate all")
+            .as_mut_ptr() as usize;
+        region.deallocate_all();
+
+        let alloc = Affix::<_, u32, u64>::new(&region);
+        let memory = alloc.allocate_all().expect("Could not allocate all");
+        let layout = Layout::from_size_align(memory.len(), 1).expect("Invalid layout");
+        unsafe {
+            let prefix = Affix::<Region<Up>, u32, u64>::prefix(memory.as_non_null_ptr(), layout);
+            let suffix = Affix::<Region<Up>, u32, u64>::suffix(memory.as_non_null_ptr(), layout);
+            assert_eq!(prefix.as_ptr() as usize % 8, 0);
+            assert!(prefix.as_ptr() as usize >= start);
+            assert_eq!(prefix.as_ptr() as usize + 4, memory.as_mut_ptr() as usize);
+            assert_eq!(
+                suffix.as_ptr() as usize,
+                memory.as_mut_ptr() as usize + memory.len()
+            );
+            assert!(suffix.as_ptr() as usize + 8 <= start + 67);
+            prefix.as_ptr().write(0xDEAD_BEEF);
+            suffix.as_ptr().write(0xCAFE_BABE_DEAD_BEEF);
+        }
+        assert!(alloc.is_full());
+
+        alloc.deallocate_all();
+        let memory = alloc.allocate_all_raw().expect("Could not allocate all");
+        assert_eq!((memory.as_mut_ptr() as usize, memory.len()), (start, 67));
+
+        alloc.deallocate_all();
+        region
+            .allocate(Layout::new::<[u8; 60]>())
+            .expect("Could not allocate 60 bytes");
+        alloc
+            .allocate_all()
+            .expect_err("Could allocate all with 7 bytes left");
+    }
 }
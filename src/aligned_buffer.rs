@@ -0,0 +1,201 @@
+use core::{
+    fmt,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+/// Storage of `N` bytes, which is aligned to `A`.
+///
+/// Regions are created over a slice of bytes, but arrays of `MaybeUninit<u8>` are only aligned to
+/// one byte. `AlignedBuffer` can be used on the stack or in a static to provide memory with a
+/// known alignment, and dereferences to `[MaybeUninit<u8>]`, so it can be passed to a region
+/// directly. `A` must be a power of two up to 4096, the size of a page on most targets.
+/// [`aligned_buffer!`] is a shorthand for [`AlignedBuffer::new`].
+///
+/// [`aligned_buffer!`]: crate::aligned_buffer
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{aligned_buffer, region::Region, AlignedBuffer, AllocateAll};
+/// use core::alloc::{Allocator, Layout};
+///
+/// let mut buffer = AlignedBuffer::<64, 32>::new();
+/// let region = Region::new(&mut buffer);
+///
+/// // No padding is needed to align the memory block
+/// region.allocate(Layout::from_size_align(32, 32)?)?;
+/// assert_eq!(region.capacity_left(), 32);
+///
+/// let mut buffer = aligned_buffer!(4096, 4096);
+/// assert_eq!(buffer.as_ptr() as usize % 4096, 0);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Alignments, which are not supported, fail to compile:
+///
+/// ```compile_fail
+/// use alloc_compose::AlignedBuffer;
+///
+/// let buffer = AlignedBuffer::<64, 3>::new();
+/// ```
+#[repr(C)]
+pub struct AlignedBuffer<const N: usize, const A: usize>
+where
+    Alignment<A>: SupportedAlignment,
+{
+    _align: [<Alignment<A> as SupportedAlignment>::Marker; 0],
+    data: [MaybeUninit<u8>; N],
+}
+
+/// An alignment in bytes, used as bound for [`AlignedBuffer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Alignment<const A: usize>;
+
+/// Implemented for every [`Alignment`], which can be used for an [`AlignedBuffer`].
+pub trait SupportedAlignment {
+    #[doc(hidden)]
+    type Marker: Copy;
+}
+
+macro_rules! impl_supported_alignment {
+    ($($marker:ident: $align:literal),+ $(,)?) => {
+        $(
+            #[doc(hidden)]
+            #[derive(Copy, Clone)]
+            #[repr(align($align))]
+            pub struct $marker;
+
+            impl SupportedAlignment for Alignment<$align> {
+                type Marker = $marker;
+            }
+        )+
+    };
+}
+
+impl_supported_alignment!(
+    Align1: 1,
+    Align2: 2,
+    Align4: 4,
+    Align8: 8,
+    Align16: 16,
+    Align32: 32,
+    Align64: 64,
+    Align128: 128,
+    Align256: 256,
+    Align512: 512,
+    Align1024: 1024,
+    Align2048: 2048,
+    Align4096: 4096,
+);
+
+impl<const N: usize, const A: usize> AlignedBuffer<N, A>
+where
+    Alignment<A>: SupportedAlignment,
+{
+    /// Creates an uninitialized buffer.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            _align: [],
+            data: [MaybeUninit::uninit(); N],
+        }
+    }
+
+    /// Creates a buffer, where every byte is initialized to `byte`.
+    ///
+    /// This is useful to detect, if memory was not zeroed or is read before it was written.
+    #[inline]
+    pub const fn filled(byte: u8) -> Self {
+        Self {
+            _align: [],
+            data: [MaybeUninit::new(byte); N],
+        }
+    }
+}
+
+impl<const N: usize, const A: usize> Default for AlignedBuffer<N, A>
+where
+    Alignment<A>: SupportedAlignment,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const A: usize> fmt::Debug for AlignedBuffer<N, A>
+where
+    Alignment<A>: SupportedAlignment,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuffer")
+            .field("size", &N)
+            .field("align", &A)
+            .finish()
+    }
+}
+
+impl<const N: usize, const A: usize> Deref for AlignedBuffer<N, A>
+where
+    Alignment<A>: SupportedAlignment,
+{
+    type Target = [MaybeUninit<u8>];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<const N: usize, const A: usize> DerefMut for AlignedBuffer<N, A>
+where
+    Alignment<A>: SupportedAlignment,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data
+    }
+}
+
+/// Creates an [`AlignedBuffer`] of `size` bytes aligned to `align`.
+///
+/// An optional third argument initializes every byte, see [`AlignedBuffer::filled`].
+///
+/// ```rust
+/// use alloc_compose::aligned_buffer;
+///
+/// let buffer = aligned_buffer!(128, 64, 0xAB);
+/// assert_eq!(buffer.len(), 128);
+/// assert_eq!(buffer.as_ptr() as usize % 64, 0);
+/// assert_eq!(unsafe { buffer[127].assume_init() }, 0xAB);
+/// ```
+#[macro_export]
+macro_rules! aligned_buffer {
+    ($size:expr, $align:expr $(,)?) => {
+        $crate::AlignedBuffer::<{ $size }, { $align }>::new()
+    };
+    ($size:expr, $align:expr, $byte:expr $(,)?) => {
+        $crate::AlignedBuffer::<{ $size }, { $align }>::filled($byte)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AlignedBuffer;
+    use core::mem;
+
+    #[test]
+    fn layout() {
+        assert_eq!(mem::size_of::<AlignedBuffer<24, 8>>(), 24);
+        assert_eq!(mem::align_of::<AlignedBuffer<24, 8>>(), 8);
+        assert_eq!(mem::size_of::<AlignedBuffer<0, 4096>>(), 0);
+        assert_eq!(mem::align_of::<AlignedBuffer<0, 4096>>(), 4096);
+
+        static BUFFER: AlignedBuffer<100, 64> = AlignedBuffer::filled(1);
+        assert_eq!(BUFFER.as_ptr() as usize % 64, 0);
+        assert_eq!(BUFFER.len(), 100);
+    }
+}
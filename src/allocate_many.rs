@@ -0,0 +1,198 @@
+use crate::Allocator;
+use core::{
+    alloc::{AllocError, Layout},
+    iter::FusedIterator,
+    ptr::NonNull,
+};
+
+/// Extends `Allocator` to allocate and deallocate many memory blocks at once.
+///
+/// Every allocator implements this trait by allocating and deallocating one memory block after
+/// another. Allocators, which can serve many requests at once more efficiently, override it. For
+/// example, all regions bump their position only once by `n` times the padded size of `layout` and
+/// split the resulting memory block, and [`TlsCache`] takes its locks only once for a whole batch
+/// of deallocations.
+///
+/// [`TlsCache`]: crate::TlsCache
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, AllocateMany};
+/// use core::{alloc::Layout, mem::MaybeUninit};
+///
+/// let mut data = [MaybeUninit::uninit(); 256];
+/// let region = Region::new(&mut data);
+///
+/// for memory in region.allocate_many(Layout::new::<[u32; 3]>(), 8) {
+///     assert_eq!(memory?.len(), 12);
+/// }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub trait AllocateMany: Allocator {
+    /// Allocates `n` memory blocks, which fit `layout`.
+    ///
+    /// The returned iterator yields exactly `n` results. Every successfully allocated memory block
+    /// has to be deallocated separately with `layout`. Memory blocks, which are not taken from the
+    /// iterator, may have been allocated already and are only released, when the allocator
+    /// releases all of its memory.
+    fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self>;
+
+    /// Deallocates all memory blocks yielded by `blocks`.
+    ///
+    /// # Safety
+    ///
+    /// Every pair of pointer and layout yielded by `blocks` has to satisfy the safety conditions of
+    /// [`Allocator::deallocate`].
+    unsafe fn deallocate_many<I>(&self, blocks: I)
+    where
+        I: IntoIterator<Item = (NonNull<u8>, Layout)>;
+}
+
+impl<A: Allocator + ?Sized> AllocateMany for A {
+    default fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+        ManyBlocks::one_by_one(self, layout, n)
+    }
+
+    default unsafe fn deallocate_many<I>(&self, blocks: I)
+    where
+        I: IntoIterator<Item = (NonNull<u8>, Layout)>,
+    {
+        for (ptr, layout) in blocks {
+            self.deallocate(ptr, layout)
+        }
+    }
+}
+
+/// An iterator over memory blocks returned by [`AllocateMany::allocate_many`].
+#[derive(Debug)]
+pub struct ManyBlocks<'a, A: ?Sized> {
+    alloc: &'a A,
+    layout: Layout,
+    remaining: usize,
+    next: Option<NonNull<u8>>,
+}
+
+impl<'a, A: ?Sized> ManyBlocks<'a, A> {
+    /// Returns an iterator, which allocates one memory block from `alloc` per step.
+    pub fn one_by_one(alloc: &'a A, layout: Layout, n: usize) -> Self {
+        Self {
+            alloc,
+            layout,
+            remaining: n,
+            next: None,
+        }
+    }
+
+    /// Returns an iterator, which splits the memory at `ptr` into `n` memory blocks.
+    ///
+    /// The memory blocks are placed `layout.pad_to_align().size()` bytes apart.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be aligned to `layout.align()` and must point to `n` times the padded size of
+    /// `layout` bytes allocated by `alloc`, which can be deallocated in pieces of `layout`.
+    pub unsafe fn contiguous(alloc: &'a A, layout: Layout, n: usize, ptr: NonNull<u8>) -> Self {
+        Self {
+            alloc,
+            layout,
+            remaining: n,
+            next: Some(ptr),
+        }
+    }
+}
+
+impl<A: Allocator + ?Sized> Iterator for ManyBlocks<'_, A> {
+    type Item = Result<NonNull<[u8]>, AllocError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        match self.next {
+            Some(ptr) => {
+                let stride = self.layout.pad_to_align().size();
+                self.next = NonNull::new(ptr.as_ptr().wrapping_add(stride));
+                Some(Ok(NonNull::slice_from_raw_parts(ptr, self.layout.size())))
+            }
+            None => Some(self.alloc.allocate(self.layout)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<A: Allocator + ?Sized> ExactSizeIterator for ManyBlocks<'_, A> {}
+
+impl<A: Allocator + ?Sized> FusedIterator for ManyBlocks<'_, A> {}
+
+/// Allocates all memory blocks with one request to `alloc` and falls back to allocating them
+/// one by one.
+///
+/// # Safety
+///
+/// `alloc` must support deallocating parts of a memory block.
+pub(crate) unsafe fn bump_many<A: Allocator + ?Sized>(
+    alloc: &A,
+    layout: Layout,
+    n: usize,
+) -> ManyBlocks<'_, A> {
+    let stride = layout.pad_to_align().size();
+    let total = stride
+        .checked_mul(n)
+        .and_then(|size| Layout::from_size_align(size, layout.align()).ok());
+    match total.map(|total| alloc.allocate(total)) {
+        Some(Ok(memory)) => ManyBlocks::contiguous(alloc, layout, n, memory.as_non_null_ptr()),
+        _ => ManyBlocks::one_by_one(alloc, layout, n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AllocateMany;
+    use crate::{helper::tracker, region::Region, AllocateAll};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::alloc::Layout;
+
+    #[test]
+    fn contiguous() {
+        let mut data = crate::aligned_buffer!(128, 4);
+        let region = Region::new(&mut data);
+        let layout = Layout::from_size_align(6, 4).unwrap();
+
+        let blocks = region
+            .allocate_many(layout, 10)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Could not allocate 10 blocks");
+        assert_eq!(blocks.len(), 10);
+        for pair in blocks.windows(2) {
+            assert_eq!(
+                pair[1].as_mut_ptr() as usize - pair[0].as_mut_ptr() as usize,
+                8
+            );
+            assert_eq!(pair[0].len(), 6);
+        }
+        assert_eq!(region.capacity_left(), 128 - 80);
+
+        assert!(region
+            .allocate_many(layout, 100)
+            .any(|result| result.is_err()));
+    }
+
+    #[test]
+    fn one_by_one() {
+        let alloc = tracker(Global);
+        let layout = Layout::new::<[u16; 3]>();
+        let blocks = alloc.allocate_many(layout, 4);
+        assert_eq!(blocks.len(), 4);
+        let blocks = blocks
+            .map(|memory| memory.expect("Could not allocate block").as_non_null_ptr())
+            .collect::<Vec<_>>();
+        unsafe { alloc.deallocate_many(blocks.into_iter().map(|ptr| (ptr, layout))) };
+    }
+}
@@ -0,0 +1,173 @@
+use crate::Allocator;
+use core::{
+    alloc::{AllocError, Layout},
+    ptr::NonNull,
+};
+
+/// Extends `Allocator` to pass a hint, where a memory block should be allocated.
+///
+/// The hint is an arbitrary index chosen by the caller, e.g. the index of the current core or of
+/// a memory bank. Every allocator implements this trait by ignoring the hint. Allocators, which
+/// can place memory blocks, override it. For example, [`Striped`] allocates from the stripe at the
+/// hinted index.
+///
+/// [`Striped`]: crate::Striped
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::AllocateWithHint;
+/// use std::alloc::{Layout, System};
+///
+/// let memory = System.allocate_with_hint(Layout::new::<u64>(), 3)?;
+/// assert_eq!(memory.len(), 8);
+/// # unsafe { std::alloc::Allocator::deallocate(&System, memory.cast(), Layout::new::<u64>()) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub trait AllocateWithHint: Allocator {
+    /// Behaves like [`allocate`], but places the memory block according to `hint`.
+    ///
+    /// [`allocate`]: Allocator::allocate
+    fn allocate_with_hint(&self, layout: Layout, hint: usize) -> Result<NonNull<[u8]>, AllocError>;
+
+    /// Behaves like [`allocate_zeroed`], but places the memory block according to `hint`.
+    ///
+    /// [`allocate_zeroed`]: Allocator::allocate_zeroed
+    fn allocate_zeroed_with_hint(
+        &self,
+        layout: Layout,
+        hint: usize,
+    ) -> Result<NonNull<[u8]>, AllocError>;
+}
+
+impl<A: Allocator + ?Sized> AllocateWithHint for A {
+    #[inline]
+    default fn allocate_with_hint(
+        &self,
+        layout: Layout,
+        _hint: usize,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate(layout)
+    }
+
+    #[inline]
+    default fn allocate_zeroed_with_hint(
+        &self,
+        layout: Layout,
+        _hint: usize,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_zeroed(layout)
+    }
+}
+
+impl<A: AllocateWithHint + ?Sized> AllocateWithHint for &A {
+    #[inline]
+    fn allocate_with_hint(&self, layout: Layout, hint: usize) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate_with_hint(layout, hint)
+    }
+
+    #[inline]
+    fn allocate_zeroed_with_hint(
+        &self,
+        layout: Layout,
+        hint: usize,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate_zeroed_with_hint(layout, hint)
+    }
+}
+
+/// Extends `Allocator` to allocate a memory block close to an existing one.
+///
+/// Linked structures like lists or trees are traversed faster, when their nodes are close to each
+/// other. `hint` is usually a pointer to a neighboring node, but it doesn't have to be allocated
+/// by this allocator. Every allocator implements this trait by ignoring the hint. Allocators
+/// consisting of several chunks override it to allocate from the chunk containing `hint` first,
+/// e.g. [`MultiRegion`] and [`Striped`].
+///
+/// [`MultiRegion`]: crate::region::MultiRegion
+/// [`Striped`]: crate::Striped
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::MultiRegion, AllocateNear};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut chunks = [[MaybeUninit::uninit(); 32]; 2];
+/// let region = MultiRegion::new(&mut chunks);
+///
+/// let head = region.allocate(Layout::new::<[u8; 16]>())?;
+/// region.allocate(Layout::new::<[u8; 24]>())?;
+/// assert_eq!(region.current_chunk(), 1);
+///
+/// // The first chunk still has space for the next node
+/// let next = region.allocate_near(Layout::new::<[u8; 16]>(), head.cast())?;
+/// assert_eq!(
+///     next.cast::<u8>().as_ptr().wrapping_add(16),
+///     head.cast().as_ptr()
+/// );
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub trait AllocateNear: Allocator {
+    /// Behaves like [`allocate`], but places the memory block close to `hint` if possible.
+    ///
+    /// [`allocate`]: Allocator::allocate
+    fn allocate_near(&self, layout: Layout, hint: NonNull<u8>)
+        -> Result<NonNull<[u8]>, AllocError>;
+
+    /// Behaves like [`allocate_zeroed`], but places the memory block close to `hint` if possible.
+    ///
+    /// [`allocate_zeroed`]: Allocator::allocate_zeroed
+    fn allocate_zeroed_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError>;
+}
+
+impl<A: Allocator + ?Sized> AllocateNear for A {
+    #[inline]
+    default fn allocate_near(
+        &self,
+        layout: Layout,
+        _hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate(layout)
+    }
+
+    #[inline]
+    default fn allocate_zeroed_near(
+        &self,
+        layout: Layout,
+        _hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_zeroed(layout)
+    }
+}
+
+impl<A: AllocateNear + ?Sized> AllocateNear for &A {
+    #[inline]
+    fn allocate_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate_near(layout, hint)
+    }
+
+    #[inline]
+    fn allocate_zeroed_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate_zeroed_near(layout, hint)
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// An allocated memory block: its address and its length.
+type Block = (NonNull<u8>, usize);
+
+/// Remembers the last `N` memory blocks handed out, so ownership tests on them skip the
+/// underlying allocator.
+///
+/// For allocators consisting of several chunks, like [`MultiRegion`], [`Owns::owns`] has to check
+/// every chunk. Memory blocks are often deallocated soon after they were allocated, e.g. when
+/// temporary buffers are freed, and a [`Fallback`] asks its primary allocator on every
+/// deallocation, if it owns the memory block. As long as a memory block is allocated, it is owned,
+/// so `CachedOwns` answers positively for every memory block in its cache. Otherwise, the question
+/// is forwarded. Memory blocks are evicted from the cache, when they are deallocated or
+/// reallocated, and the oldest one is replaced, when the cache is full.
+///
+/// [`hits`] and [`misses`] count how often the cache was able to answer.
+///
+/// [`MultiRegion`]: crate::region::MultiRegion
+/// [`Fallback`]: crate::Fallback
+/// [`hits`]: Self::hits
+/// [`misses`]: Self::misses
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{region::MultiRegion, CachedOwns, Fallback};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+/// use std::alloc::System;
+///
+/// let mut chunks = [[MaybeUninit::uninit(); 64]; 8];
+/// let alloc = Fallback {
+///     primary: CachedOwns::<_, 4>::new(MultiRegion::new(&mut chunks)),
+///     secondary: System,
+/// };
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 32]>())?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 32]>()) };
+/// assert_eq!(alloc.primary.hits(), 1);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct CachedOwns<A, const N: usize> {
+    alloc: A,
+    blocks: Cell<[Option<Block>; N]>,
+    next: Cell<usize>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+}
+
+impl<A, const N: usize> CachedOwns<A, N> {
+    /// Caches the ownership of the last `N` memory blocks allocated by `alloc`.
+    pub fn new(alloc: A) -> Self {
+        Self {
+            alloc,
+            blocks: Cell::new([None; N]),
+            next: Cell::new(0),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn get_ref(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the number of ownership tests answered by the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.get()
+    }
+
+    /// Returns the number of ownership tests forwarded to the underlying allocator.
+    pub fn misses(&self) -> usize {
+        self.misses.get()
+    }
+
+    fn insert(&self, memory: NonNull<[u8]>) {
+        if N == 0 {
+            return;
+        }
+        let next = self.next.get();
+        let mut blocks = self.blocks.get();
+        blocks[next] = Some((memory.as_non_null_ptr(), memory.len()));
+        self.blocks.set(blocks);
+        self.next.set((next + 1) % N);
+    }
+
+    fn remove(&self, ptr: NonNull<u8>) {
+        let mut blocks = self.blocks.get();
+        for block in &mut blocks {
+            if matches!(block, Some((start, _)) if *start == ptr) {
+                *block = None;
+            }
+        }
+        self.blocks.set(blocks);
+    }
+
+    fn clear(&self) {
+        self.blocks.set([None; N]);
+    }
+
+    /// Returns if `ptr..ptr + len` lies within a cached memory block.
+    fn lookup(&self, ptr: NonNull<u8>, len: usize) -> bool {
+        let start = ptr.as_ptr() as usize;
+        let cached = self
+            .blocks
+            .get()
+            .iter()
+            .flatten()
+            .any(|&(block, block_len)| {
+                let block = block.as_ptr() as usize;
+                start >= block && start - block < block_len && len <= block_len - (start - block)
+            });
+        if cached {
+            self.hits.set(self.hits.get() + 1);
+        } else {
+            self.misses.set(self.misses.get() + 1);
+        }
+        cached
+    }
+
+    /// Replaces the cached memory block at `ptr` with the result of a reallocation.
+    fn update(
+        &self,
+        ptr: NonNull<u8>,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = result?;
+        self.remove(ptr);
+        self.insert(memory);
+        Ok(memory)
+    }
+}
+
+impl<A: fmt::Debug, const N: usize> fmt::Debug for CachedOwns<A, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedOwns")
+            .field("alloc", &self.alloc)
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish()
+    }
+}
+
+impl<A: Default, const N: usize> Default for CachedOwns<A, N> {
+    fn default() -> Self {
+        Self::new(A::default())
+    }
+}
+
+unsafe impl<A: Allocator, const N: usize> Allocator for CachedOwns<A, N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.alloc.allocate(layout)?;
+        self.insert(memory);
+        Ok(memory)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.alloc.allocate_zeroed(layout)?;
+        self.insert(memory);
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.remove(ptr);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.update(ptr, self.alloc.grow(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.update(ptr, self.alloc.grow_zeroed(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.update(ptr, self.alloc.shrink(ptr, old_layout, new_layout))
+    }
+}
+
+unsafe impl<A: AllocateAll, const N: usize> AllocateAll for CachedOwns<A, N> {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all()
+    }
+
+    #[inline]
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all_zeroed()
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.clear();
+        self.alloc.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.alloc.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.alloc.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.alloc.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
+}
+
+// Memory blocks grown in place keep their old, smaller length in the cache. Memory blocks shrunk
+// in place are evicted, as their cached length would exceed the new one.
+unsafe impl<A: ReallocateInPlace, const N: usize> ReallocateInPlace for CachedOwns<A, N> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.alloc.grow_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.alloc.grow_in_place_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        let len = self.alloc.shrink_in_place(ptr, old_layout, new_layout)?;
+        self.remove(ptr);
+        Ok(len)
+    }
+}
+
+impl<A: Owns, const N: usize> Owns for CachedOwns<A, N> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.lookup(memory.as_non_null_ptr(), memory.len()) || self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.lookup(ptr, 0) || self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: ReportUsage, const N: usize> ReportUsage for CachedOwns<A, N> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: BlockSize, const N: usize> BlockSize for CachedOwns<A, N> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.alloc.block_size()
+    }
+}
+
+impl<A: Describe, const N: usize> Describe for CachedOwns<A, N> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CachedOwns<{}>({} hits, {} misses)",
+            N,
+            self.hits(),
+            self.misses()
+        )
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, const N: usize> Purge for CachedOwns<A, N> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachedOwns;
+    use crate::{region::MultiRegion, AllocateAll, Allocator, Owns};
+    use core::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
+
+    #[test]
+    fn cache() {
+        let mut chunks = [[MaybeUninit::uninit(); 64]; 4];
+        let alloc = CachedOwns::<_, 2>::new(MultiRegion::new(&mut chunks));
+        let layout = Layout::new::<[u8; 16]>();
+
+        let blocks = [(); 3].map(|_| alloc.allocate(layout).expect("Could not allocate 16 bytes"));
+        assert!(alloc.owns(blocks[2]));
+        assert!(alloc.owns(NonNull::slice_from_raw_parts(
+            unsafe { blocks[1].as_non_null_ptr().add(8) },
+            8
+        )));
+        assert_eq!((alloc.hits(), alloc.misses()), (2, 0));
+
+        // evicted by the third allocation
+        assert!(alloc.owns(blocks[0]));
+        assert_eq!((alloc.hits(), alloc.misses()), (2, 1));
+
+        unsafe { alloc.deallocate(blocks[2].as_non_null_ptr(), layout) };
+        assert!(alloc.owns(blocks[1]));
+        assert!(alloc.owns(blocks[2]));
+        assert_eq!((alloc.hits(), alloc.misses()), (3, 2));
+
+        let grown = unsafe {
+            alloc
+                .grow(
+                    blocks[1].as_non_null_ptr(),
+                    layout,
+                    Layout::new::<[u8; 32]>(),
+                )
+                .expect("Could not grow to 32 bytes")
+        };
+        assert!(alloc.owns(grown));
+        assert_eq!(alloc.hits(), 4);
+
+        alloc.deallocate_all();
+        assert!(!alloc.owns(grown));
+        assert_eq!(alloc.misses(), 3);
+    }
+}
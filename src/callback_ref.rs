@@ -5,7 +5,7 @@ use core::{
 
 /// Backend for the [`Proxy`] allocator.
 ///
-/// As `Callback` is used in `Proxy` and `AllocRef` requires, that a cloned allocator must
+/// As `Callback` is used in `Proxy` and `Allocator` requires, that a cloned allocator must
 /// behave like the same allocator, `Clone` must not be implemented on types, which don't
 /// have a shared state. It's possible to use a reference by calling [`by_ref`] or to
 /// wrapping them into `Rc` or `Arc` in order to make them cloneable instead. Note, that
@@ -18,27 +18,27 @@ use core::{
 ///   * `Clone` must not be implemented on types, which don't have a shared state.
 #[allow(unused_variables)]
 pub unsafe trait CallbackRef {
-    /// Called before [`alloc`] was invoked.
+    /// Called before [`allocate`] was invoked.
     ///
-    /// [`alloc`]: core::alloc::AllocRef::alloc
+    /// [`allocate`]: core::alloc::Allocator::allocate
     #[inline]
     fn before_allocate(&self, layout: Layout) {}
 
-    /// Called after [`alloc`] was invoked.
+    /// Called after [`allocate`] was invoked.
     ///
-    /// [`alloc`]: core::alloc::AllocRef::alloc
+    /// [`allocate`]: core::alloc::Allocator::allocate
     #[inline]
     fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {}
 
-    /// Called before [`alloc_zeroed`] was invoked.
+    /// Called before [`allocate_zeroed`] was invoked.
     ///
-    /// [`alloc_zeroed`]: core::alloc::AllocRef::alloc_zeroed
+    /// [`allocate_zeroed`]: core::alloc::Allocator::allocate_zeroed
     #[inline]
     fn before_allocate_zeroed(&self, layout: Layout) {}
 
-    /// Called after [`alloc_zeroed`] was invoked.
+    /// Called after [`allocate_zeroed`] was invoked.
     ///
-    /// [`alloc_zeroed`]: core::alloc::AllocRef::alloc_zeroed
+    /// [`allocate_zeroed`]: core::alloc::Allocator::allocate_zeroed
     #[inline]
     fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {}
 
@@ -66,15 +66,15 @@ pub unsafe trait CallbackRef {
     #[inline]
     fn after_allocate_all_zeroed(&self, result: Result<NonNull<[u8]>, AllocError>) {}
 
-    /// Called before [`dealloc`] was invoked.
+    /// Called before [`deallocate`] was invoked.
     ///
-    /// [`dealloc`]: core::alloc::AllocRef::dealloc
+    /// [`deallocate`]: core::alloc::Allocator::deallocate
     #[inline]
     fn before_deallocate(&self, ptr: NonNull<u8>, layout: Layout) {}
 
-    /// Called after [`dealloc`] was invoked.
+    /// Called after [`deallocate`] was invoked.
     ///
-    /// [`dealloc`]: core::alloc::AllocRef::dealloc
+    /// [`deallocate`]: core::alloc::Allocator::deallocate
     #[inline]
     fn after_deallocate(&self, ptr: NonNull<u8>, layout: Layout) {}
 
@@ -92,13 +92,13 @@ pub unsafe trait CallbackRef {
 
     /// Called before [`grow`] was invoked.
     ///
-    /// [`grow`]: core::alloc::AllocRef::grow
+    /// [`grow`]: core::alloc::Allocator::grow
     #[inline]
     fn before_grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) {}
 
     /// Called after [`grow`] was invoked.
     ///
-    /// [`grow`]: core::alloc::AllocRef::grow
+    /// [`grow`]: core::alloc::Allocator::grow
     #[inline]
     fn after_grow(
         &self,
@@ -111,13 +111,13 @@ pub unsafe trait CallbackRef {
 
     /// Called before [`grow_zeroed`] was invoked.
     ///
-    /// [`grow_zeroed`]: core::alloc::AllocRef::grow_zeroed
+    /// [`grow_zeroed`]: core::alloc::Allocator::grow_zeroed
     #[inline]
     fn before_grow_zeroed(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) {}
 
     /// Called after [`grow_zeroed`] was invoked.
     ///
-    /// [`grow_zeroed`]: core::alloc::AllocRef::grow_zeroed
+    /// [`grow_zeroed`]: core::alloc::Allocator::grow_zeroed
     #[inline]
     fn after_grow_zeroed(
         &self,
@@ -174,13 +174,13 @@ pub unsafe trait CallbackRef {
 
     /// Called before [`shrink`] was invoked.
     ///
-    /// [`shrink`]: core::alloc::AllocRef::shrink
+    /// [`shrink`]: core::alloc::Allocator::shrink
     #[inline]
     fn before_shrink(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) {}
 
     /// Called after [`shrink`] was invoked.
     ///
-    /// [`shrink`]: core::alloc::AllocRef::shrink
+    /// [`shrink`]: core::alloc::Allocator::shrink
     #[inline]
     fn after_shrink(
         &self,
@@ -222,11 +222,195 @@ pub unsafe trait CallbackRef {
     #[inline]
     fn after_owns(&self, success: bool) {}
 
+    /// Called when [`Canonicalize`] adjusted a requested layout, before passing it on.
+    ///
+    /// `requested` is the layout of an allocation request or the new layout of a reallocation.
+    /// Not called if the layout was already canonical.
+    ///
+    /// [`Canonicalize`]: crate::Canonicalize
+    #[inline]
+    fn after_adjust_layout(&self, requested: Layout, adjusted: Layout) {}
+
+    /// Returns, if a [`Proxy`] should pass the capacity left of its allocator to
+    /// [`after_capacity_left`]. Defaults to `false`, as querying [`capacity_left`] is not free for
+    /// every allocator.
+    ///
+    /// [`Proxy`]: crate::Proxy
+    /// [`after_capacity_left`]: Self::after_capacity_left
+    /// [`capacity_left`]: crate::AllocateAll::capacity_left
+    #[inline]
+    fn samples_capacity_left(&self) -> bool {
+        false
+    }
+
+    /// Called by a [`Proxy`] around an [`AllocateAll`] allocator after every request with the
+    /// [`capacity_left`] of the allocator, if [`samples_capacity_left`] returns `true`.
+    ///
+    /// [`Proxy`]: crate::Proxy
+    /// [`AllocateAll`]: crate::AllocateAll
+    /// [`capacity_left`]: crate::AllocateAll::capacity_left
+    /// [`samples_capacity_left`]: Self::samples_capacity_left
+    #[inline]
+    fn after_capacity_left(&self, capacity_left: usize) {}
+
+    /// Called by [`SoftLimits`] when the usage crossed one of its soft limits.
+    ///
+    /// `level` is the number of soft limits reached by `used` bytes, so it's `0` when the usage
+    /// dropped below the lowest one again. `limit` is the limit, which the soft limits are relative
+    /// to.
+    ///
+    /// [`SoftLimits`]: crate::stats::SoftLimits
+    #[inline]
+    fn on_watermark(&self, level: usize, used: usize, limit: usize) {}
+
+    /// Called after [`allocate`] was invoked through [`IdTagger`].
+    ///
+    /// `id` identifies the memory block for its whole lifetime, even if the pointer is reused
+    /// later on. Defaults to [`after_allocate`].
+    ///
+    /// [`allocate`]: core::alloc::Allocator::allocate
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`after_allocate`]: Self::after_allocate
+    #[inline]
+    fn after_allocate_with_id(
+        &self,
+        id: u64,
+        layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) {
+        self.after_allocate(layout, result)
+    }
+
+    /// Called after [`allocate_zeroed`] was invoked through [`IdTagger`].
+    ///
+    /// Defaults to [`after_allocate_zeroed`].
+    ///
+    /// [`allocate_zeroed`]: core::alloc::Allocator::allocate_zeroed
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`after_allocate_zeroed`]: Self::after_allocate_zeroed
+    #[inline]
+    fn after_allocate_zeroed_with_id(
+        &self,
+        id: u64,
+        layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) {
+        self.after_allocate_zeroed(layout, result)
+    }
+
+    /// Called before [`deallocate`] was invoked through [`IdTagger`].
+    ///
+    /// Defaults to [`before_deallocate`].
+    ///
+    /// [`deallocate`]: core::alloc::Allocator::deallocate
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`before_deallocate`]: Self::before_deallocate
+    #[inline]
+    fn before_deallocate_with_id(&self, id: u64, ptr: NonNull<u8>, layout: Layout) {
+        self.before_deallocate(ptr, layout)
+    }
+
+    /// Called after [`deallocate`] was invoked through [`IdTagger`].
+    ///
+    /// Defaults to [`after_deallocate`].
+    ///
+    /// [`deallocate`]: core::alloc::Allocator::deallocate
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`after_deallocate`]: Self::after_deallocate
+    #[inline]
+    fn after_deallocate_with_id(&self, id: u64, ptr: NonNull<u8>, layout: Layout) {
+        self.after_deallocate(ptr, layout)
+    }
+
+    /// Called after [`grow`] was invoked through [`IdTagger`].
+    ///
+    /// The memory block keeps its `id`, even if it was moved. Defaults to [`after_grow`].
+    ///
+    /// [`grow`]: core::alloc::Allocator::grow
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`after_grow`]: Self::after_grow
+    #[inline]
+    fn after_grow_with_id(
+        &self,
+        id: u64,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) {
+        self.after_grow(ptr, old_layout, new_layout, result)
+    }
+
+    /// Called after [`grow_zeroed`] was invoked through [`IdTagger`].
+    ///
+    /// Defaults to [`after_grow_zeroed`].
+    ///
+    /// [`grow_zeroed`]: core::alloc::Allocator::grow_zeroed
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`after_grow_zeroed`]: Self::after_grow_zeroed
+    #[inline]
+    fn after_grow_zeroed_with_id(
+        &self,
+        id: u64,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) {
+        self.after_grow_zeroed(ptr, old_layout, new_layout, result)
+    }
+
+    /// Called after [`shrink`] was invoked through [`IdTagger`].
+    ///
+    /// Defaults to [`after_shrink`].
+    ///
+    /// [`shrink`]: core::alloc::Allocator::shrink
+    /// [`IdTagger`]: crate::IdTagger
+    /// [`after_shrink`]: Self::after_shrink
+    #[inline]
+    fn after_shrink_with_id(
+        &self,
+        id: u64,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) {
+        self.after_shrink(ptr, old_layout, new_layout, result)
+    }
+
+    /// Called by [`Proxy::new`] once the proxy was created, e.g. to open a log file or to
+    /// register a metrics gauge.
+    ///
+    /// Proxies constructed otherwise, e.g. by a struct expression or by `Default`, don't call it.
+    ///
+    /// [`Proxy::new`]: crate::Proxy::new
+    #[inline]
+    fn on_create(&self) {}
+
+    /// Called by [`Proxy::destroy`] before the proxy is taken apart.
+    ///
+    /// A `Proxy` can't call it when it's dropped, as it has to stay `Copy` if its parts are.
+    ///
+    /// [`Proxy::destroy`]: crate::Proxy::destroy
+    #[inline]
+    fn on_destroy(&self) {}
+
+    /// Called after [`deallocate_all`] reset the allocator and after [`after_deallocate_all`].
+    ///
+    /// [`deallocate_all`]: crate::AllocateAll::deallocate_all
+    /// [`after_deallocate_all`]: Self::after_deallocate_all
+    #[inline]
+    fn on_reset(&self) {}
+
     /// Creates a "by reference" adaptor for this instance of `CallbackRef`.
     ///
     /// The returned adaptor also implements `CallbackRef` and will simply borrow this.
     #[inline]
-    fn by_ref(&self) -> &Self {
+    fn by_ref(&self) -> &Self
+    where
+        Self: Sized,
+    {
         self
     }
 }
@@ -416,10 +600,113 @@ macro_rules! impl_alloc_stats {
             fn after_owns(&self, success: bool) {
                 (**self).after_owns(success)
             }
+
+            #[inline]
+            fn after_adjust_layout(&self, requested: Layout, adjusted: Layout) {
+                (**self).after_adjust_layout(requested, adjusted)
+            }
+
+            #[inline]
+            fn samples_capacity_left(&self) -> bool {
+                (**self).samples_capacity_left()
+            }
+
+            #[inline]
+            fn after_capacity_left(&self, capacity_left: usize) {
+                (**self).after_capacity_left(capacity_left)
+            }
+
+            #[inline]
+            fn on_watermark(&self, level: usize, used: usize, limit: usize) {
+                (**self).on_watermark(level, used, limit)
+            }
+
+            #[inline]
+            fn after_allocate_with_id(
+                &self,
+                id: u64,
+                layout: Layout,
+                result: Result<NonNull<[u8]>, AllocError>,
+            ) {
+                (**self).after_allocate_with_id(id, layout, result)
+            }
+
+            #[inline]
+            fn after_allocate_zeroed_with_id(
+                &self,
+                id: u64,
+                layout: Layout,
+                result: Result<NonNull<[u8]>, AllocError>,
+            ) {
+                (**self).after_allocate_zeroed_with_id(id, layout, result)
+            }
+
+            #[inline]
+            fn before_deallocate_with_id(&self, id: u64, ptr: NonNull<u8>, layout: Layout) {
+                (**self).before_deallocate_with_id(id, ptr, layout)
+            }
+
+            #[inline]
+            fn after_deallocate_with_id(&self, id: u64, ptr: NonNull<u8>, layout: Layout) {
+                (**self).after_deallocate_with_id(id, ptr, layout)
+            }
+
+            #[inline]
+            fn after_grow_with_id(
+                &self,
+                id: u64,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+                result: Result<NonNull<[u8]>, AllocError>,
+            ) {
+                (**self).after_grow_with_id(id, ptr, old_layout, new_layout, result)
+            }
+
+            #[inline]
+            fn after_grow_zeroed_with_id(
+                &self,
+                id: u64,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+                result: Result<NonNull<[u8]>, AllocError>,
+            ) {
+                (**self).after_grow_zeroed_with_id(id, ptr, old_layout, new_layout, result)
+            }
+
+            #[inline]
+            fn after_shrink_with_id(
+                &self,
+                id: u64,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+                result: Result<NonNull<[u8]>, AllocError>,
+            ) {
+                (**self).after_shrink_with_id(id, ptr, old_layout, new_layout, result)
+            }
+
+            #[inline]
+            fn on_create(&self) {
+                (**self).on_create()
+            }
+
+            #[inline]
+            fn on_destroy(&self) {
+                (**self).on_destroy()
+            }
+
+            #[inline]
+            fn on_reset(&self) {
+                (**self).on_reset()
+            }
         }
     };
 }
 
+unsafe impl CallbackRef for () {}
+
 impl_alloc_stats!(&C);
 #[cfg(any(doc, feature = "alloc"))]
 impl_alloc_stats!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::boxed::Box<C>);
@@ -466,6 +753,9 @@ mod tests {
         after_shrink_in_place: Cell<u32>,
         before_owns: Cell<u32>,
         after_owns: Cell<u32>,
+        on_create: Cell<u32>,
+        on_destroy: Cell<u32>,
+        on_reset: Cell<u32>,
     }
 
     unsafe impl CallbackRef for Callback {
@@ -617,6 +907,15 @@ mod tests {
         fn after_owns(&self, _success: bool) {
             self.after_owns.set(self.after_owns.get() + 1)
         }
+        fn on_create(&self) {
+            self.on_create.set(self.on_create.get() + 1)
+        }
+        fn on_destroy(&self) {
+            self.on_destroy.set(self.on_destroy.get() + 1)
+        }
+        fn on_reset(&self) {
+            self.on_reset.set(self.on_reset.get() + 1)
+        }
     }
 
     fn test_callback(callback: impl CallbackRef) {
@@ -700,6 +999,9 @@ mod tests {
         );
         callback.before_owns();
         callback.after_owns(false);
+        callback.on_create();
+        callback.on_destroy();
+        callback.on_reset();
     }
 
     fn check_counts(callback: &Callback) {
@@ -729,6 +1031,9 @@ mod tests {
         assert_eq!(callback.after_shrink_in_place.get(), 1);
         assert_eq!(callback.before_owns.get(), 1);
         assert_eq!(callback.after_owns.get(), 1);
+        assert_eq!(callback.on_create.get(), 1);
+        assert_eq!(callback.on_destroy.get(), 1);
+        assert_eq!(callback.on_reset.get(), 1);
     }
 
     #[test]
@@ -753,6 +1058,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
     fn arc() {
         let callback = Arc::new(Callback::default());
         test_callback(callback.by_ref());
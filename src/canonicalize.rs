@@ -0,0 +1,315 @@
+use crate::{
+    describe::Bytes,
+    Allocator,
+    CallbackRef,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cmp,
+    fmt,
+    ptr::NonNull,
+};
+
+/// Adjusts layouts to a canonical form before passing them to the underlying allocator.
+///
+/// Every layout is enlarged to at least `MIN_SIZE` bytes, and then padded to a multiple of its
+/// alignment, e.g. to make every memory block large enough to be linked into a free list. The
+/// returned memory blocks are truncated to a multiple of the alignment, so every layout fitting
+/// a returned block also fits after adjusting it.
+///
+/// Adjusted allocation requests are reported to [`after_adjust_layout`]. Placing a [`Proxy`] in
+/// front of and behind `Canonicalize` tells requested and adjusted layouts apart. Use `()` as
+/// callbacks if not needed.
+///
+/// [`after_adjust_layout`]: CallbackRef::after_adjust_layout
+/// [`Proxy`]: crate::Proxy
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{CallbackRef, Canonicalize};
+/// use core::{alloc::Layout, cell::Cell};
+/// use std::alloc::{Allocator, System};
+///
+/// #[derive(Default)]
+/// struct Adjusted(Cell<usize>);
+///
+/// unsafe impl CallbackRef for Adjusted {
+///     fn after_adjust_layout(&self, requested: Layout, adjusted: Layout) {
+///         self.0
+///             .set(self.0.get() + adjusted.size() - requested.size())
+///     }
+/// }
+///
+/// let adjusted = Adjusted::default();
+/// let alloc = Canonicalize::<_, _, 8> {
+///     alloc: System,
+///     callbacks: adjusted.by_ref(),
+/// };
+///
+/// let layout = Layout::from_size_align(2, 1).unwrap();
+/// let memory = alloc.allocate(layout)?;
+/// assert!(memory.len() >= 8);
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+///
+/// let layout = Layout::from_size_align(10, 4).unwrap();
+/// let memory = alloc.allocate(layout)?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+/// assert_eq!(adjusted.0.get(), 6 + 2);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Canonicalize<A, C, const MIN_SIZE: usize> {
+    pub alloc: A,
+    pub callbacks: C,
+}
+
+impl<A, C, const MIN_SIZE: usize> Canonicalize<A, C, MIN_SIZE> {
+    /// Returns the canonical form of `layout`.
+    ///
+    /// Returns `Err` if the adjusted size overflows.
+    pub fn canonicalize(layout: Layout) -> Result<Layout, AllocError> {
+        let size = cmp::max(layout.size(), MIN_SIZE);
+        Layout::from_size_align(size, layout.align())
+            .map(|layout| layout.pad_to_align())
+            .map_err(|_| AllocError)
+    }
+
+    fn truncated(len: usize, layout: Layout) -> usize {
+        len & !(layout.align() - 1)
+    }
+
+    fn truncated_ptr(memory: NonNull<[u8]>, layout: Layout) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(
+            memory.as_non_null_ptr(),
+            Self::truncated(memory.len(), layout),
+        )
+    }
+}
+
+impl<A, C: CallbackRef, const MIN_SIZE: usize> Canonicalize<A, C, MIN_SIZE> {
+    fn adjust(&self, layout: Layout) -> Result<Layout, AllocError> {
+        let adjusted = Self::canonicalize(layout)?;
+        if adjusted != layout {
+            self.callbacks.after_adjust_layout(layout, adjusted);
+        }
+        Ok(adjusted)
+    }
+}
+
+unsafe impl<A: Allocator, C: CallbackRef, const MIN_SIZE: usize> Allocator
+    for Canonicalize<A, C, MIN_SIZE>
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let layout = self.adjust(layout)?;
+        Ok(Self::truncated_ptr(self.alloc.allocate(layout)?, layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let layout = self.adjust(layout)?;
+        Ok(Self::truncated_ptr(
+            self.alloc.allocate_zeroed(layout)?,
+            layout,
+        ))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if let Ok(layout) = Self::canonicalize(layout) {
+            self.alloc.deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let old_layout = Self::canonicalize(old_layout)?;
+        let new_layout = self.adjust(new_layout)?;
+        Ok(Self::truncated_ptr(
+            self.alloc.grow(ptr, old_layout, new_layout)?,
+            new_layout,
+        ))
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let old_layout = Self::canonicalize(old_layout)?;
+        let new_layout = self.adjust(new_layout)?;
+        Ok(Self::truncated_ptr(
+            self.alloc.grow_zeroed(ptr, old_layout, new_layout)?,
+            new_layout,
+        ))
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        let old_layout = Self::canonicalize(old_layout)?;
+        let new_layout = self.adjust(new_layout)?;
+        Ok(Self::truncated_ptr(
+            self.alloc.shrink(ptr, old_layout, new_layout)?,
+            new_layout,
+        ))
+    }
+}
+
+unsafe impl<A: ReallocateInPlace, C: CallbackRef, const MIN_SIZE: usize> ReallocateInPlace
+    for Canonicalize<A, C, MIN_SIZE>
+{
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let old_layout = Self::canonicalize(old_layout)?;
+        let new_layout = self.adjust(new_layout)?;
+        let len = self.alloc.grow_in_place(ptr, old_layout, new_layout)?;
+        Ok(Self::truncated(len, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let old_layout = Self::canonicalize(old_layout)?;
+        let new_layout = self.adjust(new_layout)?;
+        let len = self
+            .alloc
+            .grow_in_place_zeroed(ptr, old_layout, new_layout)?;
+        Ok(Self::truncated(len, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        let old_layout = Self::canonicalize(old_layout)?;
+        let new_layout = self.adjust(new_layout)?;
+        let len = self.alloc.shrink_in_place(ptr, old_layout, new_layout)?;
+        Ok(Self::truncated(len, new_layout))
+    }
+}
+
+impl<A: Owns, C, const MIN_SIZE: usize> Owns for Canonicalize<A, C, MIN_SIZE> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe, C, const MIN_SIZE: usize> Describe for Canonicalize<A, C, MIN_SIZE> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Canonicalize(min {})", Bytes(MIN_SIZE))
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, C, const MIN_SIZE: usize> Purge for Canonicalize<A, C, MIN_SIZE> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Canonicalize;
+    use crate::{helper::tracker, stats::Counter, Allocator, CallbackRef, Chunk, Proxy};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::{alloc::Layout, cell::RefCell};
+
+    #[derive(Default)]
+    struct Adjustments(RefCell<Vec<(usize, usize)>>);
+
+    unsafe impl CallbackRef for Adjustments {
+        fn after_adjust_layout(&self, requested: Layout, adjusted: Layout) {
+            self.0
+                .borrow_mut()
+                .push((requested.size(), adjusted.size()))
+        }
+    }
+
+    #[test]
+    fn adjust() {
+        let adjustments = Adjustments::default();
+        let counter = Counter::default();
+        let alloc = tracker(Canonicalize::<_, _, 8> {
+            alloc: Proxy {
+                alloc: Chunk::<_, 4>(Global),
+                callbacks: counter.by_ref(),
+            },
+            callbacks: adjustments.by_ref(),
+        });
+
+        let layout = Layout::from_size_align(6, 4).expect("Invalid layout");
+        let grown = Layout::from_size_align(16, 4).expect("Invalid layout");
+        unsafe {
+            let memory = alloc.allocate(layout).expect("Could not allocate 6 bytes");
+            assert_eq!(memory.len(), 8);
+            let memory = alloc
+                .grow(memory.as_non_null_ptr(), layout, grown)
+                .expect("Could not grow to 16 bytes");
+            let memory = alloc
+                .shrink(memory.as_non_null_ptr(), grown, Layout::new::<u8>())
+                .expect("Could not shrink to 1 byte");
+            assert_eq!(memory.len(), 8);
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u8>());
+        }
+
+        assert_eq!(*adjustments.0.borrow(), [(6, 8), (1, 8)]);
+        assert_eq!(counter.num_allocs(), 1);
+        assert_eq!(counter.num_deallocs(), 1);
+    }
+
+    #[test]
+    fn unit_callbacks() {
+        let alloc = tracker(Canonicalize::<_, _, 16> {
+            alloc: Global,
+            callbacks: (),
+        });
+        let layout = Layout::from_size_align(17, 8).expect("Invalid layout");
+        let memory = alloc.allocate(layout).expect("Could not allocate 17 bytes");
+        assert_eq!(memory.len(), 24);
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+    }
+}
@@ -1,6 +1,21 @@
-use crate::{helper::AllocInit, Owns, ReallocateInPlace};
+use crate::{
+    helper::AllocInit,
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
+    alloc::{AllocError, Layout},
+    fmt,
+    num::NonZeroUsize,
     ptr::NonNull,
 };
 
@@ -12,10 +27,10 @@ use core::{
 /// #![feature(allocator_api, slice_ptr_len)]
 ///
 /// use alloc_compose::Chunk;
-/// use std::alloc::{AllocRef, Layout, System};
+/// use std::alloc::{Allocator, Layout, System};
 ///
 /// let mut alloc = Chunk::<_, 64>(System);
-/// let ptr = alloc.alloc(Layout::new::<[u8; 16]>())?;
+/// let ptr = alloc.allocate(Layout::new::<[u8; 16]>())?;
 /// assert_eq!(ptr.len() % 64, 0);
 /// assert!(ptr.len() >= 64);
 /// # Ok::<(), core::alloc::AllocError>(())
@@ -28,9 +43,9 @@ use core::{
 /// #![feature(slice_ptr_get)]
 /// # #![feature(allocator_api, slice_ptr_len)]
 /// # use alloc_compose::Chunk;
-/// # use std::{alloc::{AllocRef, Layout, System}};
+/// # use std::{alloc::{Allocator, Layout, System}};
 /// # let mut alloc = Chunk::<_, 64>(System);
-/// # let ptr = alloc.alloc(Layout::new::<[u8; 16]>())?;
+/// # let ptr = alloc.allocate(Layout::new::<[u8; 16]>())?;
 ///
 /// let new_ptr = unsafe {
 ///     alloc.grow(
@@ -49,9 +64,9 @@ use core::{
 /// ```rust
 /// # #![feature(allocator_api, slice_ptr_len, slice_ptr_get)]
 /// # use alloc_compose::Chunk;
-/// # use std::{alloc::{AllocRef, Layout, System}};
+/// # use std::{alloc::{Allocator, Layout, System}};
 /// # let mut alloc = Chunk::<_, 64>(System);
-/// # let ptr = alloc.alloc(Layout::new::<[u8; 24]>())?;
+/// # let ptr = alloc.allocate(Layout::new::<[u8; 24]>())?;
 /// use alloc_compose::ReallocateInPlace;
 ///
 /// let len = unsafe {
@@ -66,7 +81,7 @@ use core::{
 /// assert!(len >= 64);
 /// # Ok::<(), core::alloc::AllocError>(())
 /// ```
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Chunk<A, const SIZE: usize>(pub A);
 
 mod sealed {
@@ -94,6 +109,13 @@ is_power_of_two!(16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31);
 #[cfg(target_pointer_width = "64")]
 is_power_of_two!(32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63);
 
+impl<A, const SIZE: usize> Chunk<A, SIZE> {
+    /// Creates a new chunk allocator on top of the given allocator.
+    pub const fn new(parent: A) -> Self {
+        Self(parent)
+    }
+}
+
 impl<A, const SIZE: usize> Chunk<A, SIZE>
 where
     Self: SizeIsPowerOfTwo,
@@ -108,6 +130,37 @@ where
         new_size
     }
 
+    /// Returns the layout requested from the underlying allocator for `size` bytes.
+    ///
+    /// Rounding up may exceed the size limit of `Layout` even if `size` does not.
+    fn rounded_layout(size: usize, align: usize) -> Result<Layout, AllocError> {
+        Layout::from_size_align(Self::round_up(size)?, align).map_err(|_| AllocError)
+    }
+
+    /// Returns the layout requested from the parent for a memory block of `layout`.
+    ///
+    /// Returns `None`, if rounding up the size exceeds the size limit of `Layout`.
+    pub const fn parent_layout(layout: Layout) -> Option<Layout> {
+        let rounded = match layout.size().checked_add(SIZE - 1) {
+            Some(size) => Self::round_down(size),
+            None => return None,
+        };
+        match Layout::from_size_align(rounded, layout.align()) {
+            Ok(layout) => Some(layout),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the number of bytes, which rounding up `layout.size()` to a multiple of `SIZE` adds.
+    ///
+    /// Returns `None`, if rounding up the size exceeds the size limit of `Layout`.
+    pub const fn overhead(layout: Layout) -> Option<usize> {
+        match Self::parent_layout(layout) {
+            Some(parent) => Some(parent.size() - layout.size()),
+            None => None,
+        }
+    }
+
     const fn round_down(size: usize) -> usize {
         size & !(SIZE - 1)
     }
@@ -121,10 +174,14 @@ where
         layout: Layout,
         alloc: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
     ) -> Result<NonNull<[u8]>, AllocError> {
-        let new_size = Self::round_up(layout.size())?;
-        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+        alloc(Self::rounded_layout(layout.size(), layout.align())?).map(Self::round_down_ptr_len)
+    }
 
-        alloc(new_layout).map(Self::round_down_ptr_len)
+    #[inline]
+    fn allocate_all_impl(
+        allocate_all: impl FnOnce() -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        allocate_all().map(Self::round_down_ptr_len)
     }
 
     #[inline]
@@ -138,16 +195,18 @@ where
         let old_size = old_layout.size();
         let current_size = Self::round_up_unchecked(old_size);
         let new_size = new_layout.size();
+        // The slack behind `old_size` may have been written, but the underlying allocator only
+        // zeroes behind `current_size`.
+        let ptr = NonNull::slice_from_raw_parts(old_ptr, current_size);
+        init.init_offset(ptr, old_size);
         if new_layout.align() <= old_layout.align() && new_size <= current_size {
-            let ptr = NonNull::slice_from_raw_parts(old_ptr, current_size);
-            init.init_offset(ptr, old_size);
             return Ok(ptr);
         }
 
         grow(
             old_ptr,
             Layout::from_size_align_unchecked(current_size, old_layout.align()),
-            Layout::from_size_align_unchecked(Self::round_up(new_size)?, new_layout.align()),
+            Self::rounded_layout(new_size, new_layout.align())?,
         )
         .map(Self::round_down_ptr_len)
     }
@@ -161,13 +220,13 @@ where
     ) -> Result<NonNull<[u8]>, AllocError> {
         let current_size = Self::round_up_unchecked(old_layout.size());
         let new_size = new_layout.size();
-        if new_layout.align() <= old_layout.align() && new_layout.size() > current_size - SIZE {
+        if new_layout.align() <= old_layout.align() && current_size - new_size < SIZE {
             return Ok(NonNull::slice_from_raw_parts(old_ptr, current_size));
         }
 
         shrink(
             old_ptr,
-            old_layout,
+            Layout::from_size_align_unchecked(current_size, old_layout.align()),
             Layout::from_size_align_unchecked(
                 Self::round_up_unchecked(new_size),
                 new_layout.align(),
@@ -177,16 +236,16 @@ where
     }
 }
 
-unsafe impl<A: AllocRef, const SIZE: usize> AllocRef for Chunk<A, SIZE>
+unsafe impl<A: Allocator, const SIZE: usize> Allocator for Chunk<A, SIZE>
 where
     Self: SizeIsPowerOfTwo,
 {
-    impl_alloc_ref!(0);
+    impl_allocator!(0);
 
-    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
-        crate::check_dealloc_precondition(ptr, layout);
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
 
-        self.0.dealloc(
+        self.0.deallocate(
             ptr,
             Layout::from_size_align_unchecked(
                 Self::round_up_unchecked(layout.size()),
@@ -196,12 +255,17 @@ where
     }
 }
 
-// unsafe impl<A: AllocateAll, const SIZE: usize> AllocateAll for Chunk<A, SIZE>
-// where
-//     Self: SizeIsPowerOfTwo,
-// {
-//     impl_alloc_all!(0);
-// }
+/// The capacities are rounded down to a multiple of `SIZE`, as the remainder can't be handed out
+/// as a chunk.
+///
+/// `allocate_all` drains the underlying allocator and rounds the length of the returned block down
+/// to a multiple of `SIZE`. The remainder stays unused until `deallocate_all` is called.
+unsafe impl<A: AllocateAll, const SIZE: usize> AllocateAll for Chunk<A, SIZE>
+where
+    Self: SizeIsPowerOfTwo,
+{
+    impl_alloc_all!(0);
+}
 
 unsafe impl<A, const SIZE: usize> ReallocateInPlace for Chunk<A, SIZE>
 where
@@ -217,6 +281,12 @@ where
     impl_realloc_in_place!(0);
 }
 
+impl<A: ReportUsage, const SIZE: usize> ReportUsage for Chunk<A, SIZE> {
+    fn usage(&self) -> Usage {
+        self.0.usage()
+    }
+}
+
 impl<A: Owns, const SIZE: usize> Owns for Chunk<A, SIZE>
 where
     Self: SizeIsPowerOfTwo,
@@ -224,57 +294,520 @@ where
     fn owns(&self, memory: NonNull<[u8]>) -> bool {
         self.0.owns(memory)
     }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.0.owns_ptr(ptr)
+    }
+}
+
+impl<A, const SIZE: usize> BlockSize for Chunk<A, SIZE>
+where
+    Self: SizeIsPowerOfTwo,
+{
+    #[inline]
+    fn block_size(&self) -> usize {
+        SIZE
+    }
+}
+
+impl<A: Describe, const SIZE: usize> Describe for Chunk<A, SIZE> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Chunk<{}>", SIZE)
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.0)
+    }
+}
+
+impl<A: Purge, const SIZE: usize> Purge for Chunk<A, SIZE> {
+    fn purge(&self, level: PurgeLevel) {
+        self.0.purge(level)
+    }
+}
+
+/// Allocate memory with a multiple size of a chunk size chosen at runtime.
+///
+/// Behaves like [`Chunk`], but the chunk size is passed to [`new`], e.g. when it is read from a
+/// configuration. The size is validated once and stored as shift, so rounding stays as cheap as
+/// for a constant size.
+///
+/// [`new`]: Self::new
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_len)]
+///
+/// use alloc_compose::{BlockSize, DynChunk};
+/// use core::num::NonZeroUsize;
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let size = NonZeroUsize::new(64).unwrap();
+/// let alloc = DynChunk::new(System, size);
+/// assert_eq!(alloc.block_size(), 64);
+///
+/// let ptr = alloc.allocate(Layout::new::<[u8; 80]>())?;
+/// assert_eq!(ptr.len(), 128);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DynChunk<A> {
+    alloc: A,
+    shift: u32,
+}
+
+impl<A> DynChunk<A> {
+    /// Rounds requests to `alloc` up to a multiple of `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not a power of two. See [`try_new`] for a fallible variant.
+    ///
+    /// [`try_new`]: Self::try_new
+    #[track_caller]
+    pub fn new(alloc: A, size: NonZeroUsize) -> Self {
+        Self::try_new(alloc, size).expect("chunk size must be a power of two")
+    }
+
+    /// Rounds requests to `alloc` up to a multiple of `size`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::LayoutUnsupported`], if `size` is not a power of two.
+    #[track_caller]
+    pub fn try_new(alloc: A, size: NonZeroUsize) -> Result<Self, ComposeError> {
+        if size.is_power_of_two() {
+            Ok(Self {
+                alloc,
+                shift: size.trailing_zeros(),
+            })
+        } else {
+            Err(ComposeError::layout_unsupported("DynChunk"))
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn get_ref(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the underlying allocator.
+    pub fn into_inner(self) -> A {
+        self.alloc
+    }
+
+    /// Returns the chunk size.
+    #[inline]
+    pub fn size(&self) -> usize {
+        1 << self.shift
+    }
+
+    #[inline]
+    fn mask(&self) -> usize {
+        self.size() - 1
+    }
+
+    fn round_up(&self, size: usize) -> Result<usize, AllocError> {
+        Ok(size.checked_add(self.mask()).ok_or(AllocError)? & !self.mask())
+    }
+
+    unsafe fn round_up_unchecked(&self, size: usize) -> usize {
+        let new_size = size.wrapping_add(self.mask()) & !self.mask();
+        debug_assert_eq!(new_size, self.round_up(size).unwrap());
+        new_size
+    }
+
+    fn rounded_layout(&self, size: usize, align: usize) -> Result<Layout, AllocError> {
+        Layout::from_size_align(self.round_up(size)?, align).map_err(|_| AllocError)
+    }
+
+    #[inline]
+    fn round_down(&self, size: usize) -> usize {
+        size >> self.shift << self.shift
+    }
+
+    #[inline]
+    fn round_down_ptr_len(&self, ptr: NonNull<[u8]>) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(ptr.as_non_null_ptr(), self.round_down(ptr.len()))
+    }
+
+    #[inline]
+    fn alloc_impl(
+        &self,
+        layout: Layout,
+        alloc: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        alloc(self.rounded_layout(layout.size(), layout.align())?)
+            .map(|ptr| self.round_down_ptr_len(ptr))
+    }
+
+    #[inline]
+    unsafe fn grow_impl(
+        &self,
+        old_ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+        grow: impl FnOnce(NonNull<u8>, Layout, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let old_size = old_layout.size();
+        let current_size = self.round_up_unchecked(old_size);
+        let new_size = new_layout.size();
+        // The slack behind `old_size` may have been written, but the underlying allocator only
+        // zeroes behind `current_size`.
+        let ptr = NonNull::slice_from_raw_parts(old_ptr, current_size);
+        init.init_offset(ptr, old_size);
+        if new_layout.align() <= old_layout.align() && new_size <= current_size {
+            return Ok(ptr);
+        }
+
+        grow(
+            old_ptr,
+            Layout::from_size_align_unchecked(current_size, old_layout.align()),
+            self.rounded_layout(new_size, new_layout.align())?,
+        )
+        .map(|ptr| self.round_down_ptr_len(ptr))
+    }
+
+    #[inline]
+    unsafe fn shrink_impl(
+        &self,
+        old_ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        shrink: impl FnOnce(NonNull<u8>, Layout, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let current_size = self.round_up_unchecked(old_layout.size());
+        let new_size = new_layout.size();
+        if new_layout.align() <= old_layout.align() && current_size - new_size < self.size() {
+            return Ok(NonNull::slice_from_raw_parts(old_ptr, current_size));
+        }
+
+        shrink(
+            old_ptr,
+            Layout::from_size_align_unchecked(current_size, old_layout.align()),
+            Layout::from_size_align_unchecked(
+                self.round_up_unchecked(new_size),
+                new_layout.align(),
+            ),
+        )
+        .map(|ptr| self.round_down_ptr_len(ptr))
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for DynChunk<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc_impl(layout, |l| self.alloc.allocate(l))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc_impl(layout, |l| self.alloc.allocate_zeroed(l))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+
+        self.alloc.deallocate(
+            ptr,
+            Layout::from_size_align_unchecked(
+                self.round_up_unchecked(layout.size()),
+                layout.align(),
+            ),
+        )
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            AllocInit::Uninitialized,
+            |ptr, old_layout, new_layout| self.alloc.grow(ptr, old_layout, new_layout),
+        )
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            AllocInit::Zeroed,
+            |ptr, old_layout, new_layout| self.alloc.grow_zeroed(ptr, old_layout, new_layout),
+        )
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.shrink_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            |ptr, old_layout, new_layout| self.alloc.shrink(ptr, old_layout, new_layout),
+        )
+    }
+}
+
+/// Like for [`Chunk`], the capacities and the block returned by `allocate_all` are rounded down to
+/// a multiple of the chunk size.
+unsafe impl<A: AllocateAll> AllocateAll for DynChunk<A> {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc
+            .allocate_all()
+            .map(|ptr| self.round_down_ptr_len(ptr))
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc
+            .allocate_all_zeroed()
+            .map(|ptr| self.round_down_ptr_len(ptr))
+    }
+
+    fn deallocate_all(&self) {
+        self.alloc.deallocate_all()
+    }
+
+    fn capacity(&self) -> usize {
+        self.round_down(self.alloc.capacity())
+    }
+
+    fn capacity_left(&self) -> usize {
+        self.round_down(self.alloc.capacity_left())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        match self.round_up(additional) {
+            Ok(additional) => self.alloc.reserve(additional),
+            Err(_) => Err(ComposeError::exhausted(core::any::type_name::<Self>())),
+        }
+    }
+}
+
+unsafe impl<A> ReallocateInPlace for DynChunk<A> {
+    default unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            AllocInit::Uninitialized,
+            |_, _, _| Err(AllocError),
+        )
+        .map(NonNull::len)
+    }
+
+    default unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Zeroed, |_, _, _| {
+            Err(AllocError)
+        })
+        .map(NonNull::len)
+    }
+
+    default unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.shrink_impl(ptr, old_layout, new_layout, |_, _, _| Err(AllocError))
+            .map(NonNull::len)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace> ReallocateInPlace for DynChunk<A> {
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            AllocInit::Uninitialized,
+            |ptr, old_layout, new_layout| {
+                self.alloc
+                    .grow_in_place(ptr, old_layout, new_layout)
+                    .map(|len| NonNull::slice_from_raw_parts(ptr, len))
+            },
+        )
+        .map(NonNull::len)
+    }
+
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            AllocInit::Zeroed,
+            |ptr, old_layout, new_layout| {
+                self.alloc
+                    .grow_in_place_zeroed(ptr, old_layout, new_layout)
+                    .map(|len| NonNull::slice_from_raw_parts(ptr, len))
+            },
+        )
+        .map(NonNull::len)
+    }
+
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.shrink_impl(
+            ptr,
+            old_layout,
+            new_layout,
+            |ptr, old_layout, new_layout| {
+                self.alloc
+                    .shrink_in_place(ptr, old_layout, new_layout)
+                    .map(|len| NonNull::slice_from_raw_parts(ptr, len))
+            },
+        )
+        .map(NonNull::len)
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for DynChunk<A> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns> Owns for DynChunk<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A> BlockSize for DynChunk<A> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.size()
+    }
+}
+
+impl<A: Describe> Describe for DynChunk<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynChunk({})", self.size())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge> Purge for DynChunk<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Chunk;
-    use crate::{helper::tracker, ReallocateInPlace};
+    use super::{Chunk, DynChunk};
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        AllocateAll,
+        Allocator,
+        BlockSize,
+        ComposeError,
+        ReallocateInPlace,
+    };
     use alloc::alloc::Global;
-    use core::alloc::{AllocRef, Layout};
+    use core::{alloc::Layout, mem::MaybeUninit, num::NonZeroUsize};
 
     #[test]
-    fn alloc() {
+    fn allocate() {
         let alloc = Chunk::<_, 64>(tracker(Global));
         let memory = alloc
-            .alloc(Layout::new::<[u8; 2]>())
+            .allocate(Layout::new::<[u8; 2]>())
             .expect("Could not allocate 64 bytes");
-        assert_eq!(memory.len() % 64, 0);
+        assert_eq!(memory.len() % alloc.block_size(), 0);
         assert!(memory.len() >= 64);
 
         unsafe {
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<u8>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u8>());
         }
     }
 
     #[test]
-    fn dealloc() {
+    fn deallocate() {
         let alloc = Chunk::<_, 64>(tracker(Global));
 
         unsafe {
             let memory = alloc
-                .alloc(Layout::new::<[u8; 4]>())
+                .allocate(Layout::new::<[u8; 4]>())
                 .expect("Could not allocate 4 bytes");
             assert_eq!(memory.len() % 64, 0);
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>());
 
             let memory = alloc
-                .alloc(Layout::new::<[u8; 8]>())
+                .allocate(Layout::new::<[u8; 8]>())
                 .expect("Could not allocate 8 bytes");
             assert_eq!(memory.len() % 64, 0);
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 8]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 8]>());
 
             let memory = alloc
-                .alloc(Layout::new::<[u8; 32]>())
+                .allocate(Layout::new::<[u8; 32]>())
                 .expect("Could not allocate 32 bytes");
             assert_eq!(memory.len() % 64, 0);
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 32]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 32]>());
 
             let memory = alloc
-                .alloc(Layout::new::<[u8; 64]>())
+                .allocate(Layout::new::<[u8; 64]>())
                 .expect("Could not allocate 64 bytes");
             assert_eq!(memory.len() % 64, 0);
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
         }
     }
 
@@ -283,7 +816,7 @@ mod tests {
         let alloc = Chunk::<_, 64>(tracker(Global));
 
         let memory = alloc
-            .alloc(Layout::new::<[u8; 4]>())
+            .allocate(Layout::new::<[u8; 4]>())
             .expect("Could not allocate 4 bytes");
         assert_eq!(memory.len() % 64, 0);
 
@@ -324,7 +857,7 @@ mod tests {
                 )
                 .expect("Could not grow to 65 bytes");
 
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 65]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 65]>());
         }
     }
 
@@ -333,7 +866,7 @@ mod tests {
         let alloc = Chunk::<_, 64>(tracker(Global));
 
         let memory = alloc
-            .alloc(Layout::new::<[u8; 128]>())
+            .allocate(Layout::new::<[u8; 128]>())
             .expect("Could not allocate 128 bytes");
         assert_eq!(memory.len() % 64, 0);
 
@@ -374,7 +907,153 @@ mod tests {
                 )
                 .expect("Could not shrink to 64 bytes");
 
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+        }
+    }
+
+    #[test]
+    fn allocate_all() {
+        let mut data = [MaybeUninit::uninit(); 50];
+        let alloc = tracker(Chunk::<_, 16>(Region::<Up>::with_direction(&mut data)));
+        assert_eq!(alloc.capacity(), 48);
+        assert_eq!(alloc.capacity_left(), 48);
+        assert!(alloc.is_empty());
+
+        alloc
+            .allocate(Layout::new::<[u8; 5]>())
+            .expect("Could not allocate 5 bytes");
+        assert_eq!(alloc.capacity_left(), 32);
+
+        let memory = alloc.allocate_all().expect("Could not allocate all");
+        assert_eq!(memory.len(), 32);
+        assert!(alloc.is_full());
+        assert!(!alloc.is_empty());
+
+        alloc.deallocate_all();
+        assert!(alloc.is_empty());
+        assert_eq!(alloc.capacity_left(), 48);
+    }
+
+    #[test]
+    fn extreme_layouts() {
+        let mut data = [MaybeUninit::uninit(); 128];
+        let alloc = tracker(Chunk::<_, 64>(Region::new(&mut data)));
+
+        for &(size, align) in &[
+            (isize::MAX as usize, 1),
+            (isize::MAX as usize - 63, 64),
+            (1, isize::MAX as usize / 2 + 1),
+        ] {
+            let layout = Layout::from_size_align(size, align).expect("Invalid layout");
+            alloc
+                .allocate(layout)
+                .expect_err("Could allocate an extreme layout");
+        }
+
+        unsafe {
+            let memory = alloc
+                .allocate(Layout::new::<[u8; 0]>())
+                .expect("Could not allocate 0 bytes");
+            assert_eq!(memory.len(), 0);
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 0]>(),
+                    Layout::new::<[u8; 0]>(),
+                )
+                .expect("Could not shrink 0 bytes");
+            alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 0]>(),
+                    Layout::from_size_align(isize::MAX as usize - 63, 1).expect("Invalid layout"),
+                )
+                .expect_err("Could grow to an extreme layout");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 0]>());
+        }
+
+        let mut data = [MaybeUninit::uninit(); 128];
+        let alloc = Chunk::<_, { isize::MAX as usize + 1 }>(Region::new(&mut data));
+        alloc
+            .allocate(Layout::new::<u8>())
+            .expect_err("Could allocate a chunk larger than `isize::MAX`");
+    }
+
+    #[test]
+    fn dyn_chunk() {
+        let size = NonZeroUsize::new(64).unwrap();
+        let alloc = DynChunk::new(tracker(Global), size);
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 4]>())
+            .expect("Could not allocate 4 bytes");
+        assert_eq!(memory.len(), 64);
+
+        unsafe {
+            let len = alloc
+                .grow_in_place(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 4]>(),
+                    Layout::new::<[u8; 64]>(),
+                )
+                .expect("Could not grow to 64 bytes");
+            assert_eq!(len, 64);
+            alloc
+                .grow_in_place(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 64]>(),
+                    Layout::new::<[u8; 65]>(),
+                )
+                .expect_err("Could grow to 65 bytes in place");
+
+            let memory = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 64]>(),
+                    Layout::new::<[u8; 65]>(),
+                )
+                .expect("Could not grow to 65 bytes");
+            assert_eq!(memory.len(), 128);
+
+            let len = alloc
+                .shrink_in_place(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 128]>(),
+                    Layout::new::<[u8; 100]>(),
+                )
+                .expect("Could not shrink to 100 bytes");
+            assert_eq!(len, 128);
+
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 100]>(),
+                    Layout::new::<[u8; 64]>(),
+                )
+                .expect("Could not shrink to 64 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
         }
+
+        let mut data = [MaybeUninit::uninit(); 50];
+        let size = NonZeroUsize::new(16).unwrap();
+        let alloc = tracker(DynChunk::new(Region::<Up>::with_direction(&mut data), size));
+        assert_eq!(alloc.capacity(), 48);
+        alloc
+            .allocate(Layout::new::<[u8; 5]>())
+            .expect("Could not allocate 5 bytes");
+        let memory = alloc.allocate_all().expect("Could not allocate all");
+        assert_eq!(memory.len(), 32);
+        alloc.deallocate_all();
+        assert_eq!(alloc.capacity_left(), 48);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be a power of two")]
+    fn dyn_chunk_invalid_size() {
+        let error = DynChunk::try_new(Global, NonZeroUsize::new(48).unwrap()).unwrap_err();
+        assert_eq!(error, ComposeError::LayoutUnsupported(error.context()));
+        assert_eq!(error.context().layer, "DynChunk");
+        assert_eq!(error.context().location.line(), line!() - 3);
+        DynChunk::new(Global, NonZeroUsize::new(48).unwrap());
     }
 }
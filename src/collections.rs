@@ -0,0 +1,285 @@
+//! Helpers for using allocators with the collections of the `alloc` crate.
+//!
+//! [`vec_in!`] and [`vec_in_region!`] build a `Vec` like `vec!`, but with a custom allocator.
+//! [`boxed_in`] places a value into a `Box` and reports allocation failures instead of aborting.
+//! [`AllocatorExt`] provides the same as methods on every allocator, which borrow it.
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{collections::boxed_in, region::Region, vec_in, vec_in_region};
+//! use core::mem::MaybeUninit;
+//!
+//! let mut data = [MaybeUninit::uninit(); 64];
+//! let region = Region::new(&mut data);
+//!
+//! let vec = vec_in![&region; 1, 2, 3];
+//! assert_eq!(vec, [1, 2, 3]);
+//!
+//! let boxed = boxed_in(&region, [0_u8; 8])?;
+//! assert_eq!(*boxed, [0; 8]);
+//!
+//! vec_in_region!(let mut stack = [10_u32, 20]; 32);
+//! stack.push(30);
+//! assert_eq!(stack, [10, 20, 30]);
+//! # Ok::<(), core::alloc::AllocError>(())
+//! ```
+//!
+//! [`vec_in!`]: crate::vec_in
+//! [`vec_in_region!`]: crate::vec_in_region
+
+use crate::Allocator;
+use alloc::boxed::Box;
+use core::{
+    alloc::{AllocError, Layout},
+    mem,
+    ptr::NonNull,
+};
+
+#[doc(hidden)]
+pub use alloc::vec::Vec;
+
+/// Creates collections, which borrow the allocator.
+///
+/// This is implemented for every allocator, so placing values into a [`Region`] or any other
+/// composition neither requires `unsafe` code nor a [`Layout`]. Like [`boxed_in`], allocation
+/// failures are returned as errors.
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{collections::AllocatorExt, region::Region};
+/// use core::mem::MaybeUninit;
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let region = Region::new(&mut data);
+///
+/// let boxed = region.boxed(42_u64)?;
+/// let mut vec = region.vec_with_capacity::<u32>(4)?;
+/// vec.extend(&[1, 2, 3, 4]);
+/// assert_eq!(*boxed, 42);
+/// assert_eq!(vec, [1, 2, 3, 4]);
+///
+/// assert!(region.vec_with_capacity::<u64>(16).is_err());
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+///
+/// [`Region`]: crate::region::Region
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+pub trait AllocatorExt: Allocator {
+    /// Moves `value` into a `Box` allocated by this allocator.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the allocator fails to allocate the `Box`.
+    fn boxed<T>(&self, value: T) -> Result<Box<T, &Self>, AllocError> {
+        boxed_in(self, value)
+    }
+
+    /// Creates an empty `Vec`, which allocates from this allocator once elements are pushed.
+    fn vec<T>(&self) -> Vec<T, &Self> {
+        Vec::new_in(self)
+    }
+
+    /// Creates an empty `Vec` with space for at least `capacity` elements allocated by this
+    /// allocator.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the capacity overflows or the allocator fails to allocate it.
+    fn vec_with_capacity<T>(&self, capacity: usize) -> Result<Vec<T, &Self>, AllocError> {
+        let mut vec = Vec::new_in(self);
+        vec.try_reserve_exact(capacity).map_err(|_| AllocError)?;
+        Ok(vec)
+    }
+}
+
+impl<A: Allocator + ?Sized> AllocatorExt for A {}
+
+/// Moves `value` into a `Box` allocated by `alloc`.
+///
+/// In contrast to `Box::new_in`, an allocation failure is returned as an error.
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+pub fn boxed_in<T, A: Allocator>(alloc: A, value: T) -> Result<Box<T, A>, AllocError> {
+    let ptr: NonNull<T> = if mem::size_of::<T>() == 0 {
+        NonNull::dangling()
+    } else {
+        alloc.allocate(Layout::new::<T>())?.as_non_null_ptr().cast()
+    };
+    unsafe {
+        ptr.as_ptr().write(value);
+        Ok(Box::from_raw_in(ptr.as_ptr(), alloc))
+    }
+}
+
+/// Creates a `Vec` in an allocator containing the arguments.
+///
+/// `vec_in!` accepts the same arguments as `vec!`, prefixed by the allocator:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::vec_in;
+/// use std::alloc::System;
+///
+/// assert_eq!(vec_in![System; 1, 2, 3], [1, 2, 3]);
+/// assert_eq!(vec_in![System; 0; 4], [0, 0, 0, 0]);
+/// ```
+#[macro_export]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+macro_rules! vec_in {
+    ($alloc:expr; $elem:expr; $n:expr) => {{
+        let n = $n;
+        let mut vec = $crate::collections::Vec::with_capacity_in(n, $alloc);
+        vec.resize(n, $elem);
+        vec
+    }};
+    ($alloc:expr; $($x:expr),* $(,)?) => {{
+        let mut vec = $crate::collections::Vec::new_in($alloc);
+        $(vec.push($x);)*
+        vec
+    }};
+}
+
+/// Declares a `Vec` in a [`Region`] over a stack buffer of the given number of bytes.
+///
+/// The buffer lives in the scope of the macro invocation, so the `Vec` may be used until the end
+/// of that scope. The region bumps [`Up`], so the `Vec` can grow in place until the buffer is
+/// exhausted.
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::vec_in_region;
+///
+/// vec_in_region!(let vec = ['a', 'b']; 16);
+/// assert_eq!(vec, ['a', 'b']);
+/// ```
+///
+/// [`Region`]: crate::region::Region
+/// [`Up`]: crate::region::Up
+#[macro_export]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+macro_rules! vec_in_region {
+    (let mut $name:ident = [$($x:expr),* $(,)?]; $bytes:expr) => {
+        let mut data = [::core::mem::MaybeUninit::<u8>::uninit(); $bytes];
+        let mut $name = $crate::vec_in![
+            $crate::region::Region::<$crate::region::Up>::with_direction(&mut data);
+            $($x),*
+        ];
+    };
+    (let $name:ident = [$($x:expr),* $(,)?]; $bytes:expr) => {
+        let mut data = [::core::mem::MaybeUninit::<u8>::uninit(); $bytes];
+        let $name = $crate::vec_in![
+            $crate::region::Region::<$crate::region::Up>::with_direction(&mut data);
+            $($x),*
+        ];
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boxed_in, AllocatorExt};
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        stats::Counter,
+        Affix,
+        Allocator,
+        CallbackRef,
+        Chunk,
+        DeferredFree,
+        Fallback,
+        Null,
+        PatternCheck,
+        PoisonOnMove,
+        Proxy,
+        Segregate,
+    };
+    use alloc::{alloc::Global, string::String};
+    use core::mem::MaybeUninit;
+
+    /// Exercises `grow` and `shrink` through collections.
+    ///
+    /// Only the most recent memory block is resized, so regions bumping upwards don't have to
+    /// move memory.
+    fn exercise<A: Allocator>(alloc: &A) {
+        let mut vec = vec_in![alloc; 0_u32; 3];
+        for i in 0..100 {
+            vec.push(i);
+        }
+        assert_eq!(vec.len(), 103);
+        assert_eq!(vec[3..6], [0, 1, 2]);
+        vec.truncate(10);
+        vec.shrink_to_fit();
+        assert_eq!(vec[9], 6);
+        vec.extend(&[7; 20]);
+        assert_eq!(vec.iter().sum::<u32>(), 21 + 7 * 20);
+
+        let mut bytes = vec_in![alloc; 1_u8, 2, 3];
+        bytes.reserve_exact(61);
+        bytes.resize(64, 4);
+        bytes.truncate(2);
+        bytes.shrink_to_fit();
+        assert_eq!(bytes, [1, 2]);
+
+        let boxed = boxed_in(alloc, String::from("boxed")).expect("Could not allocate a box");
+        assert_eq!(*boxed, "boxed");
+        let boxed = boxed_in(alloc, ()).expect("Could not allocate a zero-sized box");
+        assert_eq!(*boxed, ());
+        let boxed = vec_in![alloc; 1_u64, 2, 3].into_boxed_slice();
+        assert_eq!(*boxed, [1, 2, 3]);
+
+        let boxed = alloc.boxed([1_u16; 5]).expect("Could not allocate a box");
+        assert_eq!(*boxed, [1; 5]);
+        let mut vec = alloc
+            .vec_with_capacity(8)
+            .expect("Could not allocate 8 elements");
+        assert!(vec.capacity() >= 8);
+        vec.extend(0..8_u8);
+        let mut empty = alloc.vec();
+        empty.push('a');
+        assert_eq!(empty, ['a']);
+        assert!(alloc.vec_with_capacity::<u64>(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn compositions() {
+        exercise(&tracker(Global));
+
+        let mut data = [MaybeUninit::uninit(); 4096];
+        exercise(&tracker(Region::<Up>::with_direction(&mut data)));
+
+        let mut data = [MaybeUninit::uninit(); 4096];
+        exercise(&tracker(Chunk::<_, 64>(Region::<Up>::with_direction(
+            &mut data,
+        ))));
+
+        let mut data = [MaybeUninit::uninit(); 128];
+        exercise(&tracker(Fallback {
+            primary: Region::<Up>::with_direction(&mut data),
+            secondary: Global,
+        }));
+
+        exercise(&tracker(Segregate::<_, _, 64> {
+            small: Chunk::<_, 16>(Global),
+            large: Global,
+        }));
+
+        exercise(&tracker(Affix::<_, u64, [u8; 3]>::new(Global)));
+
+        let counter = Counter::default();
+        exercise(&tracker(Proxy {
+            alloc: PoisonOnMove(Global),
+            callbacks: counter.by_ref(),
+        }));
+        assert_eq!(counter.num_allocs(), counter.num_deallocs());
+
+        exercise(&tracker(PatternCheck::new(Chunk::<_, 32>(Global))));
+        exercise(&tracker(DeferredFree::<_, 4>::new(Global)));
+        exercise(&Fallback {
+            primary: Null,
+            secondary: tracker(Global),
+        });
+    }
+}
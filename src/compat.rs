@@ -0,0 +1,251 @@
+//! Support for nightlies from before `AllocRef` was renamed to `Allocator`.
+//!
+//! The crate is written against [`Allocator`]. When the build script detects a nightly, which
+//! only provides `AllocRef`, `cfg(alloc_ref)` is set and `Allocator` is a trait of this crate
+//! with the same methods as the renamed trait. [`Global`] and [`System`] implement it directly,
+//! other allocators can be converted in both directions by wrapping them in [`Compat`].
+//!
+//! [`Allocator`]: core::alloc::Allocator
+//! [`Global`]: alloc::alloc::Global
+//! [`System`]: std::alloc::System
+
+#[cfg(not(alloc_ref))]
+pub use core::alloc::Allocator;
+
+#[cfg(alloc_ref)]
+pub use self::alloc_ref::{Allocator, Compat};
+
+#[cfg(alloc_ref)]
+mod alloc_ref {
+    use core::{
+        alloc::{AllocError, AllocRef, Layout},
+        ptr::{self, NonNull},
+    };
+
+    /// The allocator trait of nightlies from after `AllocRef` was renamed.
+    ///
+    /// # Safety
+    ///
+    /// See `AllocRef`.
+    pub unsafe trait Allocator {
+        /// Attempts to allocate a block of memory.
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError>;
+
+        /// Behaves like `allocate`, but also ensures that the returned memory is zero-initialized.
+        fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let memory = self.allocate(layout)?;
+            unsafe { memory.as_mut_ptr().write_bytes(0, memory.len()) }
+            Ok(memory)
+        }
+
+        /// Deallocates the memory referenced by `ptr`.
+        ///
+        /// # Safety
+        ///
+        /// See `AllocRef::dealloc`.
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+        /// Attempts to extend the memory block.
+        ///
+        /// # Safety
+        ///
+        /// See `AllocRef::grow`.
+        unsafe fn grow(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let new_memory = self.allocate(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_memory.as_mut_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+            Ok(new_memory)
+        }
+
+        /// Behaves like `grow`, but also ensures that the new contents are set to zero.
+        ///
+        /// # Safety
+        ///
+        /// See `AllocRef::grow_zeroed`.
+        unsafe fn grow_zeroed(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let new_memory = self.allocate_zeroed(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_memory.as_mut_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+            Ok(new_memory)
+        }
+
+        /// Attempts to shrink the memory block.
+        ///
+        /// # Safety
+        ///
+        /// See `AllocRef::shrink`.
+        unsafe fn shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let new_memory = self.allocate(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_memory.as_mut_ptr(), new_layout.size());
+            self.deallocate(ptr, old_layout);
+            Ok(new_memory)
+        }
+
+        /// Creates a "by reference" adapter for this instance of `Allocator`.
+        fn by_ref(&self) -> &Self
+        where
+            Self: Sized,
+        {
+            self
+        }
+    }
+
+    /// Implements `Allocator` for an `AllocRef` and `AllocRef` for an `Allocator`.
+    ///
+    /// Allocators of this crate have to be wrapped to be used in the collections of `alloc`, and
+    /// `AllocRef`s of other crates have to be wrapped to be used in this crate.
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Compat<A>(pub A);
+
+    macro_rules! forward {
+        (
+            $trait:ident;
+            $allocate:ident,
+            $allocate_zeroed:ident,
+            $deallocate:ident;
+            $alloc:ident,
+            $alloc_zeroed:ident,
+            $dealloc:ident
+        ) => {
+            fn $allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                $trait::$alloc(self.get(), layout)
+            }
+
+            fn $allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                $trait::$alloc_zeroed(self.get(), layout)
+            }
+
+            unsafe fn $deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                $trait::$dealloc(self.get(), ptr, layout)
+            }
+
+            unsafe fn grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                $trait::grow(self.get(), ptr, old_layout, new_layout)
+            }
+
+            unsafe fn grow_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                $trait::grow_zeroed(self.get(), ptr, old_layout, new_layout)
+            }
+
+            unsafe fn shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                $trait::shrink(self.get(), ptr, old_layout, new_layout)
+            }
+        };
+    }
+
+    macro_rules! from_alloc_ref {
+        () => {
+            forward!(
+                AllocRef;
+                allocate, allocate_zeroed, deallocate;
+                alloc, alloc_zeroed, dealloc
+            );
+        };
+    }
+
+    macro_rules! from_allocator {
+        () => {
+            forward!(
+                Allocator;
+                alloc, alloc_zeroed, dealloc;
+                allocate, allocate_zeroed, deallocate
+            );
+        };
+    }
+
+    /// Returns the wrapped allocator.
+    trait Get {
+        type Target: ?Sized;
+
+        fn get(&self) -> &Self::Target;
+    }
+
+    impl<A: ?Sized> Get for &A {
+        type Target = A;
+
+        fn get(&self) -> &A {
+            self
+        }
+    }
+
+    impl<A> Get for Compat<A> {
+        type Target = A;
+
+        fn get(&self) -> &A {
+            &self.0
+        }
+    }
+
+    unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+        forward!(
+            Allocator;
+            allocate, allocate_zeroed, deallocate;
+            allocate, allocate_zeroed, deallocate
+        );
+    }
+
+    unsafe impl<A: AllocRef> Allocator for Compat<A> {
+        from_alloc_ref!();
+    }
+
+    unsafe impl<A: Allocator> AllocRef for Compat<A> {
+        from_allocator!();
+    }
+
+    macro_rules! impl_allocator {
+        ($(#[$meta:meta])* $ty:ty) => {
+            $(#[$meta])*
+            impl Get for $ty {
+                type Target = Self;
+
+                fn get(&self) -> &Self {
+                    self
+                }
+            }
+
+            $(#[$meta])*
+            unsafe impl Allocator for $ty {
+                from_alloc_ref!();
+            }
+        };
+    }
+
+    impl_allocator!(
+        #[cfg(any(feature = "alloc", test))]
+        alloc::alloc::Global
+    );
+    impl_allocator!(
+        #[cfg(feature = "std")]
+        std::alloc::System
+    );
+}
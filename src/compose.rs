@@ -0,0 +1,267 @@
+use crate::{region::Region, Affix, Chunk, Fallback, Generational, Guarded, Proxy, Segregate};
+use core::mem::MaybeUninit;
+
+/// A builder for allocator compositions.
+///
+/// Deeply nested compositions are hard to read when written as struct literals, as the
+/// innermost allocator comes first. `Compose` starts with the innermost allocator and wraps it
+/// layer by layer, so the code reads in the same order, the allocator is composed. No allocator
+/// is hidden behind a trait object: [`build`] returns the fully nested type.
+///
+/// [`build`]: Self::build
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{stats, AllocateAll, CallbackRef, Compose};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+/// use std::alloc::System;
+///
+/// let counter = stats::Counter::default();
+/// let mut data = [MaybeUninit::uninit(); 256];
+/// let alloc = Compose::region(&mut data)
+///     .chunked::<64>()
+///     .with_fallback(System)
+///     .with_stats(counter.by_ref())
+///     .build();
+///
+/// alloc.allocate(Layout::new::<[u8; 16]>())?;
+/// alloc.allocate(Layout::new::<[u8; 512]>())?;
+/// assert_eq!(counter.num_allocs(), 2);
+/// assert_eq!(alloc.alloc.primary.0.capacity_left(), 192);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+///
+/// This is equivalent to
+///
+/// ```rust
+/// # #![feature(allocator_api)]
+/// # use alloc_compose::{stats, CallbackRef};
+/// # use core::mem::MaybeUninit;
+/// # use std::alloc::System;
+/// # let counter = stats::Counter::default();
+/// # let mut data = [MaybeUninit::uninit(); 256];
+/// use alloc_compose::{region::Region, Chunk, Fallback, Proxy};
+///
+/// let alloc = Proxy {
+///     alloc: Fallback {
+///         primary: Chunk::<_, 64>(Region::new(&mut data)),
+///         secondary: System,
+///     },
+///     callbacks: counter.by_ref(),
+/// };
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Compose<A>(A);
+
+impl<'mem> Compose<Region<'mem>> {
+    /// Starts a composition with a [`Region`] over `memory`.
+    #[inline]
+    pub fn region(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        Self(Region::new(memory))
+    }
+}
+
+impl<A> Compose<A> {
+    /// Starts a composition with `alloc` as innermost allocator.
+    #[inline]
+    pub const fn new(alloc: A) -> Self {
+        Self(alloc)
+    }
+
+    /// Rounds up all requests to a multiple of `SIZE`.
+    ///
+    /// See [`Chunk`] for details.
+    #[inline]
+    pub fn chunked<const SIZE: usize>(self) -> Compose<Chunk<A, SIZE>> {
+        Compose(Chunk(self.0))
+    }
+
+    /// Uses `secondary`, when the current composition fails to allocate.
+    ///
+    /// See [`Fallback`] for details.
+    #[inline]
+    pub fn with_fallback<S>(self, secondary: S) -> Compose<Fallback<A, S>> {
+        Compose(Fallback {
+            primary: self.0,
+            secondary,
+        })
+    }
+
+    /// Serves requests larger than `THRESHOLD` bytes from `large`.
+    ///
+    /// See [`Segregate`] for details.
+    #[inline]
+    pub fn with_large<L, const THRESHOLD: usize>(
+        self,
+        large: L,
+    ) -> Compose<Segregate<A, L, THRESHOLD>> {
+        Compose(Segregate {
+            small: self.0,
+            large,
+        })
+    }
+
+    /// Calls `callbacks` on every operation, e.g. to collect statistics.
+    ///
+    /// See [`Proxy`] and the [`stats`] module for details.
+    ///
+    /// [`stats`]: crate::stats
+    #[inline]
+    pub fn with_stats<C>(self, callbacks: C) -> Compose<Proxy<A, C>> {
+        Compose(Proxy {
+            alloc: self.0,
+            callbacks,
+        })
+    }
+
+    /// Asks `policy`, before allocating memory.
+    ///
+    /// See [`Guarded`] for details.
+    #[inline]
+    pub fn with_policy<P>(self, policy: P) -> Compose<Guarded<A, P>> {
+        Compose(Guarded {
+            alloc: self.0,
+            policy,
+        })
+    }
+
+    /// Stores a `Prefix` before and a `Suffix` after every memory block.
+    ///
+    /// See [`Affix`] for details.
+    #[inline]
+    pub fn with_affix<Prefix, Suffix>(self) -> Compose<Affix<A, Prefix, Suffix>> {
+        Compose(Affix::new(self.0))
+    }
+
+    /// Tracks a generation, which is advanced whenever all memory is deallocated.
+    ///
+    /// See [`Generational`] for details.
+    #[inline]
+    pub fn generational(self) -> Compose<Generational<A>> {
+        Compose(Generational::new(self.0))
+    }
+
+    /// Adds [`Owns`] by remembering every allocated memory block.
+    ///
+    /// See [`WeakOwns`] for details.
+    ///
+    /// [`Owns`]: crate::Owns
+    /// [`WeakOwns`]: crate::WeakOwns
+    #[cfg(any(doc, feature = "alloc"))]
+    #[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+    #[inline]
+    pub fn with_weak_owns(self) -> Compose<crate::WeakOwns<A>> {
+        Compose(crate::WeakOwns::new(self.0))
+    }
+
+    /// Returns the composed allocator.
+    #[inline]
+    pub fn build(self) -> A {
+        self.0
+    }
+}
+
+/// Asserts at compile time, that a composed allocator implements a set of traits.
+///
+/// Nesting allocators makes trait errors hard to read, as they usually show up far away from the
+/// composition, e.g. when the allocator is passed to a collection. `assert_composes!` checks the
+/// composition right where it is declared. Each trait which is not implemented is reported
+/// separately, and the notes of the error follow the requirements down to the layer, which breaks
+/// the property.
+///
+/// Lifetimes in the type have to be named, e.g. `Region<'static>`.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{assert_composes, region::Region, AllocateAll, Chunk, Fallback, Null, Owns};
+/// use core::alloc::Allocator;
+/// use std::alloc::System;
+///
+/// assert_composes!(Fallback<Chunk<Region<'static>, 64>, Null>: Allocator + Owns);
+/// assert_composes!(Fallback<Region<'static>, Null>: Allocator + AllocateAll + Owns);
+/// assert_composes!(Chunk<System, 64>: Allocator + Send + Sync + Copy);
+/// ```
+///
+/// A region cannot be shared between threads, so neither can a composition containing one:
+///
+/// ```rust,compile_fail
+/// # #![feature(allocator_api)]
+/// # use alloc_compose::{assert_composes, region::Region, Chunk, Fallback};
+/// # use core::alloc::Allocator;
+/// # use std::alloc::System;
+/// assert_composes!(Fallback<Chunk<Region<'static>, 64>, System>: Allocator + Sync);
+/// ```
+#[macro_export]
+macro_rules! assert_composes {
+    ($ty:ty: $($bound:tt)+) => {
+        const _: () = {
+            #[allow(dead_code)]
+            fn assert_composes<T: ?Sized + $($bound)+>() {}
+
+            #[allow(dead_code)]
+            fn check() {
+                assert_composes::<$ty>()
+            }
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compose;
+    use crate::{
+        helper::tracker,
+        region::Region,
+        stats::Counter,
+        AllocateAll,
+        Allocator,
+        Chunk,
+        Fallback,
+        Null,
+        Owns,
+        Proxy,
+        Segregate,
+    };
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn build() {
+        let mut data = [MaybeUninit::new(0); 128];
+        let alloc: Proxy<Segregate<Fallback<Chunk<Region, 32>, _>, _, 64>, Counter> =
+            Compose::region(&mut data)
+                .chunked::<32>()
+                .with_fallback(tracker(Global))
+                .with_large::<_, 64>(tracker(Global))
+                .with_stats(Counter::default())
+                .build();
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert_eq!(memory.len(), 32);
+        assert_eq!(alloc.alloc.small.primary.0.capacity_left(), 96);
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 128]>())
+            .expect("Could not allocate 128 bytes");
+        assert!(!alloc.alloc.small.primary.owns(memory));
+        assert_eq!(alloc.callbacks.num_allocs(), 2);
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 128]>()) };
+    }
+
+    assert_composes!(
+        Proxy<Segregate<Fallback<Region<'static>, Null>, Region<'static>, 64>, Counter>:
+            Allocator + AllocateAll + Owns
+    );
+    assert_composes!(Segregate<Chunk<Global, 16>, Global, 64>: Allocator + Send + Sync);
+}
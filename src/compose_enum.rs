@@ -0,0 +1,395 @@
+/// Declares an enum over allocators, which dispatches every request to the active variant.
+///
+/// This allows choosing an allocator at runtime, e.g. from a configuration, while keeping static
+/// dispatch and without boxing. Every variant holds exactly one allocator. The enum implements
+/// [`Allocator`] and every crate trait listed after the name, which has to be implemented by all
+/// variants: [`AllocateAll`], [`ReallocateInPlace`], [`Owns`], [`BlockSize`], [`ReportUsage`],
+/// [`Describe`], and [`Purge`]. [`Describe`] is transparent and describes the active variant.
+///
+/// The enum may have one lifetime parameter.
+///
+/// [`Allocator`]: core::alloc::Allocator
+/// [`AllocateAll`]: crate::AllocateAll
+/// [`ReallocateInPlace`]: crate::ReallocateInPlace
+/// [`Owns`]: crate::Owns
+/// [`BlockSize`]: crate::BlockSize
+/// [`ReportUsage`]: crate::ReportUsage
+/// [`Describe`]: crate::Describe
+/// [`Purge`]: crate::Purge
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{compose_enum, region::Region};
+/// use core::mem::MaybeUninit;
+/// use std::alloc::System;
+///
+/// compose_enum! {
+///     pub enum Backend<'a> {
+///         Fast(Region<'a>),
+///         Slow(System),
+///     }
+/// }
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let alloc = if std::env::var_os("SLOW").is_some() {
+///     Backend::Slow(System)
+/// } else {
+///     Backend::Fast(Region::new(&mut data))
+/// };
+/// let vec: Vec<u32, _> = Vec::with_capacity_in(4, &alloc);
+/// ```
+#[macro_export]
+macro_rules! compose_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident $(<$lt:lifetime>)? $(: $first:ident $(+ $trait:ident)*)? {
+            $($(#[$variant_meta:meta])* $variant:ident($ty:ty)),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name $(<$lt>)? {
+            $($(#[$variant_meta])* $variant($ty)),+
+        }
+
+        $crate::compose_enum!(
+            @traits [$name $(<$lt>)?] [$($variant)+] Allocator $($first $($trait)*)?
+        );
+    };
+
+    (@traits $head:tt $variants:tt) => {};
+    (@traits $head:tt $variants:tt $trait:ident $($rest:ident)*) => {
+        $crate::compose_enum!(@impl $trait $head $variants);
+        $crate::compose_enum!(@traits $head $variants $($rest)*);
+    };
+
+    (@impl Allocator [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        unsafe impl $(<$lt>)? $crate::Allocator for $name $(<$lt>)? {
+            fn allocate(
+                &self,
+                layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(Self::$variant(alloc) => $crate::Allocator::allocate(alloc, layout),)+
+                }
+            }
+
+            fn allocate_zeroed(
+                &self,
+                layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::Allocator::allocate_zeroed(alloc, layout)
+                        }
+                    )+
+                }
+            }
+
+            #[track_caller]
+            unsafe fn deallocate(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                layout: ::core::alloc::Layout,
+            ) {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::Allocator::deallocate(alloc, ptr, layout)
+                        }
+                    )+
+                }
+            }
+
+            #[track_caller]
+            unsafe fn grow(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                old_layout: ::core::alloc::Layout,
+                new_layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::Allocator::grow(alloc, ptr, old_layout, new_layout)
+                        }
+                    )+
+                }
+            }
+
+            #[track_caller]
+            unsafe fn grow_zeroed(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                old_layout: ::core::alloc::Layout,
+                new_layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::Allocator::grow_zeroed(
+                                alloc,
+                                ptr,
+                                old_layout,
+                                new_layout,
+                            )
+                        }
+                    )+
+                }
+            }
+
+            #[track_caller]
+            unsafe fn shrink(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                old_layout: ::core::alloc::Layout,
+                new_layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::Allocator::shrink(alloc, ptr, old_layout, new_layout)
+                        }
+                    )+
+                }
+            }
+        }
+    };
+
+    (@impl AllocateAll [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        unsafe impl $(<$lt>)? $crate::AllocateAll for $name $(<$lt>)? {
+            fn allocate_all(
+                &self,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::allocate_all(alloc),)+
+                }
+            }
+
+            fn allocate_all_zeroed(
+                &self,
+            ) -> ::core::result::Result<::core::ptr::NonNull<[u8]>, ::core::alloc::AllocError> {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::allocate_all_zeroed(alloc),)+
+                }
+            }
+
+            fn deallocate_all(&self) {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::deallocate_all(alloc),)+
+                }
+            }
+
+            fn capacity(&self) -> usize {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::capacity(alloc),)+
+                }
+            }
+
+            fn capacity_left(&self) -> usize {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::capacity_left(alloc),)+
+                }
+            }
+
+            fn is_empty(&self) -> bool {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::is_empty(alloc),)+
+                }
+            }
+
+            fn is_full(&self) -> bool {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::is_full(alloc),)+
+                }
+            }
+
+            #[track_caller]
+            fn reserve(
+                &self,
+                additional: usize,
+            ) -> ::core::result::Result<(), $crate::ComposeError> {
+                match self {
+                    $(Self::$variant(alloc) => $crate::AllocateAll::reserve(alloc, additional),)+
+                }
+            }
+        }
+    };
+
+    (@impl ReallocateInPlace [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        unsafe impl $(<$lt>)? $crate::ReallocateInPlace for $name $(<$lt>)? {
+            #[track_caller]
+            unsafe fn grow_in_place(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                old_layout: ::core::alloc::Layout,
+                new_layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<usize, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::ReallocateInPlace::grow_in_place(
+                                alloc,
+                                ptr,
+                                old_layout,
+                                new_layout,
+                            )
+                        }
+                    )+
+                }
+            }
+
+            #[track_caller]
+            unsafe fn grow_in_place_zeroed(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                old_layout: ::core::alloc::Layout,
+                new_layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<usize, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::ReallocateInPlace::grow_in_place_zeroed(
+                                alloc,
+                                ptr,
+                                old_layout,
+                                new_layout,
+                            )
+                        }
+                    )+
+                }
+            }
+
+            #[track_caller]
+            unsafe fn shrink_in_place(
+                &self,
+                ptr: ::core::ptr::NonNull<u8>,
+                old_layout: ::core::alloc::Layout,
+                new_layout: ::core::alloc::Layout,
+            ) -> ::core::result::Result<usize, ::core::alloc::AllocError> {
+                match self {
+                    $(
+                        Self::$variant(alloc) => {
+                            $crate::ReallocateInPlace::shrink_in_place(
+                                alloc,
+                                ptr,
+                                old_layout,
+                                new_layout,
+                            )
+                        }
+                    )+
+                }
+            }
+        }
+    };
+
+    (@impl Owns [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        impl $(<$lt>)? $crate::Owns for $name $(<$lt>)? {
+            fn owns(&self, memory: ::core::ptr::NonNull<[u8]>) -> bool {
+                match self {
+                    $(Self::$variant(alloc) => $crate::Owns::owns(alloc, memory),)+
+                }
+            }
+
+            fn owns_ptr(&self, ptr: ::core::ptr::NonNull<u8>) -> bool {
+                match self {
+                    $(Self::$variant(alloc) => $crate::Owns::owns_ptr(alloc, ptr),)+
+                }
+            }
+        }
+    };
+
+    (@impl BlockSize [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        impl $(<$lt>)? $crate::BlockSize for $name $(<$lt>)? {
+            fn block_size(&self) -> usize {
+                match self {
+                    $(Self::$variant(alloc) => $crate::BlockSize::block_size(alloc),)+
+                }
+            }
+        }
+    };
+
+    (@impl ReportUsage [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        impl $(<$lt>)? $crate::ReportUsage for $name $(<$lt>)? {
+            fn usage(&self) -> $crate::Usage {
+                match self {
+                    $(Self::$variant(alloc) => $crate::ReportUsage::usage(alloc),)+
+                }
+            }
+        }
+    };
+
+    (@impl Describe [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        impl $(<$lt>)? $crate::Describe for $name $(<$lt>)? {
+            fn describe(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    $(Self::$variant(alloc) => $crate::Describe::describe(alloc, f),)+
+                }
+            }
+
+            fn for_each_child(&self, visit: &mut dyn FnMut(&dyn $crate::Describe)) {
+                match self {
+                    $(Self::$variant(alloc) => $crate::Describe::for_each_child(alloc, visit),)+
+                }
+            }
+        }
+    };
+
+    (@impl Purge [$name:ident $(<$lt:lifetime>)?] [$($variant:ident)+]) => {
+        impl $(<$lt>)? $crate::Purge for $name $(<$lt>)? {
+            fn purge(&self, level: $crate::PurgeLevel) {
+                match self {
+                    $(Self::$variant(alloc) => $crate::Purge::purge(alloc, level),)+
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        AllocateAll,
+        Allocator,
+        Owns,
+        ReallocateInPlace,
+        ReportUsage,
+    };
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    compose_enum! {
+        enum Regions<'a>: AllocateAll + ReallocateInPlace + Owns + ReportUsage + Describe {
+            Down(Region<'a>),
+            Up(Region<'a, Up>),
+        }
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut data = [MaybeUninit::uninit(); 64];
+        for &up in &[false, true] {
+            let region = if up {
+                Regions::Up(Region::with_direction(&mut data))
+            } else {
+                Regions::Down(Region::new(&mut data))
+            };
+            let alloc = tracker(&region);
+
+            let layout = Layout::new::<[u8; 8]>();
+            let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+            assert!(region.owns(memory));
+            assert_eq!(region.usage().free, 56);
+            let grown = unsafe {
+                region.grow_in_place(memory.as_non_null_ptr(), layout, Layout::new::<[u8; 16]>())
+            };
+            assert_eq!(grown.is_ok(), up);
+            region.deallocate_all();
+            assert!(region.is_empty());
+        }
+    }
+}
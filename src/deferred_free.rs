@@ -0,0 +1,426 @@
+use crate::{
+    AllocateAll,
+    AllocateMany,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Timestamp,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::{Cell, UnsafeCell},
+    fmt,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+/// A memory block waiting for deallocation.
+type Block = (NonNull<u8>, Layout);
+
+/// Queues deallocations and hands them to the underlying allocator in batches of `N`.
+///
+/// Deallocations are passed to [`AllocateMany::deallocate_many`], once `N` memory blocks are
+/// queued, when [`flush`] or [`purge`] is called, or when `DeferredFree` is dropped. This amortizes the costs,
+/// which the underlying allocator pays per call, like acquiring a lock or doing a system call.
+/// Until then, queued memory blocks are still considered as allocated by the underlying
+/// allocator. With `N = 0`, deallocations are forwarded immediately.
+///
+/// To bound how long memory is retained, [`with_max_age`] sets a maximum age measured with the
+/// clock `T`. As soon as the oldest queued block exceeds it, the queue is flushed by the next
+/// request or by [`purge_expired`]. By default, `T` is `()`, which never advances.
+///
+/// [`flush`]: Self::flush
+/// [`purge`]: crate::Purge::purge
+/// [`with_max_age`]: Self::with_max_age
+/// [`purge_expired`]: Self::purge_expired
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{stats, CallbackRef, DeferredFree, Proxy};
+/// use std::alloc::{Allocator, Global, Layout};
+///
+/// let counter = stats::Counter::default();
+/// let alloc = DeferredFree::<_, 4>::new(Proxy {
+///     alloc: Global,
+///     callbacks: counter.by_ref(),
+/// });
+///
+/// let memory = alloc.allocate(Layout::new::<u32>())?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>()) };
+/// assert_eq!(counter.num_deallocs(), 0);
+///
+/// alloc.flush();
+/// assert_eq!(counter.num_deallocs(), 1);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+///
+/// Flushing blocks after 5 milliseconds:
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// # #[cfg(feature = "std")] {
+/// use alloc_compose::{stats, CallbackRef, DeferredFree, Proxy, StdClock};
+/// use std::{
+///     alloc::{Allocator, Global, Layout},
+///     thread,
+///     time::Duration,
+/// };
+///
+/// let counter = stats::Counter::default();
+/// let alloc = DeferredFree::<_, 16, StdClock>::with_max_age(
+///     Proxy {
+///         alloc: Global,
+///         callbacks: counter.by_ref(),
+///     },
+///     5_000_000,
+/// );
+///
+/// let memory = alloc.allocate(Layout::new::<u32>())?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>()) };
+/// assert_eq!(alloc.purge_expired(), 0);
+///
+/// thread::sleep(Duration::from_millis(5));
+/// assert_eq!(alloc.purge_expired(), 1);
+/// assert_eq!(counter.num_deallocs(), 1);
+/// # }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct DeferredFree<A: Allocator, const N: usize, T: Timestamp = ()> {
+    alloc: A,
+    queue: UnsafeCell<[MaybeUninit<Block>; N]>,
+    len: Cell<usize>,
+    queued_at: Cell<u64>,
+    max_age: u64,
+    clock: PhantomData<T>,
+}
+
+impl<A: Allocator, const N: usize> DeferredFree<A, N> {
+    /// Queues the deallocations for `alloc`.
+    pub fn new(alloc: A) -> Self {
+        Self::with_max_age(alloc, u64::MAX)
+    }
+}
+
+impl<A: Allocator, const N: usize, T: Timestamp> DeferredFree<A, N, T> {
+    /// Queues the deallocations for `alloc` for at most `max_age` ticks of `T`.
+    pub fn with_max_age(alloc: A, max_age: u64) -> Self {
+        Self {
+            alloc,
+            queue: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            len: Cell::new(0),
+            queued_at: Cell::new(0),
+            max_age,
+            clock: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn get_ref(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the number of queued deallocations.
+    pub fn queued(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Deallocates all queued memory blocks.
+    pub fn flush(&self) {
+        let len = self.len.replace(0);
+        unsafe {
+            let queue = &*self.queue.get();
+            self.alloc
+                .deallocate_many(queue[..len].iter().map(|block| *block.as_ptr()));
+        }
+    }
+
+    /// Flushes the queue, if its oldest memory block exceeds the maximum age, and returns the
+    /// number of deallocated memory blocks.
+    pub fn purge_expired(&self) -> usize {
+        let len = self.len.get();
+        if len > 0
+            && self.max_age != u64::MAX
+            && T::elapsed(self.queued_at.get(), T::now()) >= self.max_age
+        {
+            self.flush();
+            len
+        } else {
+            0
+        }
+    }
+}
+
+impl<A: Allocator, const N: usize, T: Timestamp> Drop for DeferredFree<A, N, T> {
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<A: Allocator + fmt::Debug, const N: usize, T: Timestamp> fmt::Debug for DeferredFree<A, N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferredFree")
+            .field("alloc", &self.alloc)
+            .field("queued", &self.queued())
+            .finish()
+    }
+}
+
+impl<A: Allocator + Default, const N: usize, T: Timestamp> Default for DeferredFree<A, N, T> {
+    fn default() -> Self {
+        Self::with_max_age(A::default(), u64::MAX)
+    }
+}
+
+unsafe impl<A: Allocator, const N: usize, T: Timestamp> Allocator for DeferredFree<A, N, T> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.purge_expired();
+        self.alloc.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.purge_expired();
+        self.alloc.allocate_zeroed(layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if N == 0 {
+            return self.alloc.deallocate(ptr, layout);
+        }
+        self.purge_expired();
+        let len = self.len.get();
+        if len == 0 {
+            self.queued_at.set(T::now());
+        }
+        // SAFETY: the queue is flushed as soon as it is full, so `len < N`
+        *(*self.queue.get()).get_unchecked_mut(len) = MaybeUninit::new((ptr, layout));
+        self.len.set(len + 1);
+        if len + 1 == N {
+            self.flush();
+        }
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.grow(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+unsafe impl<A: Allocator + AllocateAll, const N: usize, T: Timestamp> AllocateAll
+    for DeferredFree<A, N, T>
+{
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all()
+    }
+
+    #[inline]
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all_zeroed()
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        // The queued memory blocks are released as well
+        self.len.set(0);
+        self.alloc.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.alloc.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.alloc.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.alloc.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
+}
+
+impl<A: Allocator + ReportUsage, const N: usize, T: Timestamp> ReportUsage
+    for DeferredFree<A, N, T>
+{
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Allocator + Owns, const N: usize, T: Timestamp> Owns for DeferredFree<A, N, T> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: Allocator + Describe, const N: usize, T: Timestamp> Describe for DeferredFree<A, N, T> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DeferredFree<{}>({} queued)", N, self.queued())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Allocator + Purge, const N: usize, T: Timestamp> Purge for DeferredFree<A, N, T> {
+    fn purge(&self, level: PurgeLevel) {
+        self.flush();
+        self.alloc.purge(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeferredFree;
+    use crate::{helper::tracker, stats::Counter, Allocator, CallbackRef, Proxy, Timestamp};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::{
+        alloc::Layout,
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+
+    #[test]
+    fn batches() {
+        let counter = Counter::default();
+        let alloc = DeferredFree::<_, 4>::new(Proxy {
+            alloc: tracker(Global),
+            callbacks: counter.by_ref(),
+        });
+        let layout = Layout::new::<[u8; 8]>();
+
+        let blocks = (0..10)
+            .map(|_| alloc.allocate(layout).expect("Could not allocate 8 bytes"))
+            .collect::<Vec<_>>();
+        for (i, memory) in blocks.into_iter().enumerate() {
+            unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+            assert_eq!(counter.num_deallocs(), (i + 1) as u64 / 4 * 4);
+        }
+        assert_eq!(alloc.queued(), 2);
+
+        drop(alloc);
+        assert_eq!(counter.num_deallocs(), 10);
+    }
+
+    #[test]
+    fn unbuffered() {
+        let counter = Counter::default();
+        let alloc = DeferredFree::<_, 0>::new(Proxy {
+            alloc: tracker(Global),
+            callbacks: counter.by_ref(),
+        });
+
+        let memory = alloc
+            .allocate(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u64>()) };
+        assert_eq!(counter.num_deallocs(), 1);
+        assert_eq!(alloc.queued(), 0);
+    }
+
+    static TICKS: AtomicU64 = AtomicU64::new(0);
+
+    struct TestClock;
+
+    impl Timestamp for TestClock {
+        fn now() -> u64 {
+            TICKS.load(Ordering::Relaxed)
+        }
+
+        fn to_duration(ticks: u64) -> Duration {
+            Duration::from_secs(ticks)
+        }
+    }
+
+    #[test]
+    fn expiry() {
+        let counter = Counter::default();
+        let alloc = DeferredFree::<_, 4, TestClock>::with_max_age(
+            Proxy {
+                alloc: tracker(Global),
+                callbacks: counter.by_ref(),
+            },
+            10,
+        );
+        let layout = Layout::new::<u64>();
+
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        TICKS.fetch_add(5, Ordering::Relaxed);
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        assert_eq!(alloc.purge_expired(), 0);
+        assert_eq!(alloc.queued(), 2);
+
+        // The age is measured from the oldest queued block
+        TICKS.fetch_add(5, Ordering::Relaxed);
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        assert_eq!(alloc.queued(), 0);
+        assert_eq!(counter.num_deallocs(), 2);
+
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        TICKS.fetch_add(10, Ordering::Relaxed);
+        assert_eq!(alloc.purge_expired(), 1);
+        assert_eq!(counter.num_deallocs(), 3);
+    }
+}
@@ -0,0 +1,106 @@
+use core::fmt;
+
+/// Describes the structure of an allocator composition.
+///
+/// Every allocator writes a short description of itself, e.g. `Region(32 KiB, 71% used)`, and
+/// reports the allocators it is composed of. The [`visualize`] module uses this to render the
+/// whole composition as a tree.
+///
+/// [`visualize`]: crate::visualize
+///
+/// # Examples
+///
+/// ```rust
+/// use alloc_compose::Describe;
+/// use core::fmt;
+///
+/// struct Bump;
+///
+/// impl Describe for Bump {
+///     fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("Bump")
+///     }
+/// }
+/// ```
+pub trait Describe {
+    /// Writes a short, single line description of this allocator.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error produced by `f`.
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Calls `visit` for every allocator, this allocator is composed of.
+    #[allow(unused_variables)]
+    #[inline]
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {}
+}
+
+impl<D: Describe + ?Sized> Describe for &D {
+    #[inline]
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).describe(f)
+    }
+
+    #[inline]
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        (**self).for_each_child(visit)
+    }
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+impl Describe for alloc::alloc::Global {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Global")
+    }
+}
+
+#[cfg(any(doc, feature = "std"))]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+impl Describe for std::alloc::System {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("System")
+    }
+}
+
+/// Formats `bytes` with a binary unit and at most one decimal, e.g. `1.5 KiB`.
+pub(crate) struct Bytes(pub usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        let mut unit = 0;
+        let mut scale = 1_usize;
+        while unit + 1 < UNITS.len() && self.0 / scale >= 1024 {
+            unit += 1;
+            scale *= 1024;
+        }
+        let whole = self.0 / scale;
+        let tenth = (self.0 % scale) as u64 * 10 / scale as u64;
+        if unit == 0 || tenth == 0 {
+            write!(f, "{} {}", whole, UNITS[unit])
+        } else {
+            write!(f, "{}.{} {}", whole, tenth, UNITS[unit])
+        }
+    }
+}
+
+/// Writes `name(capacity, n% used)`.
+pub(crate) fn describe_usage(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    capacity: usize,
+    capacity_left: usize,
+) -> fmt::Result {
+    let used = capacity - capacity_left;
+    let percent = if capacity == 0 {
+        0
+    } else if used <= usize::MAX / 100 {
+        used * 100 / capacity
+    } else {
+        used / (capacity / 100)
+    };
+    write!(f, "{}({}, {}% used)", name, Bytes(capacity), percent)
+}
@@ -0,0 +1,102 @@
+use core::{alloc::AllocError, fmt, panic::Location};
+
+/// The layer and the source location of a [`ComposeError`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorContext {
+    /// The name of the allocator, which failed.
+    pub layer: &'static str,
+    /// The location of the call, which failed.
+    ///
+    /// Functions returning a `ComposeError` are `#[track_caller]`, so this points into the code
+    /// calling into the composition rather than into the failing allocator.
+    pub location: &'static Location<'static>,
+}
+
+impl ErrorContext {
+    #[track_caller]
+    fn new(layer: &'static str) -> Self {
+        Self {
+            layer,
+            location: Location::caller(),
+        }
+    }
+}
+
+/// An error with context, returned by constructors and [`reserve`].
+///
+/// [`AllocError`] carries no information, which makes failures in deeply nested compositions
+/// hard to track down. `ComposeError` states which layer failed, why, and where it was called.
+/// It converts into `AllocError`, so `?` can be used in [`Allocator`] implementations.
+///
+/// [`reserve`]: crate::AllocateAll::reserve
+/// [`Allocator`]: core::alloc::Allocator
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, AllocateAll, ComposeError};
+/// use core::mem::MaybeUninit;
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let region = Region::new(&mut data);
+///
+/// let error = region.reserve(128).unwrap_err();
+/// assert!(matches!(error, ComposeError::Exhausted(_)));
+/// assert_eq!(error.context().location.line(), line!() - 2);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ComposeError {
+    /// The allocator has not enough memory left.
+    Exhausted(ErrorContext),
+    /// The allocator does not support the requested size or alignment.
+    LayoutUnsupported(ErrorContext),
+}
+
+impl ComposeError {
+    /// Creates an [`Exhausted`] error for `layer` at the location of the caller.
+    ///
+    /// [`Exhausted`]: Self::Exhausted
+    #[track_caller]
+    pub fn exhausted(layer: &'static str) -> Self {
+        Self::Exhausted(ErrorContext::new(layer))
+    }
+
+    /// Creates a [`LayoutUnsupported`] error for `layer` at the location of the caller.
+    ///
+    /// [`LayoutUnsupported`]: Self::LayoutUnsupported
+    #[track_caller]
+    pub fn layout_unsupported(layer: &'static str) -> Self {
+        Self::LayoutUnsupported(ErrorContext::new(layer))
+    }
+
+    /// Returns the layer and the location of the error.
+    pub fn context(&self) -> ErrorContext {
+        match *self {
+            Self::Exhausted(context) | Self::LayoutUnsupported(context) => context,
+        }
+    }
+}
+
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::Exhausted(_) => "memory exhausted",
+            Self::LayoutUnsupported(_) => "layout unsupported",
+        };
+        let context = self.context();
+        write!(f, "{}: {} at {}", context.layer, reason, context.location)
+    }
+}
+
+#[cfg(any(doc, feature = "std"))]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+impl std::error::Error for ComposeError {}
+
+impl From<ComposeError> for AllocError {
+    fn from(_: ComposeError) -> Self {
+        Self
+    }
+}
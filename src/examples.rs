@@ -0,0 +1,247 @@
+//! End-to-end recipes, which compose several allocators to solve a common problem.
+//!
+//! Every recipe is a doctest, so it's compiled and run against the public API like an
+//! integration test. This module only exists in the documentation and contains no items.
+//!
+//! # Global allocation statistics
+//!
+//! A [`Proxy`] with custom callbacks wrapped into [`AsGlobalAlloc`] records every allocation of
+//! the program. The callbacks have to be constructible in a `static`, so they are built from
+//! atomics with a `const fn`.
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{AsGlobalAlloc, CallbackRef, Proxy};
+//! use core::{
+//!     alloc::{AllocError, Layout},
+//!     ptr::NonNull,
+//!     sync::atomic::{AtomicUsize, Ordering::Relaxed},
+//! };
+//! use std::alloc::System;
+//!
+//! /// Tracks the number of allocated bytes and its peak.
+//! struct LiveBytes {
+//!     current: AtomicUsize,
+//!     peak: AtomicUsize,
+//! }
+//!
+//! impl LiveBytes {
+//!     const fn new() -> Self {
+//!         Self {
+//!             current: AtomicUsize::new(0),
+//!             peak: AtomicUsize::new(0),
+//!         }
+//!     }
+//!
+//!     fn add(&self, size: usize) {
+//!         let current = self.current.fetch_add(size, Relaxed) + size;
+//!         self.peak.fetch_max(current, Relaxed);
+//!     }
+//!
+//!     fn sub(&self, size: usize) {
+//!         self.current.fetch_sub(size, Relaxed);
+//!     }
+//! }
+//!
+//! unsafe impl CallbackRef for LiveBytes {
+//!     fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+//!         if result.is_ok() {
+//!             self.add(layout.size())
+//!         }
+//!     }
+//!
+//!     fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+//!         self.after_allocate(layout, result)
+//!     }
+//!
+//!     fn before_deallocate(&self, _ptr: NonNull<u8>, layout: Layout) {
+//!         self.sub(layout.size())
+//!     }
+//!
+//!     fn after_grow(
+//!         &self,
+//!         _ptr: NonNull<u8>,
+//!         old_layout: Layout,
+//!         new_layout: Layout,
+//!         result: Result<NonNull<[u8]>, AllocError>,
+//!     ) {
+//!         if result.is_ok() {
+//!             self.add(new_layout.size() - old_layout.size())
+//!         }
+//!     }
+//!
+//!     fn after_shrink(
+//!         &self,
+//!         _ptr: NonNull<u8>,
+//!         old_layout: Layout,
+//!         new_layout: Layout,
+//!         result: Result<NonNull<[u8]>, AllocError>,
+//!     ) {
+//!         if result.is_ok() {
+//!             self.sub(old_layout.size() - new_layout.size())
+//!         }
+//!     }
+//! }
+//!
+//! #[global_allocator]
+//! static ALLOC: AsGlobalAlloc<Proxy<System, LiveBytes>> = AsGlobalAlloc(Proxy {
+//!     alloc: System,
+//!     callbacks: LiveBytes::new(),
+//! });
+//!
+//! let stats = &ALLOC.0.callbacks;
+//! let before = stats.current.load(Relaxed);
+//!
+//! let squares: Vec<u64> = (0..1000).map(|i| i * i).collect();
+//! assert!(stats.peak.load(Relaxed) >= before + 8000);
+//!
+//! drop(squares);
+//! assert!(stats.current.load(Relaxed) < before + 8000);
+//! ```
+//!
+//! # Per-frame arena with scopes
+//!
+//! Games and simulations allocate a lot of short-lived memory every frame. A [`Region`] serves
+//! these requests by bumping a pointer and is reset at the end of the frame with
+//! [`deallocate_all`]. [`Metered`] callbacks attribute the allocations to the phase of the frame,
+//! which is currently running.
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{
+//!     aligned_buffer,
+//!     region::Region,
+//!     stats::Counter,
+//!     AllocateAll,
+//!     Metered,
+//!     Proxy,
+//! };
+//!
+//! let physics = Counter::default();
+//! let render = Counter::default();
+//!
+//! let mut memory = aligned_buffer!(4096, 16);
+//! let frame = Proxy {
+//!     alloc: Region::new(&mut memory),
+//!     callbacks: Metered::new(Counter::default()),
+//! };
+//!
+//! for _ in 0..3 {
+//!     {
+//!         let _scope = frame.callbacks.attach(&physics);
+//!         let mut contacts = Vec::with_capacity_in(16, &frame);
+//!         contacts.extend(0..16_u32);
+//!     }
+//!     {
+//!         let _scope = frame.callbacks.attach(&render);
+//!         let vertices = Box::new_in([0.0_f32; 64], &frame);
+//!         assert_eq!(vertices.len(), 64);
+//!     }
+//!     // Everything allocated in this frame is freed at once
+//!     frame.deallocate_all();
+//!     assert!(frame.is_empty());
+//! }
+//!
+//! assert_eq!(physics.num_allocs(), 3);
+//! assert_eq!(render.num_allocs(), 3);
+//! assert_eq!(frame.callbacks.callbacks.num_allocs(), 6);
+//! ```
+//!
+//! # Stack memory with heap fallback for parsing
+//!
+//! Most inputs of a parser are small, so the temporary buffers fit into a buffer on the stack.
+//! A region growing upwards can grow the last buffer in place, and [`Fallback`] moves it to the
+//! heap, as soon as it outgrows the stack. Counting the requests to the heap with a [`Proxy`]
+//! shows, how often the stack buffer was too small.
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{
+//!     region::{Region, Up},
+//!     stats::Counter,
+//!     AllocateAll,
+//!     Fallback,
+//!     Proxy,
+//! };
+//! use std::{
+//!     alloc::{Allocator, System},
+//!     mem::MaybeUninit,
+//! };
+//!
+//! fn tokenize<'s, A: Allocator>(input: &'s str, alloc: A) -> Vec<&'s str, A> {
+//!     let mut tokens = Vec::new_in(alloc);
+//!     tokens.extend(input.split_whitespace());
+//!     tokens
+//! }
+//!
+//! let mut stack = [MaybeUninit::uninit(); 512];
+//! let alloc = Fallback {
+//!     primary: Region::<Up>::with_direction(&mut stack),
+//!     secondary: Proxy {
+//!         alloc: System,
+//!         callbacks: Counter::default(),
+//!     },
+//! };
+//!
+//! let tokens = tokenize("let answer = 42 ;", &alloc);
+//! assert_eq!(tokens, ["let", "answer", "=", "42", ";"]);
+//! assert_eq!(alloc.secondary.callbacks.num_allocs(), 0);
+//! drop(tokens);
+//! alloc.primary.deallocate_all();
+//!
+//! let input = "token ".repeat(100);
+//! let tokens = tokenize(&input, &alloc);
+//! assert_eq!(tokens.len(), 100);
+//! assert_eq!(alloc.secondary.callbacks.num_allocs(), 1);
+//! ```
+//!
+//! # Small-object slab
+//!
+//! Node based data structures allocate many objects of the same few sizes. [`GrowablePool`]s
+//! for these sizes are chained with [`segregate!`], so small objects are served from slabs and
+//! freed objects are reused. Larger requests are passed to the heap.
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{segregate, stats::Counter, GrowablePool, Proxy};
+//! use std::alloc::System;
+//!
+//! let heap = Proxy {
+//!     alloc: System,
+//!     callbacks: Counter::default(),
+//! };
+//! let slab = segregate!(
+//!     GrowablePool::<_, 16, 64>::new(&heap) => 16,
+//!     GrowablePool::<_, 32, 64>::new(&heap) => 32;
+//!     &heap
+//! );
+//!
+//! // 100 objects of 16 bytes need two slabs of 64 blocks
+//! let pairs: Vec<_> = (0..100_u64).map(|i| Box::new_in([i, i * i], &slab)).collect();
+//! let name = Box::new_in(*b"a name with 24 bytes....", &slab);
+//! assert_eq!(slab.small.num_slabs(), 2);
+//! assert_eq!(slab.large.small.num_slabs(), 1);
+//! assert_eq!(heap.callbacks.num_allocs(), 3);
+//!
+//! let buffer = Vec::<u8, _>::with_capacity_in(1024, &slab);
+//! assert_eq!(heap.callbacks.num_allocs(), 4);
+//!
+//! // Freed objects are reused without requesting memory from the heap
+//! drop(pairs);
+//! let pairs: Vec<_> = (0..100_u64).map(|i| Box::new_in([i, i + 1], &slab)).collect();
+//! assert_eq!(heap.callbacks.num_allocs(), 4);
+//! # drop((pairs, name, buffer));
+//! ```
+//!
+//! [`AsGlobalAlloc`]: crate::AsGlobalAlloc
+//! [`Fallback`]: crate::Fallback
+//! [`GrowablePool`]: crate::GrowablePool
+//! [`Metered`]: crate::Metered
+//! [`Proxy`]: crate::Proxy
+//! [`Region`]: crate::region::Region
+//! [`deallocate_all`]: crate::AllocateAll::deallocate_all
+//! [`segregate!`]: crate::segregate
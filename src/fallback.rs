@@ -1,9 +1,19 @@
 use crate::{
     helper::{grow_fallback, AllocInit},
+    AllocateAll,
+    Allocator,
+    ComposeError,
+    Describe,
     Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
 };
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
+    alloc::{AllocError, Layout},
+    fmt,
     ptr::NonNull,
 };
 
@@ -13,6 +23,11 @@ use core::{
 /// request is forwarded to the `Fallback` allocator. All other requests are dispatched
 /// appropriately to one of the two allocators.
 ///
+/// Growing a memory block of the `Primary` allocator first attempts to grow it in place, if the
+/// `Primary` allocator implements [`ReallocateInPlace`]. If that fails, the memory block is moved
+/// within the `Primary` allocator. Only if the `Primary` allocator has no room for it, the memory
+/// block is copied to the `Secondary` allocator.
+///
 /// A `Fallback` is useful for fast, special-purpose allocators backed up by general-purpose
 /// allocators like [`Global`] or [`System`].
 ///
@@ -26,7 +41,7 @@ use core::{
 ///
 /// use alloc_compose::{region::Region, Fallback, Owns};
 /// use std::{
-///     alloc::{AllocRef, Layout, System},
+///     alloc::{Allocator, Layout, System},
 ///     mem::MaybeUninit,
 /// };
 ///
@@ -36,20 +51,20 @@ use core::{
 ///     secondary: System,
 /// };
 ///
-/// let small_memory = alloc.alloc(Layout::new::<u32>())?;
-/// let big_memory = alloc.alloc(Layout::new::<[u32; 64]>())?;
+/// let small_memory = alloc.allocate(Layout::new::<u32>())?;
+/// let big_memory = alloc.allocate(Layout::new::<[u32; 64]>())?;
 ///
 /// assert!(alloc.primary.owns(small_memory));
 /// assert!(!alloc.primary.owns(big_memory));
 ///
 /// unsafe {
 ///     // `big_memory` was allocated from `System`, we can dealloc it directly
-///     System.dealloc(big_memory.as_non_null_ptr(), Layout::new::<[u32; 64]>());
-///     alloc.dealloc(small_memory.as_non_null_ptr(), Layout::new::<u32>());
+///     System.deallocate(big_memory.as_non_null_ptr(), Layout::new::<[u32; 64]>());
+///     alloc.deallocate(small_memory.as_non_null_ptr(), Layout::new::<u32>());
 /// };
 /// # Ok::<(), core::alloc::AllocError>(())
 /// ```
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Fallback<Primary, Secondary> {
     /// The primary allocator
     pub primary: Primary,
@@ -57,33 +72,119 @@ pub struct Fallback<Primary, Secondary> {
     pub secondary: Secondary,
 }
 
-unsafe impl<Primary, Secondary> AllocRef for Fallback<Primary, Secondary>
+/// Grows a memory block within the allocator.
+///
+/// Allocators supporting [`ReallocateInPlace`] are grown in place first. If that fails, the
+/// memory block is moved within the allocator without asking it to grow in place again.
+trait GrowWithin {
+    unsafe fn grow_within(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError>;
+}
+
+impl<A: Allocator> GrowWithin for A {
+    #[inline]
+    default unsafe fn grow_within(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match init {
+            AllocInit::Uninitialized => self.grow(ptr, old_layout, new_layout),
+            AllocInit::Zeroed => self.grow_zeroed(ptr, old_layout, new_layout),
+        }
+    }
+}
+
+impl<A: Allocator + ReallocateInPlace> GrowWithin for A {
+    #[inline]
+    unsafe fn grow_within(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let len = match init {
+            AllocInit::Uninitialized => self.grow_in_place(ptr, old_layout, new_layout),
+            AllocInit::Zeroed => self.grow_in_place_zeroed(ptr, old_layout, new_layout),
+        };
+        match len {
+            Ok(len) => Ok(NonNull::slice_from_raw_parts(ptr, len)),
+            Err(_) => grow_fallback(self, self, ptr, old_layout, new_layout, init),
+        }
+    }
+}
+
+impl<Primary, Secondary> Fallback<Primary, Secondary> {
+    /// Creates a new fallback allocator from the given primary and secondary allocator.
+    pub const fn new(primary: Primary, secondary: Secondary) -> Self {
+        Self { primary, secondary }
+    }
+
+    /// Replaces the primary allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{region::Region, Fallback, Null};
+    /// use core::mem::MaybeUninit;
+    /// use std::alloc::System;
+    ///
+    /// let mut data = [MaybeUninit::uninit(); 64];
+    /// let alloc = Fallback::new(Null, System).with_primary(Region::new(&mut data));
+    /// ```
+    pub fn with_primary<P>(self, primary: P) -> Fallback<P, Secondary> {
+        Fallback {
+            primary,
+            secondary: self.secondary,
+        }
+    }
+
+    /// Replaces the secondary allocator.
+    pub fn with_secondary<S>(self, secondary: S) -> Fallback<Primary, S> {
+        Fallback {
+            primary: self.primary,
+            secondary,
+        }
+    }
+}
+
+unsafe impl<Primary, Secondary> Allocator for Fallback<Primary, Secondary>
 where
-    Primary: AllocRef + Owns,
-    Secondary: AllocRef,
+    Primary: Allocator + Owns,
+    Secondary: Allocator,
 {
-    fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        match self.primary.alloc(layout) {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match self.primary.allocate(layout) {
             primary @ Ok(_) => primary,
-            Err(_) => self.secondary.alloc(layout),
+            Err(_) => self.secondary.allocate(layout),
         }
     }
 
-    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        match self.primary.alloc_zeroed(layout) {
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match self.primary.allocate_zeroed(layout) {
             primary @ Ok(_) => primary,
-            Err(_) => self.secondary.alloc_zeroed(layout),
+            Err(_) => self.secondary.allocate_zeroed(layout),
         }
     }
 
-    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
         if self
             .primary
             .owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
         {
-            self.primary.dealloc(ptr, layout)
+            self.primary.deallocate(ptr, layout)
         } else {
-            self.secondary.dealloc(ptr, layout)
+            self.secondary.deallocate(ptr, layout)
         }
     }
 
@@ -97,18 +198,19 @@ where
             .primary
             .owns(NonNull::slice_from_raw_parts(ptr, old_layout.size()))
         {
-            if let Ok(memory) = self.primary.grow(ptr, old_layout, new_layout) {
-                Ok(memory)
-            } else {
-                grow_fallback(
-                    &self.primary,
-                    &self.secondary,
-                    ptr,
-                    old_layout,
-                    new_layout,
-                    AllocInit::Uninitialized,
-                )
-            }
+            let init = AllocInit::Uninitialized;
+            self.primary
+                .grow_within(ptr, old_layout, new_layout, init)
+                .or_else(|_| {
+                    grow_fallback(
+                        &self.primary,
+                        &self.secondary,
+                        ptr,
+                        old_layout,
+                        new_layout,
+                        init,
+                    )
+                })
         } else {
             self.secondary.grow(ptr, old_layout, new_layout)
         }
@@ -124,18 +226,19 @@ where
             .primary
             .owns(NonNull::slice_from_raw_parts(ptr, old_layout.size()))
         {
-            if let Ok(memory) = self.primary.grow_zeroed(ptr, old_layout, new_layout) {
-                Ok(memory)
-            } else {
-                grow_fallback(
-                    &self.primary,
-                    &self.secondary,
-                    ptr,
-                    old_layout,
-                    new_layout,
-                    AllocInit::Zeroed,
-                )
-            }
+            let init = AllocInit::Zeroed;
+            self.primary
+                .grow_within(ptr, old_layout, new_layout, init)
+                .or_else(|_| {
+                    grow_fallback(
+                        &self.primary,
+                        &self.secondary,
+                        ptr,
+                        old_layout,
+                        new_layout,
+                        init,
+                    )
+                })
         } else {
             self.secondary.grow_zeroed(ptr, old_layout, new_layout)
         }
@@ -158,6 +261,142 @@ where
     }
 }
 
+/// Dispatches to the allocator owning the memory block. Wrap an allocator, which can't resize in
+/// place, in [`MaybeReallocateInPlace`] to use it here.
+///
+/// [`MaybeReallocateInPlace`]: crate::MaybeReallocateInPlace
+unsafe impl<Primary, Secondary> ReallocateInPlace for Fallback<Primary, Secondary>
+where
+    Primary: ReallocateInPlace + Owns,
+    Secondary: ReallocateInPlace,
+{
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if self
+            .primary
+            .owns(NonNull::slice_from_raw_parts(ptr, old_layout.size()))
+        {
+            self.primary.grow_in_place(ptr, old_layout, new_layout)
+        } else {
+            self.secondary.grow_in_place(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if self
+            .primary
+            .owns(NonNull::slice_from_raw_parts(ptr, old_layout.size()))
+        {
+            self.primary
+                .grow_in_place_zeroed(ptr, old_layout, new_layout)
+        } else {
+            self.secondary
+                .grow_in_place_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if self
+            .primary
+            .owns(NonNull::slice_from_raw_parts(ptr, old_layout.size()))
+        {
+            self.primary.shrink_in_place(ptr, old_layout, new_layout)
+        } else {
+            self.secondary.shrink_in_place(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+/// Both allocators are treated as one pool: `capacity` and `capacity_left` are the sums of both,
+/// and `deallocate_all` resets both.
+///
+/// `allocate_all` drains the `Primary` allocator. Only if it is full, the `Secondary` allocator
+/// is drained.
+unsafe impl<Primary, Secondary> AllocateAll for Fallback<Primary, Secondary>
+where
+    Primary: AllocateAll,
+    Secondary: AllocateAll,
+{
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        if self.primary.is_full() {
+            self.secondary.allocate_all()
+        } else {
+            self.primary.allocate_all()
+        }
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        if self.primary.is_full() {
+            self.secondary.allocate_all_zeroed()
+        } else {
+            self.primary.allocate_all_zeroed()
+        }
+    }
+
+    fn deallocate_all(&self) {
+        self.primary.deallocate_all();
+        self.secondary.deallocate_all();
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.primary.capacity() + self.secondary.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.primary.capacity_left() + self.secondary.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.primary.is_empty() && self.secondary.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.primary.is_full() && self.secondary.is_full()
+    }
+
+    /// Succeeds, if either allocator can provide `additional` bytes on its own.
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.primary
+            .reserve(additional)
+            .or_else(|_| self.secondary.reserve(additional))
+    }
+}
+
+impl<Primary, Secondary> ReportUsage for Fallback<Primary, Secondary>
+where
+    Primary: ReportUsage,
+    Secondary: ReportUsage,
+{
+    fn usage(&self) -> Usage {
+        self.primary.usage() + self.secondary.usage()
+    }
+}
+
 impl<Primary, Secondary> Owns for Fallback<Primary, Secondary>
 where
     Primary: Owns,
@@ -166,20 +405,121 @@ where
     fn owns(&self, memory: NonNull<[u8]>) -> bool {
         self.primary.owns(memory) || self.secondary.owns(memory)
     }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.primary.owns_ptr(ptr) || self.secondary.owns_ptr(ptr)
+    }
+}
+
+impl<Primary: Describe, Secondary: Describe> Describe for Fallback<Primary, Secondary> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Fallback")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.primary);
+        visit(&self.secondary);
+    }
+}
+
+impl<Primary: Purge, Secondary: Purge> Purge for Fallback<Primary, Secondary> {
+    fn purge(&self, level: PurgeLevel) {
+        self.primary.purge(level);
+        self.secondary.purge(level);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Fallback;
-    use crate::{helper, region::Region, Chunk, Owns};
+    use crate::{
+        helper,
+        region::{Region, Up},
+        AllocateAll,
+        Allocator,
+        Chunk,
+        MaybeReallocateInPlace,
+        Owns,
+        ReallocateInPlace,
+    };
     use alloc::alloc::Global;
     use core::{
-        alloc::{AllocRef, Layout},
+        alloc::{AllocError, Layout},
+        cell::Cell,
         mem::MaybeUninit,
+        ptr::NonNull,
     };
 
+    /// Grows memory blocks only in place and counts the attempts.
+    struct InPlaceOnly<A>(A, Cell<usize>);
+
+    unsafe impl<A: Allocator> Allocator for InPlaceOnly<A> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.deallocate(ptr, layout)
+        }
+
+        unsafe fn grow(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            unreachable!("`grow` would attempt to grow in place again")
+        }
+
+        unsafe fn grow_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            unreachable!("`grow_zeroed` would attempt to grow in place again")
+        }
+    }
+
+    unsafe impl<A: ReallocateInPlace> ReallocateInPlace for InPlaceOnly<A> {
+        unsafe fn grow_in_place(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<usize, AllocError> {
+            self.1.set(self.1.get() + 1);
+            self.0.grow_in_place(ptr, old_layout, new_layout)
+        }
+
+        unsafe fn grow_in_place_zeroed(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<usize, AllocError> {
+            self.1.set(self.1.get() + 1);
+            self.0.grow_in_place_zeroed(ptr, old_layout, new_layout)
+        }
+
+        unsafe fn shrink_in_place(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<usize, AllocError> {
+            self.0.shrink_in_place(ptr, old_layout, new_layout)
+        }
+    }
+
+    impl<A: Owns> Owns for InPlaceOnly<A> {
+        fn owns(&self, memory: NonNull<[u8]>) -> bool {
+            self.0.owns(memory)
+        }
+    }
+
     #[test]
-    fn alloc() {
+    fn allocate() {
         let mut data = [MaybeUninit::new(0); 32];
         let alloc = Fallback {
             primary: helper::tracker(Region::new(&mut data)),
@@ -187,17 +527,17 @@ mod tests {
         };
 
         let small_memory = alloc
-            .alloc(Layout::new::<u32>())
+            .allocate(Layout::new::<u32>())
             .expect("Could not allocate 4 bytes");
         let big_memory = alloc
-            .alloc(Layout::new::<[u8; 64]>())
+            .allocate(Layout::new::<[u8; 64]>())
             .expect("Could not allocate 64 bytes");
 
         assert!(alloc.primary.owns(small_memory));
         assert!(!alloc.primary.owns(big_memory));
         unsafe {
-            alloc.dealloc(small_memory.as_non_null_ptr(), Layout::new::<u32>());
-            alloc.dealloc(big_memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+            alloc.deallocate(small_memory.as_non_null_ptr(), Layout::new::<u32>());
+            alloc.deallocate(big_memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
         };
     }
 
@@ -210,7 +550,7 @@ mod tests {
         };
 
         let memory = alloc
-            .alloc(Layout::new::<[u8; 32]>())
+            .allocate(Layout::new::<[u8; 32]>())
             .expect("Could not allocate 4 bytes");
         assert!(alloc.primary.owns(memory));
 
@@ -234,10 +574,56 @@ mod tests {
                 .expect("Could not grow to 128 bytes");
             assert!(!alloc.primary.owns(memory));
 
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 128]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 128]>());
         };
     }
 
+    #[test]
+    fn grow_in_place() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = Fallback {
+            primary: InPlaceOnly(Region::<Up>::with_direction(&mut data), Cell::new(0)),
+            secondary: MaybeReallocateInPlace(helper::tracker(Global)),
+        };
+        let layout = Layout::new::<[u8; 8]>();
+        let grown = Layout::new::<[u8; 32]>();
+
+        unsafe {
+            let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+            memory.as_non_null_ptr().write_bytes(0xFF, 8);
+            let grown_memory = alloc
+                .grow_zeroed(memory.as_non_null_ptr(), layout, grown)
+                .expect("Could not grow to 32 bytes");
+            assert_eq!(grown_memory.as_non_null_ptr(), memory.as_non_null_ptr());
+            assert!(alloc.primary.owns(grown_memory));
+            assert!(grown_memory.as_ref()[8..].iter().all(|&b| b == 0));
+            assert_eq!(alloc.primary.1.get(), 1);
+
+            assert_eq!(
+                alloc.grow_in_place(grown_memory.as_non_null_ptr(), grown, grown),
+                Ok(32)
+            );
+
+            let moved = alloc
+                .grow(
+                    grown_memory.as_non_null_ptr(),
+                    grown,
+                    Layout::new::<[u8; 128]>(),
+                )
+                .expect("Could not grow to 128 bytes");
+            assert!(!alloc.primary.owns(moved));
+            assert_eq!(alloc.primary.1.get(), 3);
+            assert!(alloc
+                .shrink_in_place(
+                    moved.as_non_null_ptr(),
+                    Layout::new::<[u8; 128]>(),
+                    Layout::new::<[u8; 64]>()
+                )
+                .is_err());
+            alloc.deallocate(moved.as_non_null_ptr(), Layout::new::<[u8; 128]>());
+        }
+    }
+
     #[test]
     fn shrink() {
         let mut data = [MaybeUninit::new(0); 80];
@@ -247,7 +633,7 @@ mod tests {
         };
 
         let memory = alloc
-            .alloc(Layout::new::<[u8; 64]>())
+            .allocate(Layout::new::<[u8; 64]>())
             .expect("Could not allocate 64 bytes");
         assert!(alloc.primary.owns(memory));
 
@@ -279,7 +665,7 @@ mod tests {
                 .expect("Could not shrink to 96 bytes");
             assert!(!alloc.primary.owns(memory));
 
-            alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 96]>());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 96]>());
         }
     }
 
@@ -293,15 +679,44 @@ mod tests {
         };
 
         let memory = alloc
-            .alloc(Layout::new::<[u8; 32]>())
+            .allocate(Layout::new::<[u8; 32]>())
             .expect("Could not allocate 32 bytes");
         assert!(alloc.primary.owns(memory));
         assert!(alloc.owns(memory));
 
         let memory = alloc
-            .alloc(Layout::new::<[u8; 64]>())
+            .allocate(Layout::new::<[u8; 64]>())
             .expect("Could not allocate 64 bytes");
         assert!(alloc.secondary.owns(memory));
         assert!(alloc.owns(memory));
     }
+
+    #[test]
+    fn alloc_all() {
+        let mut data_1 = [MaybeUninit::new(0); 32];
+        let mut data_2 = [MaybeUninit::new(0); 64];
+        let alloc = Fallback {
+            primary: Region::new(&mut data_1),
+            secondary: Region::new(&mut data_2),
+        };
+        assert_eq!(alloc.capacity(), 96);
+
+        alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert_eq!(alloc.capacity_left(), 80);
+
+        let memory = alloc.allocate_all().expect("Could not allocate all");
+        assert!(alloc.primary.owns(memory));
+        assert_eq!(memory.len(), 16);
+        assert!(alloc.primary.is_full());
+
+        let memory = alloc.allocate_all().expect("Could not allocate all");
+        assert!(alloc.secondary.owns(memory));
+        assert_eq!(memory.len(), 64);
+        assert!(alloc.is_full());
+
+        alloc.deallocate_all();
+        assert!(alloc.is_empty());
+    }
 }
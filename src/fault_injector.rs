@@ -0,0 +1,244 @@
+use crate::{
+    helper::{split_mix, Fnv},
+    PolicyRef,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    hash::{Hash, Hasher},
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    thread::{self, ThreadId},
+};
+
+/// A policy for [`Guarded`], which denies requests at random to simulate out-of-memory
+/// conditions.
+///
+/// The failures are drawn from a pseudo-random stream derived from a seed, so a failing test can
+/// be reproduced by reusing its seed. Every thread draws from its own stream, which is derived from
+/// the seed and the name of the thread. The test harness names each thread after the test it runs,
+/// so the failures of one test don't depend on other tests running in parallel, even if the
+/// injector is shared via `Arc`. Threads without a name fall back to their [`ThreadId`], which is
+/// not stable across runs.
+///
+/// Cloning an injector forks it: the clone starts new streams, which are independent of the
+/// original and of other clones, but are still determined by the seed.
+///
+/// Growing and shrinking may be denied as well as allocating. Deallocations are never denied.
+///
+/// [`Guarded`]: crate::Guarded
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{FaultInjector, Guarded};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = Guarded {
+///     alloc: System,
+///     policy: FaultInjector::from_seed(42).failing_one_in(4),
+/// };
+///
+/// let failures = (0..100)
+///     .filter(|_| alloc.allocate(Layout::new::<[u8; 0]>()).is_err())
+///     .count();
+/// assert_eq!(failures, alloc.policy.injected());
+/// ```
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub struct FaultInjector {
+    seed: u64,
+    one_in: u64,
+    forks: AtomicUsize,
+    injected: AtomicUsize,
+    streams: Mutex<HashMap<ThreadId, u64>>,
+}
+
+impl FaultInjector {
+    /// Fails one in eight requests, drawn from streams derived from `seed`.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            one_in: 8,
+            forks: AtomicUsize::new(0),
+            injected: AtomicUsize::new(0),
+            streams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the failure rate to one in `n` requests.
+    ///
+    /// With `n = 1` every request fails, with `n = 0` no request fails.
+    pub fn failing_one_in(mut self, n: u64) -> Self {
+        self.one_in = n;
+        self
+    }
+
+    /// Returns the seed of this injector.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the number of requests denied so far.
+    pub fn injected(&self) -> usize {
+        self.injected.load(Ordering::Relaxed)
+    }
+
+    fn thread_seed(&self) -> u64 {
+        let thread = thread::current();
+        let mut hasher = Fnv::default();
+        match thread.name() {
+            Some(name) => name.hash(&mut hasher),
+            None => thread.id().hash(&mut hasher),
+        }
+        let mut state = self.seed ^ hasher.finish();
+        split_mix(&mut state)
+    }
+
+    fn inject(&self) -> Result<(), AllocError> {
+        if self.one_in == 0 {
+            return Ok(());
+        }
+        let value = {
+            let mut streams = self.streams.lock().unwrap_or_else(PoisonError::into_inner);
+            let state = streams
+                .entry(thread::current().id())
+                .or_insert_with(|| self.thread_seed());
+            split_mix(state)
+        };
+        if value % self.one_in == 0 {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+            Err(AllocError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+impl Clone for FaultInjector {
+    fn clone(&self) -> Self {
+        let mut state = self.seed ^ (self.forks.fetch_add(1, Ordering::Relaxed) as u64 + 1);
+        Self::from_seed(split_mix(&mut state)).failing_one_in(self.one_in)
+    }
+}
+
+impl fmt::Debug for FaultInjector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultInjector")
+            .field("seed", &self.seed)
+            .field("one_in", &self.one_in)
+            .field("injected", &self.injected())
+            .finish()
+    }
+}
+
+impl PolicyRef for FaultInjector {
+    fn check_allocate(&self, _layout: Layout) -> Result<(), AllocError> {
+        self.inject()
+    }
+
+    fn check_allocate_all(&self) -> Result<(), AllocError> {
+        self.inject()
+    }
+
+    fn check_grow(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<(), AllocError> {
+        self.inject()
+    }
+
+    fn check_shrink(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<(), AllocError> {
+        self.inject()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FaultInjector;
+    use crate::PolicyRef;
+    use alloc::{sync::Arc, vec::Vec};
+    use core::alloc::Layout;
+    use std::thread;
+
+    fn failures(injector: &FaultInjector) -> Vec<bool> {
+        (0..64)
+            .map(|_| injector.check_allocate(Layout::new::<u8>()).is_err())
+            .collect()
+    }
+
+    #[test]
+    fn deterministic() {
+        let injector = FaultInjector::from_seed(7).failing_one_in(3);
+        let pattern = failures(&injector);
+        assert_eq!(injector.injected(), pattern.iter().filter(|&&f| f).count());
+        assert!(injector.injected() > 0 && injector.injected() < 64);
+
+        assert_eq!(
+            failures(&FaultInjector::from_seed(7).failing_one_in(3)),
+            pattern
+        );
+        assert_ne!(
+            failures(&FaultInjector::from_seed(8).failing_one_in(3)),
+            pattern
+        );
+
+        assert!(failures(&FaultInjector::default().failing_one_in(0))
+            .iter()
+            .all(|&f| !f));
+        assert!(failures(&FaultInjector::default().failing_one_in(1))
+            .iter()
+            .all(|&f| f));
+    }
+
+    #[test]
+    fn fork() {
+        let injector = FaultInjector::from_seed(7).failing_one_in(3);
+        let first = injector.clone();
+        let second = injector.clone();
+        assert_ne!(first.seed(), injector.seed());
+        assert_ne!(first.seed(), second.seed());
+        assert_ne!(failures(&first), failures(&second));
+
+        let again = FaultInjector::from_seed(7).failing_one_in(3);
+        assert_eq!(again.clone().seed(), first.seed());
+    }
+
+    #[test]
+    fn per_thread() {
+        let injector = Arc::new(FaultInjector::from_seed(7).failing_one_in(3));
+        let spawn = |name: &str| {
+            let injector = Arc::clone(&injector);
+            thread::Builder::new()
+                .name(name.into())
+                .spawn(move || failures(&injector))
+                .expect("Could not spawn thread")
+        };
+
+        let a = spawn("a");
+        let b = spawn("b");
+        let a_again = spawn("a");
+        let a = a.join().expect("Thread panicked");
+        let b = b.join().expect("Thread panicked");
+        assert_eq!(a, a_again.join().expect("Thread panicked"));
+        assert_ne!(a, b);
+    }
+}
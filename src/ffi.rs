@@ -0,0 +1,370 @@
+//! Interoperability with allocators written in C.
+//!
+//! [`CAllocator`] uses `malloc`-style function pointers as [`Allocator`], so C allocators can act
+//! as parents in compositions. [`CVTable`] goes the other way and exposes any composition as a
+//! table of `extern "C"` functions, which can back C libraries.
+//!
+//! [`Allocator`]: core::alloc::Allocator
+
+use crate::{Allocator, Describe, Purge};
+use core::{
+    alloc::{AllocError, Layout},
+    ffi::c_void,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ptr::{self, NonNull},
+};
+
+/// An allocator backed by `malloc`, `free`, and `realloc` function pointers.
+///
+/// Memory blocks are guaranteed to be aligned to `max_align`, which defaults to the alignment
+/// guaranteed by common `malloc` implementations. Requests with a larger alignment fail. As
+/// `malloc(0)` may return a null pointer, zero-sized requests allocate one byte.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::ffi::CAllocator;
+/// use core::ffi::c_void;
+/// use std::alloc::{Allocator, Layout};
+///
+/// extern "C" {
+///     fn malloc(size: usize) -> *mut c_void;
+///     fn free(ptr: *mut c_void);
+///     fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void;
+/// }
+///
+/// let alloc = unsafe { CAllocator::new(malloc, free, realloc) };
+///
+/// let memory = alloc.allocate(Layout::new::<[u32; 4]>())?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u32; 4]>()) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Copy, Clone)]
+pub struct CAllocator {
+    malloc: unsafe extern "C" fn(usize) -> *mut c_void,
+    free: unsafe extern "C" fn(*mut c_void),
+    realloc: unsafe extern "C" fn(*mut c_void, usize) -> *mut c_void,
+    max_align: usize,
+}
+
+impl CAllocator {
+    /// The alignment `malloc` guarantees on most platforms.
+    pub const DEFAULT_MAX_ALIGN: usize = 2 * mem::size_of::<usize>();
+
+    /// Wraps the functions of a C allocator.
+    ///
+    /// # Safety
+    ///
+    /// The functions must behave like their counterparts in the C standard library, and returned
+    /// pointers must be aligned to [`DEFAULT_MAX_ALIGN`].
+    ///
+    /// [`DEFAULT_MAX_ALIGN`]: Self::DEFAULT_MAX_ALIGN
+    pub unsafe fn new(
+        malloc: unsafe extern "C" fn(usize) -> *mut c_void,
+        free: unsafe extern "C" fn(*mut c_void),
+        realloc: unsafe extern "C" fn(*mut c_void, usize) -> *mut c_void,
+    ) -> Self {
+        Self::with_max_align(malloc, free, realloc, Self::DEFAULT_MAX_ALIGN)
+    }
+
+    /// Wraps the functions of a C allocator, which aligns memory blocks to `max_align`.
+    ///
+    /// # Safety
+    ///
+    /// The functions must behave like their counterparts in the C standard library, and returned
+    /// pointers must be aligned to `max_align`.
+    pub unsafe fn with_max_align(
+        malloc: unsafe extern "C" fn(usize) -> *mut c_void,
+        free: unsafe extern "C" fn(*mut c_void),
+        realloc: unsafe extern "C" fn(*mut c_void, usize) -> *mut c_void,
+        max_align: usize,
+    ) -> Self {
+        Self {
+            malloc,
+            free,
+            realloc,
+            max_align,
+        }
+    }
+
+    /// Returns the alignment, which memory blocks are guaranteed to have.
+    pub fn max_align(&self) -> usize {
+        self.max_align
+    }
+
+    fn block(ptr: *mut c_void, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = NonNull::new(ptr.cast()).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn realloc_impl(
+        &self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if new_layout.align() > self.max_align {
+            return Err(AllocError);
+        }
+        let new_size = new_layout.size().max(1);
+        Self::block((self.realloc)(ptr.as_ptr().cast(), new_size), new_layout)
+    }
+}
+
+impl fmt::Debug for CAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CAllocator")
+            .field("max_align", &self.max_align)
+            .finish()
+    }
+}
+
+unsafe impl Allocator for CAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.align() > self.max_align {
+            return Err(AllocError);
+        }
+        Self::block(unsafe { (self.malloc)(layout.size().max(1)) }, layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        (self.free)(ptr.as_ptr().cast())
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.realloc_impl(ptr, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let memory = self.realloc_impl(ptr, new_layout)?;
+        memory
+            .as_mut_ptr()
+            .add(old_layout.size())
+            .write_bytes(0, new_layout.size() - old_layout.size());
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.realloc_impl(ptr, new_layout)
+    }
+}
+
+impl Describe for CAllocator {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CAllocator")
+    }
+}
+
+impl Purge for CAllocator {}
+
+/// A table of `extern "C"` functions, which forward to an allocator.
+///
+/// C has no notion of layouts, so the functions take the size and the alignment explicitly, and
+/// `free` and `realloc` expect the size and alignment the memory block was allocated with. The
+/// functions return a null pointer on failure, including invalid layouts. `ctx` has to be passed
+/// as first argument to every function.
+///
+/// The table borrows the allocator, so it must not be used by C code after the allocator was
+/// dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{ffi::CVTable, region::Region};
+/// use core::mem::MaybeUninit;
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let region = Region::new(&mut data);
+/// let vtable = CVTable::new(&region);
+///
+/// unsafe {
+///     let ptr = (vtable.malloc)(vtable.ctx, 16, 8);
+///     assert!(!ptr.is_null());
+///     (vtable.free)(vtable.ctx, ptr, 16, 8);
+/// }
+/// ```
+#[repr(C)]
+pub struct CVTable<'a> {
+    pub ctx: *mut c_void,
+    pub malloc: unsafe extern "C" fn(*mut c_void, usize, usize) -> *mut c_void,
+    pub calloc: unsafe extern "C" fn(*mut c_void, usize, usize) -> *mut c_void,
+    pub free: unsafe extern "C" fn(*mut c_void, *mut c_void, usize, usize),
+    pub realloc: unsafe extern "C" fn(*mut c_void, *mut c_void, usize, usize, usize) -> *mut c_void,
+    _alloc: PhantomData<&'a ()>,
+}
+
+impl<'a> CVTable<'a> {
+    /// Creates a table forwarding to `alloc`.
+    pub fn new<A: Allocator>(alloc: &'a A) -> Self {
+        Self {
+            ctx: alloc as *const A as *mut c_void,
+            malloc: c_malloc::<A>,
+            calloc: c_calloc::<A>,
+            free: c_free::<A>,
+            realloc: c_realloc::<A>,
+            _alloc: PhantomData,
+        }
+    }
+}
+
+impl fmt::Debug for CVTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CVTable").field("ctx", &self.ctx).finish()
+    }
+}
+
+fn into_c(result: Result<NonNull<[u8]>, AllocError>) -> *mut c_void {
+    result.map_or(ptr::null_mut(), |memory| memory.as_mut_ptr().cast())
+}
+
+unsafe extern "C" fn c_malloc<A: Allocator>(
+    ctx: *mut c_void,
+    size: usize,
+    align: usize,
+) -> *mut c_void {
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => into_c((*ctx.cast::<A>()).allocate(layout)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn c_calloc<A: Allocator>(
+    ctx: *mut c_void,
+    size: usize,
+    align: usize,
+) -> *mut c_void {
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => into_c((*ctx.cast::<A>()).allocate_zeroed(layout)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn c_free<A: Allocator>(
+    ctx: *mut c_void,
+    ptr: *mut c_void,
+    size: usize,
+    align: usize,
+) {
+    if let Some(ptr) = NonNull::new(ptr.cast()) {
+        let layout = Layout::from_size_align_unchecked(size, align);
+        (*ctx.cast::<A>()).deallocate(ptr, layout)
+    }
+}
+
+unsafe extern "C" fn c_realloc<A: Allocator>(
+    ctx: *mut c_void,
+    ptr: *mut c_void,
+    old_size: usize,
+    align: usize,
+    new_size: usize,
+) -> *mut c_void {
+    let ptr = match NonNull::new(ptr.cast()) {
+        Some(ptr) => ptr,
+        None => return c_malloc::<A>(ctx, new_size, align),
+    };
+    let alloc = &*ctx.cast::<A>();
+    let old_layout = Layout::from_size_align_unchecked(old_size, align);
+    let new_layout = match Layout::from_size_align(new_size, align) {
+        Ok(layout) => layout,
+        Err(_) => return ptr::null_mut(),
+    };
+    if new_size >= old_size {
+        into_c(alloc.grow(ptr, old_layout, new_layout))
+    } else {
+        into_c(alloc.shrink(ptr, old_layout, new_layout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CVTable;
+    use crate::{helper::tracker, region::Region};
+    use alloc::alloc::Global;
+    use core::{ffi::c_void, mem::MaybeUninit, ptr};
+
+    #[test]
+    fn vtable() {
+        let alloc = tracker(Global);
+        let vtable = CVTable::new(&alloc);
+        unsafe {
+            let ptr = (vtable.calloc)(vtable.ctx, 8, 4);
+            assert_eq!(*ptr.cast::<[u8; 8]>(), [0; 8]);
+            ptr.cast::<u8>().write_bytes(1, 8);
+            let ptr = (vtable.realloc)(vtable.ctx, ptr, 8, 4, 64);
+            assert_eq!(*ptr.cast::<[u8; 8]>(), [1; 8]);
+            let ptr = (vtable.realloc)(vtable.ctx, ptr, 64, 4, 2);
+            assert_eq!(*ptr.cast::<[u8; 2]>(), [1; 2]);
+            (vtable.free)(vtable.ctx, ptr, 2, 4);
+
+            assert!((vtable.malloc)(vtable.ctx, 8, 3).is_null());
+            (vtable.free)(vtable.ctx, ptr::null_mut(), 8, 4);
+        }
+
+        let mut data = [MaybeUninit::uninit(); 16];
+        let region = Region::new(&mut data);
+        let vtable = CVTable::new(&region);
+        unsafe {
+            assert!(!(vtable.malloc)(vtable.ctx, 16, 1).is_null());
+            assert_eq!((vtable.malloc)(vtable.ctx, 1, 1), ptr::null_mut::<c_void>());
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(all(unix, feature = "std"))]
+    fn c_allocator() {
+        use super::CAllocator;
+        use crate::Allocator;
+        use core::alloc::Layout;
+
+        let alloc = tracker(unsafe { CAllocator::new(libc::malloc, libc::free, libc::realloc) });
+        unsafe {
+            let layout = Layout::new::<[u8; 4]>();
+            let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
+            memory.as_mut_ptr().write_bytes(1, 4);
+            let grown = Layout::new::<[u8; 1024]>();
+            let memory = alloc
+                .grow_zeroed(memory.as_non_null_ptr(), layout, grown)
+                .expect("Could not grow to 1024 bytes");
+            assert_eq!(*memory.as_mut_ptr().add(3), 1);
+            assert_eq!(*memory.as_mut_ptr().add(1023), 0);
+            alloc.deallocate(memory.as_non_null_ptr(), grown);
+
+            let overaligned = Layout::from_size_align(8, 4096).expect("Invalid layout");
+            assert!(alloc.allocate(overaligned).is_err());
+        }
+
+        let alloc =
+            unsafe { CAllocator::with_max_align(libc::malloc, libc::free, libc::realloc, 1) };
+        assert_eq!(alloc.max_align(), 1);
+        assert!(alloc.allocate(Layout::new::<u16>()).is_err());
+    }
+}
@@ -0,0 +1,659 @@
+use crate::{
+    describe::Bytes,
+    helper::{grow_fallback, shrink_fallback, AllocInit},
+    Allocator,
+    Describe,
+    Owns,
+    Purge,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    cmp,
+    fmt,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+type Link = Option<NonNull<Node>>;
+
+/// The header of a free memory block, which links it into both trees.
+struct Node {
+    size: usize,
+    by_address: [Link; 2],
+    by_size: [Link; 2],
+}
+
+/// Every memory block is a multiple of the granule and starts at a multiple of it, so each free
+/// block can hold a `Node`.
+const GRANULE: usize = mem::size_of::<Node>().next_power_of_two();
+
+/// Selects one of the two treaps, which are threaded through the free blocks.
+///
+/// Both treaps share the node priorities, which are derived from the address of the node. As
+/// nodes are granule aligned, the granule index is hashed, so neighboring blocks get unrelated
+/// priorities.
+#[derive(Copy, Clone)]
+enum Tree {
+    Address,
+    Size,
+}
+
+impl Tree {
+    fn priority(node: NonNull<Node>) -> usize {
+        (node.as_ptr() as usize / GRANULE).wrapping_mul(0x9E37_79B9_7F4A_7C15_u64 as usize)
+    }
+
+    unsafe fn children(self, node: NonNull<Node>) -> *mut [Link; 2] {
+        match self {
+            Self::Address => ptr::addr_of_mut!((*node.as_ptr()).by_address),
+            Self::Size => ptr::addr_of_mut!((*node.as_ptr()).by_size),
+        }
+    }
+
+    unsafe fn less(self, a: NonNull<Node>, b: NonNull<Node>) -> bool {
+        match self {
+            Self::Address => a < b,
+            Self::Size => ((*a.as_ptr()).size, a) < ((*b.as_ptr()).size, b),
+        }
+    }
+
+    /// Merges two treaps, where all nodes in `a` are less than all nodes in `b`.
+    unsafe fn merge(self, a: Link, b: Link) -> Link {
+        match (a, b) {
+            (None, link) | (link, None) => link,
+            (Some(a), Some(b)) => {
+                if Self::priority(a) > Self::priority(b) {
+                    let children = self.children(a);
+                    (*children)[1] = self.merge((*children)[1], Some(b));
+                    Some(a)
+                } else {
+                    let children = self.children(b);
+                    (*children)[0] = self.merge(Some(a), (*children)[0]);
+                    Some(b)
+                }
+            }
+        }
+    }
+
+    /// Splits a treap into the nodes less than `node` and the remaining nodes.
+    unsafe fn split(self, root: Link, node: NonNull<Node>) -> (Link, Link) {
+        let root = match root {
+            Some(root) => root,
+            None => return (None, None),
+        };
+        let children = self.children(root);
+        if self.less(root, node) {
+            let (less, greater) = self.split((*children)[1], node);
+            (*children)[1] = less;
+            (Some(root), greater)
+        } else {
+            let (less, greater) = self.split((*children)[0], node);
+            (*children)[0] = greater;
+            (less, Some(root))
+        }
+    }
+
+    unsafe fn insert(self, root: Link, node: NonNull<Node>) -> Link {
+        *self.children(node) = [None, None];
+        let (less, greater) = self.split(root, node);
+        self.merge(self.merge(less, Some(node)), greater)
+    }
+
+    /// Removes `node`, which must be part of the treap.
+    unsafe fn remove(self, root: Link, node: NonNull<Node>) -> Link {
+        let root = root?;
+        let children = self.children(root);
+        if root == node {
+            self.merge((*children)[0], (*children)[1])
+        } else {
+            let side = !self.less(node, root) as usize;
+            (*children)[side] = self.remove((*children)[side], node);
+            Some(root)
+        }
+    }
+}
+
+/// Returns the free block starting at `ptr`.
+unsafe fn find(mut link: Link, ptr: NonNull<u8>) -> Link {
+    while let Some(node) = link {
+        let node_ptr = node.cast::<u8>();
+        if node_ptr == ptr {
+            return Some(node);
+        }
+        link = (*node.as_ptr()).by_address[(node_ptr < ptr) as usize];
+    }
+    None
+}
+
+/// Returns the last free block starting before `ptr`.
+unsafe fn predecessor(mut link: Link, ptr: NonNull<u8>) -> Link {
+    let mut predecessor = None;
+    while let Some(node) = link {
+        if node.cast::<u8>() < ptr {
+            predecessor = Some(node);
+            link = (*node.as_ptr()).by_address[1];
+        } else {
+            link = (*node.as_ptr()).by_address[0];
+        }
+    }
+    predecessor
+}
+
+/// Returns the smallest free block, which can hold `size` bytes aligned to `align`, and the
+/// padding needed in front of it.
+unsafe fn best_fit(link: Link, size: usize, align: usize) -> Option<(NonNull<Node>, usize)> {
+    let node = link?;
+    let [smaller, larger] = (*node.as_ptr()).by_size;
+    let node_size = (*node.as_ptr()).size;
+    if node_size < size {
+        return best_fit(larger, size, align);
+    }
+    if let Some(fit) = best_fit(smaller, size, align) {
+        return Some(fit);
+    }
+    let padding = (node.as_ptr() as usize).wrapping_neg() & (align - 1);
+    if padding <= node_size - size {
+        Some((node, padding))
+    } else {
+        best_fit(larger, size, align)
+    }
+}
+
+/// A best-fit allocator for a provided memory block, which coalesces adjacent free blocks.
+///
+/// Free blocks are kept in two intrusive treaps, stored in the free blocks themselves: one
+/// ordered by size to find the smallest fitting block, and one ordered by address to merge a
+/// deallocated block with its free neighbors. Both are balanced by pseudo-random priorities, so
+/// allocating and deallocating takes logarithmic time on average. Over-aligned requests may have
+/// to visit more blocks until a fitting one is found.
+///
+/// Memory is handed out in multiples of a granule, which is large enough to hold the bookkeeping
+/// of a free block, i.e. 32 bytes on 32-bit and 64 bytes on 64-bit targets. Memory blocks are
+/// aligned to the granule. As small requests waste most of their granule, `FreeTree` is best
+/// used as the large side of [`Segregate`], e.g. when `System` isn't available.
+///
+/// Memory blocks are grown and shrunk in place, if possible.
+///
+/// [`Segregate`]: crate::Segregate
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::FreeTree;
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 4096];
+/// let alloc = FreeTree::new(&mut data);
+/// let capacity = alloc.capacity();
+///
+/// let layouts = [
+///     Layout::new::<[u8; 100]>(),
+///     Layout::new::<[u8; 600]>(),
+///     Layout::new::<[u8; 100]>(),
+/// ];
+/// let blocks = layouts
+///     .iter()
+///     .map(|&layout| alloc.allocate(layout))
+///     .collect::<Result<Vec<_>, _>>()?;
+///
+/// unsafe { alloc.deallocate(blocks[1].as_non_null_ptr(), layouts[1]) };
+/// assert_eq!(alloc.num_free_blocks(), 2);
+///
+/// // The hole in the middle is the best fit
+/// let memory = alloc.allocate(Layout::new::<[u8; 500]>())?;
+/// assert_eq!(memory.as_non_null_ptr(), blocks[1].as_non_null_ptr());
+///
+/// unsafe {
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 500]>());
+///     alloc.deallocate(blocks[0].as_non_null_ptr(), layouts[0]);
+///     alloc.deallocate(blocks[2].as_non_null_ptr(), layouts[2]);
+/// }
+/// assert_eq!(alloc.num_free_blocks(), 1);
+/// assert_eq!(alloc.largest_free_block(), capacity);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct FreeTree<'mem> {
+    start: NonNull<u8>,
+    capacity: usize,
+    by_address: Cell<Link>,
+    by_size: Cell<Link>,
+    capacity_left: Cell<usize>,
+    num_free_blocks: Cell<usize>,
+    _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
+}
+
+// The tree exclusively borrows its memory, so it may be moved to another thread.
+unsafe impl Send for FreeTree<'_> {}
+
+impl<'mem> FreeTree<'mem> {
+    /// Creates a new allocator managing the given memory block.
+    ///
+    /// Bytes in front of the first and after the last granule boundary are not used.
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        let len = memory.len();
+        let ptr = memory.as_mut_ptr().cast::<u8>();
+        let offset = cmp::min(ptr.align_offset(GRANULE), len);
+        let capacity = (len - offset) & !(GRANULE - 1);
+        let alloc = Self {
+            start: unsafe { NonNull::new_unchecked(ptr.add(offset)) },
+            capacity,
+            by_address: Cell::new(None),
+            by_size: Cell::new(None),
+            capacity_left: Cell::new(0),
+            num_free_blocks: Cell::new(0),
+            _marker: PhantomData,
+        };
+        if capacity != 0 {
+            unsafe { alloc.insert(alloc.start, capacity) };
+        }
+        alloc
+    }
+
+    /// Returns the number of bytes managed by this allocator.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the total number of free bytes.
+    pub fn capacity_left(&self) -> usize {
+        self.capacity_left.get()
+    }
+
+    /// Returns the number of free blocks, which cannot be coalesced.
+    ///
+    /// Together with [`largest_free_block`], this measures the fragmentation of the free memory.
+    ///
+    /// [`largest_free_block`]: Self::largest_free_block
+    pub fn num_free_blocks(&self) -> usize {
+        self.num_free_blocks.get()
+    }
+
+    /// Returns the size of the largest free block, which is the largest request guaranteed to
+    /// succeed for alignments up to the granule.
+    pub fn largest_free_block(&self) -> usize {
+        let mut largest = 0;
+        let mut link = self.by_size.get();
+        while let Some(node) = link {
+            unsafe {
+                largest = (*node.as_ptr()).size;
+                link = (*node.as_ptr()).by_size[1];
+            }
+        }
+        largest
+    }
+
+    /// Returns the size of the memory block handed out for `layout`.
+    ///
+    /// Any size between the requested size and the returned size maps to the same block size,
+    /// so every layout fitting the memory block can be used to deallocate it.
+    fn block_size(layout: Layout) -> Option<usize> {
+        let size = cmp::max(layout.size(), 1).checked_add(GRANULE - 1)?;
+        Some(size & !(GRANULE - 1))
+    }
+
+    unsafe fn insert(&self, ptr: NonNull<u8>, size: usize) {
+        let node = ptr.cast::<Node>();
+        node.as_ptr().write(Node {
+            size,
+            by_address: [None, None],
+            by_size: [None, None],
+        });
+        self.by_address
+            .set(Tree::Address.insert(self.by_address.get(), node));
+        self.by_size
+            .set(Tree::Size.insert(self.by_size.get(), node));
+        self.capacity_left.set(self.capacity_left.get() + size);
+        self.num_free_blocks.set(self.num_free_blocks.get() + 1);
+    }
+
+    unsafe fn remove(&self, node: NonNull<Node>) -> usize {
+        let size = (*node.as_ptr()).size;
+        self.by_address
+            .set(Tree::Address.remove(self.by_address.get(), node));
+        self.by_size
+            .set(Tree::Size.remove(self.by_size.get(), node));
+        self.capacity_left.set(self.capacity_left.get() - size);
+        self.num_free_blocks.set(self.num_free_blocks.get() - 1);
+        size
+    }
+
+    /// Returns a memory block to the free blocks and merges it with its free neighbors.
+    unsafe fn release(&self, mut ptr: NonNull<u8>, mut size: usize) {
+        let end = NonNull::new_unchecked(ptr.as_ptr().add(size));
+        if let Some(next) = find(self.by_address.get(), end) {
+            size += self.remove(next);
+        }
+        if let Some(previous) = predecessor(self.by_address.get(), ptr) {
+            let previous_end = previous
+                .cast::<u8>()
+                .as_ptr()
+                .add((*previous.as_ptr()).size);
+            if previous_end == ptr.as_ptr() {
+                size += self.remove(previous);
+                ptr = previous.cast();
+            }
+        }
+        self.insert(ptr, size);
+    }
+
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        if ptr.as_ptr() as usize & (new_layout.align() - 1) != 0 {
+            return Err(AllocError);
+        }
+        let old_size = Self::block_size(old_layout).ok_or(AllocError)?;
+        let new_size = Self::block_size(new_layout).ok_or(AllocError)?;
+        if new_size == old_size {
+            return Ok(new_size);
+        }
+
+        let end = NonNull::new_unchecked(ptr.as_ptr().add(old_size));
+        let next = find(self.by_address.get(), end).ok_or(AllocError)?;
+        let additional = new_size - old_size;
+        if (*next.as_ptr()).size < additional {
+            return Err(AllocError);
+        }
+        let next_size = self.remove(next);
+        if next_size != additional {
+            self.insert(
+                NonNull::new_unchecked(ptr.as_ptr().add(new_size)),
+                next_size - additional,
+            );
+        }
+        Ok(new_size)
+    }
+
+    unsafe fn shrink_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        if ptr.as_ptr() as usize & (new_layout.align() - 1) != 0 {
+            return Err(AllocError);
+        }
+        let old_size = Self::block_size(old_layout).ok_or(AllocError)?;
+        let new_size = Self::block_size(new_layout).ok_or(AllocError)?;
+        if new_size != old_size {
+            self.release(
+                NonNull::new_unchecked(ptr.as_ptr().add(new_size)),
+                old_size - new_size,
+            );
+        }
+        Ok(new_size)
+    }
+
+    unsafe fn grow_or_move(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match self.grow_impl(ptr, old_layout, new_layout) {
+            Ok(size) => {
+                let memory = NonNull::slice_from_raw_parts(ptr, size);
+                init.init_offset(memory, old_layout.size());
+                Ok(memory)
+            }
+            Err(_) => grow_fallback(self, self, ptr, old_layout, new_layout, init),
+        }
+    }
+}
+
+impl fmt::Debug for FreeTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FreeTree")
+            .field("capacity", &self.capacity())
+            .field("capacity_left", &self.capacity_left())
+            .field("num_free_blocks", &self.num_free_blocks())
+            .finish()
+    }
+}
+
+unsafe impl Allocator for FreeTree<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let size = Self::block_size(layout).ok_or(AllocError)?;
+        unsafe {
+            let (node, padding) =
+                best_fit(self.by_size.get(), size, layout.align()).ok_or(AllocError)?;
+            let node_size = self.remove(node);
+            if padding != 0 {
+                self.insert(node.cast(), padding);
+            }
+            let ptr = NonNull::new_unchecked(node.cast::<u8>().as_ptr().add(padding));
+            let rest = node_size - padding - size;
+            if rest != 0 {
+                self.insert(NonNull::new_unchecked(ptr.as_ptr().add(size)), rest);
+            }
+            Ok(NonNull::slice_from_raw_parts(ptr, size))
+        }
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if let Some(size) = Self::block_size(layout) {
+            self.release(ptr, size);
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_or_move(ptr, old_layout, new_layout, AllocInit::Uninitialized)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_or_move(ptr, old_layout, new_layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        match self.shrink_impl(ptr, old_layout, new_layout) {
+            Ok(size) => Ok(NonNull::slice_from_raw_parts(ptr, size)),
+            Err(_) => shrink_fallback(self, self, ptr, old_layout, new_layout),
+        }
+    }
+}
+
+unsafe impl ReallocateInPlace for FreeTree<'_> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let size = self.grow_impl(ptr, old_layout, new_layout)?;
+        AllocInit::Zeroed.init_offset(NonNull::slice_from_raw_parts(ptr, size), old_layout.size());
+        Ok(size)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.shrink_impl(ptr, old_layout, new_layout)
+    }
+}
+
+impl Owns for FreeTree<'_> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        let start = self.start.as_ptr() as usize;
+        let ptr = memory.as_mut_ptr() as usize;
+        ptr >= start && ptr + memory.len() <= start + self.capacity
+    }
+}
+
+impl ReportUsage for FreeTree<'_> {
+    fn usage(&self) -> Usage {
+        Usage::new(self.capacity(), self.capacity_left(), 0)
+    }
+}
+
+impl Describe for FreeTree<'_> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FreeTree({}, {} free in {} blocks, largest {})",
+            Bytes(self.capacity()),
+            Bytes(self.capacity_left()),
+            self.num_free_blocks(),
+            Bytes(self.largest_free_block()),
+        )
+    }
+}
+
+impl Purge for FreeTree<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{FreeTree, GRANULE};
+    use crate::{helper::tracker, Allocator, Owns, ReallocateInPlace};
+    use alloc::vec::Vec;
+    use core::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
+
+    #[repr(align(4096))]
+    struct Memory([MaybeUninit<u8>; 16 * 4096]);
+
+    #[test]
+    fn best_fit_and_in_place() {
+        let mut memory = Memory([MaybeUninit::uninit(); 16 * 4096]);
+        let alloc = FreeTree::new(&mut memory.0);
+        let capacity = alloc.capacity();
+        assert_eq!(capacity, 16 * 4096);
+
+        let layout = |granules: usize| {
+            Layout::from_size_align(granules * GRANULE - 1, 1).expect("Invalid layout")
+        };
+        let blocks = [1, 2, 1, 3, 1]
+            .iter()
+            .map(|&granules| {
+                let memory = alloc
+                    .allocate(layout(granules))
+                    .expect("Could not allocate");
+                assert_eq!(memory.len(), granules * GRANULE);
+                memory.as_non_null_ptr()
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            alloc.deallocate(blocks[3], layout(3));
+            alloc.deallocate(blocks[1], layout(2));
+            assert_eq!(alloc.num_free_blocks(), 3);
+
+            let memory = alloc.allocate(layout(2)).expect("Could not allocate");
+            assert_eq!(memory.as_non_null_ptr(), blocks[1]);
+
+            assert_eq!(
+                alloc.grow_in_place(blocks[2], layout(1), layout(4)),
+                Ok(4 * GRANULE)
+            );
+            assert!(alloc
+                .grow_in_place(blocks[2], layout(4), layout(5))
+                .is_err());
+            assert_eq!(
+                alloc.shrink_in_place(blocks[2], layout(4), layout(1)),
+                Ok(GRANULE)
+            );
+            assert_eq!(alloc.num_free_blocks(), 2);
+
+            let aligned = Layout::from_size_align(1, 4096).expect("Invalid layout");
+            let memory_aligned = alloc.allocate(aligned).expect("Could not allocate");
+            assert_eq!(memory_aligned.as_mut_ptr() as usize % 4096, 0);
+            assert!(alloc.owns(memory_aligned));
+
+            alloc.deallocate(memory_aligned.as_non_null_ptr(), aligned);
+            alloc.deallocate(memory.as_non_null_ptr(), layout(2));
+            for &(ptr, granules) in &[(blocks[0], 1), (blocks[2], 1), (blocks[4], 1)] {
+                alloc.deallocate(ptr, layout(granules));
+            }
+        }
+        assert_eq!(alloc.num_free_blocks(), 1);
+        assert_eq!(alloc.largest_free_block(), capacity);
+    }
+
+    #[test]
+    fn stress() {
+        let mut memory = Memory([MaybeUninit::uninit(); 16 * 4096]);
+        let alloc = tracker(FreeTree::new(&mut memory.0));
+        let mut blocks: Vec<(NonNull<u8>, Layout, u8)> = Vec::new();
+        let mut seed = 0x2545_F491_u32;
+        for i in 0..2000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let index = seed as usize % (blocks.len() + 1);
+            if index < blocks.len() && i % 3 != 0 {
+                let (ptr, layout, tag) = blocks.swap_remove(index);
+                unsafe {
+                    assert_eq!(*ptr.as_ptr(), tag);
+                    alloc.deallocate(ptr, layout);
+                }
+            } else {
+                let size = seed as usize % 1000;
+                let align = 1_usize << (seed % 10);
+                let layout = Layout::from_size_align(size, align).expect("Invalid layout");
+                if let Ok(memory) = alloc.allocate(layout) {
+                    assert_eq!(memory.as_mut_ptr() as usize % align, 0);
+                    unsafe { memory.as_mut_ptr().write(i as u8) };
+                    blocks.push((memory.as_non_null_ptr(), layout, i as u8));
+                }
+            }
+        }
+        for (ptr, layout, _) in blocks {
+            unsafe { alloc.deallocate(ptr, layout) };
+        }
+        assert_eq!(alloc.alloc.num_free_blocks(), 1);
+        assert_eq!(alloc.alloc.capacity_left(), alloc.alloc.capacity());
+    }
+}
@@ -0,0 +1,307 @@
+//! Operation generators for fuzzing allocator compositions.
+//!
+//! An [`OpSequence`] can be generated from unstructured fuzzer input via [`arbitrary`] and
+//! executed against any [`Allocator`]. While running, the executor checks the guarantees every
+//! allocator has to uphold and panics, if one is violated:
+//!
+//! - returned memory blocks are aligned and at least as large as requested,
+//! - live memory blocks do not overlap,
+//! - zeroed memory is actually zeroed, and
+//! - the contents of a memory block are preserved when growing or shrinking it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{
+//!     fuzzing::{ArbitraryOp, OpSequence},
+//!     region::Region,
+//! };
+//! use std::mem::MaybeUninit;
+//!
+//! let mut data = [MaybeUninit::new(0); 256];
+//! let ops = OpSequence {
+//!     ops: vec![
+//!         ArbitraryOp::Allocate {
+//!             size: 16,
+//!             align_shift: 3,
+//!             zeroed: true,
+//!         },
+//!         ArbitraryOp::Grow {
+//!             index: 0,
+//!             additional: 16,
+//!             zeroed: false,
+//!         },
+//!         ArbitraryOp::Deallocate { index: 0 },
+//!     ],
+//! };
+//! ops.run(&Region::new(&mut data));
+//! ```
+//!
+//! [`Allocator`]: core::alloc::Allocator
+
+use crate::Allocator;
+use alloc::{boxed::Box, vec::Vec};
+use arbitrary::Arbitrary;
+use core::{alloc::Layout, ptr::NonNull};
+
+/// The largest alignment generated is `1 << MAX_ALIGN_SHIFT`.
+const MAX_ALIGN_SHIFT: u8 = 12;
+
+/// A single operation on an allocator.
+///
+/// Memory blocks are referred to by their index in the list of live blocks. Indices are taken
+/// modulo the number of live blocks, operations on an empty list are skipped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Arbitrary)]
+pub enum ArbitraryOp {
+    /// Calls `allocate` or `allocate_zeroed` with an alignment of `1 << (align_shift % 13)`.
+    Allocate {
+        size: u16,
+        align_shift: u8,
+        zeroed: bool,
+    },
+    /// Calls `deallocate` on a live memory block.
+    Deallocate { index: usize },
+    /// Calls `grow` or `grow_zeroed` on a live memory block.
+    Grow {
+        index: usize,
+        additional: u16,
+        zeroed: bool,
+    },
+    /// Calls `shrink` on a live memory block. The new size is `size` modulo the current size.
+    Shrink { index: usize, size: u16 },
+}
+
+/// A sequence of operations, which can be run against an allocator.
+#[derive(Debug, Clone, PartialEq, Eq, Arbitrary)]
+pub struct OpSequence {
+    pub ops: Vec<ArbitraryOp>,
+}
+
+struct Block {
+    ptr: NonNull<[u8]>,
+    layout: Layout,
+    pattern: u8,
+}
+
+impl Block {
+    unsafe fn fill(&self) {
+        self.ptr
+            .as_mut_ptr()
+            .write_bytes(self.pattern, self.layout.size())
+    }
+
+    #[track_caller]
+    unsafe fn assert_filled(&self, len: usize) {
+        let data = core::slice::from_raw_parts(self.ptr.as_mut_ptr(), len);
+        assert!(
+            data.iter().all(|&b| b == self.pattern),
+            "contents of the memory block were not preserved"
+        );
+    }
+}
+
+#[track_caller]
+fn assert_fits(memory: NonNull<[u8]>, layout: Layout) {
+    assert!(
+        memory.len() >= layout.size(),
+        "returned memory block is smaller than requested: expected {} bytes, got {}",
+        layout.size(),
+        memory.len()
+    );
+    assert_eq!(
+        memory.as_mut_ptr() as usize % layout.align(),
+        0,
+        "returned memory block is not aligned to {}",
+        layout.align()
+    );
+}
+
+#[track_caller]
+unsafe fn assert_zeroed(memory: NonNull<[u8]>, range: core::ops::Range<usize>) {
+    let data = core::slice::from_raw_parts(memory.as_mut_ptr(), range.end);
+    assert!(
+        data[range].iter().all(|&b| b == 0),
+        "zeroed memory block contains non-zero bytes"
+    );
+}
+
+#[track_caller]
+fn assert_disjoint(blocks: &[Block], memory: NonNull<[u8]>, size: usize) {
+    let start = memory.as_mut_ptr() as usize;
+    for block in blocks {
+        let other = block.ptr.as_mut_ptr() as usize;
+        assert!(
+            start + size <= other || other + block.layout.size() <= start,
+            "returned memory block overlaps with a live memory block"
+        );
+    }
+}
+
+impl OpSequence {
+    /// Runs all operations against `alloc` and deallocates all live memory blocks afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `alloc` violates one of the checked invariants.
+    pub fn run<A: Allocator>(&self, alloc: &A) {
+        let mut blocks: Vec<Block> = Vec::new();
+
+        for (pattern, op) in self.ops.iter().enumerate() {
+            let pattern = pattern as u8 | 1;
+            unsafe { Self::step(alloc, &mut blocks, *op, pattern) };
+        }
+
+        for block in blocks {
+            unsafe {
+                block.assert_filled(block.layout.size());
+                alloc.deallocate(block.ptr.as_non_null_ptr(), block.layout)
+            }
+        }
+    }
+
+    unsafe fn step<A: Allocator>(alloc: &A, blocks: &mut Vec<Block>, op: ArbitraryOp, pattern: u8) {
+        match op {
+            ArbitraryOp::Allocate {
+                size,
+                align_shift,
+                zeroed,
+            } => {
+                let align = 1 << (align_shift % (MAX_ALIGN_SHIFT + 1));
+                let layout = Layout::from_size_align(size.into(), align).unwrap();
+                let result = if zeroed {
+                    alloc.allocate_zeroed(layout)
+                } else {
+                    alloc.allocate(layout)
+                };
+                if let Ok(ptr) = result {
+                    assert_fits(ptr, layout);
+                    assert_disjoint(blocks, ptr, layout.size());
+                    if zeroed {
+                        assert_zeroed(ptr, 0..layout.size());
+                    }
+                    let block = Block {
+                        ptr,
+                        layout,
+                        pattern,
+                    };
+                    block.fill();
+                    blocks.push(block);
+                }
+            }
+            ArbitraryOp::Deallocate { index } => {
+                if blocks.is_empty() {
+                    return;
+                }
+                let block = blocks.swap_remove(index % blocks.len());
+                block.assert_filled(block.layout.size());
+                alloc.deallocate(block.ptr.as_non_null_ptr(), block.layout);
+            }
+            ArbitraryOp::Grow {
+                index,
+                additional,
+                zeroed,
+            } => {
+                if blocks.is_empty() {
+                    return;
+                }
+                let index = index % blocks.len();
+                let old_layout = blocks[index].layout;
+                let new_size = old_layout.size() + usize::from(additional);
+                let new_layout = Layout::from_size_align(new_size, old_layout.align()).unwrap();
+                let ptr = blocks[index].ptr.as_non_null_ptr();
+                let result = if zeroed {
+                    alloc.grow_zeroed(ptr, old_layout, new_layout)
+                } else {
+                    alloc.grow(ptr, old_layout, new_layout)
+                };
+                if let Ok(ptr) = result {
+                    let mut block = blocks.swap_remove(index);
+                    assert_fits(ptr, new_layout);
+                    assert_disjoint(blocks, ptr, new_size);
+                    block.ptr = ptr;
+                    block.assert_filled(old_layout.size());
+                    if zeroed {
+                        assert_zeroed(ptr, old_layout.size()..new_size);
+                    }
+                    block.layout = new_layout;
+                    block.pattern = pattern;
+                    block.fill();
+                    blocks.push(block);
+                }
+            }
+            ArbitraryOp::Shrink { index, size } => {
+                if blocks.is_empty() {
+                    return;
+                }
+                let index = index % blocks.len();
+                let old_layout = blocks[index].layout;
+                let new_size = usize::from(size) % (old_layout.size() + 1);
+                let new_layout = Layout::from_size_align(new_size, old_layout.align()).unwrap();
+                let ptr = blocks[index].ptr.as_non_null_ptr();
+                if let Ok(ptr) = alloc.shrink(ptr, old_layout, new_layout) {
+                    let mut block = blocks.swap_remove(index);
+                    assert_fits(ptr, new_layout);
+                    assert_disjoint(blocks, ptr, new_size);
+                    block.ptr = ptr;
+                    block.layout = new_layout;
+                    block.assert_filled(new_size);
+                    blocks.push(block);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArbitraryOp, OpSequence};
+    use crate::{helper::tracker, region::Region, Chunk, Fallback};
+    use alloc::{alloc::Global, vec};
+    use arbitrary::{Arbitrary, Unstructured};
+    use core::mem::MaybeUninit;
+
+    fn sequence() -> OpSequence {
+        OpSequence {
+            ops: vec![
+                ArbitraryOp::Allocate {
+                    size: 24,
+                    align_shift: 3,
+                    zeroed: true,
+                },
+                ArbitraryOp::Allocate {
+                    size: 100,
+                    align_shift: 0,
+                    zeroed: false,
+                },
+                ArbitraryOp::Grow {
+                    index: 0,
+                    additional: 200,
+                    zeroed: true,
+                },
+                ArbitraryOp::Shrink { index: 1, size: 50 },
+                ArbitraryOp::Deallocate { index: 3 },
+            ],
+        }
+    }
+
+    #[test]
+    fn run() {
+        let mut data = [MaybeUninit::new(0); 256];
+        sequence().run(&tracker(Region::new(&mut data)));
+        sequence().run(&tracker(Chunk::<_, 64>(Region::new(&mut data))));
+        sequence().run(&Fallback {
+            primary: tracker(Region::new(&mut data)),
+            secondary: tracker(Global),
+        });
+    }
+
+    #[test]
+    fn arbitrary() {
+        let data = [0x55; 256];
+        let ops = OpSequence::arbitrary(&mut Unstructured::new(&data))
+            .expect("Could not generate operations");
+        ops.run(&tracker(Global));
+    }
+}
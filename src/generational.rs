@@ -0,0 +1,497 @@
+use crate::{
+    helper::{grow_fallback, shrink_fallback, AllocInit},
+    Affix,
+    AllocateAll,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ptr::NonNull,
+};
+
+/// A reset token returned by [`Generational::generation`].
+///
+/// Every call to [`AllocateAll::deallocate_all`] on a [`Generational`] allocator starts a new
+/// generation. Memory blocks remember the generation they were allocated in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Generation(usize);
+
+impl fmt::Display for Generation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// A debugging allocator, which detects the usage of memory blocks after the underlying allocator
+/// was reset.
+///
+/// Bump allocators like [`Region`] silently accept `deallocate`, `grow`, and `shrink` calls for
+/// memory, which was freed by [`deallocate_all`]. `Generational` stores the current
+/// [`Generation`] in a prefix of every allocated block and bumps the generation on every call
+/// to [`deallocate_all`]. Passing a memory block of an older generation to `deallocate`, `grow`, or
+/// `shrink` panics.
+///
+/// [`Region`]: crate::region::Region
+/// [`deallocate_all`]: crate::AllocateAll::deallocate_all
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, AllocateAll, Generational};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let alloc = Generational::new(Region::new(&mut data));
+///
+/// let token = alloc.generation();
+/// let _memory = alloc.allocate(Layout::new::<u32>())?;
+/// assert!(alloc.is_current(token));
+///
+/// alloc.deallocate_all();
+/// assert!(!alloc.is_current(token));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+///
+/// Using a memory block from a previous generation panics:
+///
+/// ```rust, should_panic
+/// # #![feature(allocator_api, slice_ptr_get)]
+/// # use alloc_compose::{region::Region, AllocateAll, Generational};
+/// # use core::{alloc::{Allocator, Layout}, mem::MaybeUninit};
+/// # let mut data = [MaybeUninit::uninit(); 64];
+/// # let alloc = Generational::new(Region::new(&mut data));
+/// let memory = alloc.allocate(Layout::new::<u32>())?;
+/// alloc.deallocate_all();
+///
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>()) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct Generational<A> {
+    alloc: Affix<A, Generation>,
+    generation: Cell<Generation>,
+    /// The block returned by `allocate_all` and its offset into the block of the parent.
+    all: Cell<Option<(NonNull<u8>, usize)>>,
+}
+
+impl<A> Generational<A> {
+    /// Creates a new generational allocator starting at the first generation.
+    pub const fn new(parent: A) -> Self {
+        Self {
+            alloc: Affix::new(parent),
+            generation: Cell::new(Generation(0)),
+            all: Cell::new(None),
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub const fn parent(&self) -> &A {
+        &self.alloc.parent
+    }
+
+    /// Returns the current generation.
+    #[inline]
+    pub fn generation(&self) -> Generation {
+        self.generation.get()
+    }
+
+    /// Returns if `generation` is the current generation, i.e. the allocator was not reset
+    /// since `generation` was obtained.
+    #[inline]
+    pub fn is_current(&self, generation: Generation) -> bool {
+        self.generation() == generation
+    }
+
+    /// Returns the generation, in which `ptr` was allocated.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory *[currently allocated]* via this allocator, and
+    /// * `layout` must *[fit]* that block of memory.
+    ///
+    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [fit]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#memory-fitting
+    #[inline]
+    pub unsafe fn generation_of(ptr: NonNull<u8>, layout: Layout) -> Generation {
+        Affix::<A, Generation>::prefix(ptr, layout).as_ptr().read()
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn check_generation(&self, ptr: NonNull<u8>, layout: Layout) {
+        let generation = Self::generation_of(ptr, layout);
+        assert_eq!(
+            generation,
+            self.generation(),
+            "`ptr` was allocated in generation {} but the allocator was reset and is in \
+             generation {} now",
+            generation,
+            self.generation()
+        );
+    }
+
+    #[inline]
+    fn tag(&self, memory: NonNull<[u8]>, layout: Layout) -> NonNull<[u8]> {
+        unsafe {
+            Affix::<A, Generation>::prefix(memory.as_non_null_ptr(), layout)
+                .as_ptr()
+                .write(self.generation())
+        }
+        memory
+    }
+
+    #[inline]
+    fn tag_all(&self, memory: NonNull<[u8]>) -> Result<NonNull<[u8]>, AllocError> {
+        // The returned block has to be usable with a layout of alignment `1`, so the prefix is
+        // placed right before the returned block. If the block is too small, it stays claimed
+        // until the next call to `deallocate_all`.
+        let offset = memory
+            .as_mut_ptr()
+            .align_offset(mem::align_of::<Generation>())
+            .checked_add(mem::size_of::<Generation>())
+            .ok_or(AllocError)?;
+        if offset > memory.len() {
+            return Err(AllocError);
+        }
+        let memory = NonNull::slice_from_raw_parts(
+            unsafe { NonNull::new_unchecked(memory.as_mut_ptr().add(offset)) },
+            memory.len() - offset,
+        );
+        let layout = unsafe { Layout::from_size_align_unchecked(memory.len(), 1) };
+        self.all.set(Some((memory.as_non_null_ptr(), offset)));
+        Ok(self.tag(memory, layout))
+    }
+
+    /// Returns the offset into the block of the parent, if `ptr` was returned by `allocate_all`.
+    #[inline]
+    fn all_offset(&self, ptr: NonNull<u8>) -> Option<usize> {
+        match self.all.get() {
+            Some((all, offset)) if all == ptr => Some(offset),
+            _ => None,
+        }
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for Generational<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Generational")
+            .field("parent", self.parent())
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
+
+/// Compares the parent allocators, the generation is not taken into account.
+impl<A: PartialEq> PartialEq for Generational<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.alloc == other.alloc
+    }
+}
+
+impl<A: Eq> Eq for Generational<A> {}
+
+impl<A: Hash> Hash for Generational<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.alloc.hash(state)
+    }
+}
+
+impl<A: Default> Default for Generational<A> {
+    fn default() -> Self {
+        Self::new(A::default())
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Generational<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate(layout).map(|m| self.tag(m, layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc
+            .allocate_zeroed(layout)
+            .map(|m| self.tag(m, layout))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.check_generation(ptr, layout);
+        if let Some(offset) = self.all_offset(ptr) {
+            // The prefix may not start at the block of the parent, so the block is rebuilt
+            self.all.set(None);
+            self.parent().deallocate(
+                NonNull::new_unchecked(ptr.as_ptr().sub(offset)),
+                Layout::from_size_align_unchecked(layout.size() + offset, 1),
+            )
+        } else {
+            self.alloc.deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.check_generation(ptr, old_layout);
+        if self.all_offset(ptr).is_some() {
+            let init = AllocInit::Uninitialized;
+            grow_fallback(self, self, ptr, old_layout, new_layout, init)
+        } else {
+            self.alloc.grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.check_generation(ptr, old_layout);
+        if self.all_offset(ptr).is_some() {
+            grow_fallback(self, self, ptr, old_layout, new_layout, AllocInit::Zeroed)
+        } else {
+            self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.check_generation(ptr, old_layout);
+        if self.all_offset(ptr).is_some() {
+            shrink_fallback(self, self, ptr, old_layout, new_layout)
+        } else {
+            self.alloc.shrink(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+unsafe impl<A: AllocateAll> AllocateAll for Generational<A> {
+    /// Allocates all memory of the parent allocator.
+    ///
+    /// The returned block may be passed to the other methods with a layout of alignment `1`.
+    /// Deallocating it returns the whole block to the parent allocator, while growing or
+    /// shrinking it moves its contents into a new memory block.
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.tag_all(self.parent().allocate_all()?)
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.tag_all(self.parent().allocate_all_zeroed()?)
+    }
+
+    /// Deallocates all memory and starts a new [`Generation`].
+    fn deallocate_all(&self) {
+        self.parent().deallocate_all();
+        self.all.set(None);
+        self.generation
+            .set(Generation(self.generation().0.wrapping_add(1)));
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.parent().capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.parent().capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.parent().is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.parent().is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.parent().reserve(additional)
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for Generational<A> {
+    fn usage(&self) -> Usage {
+        self.parent().usage()
+    }
+}
+
+impl<A: Owns> Owns for Generational<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.parent().owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.parent().owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe> Describe for Generational<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Generational(generation {})", self.generation())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(self.parent())
+    }
+}
+
+impl<A: Purge> Purge for Generational<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.parent().purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Generational;
+    use crate::{
+        helper::tracker,
+        region::{FixedRegion, Region},
+        AllocateAll,
+        Allocator,
+        Owns,
+    };
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn allocate() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = tracker(Generational::new(Region::new(&mut data)));
+        let token = alloc.alloc.generation();
+
+        let memory = alloc
+            .allocate(Layout::new::<u32>())
+            .expect("Could not allocate 4 bytes");
+        assert!(alloc.owns(memory));
+        unsafe {
+            assert_eq!(
+                Generational::<Region>::generation_of(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<u32>()
+                ),
+                token
+            );
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>());
+        }
+
+        alloc.deallocate_all();
+        assert!(alloc.is_empty());
+        assert!(!alloc.alloc.is_current(token));
+
+        let memory = alloc
+            .allocate_zeroed(Layout::new::<u32>())
+            .expect("Could not allocate 4 bytes");
+        unsafe {
+            assert_eq!(
+                Generational::<Region>::generation_of(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<u32>()
+                ),
+                alloc.alloc.generation()
+            );
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>());
+        }
+    }
+
+    #[test]
+    fn alloc_all() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = tracker(Generational::new(Region::new(&mut data)));
+
+        let memory = alloc.allocate_all().expect("Could not allocate all bytes");
+        assert!(alloc.is_full());
+        unsafe {
+            alloc.deallocate(
+                memory.as_non_null_ptr(),
+                Layout::from_size_align(memory.len(), 1).expect("Invalid layout"),
+            );
+        }
+    }
+
+    #[test]
+    fn alloc_all_unaligned() {
+        let region = FixedRegion::<64>::new();
+        let alloc = tracker(Generational::new(&region));
+
+        let byte = alloc
+            .allocate(Layout::new::<u8>())
+            .expect("Could not allocate 1 byte");
+        let capacity_left = alloc.capacity_left();
+        let memory = alloc.allocate_all().expect("Could not allocate all bytes");
+        assert!(alloc.is_full());
+        unsafe {
+            alloc.deallocate(
+                memory.as_non_null_ptr(),
+                Layout::from_size_align(memory.len(), 1).expect("Invalid layout"),
+            );
+            assert_eq!(alloc.capacity_left(), capacity_left);
+            alloc.deallocate(byte.as_non_null_ptr(), Layout::new::<u8>());
+        }
+    }
+
+    #[test]
+    #[should_panic = "`ptr` was allocated in generation 0"]
+    fn dealloc_after_reset() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = Generational::new(Region::new(&mut data));
+
+        let memory = alloc
+            .allocate(Layout::new::<u32>())
+            .expect("Could not allocate 4 bytes");
+        alloc.deallocate_all();
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>()) };
+    }
+
+    #[test]
+    #[should_panic = "`ptr` was allocated in generation 0"]
+    fn grow_after_reset() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = Generational::new(Region::new(&mut data));
+
+        let memory = alloc
+            .allocate(Layout::new::<u32>())
+            .expect("Could not allocate 4 bytes");
+        alloc.deallocate_all();
+        let _ = unsafe {
+            alloc.grow(
+                memory.as_non_null_ptr(),
+                Layout::new::<u32>(),
+                Layout::new::<u64>(),
+            )
+        };
+    }
+}
@@ -0,0 +1,129 @@
+use crate::Allocator;
+use core::{
+    alloc::{AllocError, GlobalAlloc, Layout},
+    ptr::{self, NonNull},
+};
+
+/// Implements [`GlobalAlloc`] on top of an [`Allocator`].
+///
+/// Every `GlobalAlloc` method is forwarded to exactly one `Allocator` method, so wrapped
+/// [`Proxy`]s and statistics see the same calls as if the allocator was used directly:
+///
+/// | `GlobalAlloc`                         | `Allocator`                       |
+/// |---------------------------------------|-----------------------------------|
+/// | `alloc`                               | `allocate`                        |
+/// | `alloc_zeroed`                        | `allocate_zeroed`                 |
+/// | `dealloc`                             | `deallocate`                      |
+/// | `realloc` with `new_size > old_size`  | `grow`                            |
+/// | `realloc` with `new_size < old_size`  | `shrink`                          |
+/// | `realloc` with `new_size == old_size` | none, the pointer is returned     |
+///
+/// `Allocator` methods return `Err` on failure, which is mapped to a null pointer. The length of
+/// the returned memory block is discarded, as `GlobalAlloc` has no way to report excess capacity.
+///
+/// Allocators in this crate, which implement `GlobalAlloc` directly, forward to this wrapper.
+///
+/// [`Proxy`]: crate::Proxy
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{stats::Counter, AsGlobalAlloc, Proxy};
+/// use std::alloc::{GlobalAlloc, Layout, System};
+///
+/// let alloc = AsGlobalAlloc(Proxy::new(System, Counter::default()));
+///
+/// let layout = Layout::new::<[u8; 16]>();
+/// unsafe {
+///     let ptr = alloc.alloc(layout);
+///     assert!(!ptr.is_null());
+///     let ptr = alloc.realloc(ptr, layout, 16);
+///     alloc.dealloc(ptr, layout);
+/// }
+/// assert_eq!(alloc.0.callbacks.num_allocs(), 1);
+/// assert_eq!(alloc.0.callbacks.num_grows(), 0);
+/// assert_eq!(alloc.0.callbacks.num_shrinks(), 0);
+/// ```
+///
+/// To use it as global allocator, the wrapped allocator has to be constructible in a `static`:
+///
+/// ```rust
+/// use alloc_compose::{AsGlobalAlloc, Chunk};
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static ALLOC: AsGlobalAlloc<Chunk<System, 16>> = AsGlobalAlloc(Chunk(System));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct AsGlobalAlloc<A>(pub A);
+
+impl<A> AsGlobalAlloc<A> {
+    /// Reinterprets a reference to an allocator as a reference to the wrapper.
+    #[inline]
+    pub fn from_ref(alloc: &A) -> &Self {
+        // SAFETY: `AsGlobalAlloc` is `repr(transparent)`
+        unsafe { &*(alloc as *const A).cast() }
+    }
+}
+
+unsafe impl<A: Allocator> GlobalAlloc for AsGlobalAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        into_ptr(self.0.allocate(layout))
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.deallocate(NonNull::new_unchecked(ptr), layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        into_ptr(self.0.allocate_zeroed(layout))
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let ptr = NonNull::new_unchecked(ptr);
+        if new_size > layout.size() {
+            into_ptr(self.0.grow(ptr, layout, new_layout))
+        } else if new_size < layout.size() {
+            into_ptr(self.0.shrink(ptr, layout, new_layout))
+        } else {
+            ptr.as_ptr()
+        }
+    }
+}
+
+#[inline]
+fn into_ptr(result: Result<NonNull<[u8]>, AllocError>) -> *mut u8 {
+    result.map_or(ptr::null_mut(), NonNull::as_mut_ptr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsGlobalAlloc;
+    use crate::{stats::Counter, Proxy};
+    use alloc::alloc::Global;
+    use core::alloc::{GlobalAlloc, Layout};
+
+    #[test]
+    fn forwarding() {
+        let alloc = AsGlobalAlloc(Proxy::new(Global, Counter::default()));
+        let layout = Layout::new::<[u8; 16]>();
+        unsafe {
+            let ptr = alloc.alloc_zeroed(layout);
+            assert!(!ptr.is_null());
+            assert_eq!(*ptr.add(15), 0);
+            let ptr = alloc.realloc(ptr, layout, 16);
+            let ptr = alloc.realloc(ptr, layout, 32);
+            let ptr = alloc.realloc(ptr, Layout::new::<[u8; 32]>(), 8);
+            alloc.dealloc(ptr, Layout::new::<[u8; 8]>());
+        }
+        let counter = &alloc.0.callbacks;
+        assert_eq!(counter.num_allocs(), 1);
+        assert_eq!(counter.num_grows(), 1);
+        assert_eq!(counter.num_shrinks(), 1);
+        assert_eq!(counter.num_deallocs(), 1);
+    }
+}
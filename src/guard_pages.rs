@@ -0,0 +1,338 @@
+use crate::{
+    helper::{grow_fallback, shrink_fallback, AllocInit},
+    os,
+    Allocator,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// The side of a memory block, on which [`GuardPages`] places the inaccessible page.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub enum GuardPosition {
+    /// The memory block ends right before the guard page. Catches overflows.
+    After,
+    /// The memory block starts right after the guard page. Catches underflows.
+    Before,
+}
+
+/// Places an inaccessible page next to every memory block of at least `THRESHOLD` bytes.
+///
+/// Like an electric fence, out-of-bounds accesses are not detected after the fact, but fault
+/// immediately. Every guarded memory block is page-aligned and padded by the underlying
+/// allocator, so it is placed right next to the guard page. This costs at least one page per
+/// memory block and a system call for every allocation and deallocation, so it is meant for debug
+/// builds.
+///
+/// Requests smaller than `THRESHOLD` bytes or with an alignment larger than the page size are
+/// forwarded to the underlying allocator unchanged.
+///
+/// The memory returned by the underlying allocator must be allowed to be protected, which is the
+/// case for heap allocators like [`System`] or [`Global`]. Before the memory is deallocated,
+/// the protection is removed again.
+///
+/// [`System`]: std::alloc::System
+/// [`Global`]: alloc::alloc::Global
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::GuardPages;
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = GuardPages::<_, 4096>::new(System);
+/// let memory = alloc.allocate(Layout::new::<[u8; 4096]>())?;
+///
+/// // Writing one byte past the memory block would abort the process:
+/// // unsafe { memory.as_mut_ptr().add(memory.len()).write(0) };
+///
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 4096]>()) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub struct GuardPages<A, const THRESHOLD: usize> {
+    pub alloc: A,
+    position: GuardPosition,
+}
+
+impl<A, const THRESHOLD: usize> GuardPages<A, THRESHOLD> {
+    /// Places guard pages after the memory blocks returned by `alloc`.
+    pub const fn new(alloc: A) -> Self {
+        Self::with_position(alloc, GuardPosition::After)
+    }
+
+    /// Places guard pages at `position` of the memory blocks returned by `alloc`.
+    pub const fn with_position(alloc: A, position: GuardPosition) -> Self {
+        Self { alloc, position }
+    }
+
+    /// Returns the side of the memory blocks, on which the guard pages are placed.
+    pub const fn position(&self) -> GuardPosition {
+        self.position
+    }
+
+    fn is_guarded(layout: Layout) -> bool {
+        layout.size() >= THRESHOLD && layout.align() <= os::page_size()
+    }
+
+    /// Returns the layout requested from the underlying allocator, the offset of the guard page
+    /// and the offset of the memory block.
+    fn outer_layout(&self, layout: Layout) -> Result<(Layout, usize, usize), AllocError> {
+        let page_size = os::page_size();
+        let data_len = os::round_to_pages(layout.size()).ok_or(AllocError)?;
+        let outer_size = data_len.checked_add(page_size).ok_or(AllocError)?;
+        let outer = Layout::from_size_align(outer_size, page_size).map_err(|_| AllocError)?;
+        Ok(match self.position {
+            GuardPosition::After => (
+                outer,
+                data_len,
+                (data_len - layout.size()) & !(layout.align() - 1),
+            ),
+            GuardPosition::Before => (outer, 0, page_size),
+        })
+    }
+
+    /// Returns the pointer returned by the underlying allocator for the memory block at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a guarded memory block allocated with `layout`.
+    unsafe fn outer_ptr(&self, ptr: NonNull<u8>, layout: Layout) -> NonNull<u8> {
+        let page_size = os::page_size();
+        let offset = match self.position {
+            GuardPosition::After => {
+                let addr = ptr.as_ptr() as usize;
+                let guard = (addr + layout.size() + page_size - 1) & !(page_size - 1);
+                addr - (guard - os::round_to_pages(layout.size()).unwrap_or(0))
+            }
+            GuardPosition::Before => page_size,
+        };
+        // The memory block lies `offset` bytes into the outer memory block
+        NonNull::new_unchecked(ptr.as_ptr().sub(offset))
+    }
+}
+
+impl<A: Default, const THRESHOLD: usize> Default for GuardPages<A, THRESHOLD> {
+    fn default() -> Self {
+        Self::new(A::default())
+    }
+}
+
+impl<A: Allocator, const THRESHOLD: usize> GuardPages<A, THRESHOLD> {
+    fn alloc_impl(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+        let (outer, guard, offset) = self.outer_layout(layout)?;
+        let memory = if zeroed {
+            self.alloc.allocate_zeroed(outer)?
+        } else {
+            self.alloc.allocate(outer)?
+        };
+
+        unsafe {
+            let base = memory.as_non_null_ptr();
+            let guard_ptr = NonNull::new_unchecked(base.as_ptr().add(guard));
+            if !os::protect(guard_ptr, os::page_size()) {
+                self.alloc.deallocate(base, outer);
+                return Err(AllocError);
+            }
+            let len = match self.position {
+                GuardPosition::After => guard - offset,
+                GuardPosition::Before => outer.size() - offset,
+            };
+            Ok(NonNull::slice_from_raw_parts(
+                NonNull::new_unchecked(base.as_ptr().add(offset)),
+                len,
+            ))
+        }
+    }
+}
+
+unsafe impl<A: Allocator, const THRESHOLD: usize> Allocator for GuardPages<A, THRESHOLD> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if Self::is_guarded(layout) {
+            self.alloc_impl(layout, false)
+        } else {
+            self.alloc.allocate(layout)
+        }
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if Self::is_guarded(layout) {
+            self.alloc_impl(layout, true)
+        } else {
+            self.alloc.allocate_zeroed(layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if !Self::is_guarded(layout) {
+            return self.alloc.deallocate(ptr, layout);
+        }
+
+        let (outer, guard, _) = match self.outer_layout(layout) {
+            Ok(outer) => outer,
+            Err(_) => return,
+        };
+        let base = self.outer_ptr(ptr, layout);
+        let guard_ptr = NonNull::new_unchecked(base.as_ptr().add(guard));
+        // Leak the memory rather than handing out inaccessible memory
+        if os::unprotect(guard_ptr, os::page_size()) {
+            self.alloc.deallocate(base, outer)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if Self::is_guarded(old_layout) || Self::is_guarded(new_layout) {
+            grow_fallback(
+                self,
+                self,
+                ptr,
+                old_layout,
+                new_layout,
+                AllocInit::Uninitialized,
+            )
+        } else {
+            self.alloc.grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if Self::is_guarded(old_layout) || Self::is_guarded(new_layout) {
+            grow_fallback(self, self, ptr, old_layout, new_layout, AllocInit::Zeroed)
+        } else {
+            self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if Self::is_guarded(old_layout) || Self::is_guarded(new_layout) {
+            shrink_fallback(self, self, ptr, old_layout, new_layout)
+        } else {
+            self.alloc.shrink(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+impl<A: Owns, const THRESHOLD: usize> Owns for GuardPages<A, THRESHOLD> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe, const THRESHOLD: usize> Describe for GuardPages<A, THRESHOLD> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GuardPages<{}>", THRESHOLD)
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, const THRESHOLD: usize> Purge for GuardPages<A, THRESHOLD> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GuardPages, GuardPosition};
+    use crate::{helper::tracker, os, Allocator};
+    use alloc::alloc::Global;
+    use core::alloc::Layout;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn after() {
+        let page_size = os::page_size();
+        let alloc = GuardPages::<_, 64>::new(tracker(Global));
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 32]>())
+            .expect("Could not allocate 32 bytes");
+        assert_eq!(memory.len(), 32);
+
+        unsafe {
+            let layout = Layout::from_size_align(100, 4).unwrap();
+            let memory = alloc
+                .grow(memory.as_non_null_ptr(), Layout::new::<[u8; 32]>(), layout)
+                .expect("Could not grow to 100 bytes");
+            assert_eq!((memory.as_mut_ptr() as usize + memory.len()) % page_size, 0);
+            assert_eq!(memory.as_mut_ptr() as usize % 4, 0);
+            memory.as_mut_ptr().write_bytes(1, memory.len());
+
+            let new_layout = Layout::from_size_align(page_size + 1, 4).unwrap();
+            let memory = alloc
+                .grow_zeroed(memory.as_non_null_ptr(), layout, new_layout)
+                .expect("Could not grow past a page");
+            assert_eq!((memory.as_mut_ptr() as usize + memory.len()) % page_size, 0);
+            assert_eq!(*memory.as_mut_ptr(), 1);
+            assert_eq!(*memory.as_mut_ptr().add(100), 0);
+            alloc.deallocate(memory.as_non_null_ptr(), new_layout);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn before() {
+        let page_size = os::page_size();
+        let alloc = GuardPages::<_, 0>::with_position(tracker(Global), GuardPosition::Before);
+        assert_eq!(alloc.position(), GuardPosition::Before);
+
+        let memory = alloc
+            .allocate_zeroed(Layout::new::<[u8; 100]>())
+            .expect("Could not allocate 100 bytes");
+        assert_eq!(memory.as_mut_ptr() as usize % page_size, 0);
+        assert_eq!(memory.len(), page_size);
+
+        unsafe {
+            memory.as_mut_ptr().write_bytes(1, 100);
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 100]>(),
+                    Layout::new::<[u8; 10]>(),
+                )
+                .expect("Could not shrink to 10 bytes");
+            assert_eq!(*memory.as_mut_ptr().add(9), 1);
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 10]>());
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    PolicyRef,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// Consults the provided policy before forwarding requests to the underlying allocator.
+///
+/// While a [`Proxy`] can only observe requests, `Guarded` enforces a [`PolicyRef`]: if the
+/// policy denies a request, `Err(AllocError)` is returned without touching the underlying
+/// allocator.
+///
+/// [`Proxy`]: crate::Proxy
+///
+/// # Examples
+///
+/// Deny allocations while in a critical section:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{Guarded, PolicyRef};
+/// use core::cell::Cell;
+/// use std::alloc::{AllocError, Allocator, Layout, System};
+///
+/// #[derive(Default)]
+/// struct CriticalSection(Cell<bool>);
+///
+/// impl PolicyRef for CriticalSection {
+///     fn check_allocate(&self, _layout: Layout) -> Result<(), AllocError> {
+///         if self.0.get() {
+///             Err(AllocError)
+///         } else {
+///             Ok(())
+///         }
+///     }
+/// }
+///
+/// let section = CriticalSection::default();
+/// let alloc = Guarded {
+///     alloc: System,
+///     policy: section.by_ref(),
+/// };
+///
+/// section.0.set(true);
+/// assert!(alloc.allocate(Layout::new::<u32>()).is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Guarded<A, P> {
+    pub alloc: A,
+    pub policy: P,
+}
+
+unsafe impl<A: Allocator, P: PolicyRef> Allocator for Guarded<A, P> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.policy.check_allocate(layout)?;
+        self.alloc.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.policy.check_allocate(layout)?;
+        self.alloc.allocate_zeroed(layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.policy.check_grow(ptr, old_layout, new_layout)?;
+        self.alloc.grow(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.policy.check_grow(ptr, old_layout, new_layout)?;
+        self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.policy.check_shrink(ptr, old_layout, new_layout)?;
+        self.alloc.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+unsafe impl<A: AllocateAll, P: PolicyRef> AllocateAll for Guarded<A, P> {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.policy.check_allocate_all()?;
+        self.alloc.allocate_all()
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.policy.check_allocate_all()?;
+        self.alloc.allocate_all_zeroed()
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.alloc.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.alloc.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.alloc.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.alloc.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace, P: PolicyRef> ReallocateInPlace for Guarded<A, P> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.policy.check_grow(ptr, old_layout, new_layout)?;
+        self.alloc.grow_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.policy.check_grow(ptr, old_layout, new_layout)?;
+        self.alloc.grow_in_place_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.policy.check_shrink(ptr, old_layout, new_layout)?;
+        self.alloc.shrink_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: ReportUsage, P> ReportUsage for Guarded<A, P> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns, P> Owns for Guarded<A, P> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: BlockSize, P> BlockSize for Guarded<A, P> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.alloc.block_size()
+    }
+}
+
+impl<A: Describe, P> Describe for Guarded<A, P> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Guarded")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, P> Purge for Guarded<A, P> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Guarded;
+    use crate::{
+        helper::tracker,
+        region::Region,
+        AllocateAll,
+        Allocator,
+        Chunk,
+        PolicyRef,
+        ReallocateInPlace,
+    };
+    use alloc::alloc::Global;
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        mem::MaybeUninit,
+        ptr::NonNull,
+    };
+
+    #[derive(Default)]
+    struct MaxSize {
+        max: usize,
+        denied: Cell<u32>,
+    }
+
+    impl MaxSize {
+        fn check(&self, size: usize) -> Result<(), AllocError> {
+            if size <= self.max {
+                Ok(())
+            } else {
+                self.denied.set(self.denied.get() + 1);
+                Err(AllocError)
+            }
+        }
+    }
+
+    impl PolicyRef for MaxSize {
+        fn check_allocate(&self, layout: Layout) -> Result<(), AllocError> {
+            self.check(layout.size())
+        }
+
+        fn check_allocate_all(&self) -> Result<(), AllocError> {
+            Err(AllocError)
+        }
+
+        fn check_grow(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<(), AllocError> {
+            self.check(new_layout.size())
+        }
+    }
+
+    #[test]
+    fn allocate() {
+        let policy = MaxSize {
+            max: 16,
+            ..MaxSize::default()
+        };
+        let alloc = Guarded {
+            alloc: tracker(Global),
+            policy: policy.by_ref(),
+        };
+
+        alloc
+            .allocate(Layout::new::<[u8; 32]>())
+            .expect_err("Could allocate 32 bytes");
+        alloc
+            .allocate_zeroed(Layout::new::<[u8; 32]>())
+            .expect_err("Could allocate 32 bytes");
+        assert_eq!(policy.denied.get(), 2);
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        unsafe {
+            alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::new::<[u8; 32]>(),
+                )
+                .expect_err("Could grow to 32 bytes");
+            assert_eq!(policy.denied.get(), 3);
+
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::new::<[u8; 8]>(),
+                )
+                .expect("Could not shrink to 8 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 8]>());
+        }
+    }
+
+    #[test]
+    fn in_place() {
+        let policy = MaxSize {
+            max: 64,
+            ..MaxSize::default()
+        };
+        let alloc = Guarded {
+            alloc: Chunk::<_, 64>(tracker(Global)),
+            policy: &policy,
+        };
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        unsafe {
+            alloc
+                .grow_in_place(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::new::<[u8; 32]>(),
+                )
+                .expect("Could not grow to 32 bytes");
+            alloc
+                .grow_in_place_zeroed(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 32]>(),
+                    Layout::new::<[u8; 128]>(),
+                )
+                .expect_err("Could grow to 128 bytes");
+            assert_eq!(policy.denied.get(), 1);
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 32]>());
+        }
+    }
+
+    #[test]
+    fn alloc_all() {
+        let mut data = [MaybeUninit::new(0); 32];
+        let alloc = Guarded {
+            alloc: Region::new(&mut data),
+            policy: MaxSize::default(),
+        };
+
+        alloc.allocate_all().expect_err("Could allocate all");
+        alloc.allocate_all_zeroed().expect_err("Could allocate all");
+        assert!(alloc.is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    describe::Bytes,
+    helper::AllocInit,
+    Allocator,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// An allocator that reserves `N` bytes directly in front of every memory block.
+///
+/// In contrast to [`Affix`], the header is not a Rust type but plain bytes, e.g. for headers
+/// expected by C code. As it has no alignment requirement of its own, the header always ends
+/// right where the memory block starts, so [`header`] only needs the pointer to the memory block.
+/// The header is neither initialized nor dropped. Growing and shrinking preserve it.
+///
+/// [`Affix`]: crate::Affix
+/// [`header`]: Self::header
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::HeaderBytes;
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = HeaderBytes::<_, 4>::new(System);
+///
+/// let memory = alloc.allocate(Layout::new::<u64>())?;
+/// unsafe {
+///     let header = HeaderBytes::<System, 4>::header(memory.as_non_null_ptr());
+///     header.as_ptr().write(*b"DATA");
+///     assert_eq!(memory.as_mut_ptr().sub(4).read(), b'D');
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u64>());
+/// }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HeaderBytes<A, const N: usize> {
+    pub parent: A,
+}
+
+impl<A, const N: usize> HeaderBytes<A, N> {
+    pub const fn new(parent: A) -> Self {
+        Self { parent }
+    }
+
+    /// Returns a pointer to the header of a memory block.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block of memory *[currently allocated]* via this allocator.
+    ///
+    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#currently-allocated-memory
+    pub unsafe fn header(ptr: NonNull<u8>) -> NonNull<[u8; N]> {
+        NonNull::new_unchecked(ptr.as_ptr().sub(N)).cast()
+    }
+
+    /// Returns the layout requested from the parent and the offset of the memory block in it.
+    const fn allocation_layout(layout: Layout) -> Option<(Layout, usize)> {
+        match Layout::new::<[u8; N]>().extend(layout) {
+            Ok(extended) => Some(extended),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the layout requested from the parent for a memory block of `layout`.
+    ///
+    /// Returns `None`, if the layout extended by the header overflows.
+    pub const fn parent_layout(layout: Layout) -> Option<Layout> {
+        match Self::allocation_layout(layout) {
+            Some((layout, _)) => Some(layout),
+            None => None,
+        }
+    }
+
+    /// Returns the number of bytes requested from the parent on top of `layout.size()`.
+    ///
+    /// The header is padded to the alignment of `layout`, so this may exceed `N`. Returns
+    /// `None`, if the layout extended by the header overflows.
+    pub const fn overhead(layout: Layout) -> Option<usize> {
+        match Self::parent_layout(layout) {
+            Some(parent) => Some(parent.size() - layout.size()),
+            None => None,
+        }
+    }
+
+    fn create_ptr(ptr: NonNull<[u8]>, offset: usize) -> NonNull<[u8]> {
+        let data = unsafe { NonNull::new_unchecked(ptr.as_mut_ptr().add(offset)) };
+        NonNull::slice_from_raw_parts(data, ptr.len() - offset)
+    }
+
+    #[inline]
+    fn alloc_impl(
+        layout: Layout,
+        alloc: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (layout, offset) = Self::allocation_layout(layout).ok_or(AllocError)?;
+        Ok(Self::create_ptr(alloc(layout)?, offset))
+    }
+
+    #[inline]
+    unsafe fn grow_impl(
+        old_ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        _init: AllocInit,
+        grow: impl FnOnce(NonNull<u8>, Layout, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Self::shrink_impl(old_ptr, old_layout, new_layout, grow)
+    }
+
+    #[inline]
+    unsafe fn shrink_impl(
+        old_ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        shrink: impl FnOnce(NonNull<u8>, Layout, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (old_alloc_layout, old_offset) =
+            Self::allocation_layout(old_layout).ok_or(AllocError)?;
+        let (new_alloc_layout, new_offset) =
+            Self::allocation_layout(new_layout).ok_or(AllocError)?;
+
+        // The parent only preserves the bytes relative to the base pointer, so the header must not
+        // move inside of the block.
+        if old_offset != new_offset {
+            return Err(AllocError);
+        }
+
+        let old_base_ptr = NonNull::new_unchecked(old_ptr.as_ptr().sub(old_offset));
+        let new_base_ptr = shrink(old_base_ptr, old_alloc_layout, new_alloc_layout)?;
+        Ok(Self::create_ptr(new_base_ptr, new_offset))
+    }
+}
+
+unsafe impl<A: Allocator, const N: usize> Allocator for HeaderBytes<A, N> {
+    impl_allocator!(parent);
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+
+        if let Some((layout, offset)) = Self::allocation_layout(layout) {
+            self.parent
+                .deallocate(NonNull::new_unchecked(ptr.as_ptr().sub(offset)), layout)
+        }
+    }
+}
+
+unsafe impl<A: ReallocateInPlace, const N: usize> ReallocateInPlace for HeaderBytes<A, N> {
+    impl_realloc_in_place!(parent);
+}
+
+impl<A: Owns, const N: usize> Owns for HeaderBytes<A, N> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.parent.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.parent.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe, const N: usize> Describe for HeaderBytes<A, N> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HeaderBytes({})", Bytes(N))
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.parent)
+    }
+}
+
+impl<A: Purge, const N: usize> Purge for HeaderBytes<A, N> {
+    fn purge(&self, level: PurgeLevel) {
+        self.parent.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HeaderBytes;
+    use crate::{helper::tracker, Allocator, Chunk};
+    use alloc::alloc::Global;
+    use core::alloc::Layout;
+
+    #[test]
+    fn header() {
+        let alloc = tracker(HeaderBytes::<_, 3>::new(Chunk::<_, 32>(Global)));
+        let layout = Layout::from_size_align(5, 16).expect("Invalid layout");
+        unsafe {
+            let memory = alloc.allocate(layout).expect("Could not allocate 5 bytes");
+            assert_eq!(memory.as_mut_ptr() as usize % 16, 0);
+            assert_eq!(memory.len(), 32 - 16);
+            HeaderBytes::<Global, 3>::header(memory.as_non_null_ptr())
+                .as_ptr()
+                .write([1, 2, 3]);
+
+            let grown = Layout::from_size_align(40, 16).expect("Invalid layout");
+            let memory = alloc
+                .grow_zeroed(memory.as_non_null_ptr(), layout, grown)
+                .expect("Could not grow to 40 bytes");
+            assert_eq!(
+                *HeaderBytes::<Global, 3>::header(memory.as_non_null_ptr()).as_ptr(),
+                [1, 2, 3]
+            );
+            assert_eq!(*memory.as_mut_ptr().add(39), 0);
+
+            let realigned = Layout::from_size_align(40, 1).expect("Invalid layout");
+            assert!(alloc
+                .shrink(memory.as_non_null_ptr(), grown, realigned)
+                .is_err());
+            alloc.deallocate(memory.as_non_null_ptr(), grown);
+        }
+    }
+}
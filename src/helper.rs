@@ -1,5 +1,7 @@
+use crate::Allocator;
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
+    alloc::{AllocError, Layout},
+    hash::Hasher,
     ptr::{self, NonNull},
 };
 
@@ -27,13 +29,43 @@ impl AllocInit {
     }
 }
 
+/// Advances `state` and returns the next output of the SplitMix64 generator.
+pub(crate) fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A Fowler-Noll-Vo hasher, which unlike `DefaultHasher` is stable across runs and releases.
+pub(crate) struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+}
+
 // #[derive(Copy, Clone, PartialEq, Eq)]
 // pub enum ReallocPlacement {
 //     MayMove,
 //     InPlace,
 // }
 
-pub(in crate) unsafe fn grow_fallback<A1: AllocRef, A2: AllocRef>(
+pub(crate) unsafe fn grow_fallback<A1: Allocator, A2: Allocator>(
     a1: &A1,
     a2: &A2,
     ptr: NonNull<u8>,
@@ -42,29 +74,29 @@ pub(in crate) unsafe fn grow_fallback<A1: AllocRef, A2: AllocRef>(
     init: AllocInit,
 ) -> Result<NonNull<[u8]>, AllocError> {
     let new_ptr = match init {
-        AllocInit::Uninitialized => a2.alloc(new_layout)?,
-        AllocInit::Zeroed => a2.alloc_zeroed(new_layout)?,
+        AllocInit::Uninitialized => a2.allocate(new_layout)?,
+        AllocInit::Zeroed => a2.allocate_zeroed(new_layout)?,
     };
     ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), old_layout.size());
-    a1.dealloc(ptr, old_layout);
+    a1.deallocate(ptr, old_layout);
     Ok(new_ptr)
 }
 
-pub(in crate) unsafe fn shrink_fallback<A1: AllocRef, A2: AllocRef>(
+pub(crate) unsafe fn shrink_fallback<A1: Allocator, A2: Allocator>(
     a1: &A1,
     a2: &A2,
     ptr: NonNull<u8>,
     old_layout: Layout,
     new_layout: Layout,
 ) -> Result<NonNull<[u8]>, AllocError> {
-    let new_ptr = a2.alloc(new_layout)?;
+    let new_ptr = a2.allocate(new_layout)?;
     ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), new_layout.size());
-    a1.dealloc(ptr, old_layout);
+    a1.deallocate(ptr, old_layout);
     Ok(new_ptr)
 }
 
 #[cfg(test)]
-pub fn tracker<A: AllocRef>(alloc: A) -> crate::Proxy<A, impl crate::CallbackRef> {
+pub fn tracker<A: Allocator>(alloc: A) -> crate::Proxy<A, impl crate::CallbackRef> {
     crate::Proxy {
         alloc,
         callbacks: self::tests::Tracker::default(),
@@ -74,10 +106,10 @@ pub fn tracker<A: AllocRef>(alloc: A) -> crate::Proxy<A, impl crate::CallbackRef
 #[cfg(test)]
 mod tests {
     use super::tracker;
-    use crate::{CallbackRef, Chunk};
+    use crate::{Allocator, CallbackRef, Chunk};
     use alloc::{alloc::Global, collections::BTreeMap};
     use core::{
-        alloc::{AllocError, AllocRef, Layout},
+        alloc::{AllocError, Layout},
         cell::RefCell,
         ptr::NonNull,
     };
@@ -295,13 +327,13 @@ mod tests {
         }
     }
 
-    struct DeallocGuard<A: AllocRef> {
+    struct DeallocGuard<A: Allocator> {
         allocator: A,
         ptr: NonNull<u8>,
         layout: Layout,
     }
 
-    impl<A: AllocRef> DeallocGuard<A> {
+    impl<A: Allocator> DeallocGuard<A> {
         fn new(allocator: A, ptr: NonNull<[u8]>, layout: Layout) -> Self {
             Self {
                 allocator,
@@ -311,9 +343,9 @@ mod tests {
         }
     }
 
-    impl<A: AllocRef> Drop for DeallocGuard<A> {
+    impl<A: Allocator> Drop for DeallocGuard<A> {
         fn drop(&mut self) {
-            unsafe { self.allocator.dealloc(self.ptr, self.layout) }
+            unsafe { self.allocator.deallocate(self.ptr, self.layout) }
         }
     }
 
@@ -322,7 +354,7 @@ mod tests {
     fn tracker_grow_size_greater_layout() {
         let alloc = tracker(Global);
         let layout = Layout::new::<[u8; 4]>();
-        let memory = alloc.alloc(layout).expect("Could not allocate 4 bytes");
+        let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
         let _guard = DeallocGuard::new(Global, memory, layout);
         let _ = unsafe { alloc.grow(memory.as_non_null_ptr(), layout, Layout::new::<[u8; 2]>()) };
     }
@@ -332,7 +364,7 @@ mod tests {
     fn tracker_grow_layout_size_exact() {
         let alloc = tracker(Global);
         let layout = Layout::new::<[u8; 4]>();
-        let memory = alloc.alloc(layout).expect("Could not allocate 4 bytes");
+        let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
         let _guard = DeallocGuard::new(Global, memory, layout);
         let _ = unsafe {
             alloc.grow(
@@ -348,7 +380,7 @@ mod tests {
     fn tracker_grow_layout_size_range() {
         let alloc = tracker(Chunk::<Global, 32>::default());
         let layout = Layout::new::<[u8; 4]>();
-        let memory = alloc.alloc(layout).expect("Could not allocate 4 bytes");
+        let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
         let _guard = DeallocGuard::new(Chunk::<Global, 32>::default(), memory, layout);
         let _ = unsafe {
             alloc.grow(
@@ -364,7 +396,7 @@ mod tests {
     fn tracker_grow_layout_align() {
         let alloc = tracker(Global);
         let layout = Layout::new::<[u8; 4]>();
-        let memory = alloc.alloc(layout).expect("Could not allocate 4 bytes");
+        let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
         let _guard = DeallocGuard::new(Global, memory, layout);
         let _ = unsafe {
             alloc.grow(
@@ -380,7 +412,7 @@ mod tests {
     fn tracker_grow_ptr() {
         let alloc = tracker(Global);
         let layout = Layout::new::<[u8; 4]>();
-        let memory = alloc.alloc(layout).expect("Could not allocate 4 bytes");
+        let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
         let _guard = DeallocGuard::new(Global, memory, layout);
         let _ = unsafe {
             alloc.grow(
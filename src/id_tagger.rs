@@ -0,0 +1,350 @@
+use crate::{Affix, Allocator, CallbackRef, Describe, Owns, Purge, PurgeLevel};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Tags every memory block with a unique id and passes it to the `*_with_id` hooks of the
+/// callbacks.
+///
+/// Pointers are reused after deallocation, so correlating logged events by pointer is ambiguous.
+/// `IdTagger` stores a monotonically increasing id in front of each memory block, which stays the
+/// same when the block is grown or shrunk. The callbacks receive it through
+/// [`after_allocate_with_id`], [`before_deallocate_with_id`], and the other `*_with_id` hooks,
+/// so an event log can be stitched into the lifecycle of each allocation.
+///
+/// Every allocation request draws a new id, even if it fails. The `before_*` hooks are invoked
+/// like with [`Proxy`].
+///
+/// [`after_allocate_with_id`]: CallbackRef::after_allocate_with_id
+/// [`before_deallocate_with_id`]: CallbackRef::before_deallocate_with_id
+/// [`Proxy`]: crate::Proxy
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{CallbackRef, IdTagger};
+/// use core::{
+///     alloc::{AllocError, Layout},
+///     cell::RefCell,
+///     ptr::NonNull,
+/// };
+/// use std::alloc::{Allocator, System};
+///
+/// #[derive(Default)]
+/// struct Log(RefCell<Vec<(&'static str, u64)>>);
+///
+/// unsafe impl CallbackRef for Log {
+///     fn after_allocate_with_id(
+///         &self,
+///         id: u64,
+///         _layout: Layout,
+///         _result: Result<NonNull<[u8]>, AllocError>,
+///     ) {
+///         self.0.borrow_mut().push(("alloc", id))
+///     }
+///
+///     fn before_deallocate_with_id(&self, id: u64, _ptr: NonNull<u8>, _layout: Layout) {
+///         self.0.borrow_mut().push(("dealloc", id))
+///     }
+/// }
+///
+/// let log = Log::default();
+/// let alloc = IdTagger::new(System, log.by_ref());
+///
+/// let layout = Layout::new::<u32>();
+/// let first = alloc.allocate(layout)?;
+/// let second = alloc.allocate(layout)?;
+/// unsafe {
+///     alloc.deallocate(first.as_non_null_ptr(), layout);
+///     alloc.deallocate(second.as_non_null_ptr(), layout);
+/// }
+///
+/// assert_eq!(*log.0.borrow(), [
+///     ("alloc", 0),
+///     ("alloc", 1),
+///     ("dealloc", 0),
+///     ("dealloc", 1)
+/// ]);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "64")))]
+pub struct IdTagger<A, C> {
+    alloc: Affix<A, u64>,
+    pub callbacks: C,
+    next_id: AtomicU64,
+}
+
+impl<A, C> IdTagger<A, C> {
+    /// Tags the memory blocks of `alloc`, starting with id zero.
+    pub const fn new(alloc: A, callbacks: C) -> Self {
+        Self {
+            alloc: Affix::new(alloc),
+            callbacks,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the underlying allocator.
+    pub const fn parent(&self) -> &A {
+        &self.alloc.parent
+    }
+
+    /// Returns the id, which will be assigned to the next allocation request.
+    pub fn next_id(&self) -> u64 {
+        self.next_id.load(Ordering::Relaxed)
+    }
+
+    /// Returns the id of a memory block.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory *[currently allocated]* via this allocator, and
+    /// * `layout` must *[fit]* that block of memory.
+    ///
+    /// [currently allocated]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [fit]: https://doc.rust-lang.org/nightly/core/alloc/trait.Allocator.html#memory-fitting
+    pub unsafe fn id(ptr: NonNull<u8>, layout: Layout) -> u64 {
+        Affix::<A, u64>::prefix(ptr, layout).as_ptr().read()
+    }
+
+    fn alloc_impl(
+        &self,
+        layout: Layout,
+        alloc: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> (u64, Result<NonNull<[u8]>, AllocError>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let result = alloc(layout);
+        if let Ok(memory) = result {
+            unsafe {
+                Affix::<A, u64>::prefix(memory.as_non_null_ptr(), layout)
+                    .as_ptr()
+                    .write(id)
+            };
+        }
+        (id, result)
+    }
+}
+
+impl<A: fmt::Debug, C: fmt::Debug> fmt::Debug for IdTagger<A, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdTagger")
+            .field("parent", self.parent())
+            .field("callbacks", &self.callbacks)
+            .field("next_id", &self.next_id())
+            .finish()
+    }
+}
+
+unsafe impl<A: Allocator, C: CallbackRef> Allocator for IdTagger<A, C> {
+    #[track_caller]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.callbacks.before_allocate(layout);
+        let (id, result) = self.alloc_impl(layout, |layout| self.alloc.allocate(layout));
+        self.callbacks.after_allocate_with_id(id, layout, result);
+        result
+    }
+
+    #[track_caller]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.callbacks.before_allocate_zeroed(layout);
+        let (id, result) = self.alloc_impl(layout, |layout| self.alloc.allocate_zeroed(layout));
+        self.callbacks
+            .after_allocate_zeroed_with_id(id, layout, result);
+        result
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        let id = Self::id(ptr, layout);
+        self.callbacks.before_deallocate_with_id(id, ptr, layout);
+        self.alloc.deallocate(ptr, layout);
+        self.callbacks.after_deallocate_with_id(id, ptr, layout);
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let id = Self::id(ptr, old_layout);
+        self.callbacks.before_grow(ptr, old_layout, new_layout);
+        let result = self.alloc.grow(ptr, old_layout, new_layout);
+        self.callbacks
+            .after_grow_with_id(id, ptr, old_layout, new_layout, result);
+        result
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let id = Self::id(ptr, old_layout);
+        self.callbacks
+            .before_grow_zeroed(ptr, old_layout, new_layout);
+        let result = self.alloc.grow_zeroed(ptr, old_layout, new_layout);
+        self.callbacks
+            .after_grow_zeroed_with_id(id, ptr, old_layout, new_layout, result);
+        result
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        let id = Self::id(ptr, old_layout);
+        self.callbacks.before_shrink(ptr, old_layout, new_layout);
+        let result = self.alloc.shrink(ptr, old_layout, new_layout);
+        self.callbacks
+            .after_shrink_with_id(id, ptr, old_layout, new_layout, result);
+        result
+    }
+}
+
+impl<A: Owns, C: CallbackRef> Owns for IdTagger<A, C> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.callbacks.before_owns();
+        let owns = self.alloc.owns(memory);
+        self.callbacks.after_owns(owns);
+        owns
+    }
+}
+
+impl<A: Describe, C> Describe for IdTagger<A, C> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IdTagger(next id: {})", self.next_id())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(self.parent())
+    }
+}
+
+impl<A: Purge, C> Purge for IdTagger<A, C> {
+    fn purge(&self, level: PurgeLevel) {
+        self.parent().purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdTagger;
+    use crate::{
+        helper::tracker,
+        region::Region,
+        stats::Counter,
+        Allocator,
+        CallbackRef,
+        Fallback,
+    };
+    use alloc::{alloc::Global, vec::Vec};
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::RefCell,
+        mem::MaybeUninit,
+        ptr::NonNull,
+    };
+
+    #[derive(Default)]
+    struct Lifecycle(RefCell<Vec<(u64, usize)>>);
+
+    unsafe impl CallbackRef for Lifecycle {
+        fn after_allocate_with_id(
+            &self,
+            id: u64,
+            layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.0.borrow_mut().push((id, layout.size()))
+            }
+        }
+
+        fn after_grow_with_id(
+            &self,
+            id: u64,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            _result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.0.borrow_mut().push((id, new_layout.size()))
+        }
+
+        fn after_deallocate_with_id(&self, id: u64, _ptr: NonNull<u8>, _layout: Layout) {
+            self.0.borrow_mut().push((id, 0))
+        }
+    }
+
+    #[test]
+    fn lifecycle() {
+        let mut data = [MaybeUninit::uninit(); 64];
+        let lifecycle = Lifecycle::default();
+        let alloc = tracker(IdTagger::new(
+            Fallback {
+                primary: Region::new(&mut data),
+                secondary: Global,
+            },
+            lifecycle.by_ref(),
+        ));
+
+        unsafe {
+            let first = alloc
+                .allocate(Layout::new::<[u8; 8]>())
+                .expect("Could not allocate 8 bytes");
+            alloc.deallocate(first.as_non_null_ptr(), Layout::new::<[u8; 8]>());
+            let second = alloc
+                .allocate(Layout::new::<[u8; 8]>())
+                .expect("Could not allocate 8 bytes");
+            let second = alloc
+                .grow(
+                    second.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 128]>(),
+                )
+                .expect("Could not grow to 128 bytes");
+            assert_eq!(
+                IdTagger::<Global, ()>::id(second.as_non_null_ptr(), Layout::new::<[u8; 128]>()),
+                1
+            );
+            alloc.deallocate(second.as_non_null_ptr(), Layout::new::<[u8; 128]>());
+        }
+
+        assert_eq!(alloc.alloc.next_id(), 2);
+        assert_eq!(*lifecycle.0.borrow(), [
+            (0, 8),
+            (0, 0),
+            (1, 8),
+            (1, 128),
+            (1, 0)
+        ]);
+    }
+
+    #[test]
+    fn default_hooks() {
+        let counter = Counter::default();
+        let alloc = tracker(IdTagger::new(Global, counter.by_ref()));
+        let memory = alloc
+            .allocate(Layout::new::<u32>())
+            .expect("Could not allocate 4 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>()) };
+        assert_eq!(counter.num_allocs(), 1);
+        assert_eq!(counter.num_deallocs(), 1);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    hash::{Hash, Hasher},
+    ptr::NonNull,
+};
+
+/// Runs every request to the underlying allocator inside of a [`critical_section::with`].
+///
+/// Allocators like [`Region`] or [`GrowablePool`] use interior mutability without
+/// synchronization, so they can't be shared between the main context and interrupt handlers. On
+/// single-core embedded targets, disabling interrupts while a request is served is sufficient
+/// and much cheaper than a lock. `InterruptSafe` does this through the [`critical-section`]
+/// crate, so it is `Sync` and can be used as global allocator. The target has to provide a
+/// critical section implementation, e.g. by the HAL or the `std` feature of `critical-section`.
+///
+/// Requests should stay short, as interrupts are delayed while they are served. The underlying
+/// allocator is only reachable through [`with`], which enters a critical section as well. This
+/// also applies to the implementations of `Debug`, `Clone`, `PartialEq`, and `Hash`, so
+/// `InterruptSafe` is not `Copy`.
+///
+/// [`Region`]: crate::region::Region
+/// [`GrowablePool`]: crate::GrowablePool
+/// [`critical-section`]: https://docs.rs/critical-section
+/// [`with`]: Self::with
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{GrowablePool, InterruptSafe};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// static ALLOC: InterruptSafe<GrowablePool<System, 64, 16>> =
+///     InterruptSafe::new(GrowablePool::new(System));
+///
+/// let memory = ALLOC.allocate(Layout::new::<[u8; 48]>())?;
+/// assert_eq!(memory.len(), 64);
+/// assert_eq!(ALLOC.with(|pool| pool.num_slabs()), 1);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[cfg_attr(doc, doc(cfg(feature = "critical-section")))]
+#[derive(Default)]
+pub struct InterruptSafe<A> {
+    alloc: A,
+}
+
+impl<A> InterruptSafe<A> {
+    /// Serves the requests to `alloc` inside of critical sections.
+    #[inline]
+    pub const fn new(alloc: A) -> Self {
+        Self { alloc }
+    }
+
+    /// Calls `f` with the underlying allocator inside of a critical section.
+    #[inline]
+    pub fn with<R>(&self, f: impl FnOnce(&A) -> R) -> R {
+        critical_section::with(|_| f(&self.alloc))
+    }
+
+    /// Returns the underlying allocator.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.alloc
+    }
+}
+
+// SAFETY: the underlying allocator is only accessed inside of a critical section, so no two
+// contexts access it concurrently.
+unsafe impl<A: Send> Sync for InterruptSafe<A> {}
+
+impl<A: fmt::Debug> fmt::Debug for InterruptSafe<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        critical_section::with(|_| {
+            f.debug_struct("InterruptSafe")
+                .field("alloc", &self.alloc)
+                .finish()
+        })
+    }
+}
+
+impl<A: Clone> Clone for InterruptSafe<A> {
+    fn clone(&self) -> Self {
+        Self::new(self.with(A::clone))
+    }
+}
+
+impl<A: PartialEq> PartialEq for InterruptSafe<A> {
+    fn eq(&self, other: &Self) -> bool {
+        critical_section::with(|_| self.alloc == other.alloc)
+    }
+}
+
+impl<A: Eq> Eq for InterruptSafe<A> {}
+
+impl<A: Hash> Hash for InterruptSafe<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.with(|alloc| alloc.hash(state))
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for InterruptSafe<A> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        critical_section::with(|_| self.alloc.allocate(layout))
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        critical_section::with(|_| self.alloc.allocate_zeroed(layout))
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        critical_section::with(|_| self.alloc.deallocate(ptr, layout))
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        critical_section::with(|_| self.alloc.grow(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        critical_section::with(|_| self.alloc.grow_zeroed(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        critical_section::with(|_| self.alloc.shrink(ptr, old_layout, new_layout))
+    }
+}
+
+unsafe impl<A: AllocateAll> AllocateAll for InterruptSafe<A> {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        critical_section::with(|_| self.alloc.allocate_all())
+    }
+
+    #[inline]
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        critical_section::with(|_| self.alloc.allocate_all_zeroed())
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        critical_section::with(|_| self.alloc.deallocate_all())
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        critical_section::with(|_| self.alloc.capacity())
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        critical_section::with(|_| self.alloc.capacity_left())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        critical_section::with(|_| self.alloc.is_empty())
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        critical_section::with(|_| self.alloc.is_full())
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        critical_section::with(|_| self.alloc.reserve(additional))
+    }
+}
+
+impl<A: Owns> Owns for InterruptSafe<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        critical_section::with(|_| self.alloc.owns(memory))
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        critical_section::with(|_| self.alloc.owns_ptr(ptr))
+    }
+}
+
+impl<A: BlockSize> BlockSize for InterruptSafe<A> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        critical_section::with(|_| self.alloc.block_size())
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for InterruptSafe<A> {
+    fn usage(&self) -> Usage {
+        critical_section::with(|_| self.alloc.usage())
+    }
+}
+
+impl<A: Describe> Describe for InterruptSafe<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InterruptSafe")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        critical_section::with(|_| visit(&self.alloc))
+    }
+}
+
+impl<A: Purge> Purge for InterruptSafe<A> {
+    fn purge(&self, level: PurgeLevel) {
+        critical_section::with(|_| self.alloc.purge(level))
+    }
+}
+
+impl_global_alloc!(impl<A: Allocator> InterruptSafe<A>);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::InterruptSafe;
+    use crate::{region::Region, Allocator, ReportUsage};
+    use alloc::vec::Vec;
+    use core::{alloc::Layout, mem::MaybeUninit};
+    use std::thread;
+
+    #[test]
+    fn shared() {
+        let mut data = [MaybeUninit::uninit(); 4096];
+        let alloc = InterruptSafe::new(Region::new(&mut data));
+        let layout = Layout::new::<[u8; 16]>();
+
+        let mut blocks = thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..16)
+                            .map(|_| {
+                                alloc
+                                    .allocate(layout)
+                                    .expect("Could not allocate 16 bytes")
+                                    .as_mut_ptr() as usize
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .flat_map(|thread| thread.join().expect("Thread panicked"))
+                .collect::<Vec<_>>()
+        });
+        blocks.sort_unstable();
+        blocks.dedup();
+        assert_eq!(blocks.len(), 64);
+        assert_eq!(alloc.usage().used, 64 * 16);
+        assert_eq!(alloc.with(ReportUsage::usage), alloc.usage());
+    }
+}
@@ -1,22 +1,14 @@
 #![no_std]
-#![cfg_attr(doc, feature(doc_cfg, external_doc))]
-#![cfg_attr(feature = "intrinsics", feature(core_intrinsics))]
-#![cfg_attr(doc, doc(include = "../README.md"))]
-#![feature(
-    min_const_generics,
-    specialization,
-    allocator_api,
-    nonnull_slice_from_raw_parts,
-    const_nonnull_slice_from_raw_parts,
-    slice_ptr_get,
-    slice_ptr_len,
-    const_slice_ptr_len
-)]
-#![cfg_attr(test, feature(maybe_uninit_slice))]
+#![cfg_attr(doc, feature(doc_cfg))]
+#![cfg_attr(feature = "intrinsics", feature(core_intrinsics), allow(internal_features))]
+#![cfg_attr(doc, doc = include_str!("../README.md"))]
+#![feature(specialization, allocator_api, slice_ptr_get)]
 #![allow(incomplete_features, clippy::must_use_candidate)]
 
 #[cfg(any(feature = "alloc", doc, test))]
 extern crate alloc;
+#[cfg(any(feature = "std", doc))]
+extern crate std;
 
 // pub mod stats;
 
@@ -24,15 +16,85 @@ mod helper;
 #[macro_use]
 mod macros;
 
-// mod affix;
+mod affix;
+mod aligned_buffer;
+mod allocate_many;
+mod allocate_with_hint;
+mod cached_owns;
 mod callback_ref;
+mod canonicalize;
 mod chunk;
+#[cfg(all(any(doc, feature = "alloc"), not(alloc_ref)))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+pub mod collections;
+mod compat;
+mod compose;
+mod compose_enum;
+mod deferred_free;
+mod describe;
+mod error;
+#[cfg(doc)]
+pub mod examples;
 mod fallback;
+#[cfg(any(doc, feature = "std"))]
+mod fault_injector;
+pub mod ffi;
+mod free_tree;
+#[cfg(feature = "fuzzing")]
+#[cfg_attr(doc, doc(cfg(feature = "fuzzing")))]
+pub mod fuzzing;
+mod generational;
+mod global_alloc;
+#[cfg(all(any(doc, feature = "std"), any(unix, windows)))]
+mod guard_pages;
+mod guarded;
+mod header_bytes;
+#[cfg(target_has_atomic = "64")]
+mod id_tagger;
+#[cfg(feature = "critical-section")]
+mod interrupt_safe;
+mod maybe_realloc_in_place;
+mod metered;
+mod middleware;
+#[cfg(any(doc, feature = "std"))]
+mod no_alloc;
 mod null;
+#[cfg(all(any(doc, feature = "std"), any(unix, windows)))]
+mod os;
+mod pattern_check;
+mod poison;
+mod policy_ref;
+mod pool;
 mod proxy;
+mod purge;
 pub mod region;
+mod reservation;
+mod reset_check;
+mod rewrite;
+mod router;
+mod segregate;
+mod slab;
+mod slack_fuzzer;
+mod split;
 pub mod stats;
-// mod segregate;
+#[cfg(target_has_atomic = "ptr")]
+mod striped;
+mod sync;
+#[cfg(target_has_atomic = "8")]
+mod tagging;
+mod timestamp;
+#[cfg(any(doc, feature = "std"))]
+mod tls_cache;
+mod trap_foreign_free;
+mod usage;
+#[cfg(all(any(doc, feature = "std"), any(unix, windows)))]
+mod virtual_reserve;
+pub mod visualize;
+#[cfg(any(doc, feature = "wasm"))]
+mod wasm;
+#[cfg(any(doc, feature = "alloc"))]
+mod weak_owns;
+pub mod workload;
 
 use core::{
     alloc::{AllocError, Layout},
@@ -40,11 +102,83 @@ use core::{
 };
 
 pub use self::{
+    affix::Affix,
+    aligned_buffer::{AlignedBuffer, Alignment, SupportedAlignment},
+    allocate_many::{AllocateMany, ManyBlocks},
+    allocate_with_hint::{AllocateNear, AllocateWithHint},
+    cached_owns::CachedOwns,
     callback_ref::CallbackRef,
-    chunk::Chunk,
+    canonicalize::Canonicalize,
+    chunk::{Chunk, DynChunk},
+    compat::Allocator,
+    compose::Compose,
+    deferred_free::DeferredFree,
+    describe::Describe,
+    error::{ComposeError, ErrorContext},
     fallback::Fallback,
-    null::Null,
+    free_tree::FreeTree,
+    generational::{Generation, Generational},
+    global_alloc::AsGlobalAlloc,
+    guarded::Guarded,
+    header_bytes::HeaderBytes,
+    maybe_realloc_in_place::MaybeReallocateInPlace,
+    metered::{Metered, MeteredScope},
+    middleware::Middleware,
+    null::{Null, NullZst},
+    pattern_check::PatternCheck,
+    poison::PoisonOnMove,
+    policy_ref::PolicyRef,
+    pool::GrowablePool,
     proxy::Proxy,
+    purge::{Purge, PurgeLevel},
+    reservation::{BeginAlloc, Reservation},
+    reset_check::{AllocationGuard, ResetCheck},
+    rewrite::Rewrite,
+    router::{Route, RoutePolicy, Router},
+    segregate::Segregate,
+    slab::SlabAllocator,
+    slack_fuzzer::SlackFuzzer,
+    split::Split,
+    timestamp::Timestamp,
+    trap_foreign_free::{CountForeign, ForeignHandler, PanicOnForeign, TrapForeignFree},
+    usage::{ReportUsage, Usage},
+};
+
+#[cfg(alloc_ref)]
+pub use self::compat::Compat;
+
+#[cfg(target_has_atomic = "64")]
+pub use self::id_tagger::IdTagger;
+#[cfg(target_has_atomic = "ptr")]
+pub use self::striped::Striped;
+#[cfg(target_has_atomic = "8")]
+pub use self::tagging::Tagging;
+
+#[cfg(any(doc, feature = "alloc"))]
+pub use self::weak_owns::{RangeSet, WeakOwns};
+
+#[cfg(any(doc, feature = "std"))]
+pub use self::{
+    fault_injector::FaultInjector,
+    no_alloc::{GuardedZone, NoAllocGuard, NoAllocZone},
+    timestamp::StdClock,
+    tls_cache::TlsCache,
+};
+
+#[cfg(any(doc, all(feature = "cortex-m", target_arch = "arm")))]
+pub use self::timestamp::DwtCycleCounter;
+
+#[cfg(any(doc, feature = "wasm"))]
+#[cfg_attr(doc, doc(cfg(feature = "wasm")))]
+pub use self::wasm::WasmAlloc;
+
+#[cfg(feature = "critical-section")]
+pub use self::interrupt_safe::InterruptSafe;
+
+#[cfg(all(any(doc, feature = "std"), any(unix, windows)))]
+pub use self::{
+    guard_pages::{GuardPages, GuardPosition},
+    virtual_reserve::VirtualReserve,
 };
 
 #[cfg(feature = "intrinsics")]
@@ -71,7 +205,20 @@ use crate::intrinsics::{assume, unlikely};
 #[allow(non_snake_case)]
 mod SIZE {}
 
-/// Extends `AllocRef` for allocating or deallocating all memory at once.
+/// Extends `Allocator` for allocating or deallocating all memory at once.
+///
+/// Wrappers around a single allocator forward these methods to it. Combinators dispatching
+/// between several allocators, like [`Fallback`] or [`Segregate`], aggregate them: the capacities
+/// are summed up, `deallocate_all` resets every allocator, and `allocate_all` drains one allocator
+/// at a time in the order requests would be dispatched.
+///
+/// # Safety
+///
+/// Memory blocks returned by `allocate_all` must be valid like those returned by
+/// [`Allocator::allocate`]. `capacity` and `capacity_left` must report the memory, which is
+/// actually available.
+///
+/// [`Allocator::allocate`]: core::alloc::Allocator::allocate
 pub unsafe trait AllocateAll {
     /// Attempts to allocate all of the memory the allocator can provide.
     ///
@@ -83,9 +230,9 @@ pub unsafe trait AllocateAll {
     /// The returned block may have a larger size than specified by `layout.size()`, and may or may
     /// not have its contents initialized.
     ///
-    /// Also see [`AllocRef::alloc`]
+    /// Also see [`Allocator::allocate`]
     ///
-    /// [`AllocRef::alloc`]: core::alloc::AllocRef::alloc
+    /// [`Allocator::allocate`]: core::alloc::Allocator::allocate
     ///
     /// # Errors
     ///
@@ -104,9 +251,9 @@ pub unsafe trait AllocateAll {
 
     /// Behaves like `alloc_all`, but also ensures that the returned memory is zero-initialized.
     ///
-    /// Also see [`AllocRef::alloc_zeroed`]
+    /// Also see [`Allocator::allocate_zeroed`]
     ///
-    /// [`AllocRef::alloc_zeroed`]: core::alloc::AllocRef::alloc_zeroed
+    /// [`Allocator::allocate_zeroed`]: core::alloc::Allocator::allocate_zeroed
     ///
     /// # Errors
     ///
@@ -146,8 +293,37 @@ pub unsafe trait AllocateAll {
     fn is_full(&self) -> bool {
         self.capacity_left() == 0
     }
+
+    /// Ensures, that at least `additional` bytes can be allocated without failing.
+    ///
+    /// This is useful ahead of a critical section, where an allocation failure is unacceptable.
+    /// Allocators, which acquire their memory lazily, like [`VirtualReserve`], acquire enough
+    /// memory up front. By default, only [`capacity_left`] is checked.
+    ///
+    /// Alignment padding is not accounted for, so allocations with an alignment greater than `1`
+    /// may need more than `additional` bytes.
+    ///
+    /// [`VirtualReserve`]: crate::VirtualReserve
+    /// [`capacity_left`]: Self::capacity_left
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`], if `additional` bytes cannot be provided.
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        if self.capacity_left() >= additional {
+            Ok(())
+        } else {
+            Err(ComposeError::exhausted(core::any::type_name::<Self>()))
+        }
+    }
 }
-/// Extends `AllocRef` to support growing and shrinking in place.
+/// Extends `Allocator` to support growing and shrinking in place.
+///
+/// # Safety
+///
+/// On success, the memory block must stay at the same address and its contents must be
+/// preserved up to the smaller of the old and the new size.
 pub unsafe trait ReallocateInPlace {
     /// Attempts to extend the memory block.
     ///
@@ -166,8 +342,8 @@ pub unsafe trait ReallocateInPlace {
     /// * `old_layout` must [*fit*] that block of memory (The `new_layout` argument need not fit it.).
     /// * `new_layout.size()` must be greater than or equal to `old_layout.size()`.
     ///
-    /// [*currently allocated*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.AllocRef.html#currently-allocated-memory
-    /// [*fit*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.AllocRef.html#memory-fitting
+    /// [*currently allocated*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.Allocator.html#memory-fitting
     ///
     /// # Errors
     ///
@@ -208,8 +384,8 @@ pub unsafe trait ReallocateInPlace {
     /// * `old_layout` must [*fit*] that block of memory (The `new_layout` argument need not fit it.).
     /// * `new_layout.size()` must be greater than or equal to `old_layout.size()`.
     ///
-    /// [*currently allocated*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.AllocRef.html#currently-allocated-memory
-    /// [*fit*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.AllocRef.html#memory-fitting
+    /// [*currently allocated*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.Allocator.html#memory-fitting
     ///
     /// # Errors
     ///
@@ -248,8 +424,8 @@ pub unsafe trait ReallocateInPlace {
     /// * `old_layout` must [*fit*] that block of memory (The `new_layout` argument need not fit it.).
     /// * `new_layout.size()` must be smaller than or equal to `old_layout.size()`.
     ///
-    /// [*currently allocated*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.AllocRef.html#currently-allocated-memory
-    /// [*fit*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.AllocRef.html#memory-fitting
+    /// [*currently allocated*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/nightly/alloc/alloc/trait.Allocator.html#memory-fitting
     ///
     /// # Errors
     ///
@@ -276,6 +452,38 @@ pub unsafe trait ReallocateInPlace {
 pub trait Owns {
     /// Returns if the allocator *owns* the passed memory.
     fn owns(&self, ptr: NonNull<[u8]>) -> bool;
+
+    /// Returns if the allocator *owns* the memory at `ptr`.
+    ///
+    /// This is useful, when the size of the memory block is unknown, e.g. in
+    /// `GlobalAlloc::dealloc`. By default, this checks if an empty memory block at `ptr` is owned,
+    /// which is exact for allocators managing a contiguous range of memory. Allocators
+    /// dispatching memory blocks by their size override this.
+    #[inline]
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.owns(NonNull::slice_from_raw_parts(ptr, 0))
+    }
+}
+
+/// Trait for allocators, which hand out memory in multiples of a fixed block size.
+///
+/// The length of every memory block returned by such an allocator is a multiple of
+/// [`block_size`]. Higher-level allocators can use this to choose thresholds, so that requests
+/// don't waste most of a block.
+///
+/// [`block_size`]: Self::block_size
+///
+/// # Examples
+///
+/// ```rust
+/// use alloc_compose::{BlockSize, Chunk};
+/// use std::alloc::System;
+///
+/// assert_eq!(Chunk::<_, 64>(System).block_size(), 64);
+/// ```
+pub trait BlockSize {
+    /// Returns the granularity of the memory blocks returned by this allocator.
+    fn block_size(&self) -> usize;
 }
 
 macro_rules! impl_traits {
@@ -312,6 +520,11 @@ macro_rules! impl_traits {
             fn is_full(&self) -> bool {
                 (**self).is_full()
             }
+
+            #[track_caller]
+            fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+                (**self).reserve(additional)
+            }
         }
 
         $(#[$meta])*
@@ -355,6 +568,30 @@ macro_rules! impl_traits {
             fn owns(&self, ptr: NonNull<[u8]>) -> bool {
                 (**self).owns(ptr)
             }
+
+            fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+                (**self).owns_ptr(ptr)
+            }
+        }
+
+        $(#[$meta])*
+        impl<A> BlockSize for $ty
+        where
+            A: BlockSize + ?Sized,
+        {
+            fn block_size(&self) -> usize {
+                (**self).block_size()
+            }
+        }
+
+        $(#[$meta])*
+        impl<A> ReportUsage for $ty
+        where
+            A: ReportUsage + ?Sized,
+        {
+            fn usage(&self) -> Usage {
+                (**self).usage()
+            }
         }
     };
 }
@@ -369,7 +606,7 @@ impl_traits!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::sync::Arc<A>);
 
 #[track_caller]
 #[inline]
-fn check_dealloc_precondition(ptr: NonNull<u8>, layout: Layout) {
+fn check_deallocate_precondition(ptr: NonNull<u8>, layout: Layout) {
     debug_assert!(
         ptr.as_ptr() as usize >= layout.align(),
         "`ptr` allocated with the same alignment as `layout.align()`, expected {} >= {}",
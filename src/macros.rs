@@ -1,24 +1,25 @@
 macro_rules! impl_global_alloc {
-    ($ty:path) => {
-        unsafe impl core::alloc::GlobalAlloc for $ty {
+    (impl<$(const $param:ident: $const_ty:ty),+> $ty:ty) => {
+        impl_global_alloc!(@impl [$(const $param: $const_ty),+] $ty);
+    };
+    (impl<$($param:ident: $bound:path),*> $ty:ty) => {
+        impl_global_alloc!(@impl [$($param: $bound),*] $ty);
+    };
+    (@impl [$($generics:tt)*] $ty:ty) => {
+        unsafe impl<$($generics)*> core::alloc::GlobalAlloc for $ty {
             unsafe fn alloc(&self, layout: core::alloc::Layout) -> *mut u8 {
-                core::alloc::AllocRef::alloc(&self, layout)
-                    .map(core::ptr::NonNull::as_mut_ptr)
-                    .unwrap_or(core::ptr::null_mut())
+                core::alloc::GlobalAlloc::alloc(crate::AsGlobalAlloc::from_ref(self), layout)
             }
 
             unsafe fn dealloc(&self, ptr: *mut u8, layout: core::alloc::Layout) {
-                core::alloc::AllocRef::dealloc(
-                    &self,
-                    core::ptr::NonNull::new_unchecked(ptr),
-                    layout,
-                )
+                core::alloc::GlobalAlloc::dealloc(crate::AsGlobalAlloc::from_ref(self), ptr, layout)
             }
 
             unsafe fn alloc_zeroed(&self, layout: core::alloc::Layout) -> *mut u8 {
-                core::alloc::AllocRef::alloc_zeroed(&self, layout)
-                    .map(core::ptr::NonNull::as_mut_ptr)
-                    .unwrap_or(core::ptr::null_mut())
+                core::alloc::GlobalAlloc::alloc_zeroed(
+                    crate::AsGlobalAlloc::from_ref(self),
+                    layout,
+                )
             }
 
             unsafe fn realloc(
@@ -27,38 +28,28 @@ macro_rules! impl_global_alloc {
                 layout: core::alloc::Layout,
                 new_size: usize,
             ) -> *mut u8 {
-                if new_size > layout.size() {
-                    core::alloc::AllocRef::grow(
-                        &self,
-                        core::ptr::NonNull::new_unchecked(ptr),
-                        layout,
-                        core::alloc::Layout::from_size_align_unchecked(new_size, layout.align()),
-                    )
-                    .map(core::ptr::NonNull::as_mut_ptr)
-                    .unwrap_or(core::ptr::null_mut())
-                } else {
-                    core::alloc::AllocRef::shrink(
-                        &self,
-                        core::ptr::NonNull::new_unchecked(ptr),
-                        layout,
-                        core::alloc::Layout::from_size_align_unchecked(new_size, layout.align()),
-                    )
-                    .map(core::ptr::NonNull::as_mut_ptr)
-                    .unwrap_or(core::ptr::null_mut())
-                }
+                core::alloc::GlobalAlloc::realloc(
+                    crate::AsGlobalAlloc::from_ref(self),
+                    ptr,
+                    layout,
+                    new_size,
+                )
             }
         }
     };
+    ($ty:ty) => {
+        impl_global_alloc!(impl<> $ty);
+    };
 }
 
-macro_rules! impl_alloc_ref {
+macro_rules! impl_allocator {
     ($parent:tt) => {
-        fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-            Self::alloc_impl(layout, |l| self.$parent.alloc(l))
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            Self::alloc_impl(layout, |l| self.$parent.allocate(l))
         }
 
-        fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-            Self::alloc_impl(layout, |l| self.$parent.alloc_zeroed(l))
+        fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            Self::alloc_impl(layout, |l| self.$parent.allocate_zeroed(l))
         }
 
         unsafe fn grow(
@@ -124,11 +115,25 @@ macro_rules! impl_alloc_all {
         }
 
         fn capacity(&self) -> usize {
-            self.$parent.capacity()
+            Self::round_down(self.$parent.capacity())
         }
 
         fn capacity_left(&self) -> usize {
-            self.$parent.capacity_left()
+            Self::round_down(self.$parent.capacity_left())
+        }
+
+        fn is_empty(&self) -> bool {
+            self.$parent.is_empty()
+        }
+
+        #[track_caller]
+        fn reserve(&self, additional: usize) -> Result<(), crate::ComposeError> {
+            match Self::round_up(additional) {
+                Ok(additional) => self.$parent.reserve(additional),
+                Err(_) => Err(crate::ComposeError::exhausted(
+                    core::any::type_name::<Self>(),
+                )),
+            }
         }
     };
 }
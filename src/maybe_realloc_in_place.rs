@@ -0,0 +1,290 @@
+use crate::{Allocator, Describe, Owns, Purge, PurgeLevel, ReallocateInPlace};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// Implements [`ReallocateInPlace`] for any allocator on a best-effort basis.
+///
+/// If the underlying allocator implements `ReallocateInPlace`, the requests are forwarded,
+/// otherwise they fail. Combinators like [`Affix`], [`Fallback`], or [`Proxy`] only implement
+/// `ReallocateInPlace`, if their parents do. Wrapping a parent, which can't resize in place,
+/// offers the in-place API uniformly without failing to compile. All other requests are forwarded
+/// unchanged.
+///
+/// [`Affix`]: crate::Affix
+/// [`Fallback`]: crate::Fallback
+/// [`Proxy`]: crate::Proxy
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{
+///     region::{Region, Up},
+///     Fallback,
+///     MaybeReallocateInPlace,
+///     ReallocateInPlace,
+/// };
+/// use std::{
+///     alloc::{Allocator, Layout, System},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 32];
+/// let alloc = Fallback {
+///     primary: Region::<Up>::with_direction(&mut data),
+///     secondary: MaybeReallocateInPlace(System),
+/// };
+/// assert!(!alloc.secondary.supports_realloc_in_place());
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 8]>())?;
+/// unsafe {
+///     // The region grows the last memory block in place
+///     let len = alloc.grow_in_place(
+///         memory.as_non_null_ptr(),
+///         Layout::new::<[u8; 8]>(),
+///         Layout::new::<[u8; 16]>(),
+///     )?;
+///     assert_eq!(len, 16);
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 16]>());
+///
+///     // `System` can't grow in place
+///     let memory = alloc.allocate(Layout::new::<[u8; 64]>())?;
+///     assert!(alloc
+///         .grow_in_place(
+///             memory.as_non_null_ptr(),
+///             Layout::new::<[u8; 64]>(),
+///             Layout::new::<[u8; 128]>(),
+///         )
+///         .is_err());
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+/// }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaybeReallocateInPlace<A>(pub A);
+
+/// Reports, if a type implements `ReallocateInPlace`.
+trait SupportsReallocInPlace {
+    fn supports_realloc_in_place(&self) -> bool;
+}
+
+impl<A> SupportsReallocInPlace for A {
+    #[inline]
+    default fn supports_realloc_in_place(&self) -> bool {
+        false
+    }
+}
+
+impl<A: ReallocateInPlace> SupportsReallocInPlace for A {
+    #[inline]
+    fn supports_realloc_in_place(&self) -> bool {
+        true
+    }
+}
+
+impl<A> MaybeReallocateInPlace<A> {
+    /// Returns, if the requests are forwarded to the `ReallocateInPlace` implementation of the
+    /// underlying allocator.
+    pub fn supports_realloc_in_place(&self) -> bool {
+        self.0.supports_realloc_in_place()
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for MaybeReallocateInPlace<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate_zeroed(layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.0.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.0.grow(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.0.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.0.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+unsafe impl<A> ReallocateInPlace for MaybeReallocateInPlace<A> {
+    #[track_caller]
+    default unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        Err(AllocError)
+    }
+
+    #[track_caller]
+    default unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        Err(AllocError)
+    }
+
+    #[track_caller]
+    default unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        Err(AllocError)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace> ReallocateInPlace for MaybeReallocateInPlace<A> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.0.grow_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.0.grow_in_place_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.0.shrink_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: Owns> Owns for MaybeReallocateInPlace<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.0.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.0.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe> Describe for MaybeReallocateInPlace<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MaybeReallocateInPlace")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.0)
+    }
+}
+
+impl<A: Purge> Purge for MaybeReallocateInPlace<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.0.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaybeReallocateInPlace;
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        Affix,
+        Allocator,
+        ReallocateInPlace,
+    };
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn forward_or_fail() {
+        let mut data = [MaybeUninit::new(0); 32];
+        let alloc = MaybeReallocateInPlace(Region::<Up>::with_direction(&mut data));
+        assert!(alloc.supports_realloc_in_place());
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        unsafe {
+            assert_eq!(
+                alloc.shrink_in_place(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 4]>(),
+                ),
+                Ok(4)
+            );
+        }
+
+        let alloc = Affix::<_, u32, ()>::new(MaybeReallocateInPlace(tracker(Global)));
+        assert!(!alloc.parent.supports_realloc_in_place());
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        unsafe {
+            assert!(alloc
+                .grow_in_place_zeroed(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 64]>(),
+                )
+                .is_err());
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 8]>());
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::CallbackRef;
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// Callbacks with a slot to temporarily attach additional callbacks.
+///
+/// Used as callbacks of a [`Proxy`], this allows profiling a single phase of a program without
+/// reconstructing the allocator. [`attach`] returns a [`MeteredScope`], which detaches the
+/// callbacks again when it's dropped. Every hook is forwarded to `callbacks` first and then to the
+/// attached callbacks.
+///
+/// Only one slot exists: attaching while another scope is active replaces the attached callbacks
+/// until the inner scope ends.
+///
+/// [`Proxy`]: crate::Proxy
+/// [`attach`]: Self::attach
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{stats::Counter, Metered, Proxy};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let phase = Counter::default();
+/// let alloc = Proxy {
+///     alloc: System,
+///     callbacks: Metered::new(Counter::default()),
+/// };
+///
+/// let layout = Layout::new::<u32>();
+/// let memory = alloc.allocate(layout)?;
+/// {
+///     let _scope = alloc.callbacks.attach(&phase);
+///     unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+/// }
+/// let memory = alloc.allocate(layout)?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+///
+/// assert_eq!(alloc.callbacks.callbacks.num_allocs(), 2);
+/// assert_eq!(phase.num_allocs(), 0);
+/// assert_eq!(phase.num_deallocs(), 1);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Default)]
+pub struct Metered<'a, C> {
+    pub callbacks: C,
+    scoped: Cell<Option<&'a dyn CallbackRef>>,
+}
+
+impl<'a, C> Metered<'a, C> {
+    /// Wraps `callbacks` without attached callbacks.
+    #[inline]
+    pub fn new(callbacks: C) -> Self {
+        Self {
+            callbacks,
+            scoped: Cell::new(None),
+        }
+    }
+
+    /// Attaches `callbacks` until the returned scope is dropped.
+    #[inline]
+    pub fn attach(&self, callbacks: &'a dyn CallbackRef) -> MeteredScope<'_, 'a> {
+        MeteredScope {
+            slot: &self.scoped,
+            previous: self.scoped.replace(Some(callbacks)),
+        }
+    }
+
+    /// Returns if callbacks are attached at the moment.
+    #[inline]
+    pub fn is_attached(&self) -> bool {
+        self.scoped.get().is_some()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Metered<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metered")
+            .field("callbacks", &self.callbacks)
+            .field("attached", &self.is_attached())
+            .finish()
+    }
+}
+
+/// Detaches the callbacks attached by [`Metered::attach`] when dropped.
+#[must_use = "the callbacks are detached immediately if the scope is not bound"]
+pub struct MeteredScope<'s, 'a> {
+    slot: &'s Cell<Option<&'a dyn CallbackRef>>,
+    previous: Option<&'a dyn CallbackRef>,
+}
+
+impl Drop for MeteredScope<'_, '_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.slot.set(self.previous)
+    }
+}
+
+impl fmt::Debug for MeteredScope<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MeteredScope").finish()
+    }
+}
+
+macro_rules! forward {
+    ($($name:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $name(&self, $($arg: $ty),*) {
+                self.callbacks.$name($($arg),*);
+                if let Some(scoped) = self.scoped.get() {
+                    scoped.$name($($arg),*);
+                }
+            }
+        )*
+    };
+}
+
+unsafe impl<C: CallbackRef> CallbackRef for Metered<'_, C> {
+    forward! {
+        before_allocate(layout: Layout);
+        after_allocate(layout: Layout, result: Result<NonNull<[u8]>, AllocError>);
+        before_allocate_zeroed(layout: Layout);
+        after_allocate_zeroed(layout: Layout, result: Result<NonNull<[u8]>, AllocError>);
+        before_allocate_all();
+        after_allocate_all(result: Result<NonNull<[u8]>, AllocError>);
+        before_allocate_all_zeroed();
+        after_allocate_all_zeroed(result: Result<NonNull<[u8]>, AllocError>);
+        before_deallocate(ptr: NonNull<u8>, layout: Layout);
+        after_deallocate(ptr: NonNull<u8>, layout: Layout);
+        before_deallocate_all();
+        after_deallocate_all();
+        before_grow(ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout);
+        after_grow(
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        before_grow_zeroed(ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout);
+        after_grow_zeroed(
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        before_grow_in_place(ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout);
+        after_grow_in_place(
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>
+        );
+        before_grow_in_place_zeroed(ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout);
+        after_grow_in_place_zeroed(
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>
+        );
+        before_shrink(ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout);
+        after_shrink(
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        before_shrink_in_place(ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout);
+        after_shrink_in_place(
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>
+        );
+        before_owns();
+        after_owns(success: bool);
+        after_adjust_layout(requested: Layout, adjusted: Layout);
+        after_capacity_left(capacity_left: usize);
+        on_watermark(level: usize, used: usize, limit: usize);
+        after_allocate_with_id(id: u64, layout: Layout, result: Result<NonNull<[u8]>, AllocError>);
+        after_allocate_zeroed_with_id(
+            id: u64,
+            layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        before_deallocate_with_id(id: u64, ptr: NonNull<u8>, layout: Layout);
+        after_deallocate_with_id(id: u64, ptr: NonNull<u8>, layout: Layout);
+        after_grow_with_id(
+            id: u64,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        after_grow_zeroed_with_id(
+            id: u64,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        after_shrink_with_id(
+            id: u64,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>
+        );
+        on_reset();
+    }
+
+    #[inline]
+    fn samples_capacity_left(&self) -> bool {
+        self.callbacks.samples_capacity_left()
+            || self
+                .scoped
+                .get()
+                .is_some_and(|scoped| scoped.samples_capacity_left())
+    }
+
+    // Scopes start and end independently of the proxy
+    #[inline]
+    fn on_create(&self) {
+        self.callbacks.on_create()
+    }
+
+    #[inline]
+    fn on_destroy(&self) {
+        self.callbacks.on_destroy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metered;
+    use crate::{helper::tracker, stats::Counter, Allocator, Proxy};
+    use alloc::alloc::Global;
+    use core::alloc::Layout;
+
+    #[test]
+    fn nested_scopes() {
+        let outer = Counter::default();
+        let inner = Counter::default();
+        let alloc = tracker(Proxy {
+            alloc: Global,
+            callbacks: Metered::new(Counter::default()),
+        });
+        let metered = &alloc.alloc.callbacks;
+        let layout = Layout::new::<[u8; 8]>();
+        let allocate = || {
+            let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+            unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        };
+
+        allocate();
+        {
+            let _outer = metered.attach(&outer);
+            allocate();
+            {
+                let _inner = metered.attach(&inner);
+                allocate();
+            }
+            assert!(metered.is_attached());
+            allocate();
+        }
+        assert!(!metered.is_attached());
+        allocate();
+
+        assert_eq!(metered.callbacks.num_allocs(), 5);
+        assert_eq!(outer.num_allocs(), 2);
+        assert_eq!(inner.num_allocs(), 1);
+        assert_eq!(inner.num_deallocs(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+use core::{
+    alloc::{AllocError, Layout},
+    ptr::NonNull,
+};
+
+/// Backend for the [`Rewrite`] allocator.
+///
+/// A `Middleware` maps every requested layout to the layout passed to the underlying allocator,
+/// and may adjust the returned memory block afterwards. This allows policies like rounding up the
+/// size to a multiple of a chunk or raising the alignment without implementing a whole allocator.
+///
+/// Every method defaults to passing the request through unchanged.
+///
+/// [`Rewrite`]: crate::Rewrite
+///
+/// # Safety
+///
+/// * [`map_layout`] must return the same layout every time it's called with the same `layout`,
+///   as memory blocks are deallocated with the mapped layout.
+/// * [`map_result`] must not replace a returned memory block. It may only shorten it, as long as
+///   the block still fits `layout`.
+///
+/// [`map_layout`]: Self::map_layout
+/// [`map_result`]: Self::map_result
+///
+/// # Examples
+///
+/// Align every memory block to a cache line:
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{Middleware, Rewrite};
+/// use std::alloc::{AllocError, Allocator, Layout, System};
+///
+/// struct CacheAligned;
+///
+/// unsafe impl Middleware for CacheAligned {
+///     fn map_layout(&self, layout: Layout) -> Result<Layout, AllocError> {
+///         layout.align_to(64).map_err(|_| AllocError)
+///     }
+/// }
+///
+/// let alloc = Rewrite {
+///     alloc: System,
+///     middleware: CacheAligned,
+/// };
+///
+/// let memory = alloc.allocate(Layout::new::<u8>())?;
+/// assert_eq!(memory.as_mut_ptr() as usize % 64, 0);
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u8>()) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[allow(unused_variables)]
+pub unsafe trait Middleware {
+    /// Returns the layout requested from the underlying allocator instead of `layout`.
+    ///
+    /// The returned layout has to fit `layout`, otherwise the request fails.
+    ///
+    /// # Errors
+    ///
+    /// Returning `Err` fails the request, e.g. if adjusting the size overflows.
+    #[inline]
+    fn map_layout(&self, layout: Layout) -> Result<Layout, AllocError> {
+        Ok(layout)
+    }
+
+    /// Adjusts the `result` of the underlying allocator, which was requested with `mapped` in
+    /// place of `layout`.
+    #[inline]
+    fn map_result(
+        &self,
+        layout: Layout,
+        mapped: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        result
+    }
+
+    /// Creates a "by reference" adaptor for this instance of `Middleware`.
+    ///
+    /// The returned adaptor also implements `Middleware` and will simply borrow this.
+    #[inline]
+    fn by_ref(&self) -> &Self {
+        self
+    }
+}
+
+/// Passes every request through unchanged.
+unsafe impl Middleware for () {}
+
+macro_rules! impl_middleware {
+    ($(#[$meta:meta])* $ty:ty) => {
+        $(#[$meta])*
+        unsafe impl<M> Middleware for $ty where M: Middleware + ?Sized {
+            #[inline]
+            fn map_layout(&self, layout: Layout) -> Result<Layout, AllocError> {
+                (**self).map_layout(layout)
+            }
+
+            #[inline]
+            fn map_result(
+                &self,
+                layout: Layout,
+                mapped: Layout,
+                result: Result<NonNull<[u8]>, AllocError>,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                (**self).map_result(layout, mapped, result)
+            }
+        }
+    };
+}
+
+impl_middleware!(&M);
+#[cfg(any(doc, feature = "alloc"))]
+impl_middleware!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::boxed::Box<M>);
+#[cfg(any(doc, feature = "alloc"))]
+impl_middleware!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::rc::Rc<M>);
+#[cfg(any(doc, feature = "alloc"))]
+impl_middleware!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::sync::Arc<M>);
@@ -0,0 +1,201 @@
+use crate::PolicyRef;
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    marker::PhantomData,
+    panic::Location,
+    ptr::NonNull,
+};
+
+std::thread_local! {
+    /// The location of the innermost `NoAllocGuard` of this thread.
+    static FORBIDDEN: Cell<Option<&'static Location<'static>>> = const { Cell::new(None) };
+}
+
+/// A [`PolicyRef`], which panics on requests while a [`NoAllocGuard`] is alive on the calling
+/// thread.
+///
+/// Real-time code like an audio callback or the hot loop of a renderer must not allocate, as the
+/// underlying allocator may take locks or ask the operating system for memory. Wrapping the
+/// global allocator into a [`GuardedZone`] enforces this at runtime: every allocation,
+/// reallocation, or call to [`allocate_all`] in the scope of a guard panics with the location
+/// where the guard was created. Deallocations are allowed, and other threads are not affected.
+/// The panic disarms the guard, so the panic machinery itself may allocate.
+///
+/// [`allocate_all`]: crate::AllocateAll::allocate_all
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{Guarded, NoAllocZone};
+/// use std::{
+///     alloc::{Allocator, Layout, System},
+///     panic,
+/// };
+///
+/// let alloc = Guarded {
+///     alloc: System,
+///     policy: NoAllocZone,
+/// };
+///
+/// let result = panic::catch_unwind(|| {
+///     let _guard = NoAllocZone::forbid_alloc();
+///     alloc.allocate(Layout::new::<u32>())
+/// });
+/// assert!(result.is_err());
+///
+/// assert!(alloc.allocate(Layout::new::<u32>()).is_ok());
+/// ```
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NoAllocZone;
+
+/// An allocator, which panics on requests while a [`NoAllocGuard`] is alive.
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub type GuardedZone<A> = crate::Guarded<A, NoAllocZone>;
+
+impl NoAllocZone {
+    /// Forbids allocations on the calling thread until the returned guard is dropped.
+    #[track_caller]
+    pub fn forbid_alloc() -> NoAllocGuard {
+        let location = Location::caller();
+        NoAllocGuard {
+            previous: FORBIDDEN.with(|forbidden| forbidden.replace(Some(location))),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Returns, if allocations are forbidden on the calling thread.
+    pub fn is_forbidden() -> bool {
+        FORBIDDEN
+            .try_with(|forbidden| forbidden.get().is_some())
+            .unwrap_or(false)
+    }
+
+    fn check(request: fmt::Arguments<'_>) {
+        // Taking the location allows the panic machinery to allocate
+        let location = FORBIDDEN.try_with(Cell::take).ok().flatten();
+        if let Some(location) = location {
+            panic!(
+                "{} while allocations are forbidden by the guard created at {}",
+                request, location
+            )
+        }
+    }
+}
+
+impl PolicyRef for NoAllocZone {
+    #[inline]
+    fn check_allocate(&self, layout: Layout) -> Result<(), AllocError> {
+        Self::check(format_args!("allocated {} bytes", layout.size()));
+        Ok(())
+    }
+
+    #[inline]
+    fn check_allocate_all(&self) -> Result<(), AllocError> {
+        Self::check(format_args!("allocated all memory"));
+        Ok(())
+    }
+
+    #[inline]
+    fn check_grow(
+        &self,
+        _ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<(), AllocError> {
+        Self::check(format_args!(
+            "grew {} bytes to {} bytes",
+            old_layout.size(),
+            new_layout.size()
+        ));
+        Ok(())
+    }
+
+    #[inline]
+    fn check_shrink(
+        &self,
+        _ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<(), AllocError> {
+        Self::check(format_args!(
+            "shrank {} bytes to {} bytes",
+            old_layout.size(),
+            new_layout.size()
+        ));
+        Ok(())
+    }
+}
+
+/// Forbids allocations through a [`NoAllocZone`] on the current thread while it's alive.
+///
+/// Created by [`NoAllocZone::forbid_alloc`]. Guards may be nested, dropping one restores the
+/// previous state.
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+#[must_use = "allocations are only forbidden while the guard is alive"]
+pub struct NoAllocGuard {
+    previous: Option<&'static Location<'static>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for NoAllocGuard {
+    fn drop(&mut self) {
+        let _ = FORBIDDEN.try_with(|forbidden| forbidden.set(self.previous));
+    }
+}
+
+impl fmt::Debug for NoAllocGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NoAllocGuard").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NoAllocZone;
+    use crate::{Allocator, Guarded};
+    use alloc::{alloc::Global, format, string::String};
+    use core::alloc::Layout;
+    use std::{panic, thread};
+
+    #[test]
+    fn forbid_alloc() {
+        let alloc = Guarded {
+            alloc: Global,
+            policy: NoAllocZone,
+        };
+        let layout = Layout::new::<[u8; 8]>();
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+
+        let guard = NoAllocZone::forbid_alloc();
+        let line = line!() - 1;
+        assert!(NoAllocZone::is_forbidden());
+        {
+            let _inner = NoAllocZone::forbid_alloc();
+        }
+        // Other threads may allocate
+        thread::spawn(move || assert!(!NoAllocZone::is_forbidden()))
+            .join()
+            .expect("Thread panicked");
+
+        let payload = panic::catch_unwind(|| unsafe {
+            alloc.deallocate(memory.as_non_null_ptr(), layout);
+            alloc.allocate(layout)
+        })
+        .expect_err("Allocated while forbidden");
+        let message = payload
+            .downcast_ref::<String>()
+            .expect("Panic message is not a string");
+        assert!(message.starts_with("allocated 8 bytes"));
+        assert!(message.contains(&format!("{}:{}:", file!(), line)));
+
+        drop(guard);
+        assert!(!NoAllocZone::is_forbidden());
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+    }
+}
@@ -1,10 +1,11 @@
-use crate::{AllocateAll, Owns, ReallocateInPlace};
+use crate::{AllocateAll, Allocator, Describe, Owns, Purge, ReallocateInPlace, ReportUsage, Usage};
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
-    ptr::NonNull,
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::{self, NonNull},
 };
 
-/// An emphatically empty implementation of `AllocRef`.
+/// An emphatically empty implementation of `Allocator`.
 ///
 /// Although it has no direct use, it is useful as a "terminator" in composite allocators
 /// or for disabling the global allocator.
@@ -17,9 +18,9 @@ use core::{
 /// #![feature(allocator_api)]
 ///
 /// use alloc_compose::Null;
-/// use std::alloc::{AllocRef, Global, Layout};
+/// use std::alloc::{Allocator, Global, Layout};
 ///
-/// let memory = Null.alloc(Layout::new::<u32>());
+/// let memory = Null.allocate(Layout::new::<u32>());
 /// assert!(memory.is_err())
 /// ```
 ///
@@ -28,11 +29,13 @@ use core::{
 /// ```rust
 /// # #![feature(allocator_api)]
 /// # use alloc_compose::Null;
-/// # use std::alloc::{AllocRef, Global, Layout};
-/// let memory = Null.alloc(Layout::new::<()>());
+/// # use std::alloc::{Allocator, Global, Layout};
+/// let memory = Null.allocate(Layout::new::<()>());
 /// assert!(memory.is_err())
 /// ```
 ///
+/// Use [`NullZst`], if zero-sized allocations have to succeed.
+///
 /// ## Disabling the global allocator
 ///
 /// ```rust, no_run
@@ -41,23 +44,39 @@ use core::{
 /// #[global_allocator]
 /// static A: Null = Null;
 /// ```
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Null;
 
-unsafe impl AllocRef for Null {
+/// Reports a call, which requires a memory block allocated by [`Null`].
+///
+/// As such a call violates the safety contract, it panics in debug builds. In release builds with
+/// the `no-panic` feature, the call is ignored instead.
+macro_rules! never_allocated {
+    ($method:literal) => {
+        if cfg!(any(debug_assertions, not(feature = "no-panic"))) {
+            unreachable!(concat!(
+                "Null::",
+                $method,
+                " must never be called as allocation always fails"
+            ))
+        }
+    };
+}
+
+unsafe impl Allocator for Null {
     /// Will always return `Err(AllocErr)`.
-    fn alloc(&self, _layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+    fn allocate(&self, _layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         Err(AllocError)
     }
 
     /// Will always return `Err(AllocErr)`.
-    fn alloc_zeroed(&self, _layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+    fn allocate_zeroed(&self, _layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         Err(AllocError)
     }
 
     /// Must not be called, as allocation always fails.
-    unsafe fn dealloc(&self, _ptr: NonNull<u8>, _layout: Layout) {
-        unreachable!("Null::dealloc must never be called as allocation always fails")
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        never_allocated!("dealloc")
     }
 
     /// Must not be called, as allocation always fails.
@@ -67,7 +86,8 @@ unsafe impl AllocRef for Null {
         _old_layout: Layout,
         _new_layout: Layout,
     ) -> Result<NonNull<[u8]>, AllocError> {
-        unreachable!("Null::grow must never be called as allocation always fails")
+        never_allocated!("grow");
+        Err(AllocError)
     }
 
     /// Must not be called, as allocation always fails.
@@ -77,7 +97,8 @@ unsafe impl AllocRef for Null {
         _old_layout: Layout,
         _new_layout: Layout,
     ) -> Result<NonNull<[u8]>, AllocError> {
-        unreachable!("Null::grow_zeroed must never be called as allocation always fails")
+        never_allocated!("grow_zeroed");
+        Err(AllocError)
     }
 
     /// Must not be called, as allocation always fails.
@@ -87,7 +108,8 @@ unsafe impl AllocRef for Null {
         _old_layout: Layout,
         _new_layout: Layout,
     ) -> Result<NonNull<[u8]>, AllocError> {
-        unreachable!("Null::shrink must never be called as allocation always fails")
+        never_allocated!("shrink");
+        Err(AllocError)
     }
 }
 
@@ -119,7 +141,8 @@ unsafe impl ReallocateInPlace for Null {
         _old_layout: Layout,
         _new_layout: Layout,
     ) -> Result<usize, AllocError> {
-        unreachable!("Null::grow_in_place must never be called as allocation always fails")
+        never_allocated!("grow_in_place");
+        Err(AllocError)
     }
 
     /// Must not be called, as allocation always fails.
@@ -129,7 +152,8 @@ unsafe impl ReallocateInPlace for Null {
         _old_layout: Layout,
         _new_layout: Layout,
     ) -> Result<usize, AllocError> {
-        unreachable!("Null::grow_in_place_zeroed must never be called as allocation always fails")
+        never_allocated!("grow_in_place_zeroed");
+        Err(AllocError)
     }
 
     /// Must not be called, as allocation always fails.
@@ -139,7 +163,14 @@ unsafe impl ReallocateInPlace for Null {
         _old_layout: Layout,
         _new_layout: Layout,
     ) -> Result<usize, AllocError> {
-        unreachable!("Null::shrink_in_place must never be called as allocation always fails")
+        never_allocated!("shrink_in_place");
+        Err(AllocError)
+    }
+}
+
+impl ReportUsage for Null {
+    fn usage(&self) -> Usage {
+        Usage::default()
     }
 }
 
@@ -152,21 +183,179 @@ impl Owns for Null {
 
 impl_global_alloc!(Null);
 
+impl Describe for Null {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Null")
+    }
+}
+
+impl Purge for Null {}
+
+/// Like [`Null`], but succeeds for zero-sized layouts.
+///
+/// The `Allocator` contract allows zero-sized allocations, and collections rely on them to
+/// succeed. `NullZst` returns a dangling, well-aligned empty memory block for every zero-sized
+/// layout and fails for all others, so it can terminate a composition or replace the global
+/// allocator without breaking code, which only allocates zero-sized memory.
+///
+/// As the dangling pointer equals the alignment, `NullZst` owns every empty memory block at an
+/// address, which is a power of two.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get, slice_ptr_len)]
+///
+/// use alloc_compose::NullZst;
+/// use std::alloc::{Allocator, Layout};
+///
+/// let memory = NullZst.allocate(Layout::new::<[u64; 0]>())?;
+/// assert_eq!(memory.len(), 0);
+/// assert_eq!(memory.as_mut_ptr() as usize % 8, 0);
+///
+/// assert!(NullZst.allocate(Layout::new::<u8>()).is_err());
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NullZst;
+
+impl NullZst {
+    #[inline]
+    fn dangling(layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            let ptr =
+                unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) };
+            Ok(NonNull::slice_from_raw_parts(ptr, 0))
+        } else {
+            Err(AllocError)
+        }
+    }
+}
+
+unsafe impl Allocator for NullZst {
+    /// Returns an empty memory block for zero-sized layouts and `Err(AllocErr)` otherwise.
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Self::dangling(layout)
+    }
+
+    /// Returns an empty memory block for zero-sized layouts and `Err(AllocErr)` otherwise.
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Self::dangling(layout)
+    }
+
+    /// Does nothing, as only empty memory blocks are allocated.
+    #[track_caller]
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        debug_assert_eq!(layout.size(), 0, "`layout` must be zero-sized");
+    }
+
+    /// Succeeds only if the new layout is zero-sized.
+    #[track_caller]
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        Self::dangling(new_layout)
+    }
+
+    /// Succeeds only if the new layout is zero-sized.
+    #[track_caller]
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        Self::dangling(new_layout)
+    }
+
+    /// Always succeeds, as only empty memory blocks are allocated.
+    #[track_caller]
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        Self::dangling(new_layout)
+    }
+}
+
+unsafe impl AllocateAll for NullZst {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    fn deallocate_all(&self) {}
+
+    fn capacity(&self) -> usize {
+        0
+    }
+
+    fn capacity_left(&self) -> usize {
+        0
+    }
+}
+
+impl ReportUsage for NullZst {
+    fn usage(&self) -> Usage {
+        Usage::default()
+    }
+}
+
+impl Owns for NullZst {
+    /// Returns `true` for empty memory blocks at addresses, which are a power of two.
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        matches!(memory.len(), 0) && (memory.as_mut_ptr() as usize).is_power_of_two()
+    }
+}
+
+impl_global_alloc!(NullZst);
+
+impl Describe for NullZst {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NullZst")
+    }
+}
+
+impl Purge for NullZst {}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::wildcard_imports)]
     use super::*;
+    use crate::Fallback;
+    use alloc::{alloc::Global, vec::Vec};
 
     #[test]
-    #[should_panic(expected = "unreachable")]
-    fn dealloc() {
-        unsafe { Null.dealloc(NonNull::dangling(), Layout::new::<()>()) };
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
+    fn deallocate() {
+        unsafe { Null.deallocate(NonNull::dangling(), Layout::new::<()>()) };
     }
 
     #[test]
-    fn alloc() {
-        assert!(Null.alloc(Layout::new::<u32>()).is_err());
-        assert!(Null.alloc_zeroed(Layout::new::<u32>()).is_err());
+    fn allocate() {
+        assert!(Null.allocate(Layout::new::<u32>()).is_err());
+        assert!(Null.allocate_zeroed(Layout::new::<u32>()).is_err());
         assert!(Null.allocate_all().is_err());
         assert!(Null.allocate_all_zeroed().is_err());
         assert_eq!(Null.capacity(), 0);
@@ -175,7 +364,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unreachable")]
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
     fn grow() {
         unsafe {
             let _ = Null.grow(
@@ -187,7 +379,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unreachable")]
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
     fn grow_zeroed() {
         unsafe {
             let _ = Null.grow_zeroed(
@@ -199,7 +394,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unreachable")]
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
     fn grow_in_place() {
         unsafe {
             let _ = Null.grow_in_place(
@@ -211,7 +409,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unreachable")]
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
     fn grow_in_place_zeroed() {
         unsafe {
             let _ = Null.grow_in_place_zeroed(
@@ -223,7 +424,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unreachable")]
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
     fn shrink() {
         unsafe {
             let _ = Null.shrink(
@@ -235,7 +439,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unreachable")]
+    #[cfg_attr(
+        any(debug_assertions, not(feature = "no-panic")),
+        should_panic(expected = "unreachable")
+    )]
     fn shrink_in_place() {
         unsafe {
             let _ = Null.shrink_in_place(
@@ -255,4 +462,57 @@ mod tests {
     fn debug() {
         assert_eq!(alloc::format!("{:?}", Null), "Null");
     }
+
+    #[test]
+    fn zero_sized() {
+        unsafe {
+            let memory = NullZst
+                .allocate_zeroed(Layout::new::<[u32; 0]>())
+                .expect("Could not allocate 0 bytes");
+            assert_eq!(memory.as_mut_ptr() as usize, 4);
+            assert!(NullZst.owns(memory));
+
+            let memory = NullZst
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u32; 0]>(),
+                    Layout::new::<[u16; 0]>(),
+                )
+                .expect("Could not shrink 0 bytes");
+            assert_eq!(memory.as_mut_ptr() as usize, 2);
+            NullZst
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u16; 0]>(),
+                    Layout::new::<u16>(),
+                )
+                .expect_err("Could grow to 2 bytes");
+            NullZst.deallocate(memory.as_non_null_ptr(), Layout::new::<[u16; 0]>());
+        }
+        NullZst
+            .allocate(Layout::new::<u8>())
+            .expect_err("Could allocate 1 byte");
+    }
+
+    #[test]
+    fn collections() {
+        let mut units = Vec::new_in(NullZst);
+        units.push(());
+        units.reserve(100);
+        assert_eq!(units.len(), 1);
+
+        let mut bytes = Vec::<u8, _>::new_in(NullZst);
+        bytes.try_reserve(1).expect_err("Could reserve 1 byte");
+
+        let alloc = Fallback {
+            primary: NullZst,
+            secondary: crate::helper::tracker(Global),
+        };
+        let mut vec = Vec::<u32, _>::new_in(&alloc);
+        vec.extend(&[1, 2, 3]);
+        vec.clear();
+        vec.shrink_to_fit();
+        vec.extend(&[4, 5]);
+        assert_eq!(vec, [4, 5]);
+    }
 }
@@ -0,0 +1,148 @@
+//! Thin wrappers around the virtual memory functions of the operating system.
+//!
+//! All functions operate on page-aligned addresses and lengths, which are multiples of
+//! [`page_size`].
+
+use core::ptr::NonNull;
+
+#[cfg(unix)]
+mod imp {
+    use core::ptr::{self, NonNull};
+
+    pub fn page_size() -> usize {
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    }
+
+    pub unsafe fn reserve(len: usize) -> Option<NonNull<u8>> {
+        let ptr = libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            None
+        } else {
+            NonNull::new(ptr.cast())
+        }
+    }
+
+    pub unsafe fn commit(ptr: NonNull<u8>, len: usize) -> bool {
+        libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_READ | libc::PROT_WRITE) == 0
+    }
+
+    pub unsafe fn decommit(ptr: NonNull<u8>, len: usize) {
+        libc::madvise(ptr.as_ptr().cast(), len, libc::MADV_DONTNEED);
+        libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_NONE);
+    }
+
+    pub unsafe fn protect(ptr: NonNull<u8>, len: usize) -> bool {
+        libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_NONE) == 0
+    }
+
+    pub unsafe fn unprotect(ptr: NonNull<u8>, len: usize) -> bool {
+        libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_READ | libc::PROT_WRITE) == 0
+    }
+
+    pub unsafe fn release(ptr: NonNull<u8>, len: usize) {
+        libc::munmap(ptr.as_ptr().cast(), len);
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use core::{
+        mem::MaybeUninit,
+        ptr::{self, NonNull},
+    };
+    use winapi::um::{
+        memoryapi::{VirtualAlloc, VirtualFree, VirtualProtect},
+        sysinfoapi::GetSystemInfo,
+        winnt::{
+            MEM_COMMIT,
+            MEM_DECOMMIT,
+            MEM_RELEASE,
+            MEM_RESERVE,
+            PAGE_NOACCESS,
+            PAGE_READWRITE,
+        },
+    };
+
+    pub fn page_size() -> usize {
+        unsafe {
+            let mut info = MaybeUninit::uninit();
+            GetSystemInfo(info.as_mut_ptr());
+            info.assume_init().dwPageSize as usize
+        }
+    }
+
+    pub unsafe fn reserve(len: usize) -> Option<NonNull<u8>> {
+        NonNull::new(VirtualAlloc(ptr::null_mut(), len, MEM_RESERVE, PAGE_NOACCESS).cast())
+    }
+
+    pub unsafe fn commit(ptr: NonNull<u8>, len: usize) -> bool {
+        !VirtualAlloc(ptr.as_ptr().cast(), len, MEM_COMMIT, PAGE_READWRITE).is_null()
+    }
+
+    pub unsafe fn decommit(ptr: NonNull<u8>, len: usize) {
+        VirtualFree(ptr.as_ptr().cast(), len, MEM_DECOMMIT);
+    }
+
+    pub unsafe fn protect(ptr: NonNull<u8>, len: usize) -> bool {
+        let mut old = 0;
+        VirtualProtect(ptr.as_ptr().cast(), len, PAGE_NOACCESS, &mut old) != 0
+    }
+
+    pub unsafe fn unprotect(ptr: NonNull<u8>, len: usize) -> bool {
+        let mut old = 0;
+        VirtualProtect(ptr.as_ptr().cast(), len, PAGE_READWRITE, &mut old) != 0
+    }
+
+    pub unsafe fn release(ptr: NonNull<u8>, _len: usize) {
+        VirtualFree(ptr.as_ptr().cast(), 0, MEM_RELEASE);
+    }
+}
+
+/// Returns the size of a page in bytes.
+pub fn page_size() -> usize {
+    imp::page_size()
+}
+
+/// Rounds `len` up to a multiple of [`page_size`] or returns `None` on overflow.
+pub fn round_to_pages(len: usize) -> Option<usize> {
+    let page_size = page_size();
+    Some(len.checked_add(page_size - 1)? & !(page_size - 1))
+}
+
+/// Reserves `len` bytes of address space without committing any memory.
+pub unsafe fn reserve(len: usize) -> Option<NonNull<u8>> {
+    imp::reserve(len)
+}
+
+/// Commits the reserved pages in `ptr..ptr + len` as readable and writable memory.
+pub unsafe fn commit(ptr: NonNull<u8>, len: usize) -> bool {
+    imp::commit(ptr, len)
+}
+
+/// Returns the committed pages in `ptr..ptr + len` to the operating system, but keeps the
+/// address space reserved.
+pub unsafe fn decommit(ptr: NonNull<u8>, len: usize) {
+    imp::decommit(ptr, len)
+}
+
+/// Makes the pages in `ptr..ptr + len` inaccessible.
+pub unsafe fn protect(ptr: NonNull<u8>, len: usize) -> bool {
+    imp::protect(ptr, len)
+}
+
+/// Makes the pages in `ptr..ptr + len` readable and writable again.
+pub unsafe fn unprotect(ptr: NonNull<u8>, len: usize) -> bool {
+    imp::unprotect(ptr, len)
+}
+
+/// Releases the address space reserved by [`reserve`].
+pub unsafe fn release(ptr: NonNull<u8>, len: usize) {
+    imp::release(ptr, len)
+}
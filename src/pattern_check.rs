@@ -0,0 +1,276 @@
+use crate::{Allocator, BlockSize, Describe, Owns, Purge, PurgeLevel, ReportUsage, Usage};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// Fills memory blocks with a pattern and verifies the unused tail on deallocation.
+///
+/// Every uninitialized byte of a memory block returned by the underlying allocator is filled with
+/// `pattern`, so reads of uninitialized memory are easy to spot. Many allocators return more memory
+/// than requested, e.g. [`Chunk`] rounds up to its block size. Writes into this slack stay inside
+/// of the memory block and go unnoticed by tools like Miri or sanitizers. Before a memory block
+/// is deallocated, grown, or shrunk, `PatternCheck` verifies, that the slack still contains the
+/// pattern, and panics otherwise.
+///
+/// The size of the slack is derived from [`BlockSize`], if the underlying allocator implements it.
+/// Otherwise, only the pattern is written.
+///
+/// [`Chunk`]: crate::Chunk
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{Chunk, PatternCheck};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = PatternCheck::new(Chunk::<_, 16>(System));
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 10]>())?;
+/// assert_eq!(memory.len(), 16);
+/// unsafe {
+///     // Writes past the requested size, but inside of the chunk
+///     memory.as_mut_ptr().add(12).write(0);
+///     // Panics
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 10]>());
+/// }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PatternCheck<A> {
+    pub alloc: A,
+    pub pattern: u8,
+}
+
+impl<A> PatternCheck<A> {
+    /// The pattern used by [`PatternCheck::new`].
+    pub const DEFAULT_PATTERN: u8 = 0xA5;
+
+    /// Fills the memory blocks returned by `alloc` with [`DEFAULT_PATTERN`].
+    ///
+    /// [`DEFAULT_PATTERN`]: Self::DEFAULT_PATTERN
+    pub const fn new(alloc: A) -> Self {
+        Self {
+            alloc,
+            pattern: Self::DEFAULT_PATTERN,
+        }
+    }
+}
+
+impl<A: Default> Default for PatternCheck<A> {
+    fn default() -> Self {
+        Self::new(A::default())
+    }
+}
+
+/// Returns the number of bytes usable for a memory block of `layout`.
+trait UsableSize {
+    fn usable_size(&self, layout: Layout) -> usize;
+}
+
+impl<A> UsableSize for A {
+    default fn usable_size(&self, layout: Layout) -> usize {
+        layout.size()
+    }
+}
+
+impl<A: BlockSize> UsableSize for A {
+    fn usable_size(&self, layout: Layout) -> usize {
+        let block_size = self.block_size();
+        if block_size == 0 {
+            return layout.size();
+        }
+        layout
+            .size()
+            .checked_add(block_size - 1)
+            .map_or(layout.size(), |size| size / block_size * block_size)
+    }
+}
+
+impl<A> PatternCheck<A> {
+    /// Fills `memory[from..]` with the pattern.
+    unsafe fn fill(&self, memory: NonNull<[u8]>, from: usize) {
+        if from < memory.len() {
+            memory
+                .as_mut_ptr()
+                .add(from)
+                .write_bytes(self.pattern, memory.len() - from);
+        }
+    }
+
+    #[track_caller]
+    unsafe fn verify(&self, ptr: NonNull<u8>, layout: Layout) {
+        for offset in layout.size()..self.alloc.usable_size(layout) {
+            assert!(
+                *ptr.as_ptr().add(offset) == self.pattern,
+                "Write past the requested size of {} bytes detected at offset {}",
+                layout.size(),
+                offset
+            );
+        }
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for PatternCheck<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.alloc.allocate(layout)?;
+        unsafe { self.fill(memory, 0) };
+        Ok(memory)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.alloc.allocate_zeroed(layout)?;
+        unsafe { self.fill(memory, layout.size()) };
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.verify(ptr, layout);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.verify(ptr, old_layout);
+        let memory = self.alloc.grow(ptr, old_layout, new_layout)?;
+        self.fill(memory, old_layout.size());
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.verify(ptr, old_layout);
+        let memory = self.alloc.grow_zeroed(ptr, old_layout, new_layout)?;
+        self.fill(memory, new_layout.size());
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.verify(ptr, old_layout);
+        let memory = self.alloc.shrink(ptr, old_layout, new_layout)?;
+        self.fill(memory, new_layout.size());
+        Ok(memory)
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for PatternCheck<A> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns> Owns for PatternCheck<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: BlockSize> BlockSize for PatternCheck<A> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.alloc.block_size()
+    }
+}
+
+impl<A: Describe> Describe for PatternCheck<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PatternCheck({:#04x})", self.pattern)
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge> Purge for PatternCheck<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatternCheck;
+    use crate::{helper::tracker, region::Region, Allocator, Chunk};
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn fill() {
+        let alloc = tracker(PatternCheck::new(Chunk::<_, 16>(Global)));
+        unsafe {
+            let memory = alloc
+                .allocate(Layout::new::<[u8; 4]>())
+                .expect("Could not allocate 4 bytes");
+            assert_eq!(memory.len(), 16);
+            assert_eq!(*memory.as_mut_ptr().add(15), 0xA5);
+            memory.as_mut_ptr().write_bytes(1, 4);
+
+            let memory = alloc
+                .grow_zeroed(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 4]>(),
+                    Layout::new::<[u8; 20]>(),
+                )
+                .expect("Could not grow to 20 bytes");
+            assert_eq!(*memory.as_mut_ptr().add(3), 1);
+            assert_eq!(*memory.as_mut_ptr().add(19), 0);
+            assert_eq!(*memory.as_mut_ptr().add(20), 0xA5);
+
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 20]>(),
+                    Layout::new::<[u8; 2]>(),
+                )
+                .expect("Could not shrink to 2 bytes");
+            assert_eq!(*memory.as_mut_ptr().add(2), 0xA5);
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 2]>());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "detected at offset 6")]
+    fn write_into_slack() {
+        let mut data = [MaybeUninit::uninit(); 32];
+        let alloc = PatternCheck {
+            alloc: Chunk::<_, 8>(Region::new(&mut data)),
+            pattern: 0,
+        };
+        unsafe {
+            let memory = alloc
+                .allocate_zeroed(Layout::new::<[u8; 5]>())
+                .expect("Could not allocate 5 bytes");
+            memory.as_mut_ptr().add(6).write(1);
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 5]>());
+        }
+    }
+}
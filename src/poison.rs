@@ -0,0 +1,424 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::{self, NonNull},
+};
+
+/// Wipes the old memory block, when growing or shrinking moves the data.
+///
+/// When `grow` or `shrink` cannot resize a memory block in place, the data is copied to a new
+/// memory block and the old one is deallocated. The old memory block still contains a copy of the
+/// data, which may be handed out again by the allocator. For sensitive data like keys, this is not
+/// acceptable.
+///
+/// `PoisonOnMove` first tries to resize the memory block in place, if the underlying allocator
+/// implements [`ReallocateInPlace`]. Otherwise, it moves the memory block by itself and overwrites
+/// the old memory block with zeroes before deallocating it. As the underlying allocator is never
+/// asked to move a memory block, this also covers moves, which would happen deep inside of a
+/// composition, e.g. when a [`Fallback`] moves a memory block from its primary to its secondary
+/// allocator. `PoisonOnMove` should therefore be the outermost allocator.
+///
+/// Deallocated memory is not wiped.
+///
+/// [`Fallback`]: crate::Fallback
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, maybe_uninit_slice, slice_ptr_get)]
+///
+/// use alloc_compose::{region::Region, PoisonOnMove};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::new(0); 64];
+/// let alloc = PoisonOnMove(Region::new(&mut data));
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 8]>())?;
+/// unsafe {
+///     memory.as_mut_ptr().write_bytes(0xFF, 8);
+///     let moved = alloc.grow(
+///         memory.as_non_null_ptr(),
+///         Layout::new::<[u8; 8]>(),
+///         Layout::new::<[u8; 16]>(),
+///     )?;
+///     assert_ne!(moved, memory);
+///     assert_eq!(*moved.as_mut_ptr(), 0xFF);
+/// }
+///
+/// let data = unsafe { <[MaybeUninit<u8>]>::assume_init_ref(&data) };
+/// assert_eq!(data[56..], [0; 8]);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PoisonOnMove<A>(pub A);
+
+/// Overwrites `len` bytes at `ptr` with zeroes.
+///
+/// Volatile writes are used, as the compiler could otherwise elide the writes to memory, which is
+/// deallocated right after.
+unsafe fn wipe(ptr: NonNull<u8>, len: usize) {
+    for i in 0..len {
+        ptr::write_volatile(ptr.as_ptr().add(i), 0);
+    }
+}
+
+/// Resizes in place, if `A` supports it.
+trait TryInPlace {
+    unsafe fn try_grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<usize, AllocError>;
+
+    unsafe fn try_shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError>;
+}
+
+impl<A> TryInPlace for A {
+    default unsafe fn try_grow_in_place(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+        _zeroed: bool,
+    ) -> Result<usize, AllocError> {
+        Err(AllocError)
+    }
+
+    default unsafe fn try_shrink_in_place(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        Err(AllocError)
+    }
+}
+
+impl<A: ReallocateInPlace> TryInPlace for A {
+    unsafe fn try_grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<usize, AllocError> {
+        if zeroed {
+            self.grow_in_place_zeroed(ptr, old_layout, new_layout)
+        } else {
+            self.grow_in_place(ptr, old_layout, new_layout)
+        }
+    }
+
+    unsafe fn try_shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        self.shrink_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: Allocator> PoisonOnMove<A> {
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if let Ok(len) = self
+            .0
+            .try_grow_in_place(ptr, old_layout, new_layout, zeroed)
+        {
+            return Ok(NonNull::slice_from_raw_parts(ptr, len));
+        }
+
+        let new_ptr = if zeroed {
+            self.0.allocate_zeroed(new_layout)?
+        } else {
+            self.0.allocate(new_layout)?
+        };
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), old_layout.size());
+        wipe(ptr, old_layout.size());
+        self.0.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for PoisonOnMove<A> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate_zeroed(layout)
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.0.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, false)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, true)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if let Ok(len) = self.0.try_shrink_in_place(ptr, old_layout, new_layout) {
+            return Ok(NonNull::slice_from_raw_parts(ptr, len));
+        }
+
+        let new_ptr = self.0.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), new_layout.size());
+        wipe(ptr, old_layout.size());
+        self.0.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+unsafe impl<A: AllocateAll> AllocateAll for PoisonOnMove<A> {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate_all()
+    }
+
+    #[inline]
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate_all_zeroed()
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.0.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.0.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.0.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.0.reserve(additional)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace> ReallocateInPlace for PoisonOnMove<A> {
+    #[track_caller]
+    #[inline]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.0.grow_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.0.grow_in_place_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    #[inline]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.0.shrink_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for PoisonOnMove<A> {
+    fn usage(&self) -> Usage {
+        self.0.usage()
+    }
+}
+
+impl<A: Owns> Owns for PoisonOnMove<A> {
+    #[inline]
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.0.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.0.owns_ptr(ptr)
+    }
+}
+
+impl<A: BlockSize> BlockSize for PoisonOnMove<A> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.0.block_size()
+    }
+}
+
+impl<A: Describe> Describe for PoisonOnMove<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PoisonOnMove")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.0)
+    }
+}
+
+impl<A: Purge> Purge for PoisonOnMove<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.0.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PoisonOnMove;
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        Allocator,
+    };
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn wipe_on_move() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = PoisonOnMove(tracker(Region::new(&mut data)));
+
+        unsafe {
+            let memory = alloc
+                .allocate(Layout::new::<[u8; 8]>())
+                .expect("Could not allocate 8 bytes");
+            memory.as_mut_ptr().write_bytes(0xFF, 8);
+
+            let memory = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 16]>(),
+                )
+                .expect("Could not grow to 16 bytes");
+            assert_eq!(*memory.as_mut_ptr(), 0xFF);
+
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::new::<[u8; 4]>(),
+                )
+                .expect("Could not shrink to 4 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>());
+        }
+        drop(alloc);
+
+        let data = unsafe { <[MaybeUninit<u8>]>::assume_init_ref(&data) };
+        assert_eq!(data[..36], [0; 36]);
+        assert_eq!(data[36..40], [0xFF; 4]);
+        assert_eq!(data[40..], [0; 24]);
+    }
+
+    #[test]
+    fn resize_in_place() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = PoisonOnMove(tracker(Region::<Up>::with_direction(&mut data)));
+
+        unsafe {
+            let memory = alloc
+                .allocate(Layout::new::<[u8; 8]>())
+                .expect("Could not allocate 8 bytes");
+            memory.as_mut_ptr().write_bytes(0xFF, 8);
+
+            let grown = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 16]>(),
+                )
+                .expect("Could not grow to 16 bytes");
+            assert_eq!(grown.as_mut_ptr(), memory.as_mut_ptr());
+            assert_eq!(grown.len(), 16);
+            assert_eq!(*grown.as_mut_ptr().add(7), 0xFF);
+            alloc.deallocate(grown.as_non_null_ptr(), Layout::new::<[u8; 16]>());
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use core::{
+    alloc::{AllocError, Layout},
+    ptr::NonNull,
+};
+
+/// Backend for the [`Guarded`] allocator.
+///
+/// In contrast to [`CallbackRef`], which only observes requests, a `PolicyRef` may veto them by
+/// returning `Err`. In that case the request is not forwarded to the underlying allocator and
+/// `Err(AllocError)` is returned. Deallocations cannot be denied.
+///
+/// Every method defaults to allowing the request.
+///
+/// [`Guarded`]: crate::Guarded
+/// [`CallbackRef`]: crate::CallbackRef
+///
+/// # Examples
+///
+/// Deny all allocations larger than 1 MiB:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{Guarded, PolicyRef};
+/// use std::alloc::{AllocError, Allocator, Layout, System};
+///
+/// struct MaxSize(usize);
+///
+/// impl PolicyRef for MaxSize {
+///     fn check_allocate(&self, layout: Layout) -> Result<(), AllocError> {
+///         if layout.size() <= self.0 {
+///             Ok(())
+///         } else {
+///             Err(AllocError)
+///         }
+///     }
+/// }
+///
+/// let alloc = Guarded {
+///     alloc: System,
+///     policy: MaxSize(1024 * 1024),
+/// };
+///
+/// assert!(alloc
+///     .allocate(Layout::new::<[u8; 2 * 1024 * 1024]>())
+///     .is_err());
+/// ```
+#[allow(unused_variables)]
+pub trait PolicyRef {
+    /// Called before [`allocate`] and [`allocate_zeroed`] are invoked.
+    ///
+    /// [`allocate`]: core::alloc::Allocator::allocate
+    /// [`allocate_zeroed`]: core::alloc::Allocator::allocate_zeroed
+    ///
+    /// # Errors
+    ///
+    /// Returning `Err` denies the allocation.
+    #[inline]
+    fn check_allocate(&self, layout: Layout) -> Result<(), AllocError> {
+        Ok(())
+    }
+
+    /// Called before [`allocate_all`] and [`allocate_all_zeroed`] are invoked.
+    ///
+    /// [`allocate_all`]: crate::AllocateAll::allocate_all
+    /// [`allocate_all_zeroed`]: crate::AllocateAll::allocate_all_zeroed
+    ///
+    /// # Errors
+    ///
+    /// Returning `Err` denies the allocation.
+    #[inline]
+    fn check_allocate_all(&self) -> Result<(), AllocError> {
+        Ok(())
+    }
+
+    /// Called before [`grow`], [`grow_zeroed`], [`grow_in_place`], and [`grow_in_place_zeroed`]
+    /// are invoked.
+    ///
+    /// [`grow`]: core::alloc::Allocator::grow
+    /// [`grow_zeroed`]: core::alloc::Allocator::grow_zeroed
+    /// [`grow_in_place`]: crate::ReallocateInPlace::grow_in_place
+    /// [`grow_in_place_zeroed`]: crate::ReallocateInPlace::grow_in_place_zeroed
+    ///
+    /// # Errors
+    ///
+    /// Returning `Err` denies growing the memory block.
+    #[inline]
+    fn check_grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<(), AllocError> {
+        Ok(())
+    }
+
+    /// Called before [`shrink`] and [`shrink_in_place`] are invoked.
+    ///
+    /// [`shrink`]: core::alloc::Allocator::shrink
+    /// [`shrink_in_place`]: crate::ReallocateInPlace::shrink_in_place
+    ///
+    /// # Errors
+    ///
+    /// Returning `Err` denies shrinking the memory block.
+    #[inline]
+    fn check_shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<(), AllocError> {
+        Ok(())
+    }
+
+    /// Creates a "by reference" adaptor for this instance of `PolicyRef`.
+    ///
+    /// The returned adaptor also implements `PolicyRef` and will simply borrow this.
+    #[inline]
+    fn by_ref(&self) -> &Self {
+        self
+    }
+}
+
+macro_rules! impl_policy_ref {
+    ($(#[$meta:meta])* $ty:ty) => {
+        $(#[$meta])*
+        impl<P> PolicyRef for $ty where P: PolicyRef + ?Sized {
+            #[inline]
+            fn check_allocate(&self, layout: Layout) -> Result<(), AllocError> {
+                (**self).check_allocate(layout)
+            }
+
+            #[inline]
+            fn check_allocate_all(&self) -> Result<(), AllocError> {
+                (**self).check_allocate_all()
+            }
+
+            #[inline]
+            fn check_grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<(), AllocError> {
+                (**self).check_grow(ptr, old_layout, new_layout)
+            }
+
+            #[inline]
+            fn check_shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<(), AllocError> {
+                (**self).check_shrink(ptr, old_layout, new_layout)
+            }
+        }
+    };
+}
+
+impl_policy_ref!(&P);
+#[cfg(any(doc, feature = "alloc"))]
+impl_policy_ref!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::boxed::Box<P>);
+#[cfg(any(doc, feature = "alloc"))]
+impl_policy_ref!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::rc::Rc<P>);
+#[cfg(any(doc, feature = "alloc"))]
+impl_policy_ref!(#[cfg_attr(doc, doc(cfg(feature = "alloc")))] alloc::sync::Arc<P>);
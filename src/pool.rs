@@ -0,0 +1,427 @@
+use crate::{
+    describe::Bytes,
+    Allocator,
+    BlockSize,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    mem,
+    ptr::NonNull,
+};
+
+/// A pool of fixed-size blocks, which requests new slabs from a parent allocator when it runs
+/// empty.
+///
+/// Every block is `BLOCK_SIZE` bytes, but at least pointer sized, as free blocks are linked
+/// through their first bytes. Blocks are aligned to the largest power of two dividing the block
+/// size, up to 4 KiB. Requests, which don't fit into a block, fail, so the pool is usually
+/// combined with [`Segregate`] or [`Fallback`].
+///
+/// When no free block is left, a slab of `SLAB_BLOCKS` blocks plus one block for bookkeeping is
+/// requested from `parent`. Deallocated blocks are reused, but slabs are only returned to the
+/// parent by [`deallocate_all`] or when the pool is dropped.
+///
+/// [`Segregate`]: crate::Segregate
+/// [`Fallback`]: crate::Fallback
+/// [`deallocate_all`]: Self::deallocate_all
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{GrowablePool, ReportUsage};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let pool = GrowablePool::<_, 32, 4>::new(System);
+///
+/// let blocks = (0..5)
+///     .map(|_| pool.allocate(Layout::new::<[u8; 20]>()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(blocks[0].len(), 32);
+/// assert_eq!(pool.num_slabs(), 2);
+/// assert_eq!(pool.usage().used, 5 * 32);
+///
+/// assert!(pool.allocate(Layout::new::<[u8; 64]>()).is_err());
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct GrowablePool<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> {
+    parent: A,
+    free: Cell<Option<NonNull<u8>>>,
+    slabs: Cell<Option<NonNull<u8>>>,
+    num_slabs: Cell<usize>,
+    num_free: Cell<usize>,
+}
+
+// The pool owns its slabs, so it may be moved to another thread together with the parent.
+unsafe impl<A: Allocator + Send, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Send
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+}
+
+impl<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize>
+    GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    /// The size of every block, which is `BLOCK_SIZE` rounded up to hold a pointer.
+    pub const SIZE: usize = {
+        let align = mem::align_of::<usize>();
+        let size = if BLOCK_SIZE > mem::size_of::<usize>() {
+            BLOCK_SIZE
+        } else {
+            mem::size_of::<usize>()
+        };
+        (size + align - 1) & !(align - 1)
+    };
+
+    /// The alignment of every block.
+    pub const ALIGN: usize = {
+        let align = 1 << Self::SIZE.trailing_zeros();
+        if align > 4096 {
+            4096
+        } else {
+            align
+        }
+    };
+
+    /// Creates an empty pool, which requests slabs from `parent`.
+    pub const fn new(parent: A) -> Self {
+        Self {
+            parent,
+            free: Cell::new(None),
+            slabs: Cell::new(None),
+            num_slabs: Cell::new(0),
+            num_free: Cell::new(0),
+        }
+    }
+
+    /// Returns a reference to the parent allocator.
+    pub const fn parent(&self) -> &A {
+        &self.parent
+    }
+
+    /// Returns a mutable reference to the parent allocator, if the pool holds no slabs.
+    ///
+    /// While slabs are held, replacing the parent would return them to the wrong allocator.
+    pub fn parent_mut(&mut self) -> Option<&mut A> {
+        if self.slabs.get().is_none() {
+            Some(&mut self.parent)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of slabs requested from the parent.
+    pub fn num_slabs(&self) -> usize {
+        self.num_slabs.get()
+    }
+
+    /// Returns the number of blocks, which can be allocated without requesting a new slab.
+    pub fn num_free(&self) -> usize {
+        self.num_free.get()
+    }
+
+    fn slab_layout() -> Result<Layout, AllocError> {
+        let size = SLAB_BLOCKS
+            .checked_add(1)
+            .and_then(|blocks| blocks.checked_mul(Self::SIZE))
+            .ok_or(AllocError)?;
+        Layout::from_size_align(size, Self::ALIGN).map_err(|_| AllocError)
+    }
+
+    fn fits(layout: Layout) -> bool {
+        layout.size() <= Self::SIZE && layout.align() <= Self::ALIGN
+    }
+
+    unsafe fn push(&self, ptr: NonNull<u8>) {
+        ptr.cast::<Option<NonNull<u8>>>()
+            .as_ptr()
+            .write(self.free.get());
+        self.free.set(Some(ptr));
+        self.num_free.set(self.num_free.get() + 1);
+    }
+
+    fn pop(&self) -> Option<NonNull<u8>> {
+        let ptr = self.free.get()?;
+        unsafe {
+            self.free
+                .set(ptr.cast::<Option<NonNull<u8>>>().as_ptr().read())
+        };
+        self.num_free.set(self.num_free.get() - 1);
+        Some(ptr)
+    }
+
+    fn grow_pool(&self) -> Result<(), AllocError> {
+        if SLAB_BLOCKS == 0 {
+            return Err(AllocError);
+        }
+        let slab = self
+            .parent
+            .allocate(Self::slab_layout()?)?
+            .as_non_null_ptr();
+        unsafe {
+            // The first block links the slabs
+            slab.cast::<Option<NonNull<u8>>>()
+                .as_ptr()
+                .write(self.slabs.get());
+            self.slabs.set(Some(slab));
+            self.num_slabs.set(self.num_slabs.get() + 1);
+            for block in (1..=SLAB_BLOCKS).rev() {
+                self.push(NonNull::new_unchecked(
+                    slab.as_ptr().add(block * Self::SIZE),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the first address of every slab until it returns `false`. `f` may
+    /// deallocate the slab.
+    fn for_each_slab(&self, mut f: impl FnMut(NonNull<u8>) -> bool) {
+        let mut slab = self.slabs.get();
+        while let Some(ptr) = slab {
+            slab = unsafe { ptr.cast::<Option<NonNull<u8>>>().as_ptr().read() };
+            if !f(ptr) {
+                return;
+            }
+        }
+    }
+
+    /// Returns all slabs to the parent allocator.
+    ///
+    /// All memory blocks allocated from the pool become invalid.
+    pub fn deallocate_all(&self) {
+        if let Ok(layout) = Self::slab_layout() {
+            self.for_each_slab(|slab| {
+                unsafe { self.parent.deallocate(slab, layout) };
+                true
+            });
+        }
+        self.slabs.set(None);
+        self.free.set(None);
+        self.num_slabs.set(0);
+        self.num_free.set(0);
+    }
+}
+
+impl<A: Allocator + Default, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Default
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn default() -> Self {
+        Self::new(A::default())
+    }
+}
+
+impl<A: Allocator + fmt::Debug, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> fmt::Debug
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrowablePool")
+            .field("parent", &self.parent)
+            .field("num_slabs", &self.num_slabs())
+            .field("num_free", &self.num_free())
+            .finish()
+    }
+}
+
+impl<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Drop
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn drop(&mut self) {
+        self.deallocate_all()
+    }
+}
+
+unsafe impl<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Allocator
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if !Self::fits(layout) {
+            return Err(AllocError);
+        }
+        if self.free.get().is_none() {
+            self.grow_pool()?;
+        }
+        let ptr = self.pop().ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, Self::SIZE))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.push(ptr)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if Self::fits(new_layout) {
+            Ok(NonNull::slice_from_raw_parts(ptr, Self::SIZE))
+        } else {
+            Err(AllocError)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.grow(ptr, old_layout, new_layout)?;
+        ptr.as_ptr()
+            .add(old_layout.size())
+            .write_bytes(0, memory.len() - old_layout.size());
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if Self::fits(new_layout) {
+            Ok(NonNull::slice_from_raw_parts(ptr, Self::SIZE))
+        } else {
+            Err(AllocError)
+        }
+    }
+}
+
+impl<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Owns
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.owns_ptr(memory.as_non_null_ptr())
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        let size = Self::SIZE * (SLAB_BLOCKS + 1);
+        let addr = ptr.as_ptr() as usize;
+        let mut owns = false;
+        self.for_each_slab(|slab| {
+            let start = slab.as_ptr() as usize + Self::SIZE;
+            owns = addr >= start && addr < slab.as_ptr() as usize + size;
+            !owns
+        });
+        owns
+    }
+}
+
+impl<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> BlockSize
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn block_size(&self) -> usize {
+        Self::SIZE
+    }
+}
+
+impl<A: Allocator, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> ReportUsage
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn usage(&self) -> Usage {
+        let slabs = self.num_slabs();
+        Usage::new(
+            slabs * (SLAB_BLOCKS + 1) * Self::SIZE,
+            self.num_free() * Self::SIZE,
+            slabs * Self::SIZE,
+        )
+    }
+}
+
+impl<A: Allocator + Describe, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Describe
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GrowablePool({} blocks, {} per slab, {} slabs)",
+            Bytes(Self::SIZE),
+            SLAB_BLOCKS,
+            self.num_slabs()
+        )
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.parent)
+    }
+}
+
+impl<A: Allocator + Purge, const BLOCK_SIZE: usize, const SLAB_BLOCKS: usize> Purge
+    for GrowablePool<A, BLOCK_SIZE, SLAB_BLOCKS>
+{
+    fn purge(&self, level: PurgeLevel) {
+        self.parent.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GrowablePool;
+    use crate::{helper::tracker, Allocator, Owns, ReportUsage};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::alloc::Layout;
+
+    #[test]
+    fn reuse_and_grow() {
+        let mut pool = GrowablePool::<_, 3, 2>::new(tracker(Global));
+        assert_eq!(GrowablePool::<Global, 3, 2>::SIZE, 8);
+        assert_eq!(GrowablePool::<Global, 3, 2>::ALIGN, 8);
+        assert_eq!(GrowablePool::<Global, 48, 2>::ALIGN, 16);
+
+        let layout = Layout::new::<u32>();
+        let blocks = (0..3)
+            .map(|_| pool.allocate(layout).expect("Could not allocate 4 bytes"))
+            .collect::<Vec<_>>();
+        assert_eq!(pool.num_slabs(), 2);
+        assert_eq!(pool.num_free(), 1);
+        assert!(blocks.iter().all(|&block| pool.owns(block)));
+        assert!(!pool.owns_ptr(core::ptr::NonNull::dangling()));
+
+        unsafe { pool.deallocate(blocks[1].as_non_null_ptr(), layout) };
+        let reused = pool.allocate(layout).expect("Could not allocate 4 bytes");
+        assert_eq!(reused, blocks[1]);
+        assert_eq!(pool.num_slabs(), 2);
+
+        let usage = pool.usage();
+        assert_eq!(usage.capacity, 2 * 3 * 8);
+        assert_eq!(usage.used, 3 * 8);
+        assert_eq!(usage.overhead, 2 * 8);
+
+        assert!(pool.allocate(Layout::new::<u128>()).is_err());
+        unsafe {
+            let grown = pool
+                .grow_zeroed(
+                    reused.as_non_null_ptr(),
+                    Layout::new::<u32>(),
+                    Layout::new::<u64>(),
+                )
+                .expect("Could not grow in place");
+            assert_eq!(grown.as_non_null_ptr(), reused.as_non_null_ptr());
+            assert_eq!(*grown.as_mut_ptr().add(4), 0);
+            assert_eq!(*grown.as_mut_ptr().add(7), 0);
+        }
+
+        assert!(pool.parent_mut().is_none());
+        pool.deallocate_all();
+        assert_eq!(pool.num_slabs(), 0);
+        assert_eq!(pool.usage(), Default::default());
+        assert!(pool.parent_mut().is_some());
+    }
+}
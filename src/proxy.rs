@@ -1,21 +1,49 @@
-use crate::{AllocateAll, CallbackRef, Owns, ReallocateInPlace};
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    CallbackRef,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
+    alloc::{AllocError, Layout},
+    fmt,
     ptr::NonNull,
 };
 
-/// Calls the provided callbacks when invoking methods on `AllocRef`.
+/// Calls the provided callbacks when invoking methods on `Allocator`.
 ///
 /// A typical use case for a `Proxy` allocator is collecting statistics. `alloc-compose` provides
 /// different implementations for [`CallbackRef`][].
 ///
+/// The callbacks are invoked regardless of how the `Proxy` is nested, e.g. when it's passed by
+/// reference to another combinator or when it's stored in a `Box`, `Rc`, or `Arc`. This also
+/// applies to [`Owns::owns`].
+///
+/// `CallbackRef` is object safe, so the callbacks can be chosen at runtime, e.g. as
+/// `&dyn CallbackRef` or `Box<dyn CallbackRef + Send + Sync>`. A `&Proxy<A, C>` also coerces to
+/// `&Proxy<A, dyn CallbackRef>`.
+///
+/// If `A` implements [`AllocateAll`], callbacks can opt in to receive its [`capacity_left`]
+/// after every request, e.g. to track a high-water mark with [`stats::Watermark`].
+///
+/// [`capacity_left`]: AllocateAll::capacity_left
+/// [`stats::Watermark`]: crate::stats::Watermark
+///
 /// # Examples
 ///
 /// ```rust
 /// #![feature(allocator_api, slice_ptr_get)]
 ///
 /// use alloc_compose::{stats, CallbackRef, Proxy};
-/// use std::alloc::{AllocRef, Layout, System};
+/// use std::alloc::{Allocator, Layout, System};
 ///
 /// let counter = stats::Counter::default();
 /// let mut alloc = Proxy {
@@ -24,8 +52,8 @@ use core::{
 /// };
 ///
 /// unsafe {
-///     let memory = alloc.alloc(Layout::new::<u32>())?;
-///     alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<u32>());
+///     let memory = alloc.allocate(Layout::new::<u32>())?;
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>());
 /// }
 ///
 /// assert_eq!(counter.num_allocs(), 1);
@@ -39,7 +67,7 @@ use core::{
 /// ```rust
 /// # #![feature(allocator_api, slice_ptr_get)]
 /// # use alloc_compose::{stats, CallbackRef, Proxy};
-/// # use std::alloc::{AllocRef, Layout};
+/// # use std::alloc::{Allocator, Layout};
 /// use alloc_compose::{
 ///     region::Region,
 ///     stats::{AllocInitFilter, ResultFilter},
@@ -54,10 +82,12 @@ use core::{
 /// };
 ///
 /// unsafe {
-///     let memory = alloc.alloc(Layout::new::<u32>())?;
-///     alloc.dealloc(memory.as_non_null_ptr(), Layout::new::<u32>());
+///     let memory = alloc.allocate(Layout::new::<u32>())?;
+///     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u32>());
 ///
-///     alloc.alloc_zeroed(Layout::new::<[u32; 64]>()).unwrap_err();
+///     alloc
+///         .allocate_zeroed(Layout::new::<[u32; 64]>())
+///         .unwrap_err();
 /// }
 ///
 /// assert_eq!(counter.num_allocates(), 2);
@@ -71,35 +101,115 @@ use core::{
 /// );
 /// # Ok::<(), core::alloc::AllocError>(())
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Proxy<A, C> {
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Proxy<A, C: ?Sized> {
     pub alloc: A,
     pub callbacks: C,
 }
 
-unsafe impl<A: AllocRef, C: CallbackRef> AllocRef for Proxy<A, C> {
+impl<A, C: CallbackRef> Proxy<A, C> {
+    /// Creates a proxy around `alloc` and calls [`CallbackRef::on_create`].
+    ///
+    /// In constant contexts, the proxy can be created by a struct expression instead, which
+    /// doesn't call `on_create`.
+    pub fn new(alloc: A, callbacks: C) -> Self {
+        callbacks.on_create();
+        Self { alloc, callbacks }
+    }
+
+    /// Calls [`CallbackRef::on_destroy`] and returns the allocator and the callbacks.
+    ///
+    /// Dropping a proxy does not call `on_destroy`, so this should be used, when the callbacks
+    /// need to release resources.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{CallbackRef, Proxy};
+    /// use core::cell::Cell;
+    /// use std::alloc::System;
+    ///
+    /// #[derive(Default)]
+    /// struct Gauge {
+    ///     registered: Cell<bool>,
+    /// }
+    ///
+    /// unsafe impl CallbackRef for Gauge {
+    ///     fn on_create(&self) {
+    ///         self.registered.set(true);
+    ///     }
+    ///
+    ///     fn on_destroy(&self) {
+    ///         self.registered.set(false);
+    ///     }
+    /// }
+    ///
+    /// let gauge = Gauge::default();
+    /// let alloc = Proxy::new(System, gauge.by_ref());
+    /// assert!(gauge.registered.get());
+    ///
+    /// alloc.destroy();
+    /// assert!(!gauge.registered.get());
+    /// ```
+    pub fn destroy(self) -> (A, C) {
+        self.callbacks.on_destroy();
+        (self.alloc, self.callbacks)
+    }
+}
+
+impl<A, C> Proxy<A, C> {
+    /// Replaces the callbacks, e.g. to observe a default-constructed allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{stats::Counter, CallbackRef, Proxy};
+    /// use std::alloc::{Allocator, Layout, System};
+    ///
+    /// let counter = Counter::default();
+    /// let alloc = Proxy::<System, ()>::default().with_callbacks(counter.by_ref());
+    /// alloc.allocate(Layout::new::<[u8; 0]>())?;
+    /// assert_eq!(counter.num_allocs(), 1);
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    pub fn with_callbacks<C2>(self, callbacks: C2) -> Proxy<A, C2> {
+        Proxy {
+            alloc: self.alloc,
+            callbacks,
+        }
+    }
+}
+
+unsafe impl<A: Allocator, C: CallbackRef + ?Sized> Allocator for Proxy<A, C> {
     #[track_caller]
-    fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         self.callbacks.before_allocate(layout);
-        let result = self.alloc.alloc(layout);
+        let result = self.alloc.allocate(layout);
         self.callbacks.after_allocate(layout, result);
+        self.sample_capacity_left();
         result
     }
 
     #[track_caller]
-    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         self.callbacks.before_allocate_zeroed(layout);
-        let result = self.alloc.alloc_zeroed(layout);
+        let result = self.alloc.allocate_zeroed(layout);
         self.callbacks.after_allocate_zeroed(layout, result);
+        self.sample_capacity_left();
         result
     }
 
     #[track_caller]
-    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
-        crate::check_dealloc_precondition(ptr, layout);
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
         self.callbacks.before_deallocate(ptr, layout);
-        self.alloc.dealloc(ptr, layout);
+        self.alloc.deallocate(ptr, layout);
         self.callbacks.after_deallocate(ptr, layout);
+        self.sample_capacity_left();
     }
 
     #[track_caller]
@@ -114,6 +224,7 @@ unsafe impl<A: AllocRef, C: CallbackRef> AllocRef for Proxy<A, C> {
         let result = self.alloc.grow(ptr, old_layout, new_layout);
         self.callbacks
             .after_grow(ptr, old_layout, new_layout, result);
+        self.sample_capacity_left();
         result
     }
 
@@ -130,6 +241,7 @@ unsafe impl<A: AllocRef, C: CallbackRef> AllocRef for Proxy<A, C> {
         let result = self.alloc.grow_zeroed(ptr, old_layout, new_layout);
         self.callbacks
             .after_grow_zeroed(ptr, old_layout, new_layout, result);
+        self.sample_capacity_left();
         result
     }
 
@@ -145,16 +257,18 @@ unsafe impl<A: AllocRef, C: CallbackRef> AllocRef for Proxy<A, C> {
         let result = self.alloc.shrink(ptr, old_layout, new_layout);
         self.callbacks
             .after_shrink(ptr, old_layout, new_layout, result);
+        self.sample_capacity_left();
         result
     }
 }
 
-unsafe impl<A: AllocateAll, C: CallbackRef> AllocateAll for Proxy<A, C> {
+unsafe impl<A: AllocateAll, C: CallbackRef + ?Sized> AllocateAll for Proxy<A, C> {
     #[track_caller]
     fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
         self.callbacks.before_allocate_all();
         let result = self.alloc.allocate_all();
         self.callbacks.after_allocate_all(result);
+        self.sample_capacity_left();
         result
     }
 
@@ -163,6 +277,7 @@ unsafe impl<A: AllocateAll, C: CallbackRef> AllocateAll for Proxy<A, C> {
         self.callbacks.before_allocate_all_zeroed();
         let result = self.alloc.allocate_all_zeroed();
         self.callbacks.after_allocate_all_zeroed(result);
+        self.sample_capacity_left();
         result
     }
 
@@ -171,6 +286,8 @@ unsafe impl<A: AllocateAll, C: CallbackRef> AllocateAll for Proxy<A, C> {
         self.callbacks.before_deallocate_all();
         self.alloc.deallocate_all();
         self.callbacks.after_deallocate_all();
+        self.callbacks.on_reset();
+        self.sample_capacity_left();
     }
 
     #[track_caller]
@@ -196,9 +313,15 @@ unsafe impl<A: AllocateAll, C: CallbackRef> AllocateAll for Proxy<A, C> {
     fn is_full(&self) -> bool {
         self.alloc.is_full()
     }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
 }
 
-unsafe impl<A: ReallocateInPlace, C: CallbackRef> ReallocateInPlace for Proxy<A, C> {
+unsafe impl<A: ReallocateInPlace, C: CallbackRef + ?Sized> ReallocateInPlace for Proxy<A, C> {
     #[track_caller]
     unsafe fn grow_in_place(
         &self,
@@ -212,6 +335,7 @@ unsafe impl<A: ReallocateInPlace, C: CallbackRef> ReallocateInPlace for Proxy<A,
         let result = self.alloc.grow_in_place(ptr, old_layout, new_layout);
         self.callbacks
             .after_grow_in_place(ptr, old_layout, new_layout, result);
+        self.sample_capacity_left();
         result
     }
 
@@ -228,6 +352,7 @@ unsafe impl<A: ReallocateInPlace, C: CallbackRef> ReallocateInPlace for Proxy<A,
         let result = self.alloc.grow_in_place_zeroed(ptr, old_layout, new_layout);
         self.callbacks
             .after_grow_in_place_zeroed(ptr, old_layout, new_layout, result);
+        self.sample_capacity_left();
         result
     }
 
@@ -244,15 +369,282 @@ unsafe impl<A: ReallocateInPlace, C: CallbackRef> ReallocateInPlace for Proxy<A,
         let result = self.alloc.shrink_in_place(ptr, old_layout, new_layout);
         self.callbacks
             .after_shrink_in_place(ptr, old_layout, new_layout, result);
+        self.sample_capacity_left();
         result
     }
 }
 
-impl<A: Owns, C: CallbackRef> Owns for Proxy<A, C> {
+/// Passes the capacity left of the allocator to the callbacks, if it's known and requested.
+trait SampleCapacityLeft {
+    fn sample_capacity_left(&self);
+}
+
+impl<A, C: CallbackRef + ?Sized> SampleCapacityLeft for Proxy<A, C> {
+    #[inline]
+    default fn sample_capacity_left(&self) {}
+}
+
+impl<A: AllocateAll, C: CallbackRef + ?Sized> SampleCapacityLeft for Proxy<A, C> {
+    #[inline]
+    fn sample_capacity_left(&self) {
+        if self.callbacks.samples_capacity_left() {
+            self.callbacks
+                .after_capacity_left(self.alloc.capacity_left())
+        }
+    }
+}
+
+impl<A: ReportUsage, C: ?Sized> ReportUsage for Proxy<A, C> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns, C: CallbackRef + ?Sized> Owns for Proxy<A, C> {
     fn owns(&self, ptr: NonNull<[u8]>) -> bool {
         self.callbacks.before_owns();
         let owns = self.alloc.owns(ptr);
         self.callbacks.after_owns(owns);
         owns
     }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.callbacks.before_owns();
+        let owns = self.alloc.owns_ptr(ptr);
+        self.callbacks.after_owns(owns);
+        owns
+    }
+}
+
+impl<A: BlockSize, C: ?Sized> BlockSize for Proxy<A, C> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.alloc.block_size()
+    }
+}
+
+impl<A: Describe, C: ?Sized> Describe for Proxy<A, C> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Proxy")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, C: ?Sized> Purge for Proxy<A, C> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::Proxy;
+    use crate::{
+        region::{Region, Up},
+        stats::{AtomicCounter, Counter},
+        AllocateAll,
+        Allocator,
+        CallbackRef,
+        Chunk,
+        Fallback,
+        Null,
+        Owns,
+        PoisonOnMove,
+        ReallocateInPlace,
+        Segregate,
+    };
+    use alloc::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
+    use core::{alloc::Layout, cell::Cell, mem::MaybeUninit, ptr::NonNull};
+
+    fn alloc_owned<A: Allocator + Owns>(alloc: A) -> NonNull<[u8]> {
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        assert!(alloc.owns(memory));
+        memory
+    }
+
+    #[test]
+    fn owns_callbacks() {
+        let mut data = [MaybeUninit::uninit(); 256];
+        let region = Region::new(&mut data);
+        let counter = Rc::new(Counter::default());
+        let proxy = Proxy {
+            alloc: &region,
+            callbacks: Rc::clone(&counter),
+        };
+
+        alloc_owned(&proxy);
+        assert_eq!(counter.num_owns(), 1);
+
+        alloc_owned(Fallback {
+            primary: Null,
+            secondary: &proxy,
+        });
+        assert_eq!(counter.num_owns(), 2);
+
+        alloc_owned(Chunk::<_, 16>(&proxy));
+        assert_eq!(counter.num_owns(), 3);
+
+        alloc_owned(Segregate::<_, Null, 64> {
+            small: &proxy,
+            large: Null,
+        });
+        assert_eq!(counter.num_owns(), 4);
+
+        alloc_owned(PoisonOnMove(&proxy));
+        assert_eq!(counter.num_owns(), 5);
+
+        let outer = Counter::default();
+        alloc_owned(Proxy {
+            alloc: &proxy,
+            callbacks: outer.by_ref(),
+        });
+        assert_eq!(counter.num_owns(), 6);
+        assert_eq!(outer.num_owns(), 1);
+    }
+
+    #[test]
+    fn dyn_callbacks() {
+        fn callbacks(atomic: bool) -> Box<dyn CallbackRef + Send + Sync> {
+            if atomic {
+                Box::new(AtomicCounter::default())
+            } else {
+                Box::new(())
+            }
+        }
+
+        let mut data = [MaybeUninit::uninit(); 64];
+        let region = Region::new(&mut data);
+        for &atomic in &[false, true] {
+            let proxy = Proxy {
+                alloc: &region,
+                callbacks: callbacks(atomic),
+            };
+            alloc_owned(&proxy);
+        }
+
+        let counter = Counter::default();
+        let proxy: Proxy<_, &dyn CallbackRef> = Proxy {
+            alloc: &region,
+            callbacks: &counter,
+        };
+        alloc_owned(proxy);
+        assert_eq!(counter.num_allocs(), 1);
+
+        let proxy = Proxy {
+            alloc: &region,
+            callbacks: Counter::default(),
+        };
+        let unsized_proxy: &Proxy<_, dyn CallbackRef> = &proxy;
+        alloc_owned(unsized_proxy);
+        assert_eq!(proxy.callbacks.num_owns(), 1);
+    }
+
+    #[test]
+    fn owns_smart_pointers() {
+        let mut data = [MaybeUninit::uninit(); 64];
+        let region = Region::new(&mut data);
+        let counter = Counter::default();
+        let memory = alloc_owned(&region);
+
+        let proxy = Box::new(Proxy {
+            alloc: &region,
+            callbacks: counter.by_ref(),
+        });
+        assert!(proxy.owns(memory));
+        assert_eq!(counter.num_owns(), 1);
+
+        let proxy = Rc::new(*proxy);
+        assert!(Rc::clone(&proxy).owns(memory));
+        assert_eq!(counter.num_owns(), 2);
+
+        let proxy = Arc::new(Proxy {
+            alloc: Null,
+            callbacks: Arc::new(AtomicCounter::default()),
+        });
+        assert!(!Arc::clone(&proxy).owns(memory));
+        assert_eq!(proxy.callbacks.num_owns(), 1);
+    }
+
+    #[test]
+    fn by_ref() {
+        fn exercise(alloc: impl Allocator + AllocateAll + ReallocateInPlace + Owns) {
+            let memory = alloc_owned(&alloc);
+            unsafe {
+                alloc
+                    .grow_in_place(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 8]>(),
+                        Layout::new::<[u8; 16]>(),
+                    )
+                    .expect("Could not grow in place to 16 bytes");
+            }
+            alloc.deallocate_all();
+        }
+
+        let mut data = [MaybeUninit::uninit(); 64];
+        let proxy = Proxy {
+            alloc: Region::<Up>::with_direction(&mut data),
+            callbacks: Counter::default(),
+        };
+
+        exercise(proxy.by_ref());
+        let unsized_proxy: &Proxy<_, dyn CallbackRef> = &proxy;
+        exercise(unsized_proxy);
+        assert_eq!(proxy.callbacks.num_allocs(), 2);
+        assert_eq!(proxy.callbacks.num_grows(), 2);
+        assert_eq!(proxy.callbacks.num_owns(), 2);
+        assert_eq!(proxy.callbacks.num_deallocs(), 2);
+
+        let mut vec = Vec::new_in(proxy.by_ref());
+        vec.extend_from_slice(&[1_u8, 2, 3]);
+        assert!(proxy.owns(NonNull::from(vec.as_slice())));
+        assert_eq!(proxy.callbacks.num_allocs(), 3);
+        assert_eq!(proxy.callbacks.num_owns(), 3);
+    }
+
+    #[test]
+    fn lifecycle() {
+        #[derive(Default)]
+        struct Lifecycle(Cell<[u8; 3]>);
+
+        impl Lifecycle {
+            fn record(&self, event: usize) {
+                let mut events = self.0.get();
+                events[event] += 1;
+                self.0.set(events);
+            }
+        }
+
+        unsafe impl CallbackRef for Lifecycle {
+            fn on_create(&self) {
+                self.record(0)
+            }
+
+            fn on_destroy(&self) {
+                self.record(1)
+            }
+
+            fn on_reset(&self) {
+                self.record(2)
+            }
+        }
+
+        let mut data = [MaybeUninit::uninit(); 64];
+        let lifecycle = Lifecycle::default();
+        let proxy = Proxy::new(Region::new(&mut data), lifecycle.by_ref());
+        assert_eq!(lifecycle.0.get(), [1, 0, 0]);
+
+        proxy.deallocate_all();
+        proxy.by_ref().deallocate_all();
+        assert_eq!(lifecycle.0.get(), [1, 0, 2]);
+
+        let (region, _) = proxy.destroy();
+        assert!(region.is_empty());
+        assert_eq!(lifecycle.0.get(), [1, 1, 2]);
+    }
 }
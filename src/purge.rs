@@ -0,0 +1,105 @@
+/// How much cached memory [`Purge::purge`] should give back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PurgeLevel {
+    /// Only release memory, which is cheap to reach, e.g. the cache of the calling thread.
+    Trim,
+    /// Release all cached memory, even if this requires taking locks of other threads.
+    Full,
+}
+
+/// Returns memory held by caching layers to the allocators they are built upon.
+///
+/// Caches like [`DeferredFree`] or [`TlsCache`] keep memory blocks, which are logically
+/// deallocated, to make later requests cheaper. `purge` flushes them, e.g. on a low-memory signal
+/// of the application. Combinators forward it to every allocator they are composed of, so a
+/// single call on the outermost allocator flushes all caches of the composition. Allocators
+/// without a cache keep the default implementation, which does nothing.
+///
+/// [`DeferredFree`]: crate::DeferredFree
+/// [`TlsCache`]: crate::TlsCache
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{
+///     region::Region,
+///     stats::Counter,
+///     DeferredFree,
+///     Fallback,
+///     Proxy,
+///     Purge,
+///     PurgeLevel,
+/// };
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut primary = [MaybeUninit::uninit(); 64];
+/// let mut secondary = [MaybeUninit::uninit(); 256];
+/// let alloc = Fallback {
+///     primary: Region::new(&mut primary),
+///     secondary: DeferredFree::<_, 8>::new(Proxy::new(
+///         Region::new(&mut secondary),
+///         Counter::default(),
+///     )),
+/// };
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 128]>())?;
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 128]>()) };
+/// assert_eq!(alloc.secondary.queued(), 1);
+///
+/// alloc.purge(PurgeLevel::Trim);
+/// assert_eq!(alloc.secondary.get_ref().callbacks.num_deallocs(), 1);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub trait Purge {
+    /// Releases cached memory of this allocator and the allocators it is composed of.
+    #[allow(unused_variables)]
+    #[inline]
+    fn purge(&self, level: PurgeLevel) {}
+}
+
+impl<P: Purge + ?Sized> Purge for &P {
+    #[inline]
+    fn purge(&self, level: PurgeLevel) {
+        (**self).purge(level)
+    }
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+impl<P: Purge + ?Sized> Purge for alloc::boxed::Box<P> {
+    #[inline]
+    fn purge(&self, level: PurgeLevel) {
+        (**self).purge(level)
+    }
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+impl<P: Purge + ?Sized> Purge for alloc::rc::Rc<P> {
+    #[inline]
+    fn purge(&self, level: PurgeLevel) {
+        (**self).purge(level)
+    }
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+impl<P: Purge + ?Sized> Purge for alloc::sync::Arc<P> {
+    #[inline]
+    fn purge(&self, level: PurgeLevel) {
+        (**self).purge(level)
+    }
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+impl Purge for alloc::alloc::Global {}
+
+#[cfg(any(doc, feature = "std"))]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+impl Purge for std::alloc::System {}
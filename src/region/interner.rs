@@ -0,0 +1,219 @@
+use super::Region;
+use crate::{helper::Fnv, AllocateAll, Allocator};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    hash::Hasher,
+    ptr::NonNull,
+    slice,
+    str,
+};
+
+/// Stores deduplicated strings and byte slices in a [`Region`].
+///
+/// Interning a string, which was interned before, returns the stored copy, so equal strings share
+/// their memory and can be compared by address. The index is an open-addressing hash table, which
+/// is also allocated from the region. When it grows, the previous table stays in the region, as
+/// regions don't reclaim memory.
+///
+/// The interner owns the region and never resets it, so the returned slices live as long as the
+/// memory of the region.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::region::{Interner, Region};
+/// use core::mem::MaybeUninit;
+///
+/// let mut data = [MaybeUninit::uninit(); 256];
+/// let mut interner = Interner::new(Region::new(&mut data));
+///
+/// let first = interner.intern("identifier")?;
+/// let second = interner.intern(&String::from("identifier"))?;
+/// assert!(core::ptr::eq(first, second));
+/// assert_eq!(interner.len(), 1);
+/// assert_eq!(interner.get("identifier"), Some(first));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct Interner<'mem> {
+    region: Region<'mem>,
+    table: &'mem mut [Option<&'mem [u8]>],
+    len: usize,
+}
+
+impl<'mem> Interner<'mem> {
+    /// The number of slots allocated for the first entry.
+    const MIN_SLOTS: usize = 8;
+
+    /// Interns into `region`.
+    pub fn new(region: Region<'mem>) -> Self {
+        Self {
+            region,
+            table: &mut [],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of interned strings and byte slices.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true`, if nothing was interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes left in the region.
+    pub fn capacity_left(&self) -> usize {
+        self.region.capacity_left()
+    }
+
+    /// Returns the stored copy of `string`, storing it first, if it wasn't interned yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the region is exhausted. The interner stays usable.
+    pub fn intern(&mut self, string: &str) -> Result<&'mem str, AllocError> {
+        let bytes = self.intern_bytes(string.as_bytes())?;
+        Ok(unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Returns the stored copy of `bytes`, storing it first, if it wasn't interned yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the region is exhausted. The interner stays usable.
+    pub fn intern_bytes(&mut self, bytes: &[u8]) -> Result<&'mem [u8], AllocError> {
+        if let Some(stored) = self.get_bytes(bytes) {
+            return Ok(stored);
+        }
+        if (self.len + 1) * 4 > self.table.len() * 3 {
+            self.grow()?;
+        }
+
+        let memory = self.region.allocate(Layout::for_value(bytes))?;
+        let stored = unsafe {
+            let ptr = memory.as_mut_ptr();
+            ptr.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len());
+            slice::from_raw_parts(ptr, bytes.len())
+        };
+        let slot = self.find_slot(stored);
+        self.table[slot] = Some(stored);
+        self.len += 1;
+        Ok(stored)
+    }
+
+    /// Returns the stored copy of `string` without interning it.
+    pub fn get(&self, string: &str) -> Option<&'mem str> {
+        self.get_bytes(string.as_bytes())
+            .map(|bytes| unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Returns the stored copy of `bytes` without interning it.
+    pub fn get_bytes(&self, bytes: &[u8]) -> Option<&'mem [u8]> {
+        if self.table.is_empty() {
+            return None;
+        }
+        self.table[self.find_slot(bytes)]
+    }
+
+    /// Returns the slot, which holds `bytes` or the empty slot, where it has to be inserted.
+    fn find_slot(&self, bytes: &[u8]) -> usize {
+        let mask = self.table.len() - 1;
+        let mut hasher = Fnv::default();
+        hasher.write(bytes);
+        let mut slot = hasher.finish() as usize & mask;
+        loop {
+            match self.table[slot] {
+                Some(stored) if stored != bytes => slot = (slot + 1) & mask,
+                _ => return slot,
+            }
+        }
+    }
+
+    fn grow(&mut self) -> Result<(), AllocError> {
+        let slots = (self.table.len() * 2).max(Self::MIN_SLOTS);
+        let layout = Layout::array::<Option<&[u8]>>(slots).map_err(|_| AllocError)?;
+        let ptr: NonNull<Option<&'mem [u8]>> =
+            self.region.allocate(layout)?.as_non_null_ptr().cast();
+        let table = unsafe {
+            for i in 0..slots {
+                ptr.as_ptr().add(i).write(None);
+            }
+            slice::from_raw_parts_mut(ptr.as_ptr(), slots)
+        };
+
+        let old = core::mem::replace(&mut self.table, table);
+        for &stored in old.iter().flatten() {
+            let slot = self.find_slot(stored);
+            self.table[slot] = Some(stored);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Interner<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interner")
+            .field("len", &self.len)
+            .field("slots", &self.table.len())
+            .field("capacity_left", &self.capacity_left())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+    use crate::region::Region;
+    use alloc::format;
+    use core::{mem::MaybeUninit, ptr};
+
+    #[test]
+    fn intern() {
+        let mut data = [MaybeUninit::new(0); 1024];
+        let mut interner = Interner::new(Region::new(&mut data));
+        assert_eq!(interner.get("missing"), None);
+
+        let empty = interner
+            .intern("")
+            .expect("Could not intern an empty string");
+        assert_eq!(empty, "");
+        let names = (0..20)
+            .map(|i| interner.intern(&format!("name{}", i)))
+            .collect::<Result<alloc::vec::Vec<_>, _>>()
+            .expect("Could not intern 20 names");
+        assert_eq!(interner.len(), 21);
+
+        for (i, name) in names.iter().enumerate() {
+            let again = interner
+                .intern(&format!("name{}", i))
+                .expect("Could not look up a name");
+            assert!(ptr::eq(*name, again));
+        }
+        assert_eq!(interner.len(), 21);
+        assert_eq!(interner.get_bytes(b"name7"), Some(&b"name7"[..]));
+        assert_eq!(
+            interner.intern_bytes(&[0xFF, 0]),
+            Ok(&[0xFF, 0][..]),
+            "Could not intern bytes"
+        );
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut data = [MaybeUninit::new(0); 160];
+        let mut interner = Interner::new(Region::new(&mut data));
+        let first = interner.intern("first").expect("Could not intern a string");
+        interner
+            .intern(&"x".repeat(64))
+            .expect_err("Interned more than the region holds");
+
+        assert_eq!(interner.len(), 1);
+        assert_eq!(interner.get("first"), Some(first));
+        assert_eq!(interner.intern("first"), Ok(first));
+    }
+}
@@ -4,9 +4,9 @@
 //! deallocation, and once the region is full, allocation requests returns [`AllocError`].
 //! A region only stores a reference to the provided memory and a pointer to the current position.
 //!
-//! This module provides three kinds of stack-based allocators: [`Region`], [`SharedRegion`], and
-//! [`IntrusiveRegion`]. All three allocators uses a user-provided memory to allocate and differ
-//! in the way how they store the pointer to the current position.
+//! This module provides four kinds of stack-based allocators: [`Region`], [`SharedRegion`],
+//! [`BorrowedRegion`], and [`IntrusiveRegion`]. All four allocators uses a user-provided memory to
+//! allocate and differ in the way how they store the pointer to the current position.
 //!
 //! # Which region allocator to chose?
 //!
@@ -15,21 +15,49 @@
 //!
 //! - [`Region`] stores a current position in a [`Cell`] right next to the reference to the memory.
 //! - [`SharedRegion`] wraps the [`Cell`] in a [`RC`] to support cloning of the allocator.
-//! - [`IntrusiveRegion`] stores the pointer to the current position at the end of the provided
-//!   memory block.
+//! - [`BorrowedRegion`] borrows the [`Cell`] in a [`RegionState`] provided by the user, which
+//!   supports cloning without the `alloc`-feature.
+//! - [`IntrusiveRegion`] stores the current position, and optionally user-defined metadata, at the
+//!   end of the provided memory block.
 //!
-//! This results in the fact, that [`Region`] cannot be cloned. However, using [`AllocRef::by_ref`]
+//! This results in the fact, that [`Region`] cannot be cloned. However, using [`Allocator::by_ref`]
 //! returns a reference to the region, which can itself be cloned.
-//! [`SharedRegion`] and [`IntrusiveRegion`] both can be cloned. The [`IntrusiveRegion`] has a
+//! [`SharedRegion`], [`BorrowedRegion`], and [`IntrusiveRegion`] can be cloned. The [`IntrusiveRegion`] has a
 //! better performance in most cases due to cache coherence, but it's hard to say exactly, how much
 //! capacity the allocator will have exactly, as the pointer to the current position has to be well
 //! aligned. If this feature is important, [`SharedRegion`] or [`Region`] should be used instead.
 //! [`SharedRegion`] is only available with the `alloc`-feature, as it requires the [`Rc`] to
-//! allocate memory to store the pointer in.
+//! allocate memory to store the pointer in. [`BorrowedRegion`] is the `core`-only alternative.
+//!
+//! Two regions compare equal, if they manage the same memory block, regardless of their current
+//! position. Hashing is consistent with this, so regions can be used as keys, e.g. to look up
+//! allocators in testing infrastructure.
 //!
 //! [`Rc`]: alloc::rc::Rc
 //! [`Cell`]: core::cell::Cell
 //!
+//! # Direction
+//!
+//! By default, a region bumps [`Down`] from the end of the memory towards the start. Every
+//! region can also be created with [`with_direction`] to bump [`Up`] instead. Then, memory blocks
+//! are laid out in the order they are allocated, and the last memory block can be resized in
+//! place.
+//!
+//! [`DoubleEndedRegion`] combines both directions on the same memory block. It hands out one
+//! allocator for each end, which can be reset independently.
+//!
+//! [`MultiRegion`] chains several chunks of memory and moves on to the next chunk once the
+//! current one is exhausted.
+//!
+//! [`FixedRegion`] owns its memory, so its capacity is known at compile time.
+//!
+//! [`OffsetRegion`] refers to memory blocks by offsets relative to the start of the memory and
+//! stores its position inside of the memory, so it can be used in memory shared between processes. Its
+//! state can be observed from other threads with [`OffsetRegion::snapshot`] without interfering
+//! with allocations.
+//!
+//! [`with_direction`]: Region::with_direction
+//!
 //! ## Examples
 //!
 //! ```rust
@@ -37,14 +65,14 @@
 //!
 //! use alloc_compose::{region::Region, Owns};
 //! use core::{
-//!     alloc::{AllocRef, Layout},
+//!     alloc::{Allocator, Layout},
 //!     mem::MaybeUninit,
 //! };
 //!
 //! let mut data = [MaybeUninit::uninit(); 64];
 //! let region = Region::new(&mut data);
 //!
-//! let memory = region.alloc(Layout::new::<u32>())?;
+//! let memory = region.allocate(Layout::new::<u32>())?;
 //! assert!(region.owns(memory));
 //! # Ok::<(), core::alloc::AllocError>(())
 //! ```
@@ -55,7 +83,7 @@
 //! #![feature(nonnull_slice_from_raw_parts)]
 //! # #![feature(allocator_api)]
 //! # use alloc_compose::{region::Region, Owns};
-//! # use core::{alloc::{AllocRef, Layout}, mem::MaybeUninit};
+//! # use core::{alloc::{Allocator, Layout}, mem::MaybeUninit};
 //! # let mut data = [MaybeUninit::uninit(); 64];
 //! # let region = Region::new(&mut data);
 //!
@@ -70,15 +98,41 @@
 //! assert!(region.owns(memory));
 //! ```
 //!
+//! A region bumping upwards can grow the last memory block without moving it:
+//!
+//! ```rust
+//! #![feature(allocator_api, slice_ptr_get)]
+//!
+//! use alloc_compose::region::{Region, Up};
+//! use core::{
+//!     alloc::{Allocator, Layout},
+//!     mem::MaybeUninit,
+//! };
+//!
+//! let mut data = [MaybeUninit::uninit(); 64];
+//! let region: Region<Up> = Region::with_direction(&mut data);
+//!
+//! let memory = region.allocate(Layout::new::<[u8; 8]>())?;
+//! let grown = unsafe {
+//!     region.grow(
+//!         memory.as_non_null_ptr(),
+//!         Layout::new::<[u8; 8]>(),
+//!         Layout::new::<[u8; 32]>(),
+//!     )?
+//! };
+//! assert_eq!(memory.as_mut_ptr(), grown.as_mut_ptr());
+//! # Ok::<(), core::alloc::AllocError>(())
+//! ```
+//!
 //! To reset the allocator, [`AllocateAll::deallocate_all`] may be used:
 //!
 //! ```rust
 //! # #![feature(allocator_api)]
 //! # use alloc_compose::{region::Region, Owns};
-//! # use core::{alloc::{AllocRef, Layout}, mem::MaybeUninit};
+//! # use core::{alloc::{Allocator, Layout}, mem::MaybeUninit};
 //! # let mut data = [MaybeUninit::uninit(); 64];
 //! # let region = Region::new(&mut data);
-//! # let _ = region.alloc(Layout::new::<u32>())?;
+//! # let _ = region.allocate(Layout::new::<u32>())?;
 //! use alloc_compose::AllocateAll;
 //!
 //! assert!(!region.is_empty());
@@ -87,14 +141,39 @@
 //! # Ok::<(), core::alloc::AllocError>(())
 //! ```
 
+mod interner;
 pub mod raw;
 
+#[cfg(target_has_atomic = "ptr")]
+pub use self::raw::RegionSnapshot;
+pub use self::{
+    interner::Interner,
+    raw::{Direction, Down, FragmentationStats, RegionEnd, RegionMap, RegionState, Up},
+};
+
 use self::raw::*;
-use crate::{AllocateAll, Owns};
+use crate::{
+    allocate_many::{bump_many, ManyBlocks},
+    describe::describe_usage,
+    AllocateAll,
+    AllocateMany,
+    AllocateNear,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
+    alloc::{AllocError, Layout},
+    cell::{Cell, UnsafeCell},
+    fmt,
+    hash::{Hash, Hasher},
     marker::PhantomData,
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     ptr::NonNull,
 };
 
@@ -104,113 +183,1176 @@ use core::{
 /// outlive the underlying memory.
 ///
 /// For a version without lifetime see [`RawRegion`] instead.
-pub struct Region<'mem> {
-    raw: RawRegion,
+pub struct Region<'mem, D: Direction = Down> {
+    raw: RawRegion<D>,
+    _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
+}
+
+impl<'mem> Region<'mem> {
+    /// Creates a new region from the given memory block.
+    #[inline]
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        Self::with_direction(memory)
+    }
+}
+
+impl<'mem, D: Direction> Region<'mem, D> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    #[inline]
+    pub fn with_direction(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        let memory = NonNull::from(memory);
+        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
+        Self {
+            raw: unsafe { RawRegion::with_direction(memory) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A clonable region allocator based on `Rc`.
+///
+/// It holds a lifetime to the provided memory block, which ensures, that the allocator does not
+/// outlive the underlying memory.
+///
+/// For a version without lifetime see [`RawSharedRegion`] instead.
+#[derive(Clone)]
+#[cfg(any(doc, feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+pub struct SharedRegion<'mem, D: Direction = Down> {
+    raw: RawSharedRegion<D>,
+    _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+impl<'mem> SharedRegion<'mem> {
+    /// Creates a new region from the given memory block.
+    #[inline]
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        Self::with_direction(memory)
+    }
+}
+
+#[cfg(any(doc, feature = "alloc"))]
+impl<'mem, D: Direction> SharedRegion<'mem, D> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    #[inline]
+    pub fn with_direction(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        let memory = NonNull::from(memory);
+        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
+        Self {
+            raw: unsafe { RawSharedRegion::with_direction(memory) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// An intrusive region allocator, which stores the current posision in the provided memory.
+///
+/// It holds a lifetime to the provided memory block, which ensures, that the allocator does not
+/// outlive the underlying memory.
+///
+/// Besides the current position, the region can store user-defined metadata `M` at the end of the
+/// memory block, e.g. a name or a generation, which is created by [`with_metadata`]. The metadata
+/// is dropped together with the region. Only regions without metadata can be cloned, as cloning
+/// would break the exclusivity of [`metadata_mut`].
+///
+/// For a version without lifetime see [`RawIntrusiveRegion`] instead.
+///
+/// [`with_metadata`]: Self::with_metadata
+/// [`metadata_mut`]: Self::metadata_mut
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::region::{IntrusiveRegion, Up};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// struct Arena {
+///     name: &'static str,
+///     generation: u32,
+/// }
+///
+/// let mut data = [MaybeUninit::uninit(); 128];
+/// let mut region = IntrusiveRegion::<Up, _>::with_metadata(&mut data, Arena {
+///     name: "frame",
+///     generation: 0,
+/// });
+///
+/// region.allocate(Layout::new::<[u8; 16]>())?;
+/// region.metadata_mut().generation += 1;
+/// assert_eq!(region.metadata().name, "frame");
+/// assert_eq!(region.metadata().generation, 1);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct IntrusiveRegion<'mem, D: Direction = Down, M = ()> {
+    raw: RawIntrusiveRegion<D>,
+    metadata: NonNull<M>,
+    _marker: PhantomData<(&'mem mut [MaybeUninit<u8>], M)>,
+}
+
+impl<'mem> IntrusiveRegion<'mem> {
+    /// Creates a new region from the given memory block.
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not large enough to properly store a pointer.
+    /// See [`try_new`] for a fallible variant.
+    ///
+    /// [`try_new`]: Self::try_new
+    #[inline]
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        Self::with_direction(memory)
+    }
+
+    /// Creates a new region from the given memory block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`], when `memory` is not large enough to properly store a
+    /// pointer.
+    #[inline]
+    #[track_caller]
+    pub fn try_new(memory: &'mem mut [MaybeUninit<u8>]) -> Result<Self, ComposeError> {
+        Self::try_with_direction(memory)
+    }
+}
+
+impl<'mem, D: Direction> IntrusiveRegion<'mem, D> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not large enough to properly store a pointer.
+    /// See [`try_with_direction`] for a fallible variant.
+    ///
+    /// [`try_with_direction`]: Self::try_with_direction
+    #[inline]
+    pub fn with_direction(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        Self::with_metadata(memory, ())
+    }
+
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`], when `memory` is not large enough to properly store a
+    /// pointer.
+    #[inline]
+    #[track_caller]
+    pub fn try_with_direction(memory: &'mem mut [MaybeUninit<u8>]) -> Result<Self, ComposeError> {
+        Self::try_with_metadata(memory, ())
+    }
+}
+
+impl<'mem, D: Direction, M> IntrusiveRegion<'mem, D, M> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D` and
+    /// stores `metadata` next to the current position.
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not large enough to properly store a pointer and
+    /// the metadata. See [`try_with_metadata`] for a fallible variant.
+    ///
+    /// [`try_with_metadata`]: Self::try_with_metadata
+    pub fn with_metadata(memory: &'mem mut [MaybeUninit<u8>], metadata: M) -> Self {
+        Self::try_with_metadata(memory, metadata).expect("Could not store the state in region")
+    }
+
+    /// Creates a new region from the given memory block, which bumps in the direction `D` and
+    /// stores `metadata` next to the current position.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`], when `memory` is not large enough to properly store a
+    /// pointer and the metadata.
+    #[track_caller]
+    pub fn try_with_metadata(
+        memory: &'mem mut [MaybeUninit<u8>],
+        metadata: M,
+    ) -> Result<Self, ComposeError> {
+        let memory = NonNull::from(memory);
+        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
+        unsafe {
+            let (raw, header) = RawIntrusiveRegion::try_with_header(memory, Layout::new::<M>())?;
+            let header = header.cast::<M>();
+            header.as_ptr().write(metadata);
+            Ok(Self {
+                raw,
+                metadata: header,
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    /// Returns a reference to the metadata stored in the region.
+    #[inline]
+    pub fn metadata(&self) -> &M {
+        unsafe { self.metadata.as_ref() }
+    }
+
+    /// Returns a mutable reference to the metadata stored in the region.
+    #[inline]
+    pub fn metadata_mut(&mut self) -> &mut M {
+        unsafe { self.metadata.as_mut() }
+    }
+}
+
+impl<D: Direction + Clone> Clone for IntrusiveRegion<'_, D> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            metadata: self.metadata,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<D: Direction, M> Drop for IntrusiveRegion<'_, D, M> {
+    fn drop(&mut self) {
+        unsafe { self.metadata.as_ptr().drop_in_place() }
+    }
+}
+
+/// A clonable region allocator, which stores the current position in a [`RegionState`] provided
+/// by the user.
+///
+/// This is the alternative to [`SharedRegion`] without the `alloc`-feature. It holds a lifetime
+/// to the provided memory block and the state, which ensures, that the allocator does not outlive
+/// either of them.
+///
+/// For a version without lifetime see [`RawBorrowedRegion`] instead.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{
+///     region::{BorrowedRegion, RegionState},
+///     AllocateAll,
+/// };
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let state = RegionState::new();
+/// let region = BorrowedRegion::new(&mut data, &state);
+/// let clone = region.clone();
+///
+/// region.allocate(Layout::new::<[u8; 16]>())?;
+/// assert_eq!(clone.capacity_left(), 48);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Clone)]
+pub struct BorrowedRegion<'mem, D: Direction = Down> {
+    raw: RawBorrowedRegion<D>,
+    _marker: PhantomData<(&'mem mut [MaybeUninit<u8>], &'mem RegionState)>,
+}
+
+impl<'mem> BorrowedRegion<'mem> {
+    /// Creates a new region from the given memory block and resets `state`.
+    #[inline]
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>], state: &'mem RegionState) -> Self {
+        Self::with_direction(memory, state)
+    }
+}
+
+impl<'mem, D: Direction> BorrowedRegion<'mem, D> {
+    /// Creates a new region from the given memory block and resets `state`, which bumps in the
+    /// direction `D`.
+    #[inline]
+    pub fn with_direction(memory: &'mem mut [MaybeUninit<u8>], state: &'mem RegionState) -> Self {
+        let memory = NonNull::from(memory);
+        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
+        Self {
+            raw: unsafe { RawBorrowedRegion::with_direction(memory, NonNull::from(state)) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A region allocating short-lived and long-lived memory from opposite ends of one memory block.
+///
+/// The region hands out two allocators: [`bottom`] bumps [`Up`] from the start of the memory and
+/// [`top`] bumps [`Down`] from the end. Both fail, when they would cross each other. Each end can
+/// be reset on its own, e.g. to release per-frame allocations while keeping persistent ones.
+///
+/// For a version without lifetime see [`RawDoubleEndedRegion`] instead.
+///
+/// [`bottom`]: Self::bottom
+/// [`top`]: Self::top
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::DoubleEndedRegion, AllocateAll};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let region = DoubleEndedRegion::new(&mut data);
+/// let persistent = region.top();
+/// let temporary = region.bottom();
+///
+/// persistent.allocate(Layout::new::<[u8; 16]>())?;
+/// for _ in 0..10 {
+///     temporary.allocate(Layout::new::<[u8; 48]>())?;
+///     assert!(temporary.allocate(Layout::new::<u8>()).is_err());
+///     temporary.deallocate_all();
+/// }
+/// assert_eq!(region.capacity_left(), 48);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct DoubleEndedRegion<'mem> {
+    raw: RawDoubleEndedRegion,
     _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
 }
 
-impl<'mem> Region<'mem> {
-    /// Creates a new region from the given memory block.
+impl<'mem> DoubleEndedRegion<'mem> {
+    /// Creates a new double ended region from the given memory block.
+    #[inline]
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        let memory = NonNull::from(memory);
+        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
+        Self {
+            raw: unsafe { RawDoubleEndedRegion::new(memory) },
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the allocator handing out memory from the start of the memory block.
+    #[inline]
+    pub fn bottom(&self) -> RegionEnd<'_, Up> {
+        self.raw.bottom()
+    }
+
+    /// Returns the allocator handing out memory from the end of the memory block.
+    #[inline]
+    pub fn top(&self) -> RegionEnd<'_, Down> {
+        self.raw.top()
+    }
+
+    /// Returns the size of the whole memory block.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.raw.capacity()
+    }
+
+    /// Returns the number of bytes left between both ends.
+    #[inline]
+    pub fn capacity_left(&self) -> usize {
+        self.raw.capacity_left()
+    }
+
+    /// Resets both ends of the region.
+    #[inline]
+    pub fn deallocate_all(&self) {
+        self.raw.deallocate_all()
+    }
+}
+
+impl ReportUsage for DoubleEndedRegion<'_> {
+    fn usage(&self) -> Usage {
+        self.raw.usage()
+    }
+}
+
+impl Describe for DoubleEndedRegion<'_> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(
+            f,
+            "DoubleEndedRegion",
+            self.capacity(),
+            self.capacity_left(),
+        )
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.bottom());
+        visit(&self.top());
+    }
+}
+
+impl Purge for DoubleEndedRegion<'_> {}
+
+/// A region for memory shared between processes, which refers to memory blocks by offsets.
+///
+/// It holds a lifetime to the provided memory block, which ensures, that the allocator does not
+/// outlive the underlying memory. See [`RawOffsetRegion`] for details and for attaching to a
+/// region from another process.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{region::OffsetRegion, Owns};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// #[repr(align(8))]
+/// struct Segment([MaybeUninit<u8>; 64]);
+///
+/// let mut segment = Segment([MaybeUninit::uninit(); 64]);
+/// let region = OffsetRegion::new(&mut segment.0);
+///
+/// let memory = region.allocate(Layout::new::<u32>())?;
+/// let offset = region.offset_of(memory.as_non_null_ptr()).unwrap();
+/// assert_eq!(region.resolve(offset), Some(memory.as_non_null_ptr()));
+/// assert!(region.owns(memory));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg(target_has_atomic = "ptr")]
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "ptr")))]
+pub struct OffsetRegion<'mem> {
+    raw: RawOffsetRegion,
+    _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<'mem> OffsetRegion<'mem> {
+    /// Creates a new region from the given memory block.
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not aligned or not large enough to store the
+    /// position. See [`try_new`] for a fallible variant.
+    ///
+    /// [`try_new`]: Self::try_new
+    #[inline]
+    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
+        Self::try_new(memory).expect("Could not store the position in region")
+    }
+
+    /// Creates a new region from the given memory block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::LayoutUnsupported`], when `memory` is not aligned for the position,
+    /// and [`ComposeError::Exhausted`], when the position does not fit into `memory`.
+    #[inline]
+    #[track_caller]
+    pub fn try_new(memory: &'mem mut [MaybeUninit<u8>]) -> Result<Self, ComposeError> {
+        let memory = NonNull::from(memory);
+        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
+        Ok(Self {
+            raw: unsafe { RawOffsetRegion::try_new(memory)? },
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the offset of `ptr` relative to the start of the memory, or `None`, if `ptr` does
+    /// not point into the memory.
+    #[inline]
+    pub fn offset_of(&self, ptr: NonNull<u8>) -> Option<usize> {
+        self.raw.offset_of(ptr)
+    }
+
+    /// Returns the pointer at `offset`, or `None`, if the offset is not inside of the allocated
+    /// memory.
+    #[inline]
+    pub fn resolve(&self, offset: usize) -> Option<NonNull<u8>> {
+        self.raw.resolve(offset)
+    }
+
+    /// Returns if the memory block at `ptr` is owned by this region and was allocated last, so
+    /// [`try_dealloc`] would reclaim its memory.
+    ///
+    /// [`try_dealloc`]: Self::try_dealloc
+    #[inline]
+    pub fn is_last_block(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.raw.is_last_block(ptr, layout)
+    }
+
+    /// Deallocates the memory block at `ptr` and returns if its memory was reclaimed.
+    ///
+    /// See [`RawOffsetRegion::try_dealloc`] for details.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Allocator::deallocate`].
+    #[track_caller]
+    #[inline]
+    pub unsafe fn try_dealloc(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.raw.try_dealloc(ptr, layout)
+    }
+
+    /// Returns a consistent view of the current state of the region.
+    ///
+    /// See [`RawOffsetRegion::snapshot`] for details.
+    #[inline]
+    pub fn snapshot(&self) -> RegionSnapshot {
+        self.raw.snapshot()
+    }
+
+    /// Passes a snapshot to `f` every `interval`, until `f` returns [`ControlFlow::Break`].
+    ///
+    /// See [`RawOffsetRegion::watch`] for details.
+    ///
+    /// [`ControlFlow::Break`]: core::ops::ControlFlow::Break
+    #[cfg(any(doc, feature = "std"))]
+    #[cfg_attr(doc, doc(cfg(feature = "std")))]
+    pub fn watch<B>(
+        &self,
+        interval: std::time::Duration,
+        f: impl FnMut(RegionSnapshot) -> core::ops::ControlFlow<B>,
+    ) -> B {
+        self.raw.watch(interval, f)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl Allocator for OffsetRegion<'_> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.raw.allocate(layout)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.raw.deallocate(ptr, layout)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl AllocateAll for OffsetRegion<'_> {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.raw.allocate_all()
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.raw.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.raw.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.raw.capacity_left()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl ReportUsage for OffsetRegion<'_> {
+    fn usage(&self) -> Usage {
+        self.raw.usage()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Owns for OffsetRegion<'_> {
+    #[inline]
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.raw.owns(memory)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl AllocateMany for OffsetRegion<'_> {
+    fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+        unsafe { bump_many(self, layout, n) }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Describe for OffsetRegion<'_> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(f, "OffsetRegion", self.capacity(), self.capacity_left())
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Purge for OffsetRegion<'_> {}
+
+/// A region over `M` independent chunks of `N` bytes each, e.g. a static memory pool.
+///
+/// Memory blocks are allocated from the current chunk. If it cannot satisfy a request, the next
+/// chunks are tried, and the first one, which succeeds, becomes the current chunk. Chunks before
+/// the current one are not used again until [`deallocate_all`] is called. A memory block never
+/// spans several chunks, so requests larger than `N` bytes always fail.
+///
+/// `MultiRegion` does not allocate, which makes it suitable for embedded targets.
+///
+/// [`deallocate_all`]: AllocateAll::deallocate_all
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::MultiRegion, AllocateAll};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut chunks = [[MaybeUninit::uninit(); 32]; 4];
+/// let region = MultiRegion::new(&mut chunks);
+///
+/// region.allocate(Layout::new::<[u8; 24]>())?;
+/// region.allocate(Layout::new::<[u8; 24]>())?;
+/// assert_eq!(region.current_chunk(), 1);
+/// assert!(region.allocate(Layout::new::<[u8; 64]>()).is_err());
+/// assert_eq!(region.capacity_left(), 8 + 2 * 32);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug)]
+pub struct MultiRegion<'mem, const N: usize, const M: usize> {
+    chunks: [RawRegion; M],
+    current: Cell<usize>,
+    _marker: PhantomData<&'mem mut [[MaybeUninit<u8>; N]; M]>,
+}
+
+impl<'mem, const N: usize, const M: usize> MultiRegion<'mem, N, M> {
+    /// Creates a new region, which uses every array in `memory` as a chunk.
+    #[inline]
+    pub fn new(memory: &'mem mut [[MaybeUninit<u8>; N]; M]) -> Self {
+        Self {
+            chunks: memory.each_mut().map(|chunk| {
+                let memory = NonNull::from(chunk);
+                unsafe { RawRegion::new(NonNull::slice_from_raw_parts(memory.cast(), N)) }
+            }),
+            current: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the index of the chunk, which is used for the next allocation.
+    #[inline]
+    pub fn current_chunk(&self) -> usize {
+        self.current.get()
+    }
+
+    /// Returns a coarse map of the used memory of every chunk.
+    ///
+    /// Printing the maps line by line shows, how the allocations are spread over the chunks. See
+    /// [`RegionMap`] for the format.
+    #[inline]
+    pub fn dump_map(&self, granularity: usize) -> [RegionMap; M] {
+        self.chunks
+            .each_ref()
+            .map(|chunk| chunk.dump_map(granularity))
+    }
+
+    fn remaining(&self) -> &[RawRegion] {
+        &self.chunks[self.current.get()..]
+    }
+
+    fn chunk_of(&self, ptr: NonNull<u8>) -> Option<&RawRegion> {
+        self.chunks.iter().find(|chunk| chunk.owns_ptr(ptr))
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq for MultiRegion<'_, N, M> {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.chunks == rhs.chunks
+    }
+}
+
+impl<const N: usize, const M: usize> Eq for MultiRegion<'_, N, M> {}
+
+impl<const N: usize, const M: usize> Hash for MultiRegion<'_, N, M> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.chunks.hash(state)
+    }
+}
+
+unsafe impl<const N: usize, const M: usize> Allocator for MultiRegion<'_, N, M> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let current = self.current.get();
+        for (i, chunk) in self.remaining().iter().enumerate() {
+            if let Ok(memory) = chunk.allocate(layout) {
+                self.current.set(current + i);
+                return Ok(memory);
+            }
+        }
+        Err(AllocError)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
+
+unsafe impl<const N: usize, const M: usize> AllocateAll for MultiRegion<'_, N, M> {
+    /// Allocates the rest of the first chunk from the current one, which is not full.
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        let current = self.current.get();
+        for (i, chunk) in self.remaining().iter().enumerate() {
+            if !chunk.is_full() {
+                self.current.set(current + i);
+                return chunk.allocate_all();
+            }
+        }
+        Err(AllocError)
+    }
+
+    fn deallocate_all(&self) {
+        for chunk in &self.chunks {
+            chunk.deallocate_all()
+        }
+        self.current.set(0)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        N * M
+    }
+
+    /// Returns the number of bytes left in the current chunk and the chunks after it.
+    fn capacity_left(&self) -> usize {
+        self.remaining().iter().map(RawRegion::capacity_left).sum()
+    }
+}
+
+impl<const N: usize, const M: usize> AllocateNear for MultiRegion<'_, N, M> {
+    /// Allocates from the chunk containing `hint` first, even if it's before the current chunk.
+    fn allocate_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.chunk_of(hint)
+            .and_then(|chunk| chunk.allocate(layout).ok())
+            .map_or_else(|| self.allocate(layout), Ok)
+    }
+
+    /// Allocates from the chunk containing `hint` first, even if it's before the current chunk.
+    fn allocate_zeroed_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.chunk_of(hint)
+            .and_then(|chunk| chunk.allocate_zeroed(layout).ok())
+            .map_or_else(|| self.allocate_zeroed(layout), Ok)
+    }
+}
+
+impl<const N: usize, const M: usize> ReportUsage for MultiRegion<'_, N, M> {
+    fn usage(&self) -> Usage {
+        Usage::new(self.capacity(), self.capacity_left(), 0)
+    }
+}
+
+impl<const N: usize, const M: usize> Owns for MultiRegion<'_, N, M> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.chunks.iter().any(|chunk| chunk.owns(memory))
+    }
+}
+
+impl<const N: usize, const M: usize> Describe for MultiRegion<'_, N, M> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(f, "MultiRegion", self.capacity(), self.capacity_left())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        for chunk in &self.chunks {
+            visit(chunk)
+        }
+    }
+}
+
+impl<const N: usize, const M: usize> Purge for MultiRegion<'_, N, M> {}
+
+/// A region allocator, which owns a memory block of `N` bytes.
+///
+/// As the capacity is a const parameter, it's available in constant contexts as [`CAPACITY`] or
+/// [`capacity`], and [`static_assert_fits!`] checks at compile time, whether a number of values
+/// fits into the region.
+///
+/// The memory is stored inline, so moving the region would invalidate all memory blocks allocated
+/// from it. For this reason, [`Allocator`] and [`AllocateAll`] are only implemented for references
+/// to the region.
+///
+/// Memory is bumped upwards and deallocating the last memory block reclaims its memory.
+///
+/// [`CAPACITY`]: Self::CAPACITY
+/// [`capacity`]: Self::capacity
+/// [`static_assert_fits!`]: crate::static_assert_fits
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::FixedRegion, static_assert_fits};
+///
+/// type Arena = FixedRegion<256>;
+/// static_assert_fits!(Arena, u64, 16);
+///
+/// let region = Arena::new();
+/// let mut vec = Vec::with_capacity_in(16, &region);
+/// vec.extend(0..16_u64);
+/// assert!(region.capacity_left() < Arena::CAPACITY);
+/// ```
+pub struct FixedRegion<const N: usize> {
+    memory: UnsafeCell<[MaybeUninit<u8>; N]>,
+    len: Cell<usize>,
+}
+
+impl<const N: usize> FixedRegion<N> {
+    /// The number of bytes owned by the region.
+    pub const CAPACITY: usize = N;
+
+    /// Creates a new, empty region.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            memory: UnsafeCell::new([MaybeUninit::uninit(); N]),
+            len: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of bytes owned by the region.
+    #[inline]
+    pub const fn capacity() -> usize {
+        N
+    }
+
+    /// Returns the number of bytes left in the region.
+    #[inline]
+    pub fn capacity_left(&self) -> usize {
+        N - self.len.get()
+    }
+
+    /// Returns if `count` values of type `T` fit into an empty region, regardless of the address
+    /// of the region.
+    ///
+    /// The worst case includes the padding needed to align the first value.
+    pub const fn fits<T>(count: usize) -> bool {
+        match mem::size_of::<T>().checked_mul(count) {
+            Some(size) => match size.checked_add(mem::align_of::<T>() - 1) {
+                Some(size) => size <= N,
+                None => false,
+            },
+            None => false,
+        }
+    }
+
+    fn base(&self) -> NonNull<u8> {
+        unsafe { NonNull::new_unchecked(self.memory.get().cast()) }
+    }
+
+    fn offset_of(&self, ptr: NonNull<u8>) -> usize {
+        (ptr.as_ptr() as usize).wrapping_sub(self.base().as_ptr() as usize)
+    }
+}
+
+impl<const N: usize> Default for FixedRegion<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedRegion<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedRegion")
+            .field("capacity", &N)
+            .field("len", &self.len.get())
+            .finish()
+    }
+}
+
+unsafe impl<const N: usize> Allocator for &FixedRegion<N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let len = self.len.get();
+        let padding = (self.base().as_ptr() as usize + len).wrapping_neg() & (layout.align() - 1);
+        let start = len + padding;
+        let end = start
+            .checked_add(layout.size())
+            .filter(|&end| end <= N)
+            .ok_or(AllocError)?;
+        self.len.set(end);
+        let ptr = unsafe { NonNull::new_unchecked(self.base().as_ptr().add(start)) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        let offset = self.offset_of(ptr);
+        if offset + layout.size() == self.len.get() {
+            self.len.set(offset)
+        }
+    }
+}
+
+unsafe impl<const N: usize> AllocateAll for &FixedRegion<N> {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        let len = self.len.replace(N);
+        let ptr = unsafe { NonNull::new_unchecked(self.base().as_ptr().add(len)) };
+        Ok(NonNull::slice_from_raw_parts(ptr, N - len))
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.len.set(0)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        N
+    }
+
     #[inline]
-    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
-        let memory = NonNull::from(memory);
-        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
-        Self {
-            raw: unsafe { RawRegion::new(memory) },
-            _marker: PhantomData,
-        }
+    fn capacity_left(&self) -> usize {
+        FixedRegion::capacity_left(self)
     }
 }
 
-/// A clonable region allocator based on `Rc`.
-///
-/// It holds a lifetime to the provided memory block, which ensures, that the allocator does not
-/// outlive the underlying memory.
-///
-/// For a version without lifetime see [`RawSharedRegion`] instead.
-#[derive(Clone)]
-#[cfg(any(doc, feature = "alloc"))]
-#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
-pub struct SharedRegion<'mem> {
-    raw: RawSharedRegion,
-    _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
+impl<const N: usize> ReportUsage for FixedRegion<N> {
+    fn usage(&self) -> Usage {
+        Usage::new(N, self.capacity_left(), 0)
+    }
 }
 
-#[cfg(any(doc, feature = "alloc"))]
-impl<'mem> SharedRegion<'mem> {
-    /// Creates a new region from the given memory block.
-    #[inline]
-    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
-        let memory = NonNull::from(memory);
-        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
-        Self {
-            raw: unsafe { RawSharedRegion::new(memory) },
-            _marker: PhantomData,
-        }
+impl<const N: usize> Owns for FixedRegion<N> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        let offset = self.offset_of(memory.as_non_null_ptr());
+        offset <= self.len.get() && memory.len() <= self.len.get() - offset
     }
 }
 
-/// An intrusive region allocator, which stores the current posision in the provided memory.
+impl<const N: usize> Describe for FixedRegion<N> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(f, "FixedRegion", N, self.capacity_left())
+    }
+}
+
+impl<const N: usize> Purge for FixedRegion<N> {}
+
+/// Splits `memory` into `N` consecutive parts, which are sized proportionally to `shares`.
 ///
-/// It holds a lifetime to the provided memory block, which ensures, that the allocator does not
-/// outlive the underlying memory.
+/// Part `i` receives `memory.len() * shares[i] / sum(shares)` bytes, the bytes lost by rounding
+/// down are added to the last part. For shares close to `usize::MAX`, the parts are only
+/// approximately proportional. This is used by [`segregate!`] to carve the memory of every
+/// bucket from a single buffer, but the parts can be passed to any region.
 ///
-/// For a version without lifetime see [`RawIntrusiveRegion`] instead.
-#[derive(Clone)]
-pub struct IntrusiveRegion<'mem> {
-    raw: RawIntrusiveRegion,
-    _marker: PhantomData<&'mem mut [MaybeUninit<u8>]>,
+/// # Panics
+///
+/// Panics, if the sum of `shares` is zero or overflows.
+///
+/// # Examples
+///
+/// ```rust
+/// use alloc_compose::region::split_memory;
+/// use core::mem::MaybeUninit;
+///
+/// let mut data = [MaybeUninit::uninit(); 100];
+/// let [a, b, c] = split_memory(&mut data, [1, 2, 1]);
+/// assert_eq!((a.len(), b.len(), c.len()), (25, 50, 25));
+/// ```
+///
+/// [`segregate!`]: crate::segregate
+pub fn split_memory<const N: usize>(
+    memory: &mut [MaybeUninit<u8>],
+    shares: [usize; N],
+) -> [&mut [MaybeUninit<u8>]; N] {
+    let total = shares
+        .iter()
+        .try_fold(0_usize, |sum, &share| sum.checked_add(share))
+        .expect("Sum of shares overflows");
+    assert!(total > 0, "At least one share must be non-zero");
+
+    let len = memory.len();
+    let mut rest = memory;
+    core::array::from_fn(|i| {
+        let part = if i == N - 1 {
+            rest.len()
+        } else {
+            // `len * share / total` without overflowing. The remainder is rounded down further,
+            // if its product overflows.
+            let (quotient, remainder) = (len / total, len % total);
+            let share = shares[i];
+            quotient * share
+                + remainder
+                    .checked_mul(share)
+                    .map_or_else(|| remainder / total.div_ceil(share), |rem| rem / total)
+        };
+        let (head, tail) = mem::take(&mut rest).split_at_mut(part);
+        rest = tail;
+        head
+    })
 }
 
-impl<'mem> IntrusiveRegion<'mem> {
-    /// Creates a new region from the given memory block.
-    ///
-    /// # Panics
-    ///
-    /// This function panics, when `memory` is not large enough to properly store a pointer.
-    #[inline]
-    pub fn new(memory: &'mem mut [MaybeUninit<u8>]) -> Self {
-        let memory = NonNull::from(memory);
-        let memory = NonNull::slice_from_raw_parts(memory.cast(), memory.len());
-        Self {
-            raw: unsafe { RawIntrusiveRegion::new(memory) },
-            _marker: PhantomData,
-        }
-    }
+/// Asserts at compile time, that `count` values of a type fit into a [`FixedRegion`].
+///
+/// See [`FixedRegion::fits`] for the computation of the worst case.
+///
+/// [`FixedRegion`]: crate::region::FixedRegion
+/// [`FixedRegion::fits`]: crate::region::FixedRegion::fits
+///
+/// # Examples
+///
+/// ```rust
+/// use alloc_compose::{region::FixedRegion, static_assert_fits};
+///
+/// static_assert_fits!(FixedRegion<64>, [u32; 4], 3);
+/// ```
+///
+/// Allocations, which may not fit, fail to compile:
+///
+/// ```compile_fail
+/// use alloc_compose::{region::FixedRegion, static_assert_fits};
+///
+/// static_assert_fits!(FixedRegion<64>, u32, 17);
+/// ```
+#[macro_export]
+macro_rules! static_assert_fits {
+    ($region:ty, $ty:ty, $count:expr $(,)?) => {
+        const _: () = assert!(
+            <$region>::fits::<$ty>($count),
+            concat!(
+                "`",
+                stringify!($count),
+                "` values of `",
+                stringify!($ty),
+                "` may not fit into `",
+                stringify!($region),
+                "`"
+            ),
+        );
+    };
 }
 
 macro_rules! impl_region {
-    ($ty:ident, $raw:ty) => {
-        impl PartialEq for $ty<'_> {
+    ($ty:ident $(<$param:ident>)?, $raw:ident) => {
+        impl<D: Direction $(, $param)?> $ty<'_, D $(, $param)?> {
+            /// Allocates `size` bytes aligned to the constant `ALIGN`.
+            ///
+            /// See [`RawRegion::alloc_aligned`] for details.
+            ///
+            /// # Errors
+            ///
+            /// Returns `Err`, if the memory block does not fit into the region or if `size`
+            /// exceeds `isize::MAX`.
+            ///
+            /// # Panics
+            ///
+            /// Panics, if `ALIGN` is not a power of two. With the `no-panic` feature, `Err` is
+            /// returned instead.
+            #[inline]
+            pub fn alloc_aligned<const ALIGN: usize>(
+                &self,
+                size: usize,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                self.raw.alloc_aligned::<ALIGN>(size)
+            }
+
+            /// Allocates `size` bytes without any alignment requirement.
+            ///
+            /// See [`RawRegion::alloc_unaligned`] for details.
+            ///
+            /// # Errors
+            ///
+            /// Returns `Err`, if the memory block does not fit into the region or if `size`
+            /// exceeds `isize::MAX`.
+            #[inline]
+            pub fn alloc_unaligned(&self, size: usize) -> Result<NonNull<[u8]>, AllocError> {
+                self.raw.alloc_unaligned(size)
+            }
+
+            /// Returns the memory lost to alignment padding since the last reset.
+            #[inline]
+            pub fn fragmentation_stats(&self) -> FragmentationStats {
+                self.raw.fragmentation_stats()
+            }
+
+            /// Returns a coarse map of the used memory with one digit for every `granularity`
+            /// bytes.
+            ///
+            /// See [`RegionMap`] for the format. A `granularity` of zero is treated as one.
+            #[inline]
+            pub fn dump_map(&self, granularity: usize) -> RegionMap {
+                self.raw.dump_map(granularity)
+            }
+
+            /// Returns if the memory block at `ptr` is owned by this region and was allocated
+            /// last, so [`try_dealloc`] would reclaim its memory.
+            ///
+            /// [`try_dealloc`]: Self::try_dealloc
+            #[inline]
+            pub fn is_last_block(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+                self.raw.is_last_block(ptr, layout)
+            }
+
+            /// Deallocates the memory block at `ptr` and returns if its memory was reclaimed.
+            ///
+            /// See [`RawRegion::try_dealloc`] for details.
+            ///
+            /// # Safety
+            ///
+            /// The same as for [`Allocator::deallocate`].
+            #[track_caller]
+            #[inline]
+            pub unsafe fn try_dealloc(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+                self.raw.try_dealloc(ptr, layout)
+            }
+        }
+
+        impl<D: Direction $(, $param)?> PartialEq for $ty<'_, D $(, $param)?> {
             #[inline]
             fn eq(&self, rhs: &Self) -> bool {
                 self.raw == rhs.raw
             }
         }
 
-        impl PartialEq<$raw> for $ty<'_> {
+        impl<D: Direction $(, $param)?> Eq for $ty<'_, D $(, $param)?> {}
+
+        impl<D: Direction $(, $param)?> Hash for $ty<'_, D $(, $param)?> {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.raw.hash(state)
+            }
+        }
+
+        impl<D: Direction $(, $param)?> PartialEq<$raw<D>> for $ty<'_, D $(, $param)?> {
             #[inline]
-            fn eq(&self, rhs: &$raw) -> bool {
+            fn eq(&self, rhs: &$raw<D>) -> bool {
                 &self.raw == rhs
             }
         }
 
-        impl PartialEq<$ty<'_>> for $raw {
+        impl<D: Direction $(, $param)?> PartialEq<$ty<'_, D $(, $param)?>> for $raw<D> {
             #[inline]
-            fn eq(&self, rhs: &$ty<'_>) -> bool {
+            fn eq(&self, rhs: &$ty<'_, D $(, $param)?>) -> bool {
                 self == &rhs.raw
             }
         }
 
-        unsafe impl AllocRef for $ty<'_> {
+        unsafe impl<D: Direction $(, $param)?> Allocator for $ty<'_, D $(, $param)?> {
             #[inline]
-            fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-                self.raw.alloc(layout)
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.raw.allocate(layout)
             }
 
             #[inline]
-            unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
-                self.raw.dealloc(ptr, layout)
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.raw.deallocate(ptr, layout)
             }
 
             #[inline]
@@ -230,7 +1372,7 @@ macro_rules! impl_region {
                 old_layout: Layout,
                 new_layout: Layout,
             ) -> Result<NonNull<[u8]>, AllocError> {
-                self.raw.grow(ptr, old_layout, new_layout)
+                self.raw.grow_zeroed(ptr, old_layout, new_layout)
             }
 
             #[inline]
@@ -240,11 +1382,11 @@ macro_rules! impl_region {
                 old_layout: Layout,
                 new_layout: Layout,
             ) -> Result<NonNull<[u8]>, AllocError> {
-                self.raw.grow(ptr, old_layout, new_layout)
+                self.raw.shrink(ptr, old_layout, new_layout)
             }
         }
 
-        unsafe impl AllocateAll for $ty<'_> {
+        unsafe impl<D: Direction $(, $param)?> AllocateAll for $ty<'_, D $(, $param)?> {
             #[inline]
             fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
                 self.raw.allocate_all()
@@ -271,21 +1413,126 @@ macro_rules! impl_region {
             }
         }
 
-        impl Owns for $ty<'_> {
+        impl<D: Direction $(, $param)?> ReportUsage for $ty<'_, D $(, $param)?> {
+            fn usage(&self) -> Usage {
+                self.raw.usage()
+            }
+        }
+
+        unsafe impl<D: Direction $(, $param)?> ReallocateInPlace for $ty<'_, D $(, $param)?> {
+            #[inline]
+            unsafe fn grow_in_place(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                self.raw.grow_in_place(ptr, old_layout, new_layout)
+            }
+
+            #[inline]
+            unsafe fn grow_in_place_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                self.raw.grow_in_place_zeroed(ptr, old_layout, new_layout)
+            }
+
+            #[inline]
+            unsafe fn shrink_in_place(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                self.raw.shrink_in_place(ptr, old_layout, new_layout)
+            }
+        }
+
+        impl<D: Direction $(, $param)?> Owns for $ty<'_, D $(, $param)?> {
             #[inline]
             fn owns(&self, memory: NonNull<[u8]>) -> bool {
                 self.raw.owns(memory)
             }
         }
 
-        impl_global_alloc!($ty<'_>);
+        impl<D: Direction $(, $param)?> AllocateMany for $ty<'_, D $(, $param)?> {
+            fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+                unsafe { bump_many(self, layout, n) }
+            }
+        }
+
+        impl<D: Direction $(, $param)?> Describe for $ty<'_, D $(, $param)?> {
+            fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                describe_usage(f, stringify!($ty), self.capacity(), self.capacity_left())
+            }
+        }
+
+        impl<D: Direction $(, $param)?> Purge for $ty<'_, D $(, $param)?> {}
+
+        impl_global_alloc!(impl<D: Direction $(, $param: Sized)?> $ty<'_, D $(, $param)?>);
     };
 }
 
 impl_region!(Region, RawRegion);
 #[cfg(any(doc, feature = "alloc"))]
 impl_region!(SharedRegion, RawSharedRegion);
-impl_region!(IntrusiveRegion, RawIntrusiveRegion);
+impl_region!(IntrusiveRegion<M>, RawIntrusiveRegion);
+impl_region!(BorrowedRegion, RawBorrowedRegion);
+
+/// Returns the capacity, which a region needs to hand out `count` memory blocks of `layout`,
+/// regardless of the address of its memory.
+///
+/// The first memory block may need up to `layout.align() - 1` bytes of padding, every following
+/// one occupies the size of `layout` padded to its alignment. Combined with the `parent_layout`
+/// functions of allocators like [`Chunk`], [`Affix`], or [`HeaderBytes`], the size of a buffer
+/// for a whole composition can be computed at compile time. The layouts are passed from the
+/// outermost allocator inwards:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{
+///     region::{self, Region},
+///     Affix,
+///     Chunk,
+/// };
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// const NODE: Layout = Layout::new::<[u64; 3]>();
+/// const CHUNK: Layout = Chunk::<(), 32>::parent_layout(NODE).unwrap();
+/// const BLOCK: Layout = Affix::<(), u64>::parent_layout(CHUNK).unwrap();
+/// const CAPACITY: usize = region::required_capacity(BLOCK, 10).unwrap();
+///
+/// let mut data = [MaybeUninit::uninit(); CAPACITY];
+/// let alloc = Chunk::<_, 32>(Affix::<_, u64>::new(Region::new(&mut data)));
+/// for _ in 0..10 {
+///     alloc.allocate(NODE)?;
+/// }
+/// assert!(alloc.allocate(NODE).is_err());
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+///
+/// Returns `None`, if the capacity overflows `usize`.
+///
+/// [`Chunk`]: crate::Chunk
+/// [`Affix`]: crate::Affix
+/// [`HeaderBytes`]: crate::HeaderBytes
+pub const fn required_capacity(layout: Layout, count: usize) -> Option<usize> {
+    if count == 0 {
+        return Some(0);
+    }
+    let padded = layout.pad_to_align().size();
+    match padded.checked_mul(count - 1) {
+        Some(capacity) => capacity.checked_add(layout.size() + (layout.align() - 1)),
+        None => None,
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -294,11 +1541,37 @@ mod tests {
     use crate::helper::tracker;
     use core::{cell::Cell, mem};
 
-    fn aligned_slice(memory: &mut [MaybeUninit<u8>], size: usize) -> &mut [MaybeUninit<u8>] {
-        let ptr = memory.as_mut_ptr() as usize;
-        let start = (ptr + 31) & !(31);
-        assert!(memory.len() >= start - ptr + size);
-        unsafe { core::slice::from_raw_parts_mut(start as *mut MaybeUninit<u8>, size) }
+    /// Requests memory blocks with alignments up to the largest power of two and sizes up to
+    /// `isize::MAX`, which must either fail or return a valid memory block.
+    fn alloc_extreme(region: &(impl Allocator + AllocateAll)) {
+        let capacity = region.capacity_left();
+        for shift in 0..mem::size_of::<usize>() * 8 {
+            let align = 1 << shift;
+            let sizes = [
+                0,
+                1,
+                capacity,
+                capacity + 1,
+                isize::MAX as usize - (align - 1),
+                isize::MAX as usize,
+                usize::MAX - (align - 1),
+            ];
+            for &size in &sizes {
+                let layout = match Layout::from_size_align(size, align) {
+                    Ok(layout) => layout,
+                    Err(_) => continue,
+                };
+                match region.allocate(layout) {
+                    Ok(memory) => {
+                        assert!(size <= capacity, "Allocated {:?}", layout);
+                        assert!(memory.len() >= size && memory.len() <= capacity);
+                        assert_eq!(memory.as_mut_ptr() as usize % align, 0);
+                        region.deallocate_all();
+                    }
+                    Err(_) => assert_eq!(region.capacity_left(), capacity),
+                }
+            }
+        }
     }
 
     macro_rules! impl_tests {
@@ -308,93 +1581,93 @@ mod tests {
 
                 #[test]
                 fn alloc_zero() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     assert_eq!(region.capacity(), 32);
                     assert!(region.is_empty());
 
                     region
-                        .alloc(Layout::new::<[u8; 0]>())
+                        .allocate(Layout::new::<[u8; 0]>())
                         .expect("Could not allocated 0 bytes");
                     assert!(region.is_empty());
 
                     unsafe {
                         drop(region);
-                        assert_eq!(MaybeUninit::slice_assume_init_ref(data)[..32], [1; 32]);
+                        assert_eq!(<[MaybeUninit<u8>]>::assume_init_ref(data)[..32], [1; 32]);
                     }
                 }
 
                 #[test]
-                fn alloc_zeroed() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                fn allocate_zeroed() {
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     assert_eq!(region.capacity(), 32);
                     assert!(region.is_empty());
 
                     region
-                        .alloc_zeroed(Layout::new::<[u8; 32]>())
+                        .allocate_zeroed(Layout::new::<[u8; 32]>())
                         .expect("Could not allocated 32 bytes");
                     assert!(!region.is_empty());
 
                     unsafe {
                         drop(region);
-                        assert_eq!(MaybeUninit::slice_assume_init_ref(data)[..32], [0; 32]);
+                        assert_eq!(<[MaybeUninit<u8>]>::assume_init_ref(data)[..32], [0; 32]);
                     }
                 }
 
                 #[test]
                 fn alloc_small() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     assert_eq!(region.capacity(), 32);
                     assert_eq!(region.capacity(), region.capacity_left());
 
                     region
-                        .alloc_zeroed(Layout::new::<[u8; 16]>())
+                        .allocate_zeroed(Layout::new::<[u8; 16]>())
                         .expect("Could not allocated 16 bytes");
                     assert_eq!(region.capacity_left(), 16);
 
                     unsafe {
                         drop(region);
-                        assert_eq!(MaybeUninit::slice_assume_init_ref(&data[0..16]), [1; 16]);
-                        assert_eq!(MaybeUninit::slice_assume_init_ref(&data[16..32]), [0; 16]);
+                        assert_eq!(<[MaybeUninit<u8>]>::assume_init_ref(&data[0..16]), [1; 16]);
+                        assert_eq!(<[MaybeUninit<u8>]>::assume_init_ref(&data[16..32]), [0; 16]);
                     }
                 }
 
                 #[test]
                 fn alloc_uninitialzed() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     region
-                        .alloc(Layout::new::<[u8; 32]>())
+                        .allocate(Layout::new::<[u8; 32]>())
                         .expect("Could not allocated 32 bytes");
                     assert_eq!(region.capacity_left(), 0);
 
                     unsafe {
                         drop(region);
-                        assert_eq!(MaybeUninit::slice_assume_init_ref(&data)[..32], [1; 32]);
+                        assert_eq!(<[MaybeUninit<u8>]>::assume_init_ref(&data)[..32], [1; 32]);
                     }
                 }
 
                 #[test]
                 fn alloc_all() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     assert_eq!(region.capacity(), 32);
                     assert!(region.is_empty());
 
                     let ptr = region
-                        .alloc(Layout::new::<u8>())
+                        .allocate(Layout::new::<u8>())
                         .expect("Could not allocated 1 byte");
                     assert_eq!(ptr.len(), 1);
                     assert_eq!(region.capacity_left(), 31, "capacity left");
@@ -409,40 +1682,123 @@ mod tests {
                     assert!(region.is_empty());
 
                     region
-                        .alloc(Layout::new::<[u8; 16]>())
+                        .allocate(Layout::new::<[u8; 16]>())
                         .expect("Could not allocate 16 bytes");
                     region
-                        .alloc(Layout::new::<[u8; 17]>())
+                        .allocate(Layout::new::<[u8; 17]>())
                         .expect_err("Could allocate more than 32 bytes");
                 }
 
                 #[test]
                 fn alloc_fail() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     region
-                        .alloc(Layout::new::<[u8; 33]>())
+                        .allocate(Layout::new::<[u8; 33]>())
                         .expect_err("Could allocate 33 bytes");
                 }
 
                 #[test]
                 fn alloc_aligned() {
-                    let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-                    let data = aligned_slice(&mut raw_data, 32 + $extra);
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
                     let region = tracker(<$ty>::new(data));
 
                     region
-                        .alloc(Layout::from_size_align(5, 1).expect("Invalid layout"))
+                        .allocate(Layout::from_size_align(5, 1).expect("Invalid layout"))
                         .expect("Could not allocate 5 Bytes");
                     let capacity = region.capacity_left();
 
                     let ptr = region
-                        .alloc(Layout::from_size_align(16, 16).expect("Invalid layout"))
+                        .allocate(Layout::from_size_align(16, 16).expect("Invalid layout"))
                         .expect("Could not allocate 16 Bytes");
                     assert_eq!(capacity - 16 - 11, region.capacity_left());
                     assert_eq!(ptr.as_mut_ptr() as usize % 16, 0);
+                    assert_eq!(region.alloc.fragmentation_stats(), FragmentationStats {
+                        padding: 11,
+                        max_padding: 11,
+                    });
+
+                    region.deallocate_all();
+                    assert_eq!(
+                        region.alloc.fragmentation_stats(),
+                        FragmentationStats::default()
+                    );
+                }
+
+                #[test]
+                fn alloc_fast_path() {
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
+                    let region = <$ty>::new(data);
+
+                    let ptr = region
+                        .alloc_unaligned(5)
+                        .expect("Could not allocate 5 Bytes");
+                    assert_eq!(ptr.len(), 5);
+                    assert_eq!(region.capacity_left(), 27);
+
+                    let ptr = region
+                        .alloc_aligned::<16>(8)
+                        .expect("Could not allocate 8 Bytes");
+                    assert_eq!(ptr.len(), 8 + 3);
+                    assert_eq!(ptr.as_mut_ptr() as usize % 16, 0);
+                    assert_eq!(region.capacity_left(), 16);
+
+                    region
+                        .alloc_aligned::<8>(17)
+                        .expect_err("Could allocate 17 Bytes");
+                    region
+                        .alloc_unaligned(usize::MAX)
+                        .expect_err("Could allocate usize::MAX Bytes");
+                    region
+                        .alloc_unaligned(isize::MAX as usize)
+                        .expect_err("Could allocate isize::MAX Bytes");
+                    assert_eq!(region.capacity_left(), 16);
+                }
+
+                #[test]
+                fn alloc_extreme_layouts() {
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
+                    let region = tracker(<$ty>::new(data));
+                    alloc_extreme(&region);
+
+                    region
+                        .alloc
+                        .alloc_aligned::<{ isize::MAX as usize + 1 }>(1)
+                        .expect_err("Could allocate at the largest alignment");
+                }
+
+                #[test]
+                fn try_dealloc() {
+                    let mut buffer = crate::aligned_buffer!(32 + $extra, 32, 1);
+                    let data = &mut *buffer;
+                    let region = <$ty>::new(data);
+                    let layout = Layout::new::<[u8; 8]>();
+
+                    let first = region
+                        .allocate(layout)
+                        .expect("Could not allocate 8 bytes")
+                        .as_non_null_ptr();
+                    let second = region
+                        .allocate(layout)
+                        .expect("Could not allocate 8 bytes")
+                        .as_non_null_ptr();
+                    assert!(!region.is_last_block(first, layout));
+                    assert!(region.is_last_block(second, layout));
+
+                    unsafe {
+                        assert!(!region.try_dealloc(first, layout));
+                        assert_eq!(region.capacity_left(), 16);
+                        assert!(region.try_dealloc(second, layout));
+                        assert_eq!(region.capacity_left(), 24);
+                        assert!(!region.is_last_block(second, layout));
+                        assert!(region.try_dealloc(first, layout));
+                    }
+                    assert!(region.is_empty());
                 }
             }
         };
@@ -451,46 +1807,653 @@ mod tests {
     impl_tests!(exclusive, Region, 0);
     #[cfg(any(doc, feature = "alloc"))]
     impl_tests!(shared, SharedRegion, 0);
-    impl_tests!(
-        intrusive,
-        IntrusiveRegion,
-        mem::size_of::<NonNull<Cell<NonNull<u8>>>>()
-    );
+    impl_tests!(intrusive, IntrusiveRegion, mem::size_of::<raw::State>());
+
+    #[test]
+    fn borrowed() {
+        let mut data = [MaybeUninit::new(1); 64];
+        let state = RegionState::new();
+        let region = BorrowedRegion::<Up>::with_direction(&mut data, &state);
+        let clone = region.clone();
+
+        let layout = Layout::new::<[u8; 8]>();
+        let memory = region.allocate(layout).expect("Could not allocate 8 bytes");
+        assert!(clone.owns(memory));
+        assert_eq!(clone.capacity_left(), 56);
+        let grown = unsafe {
+            clone
+                .grow(memory.as_non_null_ptr(), layout, Layout::new::<[u8; 16]>())
+                .expect("Could not grow to 16 bytes")
+        };
+        assert_eq!(grown.as_mut_ptr(), memory.as_mut_ptr());
+        assert_eq!(region.capacity_left(), 48);
+
+        let region = BorrowedRegion::new(&mut data, &state);
+        assert!(region.is_empty());
+    }
+
+    mod upward {
+        use super::*;
+
+        fn run_upward(
+            region: &(impl Allocator + AllocateAll + ReallocateInPlace + Owns),
+            fragmentation_stats: impl Fn() -> FragmentationStats,
+        ) {
+            let capacity = region.capacity();
+
+            let first = region
+                .allocate(Layout::from_size_align(5, 1).expect("Invalid layout"))
+                .expect("Could not allocate 5 bytes");
+            let second = region
+                .allocate(Layout::new::<[u64; 1]>())
+                .expect("Could not allocate 8 bytes");
+            assert_eq!(first.len(), 5);
+            assert_eq!(second.len(), 8);
+            assert!(first.as_mut_ptr() < second.as_mut_ptr());
+            assert_eq!(second.as_mut_ptr() as usize % 8, 0);
+            assert_eq!(region.capacity_left(), capacity - 16);
+            assert!(region.owns(first));
+            assert!(region.owns(second));
+            assert_eq!(fragmentation_stats(), FragmentationStats {
+                padding: 3,
+                max_padding: 3,
+            });
+
+            unsafe {
+                region
+                    .grow_in_place(
+                        first.as_non_null_ptr(),
+                        Layout::from_size_align(5, 1).expect("Invalid layout"),
+                        Layout::from_size_align(6, 1).expect("Invalid layout"),
+                    )
+                    .expect_err("Could grow a memory block, which was not allocated last");
+
+                second.as_mut_ptr().write_bytes(1, 8);
+                let grown = region
+                    .grow_zeroed(
+                        second.as_non_null_ptr(),
+                        Layout::new::<[u64; 1]>(),
+                        Layout::new::<[u64; 2]>(),
+                    )
+                    .expect("Could not grow to 16 bytes");
+                assert_eq!(grown.as_mut_ptr(), second.as_mut_ptr());
+                assert_eq!(grown.len(), 16);
+                assert_eq!(*grown.as_mut_ptr().cast::<[u8; 16]>(), [
+                    1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0
+                ]);
+                assert_eq!(region.capacity_left(), capacity - 24);
+
+                let len = region
+                    .shrink_in_place(
+                        second.as_non_null_ptr(),
+                        Layout::new::<[u64; 2]>(),
+                        Layout::new::<[u64; 1]>(),
+                    )
+                    .expect("Could not shrink to 8 bytes");
+                assert_eq!(len, 8);
+                assert_eq!(region.capacity_left(), capacity - 16);
+
+                region
+                    .grow(
+                        second.as_non_null_ptr(),
+                        Layout::new::<[u64; 1]>(),
+                        Layout::from_size_align(capacity, 8).expect("Invalid layout"),
+                    )
+                    .expect_err("Could grow beyond the capacity");
+            }
+
+            let rest = region.allocate_all().expect("Could not allocate all");
+            assert_eq!(rest.len(), capacity - 16);
+            assert!(region.is_full());
+            assert!(region.owns(rest));
+
+            region
+                .allocate(Layout::new::<u8>())
+                .expect_err("Could allocate from a full region");
+            assert_eq!(fragmentation_stats().padding, 3);
+
+            region.deallocate_all();
+            assert!(region.is_empty());
+            assert!(!region.owns(first));
+            assert_eq!(fragmentation_stats(), FragmentationStats::default());
+
+            alloc_extreme(region);
+        }
+
+        #[test]
+        fn region() {
+            let mut buffer = crate::aligned_buffer!(64, 32, 1);
+            let data = &mut *buffer;
+            let region = tracker(Region::<Up>::with_direction(data));
+            run_upward(&region, || region.alloc.fragmentation_stats());
+        }
+
+        #[test]
+        #[cfg(any(doc, feature = "alloc"))]
+        fn shared() {
+            let mut buffer = crate::aligned_buffer!(64, 32, 1);
+            let data = &mut *buffer;
+            let region = tracker(SharedRegion::<Up>::with_direction(data));
+            run_upward(&region, || region.alloc.fragmentation_stats());
+        }
+
+        #[test]
+        fn intrusive() {
+            let mut buffer = crate::aligned_buffer!(64, 32, 1);
+            let data = &mut *buffer;
+            let region = IntrusiveRegion::<Up>::with_direction(data);
+            assert_eq!(region.capacity(), 64 - mem::size_of::<raw::State>());
+            let region = tracker(region);
+            run_upward(&region, || region.alloc.fragmentation_stats());
+        }
+
+        #[test]
+        fn try_dealloc() {
+            let mut buffer = crate::aligned_buffer!(64, 32, 1);
+            let data = &mut *buffer;
+            let region = Region::<Up>::with_direction(data);
+
+            let first = region
+                .allocate(Layout::new::<u8>())
+                .expect("Could not allocate 1 byte")
+                .as_non_null_ptr();
+            let second = region
+                .allocate(Layout::new::<u64>())
+                .expect("Could not allocate 8 bytes")
+                .as_non_null_ptr();
+            assert!(region.is_last_block(second, Layout::new::<u64>()));
+            assert!(!region.is_last_block(second, Layout::new::<u32>()));
+
+            unsafe {
+                assert!(region.try_dealloc(second, Layout::new::<u64>()));
+                // The padding in front of `second` is not reclaimed
+                assert_eq!(region.capacity_left(), 56);
+                assert!(!region.try_dealloc(first, Layout::new::<u8>()));
+            }
+        }
+    }
+
+    #[test]
+    fn try_new() {
+        let mut data = [MaybeUninit::uninit(); 1];
+        let error = IntrusiveRegion::try_new(&mut data)
+            .err()
+            .expect("Could store the state in 1 byte");
+        assert_eq!(error, ComposeError::Exhausted(error.context()));
+        assert_eq!(error.context().location.line(), line!() - 4);
+        #[cfg(target_has_atomic = "ptr")]
+        {
+            let mut buffer = crate::aligned_buffer!(16, 8);
+            let error = OffsetRegion::try_new(&mut buffer[..1]).unwrap_err();
+            assert_eq!(error, ComposeError::Exhausted(error.context()));
+            let error = OffsetRegion::try_new(&mut buffer[1..]).unwrap_err();
+            assert_eq!(error, ComposeError::LayoutUnsupported(error.context()));
+            assert_eq!(error.context().layer, "RawOffsetRegion");
+        }
+
+        let mut data = [MaybeUninit::uninit(); 64];
+        assert!(IntrusiveRegion::<Up, u32>::try_with_metadata(&mut data[..20], 1).is_err());
+        let region = IntrusiveRegion::<Up, u32>::try_with_metadata(&mut data, 1)
+            .expect("Could not store the state and metadata in 64 bytes");
+        assert_eq!(*region.metadata(), 1);
+    }
+
+    #[test]
+    fn offset() {
+        let mut buffer = crate::aligned_buffer!(64, 32, 1);
+        let region = tracker(OffsetRegion::new(&mut buffer));
+        // The memory block has to be derived from the region, which borrows `buffer` mutably
+        let base = region.alloc.snapshot().base;
+        let start = unsafe { base.sub(RawOffsetRegion::HEADER_SIZE) };
+        let memory = NonNull::slice_from_raw_parts(start, 64);
+        assert_eq!(region.capacity(), 64 - RawOffsetRegion::HEADER_SIZE);
+
+        let first = region
+            .allocate(Layout::from_size_align(5, 1).expect("Invalid layout"))
+            .expect("Could not allocate 5 bytes");
+        let second = region
+            .allocate(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        let first_offset = region.alloc.offset_of(first.as_non_null_ptr());
+        let second_offset = region.alloc.offset_of(second.as_non_null_ptr());
+        assert_eq!(first_offset, Some(RawOffsetRegion::HEADER_SIZE));
+        assert_eq!(second_offset, Some(16));
+        assert_eq!(region.alloc.resolve(16), Some(second.as_non_null_ptr()));
+        assert_eq!(region.alloc.resolve(0), None);
+        assert_eq!(region.alloc.resolve(32), None);
+        assert_eq!(region.alloc.offset_of(NonNull::dangling()), None);
+        assert!(region.owns(first));
+        assert!(region.owns(second));
+
+        let attached = unsafe { RawOffsetRegion::attach(memory) };
+        assert_eq!(attached.capacity_left(), region.capacity_left());
+        let third = attached
+            .allocate(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        assert_eq!(attached.offset_of(third.as_non_null_ptr()), Some(24));
+        assert!(region.owns(third));
+        assert_eq!(region.capacity_left(), 64 - 32);
+
+        let rest = region.allocate_all().expect("Could not allocate all");
+        assert_eq!(rest.len(), 32);
+        assert!(attached.is_full());
+        region.deallocate_all();
+        assert!(attached.is_empty());
+        assert!(!region.owns(first));
+
+        alloc_extreme(&region);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn offset_concurrent() {
+        use alloc::{boxed::Box, sync::Arc, vec::Vec};
+        use std::thread;
+
+        let data = Box::into_raw(Box::new([MaybeUninit::<u64>::uninit(); 129]));
+        let memory = NonNull::slice_from_raw_parts(NonNull::new(data).unwrap().cast(), 129 * 8);
+        let region = Arc::new(unsafe { RawOffsetRegion::new(memory) });
+
+        let threads = (0..4)
+            .map(|_| {
+                let region = Arc::clone(&region);
+                thread::spawn(move || {
+                    let mut offsets = Vec::new();
+                    while let Ok(memory) = region.allocate(Layout::new::<u64>()) {
+                        offsets.push(region.offset_of(memory.as_non_null_ptr()).unwrap());
+                    }
+                    offsets
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut offsets = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().expect("Thread panicked"))
+            .collect::<Vec<_>>();
+        offsets.sort_unstable();
+        assert_eq!(offsets, (1..129).map(|i| i * 8).collect::<Vec<_>>());
+
+        drop(region);
+        drop(unsafe { Box::from_raw(data) });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn offset_snapshot() {
+        use alloc::{boxed::Box, sync::Arc};
+        use core::ops::ControlFlow;
+        use std::{thread, time::Duration};
+
+        let data = Box::into_raw(Box::new([MaybeUninit::<u64>::uninit(); 9]));
+        let memory = NonNull::slice_from_raw_parts(NonNull::new(data).unwrap().cast(), 9 * 8);
+        let region = Arc::new(unsafe { RawOffsetRegion::new(memory) });
+
+        let snapshot = region.snapshot();
+        assert!(snapshot.is_empty());
+        assert_eq!(snapshot.len, 64);
+        assert_eq!(
+            region.resolve(RawOffsetRegion::HEADER_SIZE),
+            Some(snapshot.base)
+        );
+
+        let watcher = {
+            let region = Arc::clone(&region);
+            thread::spawn(move || {
+                region.watch(Duration::from_millis(1), |snapshot| {
+                    if snapshot.is_full() {
+                        ControlFlow::Break(snapshot.used())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                })
+            })
+        };
+        while region.allocate(Layout::new::<u64>()).is_ok() {}
+        assert_eq!(watcher.join().expect("Thread panicked"), 64);
+        assert_eq!(region.snapshot().capacity_left(), 0);
+
+        drop(region);
+        drop(unsafe { Box::from_raw(data) });
+    }
+
+    #[test]
+    fn fixed_region() {
+        crate::static_assert_fits!(FixedRegion<64>, u64, 7);
+        const _: () = assert!(FixedRegion::<64>::capacity() == 64);
+        assert!(!FixedRegion::<64>::fits::<u64>(8));
+        assert!(!FixedRegion::<64>::fits::<u8>(usize::MAX));
+
+        let region = FixedRegion::<64>::new();
+        let alloc = tracker(&region);
+        let first = alloc
+            .allocate(Layout::new::<u8>())
+            .expect("Could not allocate 1 byte");
+        let second = alloc
+            .allocate(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        assert_eq!(second.as_mut_ptr() as usize % mem::align_of::<u64>(), 0);
+        assert!(region.owns(first) && region.owns(second));
+        alloc
+            .allocate(Layout::new::<[u8; 64]>())
+            .expect_err("Allocated 64 bytes in a partially used region");
+
+        let left = region.capacity_left();
+        unsafe { alloc.deallocate(second.as_non_null_ptr(), Layout::new::<u64>()) };
+        assert_eq!(region.capacity_left(), left + 8);
+        assert!(!region.owns(second));
+
+        let rest = (&region).allocate_all().expect("Could not allocate all");
+        assert_eq!(rest.len(), left + 8);
+        assert!((&region).is_full());
+        (&region).deallocate_all();
+        assert!((&region).is_empty());
+    }
+
+    #[test]
+    fn multi_region() {
+        let mut chunks = [[MaybeUninit::uninit(); 32]; 3];
+        let region = tracker(MultiRegion::new(&mut chunks));
+        assert_eq!(region.capacity(), 96);
+
+        let first = region
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        region
+            .allocate(Layout::new::<[u8; 20]>())
+            .expect("Could not allocate 20 bytes");
+        assert_eq!(region.alloc.current_chunk(), 1);
+        region
+            .allocate(Layout::new::<[u8; 33]>())
+            .expect_err("Allocated 33 bytes in 32 byte chunks");
+        assert_eq!(region.alloc.current_chunk(), 1);
+        region
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert_eq!(region.alloc.current_chunk(), 2);
+        assert_eq!(region.capacity_left(), 16);
+        assert!(region.owns(first));
+
+        let near = region
+            .alloc
+            .allocate_near(Layout::new::<[u8; 8]>(), first.as_non_null_ptr())
+            .expect("Could not allocate 8 bytes");
+        assert_eq!(near.as_mut_ptr().wrapping_add(8), first.as_mut_ptr());
+        assert_eq!(region.alloc.current_chunk(), 2);
+
+        let rest = region.allocate_all().expect("Could not allocate all");
+        assert_eq!(rest.len(), 16);
+        assert!(region.is_full());
+        region.deallocate_all();
+        assert!(region.is_empty());
+        assert_eq!(region.alloc.current_chunk(), 0);
+
+        alloc_extreme(&region);
+    }
+
+    #[test]
+    fn dump_map() {
+        use alloc::string::ToString;
+
+        let mut data = [MaybeUninit::new(0); 32];
+        let region = Region::new(&mut data);
+        assert_eq!(region.dump_map(8).to_string(), "0000");
+        region
+            .allocate(Layout::new::<[u8; 9]>())
+            .expect("Could not allocate 9 bytes");
+        assert_eq!(region.dump_map(8).to_string(), "002f");
+        assert_eq!(region.dump_map(0).to_string().len(), 32);
+        assert_eq!(region.dump_map(usize::MAX).to_string(), "5");
+
+        let mut chunks = [[MaybeUninit::uninit(); 16]; 2];
+        let region = MultiRegion::new(&mut chunks);
+        region
+            .allocate(Layout::new::<[u8; 12]>())
+            .expect("Could not allocate 12 bytes");
+        region
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        let [first, second] = region.dump_map(4);
+        assert_eq!(first.to_string(), "0fff");
+        assert_eq!(second.to_string(), "00ff");
+    }
+
+    #[test]
+    fn split_memory() {
+        let mut data = [MaybeUninit::new(0); 100];
+        let [a, b, c] = super::split_memory(&mut data, [1, 1, 1]);
+        assert_eq!((a.len(), b.len(), c.len()), (33, 33, 34));
+
+        let [a, b] = super::split_memory(&mut data, [usize::MAX / 2, usize::MAX / 2]);
+        assert_eq!((a.len(), b.len()), (50, 50));
+        let [a, b, c] = super::split_memory(&mut data, [usize::MAX - 2, 1, 0]);
+        assert_eq!(b.len(), 0);
+        assert_eq!(a.len() + c.len(), 100);
+    }
+
+    #[test]
+    fn required_capacity() {
+        let layout = Layout::from_size_align(12, 8).expect("Invalid layout");
+        assert_eq!(super::required_capacity(layout, 0), Some(0));
+        assert_eq!(super::required_capacity(layout, 1), Some(19));
+        assert_eq!(super::required_capacity(layout, 3), Some(51));
+        assert_eq!(super::required_capacity(layout, usize::MAX), None);
+        let huge = Layout::from_size_align(isize::MAX as usize, 1).expect("Invalid layout");
+        assert_eq!(crate::Chunk::<(), 32>::parent_layout(huge), None);
+        assert_eq!(crate::Affix::<(), u64>::overhead(huge), None);
+
+        // Covers every address modulo the alignment
+        let mut data = [MaybeUninit::new(0); 64];
+        for offset in 0..8 {
+            let region = Region::new(&mut data[offset..][..51]);
+            for _ in 0..3 {
+                region
+                    .allocate(layout)
+                    .expect("Could not allocate 12 bytes");
+            }
+            let region = Region::<Up>::with_direction(&mut data[offset..][..51]);
+            for _ in 0..3 {
+                region
+                    .allocate(layout)
+                    .expect("Could not allocate 12 bytes");
+            }
+        }
+    }
+
+    #[test]
+    fn raw_parts() {
+        let mut data = [MaybeUninit::<u8>::uninit(); 64];
+        let memory = NonNull::slice_from_raw_parts(NonNull::from(&mut data).cast(), 64);
+
+        let region: RawRegion<Up> = unsafe { RawRegion::with_direction(memory) };
+        let first = region
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        let (raw_memory, offset) = region.into_raw_parts();
+        assert_eq!(raw_memory, memory);
+        assert_eq!(offset, 8);
+        let region = unsafe { RawRegion::<Up>::from_raw_parts(raw_memory, offset) };
+        assert!(region.owns(first));
+        assert_eq!(region.capacity_left(), 56);
+
+        let region = unsafe { RawRegion::new(memory) };
+        region
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        let (_, offset) = region.into_raw_parts();
+        assert_eq!(offset, 56);
+        assert_eq!(
+            unsafe { RawRegion::<Down>::from_raw_parts(memory, offset) }.capacity_left(),
+            56
+        );
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn offset_raw_parts() {
+        let mut buffer = crate::aligned_buffer!(64, 32);
+        let data = &mut *buffer;
+        let memory = NonNull::slice_from_raw_parts(NonNull::from(&mut *data).cast(), 64);
+
+        let region = unsafe { RawOffsetRegion::new(memory) };
+        let first = region
+            .allocate(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        let (raw_memory, position) = region.into_raw_parts();
+        assert_eq!(position, RawOffsetRegion::HEADER_SIZE + 8);
+
+        unsafe { RawOffsetRegion::new(raw_memory) };
+        let region = unsafe { RawOffsetRegion::from_raw_parts(raw_memory, position) };
+        assert!(region.owns(first));
+        assert_eq!(region.capacity_left(), 64 - position);
+    }
+
+    #[test]
+    fn intrusive_metadata() {
+        struct Metadata<'a> {
+            generation: u64,
+            drops: &'a Cell<usize>,
+        }
+
+        impl Drop for Metadata<'_> {
+            fn drop(&mut self) {
+                self.drops.set(self.drops.get() + 1)
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut buffer = crate::aligned_buffer!(64, 32, 1);
+        let data = &mut *buffer;
+        let mut region = IntrusiveRegion::<Down, _>::with_metadata(data, Metadata {
+            generation: 7,
+            drops: &drops,
+        });
+        assert_eq!(
+            region.capacity(),
+            64 - mem::size_of::<raw::State>() - mem::size_of::<Metadata<'_>>()
+        );
+
+        let rest = region.allocate_all().expect("Could not allocate all");
+        unsafe { rest.as_mut_ptr().write_bytes(0xFF, rest.len()) };
+        assert_eq!(region.metadata().generation, 7);
+        region.metadata_mut().generation += 1;
+        region.deallocate_all();
+        assert_eq!(region.metadata().generation, 8);
+
+        assert_eq!(drops.get(), 0);
+        drop(region);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut buffer = crate::aligned_buffer!(64, 32, 1);
+        let data = &mut *buffer;
+        let region = DoubleEndedRegion::new(data);
+        let bottom = tracker(region.bottom());
+        let top = tracker(region.top());
+
+        let low = bottom
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        let high = top
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert!(low.as_mut_ptr() < high.as_mut_ptr());
+        assert_eq!(region.capacity_left(), 40);
+        assert_eq!(bottom.capacity_left(), 40);
+        assert_eq!(top.capacity_left(), 40);
+        assert!(bottom.owns(low));
+        assert!(!bottom.owns(high));
+        assert!(top.owns(high));
+        assert!(!top.owns(low));
+        assert_eq!(
+            alloc::format!("{}", crate::visualize::ascii(&region.bottom())),
+            "RegionEnd(64 B, 37% used)\n"
+        );
+
+        bottom
+            .allocate(Layout::new::<[u8; 41]>())
+            .expect_err("Could allocate across the top end");
+        top.allocate(Layout::new::<[u8; 41]>())
+            .expect_err("Could allocate across the bottom end");
+
+        unsafe {
+            bottom
+                .grow(
+                    low.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 48]>(),
+                )
+                .expect("Could not grow to 48 bytes");
+        }
+        assert!(bottom.is_full());
+        assert!(top.is_full());
+
+        bottom.deallocate_all();
+        assert!(bottom.is_empty());
+        assert!(!top.is_empty());
+        assert_eq!(region.capacity_left(), 48);
+
+        let rest = top.allocate_all().expect("Could not allocate all");
+        assert_eq!(rest.len(), 48);
+        assert!(bottom.is_full());
+
+        top.deallocate_all();
+        assert_eq!(region.capacity_left(), 64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn identity() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let mut other = [MaybeUninit::new(0); 64];
+        let region = SharedRegion::new(&mut data);
+        let clone = region.clone();
+        clone
+            .allocate(Layout::new::<u32>())
+            .expect("Could not allocate u32");
+        assert!(region == clone);
+        assert!(region != SharedRegion::new(&mut other));
+
+        let mut data = [MaybeUninit::new(0); 64];
+        let region = DoubleEndedRegion::new(&mut data);
+        assert_eq!(region.bottom(), region.bottom());
+    }
 
     #[test]
     fn vec() {
-        let mut raw_data = [MaybeUninit::<u8>::new(1); 128];
-        let data = aligned_slice(&mut raw_data, 32);
+        let mut buffer = crate::aligned_buffer!(32, 32, 1);
+        let data = &mut *buffer;
         let region = tracker(Region::new(data));
         let mut vec = alloc::vec::Vec::new_in(region.by_ref());
         vec.push(10);
     }
 
     // #[test]
-    // fn dealloc() {
+    // fn deallocate() {
     //     let mut data = [MaybeUninit::new(1); 32];
     //     let mut region = Region::new(&mut data);
     //     let layout = Layout::from_size_align(8, 1).expect("Invalid layout");
 
-    //     let memory = region.alloc(layout).expect("Could not allocate 8 bytes");
+    //     let memory = region.allocate(layout).expect("Could not allocate 8 bytes");
     //     assert!(region.owns(memory));
     //     assert_eq!(region.capacity_left(), 24);
 
     //     unsafe {
-    //         region.dealloc(memory.as_non_null_ptr(), layout);
+    //         region.deallocate(memory.as_non_null_ptr(), layout);
     //     }
     //     assert_eq!(region.capacity_left(), 32);
     //     assert!(!region.owns(memory));
 
-    //     let memory = region.alloc(layout).expect("Could not allocate 8 bytes");
+    //     let memory = region.allocate(layout).expect("Could not allocate 8 bytes");
     //     assert!(region.owns(memory));
-    //     region.alloc(layout).expect("Could not allocate 8 bytes");
+    //     region.allocate(layout).expect("Could not allocate 8 bytes");
     //     assert!(region.owns(memory));
     //     assert_eq!(memory.len(), 8);
     //     assert_eq!(region.capacity_left(), 16);
 
     //     unsafe {
-    //         region.dealloc(memory.as_non_null_ptr(), layout);
+    //         region.deallocate(memory.as_non_null_ptr(), layout);
     //     }
     //     // It is not possible to deallocate memory that was not allocated last.
     //     assert!(region.owns(memory));
@@ -503,11 +2466,11 @@ mod tests {
     //     let mut region = Region::new(&mut data);
     //     let layout = Layout::from_size_align(8, 1).expect("Invalid layout");
 
-    //     let memory = region.alloc(layout).expect("Could not allocate 8 bytes");
+    //     let memory = region.allocate(layout).expect("Could not allocate 8 bytes");
     //     assert_eq!(memory.len(), 8);
     //     assert_eq!(region.capacity_left(), 24);
 
-    //     region.alloc(layout).expect("Could not allocate 8 bytes");
+    //     region.allocate(layout).expect("Could not allocate 8 bytes");
     //     assert_eq!(region.capacity_left(), 16);
 
     //     let memory = unsafe {
@@ -520,10 +2483,10 @@ mod tests {
 
     //     region.dealloc_all();
     //     let memory = region
-    //         .alloc_zeroed(Layout::new::<[u8; 16]>())
+    //         .allocate_zeroed(Layout::new::<[u8; 16]>())
     //         .expect("Could not allocate 16 bytes");
     //     region
-    //         .alloc(Layout::new::<[u8; 8]>())
+    //         .allocate(Layout::new::<[u8; 8]>())
     //         .expect("Could not allocate 16 bytes");
 
     //     unsafe {
@@ -555,12 +2518,12 @@ mod tests {
     //     test_output(&region);
 
     //     region
-    //         .alloc(Layout::new::<[u8; 16]>())
+    //         .allocate(Layout::new::<[u8; 16]>())
     //         .expect("Could not allocate 16 bytes");
     //     test_output(&region);
 
     //     region
-    //         .alloc(Layout::new::<[u8; 16]>())
+    //         .allocate(Layout::new::<[u8; 16]>())
     //         .expect("Could not allocate 16 bytes");
     //     test_output(&region);
 
@@ -8,38 +8,237 @@
 //!
 //! [`region`]: crate::region
 
-use crate::{intrinsics::unlikely, AllocateAll, Owns};
+use crate::{
+    allocate_many::{bump_many, ManyBlocks},
+    describe::describe_usage,
+    intrinsics::unlikely,
+    AllocateAll,
+    AllocateMany,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
 use core::{
-    alloc::{AllocError, AllocRef, Layout},
+    alloc::{AllocError, Layout},
     cell::Cell,
     fmt,
-    ptr::NonNull,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::{self, NonNull},
 };
 
+#[cfg(target_has_atomic = "ptr")]
+use crate::sync::atomic::{AtomicUsize, Ordering};
+
 #[cfg(any(doc, feature = "alloc"))]
 use alloc::rc::Rc;
 
+use self::sealed::Direction as _;
+
+/// Memory lost to alignment padding in a region.
+///
+/// Padding bytes are counted for every allocation, which cannot start right at the current
+/// position of the region. The statistics are reset by [`AllocateAll::deallocate_all`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FragmentationStats {
+    /// The total number of bytes lost to alignment padding.
+    pub padding: usize,
+    /// The largest number of bytes lost to alignment padding in a single allocation.
+    pub max_padding: usize,
+}
+
+/// A coarse map of the memory of a region, returned by `dump_map`.
+///
+/// It's displayed as one hexadecimal digit for every granule of the memory, from the start of the
+/// memory to its end. Each digit tells how much of the granule is in use, from `0` for a free
+/// granule to `f` for a full one. Partially used granules are rounded up, so `0` is only shown, if
+/// no byte of the granule is in use. Memory blocks, which were deallocated without being
+/// reclaimed, and alignment padding count as used.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::region::{Region, Up};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let region: Region<Up> = Region::with_direction(&mut data);
+///
+/// region.allocate(Layout::new::<[u8; 20]>())?;
+/// assert_eq!(region.dump_map(8).to_string(), "ff800000");
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RegionMap {
+    len: usize,
+    used_start: usize,
+    used_end: usize,
+    granularity: usize,
+}
+
+impl fmt::Display for RegionMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+        let mut start = 0;
+        while start < self.len {
+            let end = start.saturating_add(self.granularity).min(self.len);
+            let used = end
+                .min(self.used_end)
+                .saturating_sub(start.max(self.used_start));
+            // `used <= end - start`, so a saturated product still yields a level of at most 15
+            let level = used.saturating_mul(15).div_ceil(end - start);
+            fmt::Write::write_char(f, char::from(DIGITS[level]))?;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+pub(super) struct State {
+    current: Cell<NonNull<u8>>,
+    fragmentation: Cell<FragmentationStats>,
+}
+
+impl State {
+    #[inline]
+    const fn new(current: NonNull<u8>) -> Self {
+        Self {
+            current: Cell::new(current),
+            fragmentation: Cell::new(FragmentationStats {
+                padding: 0,
+                max_padding: 0,
+            }),
+        }
+    }
+}
+
 trait Current {
-    fn current(&self) -> NonNull<u8>;
+    fn state(&self) -> &State;
+
+    #[inline]
+    fn current(&self) -> NonNull<u8> {
+        self.state().current.get()
+    }
 
     #[inline]
-    fn current_usize(&self) -> usize {
-        self.current().as_ptr() as usize
+    fn set_current(&self, ptr: NonNull<u8>) {
+        self.state().current.set(ptr)
+    }
+
+    #[inline]
+    fn record_padding(&self, padding: usize) {
+        let fragmentation = &self.state().fragmentation;
+        let stats = fragmentation.get();
+        fragmentation.set(FragmentationStats {
+            padding: stats.padding + padding,
+            max_padding: stats.max_padding.max(padding),
+        })
     }
 
-    fn set_current(&self, ptr: NonNull<u8>);
+    #[inline]
+    fn reset(&self, start: NonNull<u8>) {
+        self.set_current(start);
+        self.state()
+            .fragmentation
+            .set(FragmentationStats::default())
+    }
+}
+
+mod sealed {
+    use core::{alloc::AllocError, ptr::NonNull};
+
+    pub trait Direction {
+        /// Returns the position of an empty region.
+        fn start(memory: NonNull<[u8]>) -> NonNull<u8>;
+
+        /// Returns the memory block and the new position.
+        fn allocate(
+            memory: NonNull<[u8]>,
+            current: NonNull<u8>,
+            size: usize,
+            align: usize,
+        ) -> Result<(NonNull<[u8]>, NonNull<u8>), AllocError>;
+
+        /// Returns the remaining memory and the new position.
+        fn alloc_all(memory: NonNull<[u8]>, current: NonNull<u8>) -> (NonNull<[u8]>, NonNull<u8>);
+
+        /// Resizes the memory block at `ptr` in place and returns the new position.
+        unsafe fn resize(
+            memory: NonNull<[u8]>,
+            current: NonNull<u8>,
+            ptr: NonNull<u8>,
+            old_size: usize,
+            new_size: usize,
+            align: usize,
+        ) -> Result<NonNull<u8>, AllocError>;
+
+        /// Returns the new position, if the memory block at `ptr` was allocated last.
+        fn deallocate(
+            memory: NonNull<[u8]>,
+            current: NonNull<u8>,
+            ptr: NonNull<u8>,
+            size: usize,
+        ) -> Option<NonNull<u8>>;
+
+        fn capacity_left(memory: NonNull<[u8]>, current: NonNull<u8>) -> usize;
+
+        fn owns(memory: NonNull<[u8]>, current: NonNull<u8>, block: NonNull<[u8]>) -> bool;
+
+        /// Returns the part of `memory`, which can be used up to the position `limit` of the
+        /// opposite direction.
+        fn view(memory: NonNull<[u8]>, limit: NonNull<u8>) -> NonNull<[u8]>;
+    }
 }
 
+/// The direction, in which a region hands out memory.
+///
+/// This is implemented by [`Down`] and [`Up`].
+pub trait Direction: sealed::Direction {}
+
+/// Bumps the current position from the end of the memory towards the start.
+///
+/// This is the default direction of all regions. As the alignment is applied by rounding the
+/// address down, the padding ends up behind the memory block, which is returned as part of the
+/// block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Down;
+
+/// Bumps the current position from the start of the memory towards the end.
+///
+/// Sequentially allocated memory blocks are laid out in ascending order, which gives a better
+/// locality for buffers, which are consumed front to back. Additionally, the last memory block
+/// can be grown and shrunk in place.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Up;
+
+impl Direction for Down {}
+impl Direction for Up {}
+
 /// A stack allocator over an user-defined region of memory.
 ///
 /// This is the non-lifetime version of [`Region`].
 ///
 /// [`Region`]: crate::region::Region
-pub struct RawRegion {
+pub struct RawRegion<D: Direction = Down> {
     memory: NonNull<[u8]>,
-    current: Cell<NonNull<u8>>,
+    state: State,
+    _direction: PhantomData<D>,
 }
 
+// The region is the only one accessing its memory, so it may be moved to another thread.
+unsafe impl<D: Direction> Send for RawRegion<D> {}
+
 impl RawRegion {
     /// Creates a new region from the given memory block.
     ///
@@ -61,31 +260,75 @@ impl RawRegion {
     /// [`pointer::offset`]: https://doc.rust-lang.org/std/primitive.pointer.html#method.offset
     #[inline]
     pub unsafe fn new(memory: NonNull<[u8]>) -> Self {
+        Self::with_direction(memory)
+    }
+}
+
+impl<D: Direction> RawRegion<D> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawRegion::new`].
+    #[inline]
+    pub unsafe fn with_direction(memory: NonNull<[u8]>) -> Self {
         Self {
             memory,
-            current: Cell::new(end(memory)),
+            state: State::new(D::start(memory)),
+            _direction: PhantomData,
         }
     }
-}
 
-impl Current for RawRegion {
+    /// Decomposes the region into its memory block and the offset of the current position
+    /// relative to the start of the memory block.
+    ///
+    /// The region can be restored with [`from_raw_parts`], e.g. after handing the memory over FFI.
+    /// The [`FragmentationStats`] are not preserved.
+    ///
+    /// [`from_raw_parts`]: Self::from_raw_parts
     #[inline]
-    fn current(&self) -> NonNull<u8> {
-        self.current.get()
+    pub fn into_raw_parts(self) -> (NonNull<[u8]>, usize) {
+        (self.memory, offset_of(self.memory, self.current()))
     }
 
+    /// Creates a region from a memory block and the offset of the current position, as returned
+    /// by [`into_raw_parts`].
+    ///
+    /// # Safety
+    ///
+    /// See [`RawRegion::new`]. Additionally, `offset` must not be larger than `memory.len()`,
+    /// and the memory blocks allocated before decomposing the region must still be in `memory`
+    /// on the side of `offset` which was allocated in direction `D`.
+    ///
+    /// [`into_raw_parts`]: Self::into_raw_parts
     #[inline]
-    fn set_current(&self, ptr: NonNull<u8>) {
-        self.current.set(ptr)
+    pub unsafe fn from_raw_parts(memory: NonNull<[u8]>, offset: usize) -> Self {
+        debug_assert!(
+            offset <= memory.len(),
+            "`offset` must be smaller than or equal to `memory.len()`"
+        );
+        Self {
+            memory,
+            state: State::new(at(memory, offset)),
+            _direction: PhantomData,
+        }
+    }
+}
+
+impl<D: Direction> Current for RawRegion<D> {
+    #[inline]
+    fn state(&self) -> &State {
+        &self.state
     }
 }
 
 #[derive(Clone)]
 #[cfg(any(doc, feature = "alloc"))]
 #[cfg_attr(doc, doc(cfg(feature = "alloc")))]
-pub struct RawSharedRegion {
+pub struct RawSharedRegion<D: Direction = Down> {
     memory: NonNull<[u8]>,
-    current: Rc<Cell<NonNull<u8>>>,
+    state: Rc<State>,
+    _direction: PhantomData<D>,
 }
 
 /// A clonable region allocator based on `Rc`.
@@ -115,23 +358,32 @@ impl RawSharedRegion {
     /// [`pointer::offset`]: https://doc.rust-lang.org/std/primitive.pointer.html#method.offset
     #[inline]
     pub unsafe fn new(memory: NonNull<[u8]>) -> Self {
-        Self {
-            memory,
-            current: Rc::new(Cell::new(end(memory))),
-        }
+        Self::with_direction(memory)
     }
 }
 
 #[cfg(any(doc, feature = "alloc"))]
-impl Current for RawSharedRegion {
+impl<D: Direction> RawSharedRegion<D> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawSharedRegion::new`].
     #[inline]
-    fn current(&self) -> NonNull<u8> {
-        self.current.get()
+    pub unsafe fn with_direction(memory: NonNull<[u8]>) -> Self {
+        Self {
+            memory,
+            state: Rc::new(State::new(D::start(memory))),
+            _direction: PhantomData,
+        }
     }
+}
 
+#[cfg(any(doc, feature = "alloc"))]
+impl<D: Direction> Current for RawSharedRegion<D> {
     #[inline]
-    fn set_current(&self, ptr: NonNull<u8>) {
-        self.current.set(ptr)
+    fn state(&self) -> &State {
+        &self.state
     }
 }
 
@@ -141,9 +393,10 @@ impl Current for RawSharedRegion {
 ///
 /// [`IntrusiveRegion`]: crate::region::IntrusiveRegion
 #[derive(Clone)]
-pub struct RawIntrusiveRegion {
+pub struct RawIntrusiveRegion<D: Direction = Down> {
     memory: NonNull<[u8]>,
-    current: NonNull<Cell<NonNull<u8>>>,
+    state: NonNull<State>,
+    _direction: PhantomData<D>,
 }
 
 impl RawIntrusiveRegion {
@@ -168,70 +421,243 @@ impl RawIntrusiveRegion {
     ///
     /// # Panics
     ///
-    /// This function panics, when `memory` is not large enough to properly store a pointer.
+    /// This function panics, when `memory` is not large enough to properly store the current
+    /// position. See [`try_new`] for a fallible variant.
+    ///
+    /// [`try_new`]: Self::try_new
     #[inline]
     pub unsafe fn new(memory: NonNull<[u8]>) -> Self {
-        let current: NonNull<Cell<NonNull<u8>>> = alloc_impl(
-            memory,
-            end(memory),
-            Layout::new::<NonNull<Cell<NonNull<u8>>>>(),
-        )
-        .expect("Could not store pointer in region")
-        .as_non_null_ptr()
-        .cast();
-        current.as_ptr().write(Cell::new(current.cast()));
+        Self::with_direction(memory)
+    }
+
+    /// Creates a new region like [`new`], but returns `Err`, when `memory` is not large enough to
+    /// properly store the current position.
+    ///
+    /// # Safety
+    ///
+    /// See [`new`].
+    ///
+    /// [`new`]: Self::new
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`], when the current position does not fit into `memory`.
+    #[inline]
+    #[track_caller]
+    pub unsafe fn try_new(memory: NonNull<[u8]>) -> Result<Self, ComposeError> {
+        Self::try_with_direction(memory)
+    }
+}
+
+impl<D: Direction> RawIntrusiveRegion<D> {
+    /// Creates a new region from the given memory block, which bumps in the direction `D`.
+    ///
+    /// The current position is always stored at the end of `memory`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawIntrusiveRegion::new`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not large enough to properly store the current
+    /// position. See [`try_with_direction`] for a fallible variant.
+    ///
+    /// [`try_with_direction`]: Self::try_with_direction
+    #[inline]
+    pub unsafe fn with_direction(memory: NonNull<[u8]>) -> Self {
+        Self::try_with_direction(memory).expect("Could not store the state in region")
+    }
+
+    /// Creates a new region like [`with_direction`], but returns `Err`, when `memory` is not
+    /// large enough to properly store the current position.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawIntrusiveRegion::new`].
+    ///
+    /// [`with_direction`]: Self::with_direction
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`], when the current position does not fit into `memory`.
+    #[inline]
+    #[track_caller]
+    pub unsafe fn try_with_direction(memory: NonNull<[u8]>) -> Result<Self, ComposeError> {
+        Ok(Self::try_with_header(memory, Layout::new::<()>())?.0)
+    }
+
+    /// Creates a new region like [`try_with_direction`] and reserves an uninitialized memory
+    /// block of `header` in front of the current position.
+    ///
+    /// [`try_with_direction`]: Self::try_with_direction
+    #[track_caller]
+    pub(super) unsafe fn try_with_header(
+        memory: NonNull<[u8]>,
+        header: Layout,
+    ) -> Result<(Self, NonNull<u8>), ComposeError> {
+        let layout = Layout::new::<State>();
+        let Ok(state) = alloc_impl(memory, end(memory), layout.size(), layout.align()) else {
+            return Err(ComposeError::exhausted("RawIntrusiveRegion"));
+        };
+        let state = state.as_non_null_ptr().cast::<State>();
+        let Ok(header) = alloc_impl(memory, state.cast(), header.size(), header.align()) else {
+            return Err(ComposeError::exhausted("RawIntrusiveRegion"));
+        };
+        let header = header.as_non_null_ptr();
         let memory = NonNull::slice_from_raw_parts(
             memory.as_non_null_ptr(),
-            current.as_ptr() as usize - memory.as_mut_ptr() as usize,
+            header.as_ptr() as usize - memory.as_mut_ptr() as usize,
         );
-        Self { memory, current }
+        state.as_ptr().write(State::new(D::start(memory)));
+        let region = Self {
+            memory,
+            state,
+            _direction: PhantomData,
+        };
+        Ok((region, header))
     }
 }
 
-impl Current for RawIntrusiveRegion {
+impl<D: Direction> Current for RawIntrusiveRegion<D> {
     #[inline]
-    fn current(&self) -> NonNull<u8> {
-        unsafe { self.current.as_ref().get() }
+    fn state(&self) -> &State {
+        unsafe { self.state.as_ref() }
     }
+}
+
+/// The current position of a [`BorrowedRegion`], which is provided by the user.
+///
+/// The position is stored in a [`Cell`], so the state can live anywhere, e.g. on the stack next
+/// to the memory or in a `static`. It is reset, whenever a region is created from it.
+///
+/// [`BorrowedRegion`]: crate::region::BorrowedRegion
+#[repr(transparent)]
+pub struct RegionState(State);
 
+impl RegionState {
+    /// Creates a state, which is not yet bound to a region.
     #[inline]
-    fn set_current(&self, ptr: NonNull<u8>) {
-        unsafe { self.current.as_ref().set(ptr) }
+    pub const fn new() -> Self {
+        Self(State::new(NonNull::dangling()))
     }
 }
 
-#[inline]
-fn alloc_impl(
+impl Default for RegionState {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RegionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegionState")
+            .field("current", &self.0.current.get())
+            .finish()
+    }
+}
+
+/// A clonable region allocator, which stores the current position in a [`RegionState`] provided
+/// by the user.
+///
+/// This is the non-lifetime version of [`BorrowedRegion`].
+///
+/// [`BorrowedRegion`]: crate::region::BorrowedRegion
+#[derive(Clone)]
+pub struct RawBorrowedRegion<D: Direction = Down> {
     memory: NonNull<[u8]>,
-    current: NonNull<u8>,
-    layout: Layout,
-) -> Result<NonNull<[u8]>, AllocError> {
-    let current = current.as_ptr() as usize;
-    let new = current.checked_sub(layout.size()).ok_or(AllocError)?;
-    let aligned = (new & !(layout.align() - 1)) as *mut u8;
+    state: NonNull<State>,
+    _direction: PhantomData<D>,
+}
 
-    if unlikely(aligned < memory.as_mut_ptr()) {
-        Err(AllocError)
-    } else {
-        Ok(NonNull::slice_from_raw_parts(
-            unsafe { NonNull::new_unchecked(aligned) },
-            current - aligned as usize,
-        ))
+impl RawBorrowedRegion {
+    /// Creates a new region from the given memory block and resets `state`.
+    ///
+    /// # Safety
+    ///
+    /// Behavior is undefined if any of the following conditions are violated:
+    ///
+    /// * `memory` must be [valid] for reads and writes for `memory.len()` many bytes.
+    ///
+    /// * `memory` and `state` must outlive the region and all of its clones.
+    ///
+    /// * `state` must not be used by another region at the same time.
+    ///
+    /// * `memory.len()` must be no larger than `isize::MAX`.
+    ///   See the safety documentation of [`pointer::offset`].
+    ///
+    /// For a safe variant use [`BorrowedRegion`] instead.
+    ///
+    /// [`BorrowedRegion`]: crate::region::BorrowedRegion
+    /// [valid]: core::ptr#safety
+    /// [`pointer::offset`]: https://doc.rust-lang.org/std/primitive.pointer.html#method.offset
+    #[inline]
+    pub unsafe fn new(memory: NonNull<[u8]>, state: NonNull<RegionState>) -> Self {
+        Self::with_direction(memory, state)
     }
 }
 
-#[inline]
-fn alloc_all_impl(
+impl<D: Direction> RawBorrowedRegion<D> {
+    /// Creates a new region from the given memory block and resets `state`, which bumps in the
+    /// direction `D`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawBorrowedRegion::new`].
+    #[inline]
+    pub unsafe fn with_direction(memory: NonNull<[u8]>, state: NonNull<RegionState>) -> Self {
+        let state = state.cast::<State>();
+        state.as_ref().current.set(D::start(memory));
+        state
+            .as_ref()
+            .fragmentation
+            .set(FragmentationStats::default());
+        Self {
+            memory,
+            state,
+            _direction: PhantomData,
+        }
+    }
+}
+
+impl<D: Direction> Current for RawBorrowedRegion<D> {
+    #[inline]
+    fn state(&self) -> &State {
+        unsafe { self.state.as_ref() }
+    }
+}
+
+/// Bumps `current` down by `size` bytes and aligns the result to `align`.
+///
+/// `align` has to be a power of two and `size` must not exceed `isize::MAX`. In this case, an
+/// underflow of `current - size` results in an address above `current`, so a single comparison
+/// is sufficient to check if the memory block fits into the region. The same holds for alignments
+/// larger than `current`: masking rounds the address down to zero, which wraps the offset around.
+/// When called with a constant alignment, the mask is computed at compile time.
+#[inline(always)]
+fn alloc_impl(
     memory: NonNull<[u8]>,
     current: NonNull<u8>,
+    size: usize,
+    align: usize,
 ) -> Result<NonNull<[u8]>, AllocError> {
+    debug_assert!(align.is_power_of_two());
+    debug_assert!(size <= isize::MAX as usize);
+
+    let base = memory.as_mut_ptr() as usize;
     let current = current.as_ptr() as usize;
-    let new = memory.as_non_null_ptr();
+    let aligned = current.wrapping_sub(size) & !(align - 1);
+    let offset = aligned.wrapping_sub(base);
 
-    Ok(NonNull::slice_from_raw_parts(
-        new,
-        current - new.as_ptr() as usize,
-    ))
+    if unlikely(offset > current - base) {
+        Err(AllocError)
+    } else {
+        // Derive the pointer from `memory` instead of casting the address back to a pointer, so
+        // the provenance of the memory block is retained.
+        let ptr = unsafe { NonNull::new_unchecked(memory.as_mut_ptr().add(offset)) };
+        Ok(NonNull::slice_from_raw_parts(ptr, current - aligned))
+    }
 }
 
 #[inline]
@@ -239,113 +665,441 @@ fn end(ptr: NonNull<[u8]>) -> NonNull<u8> {
     unsafe { NonNull::new_unchecked(ptr.as_mut_ptr().add(ptr.len())) }
 }
 
-// unsafe impl AllocRef for RawRegion {
-//     #[inline]
-//     fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-//         let new = alloc_impl(self.memory, self.current.get(), layout)?;
-//         self.current.set(new.as_non_null_ptr());
-//         Ok(new)
-//     }
+#[inline]
+fn offset_of(memory: NonNull<[u8]>, ptr: NonNull<u8>) -> usize {
+    ptr.as_ptr() as usize - memory.as_mut_ptr() as usize
+}
 
-//     #[inline]
-//     unsafe fn dealloc(&self, _ptr: NonNull<u8>, _layout: Layout) {}
-// }
+/// Returns `true`, if both memory blocks start at the same address and have the same length.
+#[inline]
+fn same_memory(a: NonNull<[u8]>, b: NonNull<[u8]>) -> bool {
+    ptr::addr_eq(a.as_ptr(), b.as_ptr()) && a.len() == b.len()
+}
 
-// unsafe impl AllocRef for RawSharedRegion {
-//     #[inline]
-//     fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-//         let current = self.current.as_ref();
-//         let new = alloc_impl(self.memory, current.get(), layout)?;
-//         current.set(new.as_non_null_ptr());
-//         Ok(new)
-//     }
+#[inline]
+fn at(memory: NonNull<[u8]>, offset: usize) -> NonNull<u8> {
+    unsafe { NonNull::new_unchecked(memory.as_mut_ptr().add(offset)) }
+}
 
-//     #[inline]
-//     unsafe fn dealloc(&self, _ptr: NonNull<u8>, _layout: Layout) {}
-// }
+impl sealed::Direction for Down {
+    #[inline]
+    fn start(memory: NonNull<[u8]>) -> NonNull<u8> {
+        end(memory)
+    }
 
-// unsafe impl AllocRef for RawIntrusiveRegion {
-//     #[inline]
-//     fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-//         let current = unsafe { self.current.as_ref() };
-//         let new = alloc_impl(self.memory, current.get(), layout)?;
-//         current.set(new.as_non_null_ptr());
-//         Ok(new)
-//     }
+    #[inline(always)]
+    fn allocate(
+        memory: NonNull<[u8]>,
+        current: NonNull<u8>,
+        size: usize,
+        align: usize,
+    ) -> Result<(NonNull<[u8]>, NonNull<u8>), AllocError> {
+        let new = alloc_impl(memory, current, size, align)?;
+        Ok((new, new.as_non_null_ptr()))
+    }
 
-//     #[inline]
-//     unsafe fn dealloc(&self, _ptr: NonNull<u8>, _layout: Layout) {}
-// }
+    #[inline]
+    fn alloc_all(memory: NonNull<[u8]>, current: NonNull<u8>) -> (NonNull<[u8]>, NonNull<u8>) {
+        let new = memory.as_non_null_ptr();
+        (
+            NonNull::slice_from_raw_parts(new, offset_of(memory, current)),
+            new,
+        )
+    }
 
-macro_rules! impl_raw_region {
-    ($ty:ident) => {
-        impl PartialEq for $ty {
-            #[inline]
-            fn eq(&self, rhs: &Self) -> bool {
-                self.memory == rhs.memory
-            }
-        }
+    #[inline]
+    unsafe fn resize(
+        _memory: NonNull<[u8]>,
+        _current: NonNull<u8>,
+        _ptr: NonNull<u8>,
+        _old_size: usize,
+        _new_size: usize,
+        _align: usize,
+    ) -> Result<NonNull<u8>, AllocError> {
+        Err(AllocError)
+    }
 
-        impl fmt::Debug for $ty {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                f.debug_struct("RawRegion")
-                    .field("memory", &self.memory)
-                    .field("len", &self.memory.len())
-                    .field("current", &self.current())
-                    .finish()
-            }
+    /// The padding behind the memory block is only reclaimed, if `size` covers it.
+    #[inline]
+    fn deallocate(
+        memory: NonNull<[u8]>,
+        current: NonNull<u8>,
+        ptr: NonNull<u8>,
+        size: usize,
+    ) -> Option<NonNull<u8>> {
+        if ptr == current {
+            Some(at(memory, offset_of(memory, ptr) + size))
+        } else {
+            None
         }
+    }
 
-        unsafe impl AllocRef for $ty {
-            #[inline]
-            fn alloc(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-                let new = alloc_impl(self.memory, self.current(), layout)?;
-                self.set_current(new.as_non_null_ptr());
-                Ok(new)
-            }
-
-            #[inline]
-            unsafe fn dealloc(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+    #[inline]
+    fn capacity_left(memory: NonNull<[u8]>, current: NonNull<u8>) -> usize {
+        offset_of(memory, current)
+    }
 
+    #[inline]
+    fn owns(memory: NonNull<[u8]>, current: NonNull<u8>, block: NonNull<[u8]>) -> bool {
+        let ptr = block.as_mut_ptr() as usize;
+        ptr >= current.as_ptr() as usize && ptr + block.len() <= end(memory).as_ptr() as usize
+    }
+
+    #[inline]
+    fn view(memory: NonNull<[u8]>, limit: NonNull<u8>) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(limit, memory.len() - offset_of(memory, limit))
+    }
+}
+
+impl sealed::Direction for Up {
+    #[inline]
+    fn start(memory: NonNull<[u8]>) -> NonNull<u8> {
+        memory.as_non_null_ptr()
+    }
+
+    #[inline(always)]
+    fn allocate(
+        memory: NonNull<[u8]>,
+        current: NonNull<u8>,
+        size: usize,
+        align: usize,
+    ) -> Result<(NonNull<[u8]>, NonNull<u8>), AllocError> {
+        debug_assert!(align.is_power_of_two());
+        debug_assert!(size <= isize::MAX as usize);
+
+        // Rounding up to a huge alignment or adding a huge size may overflow the address space.
+        // Neither fits into the region.
+        let base = memory.as_mut_ptr() as usize;
+        let aligned = match (current.as_ptr() as usize).checked_add(align - 1) {
+            Some(address) => address & !(align - 1),
+            None => return Err(AllocError),
+        };
+        let offset = aligned.wrapping_sub(base);
+        match offset.checked_add(size) {
+            Some(new_offset) if !unlikely(new_offset > memory.len()) => Ok((
+                NonNull::slice_from_raw_parts(at(memory, offset), size),
+                at(memory, new_offset),
+            )),
+            _ => Err(AllocError),
+        }
+    }
+
+    #[inline]
+    fn alloc_all(memory: NonNull<[u8]>, current: NonNull<u8>) -> (NonNull<[u8]>, NonNull<u8>) {
+        (
+            NonNull::slice_from_raw_parts(current, memory.len() - offset_of(memory, current)),
+            end(memory),
+        )
+    }
+
+    #[inline]
+    unsafe fn resize(
+        memory: NonNull<[u8]>,
+        current: NonNull<u8>,
+        ptr: NonNull<u8>,
+        old_size: usize,
+        new_size: usize,
+        align: usize,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let offset = offset_of(memory, ptr);
+        if offset + old_size != offset_of(memory, current)
+            || !(ptr.as_ptr() as usize).is_multiple_of(align)
+            || new_size > memory.len() - offset
+        {
+            return Err(AllocError);
+        }
+        Ok(at(memory, offset + new_size))
+    }
+
+    #[inline]
+    fn deallocate(
+        memory: NonNull<[u8]>,
+        current: NonNull<u8>,
+        ptr: NonNull<u8>,
+        size: usize,
+    ) -> Option<NonNull<u8>> {
+        if offset_of(memory, ptr) + size == offset_of(memory, current) {
+            Some(ptr)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn capacity_left(memory: NonNull<[u8]>, current: NonNull<u8>) -> usize {
+        memory.len() - offset_of(memory, current)
+    }
+
+    #[inline]
+    fn owns(memory: NonNull<[u8]>, current: NonNull<u8>, block: NonNull<[u8]>) -> bool {
+        let ptr = block.as_mut_ptr() as usize;
+        ptr >= memory.as_mut_ptr() as usize && ptr + block.len() <= current.as_ptr() as usize
+    }
+
+    #[inline]
+    fn view(memory: NonNull<[u8]>, limit: NonNull<u8>) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(memory.as_non_null_ptr(), offset_of(memory, limit))
+    }
+}
+
+// unsafe impl Allocator for RawRegion {
+//     #[inline]
+//     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+//         let new = alloc_impl(self.memory, self.current.get(), layout)?;
+//         self.current.set(new.as_non_null_ptr());
+//         Ok(new)
+//     }
+
+//     #[inline]
+//     unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+// }
+
+// unsafe impl Allocator for RawSharedRegion {
+//     #[inline]
+//     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+//         let current = self.current.as_ref();
+//         let new = alloc_impl(self.memory, current.get(), layout)?;
+//         current.set(new.as_non_null_ptr());
+//         Ok(new)
+//     }
+
+//     #[inline]
+//     unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+// }
+
+// unsafe impl Allocator for RawIntrusiveRegion {
+//     #[inline]
+//     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+//         let current = unsafe { self.current.as_ref() };
+//         let new = alloc_impl(self.memory, current.get(), layout)?;
+//         current.set(new.as_non_null_ptr());
+//         Ok(new)
+//     }
+
+//     #[inline]
+//     unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+// }
+
+macro_rules! impl_raw_region {
+    ($ty:ident) => {
+        impl<D: Direction> $ty<D> {
+            /// Allocates `size` bytes aligned to the constant `ALIGN`.
+            ///
+            /// This is a fast path for [`allocate`], when the alignment is known at compile time.
+            /// The returned memory block has the same properties as if [`allocate`] was called with
+            /// the corresponding layout.
+            ///
+            /// # Errors
+            ///
+            /// Returns `Err`, if the memory block does not fit into the region or if `size`
+            /// exceeds `isize::MAX`.
+            ///
+            /// # Panics
+            ///
+            /// Panics, if `ALIGN` is not a power of two. With the `no-panic` feature, `Err` is
+            /// returned instead.
+            ///
+            /// [`allocate`]: core::alloc::Allocator::allocate
+            #[inline]
+            pub fn alloc_aligned<const ALIGN: usize>(
+                &self,
+                size: usize,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                #[cfg(not(feature = "no-panic"))]
+                assert!(ALIGN.is_power_of_two(), "`ALIGN` must be a power of two");
+                #[cfg(feature = "no-panic")]
+                if !ALIGN.is_power_of_two() {
+                    return Err(AllocError);
+                }
+                if size > isize::MAX as usize {
+                    return Err(AllocError);
+                }
+                self.alloc_impl(size, ALIGN)
+            }
+
+            /// Allocates `size` bytes without any alignment requirement.
+            ///
+            /// This is equivalent to [`alloc_aligned::<1>`], which is the fastest way to allocate
+            /// byte buffers like strings.
+            ///
+            /// # Errors
+            ///
+            /// Returns `Err`, if the memory block does not fit into the region or if `size`
+            /// exceeds `isize::MAX`.
+            ///
+            /// [`alloc_aligned::<1>`]: Self::alloc_aligned
+            #[inline]
+            pub fn alloc_unaligned(&self, size: usize) -> Result<NonNull<[u8]>, AllocError> {
+                self.alloc_aligned::<1>(size)
+            }
+
+            /// Returns the memory lost to alignment padding since the last reset.
+            #[inline]
+            pub fn fragmentation_stats(&self) -> FragmentationStats {
+                self.state().fragmentation.get()
+            }
+
+            /// Returns a coarse map of the used memory with one digit for every `granularity`
+            /// bytes.
+            ///
+            /// See [`RegionMap`] for the format. A `granularity` of zero is treated as one.
+            #[inline]
+            pub fn dump_map(&self, granularity: usize) -> RegionMap {
+                let used = D::view(self.memory, self.current());
+                let used_start = offset_of(self.memory, used.as_non_null_ptr());
+                RegionMap {
+                    len: self.memory.len(),
+                    used_start,
+                    used_end: used_start + used.len(),
+                    granularity: granularity.max(1),
+                }
+            }
+
+            /// Returns if the memory block at `ptr` is owned by this region and was allocated
+            /// last, so [`try_dealloc`] would reclaim its memory.
+            ///
+            /// [`try_dealloc`]: Self::try_dealloc
+            #[inline]
+            pub fn is_last_block(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+                self.owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+                    && D::deallocate(self.memory, self.current(), ptr, layout.size()).is_some()
+            }
+
+            /// Deallocates the memory block at `ptr` and returns if its memory was reclaimed.
+            ///
+            /// In contrast to [`Allocator::deallocate`], which never reclaims memory, the memory block
+            /// allocated last is handed back to the region. Other memory blocks are ignored and
+            /// stay unusable until the region is reset.
+            ///
+            /// # Safety
+            ///
+            /// The same as for [`Allocator::deallocate`].
+            ///
+            /// [`Allocator::deallocate`]: core::alloc::Allocator::deallocate
+            #[track_caller]
+            #[inline]
+            pub unsafe fn try_dealloc(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+                crate::check_deallocate_precondition(ptr, layout);
+                match D::deallocate(self.memory, self.current(), ptr, layout.size()) {
+                    Some(current) => {
+                        self.set_current(current);
+                        true
+                    }
+                    None => false,
+                }
+            }
+
+            #[inline(always)]
+            fn alloc_impl(&self, size: usize, align: usize) -> Result<NonNull<[u8]>, AllocError> {
+                let old = self.current();
+                let (new, current) = D::allocate(self.memory, old, size, align)?;
+                self.set_current(current);
+                let consumed =
+                    D::capacity_left(self.memory, old) - D::capacity_left(self.memory, current);
+                self.record_padding(consumed - size);
+                Ok(new)
+            }
+
+            #[inline]
+            unsafe fn resize(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                let current = D::resize(
+                    self.memory,
+                    self.current(),
+                    ptr,
+                    old_layout.size(),
+                    new_layout.size(),
+                    new_layout.align(),
+                )?;
+                self.set_current(current);
+                Ok(new_layout.size())
+            }
+        }
+
+        impl<D: Direction> PartialEq for $ty<D> {
+            #[inline]
+            fn eq(&self, rhs: &Self) -> bool {
+                same_memory(self.memory, rhs.memory)
+            }
+        }
+
+        impl<D: Direction> Eq for $ty<D> {}
+
+        impl<D: Direction> Hash for $ty<D> {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.memory.hash(state)
+            }
+        }
+
+        impl<D: Direction> fmt::Debug for $ty<D> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct("RawRegion")
+                    .field("memory", &self.memory)
+                    .field("len", &self.memory.len())
+                    .field("current", &self.current())
+                    .finish()
+            }
+        }
+
+        unsafe impl<D: Direction> Allocator for $ty<D> {
+            #[inline]
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.alloc_impl(layout.size(), layout.align())
+            }
+
+            #[inline]
+            unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+
+            #[track_caller]
             unsafe fn grow(
                 &self,
-                _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
             ) -> Result<NonNull<[u8]>, AllocError> {
-                Err(AllocError)
+                let len = self.grow_in_place(ptr, old_layout, new_layout)?;
+                Ok(NonNull::slice_from_raw_parts(ptr, len))
             }
 
+            #[track_caller]
             unsafe fn grow_zeroed(
                 &self,
-                _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
             ) -> Result<NonNull<[u8]>, AllocError> {
-                Err(AllocError)
+                let len = self.grow_in_place_zeroed(ptr, old_layout, new_layout)?;
+                Ok(NonNull::slice_from_raw_parts(ptr, len))
             }
 
+            #[track_caller]
             unsafe fn shrink(
                 &self,
-                _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
             ) -> Result<NonNull<[u8]>, AllocError> {
-                Err(AllocError)
+                let len = self.shrink_in_place(ptr, old_layout, new_layout)?;
+                Ok(NonNull::slice_from_raw_parts(ptr, len))
             }
         }
 
-        unsafe impl AllocateAll for $ty {
+        unsafe impl<D: Direction> AllocateAll for $ty<D> {
             #[inline]
             fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
-                let new = alloc_all_impl(self.memory, self.current())?;
-                self.set_current(new.as_non_null_ptr());
+                let (new, current) = D::alloc_all(self.memory, self.current());
+                self.set_current(current);
                 Ok(new)
             }
 
             #[inline]
             fn deallocate_all(&self) {
-                self.set_current(end(self.memory))
+                self.reset(D::start(self.memory))
             }
 
             #[inline]
@@ -355,20 +1109,83 @@ macro_rules! impl_raw_region {
 
             #[inline]
             fn capacity_left(&self) -> usize {
-                self.current_usize() - self.memory.as_mut_ptr() as usize
+                D::capacity_left(self.memory, self.current())
             }
         }
 
-        impl Owns for $ty {
+        /// Alignment padding is reported as overhead.
+        impl<D: Direction> ReportUsage for $ty<D> {
+            fn usage(&self) -> Usage {
+                Usage::new(
+                    self.capacity(),
+                    self.capacity_left(),
+                    self.fragmentation_stats().padding,
+                )
+            }
+        }
+
+        /// Only the last memory block of a region bumping [`Up`] can be resized in place.
+        unsafe impl<D: Direction> ReallocateInPlace for $ty<D> {
+            #[track_caller]
+            unsafe fn grow_in_place(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                crate::check_grow_precondition(ptr, old_layout, new_layout);
+                self.resize(ptr, old_layout, new_layout)
+            }
+
+            #[track_caller]
+            unsafe fn grow_in_place_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                crate::check_grow_precondition(ptr, old_layout, new_layout);
+                let len = self.resize(ptr, old_layout, new_layout)?;
+                ptr.as_ptr()
+                    .add(old_layout.size())
+                    .write_bytes(0, len - old_layout.size());
+                Ok(len)
+            }
+
+            #[track_caller]
+            unsafe fn shrink_in_place(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<usize, AllocError> {
+                crate::check_shrink_precondition(ptr, old_layout, new_layout);
+                self.resize(ptr, old_layout, new_layout)
+            }
+        }
+
+        impl<D: Direction> Owns for $ty<D> {
             #[inline]
             fn owns(&self, memory: NonNull<[u8]>) -> bool {
-                let ptr = memory.as_mut_ptr() as usize;
-                let current = self.current_usize();
-                ptr >= current && ptr + memory.len() <= end(self.memory).as_ptr() as usize
+                D::owns(self.memory, self.current(), memory)
+            }
+        }
+
+        impl<D: Direction> AllocateMany for $ty<D> {
+            fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+                unsafe { bump_many(self, layout, n) }
+            }
+        }
+
+        impl<D: Direction> Describe for $ty<D> {
+            fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                describe_usage(f, stringify!($ty), self.capacity(), self.capacity_left())
             }
         }
 
-        impl_global_alloc!($ty);
+        impl<D: Direction> Purge for $ty<D> {}
+
+        impl_global_alloc!(impl<D: Direction> $ty<D>);
     };
 }
 
@@ -376,3 +1193,838 @@ impl_raw_region!(RawRegion);
 #[cfg(any(doc, feature = "alloc"))]
 impl_raw_region!(RawSharedRegion);
 impl_raw_region!(RawIntrusiveRegion);
+impl_raw_region!(RawBorrowedRegion);
+
+/// A region allocating from both ends of the same memory block.
+///
+/// This is the non-lifetime version of [`DoubleEndedRegion`].
+///
+/// [`DoubleEndedRegion`]: crate::region::DoubleEndedRegion
+pub struct RawDoubleEndedRegion {
+    memory: NonNull<[u8]>,
+    bottom: Cell<NonNull<u8>>,
+    top: Cell<NonNull<u8>>,
+}
+
+impl RawDoubleEndedRegion {
+    /// Creates a new double ended region from the given memory block.
+    ///
+    /// # Safety
+    ///
+    /// Behavior is undefined if any of the following conditions are violated:
+    ///
+    /// * `memory` must be [valid] for reads and writes for `memory.len()` many bytes.
+    ///
+    /// * `memory` must outlive the region.
+    ///
+    /// * `memory.len()` must be no larger than `isize::MAX`.
+    ///   See the safety documentation of [`pointer::offset`].
+    ///
+    /// For a safe variant use [`DoubleEndedRegion`] instead.
+    ///
+    /// [`DoubleEndedRegion`]: crate::region::DoubleEndedRegion
+    /// [valid]: core::ptr#safety
+    /// [`pointer::offset`]: https://doc.rust-lang.org/std/primitive.pointer.html#method.offset
+    #[inline]
+    pub unsafe fn new(memory: NonNull<[u8]>) -> Self {
+        Self {
+            memory,
+            bottom: Cell::new(Up::start(memory)),
+            top: Cell::new(Down::start(memory)),
+        }
+    }
+
+    /// Returns the allocator handing out memory from the start of the memory block.
+    #[inline]
+    pub fn bottom(&self) -> RegionEnd<'_, Up> {
+        RegionEnd {
+            memory: self.memory,
+            current: &self.bottom,
+            limit: &self.top,
+            _direction: PhantomData,
+        }
+    }
+
+    /// Returns the allocator handing out memory from the end of the memory block.
+    #[inline]
+    pub fn top(&self) -> RegionEnd<'_, Down> {
+        RegionEnd {
+            memory: self.memory,
+            current: &self.top,
+            limit: &self.bottom,
+            _direction: PhantomData,
+        }
+    }
+
+    /// Returns the size of the whole memory block.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Returns the number of bytes left between both ends.
+    #[inline]
+    pub fn capacity_left(&self) -> usize {
+        offset_of(self.memory, self.top.get()) - offset_of(self.memory, self.bottom.get())
+    }
+
+    /// Resets both ends of the region.
+    #[inline]
+    pub fn deallocate_all(&self) {
+        self.bottom.set(Up::start(self.memory));
+        self.top.set(Down::start(self.memory));
+    }
+}
+
+impl ReportUsage for RawDoubleEndedRegion {
+    fn usage(&self) -> Usage {
+        Usage::new(self.capacity(), self.capacity_left(), 0)
+    }
+}
+
+impl PartialEq for RawDoubleEndedRegion {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        same_memory(self.memory, rhs.memory)
+    }
+}
+
+impl Eq for RawDoubleEndedRegion {}
+
+impl Hash for RawDoubleEndedRegion {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.memory.hash(state)
+    }
+}
+
+impl Describe for RawDoubleEndedRegion {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(
+            f,
+            "RawDoubleEndedRegion",
+            self.capacity(),
+            self.capacity_left(),
+        )
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.bottom());
+        visit(&self.top());
+    }
+}
+
+impl Purge for RawDoubleEndedRegion {}
+
+impl fmt::Debug for RawDoubleEndedRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawDoubleEndedRegion")
+            .field("memory", &self.memory)
+            .field("len", &self.memory.len())
+            .field("bottom", &self.bottom.get())
+            .field("top", &self.top.get())
+            .finish()
+    }
+}
+
+/// One end of a [`DoubleEndedRegion`].
+///
+/// The [`bottom`] end bumps [`Up`], the [`top`] end bumps [`Down`]. An allocation fails, when it
+/// would cross the current position of the opposite end. [`AllocateAll::deallocate_all`] only
+/// resets this end.
+///
+/// [`DoubleEndedRegion`]: crate::region::DoubleEndedRegion
+/// [`bottom`]: crate::region::DoubleEndedRegion::bottom
+/// [`top`]: crate::region::DoubleEndedRegion::top
+pub struct RegionEnd<'r, D: Direction> {
+    memory: NonNull<[u8]>,
+    current: &'r Cell<NonNull<u8>>,
+    limit: &'r Cell<NonNull<u8>>,
+    _direction: PhantomData<D>,
+}
+
+impl<D: Direction> RegionEnd<'_, D> {
+    /// Returns if the memory block at `ptr` is owned by this end and was allocated last, so
+    /// [`try_dealloc`] would reclaim its memory.
+    ///
+    /// [`try_dealloc`]: Self::try_dealloc
+    #[inline]
+    pub fn is_last_block(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+            && D::deallocate(self.view(), self.current.get(), ptr, layout.size()).is_some()
+    }
+
+    /// Deallocates the memory block at `ptr` and returns if its memory was reclaimed.
+    ///
+    /// See [`RawRegion::try_dealloc`] for details.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Allocator::deallocate`].
+    #[track_caller]
+    #[inline]
+    pub unsafe fn try_dealloc(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        crate::check_deallocate_precondition(ptr, layout);
+        match D::deallocate(self.view(), self.current.get(), ptr, layout.size()) {
+            Some(current) => {
+                self.current.set(current);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn view(&self) -> NonNull<[u8]> {
+        D::view(self.memory, self.limit.get())
+    }
+
+    #[inline]
+    unsafe fn resize(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        let current = D::resize(
+            self.view(),
+            self.current.get(),
+            ptr,
+            old_layout.size(),
+            new_layout.size(),
+            new_layout.align(),
+        )?;
+        self.current.set(current);
+        Ok(new_layout.size())
+    }
+}
+
+impl<D: Direction> Clone for RegionEnd<'_, D> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D: Direction> Copy for RegionEnd<'_, D> {}
+
+impl<D: Direction> PartialEq for RegionEnd<'_, D> {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        same_memory(self.memory, rhs.memory)
+    }
+}
+
+impl<D: Direction> Eq for RegionEnd<'_, D> {}
+
+impl<D: Direction> Hash for RegionEnd<'_, D> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.memory.hash(state)
+    }
+}
+
+impl<D: Direction + fmt::Debug> fmt::Debug for RegionEnd<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegionEnd")
+            .field("memory", &self.memory)
+            .field("current", &self.current.get())
+            .field("limit", &self.limit.get())
+            .finish()
+    }
+}
+
+unsafe impl<D: Direction> Allocator for RegionEnd<'_, D> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let (new, current) = D::allocate(
+            self.view(),
+            self.current.get(),
+            layout.size(),
+            layout.align(),
+        )?;
+        self.current.set(current);
+        Ok(new)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let len = self.grow_in_place(ptr, old_layout, new_layout)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let len = self.grow_in_place_zeroed(ptr, old_layout, new_layout)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let len = self.shrink_in_place(ptr, old_layout, new_layout)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+}
+
+impl<D: Direction> AllocateMany for RegionEnd<'_, D> {
+    fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+        unsafe { bump_many(self, layout, n) }
+    }
+}
+
+unsafe impl<D: Direction> AllocateAll for RegionEnd<'_, D> {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        let (new, current) = D::alloc_all(self.view(), self.current.get());
+        self.current.set(current);
+        Ok(new)
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.current.set(D::start(self.memory))
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.memory.len()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        D::capacity_left(self.view(), self.current.get())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.current.get() == D::start(self.memory)
+    }
+}
+
+impl<D: Direction> ReportUsage for RegionEnd<'_, D> {
+    fn usage(&self) -> Usage {
+        Usage::new(self.capacity(), self.capacity_left(), 0)
+    }
+}
+
+/// Only the last memory block of the [`bottom`] end can be resized in place.
+///
+/// [`bottom`]: crate::region::DoubleEndedRegion::bottom
+unsafe impl<D: Direction> ReallocateInPlace for RegionEnd<'_, D> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.resize(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let len = self.resize(ptr, old_layout, new_layout)?;
+        ptr.as_ptr()
+            .add(old_layout.size())
+            .write_bytes(0, len - old_layout.size());
+        Ok(len)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.resize(ptr, old_layout, new_layout)
+    }
+}
+
+impl<D: Direction> Owns for RegionEnd<'_, D> {
+    #[inline]
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        D::owns(self.memory, self.current.get(), memory)
+    }
+}
+
+impl<D: Direction> Describe for RegionEnd<'_, D> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(f, "RegionEnd", self.capacity(), self.capacity_left())
+    }
+}
+
+impl<D: Direction> Purge for RegionEnd<'_, D> {}
+
+/// A region for memory shared between processes, which refers to memory blocks by offsets.
+///
+/// The current position is stored as an offset at the start of the memory block, so every
+/// process mapping the memory can attach to the region, regardless of the address the memory is
+/// mapped at. Memory blocks returned by the allocator are only valid in the calling process, but
+/// they can be converted with [`offset_of`] and [`resolve`] to offsets, which are valid in every
+/// process. The position is bumped [`Up`] atomically, so the region may be used concurrently.
+///
+/// Alignments are applied to addresses in the calling process. Shared memory is usually mapped at
+/// page boundaries, so alignments up to the page size result in the same offsets in every process.
+///
+/// This is the non-lifetime version of [`OffsetRegion`].
+///
+/// [`offset_of`]: Self::offset_of
+/// [`resolve`]: Self::resolve
+/// [`OffsetRegion`]: crate::region::OffsetRegion
+#[derive(Clone)]
+#[cfg(target_has_atomic = "ptr")]
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "ptr")))]
+pub struct RawOffsetRegion {
+    memory: NonNull<[u8]>,
+}
+
+// The memory is only accessed through the atomic position and disjoint memory blocks.
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl Send for RawOffsetRegion {}
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl Sync for RawOffsetRegion {}
+
+#[cfg(target_has_atomic = "ptr")]
+impl RawOffsetRegion {
+    /// The offset of the first memory block, as the position is stored in front of it.
+    pub const HEADER_SIZE: usize = core::mem::size_of::<AtomicUsize>();
+
+    /// Creates a new region from the given memory block and initializes the stored position.
+    ///
+    /// # Safety
+    ///
+    /// Behavior is undefined if any of the following conditions are violated:
+    ///
+    /// * `memory` must be [valid] for reads and writes for `memory.len()` many bytes.
+    ///
+    /// * `memory` must outlive the region.
+    ///
+    /// * `memory.len()` must be no larger than `isize::MAX`.
+    ///   See the safety documentation of [`pointer::offset`].
+    ///
+    /// * No other region may be attached to `memory`.
+    ///
+    /// For a safe variant use [`OffsetRegion`] instead.
+    ///
+    /// [`OffsetRegion`]: crate::region::OffsetRegion
+    /// [valid]: core::ptr#safety
+    /// [`pointer::offset`]: https://doc.rust-lang.org/std/primitive.pointer.html#method.offset
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not aligned or not large enough to store the
+    /// position. See [`try_new`] for a fallible variant.
+    ///
+    /// [`try_new`]: Self::try_new
+    #[inline]
+    pub unsafe fn new(memory: NonNull<[u8]>) -> Self {
+        Self::try_new(memory).expect("Could not store the position in region")
+    }
+
+    /// Creates a new region like [`new`], but returns `Err`, when `memory` is not aligned or not
+    /// large enough to store the position.
+    ///
+    /// # Safety
+    ///
+    /// See [`new`].
+    ///
+    /// [`new`]: Self::new
+    ///
+    /// # Errors
+    ///
+    /// See [`try_attach`].
+    ///
+    /// [`try_attach`]: Self::try_attach
+    #[inline]
+    #[track_caller]
+    pub unsafe fn try_new(memory: NonNull<[u8]>) -> Result<Self, ComposeError> {
+        let region = Self::try_attach(memory)?;
+        region
+            .memory
+            .as_mut_ptr()
+            .cast::<AtomicUsize>()
+            .write(AtomicUsize::new(Self::HEADER_SIZE));
+        Ok(region)
+    }
+
+    /// Attaches to a region, which was created with [`new`] on the same memory, e.g. by another
+    /// process.
+    ///
+    /// # Safety
+    ///
+    /// See [`new`]. Instead of not being attached to another region, `memory` must contain a
+    /// region created by [`new`].
+    ///
+    /// [`new`]: Self::new
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not aligned or not large enough to store the
+    /// position. See [`try_attach`] for a fallible variant.
+    ///
+    /// [`try_attach`]: Self::try_attach
+    #[inline]
+    pub unsafe fn attach(memory: NonNull<[u8]>) -> Self {
+        Self::try_attach(memory).expect("Could not store the position in region")
+    }
+
+    /// Attaches to a region like [`attach`], but returns `Err`, when `memory` is not aligned or
+    /// not large enough to store the position.
+    ///
+    /// # Safety
+    ///
+    /// See [`attach`].
+    ///
+    /// [`attach`]: Self::attach
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::LayoutUnsupported`], when `memory` is not aligned for the
+    /// position, and [`ComposeError::Exhausted`], when the position does not fit into `memory`.
+    #[inline]
+    #[track_caller]
+    pub unsafe fn try_attach(memory: NonNull<[u8]>) -> Result<Self, ComposeError> {
+        if !(memory.as_mut_ptr() as usize).is_multiple_of(core::mem::align_of::<AtomicUsize>()) {
+            Err(ComposeError::layout_unsupported("RawOffsetRegion"))
+        } else if memory.len() < Self::HEADER_SIZE {
+            Err(ComposeError::exhausted("RawOffsetRegion"))
+        } else {
+            Ok(Self { memory })
+        }
+    }
+
+    /// Decomposes the region into its memory block and the stored position.
+    ///
+    /// As the position is stored inside of the memory, [`attach`] is sufficient to restore the
+    /// region in this or another process. The position is returned for persisting the region
+    /// outside of the memory, see [`from_raw_parts`].
+    ///
+    /// [`attach`]: Self::attach
+    /// [`from_raw_parts`]: Self::from_raw_parts
+    #[inline]
+    pub fn into_raw_parts(self) -> (NonNull<[u8]>, usize) {
+        let position = self.position().load(Ordering::Relaxed);
+        (self.memory, position)
+    }
+
+    /// Creates a region from a memory block and a position, as returned by [`into_raw_parts`],
+    /// and stores the position in the memory.
+    ///
+    /// # Safety
+    ///
+    /// See [`new`]. Additionally, `position` must be between [`HEADER_SIZE`] and `memory.len()`,
+    /// and the memory blocks allocated before decomposing the region must still be in front of
+    /// `position`.
+    ///
+    /// [`into_raw_parts`]: Self::into_raw_parts
+    /// [`new`]: Self::new
+    /// [`HEADER_SIZE`]: Self::HEADER_SIZE
+    ///
+    /// # Panics
+    ///
+    /// This function panics, when `memory` is not aligned or not large enough to store the
+    /// position.
+    #[inline]
+    pub unsafe fn from_raw_parts(memory: NonNull<[u8]>, position: usize) -> Self {
+        debug_assert!(
+            position >= Self::HEADER_SIZE && position <= memory.len(),
+            "`position` must be between `HEADER_SIZE` and `memory.len()`"
+        );
+        let region = Self::attach(memory);
+        region.position().store(position, Ordering::Relaxed);
+        region
+    }
+
+    #[inline]
+    fn position(&self) -> &AtomicUsize {
+        unsafe { &*self.memory.as_mut_ptr().cast() }
+    }
+
+    /// Returns the offset of `ptr` relative to the start of the memory, or `None`, if `ptr` does
+    /// not point into the memory.
+    #[inline]
+    pub fn offset_of(&self, ptr: NonNull<u8>) -> Option<usize> {
+        let offset = (ptr.as_ptr() as usize).checked_sub(self.memory.as_mut_ptr() as usize)?;
+        if offset <= self.memory.len() {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the pointer at `offset` in the calling process, or `None`, if the offset is not
+    /// inside of the allocated memory.
+    #[inline]
+    pub fn resolve(&self, offset: usize) -> Option<NonNull<u8>> {
+        if offset >= Self::HEADER_SIZE && offset <= self.position().load(Ordering::Relaxed) {
+            Some(at(self.memory, offset))
+        } else {
+            None
+        }
+    }
+
+    /// Returns if the memory block at `ptr` is owned by this region and was allocated last, so
+    /// [`try_dealloc`] would reclaim its memory.
+    ///
+    /// [`try_dealloc`]: Self::try_dealloc
+    #[inline]
+    pub fn is_last_block(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+            && offset_of(self.memory, ptr) + layout.size()
+                == self.position().load(Ordering::Relaxed)
+    }
+
+    /// Deallocates the memory block at `ptr` and returns if its memory was reclaimed.
+    ///
+    /// In contrast to [`Allocator::deallocate`], which never reclaims memory, the memory block allocated
+    /// last is handed back to the region. If another process allocates concurrently, the memory
+    /// block is not the last one anymore and is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Allocator::deallocate`].
+    #[track_caller]
+    #[inline]
+    pub unsafe fn try_dealloc(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        crate::check_deallocate_precondition(ptr, layout);
+        let offset = offset_of(self.memory, ptr);
+        self.position()
+            .compare_exchange(
+                offset + layout.size(),
+                offset,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+
+    /// Returns a consistent view of the current state of the region.
+    ///
+    /// The position is read with a single atomic load, so snapshots can be taken from any thread,
+    /// e.g. for metrics, while other threads or processes allocate.
+    #[inline]
+    pub fn snapshot(&self) -> RegionSnapshot {
+        RegionSnapshot {
+            base: at(self.memory, Self::HEADER_SIZE),
+            len: self.capacity(),
+            current: at(self.memory, self.position().load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Takes a snapshot every `interval` and passes it to `f`, until `f` returns
+    /// [`ControlFlow::Break`].
+    ///
+    /// This blocks the calling thread, so it's usually run on a dedicated thread.
+    ///
+    /// [`ControlFlow::Break`]: core::ops::ControlFlow::Break
+    #[cfg(any(doc, feature = "std"))]
+    #[cfg_attr(doc, doc(cfg(feature = "std")))]
+    pub fn watch<B>(
+        &self,
+        interval: std::time::Duration,
+        mut f: impl FnMut(RegionSnapshot) -> core::ops::ControlFlow<B>,
+    ) -> B {
+        loop {
+            if let core::ops::ControlFlow::Break(result) = f(self.snapshot()) {
+                return result;
+            }
+            std::thread::sleep(interval);
+        }
+    }
+}
+
+/// The state of a region at one point in time, returned by [`RawOffsetRegion::snapshot`].
+///
+/// A snapshot only stores addresses and never dereferences them, so it can be sent to other
+/// threads.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg(target_has_atomic = "ptr")]
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "ptr")))]
+pub struct RegionSnapshot {
+    /// The start of the memory available for allocations.
+    pub base: NonNull<u8>,
+    /// The number of bytes available for allocations in total.
+    pub len: usize,
+    /// The current position of the region.
+    pub current: NonNull<u8>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl Send for RegionSnapshot {}
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl Sync for RegionSnapshot {}
+
+#[cfg(target_has_atomic = "ptr")]
+impl RegionSnapshot {
+    /// Returns the number of allocated bytes, including alignment padding.
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.current.as_ptr() as usize - self.base.as_ptr() as usize
+    }
+
+    /// Returns the number of bytes left for allocations.
+    #[inline]
+    pub fn capacity_left(&self) -> usize {
+        self.len - self.used()
+    }
+
+    /// Returns if nothing was allocated at the time of the snapshot.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.used() == 0
+    }
+
+    /// Returns if no memory was left at the time of the snapshot.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.capacity_left() == 0
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl PartialEq for RawOffsetRegion {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        same_memory(self.memory, rhs.memory)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Eq for RawOffsetRegion {}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Hash for RawOffsetRegion {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.memory.hash(state)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl fmt::Debug for RawOffsetRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawOffsetRegion")
+            .field("memory", &self.memory)
+            .field("len", &self.memory.len())
+            .field("position", &self.position().load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl Allocator for RawOffsetRegion {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let position = self.position();
+        let mut offset = position.load(Ordering::Relaxed);
+        loop {
+            let (new, current) = Up::allocate(
+                self.memory,
+                at(self.memory, offset),
+                layout.size(),
+                layout.align(),
+            )?;
+            match position.compare_exchange_weak(
+                offset,
+                offset_of(self.memory, current),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(new),
+                Err(actual) => offset = actual,
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl AllocateAll for RawOffsetRegion {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        let offset = self.position().swap(self.memory.len(), Ordering::Relaxed);
+        Ok(NonNull::slice_from_raw_parts(
+            at(self.memory, offset),
+            self.memory.len() - offset,
+        ))
+    }
+
+    /// Resets the region for every process attached to it.
+    #[inline]
+    fn deallocate_all(&self) {
+        self.position().store(Self::HEADER_SIZE, Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.memory.len() - Self::HEADER_SIZE
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.memory.len() - self.position().load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl ReportUsage for RawOffsetRegion {
+    fn usage(&self) -> Usage {
+        Usage::new(self.capacity(), self.capacity_left(), 0)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Owns for RawOffsetRegion {
+    #[inline]
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        let position = at(self.memory, self.position().load(Ordering::Relaxed));
+        self.offset_of(memory.as_non_null_ptr())
+            .is_some_and(|offset| offset >= Self::HEADER_SIZE)
+            && Up::owns(self.memory, position, memory)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl AllocateMany for RawOffsetRegion {
+    fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+        unsafe { bump_many(self, layout, n) }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Describe for RawOffsetRegion {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(f, "RawOffsetRegion", self.capacity(), self.capacity_left())
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Purge for RawOffsetRegion {}
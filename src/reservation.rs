@@ -0,0 +1,175 @@
+use crate::Allocator;
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    mem,
+    ptr::NonNull,
+};
+
+/// Extends `Allocator` to allocate memory blocks, whose final size is only known after writing
+/// them.
+///
+/// [`begin_alloc`] reserves a memory block for the largest possible size. After filling it,
+/// [`Reservation::commit`] releases the unused tail by shrinking the memory block. Serializers
+/// and encoders can write directly into the memory block without allocating too much memory
+/// or copying the result.
+///
+/// Regions bumping [`Up`] release the tail in place, if the reservation was the last allocation.
+/// Allocators, which fail to shrink, keep the whole memory block.
+///
+/// [`begin_alloc`]: Self::begin_alloc
+/// [`Up`]: crate::region::Up
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{
+///     region::{Region, Up},
+///     AllocateAll,
+///     BeginAlloc,
+/// };
+/// use core::{alloc::Layout, mem::MaybeUninit};
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let region = Region::<Up>::with_direction(&mut data);
+///
+/// let reservation = region.begin_alloc(Layout::new::<[u8; 32]>())?;
+/// let message = b"variable length";
+/// unsafe {
+///     let ptr = reservation.memory().as_mut_ptr();
+///     ptr.copy_from_nonoverlapping(message.as_ptr(), message.len());
+/// }
+/// let memory = reservation.commit(message.len());
+/// assert_eq!(memory.len(), message.len());
+/// assert_eq!(region.capacity_left(), 64 - message.len());
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub trait BeginAlloc: Allocator {
+    /// Reserves a memory block, which fits `max_layout`.
+    ///
+    /// Dropping the reservation without committing it deallocates the memory block.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the allocator fails to allocate `max_layout`.
+    fn begin_alloc(&self, max_layout: Layout) -> Result<Reservation<'_, Self>, AllocError> {
+        let memory = self.allocate(max_layout)?;
+        Ok(Reservation {
+            alloc: self,
+            memory,
+            layout: max_layout,
+        })
+    }
+}
+
+impl<A: Allocator + ?Sized> BeginAlloc for A {}
+
+/// A memory block reserved with [`BeginAlloc::begin_alloc`].
+#[must_use = "the memory block is deallocated, when the reservation is dropped"]
+pub struct Reservation<'a, A: Allocator + ?Sized> {
+    alloc: &'a A,
+    memory: NonNull<[u8]>,
+    layout: Layout,
+}
+
+impl<A: Allocator + ?Sized> Reservation<'_, A> {
+    /// Returns the reserved memory block.
+    pub fn memory(&self) -> NonNull<[u8]> {
+        self.memory
+    }
+
+    /// Returns the layout, which the memory block was reserved with.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Keeps the first `used_size` bytes of the memory block and releases the rest.
+    ///
+    /// `used_size` is clamped to the size of the reserved layout, so committing more than was
+    /// reserved keeps the whole memory block.
+    ///
+    /// The returned memory block is owned by the caller. It has to be deallocated with a layout
+    /// of its length and the alignment of the reservation, as the tail may not have been
+    /// released.
+    pub fn commit(self, used_size: usize) -> NonNull<[u8]> {
+        let used_size = used_size.min(self.layout.size());
+        let memory = self.memory;
+        let layout = self.layout;
+        let alloc = self.alloc;
+        mem::forget(self);
+
+        let used = unsafe { Layout::from_size_align_unchecked(used_size, layout.align()) };
+        unsafe { alloc.shrink(memory.as_non_null_ptr(), layout, used) }.unwrap_or(memory)
+    }
+}
+
+impl<A: Allocator + ?Sized> Drop for Reservation<'_, A> {
+    fn drop(&mut self) {
+        unsafe {
+            self.alloc
+                .deallocate(self.memory.as_non_null_ptr(), self.layout)
+        }
+    }
+}
+
+impl<A: Allocator + ?Sized> fmt::Debug for Reservation<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reservation")
+            .field("memory", &self.memory)
+            .field("layout", &self.layout)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BeginAlloc;
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        AllocateAll,
+        Allocator,
+    };
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn commit() {
+        let mut data = crate::aligned_buffer!(64, 4, 0);
+        let region = Region::<Up>::with_direction(&mut data);
+        let max = Layout::new::<[u32; 8]>();
+
+        let reservation = region.begin_alloc(max).expect("Could not reserve 32 bytes");
+        assert_eq!(region.capacity_left(), 32);
+        let memory = reservation.commit(12);
+        assert_eq!(memory.len(), 12);
+        assert_eq!(region.capacity_left(), 52);
+
+        let alloc = tracker(Global);
+        drop(alloc.begin_alloc(max).expect("Could not reserve 32 bytes"));
+        let memory = alloc
+            .begin_alloc(max)
+            .expect("Could not reserve 32 bytes")
+            .commit(0);
+        unsafe {
+            alloc.deallocate(
+                memory.as_non_null_ptr(),
+                Layout::from_size_align(memory.len(), max.align()).unwrap(),
+            )
+        };
+    }
+
+    #[test]
+    fn commit_too_much() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let region = Region::new(&mut data);
+        let memory = region
+            .begin_alloc(Layout::new::<[u8; 32]>())
+            .expect("Could not reserve 32 bytes")
+            .commit(33);
+        assert_eq!(memory.len(), 32);
+        assert_eq!(region.capacity_left(), 32);
+    }
+}
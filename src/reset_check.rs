@@ -0,0 +1,386 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// Panics, if the underlying allocator is reset while memory blocks are still in use.
+///
+/// Resetting a region with [`deallocate_all`] invalidates every memory block at once, even if a
+/// collection still references one of them. `ResetCheck` hands out memory blocks wrapped into an
+/// [`AllocationGuard`] with [`alloc_guarded`] and counts the guards, which are alive. Calling
+/// [`deallocate_all`] while any guard is alive panics. As a guard borrows the allocator, it cannot
+/// outlive it, but a guard, which was leaked with [`mem::forget`], makes dropping the allocator
+/// panic. With the `std` feature, this check is skipped while the thread is already panicking, so
+/// unwinding doesn't abort the process.
+///
+/// This is meant as a debugging aid. Memory blocks allocated with [`Allocator`] methods are not
+/// tracked.
+///
+/// [`alloc_guarded`]: Self::alloc_guarded
+/// [`deallocate_all`]: AllocateAll::deallocate_all
+/// [`mem::forget`]: core::mem::forget
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, AllocateAll, ResetCheck};
+/// use core::{alloc::Layout, mem::MaybeUninit};
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let alloc = ResetCheck::new(Region::new(&mut data));
+///
+/// let node = alloc.alloc_guarded(Layout::new::<u64>())?;
+/// // Panics, as `node` is still in use
+/// alloc.deallocate_all();
+/// # drop(node);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct ResetCheck<A> {
+    /// The allocator, whose resets are checked.
+    pub alloc: A,
+    guards: Cell<usize>,
+}
+
+impl<A> ResetCheck<A> {
+    /// Tracks the memory blocks handed out by `alloc` with [`alloc_guarded`].
+    ///
+    /// [`alloc_guarded`]: Self::alloc_guarded
+    pub const fn new(alloc: A) -> Self {
+        Self {
+            alloc,
+            guards: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of [`AllocationGuard`]s, which are alive.
+    pub fn num_guards(&self) -> usize {
+        self.guards.get()
+    }
+
+    #[track_caller]
+    fn check_no_guards(&self, operation: &str) {
+        let guards = self.guards.get();
+        assert!(
+            guards == 0,
+            "{} while {} memory blocks are still in use",
+            operation,
+            guards
+        );
+    }
+}
+
+impl<A: Allocator> ResetCheck<A> {
+    /// Allocates a memory block, which is deallocated, when the returned guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, if the underlying allocator fails to allocate `layout`.
+    pub fn alloc_guarded(&self, layout: Layout) -> Result<AllocationGuard<'_, A>, AllocError> {
+        let memory = self.alloc.allocate(layout)?;
+        self.guards.set(self.guards.get() + 1);
+        Ok(AllocationGuard {
+            owner: self,
+            memory,
+            layout,
+        })
+    }
+}
+
+impl<A> Drop for ResetCheck<A> {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            return;
+        }
+        self.check_no_guards("Dropped the allocator")
+    }
+}
+
+/// A memory block allocated with [`ResetCheck::alloc_guarded`].
+///
+/// The memory block is deallocated, when the guard is dropped.
+#[must_use = "the memory block is deallocated, when the guard is dropped"]
+pub struct AllocationGuard<'a, A: Allocator> {
+    owner: &'a ResetCheck<A>,
+    memory: NonNull<[u8]>,
+    layout: Layout,
+}
+
+impl<A: Allocator> AllocationGuard<'_, A> {
+    /// Returns the guarded memory block.
+    pub fn memory(&self) -> NonNull<[u8]> {
+        self.memory
+    }
+
+    /// Returns the layout, which the memory block was allocated with.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+impl<A: Allocator> Drop for AllocationGuard<'_, A> {
+    fn drop(&mut self) {
+        unsafe {
+            self.owner
+                .alloc
+                .deallocate(self.memory.as_non_null_ptr(), self.layout)
+        };
+        self.owner.guards.set(self.owner.guards.get() - 1);
+    }
+}
+
+impl<A: Allocator> fmt::Debug for AllocationGuard<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllocationGuard")
+            .field("memory", &self.memory)
+            .field("layout", &self.layout)
+            .finish()
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for ResetCheck<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_zeroed(layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.alloc.grow(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.alloc.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+unsafe impl<A: AllocateAll> AllocateAll for ResetCheck<A> {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all()
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all_zeroed()
+    }
+
+    /// Deallocates all memory blocks of the underlying allocator.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if any [`AllocationGuard`] is alive.
+    #[track_caller]
+    fn deallocate_all(&self) {
+        self.check_no_guards("Deallocated all memory");
+        self.alloc.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.alloc.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.alloc.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.alloc.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace> ReallocateInPlace for ResetCheck<A> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.alloc.grow_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.alloc.grow_in_place_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.alloc.shrink_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for ResetCheck<A> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns> Owns for ResetCheck<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe> Describe for ResetCheck<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ResetCheck({} guards)", self.guards.get())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge> Purge for ResetCheck<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResetCheck;
+    use crate::{region::Region, AllocateAll};
+    use core::{
+        alloc::Layout,
+        mem::{self, MaybeUninit},
+    };
+
+    #[test]
+    fn guards() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = ResetCheck::new(Region::new(&mut data));
+
+        let first = alloc
+            .alloc_guarded(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        let second = alloc
+            .alloc_guarded(Layout::new::<u32>())
+            .expect("Could not allocate 4 bytes");
+        assert_eq!(alloc.num_guards(), 2);
+        assert_eq!(second.memory().len(), 4);
+
+        drop((first, second));
+        assert_eq!(alloc.num_guards(), 0);
+        alloc.deallocate_all();
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Deallocated all memory while 1 memory blocks are still in use")]
+    fn reset_with_guard() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = ResetCheck::new(Region::new(&mut data));
+        let _guard = alloc
+            .alloc_guarded(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+        alloc.deallocate_all();
+    }
+
+    #[test]
+    #[should_panic(expected = "Dropped the allocator while 1 memory blocks are still in use")]
+    fn forgotten_guard() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = ResetCheck::new(Region::new(&mut data));
+        mem::forget(
+            alloc
+                .alloc_guarded(Layout::new::<u64>())
+                .expect("Could not allocate 8 bytes"),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(expected = "Unwinding with a forgotten guard")]
+    fn unwind_with_forgotten_guard() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = ResetCheck::new(Region::new(&mut data));
+        mem::forget(
+            alloc
+                .alloc_guarded(Layout::new::<u64>())
+                .expect("Could not allocate 8 bytes"),
+        );
+        panic!("Unwinding with a forgotten guard");
+    }
+}
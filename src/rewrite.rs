@@ -0,0 +1,269 @@
+use crate::{
+    helper::AllocInit,
+    Allocator,
+    Describe,
+    Middleware,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// Rewrites the layouts of requests with the provided [`Middleware`] before forwarding them to
+/// the underlying allocator.
+///
+/// This generalizes allocators like [`Chunk`] and [`Canonicalize`] to user-defined layout
+/// policies. Deallocations and reallocations pass the mapped layout to the underlying allocator,
+/// so it sees consistent layouts for every memory block. If the middleware maps a requested
+/// layout to one, which doesn't fit it, the request fails.
+///
+/// [`Chunk`]: crate::Chunk
+/// [`Canonicalize`]: crate::Canonicalize
+///
+/// # Examples
+///
+/// Round up every size to a power of two, so the memory blocks fall into few size classes:
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{Middleware, Rewrite};
+/// use std::alloc::{AllocError, Allocator, Layout, System};
+///
+/// struct PowerOfTwo;
+///
+/// unsafe impl Middleware for PowerOfTwo {
+///     fn map_layout(&self, layout: Layout) -> Result<Layout, AllocError> {
+///         let size = layout
+///             .size()
+///             .checked_next_power_of_two()
+///             .ok_or(AllocError)?;
+///         Layout::from_size_align(size, layout.align()).map_err(|_| AllocError)
+///     }
+/// }
+///
+/// let alloc = Rewrite {
+///     alloc: System,
+///     middleware: PowerOfTwo,
+/// };
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 20]>())?;
+/// assert_eq!(memory.len(), 32);
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 20]>()) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rewrite<A, M> {
+    pub alloc: A,
+    pub middleware: M,
+}
+
+impl<A: Allocator, M: Middleware> Rewrite<A, M> {
+    fn map_layout(&self, layout: Layout) -> Result<Layout, AllocError> {
+        let mapped = self.middleware.map_layout(layout)?;
+        if mapped.size() < layout.size() || mapped.align() < layout.align() {
+            return Err(AllocError);
+        }
+        Ok(mapped)
+    }
+
+    /// Grows or shrinks the memory block depending on the mapped layouts, as the mapping doesn't
+    /// have to preserve the order of sizes.
+    unsafe fn resize(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let old_mapped = self.map_layout(old_layout)?;
+        let new_mapped = self.map_layout(new_layout)?;
+        let result = if new_mapped.size() >= old_mapped.size() {
+            match init {
+                AllocInit::Uninitialized => self.alloc.grow(ptr, old_mapped, new_mapped),
+                AllocInit::Zeroed => {
+                    // The underlying allocator only zeroes behind the mapped size
+                    init.init_offset(
+                        NonNull::slice_from_raw_parts(ptr, old_mapped.size()),
+                        old_layout.size(),
+                    );
+                    self.alloc.grow_zeroed(ptr, old_mapped, new_mapped)
+                }
+            }
+        } else {
+            self.alloc.shrink(ptr, old_mapped, new_mapped)
+        };
+        self.middleware.map_result(new_layout, new_mapped, result)
+    }
+}
+
+unsafe impl<A: Allocator, M: Middleware> Allocator for Rewrite<A, M> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let mapped = self.map_layout(layout)?;
+        self.middleware
+            .map_result(layout, mapped, self.alloc.allocate(mapped))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let mapped = self.map_layout(layout)?;
+        self.middleware
+            .map_result(layout, mapped, self.alloc.allocate_zeroed(mapped))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if let Ok(layout) = self.map_layout(layout) {
+            self.alloc.deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.resize(ptr, old_layout, new_layout, AllocInit::Uninitialized)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.resize(ptr, old_layout, new_layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.resize(ptr, old_layout, new_layout, AllocInit::Uninitialized)
+    }
+}
+
+impl<A: ReportUsage, M> ReportUsage for Rewrite<A, M> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns, M> Owns for Rewrite<A, M> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe, M> Describe for Rewrite<A, M> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Rewrite")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, M> Purge for Rewrite<A, M> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rewrite;
+    use crate::{helper::tracker, Allocator, Middleware};
+    use alloc::alloc::Global;
+    use core::{
+        alloc::{AllocError, Layout},
+        ptr::NonNull,
+    };
+
+    /// Requests at least 16 bytes, but hands out the requested size only.
+    struct MinSize;
+
+    unsafe impl Middleware for MinSize {
+        fn map_layout(&self, layout: Layout) -> Result<Layout, AllocError> {
+            Layout::from_size_align(layout.size().max(16), layout.align()).map_err(|_| AllocError)
+        }
+
+        fn map_result(
+            &self,
+            layout: Layout,
+            _mapped: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            result.map(|memory| {
+                NonNull::slice_from_raw_parts(memory.as_non_null_ptr(), layout.size())
+            })
+        }
+    }
+
+    struct Shrinking;
+
+    unsafe impl Middleware for Shrinking {
+        fn map_layout(&self, _layout: Layout) -> Result<Layout, AllocError> {
+            Ok(Layout::new::<u8>())
+        }
+    }
+
+    #[test]
+    fn rewrite() {
+        let alloc = Rewrite {
+            alloc: tracker(Global),
+            middleware: MinSize,
+        };
+        let layout = Layout::new::<[u8; 4]>();
+        let grown = Layout::new::<[u8; 8]>();
+        let large = Layout::new::<[u8; 32]>();
+        unsafe {
+            let memory = alloc.allocate(layout).expect("Could not allocate 4 bytes");
+            assert_eq!(memory.len(), 4);
+            memory.as_non_null_ptr().write_bytes(0xFF, 16);
+
+            // Both layouts map to 16 bytes, so the slack has to be zeroed
+            let memory = alloc
+                .grow_zeroed(memory.as_non_null_ptr(), layout, grown)
+                .expect("Could not grow to 8 bytes");
+            assert_eq!(memory.len(), 8);
+            assert!(memory.as_ref()[4..].iter().all(|&b| b == 0));
+
+            let memory = alloc
+                .grow(memory.as_non_null_ptr(), grown, large)
+                .expect("Could not grow to 32 bytes");
+            let memory = alloc
+                .shrink(memory.as_non_null_ptr(), large, layout)
+                .expect("Could not shrink to 4 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), layout);
+        }
+
+        let alloc = Rewrite {
+            alloc: tracker(Global),
+            middleware: Shrinking,
+        };
+        alloc
+            .allocate(Layout::new::<u32>())
+            .expect_err("Allocated with a layout not fitting the request");
+    }
+}
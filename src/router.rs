@@ -0,0 +1,406 @@
+use crate::{
+    helper::{grow_fallback, shrink_fallback, AllocInit},
+    Allocator,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// The allocators a [`RoutePolicy`] selects for a request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Route {
+    /// Only the primary allocator is used.
+    Primary,
+    /// Only the secondary allocator is used.
+    Secondary,
+    /// The primary allocator is tried first, and the secondary allocator, if that fails.
+    Both,
+}
+
+/// Decides which allocator of a [`Router`] serves a request.
+///
+/// Every `Fn(Layout) -> Route` implements `RoutePolicy`, so simple policies don't need a type on
+/// their own.
+pub trait RoutePolicy {
+    /// Returns the allocators for a request with `layout`.
+    ///
+    /// For reallocations, `layout` is the new layout.
+    fn route(&self, layout: Layout) -> Route;
+}
+
+impl<F: Fn(Layout) -> Route> RoutePolicy for F {
+    #[inline]
+    fn route(&self, layout: Layout) -> Route {
+        self(layout)
+    }
+}
+
+/// Dispatches requests to one of two allocators as decided by a [`RoutePolicy`].
+///
+/// `Router` generalizes [`Fallback`], which always routes to [`Route::Both`], and [`Segregate`],
+/// which routes by size. Custom criteria like alignment, the current thread, or a phase flag only
+/// require a policy instead of a whole new allocator.
+///
+/// Memory blocks are returned to the allocator, which owns them, so the policy may change its
+/// decision while memory blocks are allocated. When growing or shrinking a memory block, it is
+/// moved to the other allocator if the owning one is not selected for the new layout, or if it
+/// fails and the other one is selected as well.
+///
+/// [`Fallback`]: crate::Fallback
+/// [`Segregate`]: crate::Segregate
+///
+/// # Examples
+///
+/// Serve over-aligned requests from the system allocator:
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{region::Region, Owns, Route, Router};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+/// use std::alloc::System;
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let alloc = Router {
+///     primary: Region::new(&mut data),
+///     secondary: System,
+///     policy: |layout: Layout| {
+///         if layout.align() > 8 {
+///             Route::Secondary
+///         } else {
+///             Route::Both
+///         }
+///     },
+/// };
+///
+/// let memory = alloc.allocate(Layout::from_size_align(8, 8).unwrap())?;
+/// assert!(alloc.primary.owns(memory));
+/// let layout = Layout::from_size_align(8, 16).unwrap();
+/// let memory = alloc.allocate(layout)?;
+/// assert!(!alloc.primary.owns(memory));
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Router<Primary, Secondary, P> {
+    pub primary: Primary,
+    pub secondary: Secondary,
+    pub policy: P,
+}
+
+fn allocate<A: Allocator>(
+    alloc: &A,
+    layout: Layout,
+    init: AllocInit,
+) -> Result<NonNull<[u8]>, AllocError> {
+    match init {
+        AllocInit::Uninitialized => alloc.allocate(layout),
+        AllocInit::Zeroed => alloc.allocate_zeroed(layout),
+    }
+}
+
+unsafe fn grow<A: Allocator>(
+    alloc: &A,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+    init: AllocInit,
+) -> Result<NonNull<[u8]>, AllocError> {
+    match init {
+        AllocInit::Uninitialized => alloc.grow(ptr, old_layout, new_layout),
+        AllocInit::Zeroed => alloc.grow_zeroed(ptr, old_layout, new_layout),
+    }
+}
+
+/// Resizes a memory block owned by `owner`, if `keep` is set, and moves it to `other` otherwise
+/// or if resizing fails and `moving` is set.
+unsafe fn realloc<A1: Allocator, A2: Allocator>(
+    owner: &A1,
+    other: &A2,
+    keep: bool,
+    moving: bool,
+    resize: impl FnOnce(&A1) -> Result<NonNull<[u8]>, AllocError>,
+    relocate: impl FnOnce(&A1, &A2) -> Result<NonNull<[u8]>, AllocError>,
+) -> Result<NonNull<[u8]>, AllocError> {
+    if keep {
+        match resize(owner) {
+            Ok(memory) => return Ok(memory),
+            Err(_) if !moving => return Err(AllocError),
+            Err(_) => {}
+        }
+    }
+    relocate(owner, other)
+}
+
+impl<Primary, Secondary, P> Router<Primary, Secondary, P>
+where
+    Primary: Allocator + Owns,
+    Secondary: Allocator,
+    P: RoutePolicy,
+{
+    fn allocate_impl(&self, layout: Layout, init: AllocInit) -> Result<NonNull<[u8]>, AllocError> {
+        match self.policy.route(layout) {
+            Route::Primary => allocate(&self.primary, layout, init),
+            Route::Secondary => allocate(&self.secondary, layout, init),
+            Route::Both => match allocate(&self.primary, layout, init) {
+                primary @ Ok(_) => primary,
+                Err(_) => allocate(&self.secondary, layout, init),
+            },
+        }
+    }
+
+    fn owned_by_primary(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.primary
+            .owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let route = self.policy.route(new_layout);
+        if self.owned_by_primary(ptr, old_layout) {
+            realloc(
+                &self.primary,
+                &self.secondary,
+                route != Route::Secondary,
+                route != Route::Primary,
+                |primary| grow(primary, ptr, old_layout, new_layout, init),
+                |primary, secondary| {
+                    grow_fallback(primary, secondary, ptr, old_layout, new_layout, init)
+                },
+            )
+        } else {
+            realloc(
+                &self.secondary,
+                &self.primary,
+                route != Route::Primary,
+                route != Route::Secondary,
+                |secondary| grow(secondary, ptr, old_layout, new_layout, init),
+                |secondary, primary| {
+                    grow_fallback(secondary, primary, ptr, old_layout, new_layout, init)
+                },
+            )
+        }
+    }
+}
+
+unsafe impl<Primary, Secondary, P> Allocator for Router<Primary, Secondary, P>
+where
+    Primary: Allocator + Owns,
+    Secondary: Allocator,
+    P: RoutePolicy,
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_impl(layout, AllocInit::Uninitialized)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_impl(layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if self.owned_by_primary(ptr, layout) {
+            self.primary.deallocate(ptr, layout)
+        } else {
+            self.secondary.deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Uninitialized)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        let route = self.policy.route(new_layout);
+        if self.owned_by_primary(ptr, old_layout) {
+            realloc(
+                &self.primary,
+                &self.secondary,
+                route != Route::Secondary,
+                route != Route::Primary,
+                |primary| primary.shrink(ptr, old_layout, new_layout),
+                |primary, secondary| {
+                    shrink_fallback(primary, secondary, ptr, old_layout, new_layout)
+                },
+            )
+        } else {
+            realloc(
+                &self.secondary,
+                &self.primary,
+                route != Route::Primary,
+                route != Route::Secondary,
+                |secondary| secondary.shrink(ptr, old_layout, new_layout),
+                |secondary, primary| {
+                    shrink_fallback(secondary, primary, ptr, old_layout, new_layout)
+                },
+            )
+        }
+    }
+}
+
+impl<Primary, Secondary, P> Owns for Router<Primary, Secondary, P>
+where
+    Primary: Owns,
+    Secondary: Owns,
+{
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.primary.owns(memory) || self.secondary.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.primary.owns_ptr(ptr) || self.secondary.owns_ptr(ptr)
+    }
+}
+
+impl<Primary, Secondary, P> ReportUsage for Router<Primary, Secondary, P>
+where
+    Primary: ReportUsage,
+    Secondary: ReportUsage,
+{
+    fn usage(&self) -> Usage {
+        self.primary.usage() + self.secondary.usage()
+    }
+}
+
+impl<Primary: Describe, Secondary: Describe, P> Describe for Router<Primary, Secondary, P> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Router")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.primary);
+        visit(&self.secondary);
+    }
+}
+
+impl<Primary: Purge, Secondary: Purge, P> Purge for Router<Primary, Secondary, P> {
+    fn purge(&self, level: PurgeLevel) {
+        self.primary.purge(level);
+        self.secondary.purge(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Route, Router};
+    use crate::{helper, region::Region, Allocator, Owns};
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, cell::Cell, mem::MaybeUninit};
+
+    #[test]
+    fn phase_flag() {
+        let mut data = [MaybeUninit::new(0); 64];
+        let startup = Cell::new(true);
+        let alloc = Router {
+            primary: helper::tracker(Region::new(&mut data)),
+            secondary: helper::tracker(Global),
+            policy: |_| {
+                if startup.get() {
+                    Route::Primary
+                } else {
+                    Route::Secondary
+                }
+            },
+        };
+
+        let layout = Layout::new::<[u8; 16]>();
+        let memory = alloc.allocate(layout).expect("Could not allocate 16 bytes");
+        assert!(alloc.primary.owns(memory));
+        alloc
+            .allocate(Layout::new::<[u8; 64]>())
+            .expect_err("Fell back to the secondary allocator");
+
+        startup.set(false);
+        let grown = Layout::new::<[u8; 32]>();
+        let memory = unsafe {
+            alloc
+                .grow(memory.as_non_null_ptr(), layout, grown)
+                .expect("Could not grow to 32 bytes")
+        };
+        assert!(!alloc.primary.owns(memory));
+        let memory = unsafe {
+            alloc
+                .shrink(memory.as_non_null_ptr(), grown, layout)
+                .expect("Could not shrink to 16 bytes")
+        };
+        assert!(!alloc.primary.owns(memory));
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+    }
+
+    #[test]
+    fn both() {
+        let mut data = [MaybeUninit::new(0); 32];
+        let alloc = Router {
+            primary: helper::tracker(Region::new(&mut data)),
+            secondary: helper::tracker(Global),
+            policy: |_| Route::Both,
+        };
+
+        let small = alloc
+            .allocate_zeroed(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        let large = alloc
+            .allocate(Layout::new::<[u8; 64]>())
+            .expect("Could not allocate 64 bytes");
+        assert!(alloc.primary.owns(small));
+        assert!(!alloc.primary.owns(large));
+
+        let memory = unsafe {
+            alloc
+                .grow_zeroed(
+                    small.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::new::<[u8; 128]>(),
+                )
+                .expect("Could not grow to 128 bytes")
+        };
+        assert!(!alloc.primary.owns(memory));
+        assert!(unsafe { memory.as_ref() }.iter().all(|&b| b == 0));
+        unsafe {
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 128]>());
+            alloc.deallocate(large.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+        }
+    }
+}
@@ -1,168 +1,271 @@
 use crate::{
     helper::{grow_fallback, shrink_fallback, AllocInit},
-    AllocAll,
+    AllocateAll,
+    Allocator,
+    Describe,
     Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
 };
 use core::{
-    alloc::{AllocErr, AllocRef, Layout},
+    alloc::{AllocError, Layout},
     cmp,
+    fmt,
     ptr::NonNull,
 };
 
-/// Dispatches calls to `AllocRef` between two allocators depending on the size allocated.
+/// Dispatches calls to `Allocator` between two allocators depending on the size allocated.
 ///
 /// All allocations smaller than or equal to `threshold` will be dispatched to `Small`. The others
 /// will go to `Large`.
-#[derive(Debug, Copy, Clone)]
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, Owns, Segregate};
+/// use std::{
+///     alloc::{Allocator, Layout, System},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::new(0); 64];
+/// let alloc: Segregate<_, _, 16> = Segregate {
+///     small: Region::new(&mut data),
+///     large: System,
+/// };
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 8]>())?;
+/// assert!(alloc.small.owns(memory));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Segregate<Small, Large, const THRESHOLD: usize> {
     pub small: Small,
     pub large: Large,
 }
 
 impl<Small, Large, const THRESHOLD: usize> Segregate<Small, Large, THRESHOLD> {
+    /// Creates a new segregator from the given allocators for small and large requests.
+    pub const fn new(small: Small, large: Large) -> Self {
+        Self { small, large }
+    }
+
+    /// Replaces the allocator for requests up to `THRESHOLD` bytes.
+    pub fn with_small<S>(self, small: S) -> Segregate<S, Large, THRESHOLD> {
+        Segregate {
+            small,
+            large: self.large,
+        }
+    }
+
+    /// Replaces the allocator for requests larger than `THRESHOLD` bytes.
+    pub fn with_large<L>(self, large: L) -> Segregate<Small, L, THRESHOLD> {
+        Segregate {
+            small: self.small,
+            large,
+        }
+    }
+
     fn clamped(ptr: NonNull<[u8]>) -> NonNull<[u8]> {
         NonNull::slice_from_raw_parts(ptr.as_non_null_ptr(), cmp::min(ptr.len(), THRESHOLD))
     }
 }
 
-unsafe impl<Small, Large, const THRESHOLD: usize> AllocRef for Segregate<Small, Large, THRESHOLD>
+unsafe impl<Small, Large, const THRESHOLD: usize> Allocator for Segregate<Small, Large, THRESHOLD>
 where
-    Small: AllocRef,
-    Large: AllocRef,
+    Small: Allocator,
+    Large: Allocator,
 {
-    fn alloc(&mut self, layout: Layout) -> Result<NonNull<[u8]>, AllocErr> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         if layout.size() <= THRESHOLD {
-            let memory = self.small.alloc(layout)?;
+            let memory = self.small.allocate(layout)?;
             Ok(Self::clamped(memory))
         } else {
-            self.large.alloc(layout)
+            self.large.allocate(layout)
         }
     }
 
-    fn alloc_zeroed(&mut self, layout: Layout) -> Result<NonNull<[u8]>, AllocErr> {
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         if layout.size() <= THRESHOLD {
-            let memory = self.small.alloc_zeroed(layout)?;
+            let memory = self.small.allocate_zeroed(layout)?;
             Ok(Self::clamped(memory))
         } else {
-            self.large.alloc_zeroed(layout)
+            self.large.allocate_zeroed(layout)
         }
     }
 
-    unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
         if layout.size() <= THRESHOLD {
-            self.small.dealloc(ptr, layout)
+            self.small.deallocate(ptr, layout)
         } else {
-            self.large.dealloc(ptr, layout)
+            self.large.deallocate(ptr, layout)
         }
     }
 
+    #[track_caller]
     unsafe fn grow(
-        &mut self,
+        &self,
         ptr: NonNull<u8>,
-        layout: Layout,
-        new_size: usize,
-    ) -> Result<NonNull<[u8]>, AllocErr> {
-        if layout.size() <= THRESHOLD {
-            if new_size > THRESHOLD {
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if old_layout.size() <= THRESHOLD {
+            if new_layout.size() > THRESHOLD {
                 grow_fallback(
-                    &mut self.small,
-                    &mut self.large,
+                    &self.small,
+                    &self.large,
                     ptr,
-                    layout,
-                    new_size,
+                    old_layout,
+                    new_layout,
                     AllocInit::Uninitialized,
                 )
             } else {
-                let memory = self.small.grow(ptr, layout, new_size)?;
+                let memory = self.small.grow(ptr, old_layout, new_layout)?;
                 Ok(Self::clamped(memory))
             }
         } else {
-            self.large.grow(ptr, layout, new_size)
+            self.large.grow(ptr, old_layout, new_layout)
         }
     }
 
+    #[track_caller]
     unsafe fn grow_zeroed(
-        &mut self,
+        &self,
         ptr: NonNull<u8>,
-        layout: Layout,
-        new_size: usize,
-    ) -> Result<NonNull<[u8]>, AllocErr> {
-        if layout.size() <= THRESHOLD {
-            if new_size > THRESHOLD {
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if old_layout.size() <= THRESHOLD {
+            if new_layout.size() > THRESHOLD {
                 grow_fallback(
-                    &mut self.small,
-                    &mut self.large,
+                    &self.small,
+                    &self.large,
                     ptr,
-                    layout,
-                    new_size,
+                    old_layout,
+                    new_layout,
                     AllocInit::Zeroed,
                 )
             } else {
-                let memory = self.small.grow_zeroed(ptr, layout, new_size)?;
+                let memory = self.small.grow_zeroed(ptr, old_layout, new_layout)?;
                 Ok(Self::clamped(memory))
             }
         } else {
-            self.large.grow_zeroed(ptr, layout, new_size)
+            self.large.grow_zeroed(ptr, old_layout, new_layout)
         }
     }
 
+    #[track_caller]
     unsafe fn shrink(
-        &mut self,
+        &self,
         ptr: NonNull<u8>,
-        layout: Layout,
-        new_size: usize,
-    ) -> Result<NonNull<[u8]>, AllocErr> {
-        if layout.size() <= THRESHOLD {
-            let memory = self.small.shrink(ptr, layout, new_size)?;
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if old_layout.size() <= THRESHOLD {
+            let memory = self.small.shrink(ptr, old_layout, new_layout)?;
             Ok(Self::clamped(memory))
-        } else if new_size <= THRESHOLD {
+        } else if new_layout.size() <= THRESHOLD {
             // Move ownership to `self.small`
-            let memory = shrink_fallback(&mut self.large, &mut self.small, ptr, layout, new_size)?;
+            let memory = shrink_fallback(&self.large, &self.small, ptr, old_layout, new_layout)?;
             Ok(Self::clamped(memory))
         } else {
-            self.large.shrink(ptr, layout, new_size)
+            self.large.shrink(ptr, old_layout, new_layout)
         }
     }
 }
 
-unsafe impl<Small, Large, const THRESHOLD: usize> AllocAll for Segregate<Small, Large, THRESHOLD>
+impl<Small, Large, const THRESHOLD: usize> Segregate<Small, Large, THRESHOLD>
 where
-    Small: AllocAll,
-    Large: AllocAll,
+    Small: AllocateAll,
+    Large: Allocator + AllocateAll,
 {
-    fn alloc_all(&mut self, layout: Layout) -> Result<NonNull<[u8]>, AllocErr> {
-        if layout.size() <= THRESHOLD {
-            let memory = self.small.alloc_all(layout)?;
-            Ok(Self::clamped(memory))
+    fn allocate_all_impl(
+        &self,
+        small: impl FnOnce(&Small) -> Result<NonNull<[u8]>, AllocError>,
+        large: impl FnOnce(&Large) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.small.is_full() {
+            return small(&self.small).map(Self::clamped);
+        }
+        let memory = large(&self.large)?;
+        if memory.len() > THRESHOLD {
+            Ok(memory)
         } else {
-            self.large.alloc_all(layout)
+            // The block would be routed to `small` on deallocation
+            unsafe {
+                self.large.deallocate(
+                    memory.as_non_null_ptr(),
+                    Layout::from_size_align_unchecked(memory.len(), 1),
+                )
+            };
+            Err(AllocError)
         }
     }
+}
 
-    fn alloc_all_zeroed(&mut self, layout: Layout) -> Result<NonNull<[u8]>, AllocErr> {
-        if layout.size() <= THRESHOLD {
-            let memory = self.small.alloc_all_zeroed(layout)?;
-            Ok(Self::clamped(memory))
-        } else {
-            self.large.alloc_all(layout)
-        }
+/// Both allocators are treated as one pool: `capacity` and `capacity_left` are the sums of both,
+/// and `deallocate_all` resets both.
+///
+/// `allocate_all` drains `Small` first and clamps the block to `THRESHOLD`, so it is routed back
+/// to `Small` on deallocation. Only if `Small` is full, `Large` is drained. As blocks of `Large`
+/// are routed by their size, a block not larger than `THRESHOLD` is returned to `Large` and
+/// `Err` is returned instead.
+unsafe impl<Small, Large, const THRESHOLD: usize> AllocateAll for Segregate<Small, Large, THRESHOLD>
+where
+    Small: AllocateAll,
+    Large: Allocator + AllocateAll,
+{
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_all_impl(Small::allocate_all, Large::allocate_all)
     }
 
-    /// Deallocates all the memory the allocator had allocated.
-    fn dealloc_all(&mut self) {
-        self.small.dealloc_all();
-        self.large.dealloc_all();
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_all_impl(Small::allocate_all_zeroed, Large::allocate_all_zeroed)
     }
 
-    /// Returns the total capacity available in this allocator.
+    fn deallocate_all(&self) {
+        self.small.deallocate_all();
+        self.large.deallocate_all();
+    }
+
+    #[inline]
     fn capacity(&self) -> usize {
         self.small.capacity() + self.large.capacity()
     }
 
-    /// Returns the free capacity left for allocating.
+    #[inline]
     fn capacity_left(&self) -> usize {
         self.small.capacity_left() + self.large.capacity_left()
     }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.small.is_empty() && self.large.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.small.is_full() && self.large.is_full()
+    }
+}
+
+impl<Small, Large, const THRESHOLD: usize> ReportUsage for Segregate<Small, Large, THRESHOLD>
+where
+    Small: ReportUsage,
+    Large: ReportUsage,
+{
+    fn usage(&self) -> Usage {
+        self.small.usage() + self.large.usage()
+    }
 }
 
 impl<Small, Large, const THRESHOLD: usize> Owns for Segregate<Small, Large, THRESHOLD>
@@ -170,30 +273,153 @@ where
     Small: Owns,
     Large: Owns,
 {
-    fn owns(&self, ptr: NonNull<[u8]>) -> bool {
-        if ptr.len() <= THRESHOLD {
-            self.small.owns(ptr)
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        if memory.len() <= THRESHOLD {
+            self.small.owns(memory)
         } else {
-            self.large.owns(ptr)
+            self.large.owns(memory)
         }
     }
+
+    /// As the size is unknown, both allocators are asked.
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.small.owns_ptr(ptr) || self.large.owns_ptr(ptr)
+    }
+}
+
+impl<Small, Large, const THRESHOLD: usize> Describe for Segregate<Small, Large, THRESHOLD>
+where
+    Small: Describe,
+    Large: Describe,
+{
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Segregate<{}>", THRESHOLD)
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.small);
+        visit(&self.large);
+    }
+}
+
+impl<Small, Large, const THRESHOLD: usize> Purge for Segregate<Small, Large, THRESHOLD>
+where
+    Small: Purge,
+    Large: Purge,
+{
+    fn purge(&self, level: PurgeLevel) {
+        self.small.purge(level);
+        self.large.purge(level);
+    }
+}
+
+/// Builds a chain of nested [`Segregate`] from a list of thresholds in increasing order.
+///
+/// Every bucket serves the requests larger than the previous threshold and up to its own
+/// threshold, the last allocator serves the remaining requests. The macro has three forms:
+///
+/// - `segregate!(small => threshold, ...; large)` creates the allocator. The thresholds must be
+///   constants and are checked at compile time to be strictly increasing.
+/// - `segregate!(type Small => threshold, ...; Large)` names the type of such a chain, e.g. to
+///   store it in a struct or a static.
+/// - `segregate!(split memory, constructor; threshold => share, ...; large)` splits `memory` with
+///   [`split_memory`] proportionally to the shares and passes every part to `constructor` to create
+///   the allocator of the bucket.
+///
+/// [`split_memory`]: crate::region::split_memory
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, segregate, AllocateAll, Owns};
+/// use std::{
+///     alloc::{Allocator, Layout, System},
+///     mem::MaybeUninit,
+/// };
+///
+/// type Buckets<'a> = segregate!(
+///     type Region<'a> => 16, Region<'a> => 64, Region<'a> => 256; System
+/// );
+///
+/// let mut data = [MaybeUninit::uninit(); 1024];
+/// let alloc: Buckets = segregate!(split &mut data, Region::new; 16 => 1, 64 => 1, 256 => 2; System);
+/// assert_eq!(alloc.large.large.small.capacity(), 512);
+///
+/// let memory = alloc.allocate(Layout::new::<[u8; 32]>())?;
+/// assert!(alloc.large.small.owns(memory));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+///
+/// Thresholds, which are not increasing, fail to compile:
+///
+/// ```compile_fail
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::segregate;
+/// use std::alloc::System;
+///
+/// let alloc = segregate!(System => 64, System => 16; System);
+/// ```
+#[macro_export]
+macro_rules! segregate {
+    (@type $large:ty; $small:ty => $threshold:expr) => {
+        $crate::Segregate<$small, $large, { $threshold }>
+    };
+    (@type $large:ty; $small:ty => $threshold:expr, $($rest:tt)+) => {
+        $crate::Segregate<$small, $crate::segregate!(@type $large; $($rest)+), { $threshold }>
+    };
+    (@new $large:expr; $small:expr => $threshold:expr) => {
+        $crate::Segregate::<_, _, { $threshold }>::new($small, $large)
+    };
+    (@new $large:expr; $small:expr => $threshold:expr, $($rest:tt)+) => {
+        $crate::Segregate::<_, _, { $threshold }>::new(
+            $small,
+            $crate::segregate!(@new $large; $($rest)+),
+        )
+    };
+    (type $($small:ty => $threshold:expr),+ ; $large:ty $(,)?) => {
+        $crate::segregate!(@type $large; $($small => $threshold),+)
+    };
+    (split $memory:expr, $constructor:expr; $($threshold:expr => $share:expr),+ ; $large:expr $(,)?) => {{
+        let mut parts = ::core::iter::IntoIterator::into_iter(
+            $crate::region::split_memory($memory, [$($share),+]),
+        );
+        $crate::segregate!(
+            $(($constructor)(parts.next().expect("one part per bucket")) => $threshold),+ ;
+            $large
+        )
+    }};
+    ($($small:expr => $threshold:expr),+ ; $large:expr $(,)?) => {{
+        const _: () = {
+            let thresholds: &[usize] = &[$($threshold),+];
+            let mut i = 1;
+            while i < thresholds.len() {
+                assert!(
+                    thresholds[i - 1] < thresholds[i],
+                    "thresholds must be strictly increasing"
+                );
+                i += 1;
+            }
+        };
+        $crate::segregate!(@new $large; $($small => $threshold),+)
+    }};
 }
 
 #[cfg(test)]
 mod tests {
     use super::Segregate;
-    use crate::{AllocAll, Owns, Region};
-    use core::{
-        alloc::{AllocRef, Layout},
-        mem::MaybeUninit,
-    };
+    use crate::{helper::tracker, region::Region, AllocateAll, Allocator, Chunk, Fallback, Owns};
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
 
     #[test]
-    fn alloc() {
+    fn allocate() {
         let mut data_1 = [MaybeUninit::new(0); 128];
         let mut data_2 = [MaybeUninit::new(0); 128];
 
-        let mut alloc: Segregate<_, _, 32> = Segregate {
+        let alloc: Segregate<_, _, 32> = Segregate {
             small: Region::new(&mut data_1),
             large: Region::new(&mut data_2),
         };
@@ -202,108 +428,188 @@ mod tests {
         assert_eq!(alloc.capacity_left(), alloc.capacity());
 
         let mem = alloc
-            .alloc(Layout::new::<[u8; 4]>())
+            .allocate(Layout::new::<[u8; 4]>())
             .expect("Could not allocate 4 bytes");
         assert_eq!(mem.len(), 4);
         assert!(alloc.small.owns(mem));
 
-        unsafe { alloc.dealloc(mem.as_non_null_ptr(), Layout::new::<[u8; 4]>()) };
-        assert!(!alloc.owns(mem));
+        unsafe { alloc.deallocate(mem.as_non_null_ptr(), Layout::new::<[u8; 4]>()) };
 
         let mem = alloc
-            .alloc(Layout::new::<[u8; 32]>())
+            .allocate(Layout::new::<[u8; 32]>())
             .expect("Could not allocate 32 bytes");
         assert_eq!(mem.len(), 32);
         assert!(alloc.small.owns(mem));
 
         assert_eq!(alloc.capacity(), 256);
-        assert_eq!(alloc.capacity_left(), alloc.capacity() - 32);
+        assert_eq!(alloc.capacity_left(), alloc.capacity() - 36);
 
         let mem = alloc
-            .alloc(Layout::new::<[u8; 33]>())
+            .allocate(Layout::new::<[u8; 33]>())
             .expect("Could not allocate 33 bytes");
         assert_eq!(mem.len(), 33);
         assert!(alloc.large.owns(mem));
 
         assert_eq!(alloc.capacity(), 256);
-        assert_eq!(alloc.capacity_left(), alloc.capacity() - 32 - 33);
+        assert_eq!(alloc.capacity_left(), alloc.capacity() - 36 - 33);
 
-        unsafe {
-            alloc.dealloc(mem.as_non_null_ptr(), Layout::new::<[u8; 33]>());
+        alloc.deallocate_all();
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    fn owns_ptr() {
+        let mut data_1 = [MaybeUninit::new(0); 64];
+        let mut data_2 = [MaybeUninit::new(0); 64];
+        let mut data_3 = [MaybeUninit::new(0); 64];
+        let alloc = Fallback {
+            primary: Segregate::<_, _, 16> {
+                small: Region::new(&mut data_1),
+                large: Region::new(&mut data_2),
+            },
+            secondary: Region::new(&mut data_3),
+        };
+
+        let small = alloc
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        let large = alloc
+            .allocate(Layout::new::<[u8; 32]>())
+            .expect("Could not allocate 32 bytes");
+        let secondary = alloc
+            .allocate(Layout::new::<[u8; 48]>())
+            .expect("Could not allocate 48 bytes");
+        for memory in &[small, large, secondary] {
+            assert!(alloc.owns_ptr(memory.as_non_null_ptr()));
         }
-        assert_eq!(alloc.capacity_left(), alloc.capacity() - 32);
+        assert!(alloc.primary.owns_ptr(large.as_non_null_ptr()));
+        assert!(!alloc.primary.owns_ptr(secondary.as_non_null_ptr()));
+        assert!(!alloc.owns_ptr(NonNull::dangling()));
+    }
+
+    #[test]
+    fn alloc_all() {
+        let mut data_1 = [MaybeUninit::new(0); 128];
+        let mut data_2 = [MaybeUninit::new(0); 128];
 
-        alloc.dealloc_all();
-        assert_eq!(alloc.capacity(), alloc.capacity_left());
+        let alloc: Segregate<_, _, 32> = Segregate {
+            small: Region::new(&mut data_1),
+            large: Region::new(&mut data_2),
+        };
 
-        let mem = alloc
-            .alloc_all(Layout::new::<[u8; 4]>())
-            .expect("Could not allocate 4 bytes");
+        let mem = alloc.allocate_all().expect("Could not allocate all");
         assert!(alloc.small.owns(mem));
         assert_eq!(mem.len(), 32);
 
         assert_eq!(alloc.capacity(), 256);
         assert_eq!(alloc.capacity_left(), 128);
 
-        let mem = alloc
-            .alloc_all(Layout::new::<[u8; 33]>())
-            .expect("Could not allocate 33 bytes");
+        let mem = alloc.allocate_all().expect("Could not allocate all");
         assert!(alloc.large.owns(mem));
         assert_eq!(mem.len(), 128);
 
-        assert_eq!(alloc.capacity(), 256);
         assert_eq!(alloc.capacity_left(), 0);
+        assert!(alloc.is_full());
+        alloc.allocate_all().expect_err("Could allocate all");
 
-        alloc.dealloc_all();
-
-        assert_eq!(alloc.capacity_left(), alloc.capacity());
+        alloc.deallocate_all();
+        assert!(alloc.is_empty());
     }
 
     #[test]
-    fn realloc() {
-        let mut data_1 = [MaybeUninit::new(0); 128];
-        let mut data_2 = [MaybeUninit::new(0); 128];
+    fn alloc_all_large_below_threshold() {
+        let mut data_1 = [MaybeUninit::new(0); 16];
+        let mut data_2 = [MaybeUninit::new(0); 16];
 
-        let mut alloc: Segregate<_, _, 32> = Segregate {
+        let alloc: Segregate<_, _, 32> = Segregate {
             small: Region::new(&mut data_1),
             large: Region::new(&mut data_2),
         };
 
-        let mem = alloc.alloc(Layout::new::<[u8; 8]>()).unwrap();
-        assert_eq!(mem.len(), 8);
+        alloc.allocate_all().expect("Could not allocate all");
+        assert!(alloc.small.is_full());
+        alloc.allocate_all().expect_err("Could allocate all");
+    }
+
+    #[test]
+    fn realloc() {
+        let mut data = [MaybeUninit::new(0); 128];
+
+        let alloc: Segregate<_, _, 32> = Segregate {
+            small: tracker(Chunk::<_, 32>(Region::new(&mut data))),
+            large: tracker(Global),
+        };
+
+        let mem = alloc.allocate(Layout::new::<[u8; 8]>()).unwrap();
+        assert_eq!(mem.len(), 32);
         assert!(alloc.small.owns(mem));
-        assert!(alloc.owns(mem));
 
         unsafe {
             let mem = alloc
-                .grow(mem.as_non_null_ptr(), Layout::new::<[u8; 8]>(), 16)
+                .grow(
+                    mem.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 32]>(),
+                )
                 .unwrap();
-            assert_eq!(mem.len(), 16);
+            assert_eq!(mem.len(), 32);
             assert!(alloc.small.owns(mem));
-            assert!(alloc.owns(mem));
 
             let mem = alloc
-                .grow(mem.as_non_null_ptr(), Layout::new::<[u8; 8]>(), 32)
+                .grow(
+                    mem.as_non_null_ptr(),
+                    Layout::new::<[u8; 32]>(),
+                    Layout::new::<[u8; 33]>(),
+                )
                 .unwrap();
-            assert_eq!(mem.len(), 32);
-            assert!(alloc.small.owns(mem));
-            assert!(alloc.owns(mem));
+            assert!(mem.len() >= 33);
+            assert!(!alloc.small.owns(mem));
 
             let mem = alloc
-                .grow(mem.as_non_null_ptr(), Layout::new::<[u8; 32]>(), 33)
+                .grow(
+                    mem.as_non_null_ptr(),
+                    Layout::new::<[u8; 33]>(),
+                    Layout::new::<[u8; 64]>(),
+                )
                 .unwrap();
-            assert_eq!(mem.len(), 33);
+            assert!(mem.len() >= 64);
             assert!(!alloc.small.owns(mem));
-            assert!(alloc.large.owns(mem));
-            assert!(alloc.owns(mem));
 
             let mem = alloc
-                .grow(mem.as_non_null_ptr(), Layout::new::<[u8; 33]>(), 64)
+                .shrink(
+                    mem.as_non_null_ptr(),
+                    Layout::new::<[u8; 64]>(),
+                    Layout::new::<[u8; 16]>(),
+                )
                 .unwrap();
-            assert_eq!(mem.len(), 64);
-            assert!(!alloc.small.owns(mem));
-            assert!(alloc.large.owns(mem));
-            assert!(alloc.owns(mem));
+            assert_eq!(mem.len(), 32);
+            assert!(alloc.small.owns(mem));
+
+            alloc.deallocate(mem.as_non_null_ptr(), Layout::new::<[u8; 16]>());
+        }
+    }
+
+    #[test]
+    fn buckets() {
+        let mut data = [MaybeUninit::uninit(); 1024];
+        let alloc: segregate!(type Region => 8, Region => 32, Region => 128; Global) =
+            segregate!(split &mut data, Region::new; 8 => 1, 32 => 1, 128 => 2; Global);
+        assert_eq!(alloc.small.capacity(), 256);
+        assert_eq!(alloc.large.small.capacity(), 256);
+        assert_eq!(alloc.large.large.small.capacity(), 512);
+
+        for (size, bucket) in [(8, 0), (9, 1), (32, 1), (100, 2), (129, 3)] {
+            let layout = Layout::from_size_align(size, 1).unwrap();
+            let memory = alloc.allocate(layout).expect("Could not allocate");
+            let owners = [
+                alloc.small.owns(memory),
+                alloc.large.small.owns(memory),
+                alloc.large.large.small.owns(memory),
+            ];
+            for (i, &owned) in owners.iter().enumerate() {
+                assert_eq!(owned, i == bucket, "{} bytes served by bucket {}", size, i);
+            }
+            unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
         }
     }
 }
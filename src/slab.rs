@@ -0,0 +1,266 @@
+use crate::{Allocator, Describe, GrowablePool, Purge, PurgeLevel, Segregate};
+use core::{
+    alloc::{AllocError, Layout},
+    cmp,
+    fmt,
+    ptr::NonNull,
+};
+
+type Classes<P> = Segregate<
+    GrowablePool<P, 16, 255>,
+    Segregate<
+        GrowablePool<P, 32, 127>,
+        Segregate<
+            GrowablePool<P, 64, 63>,
+            Segregate<
+                GrowablePool<P, 128, 31>,
+                Segregate<
+                    GrowablePool<P, 256, 15>,
+                    Segregate<GrowablePool<P, 512, 7>, P, 512>,
+                    256,
+                >,
+                128,
+            >,
+            64,
+        >,
+        32,
+    >,
+    16,
+>;
+
+/// A general purpose allocator built from size classes of [`GrowablePool`]s.
+///
+/// Requests up to 512 bytes are served from pools with block sizes of 16, 32, 64, 128, 256, and
+/// 512 bytes, which request slabs of about 4 KiB from `P`. Larger requests are passed through to
+/// `P`. The pools are chained with [`Segregate`], so memory blocks moving between size classes on
+/// `grow` or `shrink` are copied over.
+///
+/// Blocks of a size class are aligned to their size, so requests are routed by their size rounded
+/// up to their alignment. This way, over-aligned small requests still land in a pool.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::SlabAllocator;
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = SlabAllocator::new(System);
+///
+/// let small = alloc.allocate(Layout::new::<[u8; 24]>())?;
+/// assert_eq!(small.len(), 32);
+/// let large = alloc.allocate(Layout::new::<[u8; 4096]>())?;
+/// unsafe {
+///     alloc.deallocate(small.as_non_null_ptr(), Layout::new::<[u8; 24]>());
+///     alloc.deallocate(large.as_non_null_ptr(), Layout::new::<[u8; 4096]>());
+/// }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct SlabAllocator<P: Allocator> {
+    classes: Classes<P>,
+}
+
+impl<P: Allocator + Clone> SlabAllocator<P> {
+    /// Creates a slab allocator, which requests its memory from `parent`.
+    pub fn new(parent: P) -> Self {
+        Self {
+            classes: Segregate {
+                small: GrowablePool::new(parent.clone()),
+                large: Segregate {
+                    small: GrowablePool::new(parent.clone()),
+                    large: Segregate {
+                        small: GrowablePool::new(parent.clone()),
+                        large: Segregate {
+                            small: GrowablePool::new(parent.clone()),
+                            large: Segregate {
+                                small: GrowablePool::new(parent.clone()),
+                                large: Segregate {
+                                    small: GrowablePool::new(parent.clone()),
+                                    large: parent,
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+}
+
+impl<P: Allocator> SlabAllocator<P> {
+    /// Pads the size of `layout` to its alignment, so it is routed to a sufficiently aligned
+    /// size class.
+    fn class_layout(layout: Layout) -> Layout {
+        let size = cmp::max(layout.pad_to_align().size(), layout.align());
+        unsafe { Layout::from_size_align_unchecked(size, layout.align()) }
+    }
+}
+
+impl<P: Allocator + Clone + Default> Default for SlabAllocator<P> {
+    fn default() -> Self {
+        Self::new(P::default())
+    }
+}
+
+impl<P: Allocator + fmt::Debug> fmt::Debug for SlabAllocator<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlabAllocator")
+            .field("classes", &self.classes)
+            .finish()
+    }
+}
+
+unsafe impl<P: Allocator> Allocator for SlabAllocator<P> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.classes.allocate(Self::class_layout(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.classes.allocate_zeroed(Self::class_layout(layout))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.classes.deallocate(ptr, Self::class_layout(layout))
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.classes.grow(
+            ptr,
+            Self::class_layout(old_layout),
+            Self::class_layout(new_layout),
+        )
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let memory = self.classes.grow(
+            ptr,
+            Self::class_layout(old_layout),
+            Self::class_layout(new_layout),
+        )?;
+        memory
+            .as_mut_ptr()
+            .add(old_layout.size())
+            .write_bytes(0, memory.len() - old_layout.size());
+        Ok(memory)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.classes.shrink(
+            ptr,
+            Self::class_layout(old_layout),
+            Self::class_layout(new_layout),
+        )
+    }
+}
+
+impl<P: Allocator + Describe> Describe for SlabAllocator<P> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SlabAllocator")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.classes)
+    }
+}
+
+impl<P: Allocator + Purge> Purge for SlabAllocator<P> {
+    fn purge(&self, level: PurgeLevel) {
+        self.classes.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlabAllocator;
+    use crate::{helper::tracker, region::Region, Allocator};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn size_classes() {
+        let alloc = tracker(SlabAllocator::new(Global));
+        let layouts = [
+            Layout::new::<()>(),
+            Layout::new::<u8>(),
+            Layout::from_size_align(8, 64).expect("Invalid layout"),
+            Layout::new::<[u64; 5]>(),
+            Layout::new::<[u8; 300]>(),
+            Layout::new::<[u8; 2000]>(),
+        ];
+        let blocks = layouts
+            .iter()
+            .map(|&layout| {
+                let memory = alloc.allocate(layout).expect("Could not allocate");
+                assert!(memory.len() >= layout.size());
+                assert_eq!(memory.as_mut_ptr() as usize % layout.align(), 0);
+                memory
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            blocks[3].as_mut_ptr().write_bytes(7, 40);
+            let grown = alloc
+                .grow_zeroed(
+                    blocks[3].as_non_null_ptr(),
+                    layouts[3],
+                    Layout::new::<[u64; 100]>(),
+                )
+                .expect("Could not grow to 800 bytes");
+            assert_eq!(*grown.as_mut_ptr().add(39), 7);
+            assert_eq!(*grown.as_mut_ptr().add(799), 0);
+            let shrunk = alloc
+                .shrink(
+                    grown.as_non_null_ptr(),
+                    Layout::new::<[u64; 100]>(),
+                    Layout::new::<[u64; 2]>(),
+                )
+                .expect("Could not shrink to 16 bytes");
+            assert_eq!(*shrunk.as_mut_ptr().add(15), 7);
+            alloc.deallocate(shrunk.as_non_null_ptr(), Layout::new::<[u64; 2]>());
+
+            for (i, (&memory, &layout)) in blocks.iter().zip(&layouts).enumerate() {
+                if i != 3 {
+                    alloc.deallocate(memory.as_non_null_ptr(), layout);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn region_parent() {
+        let mut data = [MaybeUninit::uninit(); 8192];
+        let region = Region::new(&mut data);
+        let alloc = SlabAllocator::new(&region);
+        for _ in 0..100 {
+            let memory = alloc
+                .allocate(Layout::new::<u64>())
+                .expect("Could not allocate 8 bytes");
+            unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u64>()) };
+        }
+        assert!(alloc.allocate(Layout::new::<[u8; 16384]>()).is_err());
+    }
+}
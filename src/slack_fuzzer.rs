@@ -0,0 +1,248 @@
+use crate::{helper::split_mix, Allocator, Describe, Owns, Purge, PurgeLevel, ReallocateInPlace};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// Randomly shortens the memory blocks returned by the underlying allocator.
+///
+/// When the underlying allocator returns more memory than requested, `SlackFuzzer` reports a
+/// random length between the requested size and the actual length. Every reported length is a
+/// valid length to deallocate the memory block with, so this is transparent to correct callers,
+/// but exposes code assuming `memory.len() == layout.size()`, or assuming the slack to be the same
+/// for equal requests. It's intended for soak tests of collections and combinators.
+///
+/// The lengths are drawn from a pseudo-random stream derived from a seed, so a failing test can
+/// be reproduced by reusing its seed.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{Chunk, SlackFuzzer};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = SlackFuzzer::new(Chunk::<_, 64>(System), 42);
+///
+/// let layout = Layout::new::<[u8; 8]>();
+/// let memory = alloc.allocate(layout)?;
+/// assert!(memory.len() >= 8 && memory.len() <= 64);
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct SlackFuzzer<A> {
+    pub alloc: A,
+    seed: u64,
+    state: Cell<u64>,
+}
+
+impl<A> SlackFuzzer<A> {
+    /// Wraps `alloc` and draws the reported lengths from a stream derived from `seed`.
+    pub fn new(alloc: A, seed: u64) -> Self {
+        Self {
+            alloc,
+            seed,
+            state: Cell::new(seed),
+        }
+    }
+
+    /// Returns the seed of this fuzzer.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn fuzz_len(&self, requested: usize, len: usize) -> usize {
+        if len <= requested {
+            return len;
+        }
+        let mut state = self.state.get();
+        let value = split_mix(&mut state);
+        self.state.set(state);
+        requested + (value % ((len - requested) as u64 + 1)) as usize
+    }
+
+    fn fuzz(
+        &self,
+        layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = result?;
+        Ok(NonNull::slice_from_raw_parts(
+            memory.as_non_null_ptr(),
+            self.fuzz_len(layout.size(), memory.len()),
+        ))
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for SlackFuzzer<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlackFuzzer")
+            .field("alloc", &self.alloc)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for SlackFuzzer<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.fuzz(layout, self.alloc.allocate(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.fuzz(layout, self.alloc.allocate_zeroed(layout))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.fuzz(new_layout, self.alloc.grow(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.fuzz(
+            new_layout,
+            self.alloc.grow_zeroed(ptr, old_layout, new_layout),
+        )
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.fuzz(new_layout, self.alloc.shrink(ptr, old_layout, new_layout))
+    }
+}
+
+unsafe impl<A: ReallocateInPlace> ReallocateInPlace for SlackFuzzer<A> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let len = self.alloc.grow_in_place(ptr, old_layout, new_layout)?;
+        Ok(self.fuzz_len(new_layout.size(), len))
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let len = self
+            .alloc
+            .grow_in_place_zeroed(ptr, old_layout, new_layout)?;
+        Ok(self.fuzz_len(new_layout.size(), len))
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        let len = self.alloc.shrink_in_place(ptr, old_layout, new_layout)?;
+        Ok(self.fuzz_len(new_layout.size(), len))
+    }
+}
+
+impl<A: Owns> Owns for SlackFuzzer<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: Describe> Describe for SlackFuzzer<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SlackFuzzer(seed {})", self.seed)
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge> Purge for SlackFuzzer<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlackFuzzer;
+    use crate::{helper::tracker, Allocator, Chunk};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::alloc::Layout;
+
+    #[test]
+    fn lengths() {
+        let alloc = tracker(SlackFuzzer::new(Chunk::<_, 64>(Global), 7));
+        let layout = Layout::new::<[u8; 10]>();
+        let mut lengths = Vec::new();
+        for _ in 0..32 {
+            let memory = alloc.allocate(layout).expect("Could not allocate 10 bytes");
+            assert!(memory.len() >= 10 && memory.len() <= 64);
+            lengths.push(memory.len());
+            unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        }
+        assert!(lengths.iter().any(|&len| len != lengths[0]));
+
+        let replay = SlackFuzzer::new(Chunk::<_, 64>(Global), 7);
+        for &len in &lengths {
+            let memory = replay
+                .allocate(layout)
+                .expect("Could not allocate 10 bytes");
+            assert_eq!(memory.len(), len);
+            unsafe { replay.deallocate(memory.as_non_null_ptr(), layout) };
+        }
+    }
+
+    #[test]
+    fn collections() {
+        let alloc = tracker(SlackFuzzer::new(Chunk::<_, 32>(Global), 1));
+        let mut vec = Vec::new_in(&alloc);
+        for i in 0..1000 {
+            vec.push(i);
+        }
+        vec.truncate(10);
+        vec.shrink_to_fit();
+        assert_eq!(vec, (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    stats::Counter,
+    Allocator,
+    CallbackRef,
+    Describe,
+    Owns,
+    Proxy,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// Splits allocations between two allocators by a fixed ratio to compare them under the same
+/// workload.
+///
+/// Out of every 100 allocations, `ratio` are served by `a` and the rest by `b`. The allocations
+/// are interleaved evenly instead of being drawn randomly, so the split is exact even for short
+/// runs. A failing allocation is not retried on the other side, as that would skew the
+/// comparison.
+///
+/// Both sides are wrapped in a [`Proxy`], so each side collects its own statistics in the
+/// callbacks `C`, which default to a [`Counter`]. Other callbacks can be used with
+/// [`from_proxies`]. A memory block stays on the side, which
+/// allocated it: `deallocate`, `grow`, and `shrink` are dispatched by asking `a`, if it
+/// [`Owns`] the memory block.
+///
+/// [`from_proxies`]: Self::from_proxies
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, Chunk, Split};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut a = [MaybeUninit::uninit(); 256];
+/// let mut b = [MaybeUninit::uninit(); 256];
+/// let alloc = Split::new(Region::new(&mut a), Chunk::<_, 16>(Region::new(&mut b)), 25);
+///
+/// for _ in 0..8 {
+///     alloc.allocate(Layout::new::<u32>())?;
+/// }
+/// assert_eq!(alloc.a.callbacks.num_allocs(), 2);
+/// assert_eq!(alloc.b.callbacks.num_allocs(), 6);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub struct Split<A, B, C = Counter> {
+    pub a: Proxy<A, C>,
+    pub b: Proxy<B, C>,
+    ratio: Cell<u8>,
+    credit: Cell<u8>,
+}
+
+impl<A, B> Split<A, B> {
+    /// Creates a split serving `ratio` percent of the allocations from `a`, which counts the
+    /// requests of both sides.
+    ///
+    /// A `ratio` above 100 is treated as 100.
+    pub fn new(a: A, b: B, ratio: u8) -> Self {
+        Self::from_proxies(
+            Proxy::new(a, Counter::default()),
+            Proxy::new(b, Counter::default()),
+            ratio,
+        )
+    }
+}
+
+impl<A, B, C> Split<A, B, C> {
+    /// Creates a split serving `ratio` percent of the allocations from `a`, which reports to the
+    /// callbacks of the proxies.
+    ///
+    /// A `ratio` above 100 is treated as 100.
+    pub fn from_proxies(a: Proxy<A, C>, b: Proxy<B, C>, ratio: u8) -> Self {
+        Self {
+            a,
+            b,
+            ratio: Cell::new(ratio.min(100)),
+            credit: Cell::new(0),
+        }
+    }
+
+    /// Returns the percentage of allocations served by `a`.
+    #[inline]
+    pub fn ratio(&self) -> u8 {
+        self.ratio.get()
+    }
+
+    /// Changes the percentage of allocations served by `a`. Memory blocks already allocated stay
+    /// where they are.
+    ///
+    /// A `ratio` above 100 is treated as 100.
+    #[inline]
+    pub fn set_ratio(&self, ratio: u8) {
+        self.ratio.set(ratio.min(100))
+    }
+
+    /// Returns if the next allocation is served by `a`.
+    fn next_is_a(&self) -> bool {
+        let credit = self.credit.get() + self.ratio.get();
+        if credit >= 100 {
+            self.credit.set(credit - 100);
+            true
+        } else {
+            self.credit.set(credit);
+            false
+        }
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug, C: fmt::Debug> fmt::Debug for Split<A, B, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Split")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .field("ratio", &self.ratio.get())
+            .finish()
+    }
+}
+
+impl<A: Owns, B, C> Split<A, B, C> {
+    fn owned_by_a(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.a
+            .alloc
+            .owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+}
+
+unsafe impl<A, B, C> Allocator for Split<A, B, C>
+where
+    A: Allocator + Owns,
+    B: Allocator,
+    C: CallbackRef,
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.next_is_a() {
+            self.a.allocate(layout)
+        } else {
+            self.b.allocate(layout)
+        }
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.next_is_a() {
+            self.a.allocate_zeroed(layout)
+        } else {
+            self.b.allocate_zeroed(layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if self.owned_by_a(ptr, layout) {
+            self.a.deallocate(ptr, layout)
+        } else {
+            self.b.deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if self.owned_by_a(ptr, old_layout) {
+            self.a.grow(ptr, old_layout, new_layout)
+        } else {
+            self.b.grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        if self.owned_by_a(ptr, old_layout) {
+            self.a.grow_zeroed(ptr, old_layout, new_layout)
+        } else {
+            self.b.grow_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if self.owned_by_a(ptr, old_layout) {
+            self.a.shrink(ptr, old_layout, new_layout)
+        } else {
+            self.b.shrink(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+impl<A: Owns, B: Owns, C> Owns for Split<A, B, C> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.a.alloc.owns(memory) || self.b.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.a.alloc.owns_ptr(ptr) || self.b.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: ReportUsage, B: ReportUsage, C> ReportUsage for Split<A, B, C> {
+    fn usage(&self) -> Usage {
+        self.a.alloc.usage() + self.b.alloc.usage()
+    }
+}
+
+impl<A: Describe, B: Describe, C> Describe for Split<A, B, C> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Split({}% / {}%)", self.ratio(), 100 - self.ratio())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.a.alloc);
+        visit(&self.b.alloc);
+    }
+}
+
+impl<A: Purge, B: Purge, C> Purge for Split<A, B, C> {
+    fn purge(&self, level: PurgeLevel) {
+        self.a.alloc.purge(level);
+        self.b.alloc.purge(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Split;
+    use crate::{helper::tracker, Allocator, FreeTree, Owns};
+    use alloc::{alloc::Global, vec::Vec};
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn ratio() {
+        let mut data = [MaybeUninit::new(0); 4096];
+        let alloc = tracker(Split::new(FreeTree::new(&mut data), Global, 30));
+        let split = &alloc.alloc;
+        let layout = Layout::new::<[u8; 16]>();
+        let shrunk = Layout::new::<[u8; 8]>();
+
+        let blocks = (0..10)
+            .map(|_| alloc.allocate(layout).expect("Could not allocate 16 bytes"))
+            .collect::<Vec<_>>();
+        assert_eq!(split.a.callbacks.num_allocs(), 3);
+        assert_eq!(split.b.callbacks.num_allocs(), 7);
+        assert_eq!(
+            blocks
+                .iter()
+                .filter(|&&block| split.a.alloc.owns(block))
+                .count(),
+            3
+        );
+
+        for block in blocks {
+            let memory = unsafe {
+                alloc
+                    .shrink(block.as_non_null_ptr(), layout, shrunk)
+                    .expect("Could not shrink to 8 bytes")
+            };
+            assert_eq!(split.a.alloc.owns(memory), split.a.alloc.owns(block));
+            unsafe { alloc.deallocate(memory.as_non_null_ptr(), shrunk) };
+        }
+        assert_eq!(split.a.callbacks.num_shrinks(), 3);
+        assert_eq!(split.b.callbacks.num_deallocs(), 7);
+
+        split.set_ratio(200);
+        assert_eq!(split.ratio(), 100);
+        let memory = alloc.allocate(layout).expect("Could not allocate 16 bytes");
+        assert!(split.a.alloc.owns(memory));
+    }
+}
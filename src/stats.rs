@@ -8,10 +8,122 @@ use crate::CallbackRef;
 use core::{
     alloc::{AllocError, Layout},
     cell::Cell,
+    fmt,
+    iter,
     ptr::NonNull,
-    sync::atomic::{AtomicU64, Ordering::Relaxed},
 };
 
+mod sealed {
+    pub trait Count: Copy {
+        const ONE: Self;
+
+        fn wrapping_add(self, rhs: Self) -> Self;
+
+        fn wrapping_sub(self, rhs: Self) -> Self;
+
+        fn max(self, rhs: Self) -> Self;
+
+        fn from_usize(value: usize) -> Self;
+    }
+
+    pub trait AtomicCount: Count {
+        type Atomic: Default + core::fmt::Debug;
+
+        fn fetch_add(atomic: &Self::Atomic, value: Self) -> Self;
+
+        fn fetch_sub(atomic: &Self::Atomic, value: Self);
+
+        fn fetch_max(atomic: &Self::Atomic, value: Self);
+
+        fn load(atomic: &Self::Atomic) -> Self;
+
+        fn store(atomic: &Self::Atomic, value: Self);
+    }
+}
+
+/// An integer type used for counting statistics.
+///
+/// On targets without large address spaces, a smaller type than `u64` may be used to save memory
+/// or to avoid emulated 64-bit arithmetic. All counters wrap around on overflow.
+pub trait Count: sealed::Count + Default + Eq + fmt::Debug {}
+
+/// An integer type, which can be counted atomically on the current target.
+pub trait AtomicCount: Count + sealed::AtomicCount {}
+
+macro_rules! impl_count {
+    ($($ty:ident: $atomic:ident if $width:literal),* $(,)?) => {
+        $(
+            impl sealed::Count for $ty {
+                const ONE: Self = 1;
+
+                #[inline]
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    $ty::wrapping_add(self, rhs)
+                }
+
+                #[inline]
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    $ty::wrapping_sub(self, rhs)
+                }
+
+                #[inline]
+                fn max(self, rhs: Self) -> Self {
+                    core::cmp::max(self, rhs)
+                }
+
+                #[inline]
+                #[allow(clippy::cast_possible_truncation)]
+                fn from_usize(value: usize) -> Self {
+                    value as $ty
+                }
+            }
+
+            impl Count for $ty {}
+
+            #[cfg(target_has_atomic = $width)]
+            impl sealed::AtomicCount for $ty {
+                type Atomic = crate::sync::atomic::$atomic;
+
+                #[inline]
+                fn fetch_add(atomic: &Self::Atomic, value: Self) -> Self {
+                    atomic.fetch_add(value, crate::sync::atomic::Ordering::Relaxed)
+                }
+
+                #[inline]
+                fn fetch_sub(atomic: &Self::Atomic, value: Self) {
+                    atomic.fetch_sub(value, crate::sync::atomic::Ordering::Relaxed);
+                }
+
+                #[inline]
+                fn fetch_max(atomic: &Self::Atomic, value: Self) {
+                    atomic.fetch_max(value, crate::sync::atomic::Ordering::Relaxed);
+                }
+
+                #[inline]
+                fn load(atomic: &Self::Atomic) -> Self {
+                    atomic.load(crate::sync::atomic::Ordering::Relaxed)
+                }
+
+                #[inline]
+                fn store(atomic: &Self::Atomic, value: Self) {
+                    atomic.store(value, crate::sync::atomic::Ordering::Relaxed)
+                }
+            }
+
+            #[cfg(target_has_atomic = $width)]
+            impl AtomicCount for $ty {}
+        )*
+    };
+}
+
+impl_count!(
+    u8: AtomicU8 if "8",
+    u16: AtomicU16 if "16",
+    u32: AtomicU32 if "32",
+    u64: AtomicU64 if "64",
+    usize: AtomicUsize if "ptr",
+);
+
 #[repr(usize)]
 #[derive(Copy, Clone, PartialEq)]
 enum Stat {
@@ -20,104 +132,132 @@ enum Stat {
     Grows = 2,
     Shrinks = 3,
     Owns = 4,
+    Moves = 5,
+}
+const STAT_COUNT: usize = 6;
+
+/// Returns if a successful `grow` or `shrink` returned a different pointer than `ptr`.
+#[inline]
+fn moved(ptr: NonNull<u8>, result: Result<NonNull<[u8]>, AllocError>) -> bool {
+    matches!(result, Ok(memory) if memory.as_non_null_ptr() != ptr)
 }
-const STAT_COUNT: usize = 5;
 
 /// A primitive counter for collectiong statistics.
+///
+/// The count type `T` defaults to `u64` in [`Counter`].
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct Counter {
-    stats: [Cell<u64>; STAT_COUNT],
+pub struct CounterOf<T: Count> {
+    stats: [Cell<T>; STAT_COUNT],
 }
 
-impl PartialEq<AtomicCounter> for Counter {
-    fn eq(&self, other: &AtomicCounter) -> bool {
+/// A primitive counter for collectiong statistics using `u64`.
+pub type Counter = CounterOf<u64>;
+
+impl<T: AtomicCount> PartialEq<AtomicCounterOf<T>> for CounterOf<T> {
+    fn eq(&self, other: &AtomicCounterOf<T>) -> bool {
         self.stats
             .iter()
             .zip(other.stats.iter())
-            .all(|(lhs, rhs)| lhs.get() == rhs.load(Relaxed))
+            .all(|(lhs, rhs)| lhs.get() == T::load(rhs))
     }
 }
 
-impl Counter {
-    fn increment_stat(&self, stat: Stat, additional: u64) {
-        self.stats[stat as usize].set(self.stats[stat as usize].get() + additional)
+impl<T: Count> CounterOf<T> {
+    fn increment_stat(&self, stat: Stat) {
+        let stat = &self.stats[stat as usize];
+        stat.set(stat.get().wrapping_add(T::ONE))
     }
-    fn get(&self, stat: Stat) -> u64 {
+    fn get(&self, stat: Stat) -> T {
         self.stats[stat as usize].get()
     }
 }
 
 /// An atomic counter for collectiong statistics which can be shared between threads.
+///
+/// The count type `T` defaults to `u64` in [`AtomicCounter`].
 #[derive(Debug, Default)]
-pub struct AtomicCounter {
-    stats: [AtomicU64; STAT_COUNT],
+pub struct AtomicCounterOf<T: AtomicCount> {
+    stats: [T::Atomic; STAT_COUNT],
 }
 
-impl PartialEq for AtomicCounter {
+/// An atomic counter for collectiong statistics using `u64`.
+#[cfg(target_has_atomic = "64")]
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "64")))]
+pub type AtomicCounter = AtomicCounterOf<u64>;
+
+impl<T: AtomicCount> PartialEq for AtomicCounterOf<T> {
     fn eq(&self, other: &Self) -> bool {
         self.stats
             .iter()
             .zip(other.stats.iter())
-            .all(|(lhs, rhs)| lhs.load(Relaxed) == rhs.load(Relaxed))
+            .all(|(lhs, rhs)| T::load(lhs) == T::load(rhs))
     }
 }
 
-impl PartialEq<Counter> for AtomicCounter {
-    fn eq(&self, other: &Counter) -> bool {
+impl<T: AtomicCount> PartialEq<CounterOf<T>> for AtomicCounterOf<T> {
+    fn eq(&self, other: &CounterOf<T>) -> bool {
         self.stats
             .iter()
             .zip(other.stats.iter())
-            .all(|(lhs, rhs)| lhs.load(Relaxed) == rhs.get())
+            .all(|(lhs, rhs)| T::load(lhs) == rhs.get())
     }
 }
 
-impl AtomicCounter {
-    fn increment_stat(&self, stat: Stat, additional: u64) {
-        self.stats[stat as usize].fetch_add(additional, Relaxed);
+impl<T: AtomicCount> AtomicCounterOf<T> {
+    fn increment_stat(&self, stat: Stat) {
+        T::fetch_add(&self.stats[stat as usize], T::ONE);
     }
-    fn get(&self, stat: Stat) -> u64 {
-        self.stats[stat as usize].load(Relaxed)
+    fn get(&self, stat: Stat) -> T {
+        T::load(&self.stats[stat as usize])
     }
 }
 
 macro_rules! impl_callback_ref {
-    ($tt:tt) => {
-        impl $tt {
-            /// Returns the number of `alloc` calls.
+    ($tt:ident, $bound:ident) => {
+        impl<T: $bound> $tt<T> {
+            /// Returns the number of `allocate` calls.
             #[inline]
-            pub fn num_allocs(&self) -> u64 {
+            pub fn num_allocs(&self) -> T {
                 self.get(Stat::Allocs)
             }
 
-            /// Returns the number of `dealloc` calls.
+            /// Returns the number of `deallocate` calls.
             #[inline]
-            pub fn num_deallocs(&self) -> u64 {
+            pub fn num_deallocs(&self) -> T {
                 self.get(Stat::Deallocs)
             }
 
             /// Returns the number of `grow` calls.
             #[inline]
-            pub fn num_grows(&self) -> u64 {
+            pub fn num_grows(&self) -> T {
                 self.get(Stat::Grows)
             }
 
             /// Returns the number of `shrink` calls.
             #[inline]
-            pub fn num_shrinks(&self) -> u64 {
+            pub fn num_shrinks(&self) -> T {
                 self.get(Stat::Shrinks)
             }
 
             /// Returns the number of `owns` calls.
             #[inline]
-            pub fn num_owns(&self) -> u64 {
+            pub fn num_owns(&self) -> T {
                 self.get(Stat::Owns)
             }
+
+            /// Returns the number of `grow` and `shrink` calls, which moved the memory block.
+            ///
+            /// Resizing in place is not counted, even if it was requested with `grow` or `shrink`.
+            #[inline]
+            pub fn num_moves(&self) -> T {
+                self.get(Stat::Moves)
+            }
         }
 
-        unsafe impl CallbackRef for $tt {
+        unsafe impl<T: $bound> CallbackRef for $tt<T> {
             #[inline]
             fn after_allocate(&self, _layout: Layout, _result: Result<NonNull<[u8]>, AllocError>) {
-                self.increment_stat(Stat::Allocs, 1)
+                self.increment_stat(Stat::Allocs)
             }
             #[inline]
             fn after_allocate_zeroed(
@@ -125,45 +265,51 @@ macro_rules! impl_callback_ref {
                 _layout: Layout,
                 _result: Result<NonNull<[u8]>, AllocError>,
             ) {
-                self.increment_stat(Stat::Allocs, 1)
+                self.increment_stat(Stat::Allocs)
             }
             #[inline]
             fn after_allocate_all(&self, _result: Result<NonNull<[u8]>, AllocError>) {
-                self.increment_stat(Stat::Allocs, 1)
+                self.increment_stat(Stat::Allocs)
             }
             #[inline]
             fn after_allocate_all_zeroed(&self, _result: Result<NonNull<[u8]>, AllocError>) {
-                self.increment_stat(Stat::Allocs, 1)
+                self.increment_stat(Stat::Allocs)
             }
 
             #[inline]
             fn before_deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
-                self.increment_stat(Stat::Deallocs, 1);
+                self.increment_stat(Stat::Deallocs);
             }
 
             #[inline]
             fn before_deallocate_all(&self) {
-                self.increment_stat(Stat::Deallocs, 1);
+                self.increment_stat(Stat::Deallocs);
             }
 
             fn after_grow(
                 &self,
-                _ptr: NonNull<u8>,
+                ptr: NonNull<u8>,
                 _old_layout: Layout,
                 _new_layout: Layout,
-                _result: Result<NonNull<[u8]>, AllocError>,
+                result: Result<NonNull<[u8]>, AllocError>,
             ) {
-                self.increment_stat(Stat::Grows, 1)
+                self.increment_stat(Stat::Grows);
+                if moved(ptr, result) {
+                    self.increment_stat(Stat::Moves)
+                }
             }
 
             fn after_grow_zeroed(
                 &self,
-                _ptr: NonNull<u8>,
+                ptr: NonNull<u8>,
                 _old_layout: Layout,
                 _new_layout: Layout,
-                _result: Result<NonNull<[u8]>, AllocError>,
+                result: Result<NonNull<[u8]>, AllocError>,
             ) {
-                self.increment_stat(Stat::Grows, 1)
+                self.increment_stat(Stat::Grows);
+                if moved(ptr, result) {
+                    self.increment_stat(Stat::Moves)
+                }
             }
 
             fn after_grow_in_place(
@@ -173,7 +319,7 @@ macro_rules! impl_callback_ref {
                 _new_layout: Layout,
                 _result: Result<usize, AllocError>,
             ) {
-                self.increment_stat(Stat::Grows, 1)
+                self.increment_stat(Stat::Grows)
             }
 
             fn after_grow_in_place_zeroed(
@@ -183,18 +329,21 @@ macro_rules! impl_callback_ref {
                 _new_layout: Layout,
                 _result: Result<usize, AllocError>,
             ) {
-                self.increment_stat(Stat::Grows, 1)
+                self.increment_stat(Stat::Grows)
             }
 
             #[inline]
             fn after_shrink(
                 &self,
-                _ptr: NonNull<u8>,
+                ptr: NonNull<u8>,
                 _old_layout: Layout,
                 _new_layout: Layout,
-                _result: Result<NonNull<[u8]>, AllocError>,
+                result: Result<NonNull<[u8]>, AllocError>,
             ) {
-                self.increment_stat(Stat::Shrinks, 1)
+                self.increment_stat(Stat::Shrinks);
+                if moved(ptr, result) {
+                    self.increment_stat(Stat::Moves)
+                }
             }
 
             #[inline]
@@ -205,19 +354,19 @@ macro_rules! impl_callback_ref {
                 _new_layout: Layout,
                 _result: Result<usize, AllocError>,
             ) {
-                self.increment_stat(Stat::Shrinks, 1)
+                self.increment_stat(Stat::Shrinks)
             }
 
             #[inline]
             fn after_owns(&self, _success: bool) {
-                self.increment_stat(Stat::Owns, 1)
+                self.increment_stat(Stat::Owns)
             }
         }
     };
 }
 
-impl_callback_ref!(Counter);
-impl_callback_ref!(AtomicCounter);
+impl_callback_ref!(CounterOf, Count);
+impl_callback_ref!(AtomicCounterOf, AtomicCount);
 
 #[repr(usize)]
 #[derive(Copy, Clone, PartialEq)]
@@ -241,8 +390,14 @@ enum FilteredStat {
     ShrinksInPlaceErr = 16,
     OwnsTrue = 17,
     OwnsFalse = 18,
+    GrowsMoved = 19,
+    ShrinksMoved = 20,
+    LiveBlocks = 21,
+    LiveBytes = 22,
+    PeakLiveBlocks = 23,
+    PeakLiveBytes = 24,
 }
-const FILTERED_STAT_COUNT: usize = 19;
+const FILTERED_STAT_COUNT: usize = 25;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum AllocInitFilter {
@@ -271,78 +426,251 @@ impl From<bool> for ResultFilter {
     }
 }
 
+/// Filters the live statistics by the size of the memory blocks.
+///
+/// Sizes are grouped in the powers of two of a [`SizeHistogram`]. A grown or shrunk memory block
+/// moves to the size class of its new size.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SizeClassFilter {
+    None,
+    /// The size class containing the given size.
+    Class(usize),
+}
+
+impl From<usize> for SizeClassFilter {
+    fn from(size: usize) -> Self {
+        Self::Class(size)
+    }
+}
+
+// The live statistics of a size class, in the same order as `FilteredStat::LiveBlocks` and the
+// following statistics.
+const LIVE_BLOCKS: usize = 0;
+const LIVE_BYTES: usize = 1;
+const PEAK_LIVE_BLOCKS: usize = 2;
+const LIVE_STAT_COUNT: usize = 4;
+
+fn live_index(stat: FilteredStat) -> usize {
+    stat as usize - FilteredStat::LiveBlocks as usize
+}
+
 /// A counter for collectiong and filtering statistics.
+///
+/// The count type `T` defaults to `u64` in [`FilteredCounter`].
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct FilteredCounter {
-    stats: [Cell<u64>; FILTERED_STAT_COUNT],
+pub struct FilteredCounterOf<T: Count> {
+    stats: [Cell<T>; FILTERED_STAT_COUNT],
+    size_classes: [[Cell<T>; LIVE_STAT_COUNT]; SIZE_CLASS_COUNT],
 }
 
-impl FilteredCounter {
-    fn increment_stat(&self, stat: FilteredStat, additional: u64) {
-        self.stats[stat as usize].set(self.stats[stat as usize].get() + additional)
+/// A counter for collectiong and filtering statistics using `u64`.
+pub type FilteredCounter = FilteredCounterOf<u64>;
+
+impl<T: Count> FilteredCounterOf<T> {
+    fn increment_stat(&self, stat: FilteredStat) {
+        let stat = &self.stats[stat as usize];
+        stat.set(stat.get().wrapping_add(T::ONE))
     }
-    fn get(&self, stat: FilteredStat) -> u64 {
+    fn get(&self, stat: FilteredStat) -> T {
         self.stats[stat as usize].get()
     }
+    fn get_live(&self, stat: FilteredStat, size: SizeClassFilter) -> T {
+        match size {
+            SizeClassFilter::None => self.get(stat),
+            SizeClassFilter::Class(size) => {
+                self.size_classes[size_class(size)][live_index(stat)].get()
+            }
+        }
+    }
+    fn live(&self, size: Option<usize>) -> &[Cell<T>] {
+        match size {
+            None => &self.stats[FilteredStat::LiveBlocks as usize..],
+            Some(size) => &self.size_classes[size_class(size)],
+        }
+    }
+    fn add_live_to(live: &[Cell<T>], blocks: T, bytes: T) {
+        for (index, value) in [(LIVE_BLOCKS, blocks), (LIVE_BYTES, bytes)] {
+            let live_value = live[index].get().wrapping_add(value);
+            live[index].set(live_value);
+            let peak = &live[index + PEAK_LIVE_BLOCKS];
+            peak.set(peak.get().max(live_value));
+        }
+    }
+    fn sub_live_from(live: &[Cell<T>], blocks: T, bytes: T) {
+        for (index, value) in [(LIVE_BLOCKS, blocks), (LIVE_BYTES, bytes)] {
+            live[index].set(live[index].get().wrapping_sub(value))
+        }
+    }
+    fn add_live(&self, size: usize) {
+        for live in [self.live(None), self.live(Some(size))] {
+            Self::add_live_to(live, T::ONE, T::from_usize(size));
+        }
+    }
+    fn sub_live(&self, size: usize) {
+        for live in [self.live(None), self.live(Some(size))] {
+            Self::sub_live_from(live, T::ONE, T::from_usize(size));
+        }
+    }
+    fn resize_live(&self, old_size: usize, new_size: usize) {
+        if new_size >= old_size {
+            Self::add_live_to(
+                self.live(None),
+                T::default(),
+                T::from_usize(new_size - old_size),
+            );
+        } else {
+            Self::sub_live_from(
+                self.live(None),
+                T::default(),
+                T::from_usize(old_size - new_size),
+            );
+        }
+        Self::sub_live_from(self.live(Some(old_size)), T::ONE, T::from_usize(old_size));
+        Self::add_live_to(self.live(Some(new_size)), T::ONE, T::from_usize(new_size));
+    }
+    fn clear_live(&self) {
+        for live in
+            iter::once(self.live(None)).chain(self.size_classes.iter().map(|live| &live[..]))
+        {
+            live[LIVE_BLOCKS].set(T::default());
+            live[LIVE_BYTES].set(T::default());
+        }
+    }
 }
 
-impl PartialEq<FilteredAtomicCounter> for FilteredCounter {
-    fn eq(&self, other: &FilteredAtomicCounter) -> bool {
-        self.stats
-            .iter()
-            .zip(other.stats.iter())
-            .all(|(lhs, rhs)| lhs.get() == rhs.load(Relaxed))
+impl<T: AtomicCount> PartialEq<FilteredAtomicCounterOf<T>> for FilteredCounterOf<T> {
+    fn eq(&self, other: &FilteredAtomicCounterOf<T>) -> bool {
+        other == self
     }
 }
 
 /// An atomic counter for collectiong and filtering statistics which can be shared between threads.
+///
+/// The count type `T` defaults to `u64` in [`FilteredAtomicCounter`].
 #[derive(Debug, Default)]
-pub struct FilteredAtomicCounter {
-    stats: [AtomicU64; FILTERED_STAT_COUNT],
+pub struct FilteredAtomicCounterOf<T: AtomicCount> {
+    stats: [T::Atomic; FILTERED_STAT_COUNT],
+    size_classes: [[T::Atomic; LIVE_STAT_COUNT]; SIZE_CLASS_COUNT],
 }
 
-impl FilteredAtomicCounter {
-    fn increment_stat(&self, stat: FilteredStat, additional: u64) {
-        self.stats[stat as usize].fetch_add(additional, Relaxed);
+/// An atomic counter for collectiong and filtering statistics using `u64`.
+#[cfg(target_has_atomic = "64")]
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "64")))]
+pub type FilteredAtomicCounter = FilteredAtomicCounterOf<u64>;
+
+impl<T: AtomicCount> FilteredAtomicCounterOf<T> {
+    fn increment_stat(&self, stat: FilteredStat) {
+        T::fetch_add(&self.stats[stat as usize], T::ONE);
     }
-    fn get(&self, stat: FilteredStat) -> u64 {
-        self.stats[stat as usize].load(Relaxed)
+    fn get(&self, stat: FilteredStat) -> T {
+        T::load(&self.stats[stat as usize])
+    }
+    fn get_live(&self, stat: FilteredStat, size: SizeClassFilter) -> T {
+        match size {
+            SizeClassFilter::None => self.get(stat),
+            SizeClassFilter::Class(size) => {
+                T::load(&self.size_classes[size_class(size)][live_index(stat)])
+            }
+        }
+    }
+    fn live(&self, size: Option<usize>) -> &[T::Atomic] {
+        match size {
+            None => &self.stats[FilteredStat::LiveBlocks as usize..],
+            Some(size) => &self.size_classes[size_class(size)],
+        }
+    }
+    fn add_live_to(live: &[T::Atomic], blocks: T, bytes: T) {
+        for (index, value) in [(LIVE_BLOCKS, blocks), (LIVE_BYTES, bytes)] {
+            let live_value = T::fetch_add(&live[index], value).wrapping_add(value);
+            T::fetch_max(&live[index + PEAK_LIVE_BLOCKS], live_value);
+        }
+    }
+    fn sub_live_from(live: &[T::Atomic], blocks: T, bytes: T) {
+        T::fetch_sub(&live[LIVE_BLOCKS], blocks);
+        T::fetch_sub(&live[LIVE_BYTES], bytes);
+    }
+    fn add_live(&self, size: usize) {
+        for live in [self.live(None), self.live(Some(size))] {
+            Self::add_live_to(live, T::ONE, T::from_usize(size));
+        }
+    }
+    fn sub_live(&self, size: usize) {
+        for live in [self.live(None), self.live(Some(size))] {
+            Self::sub_live_from(live, T::ONE, T::from_usize(size));
+        }
+    }
+    fn resize_live(&self, old_size: usize, new_size: usize) {
+        if new_size >= old_size {
+            Self::add_live_to(
+                self.live(None),
+                T::default(),
+                T::from_usize(new_size - old_size),
+            );
+        } else {
+            Self::sub_live_from(
+                self.live(None),
+                T::default(),
+                T::from_usize(old_size - new_size),
+            );
+        }
+        Self::sub_live_from(self.live(Some(old_size)), T::ONE, T::from_usize(old_size));
+        Self::add_live_to(self.live(Some(new_size)), T::ONE, T::from_usize(new_size));
+    }
+    fn clear_live(&self) {
+        for live in
+            iter::once(self.live(None)).chain(self.size_classes.iter().map(|live| &live[..]))
+        {
+            T::store(&live[LIVE_BLOCKS], T::default());
+            T::store(&live[LIVE_BYTES], T::default());
+        }
     }
 }
 
-impl PartialEq for FilteredAtomicCounter {
+impl<T: AtomicCount> PartialEq for FilteredAtomicCounterOf<T> {
     fn eq(&self, other: &Self) -> bool {
         self.stats
             .iter()
-            .zip(other.stats.iter())
-            .all(|(lhs, rhs)| lhs.load(Relaxed) == rhs.load(Relaxed))
+            .chain(self.size_classes.iter().flatten())
+            .zip(
+                other
+                    .stats
+                    .iter()
+                    .chain(other.size_classes.iter().flatten()),
+            )
+            .all(|(lhs, rhs)| T::load(lhs) == T::load(rhs))
     }
 }
 
-impl PartialEq<FilteredCounter> for FilteredAtomicCounter {
-    fn eq(&self, other: &FilteredCounter) -> bool {
+impl<T: AtomicCount> PartialEq<FilteredCounterOf<T>> for FilteredAtomicCounterOf<T> {
+    fn eq(&self, other: &FilteredCounterOf<T>) -> bool {
         self.stats
             .iter()
-            .zip(other.stats.iter())
-            .all(|(lhs, rhs)| lhs.load(Relaxed) == rhs.get())
+            .chain(self.size_classes.iter().flatten())
+            .zip(
+                other
+                    .stats
+                    .iter()
+                    .chain(other.size_classes.iter().flatten()),
+            )
+            .all(|(lhs, rhs)| T::load(lhs) == rhs.get())
     }
 }
 
 macro_rules! impl_filtered_callback_ref {
-    ($tt:tt) => {
-        impl $tt {
-            /// Returns the total number of `alloc` calls.
+    ($tt:ident, $bound:ident) => {
+        impl<T: $bound> $tt<T> {
+            /// Returns the total number of `allocate` calls.
             #[inline]
-            pub fn num_allocates(&self) -> u64 {
+            pub fn num_allocates(&self) -> T {
                 self.num_allocates_filter(AllocInitFilter::None, ResultFilter::None)
             }
 
-            /// Returns the filtered number of `alloc` calls.
+            /// Returns the filtered number of `allocate` calls.
             pub fn num_allocates_filter(
                 &self,
                 init: impl Into<AllocInitFilter>,
                 result: impl Into<ResultFilter>,
-            ) -> u64 {
+            ) -> T {
                 match (init.into(), result.into()) {
                     (AllocInitFilter::Uninitialized, ResultFilter::Ok) => {
                         self.get(FilteredStat::AllocsUninitializedOk)
@@ -356,26 +684,24 @@ macro_rules! impl_filtered_callback_ref {
                     (AllocInitFilter::Zeroed, ResultFilter::Err) => {
                         self.get(FilteredStat::AllocsZeroedErr)
                     }
-                    (AllocInitFilter::None, result) => {
-                        self.num_allocates_filter(AllocInitFilter::Uninitialized, result)
-                            + self.num_allocates_filter(AllocInitFilter::Zeroed, result)
-                    }
-                    (i, ResultFilter::None) => {
-                        self.num_allocates_filter(i, ResultFilter::Ok)
-                            + self.num_allocates_filter(i, ResultFilter::Err)
-                    }
+                    (AllocInitFilter::None, result) => self
+                        .num_allocates_filter(AllocInitFilter::Uninitialized, result)
+                        .wrapping_add(self.num_allocates_filter(AllocInitFilter::Zeroed, result)),
+                    (i, ResultFilter::None) => self
+                        .num_allocates_filter(i, ResultFilter::Ok)
+                        .wrapping_add(self.num_allocates_filter(i, ResultFilter::Err)),
                 }
             }
 
-            /// Returns the total number of `dealloc` calls.
+            /// Returns the total number of `deallocate` calls.
             #[inline]
-            pub fn num_deallocates(&self) -> u64 {
+            pub fn num_deallocates(&self) -> T {
                 self.get(FilteredStat::Deallocs)
             }
 
             /// Returns the total number of `grow` calls.
             #[inline]
-            pub fn num_grows(&self) -> u64 {
+            pub fn num_grows(&self) -> T {
                 self.num_grows_filter(
                     ReallocPlacementFilter::None,
                     AllocInitFilter::None,
@@ -389,7 +715,7 @@ macro_rules! impl_filtered_callback_ref {
                 placement: impl Into<ReallocPlacementFilter>,
                 init: impl Into<AllocInitFilter>,
                 result: impl Into<ResultFilter>,
-            ) -> u64 {
+            ) -> T {
                 match (placement.into(), init.into(), result.into()) {
                     (
                         ReallocPlacementFilter::MayMove,
@@ -431,33 +757,49 @@ macro_rules! impl_filtered_callback_ref {
                         AllocInitFilter::Zeroed,
                         ResultFilter::Err,
                     ) => self.get(FilteredStat::GrowsInPlaceZeroedErr),
-                    (ReallocPlacementFilter::None, i, result) => {
-                        self.num_grows_filter(ReallocPlacementFilter::MayMove, i, result)
-                            + self.num_grows_filter(ReallocPlacementFilter::InPlace, i, result)
-                    }
-                    (p, AllocInitFilter::None, result) => {
-                        self.num_grows_filter(p, AllocInitFilter::Uninitialized, result)
-                            + self.num_grows_filter(p, AllocInitFilter::Zeroed, result)
-                    }
-                    (p, i, ResultFilter::None) => {
-                        self.num_grows_filter(p, i, ResultFilter::Ok)
-                            + self.num_grows_filter(p, i, ResultFilter::Err)
-                    }
+                    (ReallocPlacementFilter::None, i, result) => self
+                        .num_grows_filter(ReallocPlacementFilter::MayMove, i, result)
+                        .wrapping_add(self.num_grows_filter(
+                            ReallocPlacementFilter::InPlace,
+                            i,
+                            result,
+                        )),
+                    (p, AllocInitFilter::None, result) => self
+                        .num_grows_filter(p, AllocInitFilter::Uninitialized, result)
+                        .wrapping_add(self.num_grows_filter(p, AllocInitFilter::Zeroed, result)),
+                    (p, i, ResultFilter::None) => self
+                        .num_grows_filter(p, i, ResultFilter::Ok)
+                        .wrapping_add(self.num_grows_filter(p, i, ResultFilter::Err)),
                 }
             }
 
+            /// Returns the number of `grow` calls, which moved the memory block.
+            ///
+            /// In contrast to [`ReallocPlacementFilter::MayMove`], this excludes calls, which
+            /// returned the same pointer.
+            #[inline]
+            pub fn num_grows_moved(&self) -> T {
+                self.get(FilteredStat::GrowsMoved)
+            }
+
             /// Returns the total number of `shrink` calls.
             #[inline]
-            pub fn num_shrinks(&self) -> u64 {
+            pub fn num_shrinks(&self) -> T {
                 self.num_shrinks_filter(ReallocPlacementFilter::None, ResultFilter::None)
             }
 
+            /// Returns the number of `shrink` calls, which moved the memory block.
+            #[inline]
+            pub fn num_shrinks_moved(&self) -> T {
+                self.get(FilteredStat::ShrinksMoved)
+            }
+
             /// Returns the filtered number of `shrink` calls.
             pub fn num_shrinks_filter(
                 &self,
                 placement: ReallocPlacementFilter,
                 result: impl Into<ResultFilter>,
-            ) -> u64 {
+            ) -> T {
                 match (placement, result.into()) {
                     (ReallocPlacementFilter::MayMove, ResultFilter::Ok) => {
                         self.get(FilteredStat::ShrinksMayMoveOk)
@@ -471,152 +813,235 @@ macro_rules! impl_filtered_callback_ref {
                     (ReallocPlacementFilter::InPlace, ResultFilter::Err) => {
                         self.get(FilteredStat::ShrinksInPlaceErr)
                     }
-                    (ReallocPlacementFilter::None, result) => {
-                        self.num_shrinks_filter(ReallocPlacementFilter::MayMove, result)
-                            + self.num_shrinks_filter(ReallocPlacementFilter::InPlace, result)
-                    }
-                    (p, ResultFilter::None) => {
-                        self.num_shrinks_filter(p, ResultFilter::Ok)
-                            + self.num_shrinks_filter(p, ResultFilter::Err)
-                    }
+                    (ReallocPlacementFilter::None, result) => self
+                        .num_shrinks_filter(ReallocPlacementFilter::MayMove, result)
+                        .wrapping_add(
+                            self.num_shrinks_filter(ReallocPlacementFilter::InPlace, result),
+                        ),
+                    (p, ResultFilter::None) => self
+                        .num_shrinks_filter(p, ResultFilter::Ok)
+                        .wrapping_add(self.num_shrinks_filter(p, ResultFilter::Err)),
                 }
             }
 
             /// Returns the total number of `owns` calls.
             #[inline]
-            pub fn num_owns(&self) -> u64 {
-                self.num_owns_filter(true) + self.num_owns_filter(false)
+            pub fn num_owns(&self) -> T {
+                self.num_owns_filter(true)
+                    .wrapping_add(self.num_owns_filter(false))
             }
 
             /// Returns the filtered number of `owns` calls.
-            pub fn num_owns_filter(&self, success: bool) -> u64 {
+            pub fn num_owns_filter(&self, success: bool) -> T {
                 if success {
                     self.get(FilteredStat::OwnsTrue)
                 } else {
                     self.get(FilteredStat::OwnsFalse)
                 }
             }
+
+            /// Returns the number of memory blocks, which are currently allocated.
+            ///
+            /// This and the other live statistics measure blocks by their requested layout.
+            /// Blocks returned by `allocate_all` are measured by their actual length and
+            /// `deallocate_all` resets the live statistics to zero.
+            #[inline]
+            pub fn live_blocks(&self) -> T {
+                self.get(FilteredStat::LiveBlocks)
+            }
+
+            /// Returns the filtered number of memory blocks, which are currently allocated.
+            pub fn live_blocks_filter(&self, size: impl Into<SizeClassFilter>) -> T {
+                self.get_live(FilteredStat::LiveBlocks, size.into())
+            }
+
+            /// Returns the number of bytes, which are currently allocated.
+            #[inline]
+            pub fn live_bytes(&self) -> T {
+                self.get(FilteredStat::LiveBytes)
+            }
+
+            /// Returns the filtered number of bytes, which are currently allocated.
+            pub fn live_bytes_filter(&self, size: impl Into<SizeClassFilter>) -> T {
+                self.get_live(FilteredStat::LiveBytes, size.into())
+            }
+
+            /// Returns the maximum number of memory blocks, which were allocated at the same time.
+            ///
+            /// This is the high-water mark of [`live_blocks`], e.g. the number of slots a pool
+            /// needs to serve the observed workload.
+            ///
+            /// [`live_blocks`]: Self::live_blocks
+            #[inline]
+            pub fn peak_live_blocks(&self) -> T {
+                self.get(FilteredStat::PeakLiveBlocks)
+            }
+
+            /// Returns the maximum number of memory blocks in one size class, which were
+            /// allocated at the same time.
+            ///
+            /// Each size class has its own high-water mark, so the peaks of different size classes
+            /// may have been reached at different times.
+            pub fn peak_live_blocks_filter(&self, size: impl Into<SizeClassFilter>) -> T {
+                self.get_live(FilteredStat::PeakLiveBlocks, size.into())
+            }
+
+            /// Returns the maximum number of bytes, which were allocated at the same time.
+            ///
+            /// This is the high-water mark of [`live_bytes`].
+            ///
+            /// [`live_bytes`]: Self::live_bytes
+            #[inline]
+            pub fn peak_live_bytes(&self) -> T {
+                self.get(FilteredStat::PeakLiveBytes)
+            }
+
+            /// Returns the maximum number of bytes in one size class, which were allocated at the
+            /// same time.
+            pub fn peak_live_bytes_filter(&self, size: impl Into<SizeClassFilter>) -> T {
+                self.get_live(FilteredStat::PeakLiveBytes, size.into())
+            }
         }
 
-        unsafe impl CallbackRef for $tt {
+        unsafe impl<T: $bound> CallbackRef for $tt<T> {
             #[inline]
-            fn after_allocate(&self, _layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::AllocsUninitializedOk, 1)
+                    self.increment_stat(FilteredStat::AllocsUninitializedOk);
+                    self.add_live(layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::AllocsUninitializedErr, 1)
+                    self.increment_stat(FilteredStat::AllocsUninitializedErr)
                 }
             }
 
             #[inline]
             fn after_allocate_zeroed(
                 &self,
-                _layout: Layout,
+                layout: Layout,
                 result: Result<NonNull<[u8]>, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::AllocsZeroedOk, 1)
+                    self.increment_stat(FilteredStat::AllocsZeroedOk);
+                    self.add_live(layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::AllocsZeroedErr, 1)
+                    self.increment_stat(FilteredStat::AllocsZeroedErr)
                 }
             }
 
             #[inline]
             fn after_allocate_all(&self, result: Result<NonNull<[u8]>, AllocError>) {
-                if result.is_ok() {
-                    self.increment_stat(FilteredStat::AllocsUninitializedOk, 1)
+                if let Ok(memory) = result {
+                    self.increment_stat(FilteredStat::AllocsUninitializedOk);
+                    self.add_live(memory.len())
                 } else {
-                    self.increment_stat(FilteredStat::AllocsUninitializedErr, 1)
+                    self.increment_stat(FilteredStat::AllocsUninitializedErr)
                 }
             }
 
             #[inline]
             fn after_allocate_all_zeroed(&self, result: Result<NonNull<[u8]>, AllocError>) {
-                if result.is_ok() {
-                    self.increment_stat(FilteredStat::AllocsZeroedOk, 1)
+                if let Ok(memory) = result {
+                    self.increment_stat(FilteredStat::AllocsZeroedOk);
+                    self.add_live(memory.len())
                 } else {
-                    self.increment_stat(FilteredStat::AllocsZeroedErr, 1)
+                    self.increment_stat(FilteredStat::AllocsZeroedErr)
                 }
             }
 
             #[inline]
-            fn before_deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
-                self.increment_stat(FilteredStat::Deallocs, 1);
+            fn before_deallocate(&self, _ptr: NonNull<u8>, layout: Layout) {
+                self.increment_stat(FilteredStat::Deallocs);
+                self.sub_live(layout.size());
             }
 
             #[inline]
             fn before_deallocate_all(&self) {
-                self.increment_stat(FilteredStat::Deallocs, 1);
+                self.increment_stat(FilteredStat::Deallocs);
+                self.clear_live();
             }
 
             fn after_grow(
                 &self,
-                _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
                 result: Result<NonNull<[u8]>, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::GrowsMayMoveUninitializedOk, 1)
+                    self.increment_stat(FilteredStat::GrowsMayMoveUninitializedOk);
+                    self.resize_live(old_layout.size(), new_layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::GrowsMayMoveUninitializedErr, 1)
+                    self.increment_stat(FilteredStat::GrowsMayMoveUninitializedErr)
+                }
+                if moved(ptr, result) {
+                    self.increment_stat(FilteredStat::GrowsMoved)
                 }
             }
 
             fn after_grow_zeroed(
                 &self,
-                _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
                 result: Result<NonNull<[u8]>, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::GrowsMayMoveZeroedOk, 1)
+                    self.increment_stat(FilteredStat::GrowsMayMoveZeroedOk);
+                    self.resize_live(old_layout.size(), new_layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::GrowsMayMoveZeroedErr, 1)
+                    self.increment_stat(FilteredStat::GrowsMayMoveZeroedErr)
+                }
+                if moved(ptr, result) {
+                    self.increment_stat(FilteredStat::GrowsMoved)
                 }
             }
 
             fn after_grow_in_place(
                 &self,
                 _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                old_layout: Layout,
+                new_layout: Layout,
                 result: Result<usize, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::GrowsInPlaceUninitializedOk, 1)
+                    self.increment_stat(FilteredStat::GrowsInPlaceUninitializedOk);
+                    self.resize_live(old_layout.size(), new_layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::GrowsInPlaceUninitializedErr, 1)
+                    self.increment_stat(FilteredStat::GrowsInPlaceUninitializedErr)
                 }
             }
 
             fn after_grow_in_place_zeroed(
                 &self,
                 _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                old_layout: Layout,
+                new_layout: Layout,
                 result: Result<usize, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::GrowsInPlaceZeroedOk, 1)
+                    self.increment_stat(FilteredStat::GrowsInPlaceZeroedOk);
+                    self.resize_live(old_layout.size(), new_layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::GrowsInPlaceZeroedErr, 1)
+                    self.increment_stat(FilteredStat::GrowsInPlaceZeroedErr)
                 }
             }
 
             #[inline]
             fn after_shrink(
                 &self,
-                _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
                 result: Result<NonNull<[u8]>, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::ShrinksMayMoveOk, 1)
+                    self.increment_stat(FilteredStat::ShrinksMayMoveOk);
+                    self.resize_live(old_layout.size(), new_layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::ShrinksMayMoveErr, 1)
+                    self.increment_stat(FilteredStat::ShrinksMayMoveErr)
+                }
+                if moved(ptr, result) {
+                    self.increment_stat(FilteredStat::ShrinksMoved)
                 }
             }
 
@@ -624,126 +1049,2378 @@ macro_rules! impl_filtered_callback_ref {
             fn after_shrink_in_place(
                 &self,
                 _ptr: NonNull<u8>,
-                _old_layout: Layout,
-                _new_layout: Layout,
+                old_layout: Layout,
+                new_layout: Layout,
                 result: Result<usize, AllocError>,
             ) {
                 if result.is_ok() {
-                    self.increment_stat(FilteredStat::ShrinksInPlaceOk, 1)
+                    self.increment_stat(FilteredStat::ShrinksInPlaceOk);
+                    self.resize_live(old_layout.size(), new_layout.size())
                 } else {
-                    self.increment_stat(FilteredStat::ShrinksInPlaceErr, 1)
+                    self.increment_stat(FilteredStat::ShrinksInPlaceErr)
                 }
             }
 
             #[inline]
             fn after_owns(&self, success: bool) {
                 if success {
-                    self.increment_stat(FilteredStat::OwnsTrue, 1)
+                    self.increment_stat(FilteredStat::OwnsTrue)
                 } else {
-                    self.increment_stat(FilteredStat::OwnsFalse, 1)
+                    self.increment_stat(FilteredStat::OwnsFalse)
                 }
             }
         }
     };
 }
-impl_filtered_callback_ref!(FilteredCounter);
-impl_filtered_callback_ref!(FilteredAtomicCounter);
+impl_filtered_callback_ref!(FilteredCounterOf, Count);
+impl_filtered_callback_ref!(FilteredAtomicCounterOf, AtomicCount);
 
-#[cfg(test)]
-mod tests {
-    use super::{AtomicCounter, Counter, FilteredAtomicCounter, FilteredCounter};
-    use crate::{
-        helper::tracker,
-        region::Region,
-        CallbackRef,
-        Chunk,
-        Owns,
-        Proxy,
-        ReallocateInPlace,
-    };
+pub use self::histogram::{advise, Advice, Bucket, SizeHistogram};
+use self::histogram::{bucket_index as size_class, BUCKET_COUNT as SIZE_CLASS_COUNT};
+
+mod histogram {
+    use crate::CallbackRef;
     use core::{
-        alloc::{AllocRef, Layout},
-        mem::MaybeUninit,
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        cmp,
+        mem,
         ptr::NonNull,
     };
 
-    #[allow(clippy::too_many_lines)]
-    fn run_suite(callbacks: &impl CallbackRef) {
-        let mut region = [MaybeUninit::new(0); 64];
-        let region = tracker(Proxy {
-            alloc: Chunk::<Region, 2>(Region::new(&mut region)),
-            callbacks,
-        });
+    pub(super) const BUCKET_COUNT: usize = 32;
 
-        assert!(region.alloc(Layout::new::<[u8; 256]>()).is_err());
-        assert!(region.alloc_zeroed(Layout::new::<[u8; 256]>()).is_err());
-        assert!(!region.owns(NonNull::slice_from_raw_parts(NonNull::dangling(), 0)));
+    /// Returns the index of the smallest power of two greater than or equal to `size`.
+    pub(super) fn bucket_index(size: usize) -> usize {
+        if size <= 1 {
+            return 0;
+        }
+        let bits = mem::size_of::<usize>() * 8 - (size - 1).leading_zeros() as usize;
+        cmp::min(bits, BUCKET_COUNT - 1)
+    }
 
-        unsafe {
-            let memory = region.alloc(Layout::new::<[u8; 4]>()).unwrap();
-            let memory_tmp = region.alloc_zeroed(Layout::new::<[u8; 4]>()).unwrap();
-            assert!(
-                region
-                    .shrink_in_place(
-                        memory.as_non_null_ptr(),
-                        Layout::new::<[u8; 4]>(),
-                        Layout::new::<[u8; 2]>(),
-                    )
-                    .is_err()
-            );
-            region.dealloc(memory_tmp.as_non_null_ptr(), Layout::new::<[u8; 4]>());
+    fn max_size(index: usize) -> usize {
+        if index == BUCKET_COUNT - 1 {
+            usize::MAX
+        } else {
+            1 << index
+        }
+    }
 
-            assert!(
-                region
-                    .grow_zeroed(
-                        memory.as_non_null_ptr(),
-                        Layout::new::<[u8; 4]>(),
-                        Layout::new::<[u8; 80]>()
-                    )
-                    .is_err()
-            );
-            assert!(
-                region
-                    .grow_in_place_zeroed(
-                        memory.as_non_null_ptr(),
-                        Layout::new::<[u8; 4]>(),
-                        Layout::new::<[u8; 80]>()
-                    )
-                    .is_err()
-            );
-            assert!(
-                region
-                    .grow(
-                        memory.as_non_null_ptr(),
-                        Layout::new::<[u8; 4]>(),
-                        Layout::new::<[u8; 80]>()
-                    )
-                    .is_err()
-            );
-            assert!(
-                region
-                    .grow_in_place(
-                        memory.as_non_null_ptr(),
-                        Layout::new::<[u8; 4]>(),
-                        Layout::new::<[u8; 80]>()
-                    )
-                    .is_err()
-            );
-            let memory = region
-                .grow_zeroed(
-                    memory.as_non_null_ptr(),
-                    Layout::new::<[u8; 4]>(),
-                    Layout::new::<[u8; 4]>(),
-                )
-                .unwrap();
-            let memory = region
-                .grow(
-                    memory.as_non_null_ptr(),
-                    Layout::new::<[u8; 4]>(),
-                    Layout::new::<[u8; 4]>(),
-                )
-                .unwrap();
-            let memory = region
+    /// The requests of a [`SizeHistogram`] falling into one power of two.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Bucket {
+        /// The largest size in this bucket. The smallest size is one more than half of it.
+        pub max_size: usize,
+        /// The number of requests in this bucket.
+        pub count: u64,
+        /// The sum of the sizes of all requests in this bucket.
+        pub bytes: u64,
+    }
+
+    /// Collects the sizes of successfully allocated memory blocks in buckets of powers of two.
+    ///
+    /// Allocations as well as the new sizes of grown or shrunk memory blocks are recorded. Sizes
+    /// above 1 GiB share the last bucket. The collected data can be passed to [`advise`] to tune
+    /// the parameters of an allocator composition.
+    #[derive(Debug, Default, PartialEq, Eq)]
+    pub struct SizeHistogram {
+        counts: [Cell<u64>; BUCKET_COUNT],
+        bytes: [Cell<u64>; BUCKET_COUNT],
+    }
+
+    impl SizeHistogram {
+        fn record(&self, size: usize) {
+            let index = bucket_index(size);
+            let count = &self.counts[index];
+            count.set(count.get().wrapping_add(1));
+            let bytes = &self.bytes[index];
+            bytes.set(bytes.get().wrapping_add(size as u64));
+        }
+
+        fn bucket(&self, index: usize) -> Bucket {
+            Bucket {
+                max_size: max_size(index),
+                count: self.counts[index].get(),
+                bytes: self.bytes[index].get(),
+            }
+        }
+
+        /// Returns the number of recorded requests.
+        pub fn num_records(&self) -> u64 {
+            self.counts
+                .iter()
+                .fold(0, |sum, count| sum.wrapping_add(count.get()))
+        }
+
+        /// Returns the non-empty buckets in ascending order of their sizes.
+        pub fn buckets(&self) -> impl Iterator<Item = Bucket> + '_ {
+            (0..BUCKET_COUNT)
+                .map(move |index| self.bucket(index))
+                .filter(|bucket| bucket.count > 0)
+        }
+    }
+
+    unsafe impl CallbackRef for SizeHistogram {
+        #[inline]
+        fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            if result.is_ok() {
+                self.record(layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            if result.is_ok() {
+                self.record(layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_grow(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.record(new_layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_grow_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.record(new_layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_grow_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.record(new_layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.record(new_layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_shrink(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.record(new_layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            if result.is_ok() {
+                self.record(new_layout.size())
+            }
+        }
+    }
+
+    /// Parameters for an allocator composition suggested by [`advise`].
+    ///
+    /// Requests up to `segregate_threshold` bytes are called *small*.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Advice {
+        /// The largest block size for [`Chunk`], which wastes at most an eighth of the bytes
+        /// requested by small requests.
+        ///
+        /// [`Chunk`]: crate::Chunk
+        pub chunk_size: usize,
+        /// The smallest power of two for [`Segregate`], which covers at least 90 % of all
+        /// requests.
+        ///
+        /// [`Segregate`]: crate::Segregate
+        pub segregate_threshold: usize,
+        /// Block sizes for pools of fixed-size blocks, one for each of the most frequent buckets
+        /// of small requests in ascending order. Unused entries are zero.
+        pub pool_block_sizes: [usize; Advice::POOL_COUNT],
+    }
+
+    impl Advice {
+        /// The maximum number of suggested pool block sizes.
+        pub const POOL_COUNT: usize = 4;
+    }
+
+    /// Suggests parameters for [`Chunk`], [`Segregate`], and pools, which fit the sizes collected
+    /// by `histogram`.
+    ///
+    /// The sizes within a bucket are approximated by their mean. An empty histogram results in the
+    /// smallest possible parameters.
+    ///
+    /// [`Chunk`]: crate::Chunk
+    /// [`Segregate`]: crate::Segregate
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api, slice_ptr_get)]
+    ///
+    /// use alloc_compose::{stats, CallbackRef, Proxy};
+    /// use std::alloc::{Allocator, Global, Layout};
+    ///
+    /// let histogram = stats::SizeHistogram::default();
+    /// let alloc = Proxy {
+    ///     alloc: Global,
+    ///     callbacks: histogram.by_ref(),
+    /// };
+    ///
+    /// for _ in 0..10 {
+    ///     let memory = alloc.allocate(Layout::new::<[u64; 3]>())?;
+    ///     unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u64; 3]>()) };
+    /// }
+    ///
+    /// let advice = stats::advise(&histogram);
+    /// assert_eq!(advice.chunk_size, 8);
+    /// assert_eq!(advice.segregate_threshold, 32);
+    /// assert_eq!(advice.pool_block_sizes, [32, 0, 0, 0]);
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    pub fn advise(histogram: &SizeHistogram) -> Advice {
+        // 90 % of the records, rounded up
+        let total = histogram.num_records();
+        let target = total - total / 10;
+
+        let mut threshold_index = 0;
+        let mut covered = 0_u64;
+        for index in 0..BUCKET_COUNT {
+            threshold_index = index;
+            covered = covered.saturating_add(histogram.counts[index].get());
+            if covered >= target {
+                break;
+            }
+        }
+
+        let small = || (0..=threshold_index).map(|index| histogram.bucket(index));
+        let requested = small().fold(0_u64, |sum, bucket| sum.saturating_add(bucket.bytes));
+        let waste = |chunk_size: u64| -> u64 {
+            small()
+                .filter(|bucket| bucket.count > 0)
+                .fold(0, |sum, bucket| {
+                    let average = bucket.bytes / bucket.count;
+                    let rounded = average.saturating_add(chunk_size - 1) & !(chunk_size - 1);
+                    let waste = bucket
+                        .count
+                        .saturating_mul(rounded)
+                        .saturating_sub(bucket.bytes);
+                    sum.saturating_add(waste)
+                })
+        };
+        let mut chunk_size = 1;
+        for index in 1..=cmp::min(threshold_index, BUCKET_COUNT - 2) {
+            let candidate = max_size(index);
+            if waste(candidate as u64).saturating_mul(8) > requested {
+                break;
+            }
+            chunk_size = candidate;
+        }
+
+        let mut indices = [0; BUCKET_COUNT];
+        for (i, index) in indices.iter_mut().enumerate() {
+            *index = i;
+        }
+        let indices = &mut indices[..=threshold_index];
+        indices.sort_unstable_by_key(|&index| (cmp::Reverse(histogram.counts[index].get()), index));
+        let mut pool_block_sizes = [0; Advice::POOL_COUNT];
+        let mut pools = 0;
+        for &index in indices.iter().take(Advice::POOL_COUNT) {
+            if histogram.counts[index].get() > 0 {
+                pool_block_sizes[pools] = max_size(index);
+                pools += 1;
+            }
+        }
+        pool_block_sizes[..pools].sort_unstable();
+
+        Advice {
+            chunk_size,
+            segregate_threshold: max_size(threshold_index),
+            pool_block_sizes,
+        }
+    }
+}
+
+pub use self::slack::Slack;
+
+mod slack {
+    use crate::CallbackRef;
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        cmp,
+        ptr::NonNull,
+    };
+
+    /// Collects the bytes wasted by returning larger memory blocks than requested.
+    ///
+    /// Allocators like [`Chunk`] round requests up, and [`Segregate`] passes them to allocators
+    /// which may do so. The difference between the returned length and the requested size is
+    /// called *slack*. Every successful allocation, and every successful resize, records its
+    /// slack, so the total and the maximum slack show whether a chunk size costs too much
+    /// internal fragmentation. Wrapping each side of a [`Segregate`] in its own [`Proxy`] keeps
+    /// the statistics apart.
+    ///
+    /// [`Chunk`]: crate::Chunk
+    /// [`Segregate`]: crate::Segregate
+    /// [`Proxy`]: crate::Proxy
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{stats, CallbackRef, Chunk, Proxy};
+    /// use std::alloc::{Allocator, Layout, System};
+    ///
+    /// let slack = stats::Slack::default();
+    /// let alloc = Proxy {
+    ///     alloc: Chunk::<_, 64>(System),
+    ///     callbacks: slack.by_ref(),
+    /// };
+    ///
+    /// alloc.allocate(Layout::new::<[u8; 10]>())?;
+    /// alloc.allocate(Layout::new::<[u8; 60]>())?;
+    /// assert_eq!(slack.requested_bytes(), 70);
+    /// assert_eq!(slack.total_slack(), 54 + 4);
+    /// assert_eq!(slack.max_slack(), 54);
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    #[derive(Debug, Default, PartialEq, Eq)]
+    pub struct Slack {
+        records: Cell<u64>,
+        requested: Cell<u64>,
+        total: Cell<u64>,
+        max: Cell<usize>,
+    }
+
+    impl Slack {
+        fn record(&self, requested: usize, len: usize) {
+            let slack = len.saturating_sub(requested);
+            self.records.set(self.records.get().wrapping_add(1));
+            self.requested
+                .set(self.requested.get().wrapping_add(requested as u64));
+            self.total.set(self.total.get().wrapping_add(slack as u64));
+            self.max.set(cmp::max(self.max.get(), slack));
+        }
+
+        fn record_result(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            if let Ok(memory) = result {
+                self.record(layout.size(), memory.len())
+            }
+        }
+
+        fn record_in_place(&self, layout: Layout, result: Result<usize, AllocError>) {
+            if let Ok(len) = result {
+                self.record(layout.size(), len)
+            }
+        }
+
+        /// Returns the number of recorded allocations and resizes.
+        pub fn num_records(&self) -> u64 {
+            self.records.get()
+        }
+
+        /// Returns the sum of the requested sizes.
+        pub fn requested_bytes(&self) -> u64 {
+            self.requested.get()
+        }
+
+        /// Returns the sum of the slack of all records.
+        pub fn total_slack(&self) -> u64 {
+            self.total.get()
+        }
+
+        /// Returns the largest slack of a single record.
+        pub fn max_slack(&self) -> usize {
+            self.max.get()
+        }
+    }
+
+    unsafe impl CallbackRef for Slack {
+        #[inline]
+        fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record_result(layout, result)
+        }
+
+        #[inline]
+        fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record_result(layout, result)
+        }
+
+        #[inline]
+        fn after_grow(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record_result(new_layout, result)
+        }
+
+        #[inline]
+        fn after_grow_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record_result(new_layout, result)
+        }
+
+        #[inline]
+        fn after_grow_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record_in_place(new_layout, result)
+        }
+
+        #[inline]
+        fn after_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record_in_place(new_layout, result)
+        }
+
+        #[inline]
+        fn after_shrink(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record_result(new_layout, result)
+        }
+
+        #[inline]
+        fn after_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record_in_place(new_layout, result)
+        }
+    }
+}
+
+pub use self::alignment::AlignmentAsserter;
+
+mod alignment {
+    use crate::CallbackRef;
+    use core::{alloc::Layout, cell::Cell, fmt, mem, ptr::NonNull};
+
+    const NUM_ALIGNMENTS: usize = mem::size_of::<usize>() * 8;
+
+    /// Counts requests per alignment and asserts, that no request exceeds a maximum alignment.
+    ///
+    /// Some allocators only support a limited alignment, e.g. a pool of 16-byte blocks. An
+    /// over-aligned type reaching such an allocator either fails at runtime or has to be routed
+    /// elsewhere, which is easily missed. Created with [`new`], the asserter panics at the
+    /// request exceeding `max_align`. Created with [`counting`], it only counts those requests.
+    ///
+    /// Allocations and reallocations, including the in-place variants, are counted by the
+    /// alignment of the requested layout.
+    ///
+    /// [`new`]: Self::new
+    /// [`counting`]: Self::counting
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{stats::AlignmentAsserter, CallbackRef, Proxy};
+    /// use std::alloc::{Allocator, Layout, System};
+    ///
+    /// let alignments = AlignmentAsserter::counting(16);
+    /// let alloc = Proxy {
+    ///     alloc: System,
+    ///     callbacks: alignments.by_ref(),
+    /// };
+    ///
+    /// alloc.allocate(Layout::new::<u64>())?;
+    /// alloc.allocate(Layout::new::<u64>())?;
+    /// alloc.allocate(Layout::from_size_align(64, 64).unwrap())?;
+    /// assert_eq!(alignments.num_requests(8), 2);
+    /// assert_eq!(alignments.num_requests(64), 1);
+    /// assert_eq!(alignments.num_exceeding(), 1);
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    ///
+    /// Asserting the alignment:
+    ///
+    /// ```rust,should_panic
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{stats::AlignmentAsserter, CallbackRef, Proxy};
+    /// use std::alloc::{Allocator, Layout, System};
+    ///
+    /// let alloc = Proxy {
+    ///     alloc: System,
+    ///     callbacks: AlignmentAsserter::new(16),
+    /// };
+    ///
+    /// // panics
+    /// alloc.allocate(Layout::from_size_align(64, 64).unwrap());
+    /// ```
+    pub struct AlignmentAsserter {
+        max_align: usize,
+        panic: bool,
+        requests: [Cell<u64>; NUM_ALIGNMENTS],
+        exceeding: Cell<u64>,
+    }
+
+    impl AlignmentAsserter {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: Cell<u64> = Cell::new(0);
+
+        const fn with_panic(max_align: usize, panic: bool) -> Self {
+            Self {
+                max_align,
+                panic,
+                requests: [Self::ZERO; NUM_ALIGNMENTS],
+                exceeding: Cell::new(0),
+            }
+        }
+
+        /// Creates an asserter, which panics when a request is aligned to more than `max_align`.
+        pub const fn new(max_align: usize) -> Self {
+            Self::with_panic(max_align, true)
+        }
+
+        /// Creates an asserter, which counts requests aligned to more than `max_align` instead of
+        /// panicking.
+        pub const fn counting(max_align: usize) -> Self {
+            Self::with_panic(max_align, false)
+        }
+
+        /// Returns the maximum expected alignment.
+        pub const fn max_align(&self) -> usize {
+            self.max_align
+        }
+
+        /// Returns the number of requests aligned to `align`.
+        ///
+        /// Returns zero if `align` is not a power of two.
+        pub fn num_requests(&self, align: usize) -> u64 {
+            if align.is_power_of_two() {
+                self.requests[align.trailing_zeros() as usize].get()
+            } else {
+                0
+            }
+        }
+
+        /// Returns the number of requests aligned to more than [`max_align`].
+        ///
+        /// [`max_align`]: Self::max_align
+        pub fn num_exceeding(&self) -> u64 {
+            self.exceeding.get()
+        }
+
+        /// Returns an iterator over all requested alignments and their number of requests.
+        pub fn alignments(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+            self.requests
+                .iter()
+                .enumerate()
+                .map(|(i, requests)| (1 << i, requests.get()))
+                .filter(|&(_, requests)| requests != 0)
+        }
+
+        #[track_caller]
+        fn record(&self, layout: Layout) {
+            let requests = &self.requests[layout.align().trailing_zeros() as usize];
+            requests.set(requests.get() + 1);
+            if layout.align() > self.max_align {
+                self.exceeding.set(self.exceeding.get() + 1);
+                if self.panic {
+                    panic!(
+                        "Requested alignment of {} exceeds the maximum alignment of {}",
+                        layout.align(),
+                        self.max_align
+                    );
+                }
+            }
+        }
+    }
+
+    impl fmt::Debug for AlignmentAsserter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            struct Alignments<'a>(&'a AlignmentAsserter);
+
+            impl fmt::Debug for Alignments<'_> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_map().entries(self.0.alignments()).finish()
+                }
+            }
+
+            f.debug_struct("AlignmentAsserter")
+                .field("max_align", &self.max_align)
+                .field("panic", &self.panic)
+                .field("requests", &Alignments(self))
+                .field("exceeding", &self.num_exceeding())
+                .finish()
+        }
+    }
+
+    unsafe impl CallbackRef for AlignmentAsserter {
+        #[track_caller]
+        fn before_allocate(&self, layout: Layout) {
+            self.record(layout)
+        }
+
+        #[track_caller]
+        fn before_allocate_zeroed(&self, layout: Layout) {
+            self.record(layout)
+        }
+
+        #[track_caller]
+        fn before_grow(&self, _ptr: NonNull<u8>, _old_layout: Layout, new_layout: Layout) {
+            self.record(new_layout)
+        }
+
+        #[track_caller]
+        fn before_grow_zeroed(&self, _ptr: NonNull<u8>, _old_layout: Layout, new_layout: Layout) {
+            self.record(new_layout)
+        }
+
+        #[track_caller]
+        fn before_grow_in_place(&self, _ptr: NonNull<u8>, _old_layout: Layout, new_layout: Layout) {
+            self.record(new_layout)
+        }
+
+        #[track_caller]
+        fn before_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+        ) {
+            self.record(new_layout)
+        }
+
+        #[track_caller]
+        fn before_shrink(&self, _ptr: NonNull<u8>, _old_layout: Layout, new_layout: Layout) {
+            self.record(new_layout)
+        }
+
+        #[track_caller]
+        fn before_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+        ) {
+            self.record(new_layout)
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "ptr")))]
+pub use self::event_log::{ConcurrentEventLog, Event};
+
+#[cfg(target_has_atomic = "ptr")]
+mod event_log {
+    use crate::CallbackRef;
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::UnsafeCell,
+        fmt,
+        mem::MaybeUninit,
+        ptr::NonNull,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    };
+
+    /// An event recorded by [`ConcurrentEventLog`].
+    ///
+    /// Zeroed requests are recorded like their uninitialized counterparts. Resizing in place is
+    /// recorded as `Grow` or `Shrink`, which returned `ptr` on success.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum Event {
+        Allocate {
+            layout: Layout,
+            result: Result<NonNull<u8>, AllocError>,
+        },
+        Deallocate {
+            ptr: NonNull<u8>,
+            layout: Layout,
+        },
+        Grow {
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<u8>, AllocError>,
+        },
+        Shrink {
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<u8>, AllocError>,
+        },
+    }
+
+    /// A slot is empty for lap `n` if its sequence is `2 * n` and full if it is `2 * n + 1`.
+    struct Slot {
+        sequence: AtomicUsize,
+        event: UnsafeCell<MaybeUninit<Event>>,
+    }
+
+    impl Slot {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: Self = Self {
+            sequence: AtomicUsize::new(0),
+            event: UnsafeCell::new(MaybeUninit::uninit()),
+        };
+    }
+
+    /// A fixed-capacity event log, which may be shared between threads.
+    ///
+    /// Any number of threads may record events concurrently, without locking and without
+    /// allocating, which makes the log suitable for tracing a global allocator. Producers never
+    /// wait for the consumer: if the log is full, the event is dropped and counted in
+    /// [`num_dropped`]. Recorded events are taken out in order by [`drain`], which is meant to be
+    /// called from a single consumer.
+    ///
+    /// [`num_dropped`]: Self::num_dropped
+    /// [`drain`]: Self::drain
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api, slice_ptr_get)]
+    ///
+    /// use alloc_compose::{
+    ///     stats::{ConcurrentEventLog, Event},
+    ///     CallbackRef,
+    ///     Proxy,
+    /// };
+    /// use std::{
+    ///     alloc::{Allocator, Layout, System},
+    ///     thread,
+    /// };
+    ///
+    /// let log = ConcurrentEventLog::<64>::new();
+    /// let alloc = Proxy {
+    ///     alloc: System,
+    ///     callbacks: log.by_ref(),
+    /// };
+    ///
+    /// thread::scope(|scope| {
+    ///     for _ in 0..4 {
+    ///         scope.spawn(|| {
+    ///             let memory = alloc.allocate(Layout::new::<u64>()).unwrap();
+    ///             unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<u64>()) };
+    ///         });
+    ///     }
+    /// });
+    ///
+    /// let mut deallocs = 0;
+    /// let drained = log.drain(|event| {
+    ///     if let Event::Deallocate { .. } = event {
+    ///         deallocs += 1;
+    ///     }
+    /// });
+    /// assert_eq!(drained, 8);
+    /// assert_eq!(deallocs, 4);
+    /// ```
+    pub struct ConcurrentEventLog<const N: usize> {
+        slots: [Slot; N],
+        head: AtomicUsize,
+        tail: AtomicUsize,
+        draining: AtomicBool,
+        dropped: AtomicUsize,
+    }
+
+    // SAFETY: an event is only read after it was published by the release store of its sequence,
+    // and the pointers contained in events are never dereferenced.
+    unsafe impl<const N: usize> Send for ConcurrentEventLog<N> {}
+    unsafe impl<const N: usize> Sync for ConcurrentEventLog<N> {}
+
+    impl<const N: usize> ConcurrentEventLog<N> {
+        /// Creates an empty log with room for `N` events.
+        pub const fn new() -> Self {
+            Self {
+                slots: [Slot::EMPTY; N],
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+                draining: AtomicBool::new(false),
+                dropped: AtomicUsize::new(0),
+            }
+        }
+
+        /// Returns the number of events, which were dropped because the log was full.
+        pub fn num_dropped(&self) -> usize {
+            self.dropped.load(Ordering::Relaxed)
+        }
+
+        /// Returns the number of recorded events, which were not drained yet.
+        ///
+        /// While other threads record events, the returned value may be outdated immediately.
+        pub fn len(&self) -> usize {
+            self.tail
+                .load(Ordering::Relaxed)
+                .wrapping_sub(self.head.load(Ordering::Relaxed))
+        }
+
+        /// Returns `true` if there are no events to drain.
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        fn record(&self, event: Event) {
+            if N == 0 {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            let mut position = self.tail.load(Ordering::Relaxed);
+            loop {
+                let slot = &self.slots[position % N];
+                let empty = (position / N).wrapping_mul(2);
+                let sequence = slot.sequence.load(Ordering::Acquire);
+                if sequence == empty {
+                    match self.tail.compare_exchange_weak(
+                        position,
+                        position.wrapping_add(1),
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(_) => {
+                            // SAFETY: the slot was claimed exclusively by advancing `tail`
+                            unsafe { (*slot.event.get()).as_mut_ptr().write(event) };
+                            slot.sequence
+                                .store(empty.wrapping_add(1), Ordering::Release);
+                            return;
+                        }
+                        Err(current) => position = current,
+                    }
+                } else if (sequence.wrapping_sub(empty) as isize) < 0 {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                } else {
+                    position = self.tail.load(Ordering::Relaxed);
+                }
+            }
+        }
+
+        /// Calls `f` with every recorded event in order and removes it from the log.
+        ///
+        /// Events, which are still being written by another thread, are left for the next call.
+        /// Only one thread may drain the log at a time. If another thread is currently draining,
+        /// this returns immediately. Returns the number of drained events.
+        pub fn drain(&self, mut f: impl FnMut(Event)) -> usize {
+            if N == 0 || self.draining.swap(true, Ordering::Acquire) {
+                return 0;
+            }
+            let mut drained = 0;
+            let mut position = self.head.load(Ordering::Relaxed);
+            loop {
+                let slot = &self.slots[position % N];
+                let full = (position / N).wrapping_mul(2).wrapping_add(1);
+                if slot.sequence.load(Ordering::Acquire) != full {
+                    break;
+                }
+                // SAFETY: the event was published by the producer, which claimed this slot
+                let event = unsafe { (*slot.event.get()).as_ptr().read() };
+                slot.sequence.store(full.wrapping_add(1), Ordering::Release);
+                position = position.wrapping_add(1);
+                self.head.store(position, Ordering::Relaxed);
+                drained += 1;
+                f(event);
+            }
+            self.draining.store(false, Ordering::Release);
+            drained
+        }
+    }
+
+    impl<const N: usize> Default for ConcurrentEventLog<N> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<const N: usize> fmt::Debug for ConcurrentEventLog<N> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ConcurrentEventLog")
+                .field("capacity", &N)
+                .field("len", &self.len())
+                .field("dropped", &self.num_dropped())
+                .finish()
+        }
+    }
+
+    fn first(result: Result<NonNull<[u8]>, AllocError>) -> Result<NonNull<u8>, AllocError> {
+        result.map(NonNull::as_non_null_ptr)
+    }
+
+    fn in_place(
+        ptr: NonNull<u8>,
+        result: Result<usize, AllocError>,
+    ) -> Result<NonNull<u8>, AllocError> {
+        result.map(|_| ptr)
+    }
+
+    unsafe impl<const N: usize> CallbackRef for ConcurrentEventLog<N> {
+        fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record(Event::Allocate {
+                layout,
+                result: first(result),
+            })
+        }
+
+        fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.after_allocate(layout, result)
+        }
+
+        fn after_deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.record(Event::Deallocate { ptr, layout })
+        }
+
+        fn after_grow(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record(Event::Grow {
+                ptr,
+                old_layout,
+                new_layout,
+                result: first(result),
+            })
+        }
+
+        fn after_grow_zeroed(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.after_grow(ptr, old_layout, new_layout, result)
+        }
+
+        fn after_grow_in_place(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record(Event::Grow {
+                ptr,
+                old_layout,
+                new_layout,
+                result: in_place(ptr, result),
+            })
+        }
+
+        fn after_grow_in_place_zeroed(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.after_grow_in_place(ptr, old_layout, new_layout, result)
+        }
+
+        fn after_shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record(Event::Shrink {
+                ptr,
+                old_layout,
+                new_layout,
+                result: first(result),
+            })
+        }
+
+        fn after_shrink_in_place(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record(Event::Shrink {
+                ptr,
+                old_layout,
+                new_layout,
+                result: in_place(ptr, result),
+            })
+        }
+    }
+}
+
+#[cfg(any(doc, feature = "std"))]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub use self::backtrace::{BacktraceTracker, OutstandingAllocation};
+
+#[cfg(any(doc, feature = "std"))]
+mod backtrace {
+    use crate::CallbackRef;
+    use alloc::{collections::BTreeMap, vec::Vec};
+    use core::{
+        alloc::{AllocError, Layout},
+        num::NonZeroU64,
+        ptr::NonNull,
+        sync::atomic::{AtomicU64, Ordering::Relaxed},
+    };
+    use std::{
+        backtrace::Backtrace,
+        io::{self, Write},
+        sync::{Mutex, MutexGuard},
+    };
+
+    /// A live allocation recorded by [`BacktraceTracker`].
+    #[derive(Debug)]
+    pub struct OutstandingAllocation<'a> {
+        pub ptr: NonNull<u8>,
+        pub layout: Layout,
+        /// The backtrace of the allocation site, or `None` if the allocation was not sampled.
+        pub backtrace: Option<&'a Backtrace>,
+    }
+
+    #[derive(Debug)]
+    struct Record {
+        ptr: NonNull<u8>,
+        layout: Layout,
+        sequence: u64,
+        backtrace: Option<Backtrace>,
+    }
+
+    // SAFETY: `ptr` is only handed out to the user and never dereferenced by the tracker
+    unsafe impl Send for Record {}
+
+    /// Records the allocation site of every live allocation.
+    ///
+    /// The tracker captures a [`Backtrace`] when a memory block is allocated and keeps it until
+    /// the block is deallocated. Growing or shrinking a block retains the original allocation
+    /// site. Outstanding allocations can be inspected with [`for_each_outstanding`] or written
+    /// out with [`dump`], which makes it easy to find the origin of a leak.
+    ///
+    /// As capturing a backtrace is expensive, [`with_sampling`] only captures every n-th
+    /// allocation. Unsampled allocations are still tracked, but without a backtrace.
+    ///
+    /// Both capturing a backtrace and recording the allocation allocate memory from the global
+    /// allocator, so the tracker must not be used to wrap the global allocator itself.
+    ///
+    /// [`for_each_outstanding`]: Self::for_each_outstanding
+    /// [`dump`]: Self::dump
+    /// [`with_sampling`]: Self::with_sampling
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api, slice_ptr_get)]
+    ///
+    /// use alloc_compose::{stats::BacktraceTracker, CallbackRef, Proxy};
+    /// use std::alloc::{Allocator, Layout, System};
+    ///
+    /// let tracker = BacktraceTracker::new();
+    /// let alloc = Proxy {
+    ///     alloc: System,
+    ///     callbacks: tracker.by_ref(),
+    /// };
+    ///
+    /// let memory = alloc.allocate(Layout::new::<[u8; 4]>())?;
+    /// assert_eq!(tracker.num_outstanding(), 1);
+    ///
+    /// let mut report = Vec::new();
+    /// tracker.dump(&mut report)?;
+    /// assert!(String::from_utf8(report)?.starts_with("1 outstanding allocation"));
+    ///
+    /// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>()) };
+    /// assert_eq!(tracker.num_outstanding(), 0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct BacktraceTracker {
+        records: Mutex<BTreeMap<usize, Record>>,
+        sample_rate: NonZeroU64,
+        allocations: AtomicU64,
+    }
+
+    impl Default for BacktraceTracker {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl BacktraceTracker {
+        /// Creates a tracker capturing a backtrace for every allocation.
+        pub fn new() -> Self {
+            Self::with_sampling(NonZeroU64::new(1).unwrap())
+        }
+
+        /// Creates a tracker capturing a backtrace for every `sample_rate`-th allocation.
+        pub fn with_sampling(sample_rate: NonZeroU64) -> Self {
+            Self {
+                records: Mutex::default(),
+                sample_rate,
+                allocations: AtomicU64::new(0),
+            }
+        }
+
+        /// Returns the number of allocations, which have not been deallocated yet.
+        pub fn num_outstanding(&self) -> usize {
+            self.records().len()
+        }
+
+        /// Calls `f` for every outstanding allocation in ascending address order.
+        ///
+        /// The tracker is locked while iterating, so `f` must not allocate from an allocator
+        /// reporting to this tracker.
+        pub fn for_each_outstanding(&self, mut f: impl FnMut(OutstandingAllocation<'_>)) {
+            for record in self.records().values() {
+                f(OutstandingAllocation {
+                    ptr: record.ptr,
+                    layout: record.layout,
+                    backtrace: record.backtrace.as_ref(),
+                })
+            }
+        }
+
+        /// Returns all outstanding allocations in the order they were allocated.
+        ///
+        /// Growing or shrinking a memory block does not change its position. In contrast to
+        /// [`for_each_outstanding`], the tracker is not locked while iterating, as a snapshot of
+        /// the allocations is taken.
+        ///
+        /// [`for_each_outstanding`]: Self::for_each_outstanding
+        pub fn iter_live(&self) -> impl Iterator<Item = (NonNull<u8>, Layout)> {
+            let mut live: Vec<_> = self
+                .records()
+                .values()
+                .map(|record| (record.sequence, record.ptr, record.layout))
+                .collect();
+            live.sort_unstable_by_key(|&(sequence, ..)| sequence);
+            live.into_iter().map(|(_, ptr, layout)| (ptr, layout))
+        }
+
+        /// Writes a human readable report of all outstanding allocations to `writer`.
+        ///
+        /// # Errors
+        ///
+        /// Returns any error produced by `writer`.
+        pub fn dump(&self, mut writer: impl Write) -> io::Result<()> {
+            let records = self.records();
+            writeln!(writer, "{} outstanding allocation(s)", records.len())?;
+            for record in records.values() {
+                writeln!(
+                    writer,
+                    "\nallocation at {:p} with size {} and alignment {}:",
+                    record.ptr,
+                    record.layout.size(),
+                    record.layout.align()
+                )?;
+                match &record.backtrace {
+                    Some(backtrace) => writeln!(writer, "{}", backtrace)?,
+                    None => writeln!(writer, "<not sampled>")?,
+                }
+            }
+            Ok(())
+        }
+
+        fn records(&self) -> MutexGuard<'_, BTreeMap<usize, Record>> {
+            // A panic while holding the lock cannot leave the map in an inconsistent state
+            self.records
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        }
+
+        fn insert(&self, memory: NonNull<[u8]>, layout: Layout) {
+            let sequence = self.allocations.fetch_add(1, Relaxed);
+            let backtrace = if sequence.is_multiple_of(self.sample_rate.get()) {
+                Some(Backtrace::force_capture())
+            } else {
+                None
+            };
+            let ptr = memory.as_non_null_ptr();
+            self.records().insert(ptr.as_ptr() as usize, Record {
+                ptr,
+                layout,
+                sequence,
+                backtrace,
+            });
+        }
+
+        fn relocate(&self, ptr: NonNull<u8>, memory: NonNull<[u8]>, new_layout: Layout) {
+            let mut records = self.records();
+            if let Some(mut record) = records.remove(&(ptr.as_ptr() as usize)) {
+                record.ptr = memory.as_non_null_ptr();
+                record.layout = new_layout;
+                records.insert(memory.as_mut_ptr() as usize, record);
+            }
+        }
+    }
+
+    unsafe impl CallbackRef for BacktraceTracker {
+        fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            if let Ok(memory) = result {
+                self.insert(memory, layout)
+            }
+        }
+
+        fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.after_allocate(layout, result)
+        }
+
+        fn after_allocate_all(&self, result: Result<NonNull<[u8]>, AllocError>) {
+            if let Ok(memory) = result {
+                if let Ok(layout) = Layout::from_size_align(memory.len(), 1) {
+                    self.insert(memory, layout)
+                }
+            }
+        }
+
+        fn after_allocate_all_zeroed(&self, result: Result<NonNull<[u8]>, AllocError>) {
+            self.after_allocate_all(result)
+        }
+
+        fn after_deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+            self.records().remove(&(ptr.as_ptr() as usize));
+        }
+
+        fn after_deallocate_all(&self) {
+            self.records().clear()
+        }
+
+        fn after_grow(
+            &self,
+            ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            if let Ok(memory) = result {
+                self.relocate(ptr, memory, new_layout)
+            }
+        }
+
+        fn after_grow_zeroed(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.after_grow(ptr, old_layout, new_layout, result)
+        }
+
+        fn after_grow_in_place(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.after_grow(
+                ptr,
+                old_layout,
+                new_layout,
+                result.map(|len| NonNull::slice_from_raw_parts(ptr, len)),
+            )
+        }
+
+        fn after_grow_in_place_zeroed(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.after_grow_in_place(ptr, old_layout, new_layout, result)
+        }
+
+        fn after_shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.after_grow(ptr, old_layout, new_layout, result)
+        }
+
+        fn after_shrink_in_place(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.after_grow_in_place(ptr, old_layout, new_layout, result)
+        }
+    }
+}
+
+pub use self::latency::{LatencyBucket, LatencyHistogram, Operation};
+
+mod latency {
+    use crate::{CallbackRef, Timestamp};
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        cmp,
+        fmt,
+        marker::PhantomData,
+        ptr::NonNull,
+        time::Duration,
+    };
+
+    const BUCKET_COUNT: usize = 32;
+
+    /// The kind of request timed by a [`LatencyHistogram`] or recorded by a [`LastError`].
+    ///
+    /// Zeroed requests and resizing in place are recorded like their counterparts.
+    ///
+    /// [`LastError`]: super::LastError
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Operation {
+        Allocate = 0,
+        Deallocate = 1,
+        Grow = 2,
+        Shrink = 3,
+    }
+
+    /// The requests of a [`LatencyHistogram`] falling into one power of two ticks of its clock.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct LatencyBucket {
+        /// The longest duration in this bucket. The shortest one is half of it.
+        pub max_duration: Duration,
+        /// The number of requests in this bucket.
+        pub count: u64,
+    }
+
+    /// Returns the index of the smallest power of two greater than or equal to `ticks`.
+    fn bucket_index(ticks: u64) -> usize {
+        if ticks <= 1 {
+            return 0;
+        }
+        let bits = 64 - (ticks - 1).leading_zeros() as usize;
+        cmp::min(bits, BUCKET_COUNT - 1)
+    }
+
+    fn max_duration<T: Timestamp>(index: usize) -> Duration {
+        if index == BUCKET_COUNT - 1 {
+            Duration::MAX
+        } else {
+            T::to_duration(1 << index)
+        }
+    }
+
+    /// Measures the time between the `before_*` and `after_*` hooks of every request.
+    ///
+    /// The time is read from the clock `T`, e.g. [`StdClock`] or, on embedded targets,
+    /// [`DwtCycleCounter`]. The durations are collected per [`Operation`] in buckets of powers of
+    /// two ticks, durations above 2^30 ticks share the last bucket. [`quantile`] reports the tail
+    /// latency of an operation, e.g. to check a composition against the time budget of a frame.
+    ///
+    /// Only the start of the current request is stored, so the histogram must not be shared
+    /// between allocators, which may be called concurrently or recursively.
+    ///
+    /// [`quantile`]: Self::quantile
+    /// [`StdClock`]: crate::StdClock
+    /// [`DwtCycleCounter`]: crate::DwtCycleCounter
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api, slice_ptr_get)]
+    ///
+    /// # #[cfg(feature = "std")] {
+    /// use alloc_compose::{
+    ///     stats::{LatencyHistogram, Operation},
+    ///     GrowablePool,
+    ///     Proxy,
+    ///     StdClock,
+    /// };
+    /// use std::{
+    ///     alloc::{Allocator, Layout, System},
+    ///     time::Duration,
+    /// };
+    ///
+    /// let alloc = Proxy {
+    ///     alloc: GrowablePool::<_, 64, 16>::new(System),
+    ///     callbacks: LatencyHistogram::<StdClock>::default(),
+    /// };
+    ///
+    /// for _ in 0..100 {
+    ///     let memory = alloc.allocate(Layout::new::<[u8; 64]>())?;
+    ///     unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>()) };
+    /// }
+    ///
+    /// let latencies = &alloc.callbacks;
+    /// assert_eq!(latencies.num_records(Operation::Allocate), 100);
+    /// let p99 = latencies.quantile(Operation::Allocate, 0.99).unwrap();
+    /// assert!(p99 < Duration::from_millis(100));
+    /// # }
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    pub struct LatencyHistogram<T> {
+        start: Cell<Option<u64>>,
+        counts: [[Cell<u64>; BUCKET_COUNT]; 4],
+        max: [Cell<u64>; 4],
+        clock: PhantomData<T>,
+    }
+
+    impl<T> Default for LatencyHistogram<T> {
+        fn default() -> Self {
+            Self {
+                start: Cell::default(),
+                counts: Default::default(),
+                max: Default::default(),
+                clock: PhantomData,
+            }
+        }
+    }
+
+    impl<T> fmt::Debug for LatencyHistogram<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("LatencyHistogram")
+                .field("counts", &self.counts)
+                .field("max", &self.max)
+                .finish()
+        }
+    }
+
+    impl<T: Timestamp> LatencyHistogram<T> {
+        fn start(&self) {
+            self.start.set(Some(T::now()))
+        }
+
+        fn stop(&self, operation: Operation) {
+            if let Some(start) = self.start.take() {
+                let ticks = T::elapsed(start, T::now());
+                let count = &self.counts[operation as usize][bucket_index(ticks)];
+                count.set(count.get().wrapping_add(1));
+                let max = &self.max[operation as usize];
+                max.set(cmp::max(max.get(), ticks));
+            }
+        }
+
+        /// Returns the number of timed requests of `operation`.
+        pub fn num_records(&self, operation: Operation) -> u64 {
+            self.counts[operation as usize]
+                .iter()
+                .fold(0, |sum, count| sum.wrapping_add(count.get()))
+        }
+
+        /// Returns the longest duration of a request of `operation`.
+        pub fn max(&self, operation: Operation) -> Duration {
+            T::to_duration(self.max[operation as usize].get())
+        }
+
+        /// Returns the non-empty buckets of `operation` in ascending order of their durations.
+        pub fn buckets(&self, operation: Operation) -> impl Iterator<Item = LatencyBucket> + '_ {
+            self.counts[operation as usize]
+                .iter()
+                .enumerate()
+                .map(|(index, count)| LatencyBucket {
+                    max_duration: max_duration::<T>(index),
+                    count: count.get(),
+                })
+                .filter(|bucket| bucket.count > 0)
+        }
+
+        /// Returns an upper bound of the duration, which a fraction of `q` of the requests of
+        /// `operation` did not exceed, or `None`, if no request was timed.
+        ///
+        /// The bound is the upper end of the bucket containing the quantile, but never larger
+        /// than [`max`].
+        ///
+        /// [`max`]: Self::max
+        ///
+        /// # Panics
+        ///
+        /// Panics if `q` is not between `0.0` and `1.0`.
+        pub fn quantile(&self, operation: Operation, q: f64) -> Option<Duration> {
+            assert!((0.0..=1.0).contains(&q), "quantile must be between 0 and 1");
+            let total = self.num_records(operation);
+            if total == 0 {
+                return None;
+            }
+            // `f64::ceil` is not available in `core`
+            #[allow(clippy::cast_precision_loss)]
+            let scaled = q * total as f64;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let mut rank = scaled as u64;
+            #[allow(clippy::cast_precision_loss)]
+            if (rank as f64) < scaled || rank == 0 {
+                rank += 1;
+            }
+            let mut seen = 0;
+            self.buckets(operation)
+                .find(|bucket| {
+                    seen += bucket.count;
+                    seen >= rank
+                })
+                .map(|bucket| cmp::min(bucket.max_duration, self.max(operation)))
+        }
+
+        /// Clears all recorded durations.
+        pub fn reset(&self) {
+            for count in self.counts.iter().flatten() {
+                count.set(0);
+            }
+            for max in &self.max {
+                max.set(0);
+            }
+        }
+    }
+
+    unsafe impl<T: Timestamp> CallbackRef for LatencyHistogram<T> {
+        #[inline]
+        fn before_allocate(&self, _layout: Layout) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_allocate(&self, _layout: Layout, _result: Result<NonNull<[u8]>, AllocError>) {
+            self.stop(Operation::Allocate)
+        }
+
+        #[inline]
+        fn before_allocate_zeroed(&self, _layout: Layout) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_allocate_zeroed(
+            &self,
+            _layout: Layout,
+            _result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.stop(Operation::Allocate)
+        }
+
+        #[inline]
+        fn before_deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+            self.stop(Operation::Deallocate)
+        }
+
+        #[inline]
+        fn before_grow(&self, _ptr: NonNull<u8>, _old_layout: Layout, _new_layout: Layout) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_grow(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+            _result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.stop(Operation::Grow)
+        }
+
+        #[inline]
+        fn before_grow_zeroed(&self, _ptr: NonNull<u8>, _old_layout: Layout, _new_layout: Layout) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_grow_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+            _result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.stop(Operation::Grow)
+        }
+
+        #[inline]
+        fn before_grow_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+        ) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_grow_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+            _result: Result<usize, AllocError>,
+        ) {
+            self.stop(Operation::Grow)
+        }
+
+        #[inline]
+        fn before_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+        ) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+            _result: Result<usize, AllocError>,
+        ) {
+            self.stop(Operation::Grow)
+        }
+
+        #[inline]
+        fn before_shrink(&self, _ptr: NonNull<u8>, _old_layout: Layout, _new_layout: Layout) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_shrink(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+            _result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.stop(Operation::Shrink)
+        }
+
+        #[inline]
+        fn before_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+        ) {
+            self.start()
+        }
+
+        #[inline]
+        fn after_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            _new_layout: Layout,
+            _result: Result<usize, AllocError>,
+        ) {
+            self.stop(Operation::Shrink)
+        }
+    }
+}
+
+pub use self::watermark::{SoftLimits, Watermark};
+
+mod watermark {
+    use crate::CallbackRef;
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        cmp,
+        ptr::NonNull,
+    };
+
+    /// Tracks the smallest capacity left of an [`AllocateAll`] allocator, its high-water mark.
+    ///
+    /// A [`Proxy`] samples [`capacity_left`] after every request and passes it to the
+    /// `Watermark`, so the peak usage of a region is known without modifying the region itself.
+    /// This helps to size the backing memory of a region, e.g. one which is reset every frame.
+    ///
+    /// [`AllocateAll`]: crate::AllocateAll
+    /// [`Proxy`]: crate::Proxy
+    /// [`capacity_left`]: crate::AllocateAll::capacity_left
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{region::Region, stats, AllocateAll, CallbackRef, Proxy};
+    /// use core::{
+    ///     alloc::{Allocator, Layout},
+    ///     mem::MaybeUninit,
+    /// };
+    ///
+    /// let watermark = stats::Watermark::default();
+    /// let mut data = [MaybeUninit::uninit(); 256];
+    /// let alloc = Proxy {
+    ///     alloc: Region::new(&mut data),
+    ///     callbacks: watermark.by_ref(),
+    /// };
+    ///
+    /// alloc.allocate(Layout::new::<[u8; 100]>())?;
+    /// alloc.deallocate_all();
+    /// alloc.allocate(Layout::new::<[u8; 10]>())?;
+    ///
+    /// assert_eq!(watermark.min_capacity_left(), Some(156));
+    /// assert_eq!(alloc.capacity_left(), 246);
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Watermark {
+        min: Cell<Option<usize>>,
+    }
+
+    impl Default for Watermark {
+        fn default() -> Self {
+            Self {
+                min: Cell::new(None),
+            }
+        }
+    }
+
+    impl Watermark {
+        /// Returns the smallest sampled capacity left or `None`, if nothing was sampled yet.
+        pub fn min_capacity_left(&self) -> Option<usize> {
+            self.min.get()
+        }
+
+        /// Forgets the sampled capacities.
+        pub fn reset(&self) {
+            self.min.set(None)
+        }
+    }
+
+    unsafe impl CallbackRef for Watermark {
+        #[inline]
+        fn samples_capacity_left(&self) -> bool {
+            true
+        }
+
+        #[inline]
+        fn after_capacity_left(&self, capacity_left: usize) {
+            let min = self
+                .min
+                .get()
+                .map_or(capacity_left, |min| cmp::min(min, capacity_left));
+            self.min.set(Some(min))
+        }
+    }
+
+    /// Notifies callbacks, when the number of allocated bytes crosses soft limits.
+    ///
+    /// The soft limits are given in percent of `limit`, e.g. `[80, 95]`. Every time the allocated
+    /// bytes reach or fall below one of them, [`on_watermark`] is called on `callbacks` with the
+    /// number of soft limits reached. This way, an application can shed load before requests fail
+    /// at the hard limit of the allocator, and resume when the usage dropped again. Successful
+    /// allocations, deallocations, and resizes are recorded with the size of their layouts.
+    ///
+    /// [`on_watermark`]: crate::CallbackRef::on_watermark
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api, slice_ptr_get)]
+    ///
+    /// use alloc_compose::{stats::SoftLimits, CallbackRef, Proxy};
+    /// use core::cell::Cell;
+    /// use std::alloc::{Allocator, Layout, System};
+    ///
+    /// #[derive(Default)]
+    /// struct Shedding(Cell<bool>);
+    ///
+    /// unsafe impl CallbackRef for Shedding {
+    ///     fn on_watermark(&self, level: usize, _used: usize, _limit: usize) {
+    ///         self.0.set(level > 0)
+    ///     }
+    /// }
+    ///
+    /// let shedding = Shedding::default();
+    /// let alloc = Proxy {
+    ///     alloc: System,
+    ///     callbacks: SoftLimits::new(1024, [80, 95], shedding.by_ref()),
+    /// };
+    ///
+    /// let memory = alloc.allocate(Layout::new::<[u8; 900]>())?;
+    /// assert!(shedding.0.get());
+    /// assert_eq!(alloc.callbacks.level(), 1);
+    ///
+    /// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 900]>()) };
+    /// assert!(!shedding.0.get());
+    /// # Ok::<(), core::alloc::AllocError>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct SoftLimits<C, const N: usize> {
+        pub callbacks: C,
+        limit: usize,
+        thresholds: [usize; N],
+        used: Cell<usize>,
+        level: Cell<usize>,
+    }
+
+    impl<C: CallbackRef, const N: usize> SoftLimits<C, N> {
+        /// Notifies `callbacks`, when the allocated bytes cross `percent` of `limit`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `percent` is not strictly increasing.
+        pub fn new(limit: usize, percent: [u8; N], callbacks: C) -> Self {
+            assert!(
+                percent.windows(2).all(|pair| pair[0] < pair[1]),
+                "soft limits must be strictly increasing"
+            );
+            Self {
+                callbacks,
+                limit,
+                thresholds: percent.map(|percent| {
+                    let percent = usize::from(percent);
+                    limit / 100 * percent + limit % 100 * percent / 100
+                }),
+                used: Cell::new(0),
+                level: Cell::new(0),
+            }
+        }
+
+        /// Returns the number of allocated bytes.
+        pub fn used(&self) -> usize {
+            self.used.get()
+        }
+
+        /// Returns the number of soft limits reached.
+        pub fn level(&self) -> usize {
+            self.level.get()
+        }
+
+        fn update(&self, used: usize) {
+            self.used.set(used);
+            let level = self
+                .thresholds
+                .iter()
+                .take_while(|&&threshold| used >= threshold)
+                .count();
+            if level != self.level.replace(level) {
+                self.callbacks.on_watermark(level, used, self.limit)
+            }
+        }
+
+        fn add(&self, size: usize) {
+            self.update(self.used.get().saturating_add(size))
+        }
+
+        fn sub(&self, size: usize) {
+            self.update(self.used.get().saturating_sub(size))
+        }
+
+        fn resize<T>(&self, old_layout: Layout, new_layout: Layout, result: Result<T, AllocError>) {
+            if result.is_ok() {
+                let used = self.used.get().saturating_sub(old_layout.size());
+                self.update(used.saturating_add(new_layout.size()))
+            }
+        }
+    }
+
+    unsafe impl<C: CallbackRef, const N: usize> CallbackRef for SoftLimits<C, N> {
+        #[inline]
+        fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            if result.is_ok() {
+                self.add(layout.size())
+            }
+        }
+
+        #[inline]
+        fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.after_allocate(layout, result)
+        }
+
+        #[inline]
+        fn after_deallocate(&self, _ptr: NonNull<u8>, layout: Layout) {
+            self.sub(layout.size())
+        }
+
+        #[inline]
+        fn after_deallocate_all(&self) {
+            self.update(0)
+        }
+
+        #[inline]
+        fn after_grow(
+            &self,
+            _ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.resize(old_layout, new_layout, result)
+        }
+
+        #[inline]
+        fn after_grow_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.resize(old_layout, new_layout, result)
+        }
+
+        #[inline]
+        fn after_grow_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.resize(old_layout, new_layout, result)
+        }
+
+        #[inline]
+        fn after_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.resize(old_layout, new_layout, result)
+        }
+
+        #[inline]
+        fn after_shrink(
+            &self,
+            _ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.resize(old_layout, new_layout, result)
+        }
+
+        #[inline]
+        fn after_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.resize(old_layout, new_layout, result)
+        }
+    }
+}
+
+pub use self::last_error::{Failure, LastError};
+
+mod last_error {
+    use super::Operation;
+    use crate::CallbackRef;
+    use core::{
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        fmt,
+        ptr::NonNull,
+    };
+
+    /// A failed request recorded by [`LastError`].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Failure {
+        /// The kind of the request.
+        pub operation: Operation,
+        /// The requested layout, or `None` for [`allocate_all`].
+        ///
+        /// [`allocate_all`]: crate::AllocateAll::allocate_all
+        pub layout: Option<Layout>,
+        /// The reason passed to [`LastError::deny`] while processing the request, if any.
+        pub reason: Option<&'static str>,
+    }
+
+    impl fmt::Display for Failure {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.layout {
+                Some(layout) => write!(
+                    f,
+                    "{:?} of {} bytes aligned to {} failed",
+                    self.operation,
+                    layout.size(),
+                    layout.align()
+                )?,
+                None => write!(f, "{:?} of all memory failed", self.operation)?,
+            }
+            match self.reason {
+                Some(reason) => write!(f, ": {}", reason),
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// Records the last failed request, optionally with the reason of the failure.
+    ///
+    /// [`AllocError`] carries no information, so a failure deep inside of a composition is hard
+    /// to explain. A [`PolicyRef`] or any other layer holding a reference to the `LastError`
+    /// calls [`deny`] with a static reason before returning `Err`. The outer [`Proxy`] then
+    /// records the failed request together with the reason, which is retrieved with
+    /// [`last_error`]. Failures without a reason are recorded as well.
+    ///
+    /// Only one reason is pending at a time, so the `LastError` must not be shared between
+    /// allocators, which may be called concurrently.
+    ///
+    /// [`PolicyRef`]: crate::PolicyRef
+    /// [`Proxy`]: crate::Proxy
+    /// [`deny`]: Self::deny
+    /// [`last_error`]: Self::last_error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    ///
+    /// use alloc_compose::{
+    ///     stats::{LastError, Operation},
+    ///     CallbackRef,
+    ///     Guarded,
+    ///     PolicyRef,
+    ///     Proxy,
+    /// };
+    /// use std::alloc::{AllocError, Allocator, Layout, System};
+    ///
+    /// struct MaxSize<'a> {
+    ///     max: usize,
+    ///     errors: &'a LastError,
+    /// }
+    ///
+    /// impl PolicyRef for MaxSize<'_> {
+    ///     fn check_allocate(&self, layout: Layout) -> Result<(), AllocError> {
+    ///         if layout.size() <= self.max {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(self.errors.deny("larger than the maximum size"))
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let errors = LastError::new();
+    /// let alloc = Proxy {
+    ///     alloc: Guarded {
+    ///         alloc: System,
+    ///         policy: MaxSize {
+    ///             max: 1024,
+    ///             errors: &errors,
+    ///         },
+    ///     },
+    ///     callbacks: errors.by_ref(),
+    /// };
+    ///
+    /// assert!(alloc.allocate(Layout::new::<[u8; 2048]>()).is_err());
+    /// let failure = errors.last_error().unwrap();
+    /// assert_eq!(failure.operation, Operation::Allocate);
+    /// assert_eq!(
+    ///     failure.to_string(),
+    ///     "Allocate of 2048 bytes aligned to 1 failed: larger than the maximum size"
+    /// );
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct LastError {
+        pending: Cell<Option<&'static str>>,
+        last: Cell<Option<Failure>>,
+    }
+
+    impl LastError {
+        pub const fn new() -> Self {
+            Self {
+                pending: Cell::new(None),
+                last: Cell::new(None),
+            }
+        }
+
+        /// Stores `reason` for the request, which is currently processed, and returns
+        /// `AllocError`, so it can be used as `Err(errors.deny(reason))`.
+        pub fn deny(&self, reason: &'static str) -> AllocError {
+            self.pending.set(Some(reason));
+            AllocError
+        }
+
+        /// Returns the last failed request.
+        pub fn last_error(&self) -> Option<Failure> {
+            self.last.get()
+        }
+
+        /// Returns the last failed request and forgets it.
+        pub fn take(&self) -> Option<Failure> {
+            self.last.take()
+        }
+
+        fn record<T>(
+            &self,
+            operation: Operation,
+            layout: Option<Layout>,
+            result: Result<T, AllocError>,
+        ) {
+            let reason = self.pending.take();
+            if result.is_err() {
+                self.last.set(Some(Failure {
+                    operation,
+                    layout,
+                    reason,
+                }))
+            }
+        }
+    }
+
+    unsafe impl CallbackRef for LastError {
+        fn after_allocate(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record(Operation::Allocate, Some(layout), result)
+        }
+
+        fn after_allocate_zeroed(&self, layout: Layout, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record(Operation::Allocate, Some(layout), result)
+        }
+
+        fn after_allocate_all(&self, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record(Operation::Allocate, None, result)
+        }
+
+        fn after_allocate_all_zeroed(&self, result: Result<NonNull<[u8]>, AllocError>) {
+            self.record(Operation::Allocate, None, result)
+        }
+
+        fn after_grow(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record(Operation::Grow, Some(new_layout), result)
+        }
+
+        fn after_grow_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record(Operation::Grow, Some(new_layout), result)
+        }
+
+        fn after_grow_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record(Operation::Grow, Some(new_layout), result)
+        }
+
+        fn after_grow_in_place_zeroed(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record(Operation::Grow, Some(new_layout), result)
+        }
+
+        fn after_shrink(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<NonNull<[u8]>, AllocError>,
+        ) {
+            self.record(Operation::Shrink, Some(new_layout), result)
+        }
+
+        fn after_shrink_in_place(
+            &self,
+            _ptr: NonNull<u8>,
+            _old_layout: Layout,
+            new_layout: Layout,
+            result: Result<usize, AllocError>,
+        ) {
+            self.record(Operation::Shrink, Some(new_layout), result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        advise,
+        Advice,
+        AlignmentAsserter,
+        AtomicCounter,
+        AtomicCounterOf,
+        Bucket,
+        ConcurrentEventLog,
+        Counter,
+        CounterOf,
+        Event,
+        FilteredAtomicCounter,
+        FilteredCounter,
+        FilteredCounterOf,
+        LastError,
+        Operation,
+        SizeClassFilter,
+        SizeHistogram,
+        Slack,
+        SoftLimits,
+        Watermark,
+    };
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        AllocateAll,
+        Allocator,
+        CallbackRef,
+        Chunk,
+        Fallback,
+        Owns,
+        Proxy,
+        ReallocateInPlace,
+        Segregate,
+    };
+    use alloc::{alloc::Global, vec::Vec};
+    use core::{
+        alloc::Layout,
+        cell::Cell,
+        mem::MaybeUninit,
+        ptr::NonNull,
+    };
+
+    #[allow(clippy::too_many_lines)]
+    fn run_suite(callbacks: &impl CallbackRef) {
+        let mut region = [MaybeUninit::new(0); 64];
+        let region = tracker(Proxy {
+            alloc: Chunk::<Region, 2>(Region::new(&mut region)),
+            callbacks,
+        });
+
+        assert!(region.allocate(Layout::new::<[u8; 256]>()).is_err());
+        assert!(region.allocate_zeroed(Layout::new::<[u8; 256]>()).is_err());
+        assert!(!region.owns(NonNull::slice_from_raw_parts(NonNull::dangling(), 0)));
+
+        unsafe {
+            let memory = region.allocate(Layout::new::<[u8; 4]>()).unwrap();
+            let memory_tmp = region.allocate_zeroed(Layout::new::<[u8; 4]>()).unwrap();
+            assert!(
+                region
+                    .shrink_in_place(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 4]>(),
+                        Layout::new::<[u8; 2]>(),
+                    )
+                    .is_err()
+            );
+            region.deallocate(memory_tmp.as_non_null_ptr(), Layout::new::<[u8; 4]>());
+
+            assert!(
+                region
+                    .grow_zeroed(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 4]>(),
+                        Layout::new::<[u8; 80]>()
+                    )
+                    .is_err()
+            );
+            assert!(
+                region
+                    .grow_in_place_zeroed(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 4]>(),
+                        Layout::new::<[u8; 80]>()
+                    )
+                    .is_err()
+            );
+            assert!(
+                region
+                    .grow(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 4]>(),
+                        Layout::new::<[u8; 80]>()
+                    )
+                    .is_err()
+            );
+            assert!(
+                region
+                    .grow_in_place(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 4]>(),
+                        Layout::new::<[u8; 80]>()
+                    )
+                    .is_err()
+            );
+            let memory = region
+                .grow_zeroed(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 4]>(),
+                    Layout::new::<[u8; 4]>(),
+                )
+                .unwrap();
+            let memory = region
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 4]>(),
+                    Layout::new::<[u8; 4]>(),
+                )
+                .unwrap();
+            let memory = region
                 .shrink(
                     memory.as_non_null_ptr(),
                     Layout::new::<[u8; 4]>(),
@@ -774,7 +3451,7 @@ mod tests {
                 .unwrap();
 
             assert!(region.owns(memory));
-            region.dealloc(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>());
+            region.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>());
         }
     }
 
@@ -789,6 +3466,7 @@ mod tests {
         assert_eq!(counter.num_shrinks(), 3);
         assert_eq!(counter.num_owns(), 2);
         assert_eq!(counter.num_deallocs(), 2);
+        assert_eq!(counter.num_moves(), 0);
 
         let atomic_counter = AtomicCounter::default();
         run_suite(atomic_counter.by_ref());
@@ -837,6 +3515,8 @@ mod tests {
         assert_eq!(counter.num_owns_filter(false), 1);
         assert_eq!(counter.num_owns(), 2);
         assert_eq!(counter.num_deallocates(), 2);
+        assert_eq!(counter.num_grows_moved(), 0);
+        assert_eq!(counter.num_shrinks_moved(), 0);
 
         let atomic_counter = FilteredAtomicCounter::default();
         run_suite(atomic_counter.by_ref());
@@ -869,4 +3549,590 @@ mod tests {
         assert_eq!(atomic_counter, counter);
         assert_eq!(atomic_counter, atomic_counter);
     }
+
+    #[test]
+    fn moves() {
+        let mut primary = [MaybeUninit::new(0); 16];
+        let mut secondary = [MaybeUninit::new(0); 64];
+        let counter = Counter::default();
+        let filtered_counter = FilteredCounter::default();
+        let alloc = tracker(Proxy {
+            alloc: Proxy {
+                alloc: Fallback {
+                    primary: Chunk::<_, 16>(Region::new(&mut primary)),
+                    secondary: Region::new(&mut secondary),
+                },
+                callbacks: counter.by_ref(),
+            },
+            callbacks: filtered_counter.by_ref(),
+        });
+
+        unsafe {
+            let memory = alloc.allocate(Layout::new::<[u8; 4]>()).unwrap();
+            let memory = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 4]>(),
+                    Layout::new::<[u8; 8]>(),
+                )
+                .unwrap();
+            let memory = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 32]>(),
+                )
+                .unwrap();
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 32]>());
+        }
+
+        assert_eq!(counter.num_grows(), 2);
+        assert_eq!(counter.num_moves(), 1);
+        assert_eq!(filtered_counter.num_grows_moved(), 1);
+        assert_eq!(filtered_counter.num_shrinks_moved(), 0);
+    }
+
+    #[test]
+    fn peak_live() {
+        fn run(alloc: &impl Allocator) {
+            let small = Layout::new::<[u8; 8]>();
+            let large = Layout::new::<[u8; 32]>();
+            unsafe {
+                let a = alloc.allocate(small).expect("Could not allocate 8 bytes");
+                let b = alloc
+                    .allocate_zeroed(small)
+                    .expect("Could not allocate 8 bytes");
+                let b = alloc
+                    .grow(b.as_non_null_ptr(), small, large)
+                    .expect("Could not grow to 32 bytes");
+                alloc.deallocate(a.as_non_null_ptr(), small);
+                let c = alloc.allocate(small).expect("Could not allocate 8 bytes");
+                alloc.deallocate(b.as_non_null_ptr(), large);
+                alloc.deallocate(c.as_non_null_ptr(), small);
+            }
+        }
+
+        // Whether `grow` moves the memory block has to be deterministic to compare both counters
+        let mut memory = [MaybeUninit::new(0); 128];
+        let counter = FilteredCounter::default();
+        run(&tracker(Proxy {
+            alloc: Region::<Up>::with_direction(&mut memory),
+            callbacks: counter.by_ref(),
+        }));
+        assert_eq!(counter.live_blocks(), 0);
+        assert_eq!(counter.live_bytes(), 0);
+        assert_eq!(counter.peak_live_blocks(), 2);
+        assert_eq!(counter.peak_live_bytes(), 40);
+
+        let mut memory = [MaybeUninit::new(0); 128];
+        let atomic_counter = FilteredAtomicCounter::default();
+        run(&tracker(Proxy {
+            alloc: Region::<Up>::with_direction(&mut memory),
+            callbacks: atomic_counter.by_ref(),
+        }));
+        assert_eq!(atomic_counter.peak_live_blocks(), 2);
+        assert_eq!(atomic_counter.peak_live_bytes(), 40);
+        assert_eq!(counter, atomic_counter);
+    }
+
+    #[test]
+    fn peak_live_size_classes() {
+        fn run(alloc: &impl Allocator) {
+            let small = Layout::new::<[u8; 8]>();
+            let large = Layout::new::<[u8; 64]>();
+            let allocate = |layout| alloc.allocate(layout).expect("Could not allocate");
+            unsafe {
+                let a = allocate(small);
+                let b = allocate(small);
+                let c = allocate(small);
+                alloc.deallocate(b.as_non_null_ptr(), small);
+                alloc.deallocate(c.as_non_null_ptr(), small);
+                let d = allocate(large);
+                let e = allocate(large);
+                let e = alloc
+                    .shrink(e.as_non_null_ptr(), large, small)
+                    .expect("Could not shrink to 8 bytes");
+                alloc.deallocate(a.as_non_null_ptr(), small);
+                alloc.deallocate(d.as_non_null_ptr(), large);
+                alloc.deallocate(e.as_non_null_ptr(), small);
+            }
+        }
+
+        let counter = FilteredCounter::default();
+        run(&tracker(Proxy {
+            alloc: Global,
+            callbacks: counter.by_ref(),
+        }));
+        assert_eq!(counter.live_blocks_filter(8), 0);
+        assert_eq!(counter.live_bytes_filter(64), 0);
+
+        // Three small blocks were live at the beginning, two large blocks later on
+        assert_eq!(counter.peak_live_blocks_filter(8), 3);
+        assert_eq!(counter.peak_live_bytes_filter(8), 24);
+        assert_eq!(counter.peak_live_blocks_filter(64), 2);
+        assert_eq!(counter.peak_live_bytes_filter(64), 128);
+        assert_eq!(counter.peak_live_blocks_filter(5), 3);
+        assert_eq!(counter.peak_live_blocks_filter(33), 2);
+        assert_eq!(counter.peak_live_blocks_filter(16), 0);
+        assert_eq!(counter.peak_live_blocks(), 3);
+        assert_eq!(counter.peak_live_bytes(), 136);
+        assert_eq!(
+            counter.peak_live_bytes_filter(SizeClassFilter::None),
+            counter.peak_live_bytes()
+        );
+
+        let atomic_counter = FilteredAtomicCounter::default();
+        run(&tracker(Proxy {
+            alloc: Global,
+            callbacks: atomic_counter.by_ref(),
+        }));
+        assert_eq!(atomic_counter.peak_live_blocks_filter(8), 3);
+        assert_eq!(atomic_counter.peak_live_blocks_filter(64), 2);
+    }
+
+    #[test]
+    fn narrow_counter() {
+        let counter = CounterOf::<u16>::default();
+        run_suite(counter.by_ref());
+        assert_eq!(counter.num_allocs(), 4_u16);
+        assert_eq!(counter.num_grows(), 8_u16);
+
+        let atomic_counter = AtomicCounterOf::<usize>::default();
+        run_suite(atomic_counter.by_ref());
+        assert_eq!(atomic_counter.num_allocs(), 4_usize);
+        assert_eq!(atomic_counter.num_grows(), 8_usize);
+
+        let filtered_counter = FilteredCounterOf::<u8>::default();
+        for _ in 0..u8::MAX {
+            filtered_counter.after_owns(true);
+        }
+        filtered_counter.after_owns(false);
+        filtered_counter.after_owns(false);
+        assert_eq!(filtered_counter.num_owns_filter(true), u8::MAX);
+        assert_eq!(filtered_counter.num_owns(), 1);
+    }
+
+    #[test]
+    fn size_histogram() {
+        let histogram = SizeHistogram::default();
+        let alloc = tracker(Proxy {
+            alloc: Global,
+            callbacks: histogram.by_ref(),
+        });
+        let allocate = |size, n| {
+            let layout = Layout::from_size_align(size, 1).unwrap();
+            for _ in 0..n {
+                let memory = alloc.allocate(layout).expect("Could not allocate");
+                unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+            }
+        };
+        allocate(12, 100);
+        allocate(24, 100);
+        allocate(1000, 10);
+        allocate(100_000, 1);
+
+        assert_eq!(histogram.num_records(), 211);
+        let buckets = histogram.buckets().collect::<Vec<_>>();
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0], Bucket {
+            max_size: 16,
+            count: 100,
+            bytes: 1200,
+        });
+        assert_eq!(buckets[3].max_size, 131_072);
+
+        assert_eq!(advise(&histogram), Advice {
+            chunk_size: 8,
+            segregate_threshold: 32,
+            pool_block_sizes: [16, 32, 0, 0],
+        });
+        assert_eq!(advise(&SizeHistogram::default()), Advice {
+            chunk_size: 1,
+            segregate_threshold: 1,
+            pool_block_sizes: [0; 4],
+        });
+    }
+
+    #[test]
+    fn slack() {
+        let small = Slack::default();
+        let large = Slack::default();
+        let alloc = tracker(Segregate::<_, _, 32> {
+            small: Proxy {
+                alloc: Chunk::<_, 16>(Global),
+                callbacks: small.by_ref(),
+            },
+            large: Proxy {
+                alloc: Chunk::<_, 64>(Global),
+                callbacks: large.by_ref(),
+            },
+        });
+
+        unsafe {
+            let memory = alloc
+                .allocate(Layout::new::<[u8; 20]>())
+                .expect("Could not allocate 20 bytes");
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 20]>(),
+                    Layout::new::<[u8; 17]>(),
+                )
+                .expect("Could not shrink to 17 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 17]>());
+
+            let memory = alloc
+                .allocate_zeroed(Layout::new::<[u8; 100]>())
+                .expect("Could not allocate 100 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 100]>());
+        }
+        assert!(alloc.allocate(Layout::new::<[u8; 0]>()).is_ok());
+
+        assert_eq!(small.num_records(), 3);
+        assert_eq!(small.requested_bytes(), 37);
+        assert_eq!(small.total_slack(), 12 + 15);
+        assert_eq!(small.max_slack(), 15);
+        assert_eq!(large.num_records(), 1);
+        assert_eq!(large.total_slack(), 28);
+        assert_eq!(large.max_slack(), 28);
+    }
+
+    #[test]
+    fn alignment_asserter() {
+        let alignments = AlignmentAsserter::counting(16);
+        let alloc = tracker(Proxy {
+            alloc: Global,
+            callbacks: alignments.by_ref(),
+        });
+
+        unsafe {
+            let memory = alloc
+                .allocate(Layout::new::<u32>())
+                .expect("Could not allocate 4 bytes");
+            let aligned = Layout::from_size_align(64, 64).expect("Invalid layout");
+            let memory = alloc
+                .grow(memory.as_non_null_ptr(), Layout::new::<u32>(), aligned)
+                .expect("Could not grow to 64 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), aligned);
+        }
+
+        assert_eq!(alignments.num_requests(4), 1);
+        assert_eq!(alignments.num_requests(64), 1);
+        assert_eq!(alignments.num_requests(3), 0);
+        assert_eq!(alignments.num_exceeding(), 1);
+        assert_eq!(alignments.alignments().collect::<Vec<_>>(), [
+            (4, 1),
+            (64, 1)
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requested alignment of 32 exceeds the maximum alignment of 16")]
+    fn alignment_asserter_panics() {
+        let alloc = Proxy {
+            alloc: Global,
+            callbacks: AlignmentAsserter::new(16),
+        };
+        let _ = alloc.allocate(Layout::from_size_align(8, 32).expect("Invalid layout"));
+    }
+
+    #[test]
+    fn concurrent_event_log() {
+        let log = ConcurrentEventLog::<4>::new();
+        let alloc = tracker(Proxy {
+            alloc: Global,
+            callbacks: log.by_ref(),
+        });
+
+        let layout = Layout::new::<[u8; 8]>();
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        let mut events = Vec::new();
+        assert_eq!(log.drain(|event| events.push(event)), 2);
+        assert_eq!(events, [
+            Event::Allocate {
+                layout,
+                result: Ok(memory.as_non_null_ptr()),
+            },
+            Event::Deallocate {
+                ptr: memory.as_non_null_ptr(),
+                layout,
+            },
+        ]);
+        assert!(log.is_empty());
+
+        for _ in 0..6 {
+            log.after_deallocate(NonNull::dangling(), layout);
+        }
+        assert_eq!(log.len(), 4);
+        assert_eq!(log.num_dropped(), 2);
+        assert_eq!(log.drain(|_| {}), 4);
+        log.after_deallocate(NonNull::dangling(), layout);
+        assert_eq!(log.drain(|_| {}), 1);
+        assert_eq!(log.num_dropped(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_event_log_threads() {
+        use alloc::sync::Arc;
+        use std::thread;
+
+        let log = Arc::new(ConcurrentEventLog::<256>::new());
+        let producers = (0..4)
+            .map(|_| {
+                let log = Arc::clone(&log);
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        log.after_deallocate(NonNull::dangling(), Layout::new::<u8>());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut drained = 0;
+        while drained + log.num_dropped() < 4000 {
+            drained += log.drain(|_| {});
+        }
+        for producer in producers {
+            producer.join().expect("Thread panicked");
+        }
+        assert_eq!(drained + log.num_dropped(), 4000);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn watermark() {
+        let watermark = Watermark::default();
+        let mut data = [MaybeUninit::new(0); 64];
+        let alloc = Proxy {
+            alloc: Region::new(&mut data),
+            callbacks: watermark.by_ref(),
+        };
+        assert_eq!(watermark.min_capacity_left(), None);
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        unsafe {
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 16]>());
+        }
+        alloc.deallocate_all();
+        assert_eq!(alloc.capacity_left(), 64);
+        assert_eq!(watermark.min_capacity_left(), Some(48));
+        alloc
+            .allocate(Layout::new::<[u8; 64]>())
+            .expect("Could not allocate 64 bytes");
+        assert!(alloc.allocate(Layout::new::<u8>()).is_err());
+        assert_eq!(watermark.min_capacity_left(), Some(0));
+
+        watermark.reset();
+        assert_eq!(watermark.min_capacity_left(), None);
+    }
+
+    #[test]
+    fn soft_limits() {
+        struct Levels(Cell<[usize; 4]>, Cell<usize>);
+
+        unsafe impl CallbackRef for Levels {
+            fn on_watermark(&self, level: usize, used: usize, limit: usize) {
+                assert_eq!(limit, 100);
+                let mut levels = self.0.get();
+                levels[self.1.get()] = level * 1000 + used;
+                self.0.set(levels);
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        let levels = Levels(Cell::new([0; 4]), Cell::new(0));
+        let alloc = tracker(Proxy {
+            alloc: Global,
+            callbacks: SoftLimits::new(100, [50, 90], levels.by_ref()),
+        });
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 40]>())
+            .expect("Could not allocate 40 bytes");
+        assert_eq!(levels.1.get(), 0);
+        let memory = unsafe {
+            alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 40]>(),
+                    Layout::new::<[u8; 95]>(),
+                )
+                .expect("Could not grow to 95 bytes")
+        };
+        let other = alloc
+            .allocate_zeroed(Layout::new::<[u8; 10]>())
+            .expect("Could not allocate 10 bytes");
+        assert_eq!(alloc.alloc.callbacks.used(), 105);
+        unsafe {
+            let memory = alloc
+                .shrink(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 95]>(),
+                    Layout::new::<[u8; 50]>(),
+                )
+                .expect("Could not shrink to 50 bytes");
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 50]>());
+            alloc.deallocate(other.as_non_null_ptr(), Layout::new::<[u8; 10]>());
+        }
+
+        assert_eq!(levels.0.get(), [2095, 1060, 10, 0]);
+        assert_eq!(alloc.alloc.callbacks.level(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn latency_histogram() {
+        use super::{LatencyHistogram, Operation};
+        use crate::StdClock;
+        use core::time::Duration;
+
+        let alloc = Proxy {
+            alloc: Global,
+            callbacks: LatencyHistogram::<StdClock>::default(),
+        };
+        assert_eq!(alloc.callbacks.quantile(Operation::Allocate, 0.5), None);
+
+        for _ in 0..10 {
+            let memory = alloc.allocate(Layout::new::<[u8; 8]>()).unwrap();
+            unsafe {
+                let memory = alloc
+                    .grow(
+                        memory.as_non_null_ptr(),
+                        Layout::new::<[u8; 8]>(),
+                        Layout::new::<[u8; 64]>(),
+                    )
+                    .unwrap();
+                alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+            }
+        }
+        let latencies = &alloc.callbacks;
+        assert_eq!(latencies.num_records(Operation::Allocate), 10);
+        assert_eq!(latencies.num_records(Operation::Grow), 10);
+        assert_eq!(latencies.num_records(Operation::Deallocate), 10);
+        assert_eq!(latencies.num_records(Operation::Shrink), 0);
+
+        let buckets = latencies.buckets(Operation::Allocate).collect::<Vec<_>>();
+        assert_eq!(buckets.iter().map(|bucket| bucket.count).sum::<u64>(), 10);
+        assert!(buckets
+            .windows(2)
+            .all(|pair| pair[0].max_duration < pair[1].max_duration));
+
+        let median = latencies.quantile(Operation::Allocate, 0.5).unwrap();
+        let tail = latencies.quantile(Operation::Allocate, 1.0).unwrap();
+        assert!(median <= tail);
+        assert!(tail <= latencies.max(Operation::Allocate));
+
+        latencies.reset();
+        assert_eq!(latencies.num_records(Operation::Allocate), 0);
+        assert_eq!(latencies.max(Operation::Allocate), Duration::ZERO);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg_attr(miri, ignore)]
+    fn backtrace_tracker() {
+        use super::BacktraceTracker;
+        use alloc::string::String;
+        use core::num::NonZeroU64;
+
+        let tracker = BacktraceTracker::with_sampling(NonZeroU64::new(2).unwrap());
+        let alloc = Proxy {
+            alloc: Global,
+            callbacks: tracker.by_ref(),
+        };
+
+        let first = alloc.allocate(Layout::new::<[u8; 4]>()).unwrap();
+        let second = alloc.allocate_zeroed(Layout::new::<[u8; 8]>()).unwrap();
+        let second = unsafe {
+            alloc
+                .grow(
+                    second.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 16]>(),
+                )
+                .unwrap()
+        };
+        assert_eq!(tracker.num_outstanding(), 2);
+
+        let mut sampled = 0;
+        tracker.for_each_outstanding(|allocation| {
+            if allocation.ptr == second.as_non_null_ptr() {
+                assert_eq!(allocation.layout, Layout::new::<[u8; 16]>());
+                assert!(allocation.backtrace.is_none());
+            } else {
+                assert_eq!(allocation.ptr, first.as_non_null_ptr());
+                assert!(allocation.backtrace.is_some());
+            }
+            sampled += allocation.backtrace.is_some() as usize;
+        });
+        assert_eq!(sampled, 1);
+
+        let third = alloc.allocate(Layout::new::<[u8; 2]>()).unwrap();
+        assert_eq!(tracker.iter_live().collect::<Vec<_>>(), [
+            (first.as_non_null_ptr(), Layout::new::<[u8; 4]>()),
+            (second.as_non_null_ptr(), Layout::new::<[u8; 16]>()),
+            (third.as_non_null_ptr(), Layout::new::<[u8; 2]>()),
+        ]);
+        unsafe { alloc.deallocate(third.as_non_null_ptr(), Layout::new::<[u8; 2]>()) };
+
+        let mut report = Vec::new();
+        tracker.dump(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("2 outstanding allocation(s)"));
+        assert!(report.contains("with size 16 and alignment 1"));
+        assert!(report.contains("<not sampled>"));
+
+        unsafe {
+            alloc.deallocate(first.as_non_null_ptr(), Layout::new::<[u8; 4]>());
+            alloc.deallocate(second.as_non_null_ptr(), Layout::new::<[u8; 16]>());
+        }
+        assert_eq!(tracker.num_outstanding(), 0);
+    }
+
+    #[test]
+    fn last_error() {
+        let errors = LastError::new();
+        let mut data = [MaybeUninit::new(0); 32];
+        let alloc = Proxy {
+            alloc: Region::new(&mut data),
+            callbacks: errors.by_ref(),
+        };
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert_eq!(errors.last_error(), None);
+        unsafe {
+            alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::new::<[u8; 64]>(),
+                )
+                .expect_err("Could grow to 64 bytes");
+        }
+        let failure = errors.take().expect("Failure was not recorded");
+        assert_eq!(failure.operation, Operation::Grow);
+        assert_eq!(failure.layout, Some(Layout::new::<[u8; 64]>()));
+        assert_eq!(failure.reason, None);
+        assert_eq!(errors.last_error(), None);
+
+        // A reason is only attached to the request, which is currently processed
+        let _ = errors.deny("stale");
+        alloc
+            .allocate(Layout::new::<u8>())
+            .expect("Could not allocate 1 byte");
+        let _ = errors.deny("frozen");
+        alloc.allocate_all().expect("Could not allocate all");
+        alloc
+            .allocate(Layout::new::<u8>())
+            .expect_err("Could allocate 1 byte in a full region");
+        assert_eq!(
+            errors.last_error().map(|failure| failure.reason),
+            Some(None)
+        );
+    }
 }
@@ -0,0 +1,351 @@
+use crate::{
+    helper::{grow_fallback, AllocInit},
+    sync::atomic::{AtomicUsize, Ordering},
+    AllocateNear,
+    AllocateWithHint,
+    Allocator,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+};
+
+/// Spreads allocations over `N` allocators, called stripes.
+///
+/// Every allocation is served by the next stripe in a round-robin fashion, or by the stripe at the
+/// index passed to [`allocate_with_hint`] modulo `N`. When the selected stripe fails, the following
+/// stripes are tried in order. This spreads the traffic of arenas over multiple memory banks, or
+/// implements simple per-core arenas, when the index of the current core is passed as hint.
+///
+/// Memory blocks are returned to the stripe, which owns them, so every stripe has to implement
+/// [`Owns`]. A grown memory block stays in its stripe if possible, and is moved to another
+/// stripe otherwise.
+///
+/// [`allocate_with_hint`]: crate::AllocateWithHint::allocate_with_hint
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, AllocateWithHint, Owns, Striped};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut bank0 = [MaybeUninit::uninit(); 64];
+/// let mut bank1 = [MaybeUninit::uninit(); 64];
+/// let alloc = Striped::new([Region::new(&mut bank0), Region::new(&mut bank1)]);
+///
+/// let first = alloc.allocate(Layout::new::<u32>())?;
+/// let second = alloc.allocate(Layout::new::<u32>())?;
+/// assert!(alloc.stripes[0].owns(first));
+/// assert!(alloc.stripes[1].owns(second));
+///
+/// let hinted = alloc.allocate_with_hint(Layout::new::<u32>(), 3)?;
+/// assert!(alloc.stripes[1].owns(hinted));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug)]
+pub struct Striped<A, const N: usize> {
+    pub stripes: [A; N],
+    next: AtomicUsize,
+}
+
+impl<A, const N: usize> Striped<A, N> {
+    /// Spreads the allocations over `stripes`, starting with the first one.
+    pub fn new(stripes: [A; N]) -> Self {
+        Self {
+            stripes,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<A: Default, const N: usize> Default for Striped<A, N> {
+    fn default() -> Self {
+        Self::new([(); N].map(|_| A::default()))
+    }
+}
+
+impl<A: Allocator + Owns, const N: usize> Striped<A, N> {
+    /// Allocates from the stripe at `start` and from the following stripes, if it fails.
+    fn allocate_from(
+        &self,
+        start: usize,
+        layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        for index in (start..N).chain(0..start) {
+            let stripe = &self.stripes[index];
+            let result = match init {
+                AllocInit::Uninitialized => stripe.allocate(layout),
+                AllocInit::Zeroed => stripe.allocate_zeroed(layout),
+            };
+            if result.is_ok() {
+                return result;
+            }
+        }
+        Err(AllocError)
+    }
+
+    fn next_stripe(&self) -> usize {
+        if N == 0 {
+            0
+        } else {
+            self.next.fetch_add(1, Ordering::Relaxed) % N
+        }
+    }
+
+    fn stripe_of(&self, ptr: NonNull<u8>) -> Option<usize> {
+        self.stripes.iter().position(|stripe| stripe.owns_ptr(ptr))
+    }
+
+    /// Returns the index of the stripe, which owns the memory block.
+    fn owner(&self, ptr: NonNull<u8>, layout: Layout) -> usize {
+        let memory = NonNull::slice_from_raw_parts(ptr, layout.size());
+        self.stripes
+            .iter()
+            .position(|stripe| stripe.owns(memory))
+            .expect("The memory block is not owned by any stripe")
+    }
+
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let index = self.owner(ptr, old_layout);
+        let owner = &self.stripes[index];
+        let result = match init {
+            AllocInit::Uninitialized => owner.grow(ptr, old_layout, new_layout),
+            AllocInit::Zeroed => owner.grow_zeroed(ptr, old_layout, new_layout),
+        };
+        if result.is_ok() {
+            return result;
+        }
+        for other in (index + 1..N).chain(0..index) {
+            let result = grow_fallback(
+                owner,
+                &self.stripes[other],
+                ptr,
+                old_layout,
+                new_layout,
+                init,
+            );
+            if result.is_ok() {
+                return result;
+            }
+        }
+        Err(AllocError)
+    }
+}
+
+unsafe impl<A: Allocator + Owns, const N: usize> Allocator for Striped<A, N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_from(self.next_stripe(), layout, AllocInit::Uninitialized)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_from(self.next_stripe(), layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.stripes[self.owner(ptr, layout)].deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Uninitialized)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.stripes[self.owner(ptr, old_layout)].shrink(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: Allocator + Owns, const N: usize> AllocateWithHint for Striped<A, N> {
+    fn allocate_with_hint(&self, layout: Layout, hint: usize) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_from(
+            hint.checked_rem(N).unwrap_or(0),
+            layout,
+            AllocInit::Uninitialized,
+        )
+    }
+
+    fn allocate_zeroed_with_hint(
+        &self,
+        layout: Layout,
+        hint: usize,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_from(hint.checked_rem(N).unwrap_or(0), layout, AllocInit::Zeroed)
+    }
+}
+
+impl<A: Allocator + Owns, const N: usize> AllocateNear for Striped<A, N> {
+    /// Allocates from the stripe owning `hint` first, or from the next stripe otherwise.
+    fn allocate_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let start = self.stripe_of(hint).unwrap_or_else(|| self.next_stripe());
+        self.allocate_from(start, layout, AllocInit::Uninitialized)
+    }
+
+    /// Allocates from the stripe owning `hint` first, or from the next stripe otherwise.
+    fn allocate_zeroed_near(
+        &self,
+        layout: Layout,
+        hint: NonNull<u8>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let start = self.stripe_of(hint).unwrap_or_else(|| self.next_stripe());
+        self.allocate_from(start, layout, AllocInit::Zeroed)
+    }
+}
+
+impl<A: Owns, const N: usize> Owns for Striped<A, N> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.stripes.iter().any(|stripe| stripe.owns(memory))
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.stripes.iter().any(|stripe| stripe.owns_ptr(ptr))
+    }
+}
+
+impl<A: ReportUsage, const N: usize> ReportUsage for Striped<A, N> {
+    fn usage(&self) -> Usage {
+        self.stripes
+            .iter()
+            .fold(Usage::default(), |usage, stripe| usage + stripe.usage())
+    }
+}
+
+impl<A: Describe, const N: usize> Describe for Striped<A, N> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Striped<{}>", N)
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        for stripe in &self.stripes {
+            visit(stripe)
+        }
+    }
+}
+
+impl<A: Purge, const N: usize> Purge for Striped<A, N> {
+    fn purge(&self, level: PurgeLevel) {
+        for stripe in &self.stripes {
+            stripe.purge(level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Striped;
+    use crate::{helper, region::Region, AllocateNear, AllocateWithHint, Allocator, Owns};
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn round_robin() {
+        let mut data = [[MaybeUninit::new(0); 48]; 3];
+        let [a, b, c] = &mut data;
+        let alloc = Striped::new([
+            helper::tracker(Region::new(a)),
+            helper::tracker(Region::new(b)),
+            helper::tracker(Region::new(c)),
+        ]);
+        let layout = Layout::new::<[u8; 16]>();
+
+        for index in 0..3 {
+            let memory = alloc.allocate(layout).expect("Could not allocate 16 bytes");
+            assert!(alloc.stripes[index].owns(memory));
+        }
+        let memory = alloc
+            .allocate_zeroed_with_hint(layout, 4)
+            .expect("Could not allocate 16 bytes");
+        assert!(alloc.stripes[1].owns(memory));
+
+        // The second stripe is too small, so the third one is used
+        let large = Layout::new::<[u8; 32]>();
+        let hinted = alloc
+            .allocate_with_hint(large, 1)
+            .expect("Could not allocate 32 bytes");
+        assert!(alloc.stripes[2].owns(hinted));
+
+        // Growing moves the memory block to a stripe with enough space
+        let grown = unsafe {
+            alloc
+                .grow_zeroed(memory.as_non_null_ptr(), layout, large)
+                .expect("Could not grow to 32 bytes")
+        };
+        assert!(alloc.stripes[0].owns(grown));
+        assert!(unsafe { grown.as_ref() }.iter().all(|&b| b == 0));
+        unsafe {
+            alloc.deallocate(grown.as_non_null_ptr(), large);
+            alloc.deallocate(hinted.as_non_null_ptr(), large);
+        }
+
+        alloc
+            .allocate(Layout::new::<[u8; 64]>())
+            .expect_err("No stripe can hold 64 bytes");
+    }
+
+    #[test]
+    fn near() {
+        let mut data = crate::aligned_buffer!(64, 8, 0);
+        let (a, b) = data.split_at_mut(32);
+        let alloc = Striped::new([Region::new(a), Region::new(b)]);
+        let layout = Layout::new::<u64>();
+
+        let first = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        for _ in 0..3 {
+            let memory = alloc
+                .allocate_near(layout, first.as_non_null_ptr())
+                .expect("Could not allocate 8 bytes");
+            assert!(alloc.stripes[0].owns(memory));
+        }
+        let memory = alloc
+            .allocate_zeroed_near(layout, first.as_non_null_ptr())
+            .expect("Could not allocate 8 bytes");
+        assert!(alloc.stripes[1].owns(memory));
+    }
+}
@@ -0,0 +1,27 @@
+//! Synchronization primitives of the lock-free and the thread-caching allocators.
+//!
+//! When compiled with `RUSTFLAGS="--cfg loom"`, the primitives of [`loom`] are used instead of the
+//! ones of `core` and `std`, so the tests in `tests/loom.rs` can explore every interleaving of the
+//! compare-exchange loops and statistic updates:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --features std --test loom
+//! ```
+//!
+//! [`loom`]: https://docs.rs/loom
+
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic;
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic;
+
+#[cfg(all(any(doc, feature = "std"), loom))]
+pub(crate) use loom::{
+    sync::{Mutex, MutexGuard},
+    thread_local,
+};
+#[cfg(all(any(doc, feature = "std"), not(loom)))]
+pub(crate) use std::{
+    sync::{Mutex, MutexGuard},
+    thread_local,
+};
@@ -0,0 +1,292 @@
+use crate::{Affix, Allocator, Describe, Owns, Purge, PurgeLevel};
+use core::{
+    alloc::{AllocError, Layout},
+    fmt,
+    ptr::NonNull,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Tags every memory block and checks the tag, when the memory block is deallocated or
+/// reallocated.
+///
+/// This is a software variant of memory tagging like the Memory Tagging Extension of ARM: every
+/// memory block gets a non-zero tag, which is stored in a prefix of one byte in front of it. When
+/// a memory block is deallocated, its tag is cleared. Deallocating, growing, or shrinking a
+/// memory block without a valid tag panics, which catches double frees, freeing memory of another
+/// allocator, and buffer underflows clobbering the prefix. Reallocated memory blocks get a new tag,
+/// and the tag of a moved-out memory block is cleared.
+///
+/// Memory is not inspected on regular accesses, and the underlying allocator may overwrite the
+/// cleared tag after reusing the memory, so not every use-after-free is detected. Composing
+/// `Tagging` with [`DeferredFree`] delays the reuse.
+///
+/// [`DeferredFree`]: crate::DeferredFree
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{region::Region, Tagging};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let alloc = Tagging::new(Region::new(&mut data));
+///
+/// let layout = Layout::new::<u32>();
+/// let memory = alloc.allocate(layout)?;
+/// assert!(unsafe { Tagging::<Region>::tag(memory.as_non_null_ptr(), layout) }.is_some());
+/// unsafe {
+///     alloc.deallocate(memory.as_non_null_ptr(), layout);
+///     // Panics
+///     alloc.deallocate(memory.as_non_null_ptr(), layout);
+/// }
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[cfg_attr(doc, doc(cfg(target_has_atomic = "8")))]
+pub struct Tagging<A> {
+    alloc: Affix<A, u8>,
+    next_tag: AtomicU8,
+}
+
+impl<A> Tagging<A> {
+    /// The tag of a memory block, which is not allocated.
+    const UNTAGGED: u8 = 0;
+
+    /// Tags the memory blocks of `alloc`.
+    pub const fn new(alloc: A) -> Self {
+        Self {
+            alloc: Affix::new(alloc),
+            next_tag: AtomicU8::new(1),
+        }
+    }
+
+    /// Returns the underlying allocator.
+    pub const fn parent(&self) -> &A {
+        &self.alloc.parent
+    }
+
+    /// Returns the layout requested from the underlying allocator for a memory block of `layout`.
+    ///
+    /// Returns `None`, if the layout extended by the tag overflows.
+    pub const fn parent_layout(layout: Layout) -> Option<Layout> {
+        Affix::<A, u8>::parent_layout(layout)
+    }
+
+    /// Returns the number of bytes requested on top of `layout.size()` to store the tag.
+    ///
+    /// Returns `None`, if the layout extended by the tag overflows.
+    pub const fn overhead(layout: Layout) -> Option<usize> {
+        Affix::<A, u8>::overhead(layout)
+    }
+
+    /// Returns the tag of a memory block or `None`, if it's not tagged.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `layout` must denote a memory block, which was allocated via this allocator. The
+    /// memory block may have been deallocated, as long as the underlying allocator still holds
+    /// the memory.
+    pub unsafe fn tag(ptr: NonNull<u8>, layout: Layout) -> Option<u8> {
+        match Affix::<A, u8>::prefix(ptr, layout).as_ptr().read() {
+            Self::UNTAGGED => None,
+            tag => Some(tag),
+        }
+    }
+
+    fn next_tag(&self) -> u8 {
+        loop {
+            let tag = self.next_tag.fetch_add(1, Ordering::Relaxed);
+            if tag != Self::UNTAGGED {
+                return tag;
+            }
+        }
+    }
+
+    unsafe fn set_tag(ptr: NonNull<u8>, layout: Layout, tag: u8) {
+        Affix::<A, u8>::prefix(ptr, layout).as_ptr().write(tag)
+    }
+
+    /// Returns the tag of the memory block and clears it.
+    #[track_caller]
+    unsafe fn take_tag(ptr: NonNull<u8>, layout: Layout) -> u8 {
+        match Self::tag(ptr, layout) {
+            Some(tag) => {
+                Self::set_tag(ptr, layout, Self::UNTAGGED);
+                tag
+            }
+            None => panic!(
+                "The memory block at {:p} is not tagged. It was either deallocated already or \
+                 allocated by another allocator",
+                ptr
+            ),
+        }
+    }
+
+    fn tagged(
+        &self,
+        layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = result?;
+        unsafe { Self::set_tag(memory.as_non_null_ptr(), layout, self.next_tag()) };
+        Ok(memory)
+    }
+
+    /// Clears the tag of the old memory block before reallocating it and tags the result.
+    #[track_caller]
+    unsafe fn retag(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        realloc: impl FnOnce() -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let tag = Self::take_tag(ptr, old_layout);
+        let result = realloc();
+        if result.is_err() {
+            Self::set_tag(ptr, old_layout, tag);
+        }
+        self.tagged(new_layout, result)
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for Tagging<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tagging")
+            .field("parent", self.parent())
+            .finish()
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Tagging<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.tagged(layout, self.alloc.allocate(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.tagged(layout, self.alloc.allocate_zeroed(layout))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        Self::take_tag(ptr, layout);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.retag(ptr, old_layout, new_layout, || {
+            self.alloc.grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.retag(ptr, old_layout, new_layout, || {
+            self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.retag(ptr, old_layout, new_layout, || {
+            self.alloc.shrink(ptr, old_layout, new_layout)
+        })
+    }
+}
+
+impl<A: Owns> Owns for Tagging<A> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+}
+
+impl<A: Describe> Describe for Tagging<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Tagging")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(self.parent())
+    }
+}
+
+impl<A: Purge> Purge for Tagging<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.parent().purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tagging;
+    use crate::{helper::tracker, region::Region, Allocator};
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn retag() {
+        let alloc = Tagging::new(tracker(Global));
+        let layout = Layout::new::<[u8; 8]>();
+        let grown = Layout::new::<[u8; 64]>();
+        unsafe {
+            let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+            let ptr = memory.as_non_null_ptr();
+            assert_eq!(Tagging::<Global>::tag(ptr, layout), Some(1));
+
+            let memory = alloc
+                .grow_zeroed(ptr, layout, grown)
+                .expect("Could not grow to 64 bytes");
+            assert!(memory.as_ref().iter().skip(8).all(|&b| b == 0));
+            let ptr = memory.as_non_null_ptr();
+            assert_eq!(Tagging::<Global>::tag(ptr, grown), Some(2));
+
+            let memory = alloc
+                .shrink(ptr, grown, layout)
+                .expect("Could not shrink to 8 bytes");
+            let ptr = memory.as_non_null_ptr();
+            assert_eq!(Tagging::<Global>::tag(ptr, layout), Some(3));
+            alloc.deallocate(ptr, layout);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "is not tagged")]
+    fn double_free() {
+        let mut data = [MaybeUninit::new(0); 32];
+        let alloc = Tagging::new(Region::new(&mut data));
+        let layout = Layout::new::<u64>();
+        let memory = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        unsafe {
+            alloc.deallocate(memory.as_non_null_ptr(), layout);
+            assert_eq!(
+                Tagging::<Region>::tag(memory.as_non_null_ptr(), layout),
+                None
+            );
+            alloc.deallocate(memory.as_non_null_ptr(), layout);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use core::time::Duration;
+
+/// A monotonic clock for time-based statistics and policies.
+///
+/// Timestamps are measured in ticks of the clock, which don't have to be nanoseconds. This way,
+/// cheap hardware counters like the cycle counter of a Cortex-M core can be used on embedded
+/// targets, where `std::time::Instant` is not available.
+///
+/// # Examples
+///
+/// A clock counting the milliseconds of a system timer:
+///
+/// ```rust
+/// use alloc_compose::Timestamp;
+/// use core::{
+///     sync::atomic::{AtomicU64, Ordering},
+///     time::Duration,
+/// };
+///
+/// // Incremented by the timer interrupt
+/// static MILLIS: AtomicU64 = AtomicU64::new(0);
+///
+/// struct SysTick;
+///
+/// impl Timestamp for SysTick {
+///     fn now() -> u64 {
+///         MILLIS.load(Ordering::Relaxed)
+///     }
+///
+///     fn to_duration(ticks: u64) -> Duration {
+///         Duration::from_millis(ticks)
+///     }
+/// }
+///
+/// let start = SysTick::now();
+/// MILLIS.fetch_add(3, Ordering::Relaxed);
+/// let ticks = SysTick::elapsed(start, SysTick::now());
+/// assert_eq!(SysTick::to_duration(ticks), Duration::from_millis(3));
+/// ```
+pub trait Timestamp {
+    /// Returns the current value of the clock.
+    fn now() -> u64;
+
+    /// Converts a number of ticks to a duration.
+    fn to_duration(ticks: u64) -> Duration;
+
+    /// Returns the number of ticks between `start` and `end`.
+    ///
+    /// Clocks, which wrap around before `u64::MAX`, override this to handle the wrap around.
+    #[inline]
+    fn elapsed(start: u64, end: u64) -> u64 {
+        end.wrapping_sub(start)
+    }
+}
+
+/// A clock, which never advances.
+///
+/// Allocators with an optional expiry use it by default, so their blocks never expire.
+impl Timestamp for () {
+    #[inline]
+    fn now() -> u64 {
+        0
+    }
+
+    #[inline]
+    fn to_duration(_ticks: u64) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// Counts nanoseconds with [`std::time::Instant`].
+#[cfg(any(doc, feature = "std"))]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StdClock;
+
+#[cfg(any(doc, feature = "std"))]
+impl Timestamp for StdClock {
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn now() -> u64 {
+        static ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        ORIGIN
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_nanos() as u64
+    }
+
+    #[inline]
+    fn to_duration(ticks: u64) -> Duration {
+        Duration::from_nanos(ticks)
+    }
+}
+
+/// Counts the cycles of a Cortex-M core with the cycle counter of its DWT unit.
+///
+/// `FREQUENCY` is the core clock in Hz and is used to convert cycles to durations. The counter
+/// has 32 bits, so it wraps around after a few seconds and measures shorter intervals only. It's
+/// available on Cortex-M3 and above and has to be started once with [`enable`].
+///
+/// [`enable`]: Self::enable
+#[cfg(any(doc, all(feature = "cortex-m", target_arch = "arm")))]
+#[cfg_attr(doc, doc(cfg(all(feature = "cortex-m", target_arch = "arm"))))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DwtCycleCounter<const FREQUENCY: u32>(());
+
+#[cfg(any(doc, all(feature = "cortex-m", target_arch = "arm")))]
+impl<const FREQUENCY: u32> DwtCycleCounter<FREQUENCY> {
+    const DEMCR: *mut u32 = 0xE000_EDFC as *mut u32;
+    const DWT_CTRL: *mut u32 = 0xE000_1000 as *mut u32;
+    const DWT_CYCCNT: *mut u32 = 0xE000_1004 as *mut u32;
+    const NONZERO_FREQUENCY: () = assert!(FREQUENCY != 0, "`FREQUENCY` must not be zero");
+
+    /// Creates a handle to a cycle counter, which was already started.
+    ///
+    /// # Safety
+    ///
+    /// The core must have a DWT unit with a cycle counter, i.e. it's a Cortex-M3 or above, and
+    /// the counter must be enabled, for example with [`enable`].
+    ///
+    /// [`enable`]: Self::enable
+    pub const unsafe fn new() -> Self {
+        let () = Self::NONZERO_FREQUENCY;
+        Self(())
+    }
+
+    /// Enables the trace unit and starts the cycle counter.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on a Cortex-M3 or above, where no other code configures the DWT unit
+    /// concurrently.
+    pub unsafe fn enable() -> Self {
+        Self::DEMCR.write_volatile(Self::DEMCR.read_volatile() | 1 << 24);
+        Self::DWT_CTRL.write_volatile(Self::DWT_CTRL.read_volatile() | 1);
+        Self::new()
+    }
+}
+
+#[cfg(any(doc, all(feature = "cortex-m", target_arch = "arm")))]
+impl<const FREQUENCY: u32> Timestamp for DwtCycleCounter<FREQUENCY> {
+    #[inline]
+    fn now() -> u64 {
+        // SAFETY: reading the cycle counter has no side effects
+        u64::from(unsafe { Self::DWT_CYCCNT.read_volatile() })
+    }
+
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn to_duration(ticks: u64) -> Duration {
+        let () = Self::NONZERO_FREQUENCY;
+        let frequency = u64::from(FREQUENCY);
+        // `rem < FREQUENCY <= u32::MAX`, so the product can't overflow
+        let (secs, rem) = (ticks / frequency, ticks % frequency);
+        Duration::new(secs, (rem * 1_000_000_000 / frequency) as u32)
+    }
+
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn elapsed(start: u64, end: u64) -> u64 {
+        u64::from((end as u32).wrapping_sub(start as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timestamp;
+    use core::time::Duration;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(feature = "std")]
+    fn std_clock() {
+        use super::StdClock;
+
+        let start = StdClock::now();
+        std::thread::sleep(Duration::from_millis(1));
+        let ticks = StdClock::elapsed(start, StdClock::now());
+        assert!(StdClock::to_duration(ticks) >= Duration::from_millis(1));
+    }
+
+    #[test]
+    #[cfg(all(feature = "cortex-m", target_arch = "arm"))]
+    fn dwt_cycle_counter() {
+        type Dwt = super::DwtCycleCounter<64_000_000>;
+
+        assert_eq!(Dwt::to_duration(64), Duration::from_micros(1));
+        assert_eq!(
+            Dwt::to_duration(u64::MAX),
+            Duration::new(u64::MAX / 64_000_000, 711_743_984)
+        );
+        assert_eq!(Dwt::elapsed(u64::from(u32::MAX) - 1, 2), 4);
+    }
+}
@@ -0,0 +1,629 @@
+use crate::{
+    helper::{grow_fallback, shrink_fallback, AllocInit},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+        MutexGuard,
+    },
+    AllocateMany,
+    Allocator,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    Timestamp,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cmp,
+    fmt,
+    marker::PhantomData,
+    num::NonZeroUsize,
+    ptr::NonNull,
+};
+use std::{boxed::Box, sync::PoisonError, vec::Vec};
+
+/// The smallest size class. Every cached block has to hold a pointer to the next free block.
+const MIN_SIZE: usize = 16;
+/// The number of size classes, from `MIN_SIZE` to `MIN_SIZE << (CLASSES - 1)`.
+const CLASSES: usize = 7;
+/// The number of blocks moved between a cache and the parent allocator at once.
+const BATCH: usize = 16;
+
+/// Hands out a distinct index to every thread in the order the threads first allocate.
+#[cfg(not(loom))]
+static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(loom)]
+loom::lazy_static! {
+    static ref NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+}
+
+crate::sync::thread_local! {
+    static THREAD_INDEX: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A singly linked list of free blocks, stored inside of the blocks themselves.
+#[derive(Default)]
+struct FreeList {
+    head: Option<NonNull<u8>>,
+    len: usize,
+    /// The timestamp, when the first block was pushed to the empty list.
+    since: u64,
+}
+
+impl FreeList {
+    unsafe fn push(&mut self, ptr: NonNull<u8>) {
+        ptr.cast::<Option<NonNull<u8>>>().as_ptr().write(self.head);
+        self.head = Some(ptr);
+        self.len += 1;
+    }
+
+    unsafe fn pop(&mut self) -> Option<NonNull<u8>> {
+        let ptr = self.head?;
+        self.head = ptr.cast::<Option<NonNull<u8>>>().as_ptr().read();
+        self.len -= 1;
+        Some(ptr)
+    }
+}
+
+/// The free lists of one thread for every size class.
+#[derive(Default)]
+struct Magazines([FreeList; CLASSES]);
+
+// SAFETY: The free blocks are owned by the magazine and only accessed while it is locked.
+unsafe impl Send for Magazines {}
+
+/// Caches small memory blocks per thread and refills the caches from a shared parent in batches.
+///
+/// Requests up to 1 KiB are rounded up to a power of two and served from a cache of the calling
+/// thread. When a cache runs empty, a batch of blocks is allocated from the parent allocator at
+/// once; when it grows too large, a batch is returned. The parent allocator is protected by a
+/// mutex, so its lock is only taken once per batch instead of once per request. Larger requests
+/// are forwarded to the parent allocator directly.
+///
+/// Every thread is assigned one of the caches round-robin. As long as there are at least as many
+/// caches as threads, no two threads share a cache and locking a cache is uncontended.
+///
+/// Cached blocks are returned to the parent allocator by [`flush`], by [`purge`], or when the
+/// `TlsCache` is dropped. [`PurgeLevel::Trim`] only flushes the cache of the calling thread like
+/// `flush`, while [`PurgeLevel::Full`] flushes the caches of all threads.
+///
+/// With [`with_max_age`], the blocks are also returned, when a cache of the calling thread has
+/// not been empty for `max_age` ticks of the clock `T`. Expired caches are checked on every
+/// cached request and by [`purge_expired`]. By default, `T` is `()`, which never advances.
+///
+/// [`flush`]: Self::flush
+/// [`purge`]: crate::Purge::purge
+/// [`with_max_age`]: Self::with_max_age
+/// [`purge_expired`]: Self::purge_expired
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::TlsCache;
+/// use std::{
+///     alloc::{Allocator, Layout, System},
+///     sync::Arc,
+///     thread,
+/// };
+///
+/// let alloc = Arc::new(TlsCache::new(System));
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let alloc = Arc::clone(&alloc);
+///         thread::spawn(move || {
+///             for _ in 0..100 {
+///                 let memory = alloc.allocate(Layout::new::<[u8; 24]>()).unwrap();
+///                 assert_eq!(memory.len(), 32);
+///                 unsafe {
+///                     alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 24]>())
+///                 };
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// ```
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub struct TlsCache<A: Allocator, T: Timestamp = ()> {
+    parent: Mutex<A>,
+    caches: Box<[Mutex<Magazines>]>,
+    max_age: u64,
+    clock: PhantomData<T>,
+}
+
+impl<A: Allocator> TlsCache<A> {
+    /// Creates a cache in front of `parent` with 32 thread caches.
+    pub fn new(parent: A) -> Self {
+        Self::with_caches(parent, 32)
+    }
+
+    /// Creates a cache in front of `parent` with `caches` thread caches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `caches` is zero.
+    pub fn with_caches(parent: A, caches: usize) -> Self {
+        let caches = NonZeroUsize::new(caches).expect("at least one cache is required");
+        Self::with_max_age(parent, caches, u64::MAX)
+    }
+}
+
+impl<A: Allocator, T: Timestamp> TlsCache<A, T> {
+    /// Creates a cache in front of `parent` with `caches` thread caches, which return their
+    /// blocks after `max_age` ticks of `T`.
+    pub fn with_max_age(parent: A, caches: NonZeroUsize, max_age: u64) -> Self {
+        Self {
+            parent: Mutex::new(parent),
+            caches: (0..caches.get())
+                .map(|_| Mutex::default())
+                .collect::<Vec<_>>()
+                .into(),
+            max_age,
+            clock: PhantomData,
+        }
+    }
+
+    /// Returns all blocks cached for the calling thread to the parent allocator.
+    pub fn flush(&self) {
+        let mut magazines = self.cache();
+        let mut parent = None;
+        for (class, list) in magazines.0.iter_mut().enumerate() {
+            self.release(list, class, list.len, &mut parent);
+        }
+    }
+
+    /// Returns the blocks of all expired caches to the parent allocator and returns the number of
+    /// returned blocks.
+    pub fn purge_expired(&self) -> usize {
+        let mut parent = None;
+        self.caches
+            .iter()
+            .map(|cache| {
+                let mut magazines = cache.lock().unwrap_or_else(PoisonError::into_inner);
+                self.release_expired(&mut magazines, &mut parent)
+            })
+            .sum()
+    }
+
+    /// Returns the blocks cached for all threads to the parent allocator.
+    fn flush_all(&self) {
+        let mut parent = None;
+        for cache in self.caches.iter() {
+            let mut magazines = cache.lock().unwrap_or_else(PoisonError::into_inner);
+            for (class, list) in magazines.0.iter_mut().enumerate() {
+                self.release(list, class, list.len, &mut parent);
+            }
+        }
+    }
+
+    fn lock_parent(&self) -> MutexGuard<'_, A> {
+        self.parent.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn cache(&self) -> MutexGuard<'_, Magazines> {
+        let index = THREAD_INDEX.try_with(|&index| index).unwrap_or(0);
+        self.caches[index % self.caches.len()]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the size class for `layout` or `None`, if it's not cached.
+    fn class_of(layout: Layout) -> Option<usize> {
+        let size = cmp::max(cmp::max(layout.size(), layout.align()), MIN_SIZE);
+        let class = (size.next_power_of_two() / MIN_SIZE).trailing_zeros() as usize;
+        if class < CLASSES {
+            Some(class)
+        } else {
+            None
+        }
+    }
+
+    fn class_layout(class: usize) -> Layout {
+        let size = MIN_SIZE << class;
+        unsafe { Layout::from_size_align_unchecked(size, size) }
+    }
+
+    /// Returns `count` blocks of `list` to the parent allocator and locks it on demand.
+    fn release<'a>(
+        &'a self,
+        list: &mut FreeList,
+        class: usize,
+        count: usize,
+        parent: &mut Option<MutexGuard<'a, A>>,
+    ) {
+        if count == 0 {
+            return;
+        }
+        let parent = parent.get_or_insert_with(|| self.lock_parent());
+        for _ in 0..count {
+            match unsafe { list.pop() } {
+                Some(ptr) => unsafe { parent.deallocate(ptr, Self::class_layout(class)) },
+                None => break,
+            }
+        }
+    }
+
+    /// Returns the blocks of all lists in `magazines`, which exceeded the maximum age, and returns
+    /// the number of returned blocks.
+    fn release_expired<'a>(
+        &'a self,
+        magazines: &mut Magazines,
+        parent: &mut Option<MutexGuard<'a, A>>,
+    ) -> usize {
+        if self.max_age == u64::MAX {
+            return 0;
+        }
+        let now = T::now();
+        let mut released = 0;
+        for (class, list) in magazines.0.iter_mut().enumerate() {
+            if list.len > 0 && T::elapsed(list.since, now) >= self.max_age {
+                released += list.len;
+                self.release(list, class, list.len, parent);
+            }
+        }
+        released
+    }
+
+    /// Pushes `ptr` to `list` and starts its age, if it was empty.
+    unsafe fn push(list: &mut FreeList, ptr: NonNull<u8>) {
+        if list.len == 0 {
+            list.since = T::now();
+        }
+        list.push(ptr);
+    }
+
+    fn alloc_cached(&self, class: usize) -> Result<NonNull<[u8]>, AllocError> {
+        let mut magazines = self.cache();
+        let mut parent = None;
+        self.release_expired(&mut magazines, &mut parent);
+        let list = &mut magazines.0[class];
+        if list.head.is_none() {
+            let layout = Self::class_layout(class);
+            let parent = parent.get_or_insert_with(|| self.lock_parent());
+            for _ in 0..BATCH {
+                match parent.allocate(layout) {
+                    Ok(memory) => unsafe { Self::push(list, memory.as_non_null_ptr()) },
+                    Err(_) => break,
+                }
+            }
+        }
+        let ptr = unsafe { list.pop() }.ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, MIN_SIZE << class))
+    }
+
+    unsafe fn dealloc_cached(&self, ptr: NonNull<u8>, class: usize) {
+        let mut magazines = self.cache();
+        let mut parent = None;
+        self.release_expired(&mut magazines, &mut parent);
+        let list = &mut magazines.0[class];
+        Self::push(list, ptr);
+        if list.len > 2 * BATCH {
+            self.release(list, class, BATCH, &mut parent);
+        }
+    }
+}
+
+impl<A: Allocator, T: Timestamp> Drop for TlsCache<A, T> {
+    fn drop(&mut self) {
+        self.flush_all()
+    }
+}
+
+impl<A: Allocator, T: Timestamp> fmt::Debug for TlsCache<A, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsCache")
+            .field("caches", &self.caches.len())
+            .finish()
+    }
+}
+
+unsafe impl<A: Allocator, T: Timestamp> Allocator for TlsCache<A, T> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match Self::class_of(layout) {
+            Some(class) => self.alloc_cached(class),
+            None => self.lock_parent().allocate(layout),
+        }
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match Self::class_of(layout) {
+            Some(class) => {
+                let memory = self.alloc_cached(class)?;
+                unsafe { memory.as_mut_ptr().write_bytes(0, memory.len()) };
+                Ok(memory)
+            }
+            None => self.lock_parent().allocate_zeroed(layout),
+        }
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        match Self::class_of(layout) {
+            Some(class) => self.dealloc_cached(ptr, class),
+            None => self.lock_parent().deallocate(ptr, layout),
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        match (Self::class_of(old_layout), Self::class_of(new_layout)) {
+            (Some(old), Some(new)) if old == new => {
+                Ok(NonNull::slice_from_raw_parts(ptr, MIN_SIZE << new))
+            }
+            (None, None) => self.lock_parent().grow(ptr, old_layout, new_layout),
+            _ => grow_fallback(
+                self,
+                self,
+                ptr,
+                old_layout,
+                new_layout,
+                AllocInit::Uninitialized,
+            ),
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        match (Self::class_of(old_layout), Self::class_of(new_layout)) {
+            (Some(old), Some(new)) if old == new => {
+                let len = MIN_SIZE << new;
+                ptr.as_ptr()
+                    .add(old_layout.size())
+                    .write_bytes(0, len - old_layout.size());
+                Ok(NonNull::slice_from_raw_parts(ptr, len))
+            }
+            (None, None) => self.lock_parent().grow_zeroed(ptr, old_layout, new_layout),
+            _ => grow_fallback(self, self, ptr, old_layout, new_layout, AllocInit::Zeroed),
+        }
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        match (Self::class_of(old_layout), Self::class_of(new_layout)) {
+            (Some(old), Some(new)) if old == new => {
+                Ok(NonNull::slice_from_raw_parts(ptr, MIN_SIZE << new))
+            }
+            (None, None) => self.lock_parent().shrink(ptr, old_layout, new_layout),
+            _ => shrink_fallback(self, self, ptr, old_layout, new_layout),
+        }
+    }
+}
+
+impl<A: Allocator, T: Timestamp> AllocateMany for TlsCache<A, T> {
+    #[track_caller]
+    unsafe fn deallocate_many<I>(&self, blocks: I)
+    where
+        I: IntoIterator<Item = (NonNull<u8>, Layout)>,
+    {
+        let mut magazines = self.cache();
+        let mut parent = None;
+        for (ptr, layout) in blocks {
+            crate::check_deallocate_precondition(ptr, layout);
+            match Self::class_of(layout) {
+                Some(class) => {
+                    let list = &mut magazines.0[class];
+                    Self::push(list, ptr);
+                    if list.len > 2 * BATCH {
+                        self.release(list, class, BATCH, &mut parent);
+                    }
+                }
+                None => parent
+                    .get_or_insert_with(|| self.lock_parent())
+                    .deallocate(ptr, layout),
+            }
+        }
+    }
+}
+
+impl<A: Allocator + Owns, T: Timestamp> Owns for TlsCache<A, T> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.lock_parent().owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.lock_parent().owns_ptr(ptr)
+    }
+}
+
+impl<A: Allocator + Describe, T: Timestamp> Describe for TlsCache<A, T> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TlsCache({} caches)", self.caches.len())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&*self.lock_parent())
+    }
+}
+
+impl<A: Allocator + Purge, T: Timestamp> Purge for TlsCache<A, T> {
+    fn purge(&self, level: PurgeLevel) {
+        match level {
+            PurgeLevel::Trim => self.flush(),
+            PurgeLevel::Full => self.flush_all(),
+        }
+        self.lock_parent().purge(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TlsCache, BATCH};
+    use crate::{
+        helper::tracker,
+        stats::AtomicCounter,
+        Allocator,
+        Proxy,
+        Purge,
+        PurgeLevel,
+        Timestamp,
+    };
+    use alloc::alloc::Global;
+    use core::{
+        alloc::Layout,
+        num::NonZeroUsize,
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+    use std::{sync::Arc, thread, vec::Vec};
+
+    #[test]
+    fn reuse() {
+        let alloc = TlsCache::new(tracker(Global));
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 24]>())
+            .expect("Could not allocate 24 bytes");
+        assert_eq!(memory.len(), 32);
+        unsafe {
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 24]>());
+            let reused = alloc
+                .allocate_zeroed(Layout::new::<[u8; 32]>())
+                .expect("Could not allocate 32 bytes");
+            assert_eq!(reused, memory);
+            assert_eq!(*reused.as_mut_ptr(), 0);
+
+            let grown = alloc
+                .grow(
+                    reused.as_non_null_ptr(),
+                    Layout::new::<[u8; 32]>(),
+                    Layout::new::<[u8; 4096]>(),
+                )
+                .expect("Could not grow to 4096 bytes");
+            assert_eq!(grown.len(), 4096);
+            alloc.deallocate(grown.as_non_null_ptr(), Layout::new::<[u8; 4096]>());
+        }
+        alloc.flush();
+    }
+
+    #[test]
+    fn batches() {
+        let counter = Arc::new(AtomicCounter::default());
+        let alloc = Arc::new(TlsCache::with_caches(
+            Proxy {
+                alloc: Global,
+                callbacks: Arc::clone(&counter),
+            },
+            4,
+        ));
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let alloc = Arc::clone(&alloc);
+                thread::spawn(move || {
+                    let layout = Layout::new::<[u8; 64]>();
+                    let blocks: Vec<_> = (0..BATCH)
+                        .map(|_| alloc.allocate(layout).expect("Could not allocate 64 bytes"))
+                        .collect();
+                    for memory in blocks {
+                        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().expect("Thread panicked");
+        }
+
+        assert!(counter.num_allocs() <= 4 * BATCH as u64);
+        drop(alloc);
+        assert_eq!(counter.num_allocs(), counter.num_deallocs());
+    }
+
+    #[test]
+    fn purge() {
+        let counter = Arc::new(AtomicCounter::default());
+        let alloc = Arc::new(TlsCache::new(Proxy {
+            alloc: Global,
+            callbacks: Arc::clone(&counter),
+        }));
+        let layout = Layout::new::<[u8; 64]>();
+
+        let memory = alloc.allocate(layout).expect("Could not allocate 64 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        assert_ne!(counter.num_allocs(), counter.num_deallocs());
+        alloc.purge(PurgeLevel::Trim);
+        assert_eq!(counter.num_allocs(), counter.num_deallocs());
+
+        let other = Arc::clone(&alloc);
+        thread::spawn(move || {
+            let memory = other.allocate(layout).expect("Could not allocate 64 bytes");
+            unsafe { other.deallocate(memory.as_non_null_ptr(), layout) };
+        })
+        .join()
+        .expect("Thread panicked");
+        assert_ne!(counter.num_allocs(), counter.num_deallocs());
+        alloc.purge(PurgeLevel::Full);
+        assert_eq!(counter.num_allocs(), counter.num_deallocs());
+    }
+
+    static TICKS: AtomicU64 = AtomicU64::new(0);
+
+    struct TestClock;
+
+    impl Timestamp for TestClock {
+        fn now() -> u64 {
+            TICKS.load(Ordering::Relaxed)
+        }
+
+        fn to_duration(ticks: u64) -> Duration {
+            Duration::from_secs(ticks)
+        }
+    }
+
+    #[test]
+    fn expiry() {
+        let counter = Arc::new(AtomicCounter::default());
+        let alloc = TlsCache::<_, TestClock>::with_max_age(
+            Proxy {
+                alloc: Global,
+                callbacks: Arc::clone(&counter),
+            },
+            NonZeroUsize::MIN,
+            10,
+        );
+        let small = Layout::new::<[u8; 16]>();
+        let large = Layout::new::<[u8; 64]>();
+
+        let memory = alloc.allocate(small).expect("Could not allocate 16 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), small) };
+        assert_eq!(alloc.purge_expired(), 0);
+
+        TICKS.fetch_add(10, Ordering::Relaxed);
+        assert_eq!(alloc.purge_expired(), BATCH);
+        assert_eq!(counter.num_allocs(), counter.num_deallocs());
+
+        // Expired caches are also returned by the next request
+        let memory = alloc.allocate(small).expect("Could not allocate 16 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), small) };
+        TICKS.fetch_add(10, Ordering::Relaxed);
+        let memory = alloc.allocate(large).expect("Could not allocate 64 bytes");
+        assert_eq!(counter.num_deallocs(), 2 * BATCH as u64);
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), large) };
+    }
+}
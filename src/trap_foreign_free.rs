@@ -0,0 +1,364 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// Called by [`TrapForeignFree`] when a memory block not owned by the underlying allocator is
+/// passed to it.
+///
+/// This is implemented for closures taking the pointer and the layout, e.g. to log the foreign
+/// pointer, for [`PanicOnForeign`], and for [`CountForeign`].
+pub trait ForeignHandler {
+    /// Handles the foreign memory block at `ptr`, which was passed with `layout`.
+    fn on_foreign(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+impl<F: Fn(NonNull<u8>, Layout)> ForeignHandler for F {
+    #[inline]
+    fn on_foreign(&self, ptr: NonNull<u8>, layout: Layout) {
+        self(ptr, layout)
+    }
+}
+
+/// Panics on foreign memory blocks.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PanicOnForeign;
+
+impl ForeignHandler for PanicOnForeign {
+    #[track_caller]
+    fn on_foreign(&self, ptr: NonNull<u8>, layout: Layout) {
+        panic!(
+            "{:p} with {:?} is not owned by the allocator",
+            ptr.as_ptr(),
+            layout
+        )
+    }
+}
+
+/// Counts foreign memory blocks.
+#[derive(Debug, Default)]
+pub struct CountForeign(Cell<usize>);
+
+impl CountForeign {
+    /// Returns the number of foreign memory blocks passed so far.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.0.get()
+    }
+}
+
+impl ForeignHandler for CountForeign {
+    #[inline]
+    fn on_foreign(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        self.0.set(self.0.get() + 1)
+    }
+}
+
+/// Checks, that memory blocks passed to `deallocate`, `grow`, and `shrink` are owned by the
+/// underlying allocator.
+///
+/// Passing a memory block from a different allocator usually corrupts allocators like regions or
+/// free lists silently. When pointers from several allocators are mixed, `TrapForeignFree` calls
+/// its [`ForeignHandler`] for every foreign memory block instead of forwarding it. A foreign
+/// deallocation is dropped afterwards and a foreign reallocation returns `Err`.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{region::Region, CountForeign, TrapForeignFree};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+/// use std::alloc::System;
+///
+/// let mut data = [MaybeUninit::uninit(); 64];
+/// let alloc = TrapForeignFree {
+///     alloc: Region::new(&mut data),
+///     handler: CountForeign::default(),
+/// };
+///
+/// let layout = Layout::new::<u32>();
+/// let foreign = System.allocate(layout)?;
+/// unsafe { alloc.deallocate(foreign.as_non_null_ptr(), layout) };
+/// assert_eq!(alloc.handler.count(), 1);
+/// # unsafe { System.deallocate(foreign.as_non_null_ptr(), layout) };
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TrapForeignFree<A, H = PanicOnForeign> {
+    pub alloc: A,
+    pub handler: H,
+}
+
+impl<A: Owns, H: ForeignHandler> TrapForeignFree<A, H> {
+    #[track_caller]
+    fn check(&self, ptr: NonNull<u8>, layout: Layout) -> Result<(), AllocError> {
+        if self
+            .alloc
+            .owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        {
+            Ok(())
+        } else {
+            self.handler.on_foreign(ptr, layout);
+            Err(AllocError)
+        }
+    }
+}
+
+unsafe impl<A: Allocator + Owns, H: ForeignHandler> Allocator for TrapForeignFree<A, H> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_zeroed(layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if self.check(ptr, layout).is_ok() {
+            self.alloc.deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.check(ptr, old_layout)?;
+        self.alloc.grow(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.check(ptr, old_layout)?;
+        self.alloc.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.check(ptr, old_layout)?;
+        self.alloc.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+unsafe impl<A: AllocateAll, H> AllocateAll for TrapForeignFree<A, H> {
+    #[inline]
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all()
+    }
+
+    #[inline]
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc.allocate_all_zeroed()
+    }
+
+    #[inline]
+    fn deallocate_all(&self) {
+        self.alloc.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.alloc.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.alloc.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.alloc.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace + Owns, H: ForeignHandler> ReallocateInPlace
+    for TrapForeignFree<A, H>
+{
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.check(ptr, old_layout)?;
+        self.alloc.grow_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.check(ptr, old_layout)?;
+        self.alloc.grow_in_place_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.check(ptr, old_layout)?;
+        self.alloc.shrink_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl<A: ReportUsage, H> ReportUsage for TrapForeignFree<A, H> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A: Owns, H> Owns for TrapForeignFree<A, H> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.alloc.owns(memory)
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.alloc.owns_ptr(ptr)
+    }
+}
+
+impl<A: BlockSize, H> BlockSize for TrapForeignFree<A, H> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.alloc.block_size()
+    }
+}
+
+impl<A: Describe, H> Describe for TrapForeignFree<A, H> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TrapForeignFree")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge, H> Purge for TrapForeignFree<A, H> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CountForeign, TrapForeignFree};
+    use crate::{helper::tracker, region::Region, Allocator};
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, cell::Cell, mem::MaybeUninit, ptr::NonNull};
+
+    #[test]
+    fn handlers() {
+        let mut data = [MaybeUninit::uninit(); 64];
+        let alloc = TrapForeignFree {
+            alloc: Region::new(&mut data),
+            handler: CountForeign::default(),
+        };
+        let layout = Layout::new::<[u8; 8]>();
+        let grown = Layout::new::<[u8; 16]>();
+        let foreign = tracker(Global);
+        let memory = foreign
+            .allocate(layout)
+            .expect("Could not allocate 8 bytes");
+        unsafe {
+            alloc.deallocate(memory.as_non_null_ptr(), layout);
+            assert!(alloc.grow(memory.as_non_null_ptr(), layout, grown).is_err());
+            assert!(alloc
+                .shrink(memory.as_non_null_ptr(), grown, layout)
+                .is_err());
+        }
+        assert_eq!(alloc.handler.count(), 3);
+
+        let owned = alloc.allocate(layout).expect("Could not allocate 8 bytes");
+        unsafe { alloc.deallocate(owned.as_non_null_ptr(), layout) };
+        assert_eq!(alloc.handler.count(), 3);
+
+        let logged = Cell::new(None);
+        let mut data = [MaybeUninit::uninit(); 64];
+        let alloc = TrapForeignFree {
+            alloc: Region::new(&mut data),
+            handler: |ptr: NonNull<u8>, layout| logged.set(Some((ptr, layout))),
+        };
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+        assert_eq!(logged.get(), Some((memory.as_non_null_ptr(), layout)));
+
+        unsafe { foreign.deallocate(memory.as_non_null_ptr(), layout) };
+    }
+
+    #[test]
+    #[should_panic(expected = "is not owned by the allocator")]
+    fn panic() {
+        let mut data = [MaybeUninit::uninit(); 64];
+        let alloc = TrapForeignFree {
+            alloc: Region::new(&mut data),
+            handler: super::PanicOnForeign,
+        };
+        let layout = Layout::new::<u32>();
+        let mut foreign = [MaybeUninit::uninit(); 64];
+        let memory = Region::new(&mut foreign)
+            .allocate(layout)
+            .expect("Could not allocate 4 bytes");
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+    }
+}
@@ -0,0 +1,137 @@
+use core::ops::{Add, AddAssign};
+
+/// A breakdown of the memory managed by an allocator.
+///
+/// `used + free + overhead` always adds up to `capacity`. Usages of several allocators are
+/// aggregated with `+`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Usage {
+    /// The total number of bytes managed by the allocator.
+    pub capacity: usize,
+    /// The number of bytes handed out to callers.
+    pub used: usize,
+    /// The number of bytes left for allocating.
+    pub free: usize,
+    /// The number of bytes neither handed out nor available, e.g. lost to alignment padding.
+    pub overhead: usize,
+}
+
+impl Usage {
+    /// Derives the number of used bytes from the other values.
+    ///
+    /// The used bytes saturate at zero, if `free` and `overhead` exceed `capacity`.
+    pub const fn new(capacity: usize, free: usize, overhead: usize) -> Self {
+        Self {
+            capacity,
+            used: capacity.saturating_sub(free).saturating_sub(overhead),
+            free,
+            overhead,
+        }
+    }
+}
+
+impl Add for Usage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            capacity: self.capacity + rhs.capacity,
+            used: self.used + rhs.used,
+            free: self.free + rhs.free,
+            overhead: self.overhead + rhs.overhead,
+        }
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+/// Reports the memory usage of an allocator composition.
+///
+/// Allocators backed by a fixed amount of memory report their own [`Usage`]. Wrappers around a
+/// single allocator forward to it, and combinators dispatching between several allocators, like
+/// [`Fallback`] or [`Segregate`], sum up the usages of their children. Calling [`usage`] on the
+/// outermost allocator therefore yields the usage of the whole composition.
+///
+/// [`Fallback`]: crate::Fallback
+/// [`Segregate`]: crate::Segregate
+/// [`usage`]: Self::usage
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{region::Region, Fallback, ReportUsage};
+/// use core::{
+///     alloc::{Allocator, Layout},
+///     mem::MaybeUninit,
+/// };
+///
+/// let mut primary = [MaybeUninit::uninit(); 32];
+/// let mut secondary = [MaybeUninit::uninit(); 64];
+/// let alloc = Fallback {
+///     primary: Region::new(&mut primary),
+///     secondary: Region::new(&mut secondary),
+/// };
+///
+/// alloc.allocate(Layout::new::<[u8; 48]>())?;
+/// let usage = alloc.usage();
+/// assert_eq!(usage.capacity, 96);
+/// assert_eq!(usage.used, 48);
+/// assert_eq!(usage.free, 48);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+pub trait ReportUsage {
+    /// Returns the current memory usage of this allocator and the allocators it is composed of.
+    fn usage(&self) -> Usage;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReportUsage, Usage};
+    use crate::{
+        helper::tracker,
+        region::{Region, Up},
+        Allocator,
+        Chunk,
+        Fallback,
+        Null,
+        Segregate,
+    };
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn aggregate() {
+        let mut small = [MaybeUninit::uninit(); 64];
+        let mut large = [MaybeUninit::uninit(); 128];
+        let alloc = tracker(Fallback {
+            primary: Segregate::<_, _, 16> {
+                small: Chunk::<_, 8>(Region::<Up>::with_direction(&mut small)),
+                large: Region::<Up>::with_direction(&mut large),
+            },
+            secondary: Null,
+        });
+        assert_eq!(alloc.usage(), Usage::new(192, 192, 0));
+
+        alloc
+            .allocate(Layout::new::<[u8; 5]>())
+            .expect("Could not allocate 5 bytes");
+        alloc
+            .allocate(Layout::new::<[u8; 33]>())
+            .expect("Could not allocate 33 bytes");
+        alloc
+            .allocate(Layout::new::<u64>())
+            .expect("Could not allocate 8 bytes");
+
+        let usage = alloc.usage();
+        assert_eq!(usage.capacity, 192);
+        assert_eq!(usage.used, 8 + 33 + 8);
+        assert!(usage.overhead < 8);
+        assert_eq!(usage.used + usage.free + usage.overhead, usage.capacity);
+        assert_eq!(usage, alloc.alloc.primary.usage() + Usage::default());
+    }
+}
@@ -0,0 +1,460 @@
+use crate::{
+    allocate_many::{bump_many, ManyBlocks},
+    describe::describe_usage,
+    os,
+    AllocateAll,
+    AllocateMany,
+    Allocator,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::Cell,
+    fmt,
+    ptr::{self, NonNull},
+};
+
+/// A region allocator over reserved address space, which commits pages on demand.
+///
+/// On creation, only address space is reserved from the operating system, no memory is
+/// committed. Pages are committed lazily, when the current position advances into them, and all
+/// pages are returned to the operating system on [`deallocate_all`]. This allows regions with
+/// gigabyte-scale capacity, which cost almost no resident memory while idle.
+///
+/// Like [`Region`] bumping [`Up`], the last memory block can be resized in place, and
+/// deallocating the last memory block frees its memory for the next allocation.
+///
+/// [`deallocate_all`]: AllocateAll::deallocate_all
+/// [`Region`]: crate::region::Region
+/// [`Up`]: crate::region::Up
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::{AllocateAll, VirtualReserve};
+/// use core::alloc::{Allocator, Layout};
+///
+/// let alloc = VirtualReserve::new(1 << 30)?;
+/// assert_eq!(alloc.committed(), 0);
+///
+/// alloc.allocate(Layout::new::<[u8; 16]>())?;
+/// assert!(alloc.committed() >= 16);
+/// assert!(alloc.committed() < alloc.capacity());
+///
+/// alloc.deallocate_all();
+/// assert_eq!(alloc.committed(), 0);
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub struct VirtualReserve {
+    memory: NonNull<[u8]>,
+    current: Cell<usize>,
+    committed: Cell<usize>,
+}
+
+impl VirtualReserve {
+    /// Reserves address space for at least `capacity` bytes.
+    ///
+    /// The capacity is rounded up to a multiple of the page size.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComposeError::Exhausted`] if the operating system cannot reserve the address
+    /// space, and [`ComposeError::LayoutUnsupported`] if `capacity` is too large.
+    #[track_caller]
+    pub fn new(capacity: usize) -> Result<Self, ComposeError> {
+        let len = match os::round_to_pages(capacity) {
+            Some(len) if len <= isize::MAX as usize => len,
+            _ => return Err(ComposeError::layout_unsupported("VirtualReserve")),
+        };
+        let ptr = if len == 0 {
+            NonNull::dangling()
+        } else if let Some(ptr) = unsafe { os::reserve(len) } {
+            ptr
+        } else {
+            return Err(ComposeError::exhausted("VirtualReserve"));
+        };
+        Ok(Self {
+            memory: NonNull::slice_from_raw_parts(ptr, len),
+            current: Cell::new(0),
+            committed: Cell::new(0),
+        })
+    }
+
+    /// Returns the number of bytes currently committed.
+    #[inline]
+    pub fn committed(&self) -> usize {
+        self.committed.get()
+    }
+
+    fn base(&self) -> usize {
+        self.memory.as_mut_ptr() as usize
+    }
+
+    fn offset_of(&self, ptr: NonNull<u8>) -> usize {
+        ptr.as_ptr() as usize - self.base()
+    }
+
+    fn is_last(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.offset_of(ptr) + size == self.current.get()
+    }
+
+    /// Ensures, that the first `len` bytes are committed.
+    fn commit(&self, len: usize) -> Result<(), AllocError> {
+        let committed = self.committed.get();
+        if len <= committed {
+            return Ok(());
+        }
+        // `memory.len()` is a multiple of the page size, so this cannot exceed the reservation
+        let new_committed = os::round_to_pages(len).ok_or(AllocError)?;
+        unsafe {
+            let ptr = NonNull::new_unchecked(self.memory.as_mut_ptr().add(committed));
+            if !os::commit(ptr, new_committed - committed) {
+                return Err(AllocError);
+            }
+        }
+        self.committed.set(new_committed);
+        Ok(())
+    }
+
+    fn alloc_impl(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let base = self.base();
+        let current = base + self.current.get();
+        let start =
+            current.checked_add(layout.align() - 1).ok_or(AllocError)? & !(layout.align() - 1);
+        let end = start.checked_add(layout.size()).ok_or(AllocError)?;
+        if end - base > self.memory.len() {
+            return Err(AllocError);
+        }
+
+        self.commit(end - base)?;
+        self.current.set(end - base);
+        let ptr = unsafe { self.memory.as_non_null_ptr().add(start - base) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn resize_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        if !(ptr.as_ptr() as usize).is_multiple_of(new_layout.align()) {
+            return Err(AllocError);
+        }
+        if !self.is_last(ptr, old_layout.size()) {
+            return if new_layout.size() <= old_layout.size() {
+                Ok(old_layout.size())
+            } else {
+                Err(AllocError)
+            };
+        }
+
+        let end = self.offset_of(ptr) + new_layout.size();
+        if end > self.memory.len() {
+            return Err(AllocError);
+        }
+        self.commit(end)?;
+        self.current.set(end);
+        Ok(new_layout.size())
+    }
+
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let len = if zeroed {
+            self.grow_in_place_zeroed(ptr, old_layout, new_layout)
+        } else {
+            self.grow_in_place(ptr, old_layout, new_layout)
+        };
+        if let Ok(len) = len {
+            return Ok(NonNull::slice_from_raw_parts(ptr, len));
+        }
+
+        let new_ptr = if zeroed {
+            self.allocate_zeroed(new_layout)?
+        } else {
+            self.allocate(new_layout)?
+        };
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), old_layout.size());
+        Ok(new_ptr)
+    }
+}
+
+impl Drop for VirtualReserve {
+    fn drop(&mut self) {
+        if self.capacity() != 0 {
+            unsafe { os::release(self.memory.as_non_null_ptr(), self.memory.len()) }
+        }
+    }
+}
+
+impl fmt::Debug for VirtualReserve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualReserve")
+            .field("memory", &self.memory)
+            .field("len", &self.memory.len())
+            .field("current", &self.current.get())
+            .field("committed", &self.committed.get())
+            .finish()
+    }
+}
+
+unsafe impl Allocator for VirtualReserve {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc_impl(layout)
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if self.is_last(ptr, layout.size()) {
+            self.current.set(self.offset_of(ptr));
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, false)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, true)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if let Ok(len) = self.shrink_in_place(ptr, old_layout, new_layout) {
+            return Ok(NonNull::slice_from_raw_parts(ptr, len));
+        }
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), new_layout.size());
+        Ok(new_ptr)
+    }
+}
+
+impl AllocateMany for VirtualReserve {
+    fn allocate_many(&self, layout: Layout, n: usize) -> ManyBlocks<'_, Self> {
+        unsafe { bump_many(self, layout, n) }
+    }
+}
+
+unsafe impl AllocateAll for VirtualReserve {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        let current = self.current.get();
+        self.commit(self.memory.len())?;
+        self.current.set(self.memory.len());
+        let ptr = unsafe { NonNull::new_unchecked(self.memory.as_mut_ptr().add(current)) };
+        Ok(NonNull::slice_from_raw_parts(
+            ptr,
+            self.memory.len() - current,
+        ))
+    }
+
+    fn deallocate_all(&self) {
+        let committed = self.committed.get();
+        if committed != 0 {
+            unsafe { os::decommit(self.memory.as_non_null_ptr(), committed) }
+        }
+        self.current.set(0);
+        self.committed.set(0);
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.memory.len()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.memory.len() - self.current.get()
+    }
+
+    /// Commits the pages for the next `additional` bytes.
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        if additional > self.capacity_left()
+            || self.commit(self.current.get() + additional).is_err()
+        {
+            return Err(ComposeError::exhausted("VirtualReserve"));
+        }
+        Ok(())
+    }
+}
+
+unsafe impl ReallocateInPlace for VirtualReserve {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.resize_in_place(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        let len = self.resize_in_place(ptr, old_layout, new_layout)?;
+        ptr.as_ptr()
+            .add(old_layout.size())
+            .write_bytes(0, len - old_layout.size());
+        Ok(len)
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.resize_in_place(ptr, old_layout, new_layout)
+    }
+}
+
+impl ReportUsage for VirtualReserve {
+    fn usage(&self) -> Usage {
+        Usage::new(self.capacity(), self.capacity_left(), 0)
+    }
+}
+
+impl Owns for VirtualReserve {
+    #[inline]
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        let ptr = memory.as_mut_ptr() as usize;
+        ptr >= self.base() && ptr + memory.len() <= self.base() + self.current.get()
+    }
+}
+
+impl Describe for VirtualReserve {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        describe_usage(f, "VirtualReserve", self.capacity(), self.capacity_left())
+    }
+}
+
+impl Purge for VirtualReserve {}
+
+impl_global_alloc!(VirtualReserve);
+
+#[cfg(test)]
+mod tests {
+    use super::VirtualReserve;
+    use crate::{helper::tracker, os, AllocateAll, Allocator, Fallback, Owns};
+    use core::alloc::Layout;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn commit_on_demand() {
+        let alloc = tracker(VirtualReserve::new(1 << 30).expect("Could not reserve 1 GiB"));
+        let page_size = os::page_size();
+        assert_eq!(alloc.capacity(), 1 << 30);
+        assert_eq!(alloc.alloc.committed(), 0);
+
+        let memory = alloc
+            .allocate_zeroed(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert!(alloc.owns(memory));
+        assert_eq!(alloc.alloc.committed(), page_size);
+
+        unsafe {
+            let memory = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 16]>(),
+                    Layout::from_size_align(3 * page_size, 1).unwrap(),
+                )
+                .expect("Could not grow in place");
+            memory.as_mut_ptr().write_bytes(1, memory.len());
+            assert_eq!(alloc.alloc.committed(), 3 * page_size);
+
+            alloc.deallocate(
+                memory.as_non_null_ptr(),
+                Layout::from_size_align(3 * page_size, 1).unwrap(),
+            );
+        }
+        assert!(alloc.is_empty());
+
+        alloc.deallocate_all();
+        assert_eq!(alloc.alloc.committed(), 0);
+
+        let memory = alloc
+            .allocate_zeroed(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+        assert_eq!(unsafe { *memory.as_mut_ptr() }, 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn exhausted() {
+        let alloc = VirtualReserve::new(1).expect("Could not reserve a page");
+        let memory = alloc.allocate_all().expect("Could not allocate all memory");
+        assert_eq!(memory.len(), os::page_size());
+        assert!(alloc.is_full());
+        alloc.allocate(Layout::new::<u8>()).unwrap_err();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn reserve() {
+        let alloc = tracker(Fallback {
+            primary: VirtualReserve::new(1).expect("Could not reserve a page"),
+            secondary: VirtualReserve::new(1 << 20).expect("Could not reserve 1 MiB"),
+        });
+        let page_size = os::page_size();
+
+        alloc.reserve(page_size).expect("Could not reserve a page");
+        assert_eq!(alloc.alloc.primary.committed(), page_size);
+        assert_eq!(alloc.alloc.secondary.committed(), 0);
+
+        alloc
+            .reserve(page_size + 1)
+            .expect("Could not reserve more than a page");
+        assert_eq!(alloc.alloc.secondary.committed(), 2 * page_size);
+
+        alloc
+            .reserve((1 << 20) + 1)
+            .expect_err("Could reserve more than the capacity");
+    }
+}
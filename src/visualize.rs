@@ -0,0 +1,246 @@
+//! Rendering of allocator compositions.
+//!
+//! Every allocator implementing [`Describe`] can be rendered as ASCII tree with [`ascii`] or as
+//! [Graphviz] graph with [`dot`]. Both return a type implementing [`Display`], so no memory is
+//! allocated while rendering.
+//!
+//! [Graphviz]: https://graphviz.org/
+//! [`Display`]: core::fmt::Display
+//!
+//! # Examples
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{region::Region, visualize, Fallback, Null};
+//! use core::{
+//!     alloc::{Allocator, Layout},
+//!     mem::MaybeUninit,
+//! };
+//!
+//! let mut data = [MaybeUninit::uninit(); 32 * 1024];
+//! let alloc = Fallback {
+//!     primary: Region::new(&mut data),
+//!     secondary: Null,
+//! };
+//! alloc.allocate(Layout::new::<[u8; 8 * 1024]>())?;
+//!
+//! assert_eq!(
+//!     visualize::ascii(&alloc).to_string(),
+//!     "Fallback\n├─ Region(32 KiB, 25% used)\n└─ Null\n"
+//! );
+//! # Ok::<(), core::alloc::AllocError>(())
+//! ```
+
+use crate::Describe;
+use core::fmt::{self, Write};
+
+/// Renders `alloc` as ASCII tree.
+///
+/// Every allocator is written on its own line, followed by the allocators it is composed of.
+pub fn ascii(alloc: &dyn Describe) -> Ascii<'_> {
+    Ascii(alloc)
+}
+
+/// Renders `alloc` as [Graphviz] graph in the DOT language.
+///
+/// [Graphviz]: https://graphviz.org/
+pub fn dot(alloc: &dyn Describe) -> Dot<'_> {
+    Dot(alloc)
+}
+
+/// An allocator composition rendered as ASCII tree.
+///
+/// This is created by [`ascii`].
+#[derive(Copy, Clone)]
+pub struct Ascii<'a>(&'a dyn Describe);
+
+/// An allocator composition rendered as [Graphviz] graph.
+///
+/// This is created by [`dot`].
+///
+/// [Graphviz]: https://graphviz.org/
+#[derive(Copy, Clone)]
+pub struct Dot<'a>(&'a dyn Describe);
+
+struct Description<'a>(&'a dyn Describe);
+
+impl fmt::Display for Description<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.describe(f)
+    }
+}
+
+fn num_children(alloc: &dyn Describe) -> usize {
+    let mut count = 0;
+    alloc.for_each_child(&mut |_| count += 1);
+    count
+}
+
+/// Calls `f` for every child and stops at the first error.
+fn try_for_each_child(
+    alloc: &dyn Describe,
+    mut f: impl FnMut(&dyn Describe, bool) -> fmt::Result,
+) -> fmt::Result {
+    let count = num_children(alloc);
+    let mut index = 0;
+    let mut result = Ok(());
+    alloc.for_each_child(&mut |child| {
+        if result.is_ok() {
+            index += 1;
+            result = f(child, index == count);
+        }
+    });
+    result
+}
+
+/// The indentation of a line as linked list through the stack.
+struct Indent<'a> {
+    parent: Option<&'a Indent<'a>>,
+    last: bool,
+}
+
+impl Indent<'_> {
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(parent) = self.parent {
+            parent.write(f)?;
+        }
+        f.write_str(if self.last { "   " } else { "│  " })
+    }
+}
+
+fn write_ascii(
+    f: &mut fmt::Formatter<'_>,
+    alloc: &dyn Describe,
+    indent: Option<&Indent<'_>>,
+) -> fmt::Result {
+    alloc.describe(f)?;
+    f.write_char('\n')?;
+    try_for_each_child(alloc, |child, last| {
+        if let Some(indent) = indent {
+            indent.write(f)?;
+        }
+        f.write_str(if last { "└─ " } else { "├─ " })?;
+        write_ascii(
+            f,
+            child,
+            Some(&Indent {
+                parent: indent,
+                last,
+            }),
+        )
+    })
+}
+
+impl fmt::Display for Ascii<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_ascii(f, self.0, None)
+    }
+}
+
+impl fmt::Debug for Ascii<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Escapes quotes and backslashes in DOT labels.
+struct Escape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for Escape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '"' || c == '\\' {
+                self.0.write_char('\\')?;
+            }
+            self.0.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_dot(f: &mut fmt::Formatter<'_>, alloc: &dyn Describe, next_id: &mut usize) -> fmt::Result {
+    let id = *next_id;
+    *next_id += 1;
+    write!(f, "    n{} [label=\"", id)?;
+    write!(Escape(f), "{}", Description(alloc))?;
+    f.write_str("\"];\n")?;
+    try_for_each_child(alloc, |child, _| {
+        writeln!(f, "    n{} -> n{};", id, *next_id)?;
+        write_dot(f, child, next_id)
+    })
+}
+
+impl fmt::Display for Dot<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("digraph {\n    node [shape=box];\n")?;
+        write_dot(f, self.0, &mut 0)?;
+        f.write_str("}\n")
+    }
+}
+
+impl fmt::Debug for Dot<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::{ascii, dot};
+    use crate::{
+        describe::Bytes,
+        region::Region,
+        stats::Counter,
+        Allocator,
+        Chunk,
+        Fallback,
+        Null,
+        Proxy,
+        Segregate,
+    };
+    use alloc::{alloc::Global, format};
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn bytes() {
+        assert_eq!(format!("{}", Bytes(0)), "0 B");
+        assert_eq!(format!("{}", Bytes(1023)), "1023 B");
+        assert_eq!(format!("{}", Bytes(1024)), "1 KiB");
+        assert_eq!(format!("{}", Bytes(1536)), "1.5 KiB");
+        assert_eq!(format!("{}", Bytes(3 * 1024 * 1024)), "3 MiB");
+        assert_eq!(format!("{}", Bytes(usize::MAX)), "15.9 EiB");
+    }
+
+    #[test]
+    fn nested() {
+        let mut data = [MaybeUninit::uninit(); 128];
+        let alloc = Segregate::<_, _, 64> {
+            small: Fallback {
+                primary: Chunk::<_, 32>(Region::new(&mut data)),
+                secondary: Null,
+            },
+            large: Proxy {
+                alloc: Global,
+                callbacks: Counter::default(),
+            },
+        };
+        alloc
+            .allocate(Layout::new::<[u8; 16]>())
+            .expect("Could not allocate 16 bytes");
+
+        assert_eq!(
+            format!("{}", ascii(&alloc)),
+            "Segregate<64>\n├─ Fallback\n│  ├─ Chunk<32>\n│  │  └─ Region(128 B, 25% used)\n│  └─ \
+             Null\n└─ Proxy\n   └─ Global\n"
+        );
+        assert_eq!(
+            format!("{}", dot(&alloc)),
+            "digraph {\n    node [shape=box];\n    n0 [label=\"Segregate<64>\"];\n    n0 -> n1;\n    \
+             n1 [label=\"Fallback\"];\n    n1 -> n2;\n    n2 [label=\"Chunk<32>\"];\n    n2 -> \
+             n3;\n    n3 [label=\"Region(128 B, 25% used)\"];\n    n1 -> n4;\n    n4 \
+             [label=\"Null\"];\n    n0 -> n5;\n    n5 [label=\"Proxy\"];\n    n5 -> n6;\n    n6 \
+             [label=\"Global\"];\n}\n"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{
+    helper::{grow_fallback, shrink_fallback, AllocInit},
+    region::FixedRegion,
+    Allocator,
+    Describe,
+    FreeTree,
+    Owns,
+    Purge,
+    ReportUsage,
+    Usage,
+};
+use core::{
+    alloc::{AllocError, Layout},
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+/// A small global allocator for WebAssembly, composed of a [`FixedRegion`] and a [`FreeTree`].
+///
+/// Allocations are bumped from the `REGION` bytes of the region first, which only costs a few
+/// instructions and suits the long-living allocations made at startup. Once the region is
+/// exhausted, the `HEAP` bytes managed by the free tree are used, which reuses deallocated
+/// memory. Both buffers are stored inline, so a `static` allocator ends up in the zero-initialized
+/// data of the binary and no code for requesting memory from the host is linked in.
+///
+/// On `wasm32` targets without the `atomics` target feature, the allocator is `Sync`, so it can be
+/// used as `#[global_allocator]`.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use alloc_compose::WasmAlloc;
+///
+/// #[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+/// #[global_allocator]
+/// static ALLOC: WasmAlloc<4096, 65536> = unsafe { WasmAlloc::new() };
+///
+/// let alloc = unsafe { WasmAlloc::<64, 1024>::new() };
+/// let mut vec = Vec::new_in(&alloc);
+/// vec.extend(0..100_u32);
+/// assert_eq!(vec.iter().sum::<u32>(), 4950);
+/// ```
+pub struct WasmAlloc<const REGION: usize, const HEAP: usize> {
+    region: FixedRegion<REGION>,
+    memory: UnsafeCell<[MaybeUninit<u8>; HEAP]>,
+    heap: UnsafeCell<Option<FreeTree<'static>>>,
+}
+
+impl<const REGION: usize, const HEAP: usize> WasmAlloc<REGION, HEAP> {
+    /// Creates a new allocator. The free tree is set up on its first use.
+    ///
+    /// # Safety
+    ///
+    /// The allocator must not be moved after its first allocation, e.g. by storing it in a
+    /// `static`.
+    #[inline]
+    pub const unsafe fn new() -> Self {
+        Self {
+            region: FixedRegion::new(),
+            memory: UnsafeCell::new([MaybeUninit::uninit(); HEAP]),
+            heap: UnsafeCell::new(None),
+        }
+    }
+
+    fn heap(&self) -> &FreeTree<'static> {
+        unsafe {
+            let heap = self.heap.get();
+            if (*heap).is_none() {
+                *heap = Some(FreeTree::new(&mut *self.memory.get()));
+            }
+            (*heap).as_ref().unwrap_unchecked()
+        }
+    }
+
+    fn in_region(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.region
+            .owns(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        init: AllocInit,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if self.in_region(ptr, old_layout) {
+            grow_fallback(&&self.region, self, ptr, old_layout, new_layout, init)
+        } else {
+            match init {
+                AllocInit::Uninitialized => self.heap().grow(ptr, old_layout, new_layout),
+                AllocInit::Zeroed => self.heap().grow_zeroed(ptr, old_layout, new_layout),
+            }
+        }
+    }
+}
+
+impl<const REGION: usize, const HEAP: usize> fmt::Debug for WasmAlloc<REGION, HEAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmAlloc")
+            .field("region", &self.region)
+            .field("heap", unsafe { &*self.heap.get() })
+            .finish()
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+unsafe impl<const REGION: usize, const HEAP: usize> Sync for WasmAlloc<REGION, HEAP> {}
+
+unsafe impl<const REGION: usize, const HEAP: usize> Allocator for WasmAlloc<REGION, HEAP> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        (&self.region)
+            .allocate(layout)
+            .or_else(|_| self.heap().allocate(layout))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        if self.in_region(ptr, layout) {
+            (&self.region).deallocate(ptr, layout)
+        } else {
+            self.heap().deallocate(ptr, layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Uninitialized)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.grow_impl(ptr, old_layout, new_layout, AllocInit::Zeroed)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        if !self.in_region(ptr, old_layout) {
+            self.heap().shrink(ptr, old_layout, new_layout)
+        } else if (ptr.as_ptr() as usize).is_multiple_of(new_layout.align()) {
+            Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+        } else {
+            shrink_fallback(&&self.region, self, ptr, old_layout, new_layout)
+        }
+    }
+}
+
+impl<const REGION: usize, const HEAP: usize> Owns for WasmAlloc<REGION, HEAP> {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.region.owns(memory) || self.heap().owns(memory)
+    }
+}
+
+impl<const REGION: usize, const HEAP: usize> ReportUsage for WasmAlloc<REGION, HEAP> {
+    fn usage(&self) -> Usage {
+        let heap = self.heap();
+        Usage::new(
+            REGION + heap.capacity(),
+            self.region.capacity_left() + heap.capacity_left(),
+            HEAP - heap.capacity(),
+        )
+    }
+}
+
+impl<const REGION: usize, const HEAP: usize> Describe for WasmAlloc<REGION, HEAP> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WasmAlloc")
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.region);
+        visit(self.heap());
+    }
+}
+
+impl<const REGION: usize, const HEAP: usize> Purge for WasmAlloc<REGION, HEAP> {}
+
+impl_global_alloc!(impl<const REGION: usize, const HEAP: usize> WasmAlloc<REGION, HEAP>);
+
+#[cfg(test)]
+mod tests {
+    use super::WasmAlloc;
+    use crate::{helper::tracker, region::FixedRegion, Allocator, FreeTree, Owns, ReportUsage};
+    use alloc::vec::Vec;
+    use core::{alloc::Layout, mem};
+
+    #[test]
+    fn footprint() {
+        let overhead = mem::size_of::<FixedRegion<0>>() + mem::size_of::<Option<FreeTree<'_>>>();
+        assert_eq!(mem::size_of::<WasmAlloc<0, 0>>(), overhead);
+        assert!(overhead <= 8 * mem::size_of::<usize>());
+        assert_eq!(mem::size_of::<WasmAlloc<64, 1024>>(), overhead + 64 + 1024);
+    }
+
+    #[test]
+    fn region_then_heap() {
+        let alloc = unsafe { WasmAlloc::<32, 1024>::new() };
+        let tracked = tracker(&alloc);
+        let layout = Layout::new::<[u8; 16]>();
+        let first = tracked
+            .allocate(layout)
+            .expect("Could not allocate 16 bytes");
+        let blocks = (0..8)
+            .map(|_| {
+                tracked
+                    .allocate(layout)
+                    .expect("Could not allocate 16 bytes")
+            })
+            .collect::<Vec<_>>();
+        assert!(alloc.region.owns(first));
+        assert!(alloc.region.owns(blocks[0]));
+        assert!(blocks[1..].iter().all(|&block| !alloc.region.owns(block)));
+        assert!(blocks.iter().all(|&block| alloc.owns(block)));
+        assert_eq!(alloc.region.capacity_left(), 0);
+
+        let used = alloc.usage().used;
+        for block in blocks {
+            unsafe { tracked.deallocate(block.as_non_null_ptr(), layout) };
+        }
+        assert!(alloc.usage().used < used);
+        assert_eq!(alloc.heap().num_free_blocks(), 1);
+        assert_eq!(alloc.heap().capacity_left(), alloc.heap().capacity());
+
+        let mut vec = Vec::new_in(&tracked);
+        vec.extend(0..200_u32);
+        vec.truncate(3);
+        vec.shrink_to_fit();
+        assert_eq!(vec, [0, 1, 2]);
+        drop(vec);
+        unsafe { tracked.deallocate(first.as_non_null_ptr(), layout) };
+    }
+
+    #[test]
+    fn reuse() {
+        let alloc = unsafe { WasmAlloc::<16, 1024>::new() };
+        let tracked = tracker(&alloc);
+        let layout = Layout::new::<[u8; 512]>();
+        for _ in 0..16 {
+            let memory = tracked
+                .allocate(layout)
+                .expect("Could not allocate 512 bytes");
+            assert!(!alloc.region.owns(memory));
+            unsafe { tracked.deallocate(memory.as_non_null_ptr(), layout) };
+        }
+        assert_eq!(alloc.heap().largest_free_block(), alloc.heap().capacity());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::{
+    AllocateAll,
+    Allocator,
+    BlockSize,
+    ComposeError,
+    Describe,
+    Owns,
+    Purge,
+    PurgeLevel,
+    ReallocateInPlace,
+    ReportUsage,
+    Usage,
+};
+use alloc::collections::BTreeMap;
+use core::{
+    alloc::{AllocError, Layout},
+    cell::RefCell,
+    fmt,
+    ptr::NonNull,
+};
+
+/// A set of disjoint address ranges.
+///
+/// Allocators, whose memory is not a single contiguous slice, can register every block they
+/// manage, so [`Owns`] can be answered by looking up the set. A memory block is owned, if it lies
+/// completely within one registered range.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{Owns, RangeSet};
+/// use std::{mem::MaybeUninit, ptr::NonNull};
+///
+/// let mut data = [MaybeUninit::new(0_u8); 32];
+/// let memory = NonNull::from(&mut data[..8]);
+/// let memory = NonNull::slice_from_raw_parts(memory.as_non_null_ptr().cast(), memory.len());
+///
+/// let ranges = RangeSet::new();
+/// ranges.register(memory);
+/// assert!(ranges.owns(memory));
+///
+/// ranges.unregister(memory.as_non_null_ptr());
+/// assert!(!ranges.owns(memory));
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+pub struct RangeSet {
+    ranges: RefCell<BTreeMap<usize, usize>>,
+}
+
+impl RangeSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the range covered by `memory` to the set.
+    ///
+    /// A range starting at the same address is replaced. Returns `false`, if the range wraps
+    /// around the end of the address space and was not added.
+    pub fn register(&self, memory: NonNull<[u8]>) -> bool {
+        let start = memory.as_mut_ptr() as usize;
+        match start.checked_add(memory.len()) {
+            Some(end) => {
+                self.ranges.borrow_mut().insert(start, end);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the range starting at `ptr` from the set.
+    ///
+    /// Returns `false`, if no range starts at `ptr`.
+    pub fn unregister(&self, ptr: NonNull<u8>) -> bool {
+        self.ranges
+            .borrow_mut()
+            .remove(&(ptr.as_ptr() as usize))
+            .is_some()
+    }
+
+    /// Removes all ranges from the set.
+    pub fn clear(&self) {
+        self.ranges.borrow_mut().clear()
+    }
+
+    /// Returns the number of registered ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.borrow().len()
+    }
+
+    /// Returns if no range is registered.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.borrow().is_empty()
+    }
+}
+
+impl Owns for RangeSet {
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        let start = memory.as_mut_ptr() as usize;
+        self.ranges
+            .borrow()
+            .range(..=start)
+            .next_back()
+            .is_some_and(|(_, &end)| {
+                start
+                    .checked_add(memory.len())
+                    .is_some_and(|memory_end| memory_end <= end)
+            })
+    }
+
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        let start = ptr.as_ptr() as usize;
+        self.ranges
+            .borrow()
+            .range(..=start)
+            .next_back()
+            .is_some_and(|(&block, &end)| start < end || start == block)
+    }
+}
+
+/// Adds [`Owns`] to an allocator by registering every returned block in a [`RangeSet`].
+///
+/// This enables combinators requiring `Owns`, like [`Fallback`], on top of allocators which
+/// cannot tell by themselves if they have allocated a memory block, e.g. the system allocator.
+/// In contrast to a native `Owns` implementation, only blocks, which were allocated through this
+/// wrapper, are owned.
+///
+/// [`Fallback`]: crate::Fallback
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api, slice_ptr_get)]
+///
+/// use alloc_compose::{Owns, WeakOwns};
+/// use std::alloc::{Allocator, Layout, System};
+///
+/// let alloc = WeakOwns::new(System);
+/// let memory = alloc.allocate(Layout::new::<[u8; 4]>())?;
+/// assert!(alloc.owns(memory));
+///
+/// unsafe { alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 4]>()) };
+/// assert!(!alloc.owns(memory));
+/// # Ok::<(), core::alloc::AllocError>(())
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(doc, doc(cfg(feature = "alloc")))]
+pub struct WeakOwns<A> {
+    alloc: A,
+    ranges: RangeSet,
+}
+
+impl<A> WeakOwns<A> {
+    /// Wraps `alloc` with an empty set of ranges.
+    pub fn new(alloc: A) -> Self {
+        Self {
+            alloc,
+            ranges: RangeSet::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub const fn parent(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the ranges currently registered by this allocator.
+    pub const fn ranges(&self) -> &RangeSet {
+        &self.ranges
+    }
+
+    fn register(
+        &self,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = result?;
+        self.ranges.register(memory);
+        Ok(memory)
+    }
+
+    fn reregister(
+        &self,
+        ptr: NonNull<u8>,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = result?;
+        self.ranges.unregister(ptr);
+        self.ranges.register(memory);
+        Ok(memory)
+    }
+
+    fn reregister_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        result: Result<usize, AllocError>,
+    ) -> Result<usize, AllocError> {
+        let len = result?;
+        self.ranges
+            .register(NonNull::slice_from_raw_parts(ptr, len));
+        Ok(len)
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for WeakOwns<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.register(self.alloc.allocate(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.register(self.alloc.allocate_zeroed(layout))
+    }
+
+    #[track_caller]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        crate::check_deallocate_precondition(ptr, layout);
+        self.ranges.unregister(ptr);
+        self.alloc.deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.reregister(ptr, self.alloc.grow(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.reregister(ptr, self.alloc.grow_zeroed(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.reregister(ptr, self.alloc.shrink(ptr, old_layout, new_layout))
+    }
+}
+
+unsafe impl<A: AllocateAll> AllocateAll for WeakOwns<A> {
+    fn allocate_all(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.register(self.alloc.allocate_all())
+    }
+
+    fn allocate_all_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.register(self.alloc.allocate_all_zeroed())
+    }
+
+    fn deallocate_all(&self) {
+        self.ranges.clear();
+        self.alloc.deallocate_all()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.alloc.capacity()
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.alloc.capacity_left()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.alloc.is_empty()
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.alloc.is_full()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn reserve(&self, additional: usize) -> Result<(), ComposeError> {
+        self.alloc.reserve(additional)
+    }
+}
+
+unsafe impl<A: ReallocateInPlace> ReallocateInPlace for WeakOwns<A> {
+    #[track_caller]
+    unsafe fn grow_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.reregister_in_place(ptr, self.alloc.grow_in_place(ptr, old_layout, new_layout))
+    }
+
+    #[track_caller]
+    unsafe fn grow_in_place_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_grow_precondition(ptr, old_layout, new_layout);
+        self.reregister_in_place(
+            ptr,
+            self.alloc.grow_in_place_zeroed(ptr, old_layout, new_layout),
+        )
+    }
+
+    #[track_caller]
+    unsafe fn shrink_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<usize, AllocError> {
+        crate::check_shrink_precondition(ptr, old_layout, new_layout);
+        self.reregister_in_place(ptr, self.alloc.shrink_in_place(ptr, old_layout, new_layout))
+    }
+}
+
+impl<A: ReportUsage> ReportUsage for WeakOwns<A> {
+    fn usage(&self) -> Usage {
+        self.alloc.usage()
+    }
+}
+
+impl<A> Owns for WeakOwns<A> {
+    #[inline]
+    fn owns(&self, memory: NonNull<[u8]>) -> bool {
+        self.ranges.owns(memory)
+    }
+
+    #[inline]
+    fn owns_ptr(&self, ptr: NonNull<u8>) -> bool {
+        self.ranges.owns_ptr(ptr)
+    }
+}
+
+impl<A: BlockSize> BlockSize for WeakOwns<A> {
+    #[inline]
+    fn block_size(&self) -> usize {
+        self.alloc.block_size()
+    }
+}
+
+impl<A: Describe> Describe for WeakOwns<A> {
+    fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WeakOwns({} ranges)", self.ranges.len())
+    }
+
+    fn for_each_child(&self, visit: &mut dyn FnMut(&dyn Describe)) {
+        visit(&self.alloc)
+    }
+}
+
+impl<A: Purge> Purge for WeakOwns<A> {
+    fn purge(&self, level: PurgeLevel) {
+        self.alloc.purge(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RangeSet, WeakOwns};
+    use crate::{helper::tracker, Allocator, Fallback, Owns};
+    use alloc::alloc::Global;
+    use core::{
+        alloc::Layout,
+        ptr::{self, NonNull},
+    };
+
+    #[test]
+    fn range_set() {
+        let base = NonNull::<[u8; 64]>::dangling().cast::<u8>();
+        let at = |offset, len| {
+            NonNull::slice_from_raw_parts(
+                unsafe { NonNull::new_unchecked(base.as_ptr().wrapping_add(offset)) },
+                len,
+            )
+        };
+
+        let ranges = RangeSet::new();
+        ranges.register(at(0, 16));
+        ranges.register(at(32, 16));
+        assert_eq!(ranges.len(), 2);
+
+        assert!(ranges.owns(at(0, 16)));
+        assert!(ranges.owns(at(4, 8)));
+        assert!(ranges.owns(at(32, 0)));
+        assert!(!ranges.owns(at(8, 16)));
+        assert!(!ranges.owns(at(16, 8)));
+        assert!(!ranges.owns(at(40, 16)));
+
+        assert!(ranges.unregister(at(0, 16).as_non_null_ptr()));
+        assert!(!ranges.unregister(at(4, 8).as_non_null_ptr()));
+        assert!(!ranges.owns(at(4, 8)));
+
+        ranges.clear();
+        assert!(ranges.is_empty());
+
+        let top = |len| {
+            NonNull::slice_from_raw_parts(
+                unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(usize::MAX - 15)) },
+                len,
+            )
+        };
+        assert!(!ranges.register(top(32)));
+        assert!(ranges.is_empty());
+        assert!(ranges.register(top(8)));
+        assert!(ranges.owns(top(8)));
+        assert!(!ranges.owns(top(32)));
+    }
+
+    #[test]
+    fn fallback() {
+        let alloc = Fallback {
+            primary: WeakOwns::new(tracker(Global)),
+            secondary: tracker(Global),
+        };
+
+        let memory = alloc
+            .allocate(Layout::new::<[u8; 8]>())
+            .expect("Could not allocate 8 bytes");
+        assert!(alloc.primary.owns(memory));
+
+        unsafe {
+            let memory = alloc
+                .grow(
+                    memory.as_non_null_ptr(),
+                    Layout::new::<[u8; 8]>(),
+                    Layout::new::<[u8; 64]>(),
+                )
+                .expect("Could not grow to 64 bytes");
+            assert!(alloc.primary.owns(memory));
+            assert_eq!(alloc.primary.ranges().len(), 1);
+
+            alloc.deallocate(memory.as_non_null_ptr(), Layout::new::<[u8; 64]>());
+            assert!(!alloc.primary.owns(memory));
+            assert!(alloc.primary.ranges().is_empty());
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Allocation patterns for benchmarking allocator compositions.
+//!
+//! The benchmarks of this crate are built from these workloads, so custom compositions can be
+//! measured the same way. Every workload deallocates all memory blocks it allocated, and returns
+//! the number of successful requests, which allows to check, that an allocator did not run out
+//! of memory during a benchmark. The workloads don't allocate on their own.
+//!
+//! # Examples
+//!
+//! ```rust
+//! #![feature(allocator_api)]
+//!
+//! use alloc_compose::{region::Region, workload, AllocateAll, Fallback};
+//! use core::{alloc::Layout, mem::MaybeUninit};
+//! use std::alloc::System;
+//!
+//! let mut data = [MaybeUninit::uninit(); 256];
+//! let alloc = Fallback {
+//!     primary: Region::new(&mut data),
+//!     secondary: System,
+//! };
+//!
+//! // The region serves 4 of 8 requests, the others fall back to `System`
+//! assert_eq!(
+//!     workload::stack::<_, 8>(&alloc, Layout::new::<[u8; 64]>()),
+//!     8
+//! );
+//! ```
+
+use crate::Allocator;
+use core::{alloc::Layout, ptr::NonNull};
+
+unsafe fn deallocate<A: Allocator>(alloc: &A, block: Option<NonNull<u8>>, layout: Layout) {
+    if let Some(ptr) = block {
+        alloc.deallocate(ptr, layout)
+    }
+}
+
+/// Allocates `N` memory blocks of `layout` and deallocates them in reverse order.
+pub fn stack<A: Allocator, const N: usize>(alloc: &A, layout: Layout) -> usize {
+    let mut blocks = [None; N];
+    let mut successes = 0;
+    for block in &mut blocks {
+        *block = alloc.allocate(layout).ok().map(NonNull::as_non_null_ptr);
+        successes += block.is_some() as usize;
+    }
+    for &block in blocks.iter().rev() {
+        unsafe { deallocate(alloc, block, layout) };
+    }
+    successes
+}
+
+/// Allocates `N` memory blocks of `layout` and deallocates them in allocation order.
+pub fn queue<A: Allocator, const N: usize>(alloc: &A, layout: Layout) -> usize {
+    let mut blocks = [None; N];
+    let mut successes = 0;
+    for block in &mut blocks {
+        *block = alloc.allocate(layout).ok().map(NonNull::as_non_null_ptr);
+        successes += block.is_some() as usize;
+    }
+    for &block in &blocks {
+        unsafe { deallocate(alloc, block, layout) };
+    }
+    successes
+}
+
+/// Allocates one memory block per layout, cycling through `layouts`, and deallocates them in
+/// reverse order.
+///
+/// Mixing sizes exercises dispatching combinators like [`Segregate`].
+///
+/// [`Segregate`]: crate::Segregate
+pub fn mixed<A: Allocator, const N: usize>(alloc: &A, layouts: &[Layout]) -> usize {
+    if layouts.is_empty() {
+        return 0;
+    }
+    let mut blocks = [None; N];
+    let mut successes = 0;
+    for (block, &layout) in blocks.iter_mut().zip(layouts.iter().cycle()) {
+        *block = alloc.allocate(layout).ok().map(NonNull::as_non_null_ptr);
+        successes += block.is_some() as usize;
+    }
+    for (i, &block) in blocks.iter().enumerate().rev() {
+        unsafe { deallocate(alloc, block, layouts[i % layouts.len()]) };
+    }
+    successes
+}
+
+/// Allocates a memory block of `layout` and doubles its size `steps` times, like a growing
+/// vector.
+///
+/// Returns the number of successful requests, including the allocation.
+pub fn grow<A: Allocator>(alloc: &A, layout: Layout, steps: usize) -> usize {
+    let mut ptr = match alloc.allocate(layout) {
+        Ok(memory) => memory.as_non_null_ptr(),
+        Err(_) => return 0,
+    };
+    let mut layout = layout;
+    let mut successes = 1;
+    for _ in 0..steps {
+        let new_layout = match layout
+            .size()
+            .checked_mul(2)
+            .and_then(|size| Layout::from_size_align(size, layout.align()).ok())
+        {
+            Some(new_layout) => new_layout,
+            None => break,
+        };
+        match unsafe { alloc.grow(ptr, layout, new_layout) } {
+            Ok(memory) => {
+                ptr = memory.as_non_null_ptr();
+                layout = new_layout;
+                successes += 1;
+            }
+            Err(_) => break,
+        }
+    }
+    unsafe { alloc.deallocate(ptr, layout) };
+    successes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{helper::tracker, region::Region, AllocateAll, Chunk, Segregate};
+    use alloc::alloc::Global;
+    use core::{alloc::Layout, mem::MaybeUninit};
+
+    #[test]
+    fn workloads() {
+        let mut data = [MaybeUninit::uninit(); 128];
+        let region = tracker(Region::new(&mut data));
+        assert_eq!(super::stack::<_, 8>(&region, Layout::new::<[u8; 32]>()), 4);
+        region.deallocate_all();
+        assert_eq!(super::queue::<_, 2>(&region, Layout::new::<[u8; 32]>()), 2);
+
+        let alloc = tracker(Segregate::<_, _, 64> {
+            small: Chunk::<_, 64>(Global),
+            large: Global,
+        });
+        let layouts = [Layout::new::<[u8; 16]>(), Layout::new::<[u8; 256]>()];
+        assert_eq!(super::queue::<_, 16>(&alloc, layouts[1]), 16);
+        assert_eq!(super::mixed::<_, 15>(&alloc, &layouts), 15);
+        assert_eq!(super::grow(&alloc, layouts[0], 8), 9);
+    }
+}
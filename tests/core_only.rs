@@ -0,0 +1,56 @@
+//! Checks, that the documented `core`-only subset is available without the `alloc` feature.
+#![cfg(not(feature = "alloc"))]
+#![feature(allocator_api, slice_ptr_get)]
+
+use alloc_compose::{
+    region::{BorrowedRegion, IntrusiveRegion, Region, RegionState},
+    stats::Counter,
+    AllocateAll,
+    Allocator,
+    Chunk,
+    DeferredFree,
+    Fallback,
+    FreeTree,
+    Null,
+    Proxy,
+    ReportUsage,
+    Segregate,
+};
+use core::{alloc::Layout, mem::MaybeUninit};
+
+#[test]
+fn composition() {
+    let mut small = [MaybeUninit::uninit(); 256];
+    let mut large = [MaybeUninit::uninit(); 1024];
+    let mut fallback = [MaybeUninit::uninit(); 256];
+    let state = RegionState::new();
+    let alloc = Proxy::new(
+        Fallback {
+            primary: Segregate::<_, _, 64> {
+                small: Chunk::<_, 16>(BorrowedRegion::new(&mut small, &state)),
+                large: DeferredFree::<_, 4>::new(FreeTree::new(&mut large)),
+            },
+            secondary: Fallback {
+                primary: IntrusiveRegion::new(&mut fallback),
+                secondary: Null,
+            },
+        },
+        Counter::default(),
+    );
+
+    let layout = Layout::new::<[u8; 128]>();
+    let memory = alloc
+        .allocate(layout)
+        .expect("Could not allocate 128 bytes");
+    unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+    alloc
+        .allocate(Layout::new::<u32>())
+        .expect("Could not allocate 4 bytes");
+    assert_eq!(alloc.callbacks.num_allocs(), 2);
+    assert!(alloc.usage().used > 0);
+
+    let mut data = [MaybeUninit::uninit(); 64];
+    let region = Region::new(&mut data);
+    region.allocate_all().expect("Could not allocate all");
+    assert!(region.is_full());
+}
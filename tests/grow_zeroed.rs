@@ -0,0 +1,166 @@
+//! Checks, that `grow_zeroed` and `grow_in_place_zeroed` zero every byte behind
+//! `old_layout.size()`, including the slack of the old memory block, which may have been written.
+#![cfg(feature = "alloc")]
+#![feature(allocator_api, slice_ptr_get)]
+
+use alloc_compose::{
+    region::{Region, SharedRegion, Up},
+    stats::Counter,
+    Affix,
+    AllocateAll,
+    Allocator,
+    CachedOwns,
+    Chunk,
+    DeferredFree,
+    DynChunk,
+    Fallback,
+    FreeTree,
+    Generational,
+    GrowablePool,
+    HeaderBytes,
+    PatternCheck,
+    PoisonOnMove,
+    Proxy,
+    ReallocateInPlace,
+    Segregate,
+    SlabAllocator,
+    SlackFuzzer,
+    WeakOwns,
+};
+use core::{alloc::Layout, mem::MaybeUninit, num::NonZeroUsize, ptr::NonNull};
+use std::alloc::Global;
+
+const SIZES: [(usize, usize); 6] = [(1, 2), (4, 16), (16, 17), (24, 64), (60, 200), (0, 33)];
+
+/// Allocates `old_size` bytes and overwrites the whole returned memory block with a pattern.
+fn allocate_dirty(alloc: &impl Allocator, old_size: usize) -> (NonNull<[u8]>, Layout) {
+    let layout = Layout::from_size_align(old_size, 8).expect("Invalid layout");
+    let memory = alloc
+        .allocate(layout)
+        .unwrap_or_else(|_| panic!("Could not allocate {} bytes", old_size));
+    unsafe { memory.as_mut_ptr().write_bytes(0xAA, memory.len()) };
+    (memory, layout)
+}
+
+/// Asserts, that the first `old_size` bytes are preserved and the rest of `memory` is zeroed.
+fn assert_zeroed(name: &str, memory: NonNull<[u8]>, old_size: usize) {
+    let bytes = unsafe { memory.as_ref() };
+    assert!(
+        bytes[..old_size].iter().all(|&b| b == 0xAA),
+        "{}: lost contents when growing from {} bytes",
+        name,
+        old_size
+    );
+    assert!(
+        bytes[old_size..].iter().all(|&b| b == 0),
+        "{}: did not zero {} bytes behind {} bytes",
+        name,
+        bytes.len() - old_size,
+        old_size
+    );
+}
+
+fn check_grow_zeroed(name: &str, alloc: &impl Allocator) {
+    for &(old_size, new_size) in &SIZES {
+        let (memory, old_layout) = allocate_dirty(alloc, old_size);
+        let new_layout = Layout::from_size_align(new_size, 8).expect("Invalid layout");
+        unsafe {
+            match alloc.grow_zeroed(memory.as_non_null_ptr(), old_layout, new_layout) {
+                Ok(memory) => {
+                    assert_zeroed(name, memory, old_size);
+                    alloc.deallocate(memory.as_non_null_ptr(), new_layout);
+                }
+                Err(_) => alloc.deallocate(memory.as_non_null_ptr(), old_layout),
+            }
+        }
+    }
+}
+
+fn check_grow_in_place_zeroed(name: &str, alloc: &(impl Allocator + ReallocateInPlace)) {
+    check_grow_zeroed(name, alloc);
+    for &(old_size, new_size) in &SIZES {
+        let (memory, old_layout) = allocate_dirty(alloc, old_size);
+        let new_layout = Layout::from_size_align(new_size, 8).expect("Invalid layout");
+        unsafe {
+            match alloc.grow_in_place_zeroed(memory.as_non_null_ptr(), old_layout, new_layout) {
+                Ok(len) => {
+                    assert!(len >= new_size);
+                    let memory = NonNull::slice_from_raw_parts(memory.as_non_null_ptr(), len);
+                    assert_zeroed(name, memory, old_size);
+                    alloc.deallocate(memory.as_non_null_ptr(), new_layout);
+                }
+                Err(_) => alloc.deallocate(memory.as_non_null_ptr(), old_layout),
+            }
+        }
+    }
+}
+
+#[test]
+fn regions() {
+    let mut data = [MaybeUninit::uninit(); 1024];
+    check_grow_in_place_zeroed("Region<Up>", &Region::<Up>::with_direction(&mut data));
+    let mut data = [MaybeUninit::uninit(); 1024];
+    check_grow_in_place_zeroed("Region", &Region::new(&mut data));
+    let mut data = [MaybeUninit::uninit(); 1024];
+    check_grow_in_place_zeroed(
+        "SharedRegion<Up>",
+        &SharedRegion::<Up>::with_direction(&mut data),
+    );
+    let mut data = [MaybeUninit::uninit(); 4096];
+    check_grow_in_place_zeroed("FreeTree", &FreeTree::new(&mut data));
+}
+
+#[test]
+fn rounding() {
+    let mut data = [MaybeUninit::uninit(); 1024];
+    let region = Region::<Up>::with_direction(&mut data);
+    check_grow_in_place_zeroed("Chunk", &Chunk::<_, 16>(&region));
+    region.deallocate_all();
+    let size = NonZeroUsize::new(32).unwrap();
+    check_grow_in_place_zeroed("DynChunk", &DynChunk::new(&region, size));
+    region.deallocate_all();
+    check_grow_zeroed("Chunk<Global>", &Chunk::<_, 64>(Global));
+    check_grow_zeroed("GrowablePool", &GrowablePool::<_, 256, 4>::new(Global));
+    check_grow_zeroed("SlabAllocator", &SlabAllocator::new(Global));
+}
+
+#[test]
+fn headers() {
+    let mut data = [MaybeUninit::uninit(); 2048];
+    let region = Region::<Up>::with_direction(&mut data);
+    check_grow_in_place_zeroed("Affix", &Affix::<_, u32, u64>::new(&region));
+    region.deallocate_all();
+    check_grow_in_place_zeroed("HeaderBytes", &HeaderBytes::<_, 16>::new(&region));
+    region.deallocate_all();
+    check_grow_zeroed("Generational", &Generational::new(&region));
+}
+
+#[test]
+fn combinators() {
+    let mut small = [MaybeUninit::uninit(); 64];
+    let mut data = [MaybeUninit::uninit(); 2048];
+    let region = Region::<Up>::with_direction(&mut data);
+    check_grow_zeroed("Fallback", &Fallback {
+        primary: Region::<Up>::with_direction(&mut small),
+        secondary: &region,
+    });
+    region.deallocate_all();
+    check_grow_zeroed("Segregate", &Segregate::<_, _, 32> {
+        small: &region,
+        large: Global,
+    });
+    region.deallocate_all();
+    check_grow_in_place_zeroed("Proxy", &Proxy::new(&region, Counter::default()));
+    region.deallocate_all();
+    check_grow_zeroed("PoisonOnMove", &PoisonOnMove(&region));
+    region.deallocate_all();
+    check_grow_zeroed("PatternCheck", &PatternCheck::new(&region));
+    region.deallocate_all();
+    check_grow_zeroed("SlackFuzzer", &SlackFuzzer::new(&region, 42));
+    region.deallocate_all();
+    check_grow_zeroed("WeakOwns", &WeakOwns::new(&region));
+    region.deallocate_all();
+    check_grow_zeroed("DeferredFree", &DeferredFree::<_, 4>::new(&region));
+    region.deallocate_all();
+    check_grow_zeroed("CachedOwns", &CachedOwns::<_, 4>::new(&region));
+}
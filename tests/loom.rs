@@ -0,0 +1,123 @@
+//! Model checks the lock-free code paths with [`loom`](https://docs.rs/loom).
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --features std --test loom`.
+#![cfg(all(loom, feature = "std"))]
+#![feature(allocator_api, slice_ptr_get)]
+
+use alloc_compose::{
+    region::OffsetRegion,
+    stats::AtomicCounter,
+    AllocateAll,
+    Allocator,
+    Proxy,
+    TlsCache,
+};
+use core::{alloc::Layout, mem::MaybeUninit};
+use loom::{sync::Arc, thread};
+use std::alloc::System;
+
+/// Creates a region over leaked memory, so it can be moved into the spawned threads.
+fn offset_region(size: usize) -> OffsetRegion<'static> {
+    let memory = Box::leak(vec![MaybeUninit::uninit(); size].into_boxed_slice());
+    OffsetRegion::new(memory)
+}
+
+#[test]
+fn offset_region_allocate() {
+    loom::model(|| {
+        let region = Arc::new(offset_region(64));
+        let layout = Layout::new::<[u8; 16]>();
+
+        let thread = {
+            let region = Arc::clone(&region);
+            thread::spawn(move || {
+                region
+                    .allocate(layout)
+                    .expect("Could not allocate 16 bytes")
+            })
+        };
+        let first = region
+            .allocate(layout)
+            .expect("Could not allocate 16 bytes");
+        let second = thread.join().unwrap();
+
+        let (first, second) = (first.as_mut_ptr() as usize, second.as_mut_ptr() as usize);
+        assert!(first + 16 <= second || second + 16 <= first);
+        assert_eq!(region.capacity_left(), region.capacity() - 32);
+    });
+}
+
+#[test]
+fn offset_region_try_dealloc() {
+    loom::model(|| {
+        let region = Arc::new(offset_region(64));
+        let layout = Layout::new::<[u8; 16]>();
+        let memory = region
+            .allocate(layout)
+            .expect("Could not allocate 16 bytes");
+
+        let thread = {
+            let region = Arc::clone(&region);
+            thread::spawn(move || {
+                region
+                    .allocate(layout)
+                    .expect("Could not allocate 16 bytes")
+            })
+        };
+        let reclaimed = unsafe { region.try_dealloc(memory.as_non_null_ptr(), layout) };
+        let other = thread.join().unwrap();
+
+        // Either the block was reclaimed before the other thread allocated, or not at all
+        if reclaimed {
+            assert_eq!(other.as_mut_ptr(), memory.as_mut_ptr());
+            assert_eq!(region.capacity_left(), region.capacity() - 16);
+        } else {
+            assert_ne!(other.as_mut_ptr(), memory.as_mut_ptr());
+            assert_eq!(region.capacity_left(), region.capacity() - 32);
+        }
+    });
+}
+
+#[test]
+fn atomic_counter() {
+    loom::model(|| {
+        let alloc = Arc::new(Proxy::new(System, AtomicCounter::default()));
+        let layout = Layout::new::<[u8; 16]>();
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let alloc = Arc::clone(&alloc);
+                thread::spawn(move || unsafe {
+                    let memory = alloc.allocate(layout).expect("Could not allocate 16 bytes");
+                    alloc.deallocate(memory.as_non_null_ptr(), layout);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(alloc.callbacks.num_allocs(), 2);
+        assert_eq!(alloc.callbacks.num_deallocs(), 2);
+    });
+}
+
+#[test]
+fn tls_cache() {
+    loom::model(|| {
+        // A single cache is shared by both threads, so the cache lock is contended
+        let alloc = Arc::new(TlsCache::with_caches(System, 1));
+        let layout = Layout::new::<[u8; 16]>();
+
+        let thread = {
+            let alloc = Arc::clone(&alloc);
+            thread::spawn(move || unsafe {
+                let memory = alloc.allocate(layout).expect("Could not allocate 16 bytes");
+                alloc.deallocate(memory.as_non_null_ptr(), layout);
+            })
+        };
+        let memory = alloc.allocate(layout).expect("Could not allocate 16 bytes");
+        thread.join().unwrap();
+        unsafe { alloc.deallocate(memory.as_non_null_ptr(), layout) };
+    });
+}